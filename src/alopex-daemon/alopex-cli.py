@@ -0,0 +1,2254 @@
+#!/usr/bin/env python3
+"""
+ALOPEX Command Line Client
+Non-interactive access to alopexd for servers and scripts
+"""
+
+import sys
+import os
+import json
+import time
+import base64
+import getpass
+import argparse
+import importlib.util
+from pathlib import Path
+from typing import Any, Dict, List, Optional
+
+def _configure_sys_path():
+    """Find the ALOPEX network package (installed or in the source tree)"""
+    candidates = [
+        Path(os.getenv("ALOPEX_PYTHON_PATH", "")),
+        Path("/usr/lib/alopex"),
+        Path("/usr/local/lib/alopex"),
+        Path(__file__).parent.parent / "alopex-qt"
+    ]
+    for candidate in candidates:
+        if (candidate / "network").exists():
+            sys.path.insert(0, str(candidate))
+            return
+
+    print("alopex-cli: ALOPEX core modules not found (set ALOPEX_PYTHON_PATH)", file=sys.stderr)
+    sys.exit(1)
+
+_configure_sys_path()
+
+from network.ipc import IpcClient, IpcError, ErrorCode, SOCKET_PATH
+from network.fleet import FLEET_FILE, FleetHost, find_host, load_fleet
+from network.diagnostics import write_snapshot
+from network.units import UNIT_STYLES, DEFAULT_UNITS, UnitPreference
+from network.spectrum import CHANNELS, by_channel, channel_load, suggest_channel, regulatory_summary
+from network.rfkill import BLOCK_LABELS
+from network.hotspot import format_limit
+from network.port_probe import parse_target, summary
+from network.connectivity import report
+from network.troubleshoot import format_steps, summary as troubleshoot_summary
+from network.cable_test import STATUS_TEXT
+from network.transceiver import format_power
+from network.nic_queues import format_cpu_list
+from network.bandwidth import parse_time, to_csv
+from network.notifications import SMTP_TIMEOUT
+
+# Exit status per error code, so scripts can tell failures apart
+EXIT_CODES = {
+    ErrorCode.INVALID_REQUEST: 2,
+    ErrorCode.INVALID_CONFIG: 2,
+    ErrorCode.DAEMON_UNAVAILABLE: 3,
+    ErrorCode.NOT_FOUND: 4,
+    ErrorCode.PERMISSION_DENIED: 5,
+    ErrorCode.BUSY: 6,
+    ErrorCode.TIMED_OUT: 7,
+    ErrorCode.BACKEND_UNAVAILABLE: 8,
+    ErrorCode.CANCELLED: 130
+}
+
+def print_table(headers: List[str], rows: List[List[Any]]):
+    """Print rows as left-aligned columns"""
+    cells = [[str(h) for h in headers]] + [["--" if c in (None, "") else str(c) for c in row] for row in rows]
+    widths = [max(len(row[i]) for row in cells) for i in range(len(headers))]
+    for row in cells:
+        print("  ".join(cell.ljust(width) for cell, width in zip(row, widths)).rstrip())
+
+def format_duration(seconds: float) -> str:
+    """Elapsed time the way alopexd status writes it, e.g. 3h 7m"""
+    seconds = int(seconds)
+    return f"{seconds // 3600}h {seconds % 3600 // 60}m"
+
+def print_json(data: Any):
+    """Print data as stable, key-sorted JSON"""
+    print(json.dumps(data, indent=2, sort_keys=True))
+
+class AlopexCli:
+    """alopex-cli command implementations; each returns an exit status"""
+
+    def __init__(self, socket_path: Path, timeout: float, quiet: bool = False, json_output: bool = False,
+                 units: UnitPreference = UnitPreference(), host: Optional[FleetHost] = None):
+        self.client = host.client(socket_path, timeout) if host else IpcClient(socket_path, timeout=timeout)
+        self.quiet = quiet
+        self.json = json_output
+        self.units = units
+
+    def emit(self, data: Any, text: Optional[str] = None):
+        """Print a command's result: the data as JSON, or the human summary"""
+        if self.json:
+            print_json(data)
+        elif text and not self.quiet:
+            print(text)
+
+    def emit_table(self, data: Any, headers: List[str], rows: List[List[Any]]):
+        """Print a listing: the data as JSON, or rows as a table"""
+        if self.json:
+            print_json(data)
+        else:
+            print_table(headers, rows)
+
+    def _progress(self, message: str):
+        if not self.quiet and not self.json and sys.stderr.isatty():
+            print(f"\r\033[K{message}", end="", file=sys.stderr, flush=True)
+
+    def run_job(self, request_type: str, timeout: float = 120.0, **params) -> Any:
+        """Start a job, show its progress and return its result; Ctrl-C cancels it"""
+        job = self.client.request(request_type, **params)
+        waiter = self.client.with_timeout(self.client.timeout + 1)
+
+        try:
+            waited = 0.0
+            while job["state"] == "running":
+                if job["message"]:
+                    self._progress(f"{job['description']}: {job['message']}")
+                if waited >= timeout:
+                    raise IpcError(f"Gave up waiting for job {job['id']}", ErrorCode.TIMED_OUT, {"job": job["id"]})
+                job = waiter.request("WaitJob", id=job["id"], timeout=0.5)
+                waited += 0.5
+        except KeyboardInterrupt:
+            job = self.client.request("CancelJob", id=job["id"])
+        finally:
+            self._progress("")
+
+        if job["state"] != "completed":
+            raise IpcError.from_reply(job["error"])
+        return job["result"]
+
+    def _interfaces(self, group: Optional[str] = None) -> List[Dict]:
+        return self.client.request("GetInterfaces", group=group)
+
+    def _interface(self, name: str) -> Dict:
+        """An interface by kernel name or label"""
+        interfaces = self._interfaces()
+        iface = next((i for i in interfaces if i["name"] == name), None) or next(
+            (i for i in interfaces if (i.get("label") or "").lower() == name.lower()), None)
+        if not iface:
+            raise IpcError(f"No such interface: {name}", ErrorCode.NOT_FOUND, {"interface": name})
+        return iface
+
+    def _wifi_interface(self, requested: Optional[str]) -> str:
+        if requested:
+            return requested
+        wifi = [i["name"] for i in self._interfaces() if i["interface_type"] == "WiFi"]
+        if not wifi:
+            raise IpcError("No WiFi interface found", ErrorCode.NOT_FOUND)
+        return wifi[0]
+
+    def list(self, args) -> int:
+        """alopex-cli list [-g GROUP | --fleet] [-a]"""
+        if args.fleet:
+            # Every host behind the relay, each interface tagged with its host
+            interfaces = self.client.request("GetFleetInterfaces")
+        else:
+            interfaces = self._interfaces(args.group)
+        rows = []
+        hidden = 0
+        for iface in interfaces:
+            # A container network shows as its bridge; its veths come and go with the containers
+            if not args.all and iface.get("container_network") and iface["interface_type"] != "Bridge":
+                hidden += 1
+                continue
+            connection = iface.get("connection") or {}
+            rows.append(([iface["host"]] if args.fleet else []) + [
+                iface["name"], iface.get("label"), iface["interface_type"],
+                BLOCK_LABELS[iface["rfkill"]] if iface.get("rfkill") else iface["status"],
+                connection.get("status") if iface["managed"] else "unmanaged",
+                connection.get("profile_name"), iface["ip"]
+            ])
+        headers = ["DEVICE", "LABEL", "TYPE", "LINK", "STATE", "PROFILE", "ADDRESS"]
+        self.emit_table(interfaces, ["HOST"] + headers if args.fleet else headers, rows)
+        if hidden and not self.json and not self.quiet:
+            print(f"{hidden} container interfaces not shown ('alopex-cli containers' groups them, -a lists them)")
+        return 0
+
+    def rename(self, args) -> int:
+        """alopex-cli rename <interface> <new-name> [--no-persist]"""
+        result = self.client.request("RenameInterface", to=args.to, persist=not args.no_persist,
+                                     **{"from": args.interface})
+        lines = [f"{result['from']} is now {result['to']}"]
+        if result["profiles"]:
+            lines.append(f"Profiles updated: {', '.join(result['profiles'])}")
+        if result["link_file"]:
+            lines.append(f"Kept across reboots by {result['link_file']}")
+        elif result.get("persist_error"):
+            lines.append(f"Not kept across reboots: {result['persist_error']}")
+        self.emit(result, "\n".join(lines))
+        return 0 if not result.get("persist_error") else 1
+
+    def sysctls(self, args) -> int:
+        """alopex-cli sysctls <interface>"""
+        iface = self._interface(args.interface)
+        result = self.client.request("GetSysctls", interface=iface["name"])
+        rows = result["sysctls"]
+        if not rows:
+            active = f"{result['profile']} sets no sysctls" if result["profile"] else "no profile active"
+            self.emit(result, f"{iface['name']}: {active}")
+            return 0
+        self.emit_table(result, ["SETTING", "KEY", "WANTED", "IN FORCE", "BEFORE"], [
+            [row["name"], row["key"], row["wanted"], "missing" if row["effective"] is None else row["effective"],
+             row["previous"] if row["applied"] else None]
+            for row in rows
+        ])
+        return 0 if all(row["in_effect"] for row in rows if row["wanted"] is not None) else 1
+
+    def shaping_show(self, args) -> int:
+        """alopex-cli shaping show <interface>"""
+        iface = self._interface(args.interface)
+        result = self.client.request("GetShaping", interface=iface["name"])
+        wanted = result["wanted"]
+        qdisc = result["qdisc"] or {}
+        lines = [f"{iface['name']}: qdisc {' '.join(filter(None, (qdisc.get('kind'), qdisc.get('bandwidth'))))}"
+                 if qdisc else f"{iface['name']}: no qdisc reported",
+                 f"Congestion control: {result['congestion_control'] or 'host default'}"]
+        if result["profile"]:
+            asked = [" ".join(filter(None, (wanted["qdisc"], wanted["bandwidth"]))), wanted["congestion_control"]]
+            lines.append(f"{result['profile']} wants: {', '.join(filter(None, asked)) or 'nothing'}"
+                         + ("" if result["in_effect"] else " (not in force)"))
+        self.emit(result, "\n".join(lines))
+        return 0 if result["in_effect"] else 1
+
+    def shaping_test(self, args) -> int:
+        """alopex-cli shaping test <interface> [--qdisc Q] [--bandwidth B] [--seconds N] [--upload]"""
+        iface = self._interface(args.interface)
+        params = {"interface": iface["name"], "direction": "upload" if args.upload else "download"}
+        if args.qdisc:
+            params.update(qdisc=args.qdisc, bandwidth=args.bandwidth)
+        if args.seconds is not None:
+            params["seconds"] = args.seconds
+        result = self.run_job("RunShapingTest", timeout=240.0, **params)
+        if self.json:
+            print_json(result)
+            return 0
+        print_table(["RUN", "QDISC", "IDLE", "LOADED", "INCREASE", "THROUGHPUT"], [
+            [run.upper(), " ".join(filter(None, (result[run]["qdisc"] or {}).values())) or None,
+             *[f"{value:g} ms" if value is not None else None
+               for value in (result[run]["idle"]["rtt"], result[run]["loaded"]["rtt"], result[run]["increase_ms"])],
+             f"{result[run]['throughput']['mbps']:g} Mbit/s"]
+            for run in ("a", "b")
+        ])
+        print()
+        print(result["summary"])
+        return 0
+
+    def bufferbloat(self, args) -> int:
+        """alopex-cli bufferbloat <interface> [--seconds N] [--apply]"""
+        iface = self._interface(args.interface)
+        params = {"interface": iface["name"]}
+        if args.seconds is not None:
+            params["seconds"] = args.seconds
+        result = self.run_job("RunBufferbloatTest", timeout=240.0, **params)
+        suggestion = result["suggestion"]
+        applied = None
+        if args.apply and suggestion and result["profile"]:
+            applied = self.client.request("UpdateProfile", name=result["profile"], qdisc=suggestion["qdisc"],
+                                          qdisc_bandwidth=suggestion["bandwidth"])
+        failing = result["grade"] in ("C", "D", "F")
+        if self.json:
+            print_json(dict(result, applied=applied is not None))
+            return 1 if failing else 0
+
+        print_table(["DIRECTION", "IDLE", "LOADED", "INCREASE", "GRADE", "THROUGHPUT"], [
+            [direction, *[f"{value:g} ms" if value is not None else None
+                          for value in (result[direction]["idle"]["rtt"], result[direction]["loaded"]["rtt"],
+                                        result[direction]["increase_ms"])],
+             result[direction]["grade"], f"{result[direction]['throughput']['mbps']:g} Mbit/s"]
+            for direction in ("download", "upload")
+        ])
+        print()
+        print(result["summary"])
+        if applied:
+            print(f"{result['profile']}: qdisc cake {applied['qdisc_bandwidth']}")
+        elif suggestion and result["profile"]:
+            print(f"Run it again with --apply to put cake {suggestion['bandwidth']} on {result['profile']}")
+        elif args.apply and suggestion:
+            print(f"No profile is active on {iface['name']}; nothing to apply it to", file=sys.stderr)
+        return 1 if failing else 0
+
+    def mptcp_list(self, args) -> int:
+        """alopex-cli mptcp list"""
+        state = self.client.request("GetMptcp")
+        limits = state["limits"]
+        if not self.json and not self.quiet:
+            print(f"MPTCP {'enabled' if state['enabled'] else 'disabled'}: up to {limits['subflows']} subflows, "
+                  f"{limits['add_addr_accepted']} accepted addresses")
+        self.emit_table(state, ["ID", "ADDRESS", "INTERFACE", "FLAGS", "PROFILE"], [
+            [endpoint["id"], endpoint["address"], endpoint["interface"], ",".join(endpoint["flags"]) or None,
+             endpoint["profile"]]
+            for endpoint in state["endpoints"]
+        ])
+        return 0
+
+    def mptcp_limits(self, args) -> int:
+        """alopex-cli mptcp limits <subflows> <add_addr_accepted>"""
+        limits = self.client.request("SetMptcpLimits", subflows=args.subflows,
+                                     add_addr_accepted=args.add_addr_accepted)
+        self.emit(limits, f"MPTCP: up to {limits['subflows']} subflows, "
+                          f"{limits['add_addr_accepted']} accepted addresses")
+        return 0
+
+    def announce(self, args) -> int:
+        """alopex-cli announce <interface>"""
+        iface = self._interface(args.interface)
+        result = self.client.request("AnnounceAddresses", interface=iface["name"])
+        lines = [f"{iface['name']}: announced {', '.join(result['sent'])}"]
+        lines += [f"  {address}: {error}" for address, error in result["errors"].items()]
+        self.emit(result, "\n".join(lines))
+        return 0 if not result["errors"] else 1
+
+    def label_list(self, args) -> int:
+        """alopex-cli label list"""
+        labels = self.client.request("ListInterfaceLabels")
+        self.emit_table(labels, ["DEVICE", "LABEL", "GROUPS"],
+                        [[entry["interface"], entry["label"], ", ".join(entry["groups"])] for entry in labels])
+        return 0
+
+    def label_set(self, args) -> int:
+        """alopex-cli label set <interface> [label] [-g GROUP ...]"""
+        entry = self.client.request("SetInterfaceLabel", interface=args.interface, label=args.label,
+                                    groups=args.group)
+        groups = f" in {', '.join(entry['groups'])}" if entry["groups"] else ""
+        self.emit(entry, f"{entry['interface']}: {entry['label'] or 'no label'}{groups}")
+        return 0
+
+    def label_clear(self, args) -> int:
+        """alopex-cli label clear <interface>"""
+        entry = self.client.request("SetInterfaceLabel", interface=args.interface, label="", groups=[])
+        self.emit(entry, f"{entry['interface']}: label and groups removed")
+        return 0
+
+    def status(self, args) -> int:
+        """alopex-cli status"""
+        status = self.client.request("GetStatus")
+        if self.json:
+            print_json(status)
+            return 0
+
+        print(f"alopexd {'(mock) ' if status.get('mock') else ''}pid {status['pid']}, "
+              f"up {format_duration(status['uptime'])}")
+        print(f"Profiles: {status['profiles']}  Connected interfaces: {status['connected_interfaces']}")
+        print("Backends: " + ", ".join(f"{kind}={name}" for kind, name in status["backends"].items()))
+        print(f"Location: {status.get('location') or 'none'}")
+        if status.get("metered"):
+            print("Primary connection is metered")
+        if status.get("airplane_mode"):
+            print("Airplane mode is on")
+        for warning in status.get("link_warnings", []):
+            print(f"Warning: {warning}")
+        return 0
+
+    def whoami(self, args) -> int:
+        """alopex-cli whoami"""
+        hello = self.client.hello()
+        capabilities = hello["capabilities"]
+        caller = f"certificate {hello['certificate']}" if hello["uid"] is None else f"uid {hello['uid']}"
+        if capabilities is None:
+            self.emit(hello, f"{caller}: access control is off, every request is allowed")
+        elif not capabilities["observe"]:
+            self.emit(hello, f"{caller}: no role, every request is refused")
+        else:
+            denied = capabilities["denied"]
+            self.emit(hello, f"{caller}: {hello['role']}" + (f"; refused: {', '.join(denied)}" if denied else ""))
+        return 0
+
+    def hosts(self, args) -> int:
+        """alopex-cli hosts"""
+        rows = []
+        for host in load_fleet():
+            entry = {"name": host.name, "address": str(self.client.socket_path) if host.local else host.address,
+                     "local": host.local, "status": None, "error": None}
+            try:
+                entry["status"] = host.client(self.client.socket_path, self.client.timeout).request("GetStatus")
+            except IpcError as e:
+                entry["error"] = e.message
+            rows.append(entry)
+
+        def state(entry):
+            if entry["error"]:
+                return f"unreachable: {entry['error']}"
+            return "mock" if entry["status"].get("mock") else "up"
+
+        self.emit_table(rows, ["NAME", "ADDRESS", "STATE", "HOSTNAME", "UPTIME", "CONNECTED", "LOCATION"], [
+            [r["name"], r["address"], state(r), (r["status"] or {}).get("hostname"),
+             format_duration(r["status"]["uptime"]) if r["status"] else None,
+             r["status"]["connected_interfaces"] if r["status"] else None, (r["status"] or {}).get("location")]
+            for r in rows
+        ])
+        return 0
+
+    def relay(self, args) -> int:
+        """alopex-cli relay"""
+        hosts = self.client.request("GetRelayHosts")
+        now = time.time()
+
+        def state(host):
+            if host["address"] == "local":
+                return "relay"
+            if host["checked"] is None:
+                return "not polled yet"
+            return "up" if host["reachable"] else f"unreachable: {host['error']}"
+
+        self.emit_table(hosts, ["NAME", "ADDRESS", "STATE", "INTERFACES", "CONNECTED", "UPDATED"], [
+            [h["name"], h["address"], state(h), h["interfaces"], (h["status"] or {}).get("connected_interfaces"),
+             f"{format_duration(now - h['updated'])} ago" if h["updated"] and h["address"] != "local" else None]
+            for h in hosts
+        ])
+        return 0
+
+    def events(self, args) -> int:
+        """alopex-cli events [--since 24h] [--filter TERM]... [-n N]"""
+        try:
+            since = parse_time(args.since) if args.since else None
+        except ValueError as e:
+            raise IpcError(str(e), ErrorCode.INVALID_REQUEST)
+        events = self.client.request("GetEvents", since=since, filter=args.filter, limit=args.count)
+
+        def details(event):
+            return " ".join(f"{key}={value}" for key, value in event.items()
+                            if key not in ("event", "timestamp") and value not in (None, "", [], {}))
+
+        self.emit_table(events, ["TIME", "EVENT", "DETAILS"], [
+            [time.strftime("%Y-%m-%d %H:%M:%S", time.localtime(e["timestamp"])), e["event"], details(e)]
+            for e in events
+        ])
+        return 0
+
+    def undo(self, args) -> int:
+        """alopex-cli undo [--list]"""
+        if args.list:
+            changes = self.client.request("ListChanges")
+            if self.json:
+                print_json(changes)
+            elif not changes:
+                print("No changes to undo")
+            else:
+                print_table(["TIME", "CHANGE"], [
+                    [time.strftime("%H:%M:%S", time.localtime(c["applied_at"])), c["summary"]] for c in changes
+                ])
+            return 0
+        undone = self.client.request("Undo")
+        self.emit(undone, f"Undid: {undone['undone']['summary']}")
+        return 0
+
+    def connect(self, args) -> int:
+        """alopex-cli connect <interface|profile>"""
+        profiles = self.client.request("ListProfiles")
+        profile = next((p for p in profiles if p["name"] == args.target), None)
+
+        if not profile:
+            # Highest-priority profile for the interface; ListProfiles is sorted by priority
+            profile = next((p for p in profiles if p["interface"] == args.target), None)
+        if not profile:
+            iface = self._interface(args.target)
+            if iface["interface_type"] != "Ethernet":
+                raise IpcError(f"No profile for {args.target}; use 'wifi connect' or 'vpn up'",
+                               ErrorCode.NOT_FOUND, {"interface": args.target})
+            profile = self.client.request("CreateProfile", name=args.target, interface=args.target,
+                                          connection_type="ethernet", method="dhcp")
+
+        state = self.run_job("Connect", profile=profile["name"])
+        self.emit(state, f"{state['interface']}: {profile['name']} active ({state.get('ip_address') or 'no address'})")
+        return 0
+
+    def disconnect(self, args) -> int:
+        """alopex-cli disconnect <interface>"""
+        result = self.client.request("Disconnect", interface=args.interface)
+        self.emit(result, f"{args.interface}: disconnected")
+        return 0
+
+    def wifi_scan(self, args) -> int:
+        """alopex-cli wifi scan"""
+        networks = self.run_job("ScanWifi", interface=self._wifi_interface(args.interface))
+        self.emit_table(
+            networks, ["", "SSID", "SIGNAL", "QUALITY", "SECURITY", "CHANNEL"],
+            [["*" if n["connected"] else "", n["ssid"], f"{n['signal_strength']} dBm",
+              f"{n['quality_percent']}%", n["security"], n["channel"]] for n in networks]
+        )
+        return 0
+
+    def wifi_channels(self, args) -> int:
+        """alopex-cli wifi channels [--band 2.4|5]"""
+        networks = self.run_job("ScanWifi", interface=self._wifi_interface(args.interface))
+        bands = [band for band in CHANNELS if args.band is None or band.startswith(args.band)]
+        report = []
+        for band in bands:
+            load = channel_load(networks, band)
+            grouped = by_channel(networks, band)
+            report.append({
+                "band": band,
+                "suggested": suggest_channel(networks, band),
+                "channels": [{"channel": channel, "load": load[channel],
+                              "networks": [n["ssid"] for n in grouped.get(channel, [])]}
+                             for channel in CHANNELS[band]]
+            })
+        if self.json:
+            print_json(report)
+            return 0
+
+        for index, entry in enumerate(report):
+            if index:
+                print()
+            print(f"{entry['band']}: least congested channel {entry['suggested']}")
+            # Only channels something can be heard on, to keep 5 GHz readable
+            print_table(["CHANNEL", "LOAD", "NETWORKS"],
+                        [[c["channel"], c["load"], ", ".join(c["networks"])]
+                         for c in entry["channels"] if c["load"] or c["networks"]])
+        return 0
+
+    def wifi_country(self, args) -> int:
+        """alopex-cli wifi country [CODE]"""
+        if args.country:
+            self.client.request("SetRegulatoryDomain", country=args.country)
+        domain = self.client.request("GetRegulatoryDomain", interface=args.interface)
+        if self.json:
+            print_json(domain)
+            return 0
+
+        region = f" ({domain['dfs_region']})" if domain["dfs_region"] else ""
+        print(f"Regulatory domain: {domain['country'] or 'unknown'}{region}")
+        if domain["channels"]:
+            print()
+            print_table(["BAND", "USABLE", "LISTEN ONLY", "RADAR (DFS)"], [
+                [band["band"], band["usable"], band["listen_only"], band["radar"]]
+                for band in regulatory_summary(domain["channels"])
+            ])
+        return 0
+
+    def wifi_powersave(self, args) -> int:
+        """alopex-cli wifi powersave [on|off|default] [--no-save]"""
+        interface = self._wifi_interface(args.interface)
+        if args.mode:
+            enabled = {"on": True, "off": False, "default": None}[args.mode]
+            self.client.request("SetPowerSave", interface=interface, enabled=enabled, save=not args.no_save)
+        state = self.client.request("GetPowerSave", interface=interface)
+        if self.json:
+            print_json(state)
+            return 0
+
+        current = {True: "on", False: "off", None: "unknown"}[state["power_save"]]
+        print(f"{interface}: power save {current}")
+        if state["profile"]:
+            saved = {True: "on", False: "off", None: "driver default"}[state["profile_power_save"]]
+            print(f"Profile {state['profile']}: {saved}")
+        return 0
+
+    def wifi_alerts(self, args) -> int:
+        """alopex-cli wifi alerts [-n N] [--relearn [SSID]]"""
+        if args.relearn is not None:
+            result = self.client.request("ForgetWifiBaseline", ssid=args.relearn or None)
+            self.emit(result, f"Relearning {args.relearn or 'all SSIDs'}")
+            return 0
+        result = self.client.request("GetWifiAlerts", limit=args.count)
+        if not result["enabled"]:
+            self.emit(result, "WiFi watch is off; set wifi_watch.enabled in enterprise.json")
+            return 0
+        self.emit_table(
+            result, ["TIME", "INTERFACE", "ALERT", "DETAILS"],
+            [[time.strftime("%Y-%m-%d %H:%M:%S", time.localtime(a["timestamp"])), a["interface"], a["alert"],
+              a["message"]] for a in result["alerts"]]
+        )
+        return 0
+
+    def wifi_roams(self, args) -> int:
+        """alopex-cli wifi roams [-i IFACE] [-n N]"""
+        result = self.client.request("GetRoamingTimeline", interface=args.interface, limit=args.count)
+        if not result["enabled"]:
+            self.emit(result, "The roaming timeline is off; set roaming.enabled in enterprise.json")
+            return 0
+        if self.json:
+            print_json(result)
+            return 0
+
+        def signal(dbm):
+            return f"{dbm} dBm" if dbm is not None else None
+
+        def stayed(seconds):
+            # Roams are often seconds apart, below what format_duration shows
+            if seconds is None:
+                return None
+            return f"{int(seconds)}s" if seconds < 60 else format_duration(seconds)
+
+        rows = []
+        for entry in result["entries"]:
+            disconnected = entry["kind"] == "disconnected"
+            rows.append([time.strftime("%Y-%m-%d %H:%M:%S", time.localtime(entry["timestamp"])), entry["interface"],
+                         entry["kind"], entry["ssid"], entry["bssid"] if disconnected else entry["previous_bssid"],
+                         signal(entry["signal"]), stayed(entry["duration"]), None if disconnected else entry["bssid"],
+                         signal(entry.get("new_signal"))])
+        if rows:
+            print_table(["TIME", "INTERFACE", "EVENT", "SSID", "LEFT", "SIGNAL", "STAYED", "JOINED", "NEW SIGNAL"],
+                        rows)
+        elif not self.quiet:
+            print("No associations recorded")
+        for stint in result["current"]:
+            print(f"{stint['interface']}: on {stint['bssid']} ({stint['ssid'] or 'unknown SSID'}, "
+                  f"{signal(stint['signal'])}) for {format_duration(stint['duration'])}")
+        return 0
+
+    def wifi_connect(self, args) -> int:
+        """alopex-cli wifi connect <ssid> [--ask-pass] [--eap M --identity U --ca-cert ID --client-cert ID]"""
+        interface = self._wifi_interface(args.interface)
+        password = getpass.getpass(f"Password for {args.ssid}: ") if args.ask_pass else None
+        enterprise = {"eap": args.eap, "identity": args.identity, "ca_cert": args.ca_cert,
+                      "client_cert": args.client_cert}
+
+        existing = next((p for p in self.client.request("ListProfiles", interface=interface)
+                         if p["connection_type"] == "wifi" and p["ssid"] == args.ssid), None)
+        if existing and password is None and not any(enterprise.values()):
+            name = existing["name"]
+        else:
+            name = existing["name"] if existing else args.ssid
+            self.client.request("CreateProfile", name=name, interface=interface, connection_type="wifi",
+                                method="dhcp", ssid=args.ssid, password=password, replace=True, **enterprise)
+
+        state = self.run_job("Connect", profile=name)
+        self.emit(state, f"{interface}: connected to {args.ssid} ({state.get('ip_address') or 'no address'})")
+        return 0
+
+    def vpn_list(self, args) -> int:
+        """alopex-cli vpn list"""
+        tunnels = self.client.request("ListVpn")
+        self.emit_table(tunnels, ["NAME", "TYPE", "ACTIVE", "CONFIG"],
+                        [[v["name"], v["type"], "yes" if v["active"] else "no", v["path"]] for v in tunnels])
+        return 0
+
+    def vpn_up(self, args) -> int:
+        """alopex-cli vpn up <name>"""
+        if not any(v["name"] == args.name for v in self.client.request("ListVpn")):
+            raise IpcError(f"No VPN configuration named {args.name}", ErrorCode.NOT_FOUND, {"vpn": args.name})
+
+        profile = next((p for p in self.client.request("ListProfiles", interface=args.name)
+                        if p["connection_type"] == "vpn"), None)
+        if not profile:
+            profile = self.client.request("CreateProfile", name=args.name, interface=args.name,
+                                          connection_type="vpn", method="manual", auto_connect=False)
+
+        state = self.run_job("Connect", profile=profile["name"])
+        self.emit(state, f"VPN {args.name} up")
+        return 0
+
+    def vpn_down(self, args) -> int:
+        """alopex-cli vpn down <name>"""
+        result = self.client.request("Disconnect", interface=args.name)
+        self.emit(result, f"VPN {args.name} down")
+        return 0
+
+    def tunnel_list(self, args) -> int:
+        """alopex-cli tunnel list"""
+        tunnels = [p for p in self.client.request("ListProfiles")
+                   if (p.get("netdev") or {}).get("kind") in ("gre", "gretap", "vxlan")]
+        self.emit_table(tunnels, ["NAME", "KIND", "REMOTE", "LOCAL", "KEY/VNI", "ADDRESS", "AUTO"], [
+            [p["name"], p["netdev"]["kind"], p["netdev"]["remote"], p["netdev"].get("local"),
+             p["netdev"].get("vni", p["netdev"].get("key")), p["ip_address"], "yes" if p["auto_connect"] else "no"]
+            for p in tunnels
+        ])
+        return 0
+
+    def tunnel_add(self, args) -> int:
+        """alopex-cli tunnel add <name> <gre|gretap|vxlan> <remote> [options]"""
+        netdev = {"kind": args.kind, "remote": args.remote, "local": args.local, "key": args.key, "ttl": args.ttl}
+        if args.kind == "vxlan":
+            netdev.update(vni=args.vni, port=args.port, parent=args.dev)
+        elif args.vni is not None or args.port is not None or args.dev:
+            raise IpcError("--vni, --port and --dev only apply to vxlan", ErrorCode.INVALID_REQUEST,
+                           {"kind": args.kind})
+        profile = self.client.request(
+            "CreateProfile", name=args.name, interface=args.name, connection_type="ethernet",
+            method="static" if args.address else "manual", ip_address=args.address,
+            netdev={key: value for key, value in netdev.items() if value is not None},
+            auto_connect=not args.no_auto, replace=args.replace)
+        self.emit(profile, f"{profile['name']}: {args.kind} to {profile['netdev']['remote']} saved; "
+                           f"'alopex-cli connect {profile['name']}' brings it up")
+        return 0
+
+    def profile_list(self, args) -> int:
+        """alopex-cli profile list"""
+        profiles = self.client.request("ListProfiles")
+        metered = {True: "yes", False: "no", None: "auto"}
+        self.emit_table(profiles, ["NAME", "TYPE", "INTERFACE", "METHOD", "AUTO", "METERED", "UNITS"], [
+            [p["name"], p["connection_type"], p["interface"], p["method"], "yes" if p["auto_connect"] else "no",
+             metered[p["metered"]], ", ".join(p["units"])] for p in profiles
+        ])
+        return 0
+
+    def profile_units(self, args) -> int:
+        """alopex-cli profile units <name> [unit...]"""
+        profile = self.client.request("UpdateProfile", name=args.name, units=args.units)
+        summary = ", ".join(profile["units"]) or "none"
+        self.emit(profile, f"{args.name}: units {summary}")
+        return 0
+
+    def profile_sysctl(self, args) -> int:
+        """alopex-cli profile sysctl <name> [setting=value...]"""
+        settings = {}
+        for pair in args.settings:
+            setting, sep, value = pair.partition("=")
+            if not sep or not setting:
+                raise IpcError(f"Not setting=value: {pair}", ErrorCode.INVALID_CONFIG, {"sysctl": pair})
+            settings[setting.strip()] = value.strip()
+        profile = self.client.request("UpdateProfile", name=args.name, sysctls=settings)
+        summary = ", ".join(f"{key}={value}" for key, value in profile["sysctls"].items()) or "none"
+        self.emit(profile, f"{args.name}: sysctls {summary}")
+        return 0
+
+    def profile_mptcp(self, args) -> int:
+        """alopex-cli profile mptcp <name> [flag...]"""
+        profile = self.client.request("UpdateProfile", name=args.name, mptcp_flags=args.flags)
+        flags = ", ".join(profile["mptcp_flags"])
+        self.emit(profile, f"{args.name}: MPTCP endpoints {flags}" if flags else f"{args.name}: no MPTCP endpoints")
+        return 0
+
+    def profile_qdisc(self, args) -> int:
+        """alopex-cli profile qdisc <name> fq|fq_codel|cake|none [--bandwidth B]"""
+        profile = self.client.request("UpdateProfile", name=args.name, qdisc=args.qdisc,
+                                      qdisc_bandwidth=args.bandwidth)
+        qdisc = " ".join(filter(None, (profile["qdisc"], profile["qdisc_bandwidth"]))) or "kernel default"
+        self.emit(profile, f"{args.name}: qdisc {qdisc}")
+        return 0
+
+    def profile_congestion(self, args) -> int:
+        """alopex-cli profile congestion <name> <algorithm>|none"""
+        profile = self.client.request("UpdateProfile", name=args.name, congestion_control=args.algorithm)
+        self.emit(profile, f"{args.name}: congestion control {profile['congestion_control'] or 'host default'}")
+        return 0
+
+    def profile_metered(self, args) -> int:
+        """alopex-cli profile metered <name> yes|no|auto"""
+        value = {"yes": True, "no": False, "auto": None}[args.state]
+        profile = self.client.request("UpdateProfile", name=args.name, metered=value)
+        self.emit(profile, f"{args.name}: metered {args.state}")
+        return 0
+
+    def profile_critical(self, args) -> int:
+        """alopex-cli profile critical <name> yes|no"""
+        profile = self.client.request("UpdateProfile", name=args.name, boot_critical=args.state == "yes")
+        self.emit(profile, f"{args.name}: boot-critical {args.state}")
+        return 0
+
+    def profile_arp(self, args) -> int:
+        """alopex-cli profile garp|proxy-arp <name> yes|no"""
+        field = {"garp": "gratuitous_arp", "proxy-arp": "proxy_arp"}[args.profile_command]
+        profile = self.client.request("UpdateProfile", name=args.name, **{field: args.state == "yes"})
+        what = "gratuitous ARP" if field == "gratuitous_arp" else "proxy ARP"
+        self.emit(profile, f"{args.name}: {what} {args.state}")
+        return 0
+
+    def profile_metric(self, args) -> int:
+        """alopex-cli profile metric <name> <metric>|auto"""
+        if args.metric == "auto":
+            value = None
+        elif args.metric.isdigit():
+            value = int(args.metric)
+        else:
+            raise IpcError(f"Not a route metric: {args.metric}", ErrorCode.INVALID_CONFIG, {"metric": args.metric})
+        profile = self.client.request("UpdateProfile", name=args.name, route_metric=value)
+        self.emit(profile, f"{args.name}: route metric {args.metric}")
+        return 0
+
+    def profile_retries(self, args) -> int:
+        """alopex-cli profile retries"""
+        retries = self.client.request("ListAutoConnectRetries")
+        self.emit_table(retries, ["PROFILE", "INTERFACE", "FAILURES", "NEXT TRY", "LAST ERROR"], [
+            [r["profile"], r["interface"], r["failures"],
+             "gave up" if r["gave_up"] else time.strftime("%H:%M:%S", time.localtime(r["next_attempt"])),
+             r["last_error"] or "--"] for r in retries
+        ])
+        return 0
+
+    def profile_retry(self, args) -> int:
+        """alopex-cli profile retry [name]"""
+        result = self.client.request("ResetAutoConnect", profile=args.name)
+        self.emit(result, f"Retrying {', '.join(result['reset'])}" if result["reset"] else "Nothing to retry")
+        return 0
+
+    def profile_certs(self, args) -> int:
+        """alopex-cli profile certs <name> [--ca ID|none] [--client ID|none]"""
+        changes = {field: None if value == "none" else value
+                   for field, value in (("ca_cert", args.ca), ("client_cert", args.client)) if value is not None}
+        profile = self.client.request("UpdateProfile", name=args.name, **changes)
+        self.emit(profile, f"{args.name}: CA {profile['ca_cert'] or 'none'}, "
+                           f"client certificate {profile['client_cert'] or 'none'}")
+        return 0
+
+    def cert_list(self, args) -> int:
+        """alopex-cli cert list"""
+        stored = self.client.request("ListCertificates")
+
+        def expires(cert):
+            if cert["not_after"] is None:
+                return None
+            date = time.strftime("%Y-%m-%d", time.localtime(cert["not_after"]))
+            return f"{date} (expired)" if cert["days_left"] <= 0 else f"{date} ({int(cert['days_left'])} days)"
+
+        self.emit_table(stored, ["ID", "KIND", "NAME", "EXPIRES", "KEY", "SUBJECT"], [
+            [c["id"], c["kind"], c["name"], expires(c), c["key_id"], c["subject"]] for c in stored
+        ])
+        return 0
+
+    def cert_import(self, args) -> int:
+        """alopex-cli cert import <file> [--name N] [--ask-pass]"""
+        try:
+            data = args.file.read_bytes()
+        except OSError as e:
+            raise IpcError(f"Cannot read {args.file}: {e.strerror}", ErrorCode.NOT_FOUND, {"path": str(args.file)})
+        password = getpass.getpass(f"Password for the key in {args.file.name}: ") if args.ask_pass else None
+        imported = self.client.request("ImportCertificate", data=base64.b64encode(data).decode(), name=args.name,
+                                       password=password)
+        self.emit(imported, "\n".join(f"Imported {c['kind']} {c['id']}: {c['name']}" for c in imported))
+        return 0
+
+    def cert_remove(self, args) -> int:
+        """alopex-cli cert remove <id>"""
+        result = self.client.request("RemoveCertificate", id=args.id)
+        self.emit(result, f"Removed {args.id}")
+        return 0
+
+    def location_list(self, args) -> int:
+        """alopex-cli location list"""
+        result = self.client.request("ListLocations")
+        rows = [
+            ["*" if location["name"] == result["active"] else "", location["name"],
+             ", ".join(location["profiles"]), ", ".join(location["dns_servers"]), location["proxy"],
+             location["firewall_zone"], ", ".join(location["match_ssids"] + location["match_gateway_macs"])]
+            for location in result["locations"]
+        ]
+        self.emit_table(result, ["", "NAME", "PROFILES", "DNS", "PROXY", "ZONE", "MATCH"], rows)
+        if not self.json and not self.quiet:
+            print(f"Automatic switching {'on' if result['auto_switch'] else 'off'}")
+        return 0
+
+    def location_set(self, args) -> int:
+        """alopex-cli location set <name> [--profile P]... [--dns IP]... [--proxy URL] [--zone Z] [--ssid S]..."""
+        location = self.client.request(
+            "SetLocation", name=args.name, profiles=args.profile, dns_servers=args.dns, proxy=args.proxy,
+            firewall_zone=args.zone, match_ssids=args.ssid, match_gateway_macs=args.gateway_mac,
+            priority=args.priority
+        )
+        self.emit(location, f"Location {args.name} saved")
+        return 0
+
+    def location_remove(self, args) -> int:
+        """alopex-cli location remove <name>"""
+        result = self.client.request("DeleteLocation", name=args.name)
+        self.emit(result, f"Location {args.name} removed")
+        return 0
+
+    def location_switch(self, args) -> int:
+        """alopex-cli location switch <name>"""
+        result = self.run_job("SwitchLocation", name=args.name)
+        if not self.json:
+            for profile in result["failed"]:
+                print(f"failed to connect {profile}", file=sys.stderr)
+        self.emit(result, f"Location {args.name} active")
+        return 0
+
+    def location_auto(self, args) -> int:
+        """alopex-cli location auto on|off"""
+        result = self.client.request("SetLocationAutoSwitch", enabled=args.state == "on")
+        self.emit(result, f"Automatic location switching {args.state}")
+        return 0
+
+    def metrics(self, args) -> int:
+        """alopex-cli metrics <interface>"""
+        iface = self._interface(args.interface)
+        metrics = iface["metrics"]
+        if self.json or args.metrics_json:
+            print_json(dict(metrics, interface=iface["name"], status=iface["status"]))
+            return 0
+
+        print_table(["METRIC", "VALUE"], [
+            ["status", iface["status"]],
+            ["rx", f"{self.units.format_size(metrics['bytes_rx'])} ({metrics['packets_rx']} packets)"],
+            ["tx", f"{self.units.format_size(metrics['bytes_tx'])} ({metrics['packets_tx']} packets)"],
+            ["rate rx/tx", f"{self.units.format_rate(metrics['speed_down'] * 1024)}/"
+                           f"{self.units.format_rate(metrics['speed_up'] * 1024)}"],
+            ["session rx/tx", f"{self.units.format_size(metrics['total_session_rx'])}/"
+                              f"{self.units.format_size(metrics['total_session_tx'])}"
+                              if metrics["session_started"] else None],
+            ["uptime", format_duration(metrics["uptime"]) if metrics["uptime"] is not None else None],
+            ["errors rx/tx", f"{metrics['errors_rx']}/{metrics['errors_tx']}"],
+            ["dropped rx/tx", f"{metrics['dropped_rx']}/{metrics['dropped_tx']}"],
+            ["link speed", f"{metrics['link_speed']} Mbps" + (f" (NIC max {metrics['max_link_speed']} Mbps)"
+                                                             if metrics["max_link_speed"] else "")
+                           if metrics["link_speed"] else None],
+            ["duplex", metrics["duplex"]],
+            ["warning", metrics["link_warning"]],
+            ["mtu", metrics["mtu"]]
+        ])
+        return 0
+
+    def bandwidth_export(self, args) -> int:
+        """alopex-cli bandwidth export <interface> [--since 24h] [--until T] [--format csv|json] [-o FILE]"""
+        try:
+            since = parse_time(args.since) if args.since else None
+            until = parse_time(args.until) if args.until else None
+        except ValueError as e:
+            raise IpcError(str(e), ErrorCode.INVALID_REQUEST)
+        history = self.client.request("GetBandwidthHistory", interface=args.interface, since=since, until=until)
+
+        output_format = args.format
+        if not output_format:
+            output_format = "json" if self.json or (args.output and args.output.suffix == ".json") else "csv"
+        if output_format == "json":
+            text = json.dumps(history, indent=2, sort_keys=True) + "\n"
+        else:
+            text = to_csv(history["samples"])
+        if not args.output:
+            sys.stdout.write(text)
+            return 0
+        try:
+            args.output.write_text(text)
+        except OSError as e:
+            raise IpcError(f"Cannot write {args.output}: {e.strerror}", ErrorCode.INVALID_REQUEST,
+                           {"path": str(args.output)})
+        if not self.quiet:
+            print(f"Wrote {len(history['samples'])} minutes of {args.interface} to {args.output}", file=sys.stderr)
+        return 0
+
+    def sla_list(self, args) -> int:
+        """alopex-cli sla list"""
+        probes = self.client.request("GetSlaProbes")
+        if not probes and not self.json:
+            print("No SLA probes; add them under sla.probes in enterprise.json")
+            return 0
+
+        def ms(value):
+            return f"{value:.1f} ms" if value is not None else None
+
+        rows = []
+        for probe in probes:
+            hour = probe["hour"]
+            target = probe["target"] + (f":{probe['port']}" if probe["port"] else "")
+            rows.append([probe["name"], target, probe["state"],
+                         f"{hour['loss']:.1f}%" if hour["loss"] is not None else None,
+                         ms(hour["rtt"]), ms(hour["jitter"]), f"{hour['breaching_rounds']}/{hour['rounds']}"])
+        self.emit_table(probes, ["PROBE", "TARGET", "STATE", "LOSS", "RTT", "JITTER", "BREACHING"], rows)
+        return 0
+
+    def sla_history(self, args) -> int:
+        """alopex-cli sla history <probe>"""
+        rounds = self.client.request("GetSlaHistory", probe=args.probe)
+        self.emit_table(rounds, ["TIME", "RECEIVED", "LOSS", "RTT", "JITTER", "EXCEEDED"], [
+            [time.strftime("%H:%M:%S", time.localtime(r["timestamp"])), f"{r['received']}/{r['sent']}",
+             f"{r['loss']:.1f}%", f"{r['rtt']:.1f} ms" if r["rtt"] is not None else None,
+             f"{r['jitter']:.1f} ms" if r["jitter"] is not None else None, ", ".join(r["exceeded"])]
+            for r in rounds
+        ])
+        return 0
+
+    def failover_list(self, args) -> int:
+        """alopex-cli failover list"""
+        groups = self.client.request("ListFailover")
+        if not groups and not self.json:
+            print("No failover groups; add them under failover.groups in enterprise.json")
+            return 0
+        rows = []
+        for group in groups:
+            decision = group["last_decision"]
+            if group["failback_at"]:
+                failback = "at " + time.strftime("%H:%M:%S", time.localtime(group["failback_at"]))
+            else:
+                failback = group["failback"]
+            rows.append([group["name"], group["primary"], group["backup"], group["interface"], failback,
+                         f"{decision['decision']}: {decision['reason']}" if decision else None])
+        self.emit_table(groups, ["GROUP", "PRIMARY", "BACKUP", "ACTIVE", "FAILBACK", "LAST DECISION"], rows)
+        return 0
+
+    def failover_use(self, args) -> int:
+        """alopex-cli failover use <group> primary|backup"""
+        group = self.client.request("SetFailover", group=args.group, active=args.link)
+        self.emit(group, f"{group['name']}: on {group['interface']} ({group['active']})")
+        return 0
+
+    def session_list(self, args) -> int:
+        """alopex-cli session list"""
+        sessions = self.client.request("GetSessionCounters")
+        self.emit_table(sessions, ["DEVICE", "PROFILE", "SINCE", "RX", "TX"], [
+            [s["interface"], s["profile"], time.strftime("%Y-%m-%d %H:%M", time.localtime(s["started_at"])),
+             self.units.format_size(s["rx"]), self.units.format_size(s["tx"])] for s in sessions
+        ])
+        return 0
+
+    def session_reset(self, args) -> int:
+        """alopex-cli session reset [interface]"""
+        result = self.client.request("ResetSessionCounters", interface=args.interface)
+        self.emit(result, f"Reset session counters of {', '.join(result['reset']) or 'no interfaces'}")
+        return 0
+
+    def dns_show(self, args) -> int:
+        """alopex-cli dns show"""
+        servers = self.client.request("GetDnsHealth")
+        self.emit_table(servers, ["SERVER", "STATUS", "LATENCY", "FAILED", "ERROR"], [
+            [h["server"], h["status"], f"{h['latency_ms']} ms" if h["latency_ms"] is not None else None,
+             f"{h['failures']}/{h['checks']}", h["last_error"]] for h in servers
+        ])
+        return 0
+
+    def dns_set(self, args) -> int:
+        """alopex-cli dns set <interface> [server...]"""
+        result = self.client.request("SetDns", interface=args.interface, servers=args.servers)
+        servers = ", ".join(result["dns_servers"]) or "those from DHCP"
+        self.emit(result, f"{result['profile']}: DNS set to {servers}")
+        return 0
+
+    def dns_cache(self, args) -> int:
+        """alopex-cli dns cache"""
+        forwarder = self.client.request("GetDnsForwarder")
+        if not forwarder["enabled"]:
+            self.emit(forwarder, "The DNS forwarder is off; enable dns_forwarder in enterprise.json")
+            return 0
+        hit_rate = f"{forwarder['hit_rate']}%" if forwarder["hit_rate"] is not None else None
+        self.emit_table(forwarder, ["FORWARDER", "VALUE"], [
+            ["listening", ", ".join(forwarder["listening"])],
+            ["upstreams", ", ".join(forwarder["upstreams"])],
+            ["last answered by", forwarder["last_upstream"]],
+            ["queries", forwarder["queries"]],
+            ["unanswered", forwarder["failures"]],
+            ["cached", f"{forwarder['cache_entries']}/{forwarder['cache_size']}"],
+            ["hit rate", hit_rate]
+        ])
+        return 0
+
+    def dns_flush(self, args) -> int:
+        """alopex-cli dns flush"""
+        result = self.client.request("FlushDnsCache")
+        self.emit(result, f"Flushed {result['flushed']} cached answers")
+        return 0
+
+    def dns_filter(self, args) -> int:
+        """alopex-cli dns filter [--reload] [--on ZONE] [--off ZONE]"""
+        result = self.client.request("ReloadDnsFilter" if args.reload else "GetDnsFilter")
+        for zone, enabled in [(zone, True) for zone in args.on] + [(zone, False) for zone in args.off]:
+            result = self.client.request("SetDnsFilter", zone=zone, enabled=enabled)
+        if not result["enabled"]:
+            self.emit(result, "DNS filtering is off; enable dns_forwarder.filter in enterprise.json")
+            return 0
+        if self.json:
+            print_json(result)
+            return 0
+
+        zones = ", ".join(f"{zone} {'on' if on else 'off'}" for zone, on in result["zones"].items())
+        print(f"Blocking {result['names']} names; {result['blocked']} queries refused")
+        print(f"Zones: {zones + ', ' if zones else ''}others {'on' if result['default'] else 'off'}")
+        for entry in result["lists"]:
+            if entry["error"]:
+                print(f"warning: {entry['path']}: {entry['error']}", file=sys.stderr)
+        if result["clients"]:
+            print()
+            print_table(["CLIENT", "BLOCKED", "TOP NAMES"], [
+                [c["client"], c["blocked"], ", ".join(f"{name} ({count})" for name, count in c["top"][:3])]
+                for c in result["clients"]
+            ])
+        return 0
+
+    def dhcp_show(self, args) -> int:
+        """alopex-cli dhcp show <interface>"""
+        lease = self.client.request("GetDhcpLease", interface=args.interface)
+        if self.json:
+            print_json(lease)
+            return 0
+
+        def duration(seconds):
+            return format_duration(seconds) if seconds is not None else None
+
+        print_table(["LEASE", "VALUE"], [
+            ["address", lease["ip_address"]],
+            ["server", lease["server"]],
+            ["routers", ", ".join(lease["routers"])],
+            ["dns", ", ".join(lease["dns_servers"])],
+            ["lease time", duration(lease["lease_time"])],
+            ["renews in", duration(lease["renew_in"])],
+            ["expires in", duration(lease["expires_in"])]
+        ] + [[f"option {key}", value] for key, value in sorted(lease.get("options", {}).items())])
+        return 0
+
+    def dhcp_renew(self, args) -> int:
+        """alopex-cli dhcp renew <interface>"""
+        lease = self.client.request("RenewDhcpLease", interface=args.interface)
+        self.emit(lease, f"{args.interface}: renewed {lease['ip_address']} from {lease['server'] or 'server'}")
+        return 0
+
+    def dhcp_release(self, args) -> int:
+        """alopex-cli dhcp release <interface>"""
+        result = self.client.request("ReleaseDhcpLease", interface=args.interface)
+        self.emit(result, f"{args.interface}: lease released")
+        return 0
+
+    def dhcp_leases(self, args) -> int:
+        """alopex-cli dhcp leases [interface]"""
+        leases = self.client.request("ListDhcpLeases", interface=args.interface)
+        self.emit_table(leases, ["DEVICE", "ADDRESS", "MAC", "HOSTNAME", "EXPIRES IN", ""], [
+            [lease["interface"], lease["ip"], lease["mac"], lease["hostname"], format_duration(lease["expires_in"]),
+             "reserved" if lease["reserved"] else ""] for lease in leases
+        ])
+        return 0
+
+    def dhcp_reserve(self, args) -> int:
+        """alopex-cli dhcp reserve|unreserve <profile> <mac> [ip]"""
+        ip = args.ip if args.dhcp_command == "reserve" else None
+        result = self.client.request("SetDhcpReservation", profile=args.profile, mac=args.mac, ip=ip)
+        self.emit(result, f"{args.profile}: {args.mac} always gets {ip}" if ip
+                  else f"{args.profile}: {args.mac} takes any pool address")
+        return 0
+
+    def rfkill_list(self, args) -> int:
+        """alopex-cli rfkill list"""
+        devices = self.client.request("ListRfkill")
+        self.emit_table(devices, ["ID", "TYPE", "DEVICE", "INTERFACE", "STATE"],
+                        [[d["index"], d["type"], d["name"], d["interface"], BLOCK_LABELS[d["block"]]]
+                         for d in devices])
+        return 0
+
+    def rfkill_block(self, args) -> int:
+        """alopex-cli rfkill block|unblock <wifi|bluetooth|wwan|all|ID>"""
+        blocked = args.rfkill_command == "block"
+        devices = self.client.request("SetRfkill", target=args.target, blocked=blocked)
+        self.emit(devices, "\n".join(f"{d['name']} ({d['type']}): {BLOCK_LABELS[d['block']]}" for d in devices))
+        # Only the switch itself can lift a hard block
+        stuck = [d["name"] for d in devices if not blocked and d["block"] == "hard"]
+        if stuck:
+            print(f"{', '.join(stuck)} still blocked by a hardware switch or key", file=sys.stderr)
+            return 1
+        return 0
+
+    def airplane(self, args) -> int:
+        """alopex-cli airplane [on|off]"""
+        if args.state:
+            state = self.client.request("SetAirplaneMode", enabled=args.state == "on")
+        else:
+            state = self.client.request("GetAirplaneMode")
+        if self.json:
+            print_json(state)
+            return 0
+
+        print(f"Airplane mode {'on' if state['enabled'] else 'off'}")
+        for radio in state["radios"]:
+            print(f"  {radio['name']} ({radio['type']}): {BLOCK_LABELS[radio['block']]}")
+        return 0
+
+    def hotspot_start(self, args) -> int:
+        """alopex-cli hotspot start <ssid> [-i IFACE] [--ask-pass] [--channel N] [--isolate]"""
+        interface = self._wifi_interface(args.interface)
+        password = None
+        if args.ask_pass:
+            password = getpass.getpass(f"Password for {args.ssid} (8-63 characters, empty for an open network): ")
+
+        existing = next((p for p in self.client.request("ListProfiles", interface=interface)
+                         if p["connection_type"] == "hotspot" and p["ssid"] == args.ssid), None)
+        if existing and password is None and args.channel is None and not args.isolate:
+            name = existing["name"]
+        else:
+            name = existing["name"] if existing else f"{args.ssid} hotspot"
+            self.client.request("CreateProfile", name=name, interface=interface, connection_type="hotspot",
+                                method="shared", ssid=args.ssid, password=password or None, channel=args.channel,
+                                blocked_clients=existing["blocked_clients"] if existing else [],
+                                client_filter=existing["client_filter"] if existing else "deny",
+                                allowed_clients=existing["allowed_clients"] if existing else [],
+                                client_isolation=args.isolate or bool(existing and existing["client_isolation"]),
+                                client_limits=existing["client_limits"] if existing else {},
+                                auto_connect=False, replace=True)
+
+        state = self.run_job("Connect", profile=name)
+        self.emit(state, f"{interface}: sharing as {args.ssid} ({state.get('ip_address') or 'no address'})")
+        return 0
+
+    def _hotspot(self, interface: Optional[str]) -> Dict:
+        """The running hotspot on interface, or the only one running"""
+        hotspots = self.client.request("ListHotspotClients", interface=interface)
+        if len(hotspots) != 1:
+            raise IpcError("No hotspot running" if not hotspots else "Several hotspots running; pick one with -i",
+                           ErrorCode.NOT_FOUND if not hotspots else ErrorCode.INVALID_REQUEST,
+                           {"running": [h["interface"] for h in hotspots]})
+        return hotspots[0]
+
+    def hotspot_stop(self, args) -> int:
+        """alopex-cli hotspot stop [-i IFACE]"""
+        hotspot = self._hotspot(args.interface)
+        result = self.client.request("Disconnect", interface=hotspot["interface"])
+        self.emit(result, f"{hotspot['interface']}: stopped sharing {hotspot['ssid']}")
+        return 0
+
+    def hotspot_clients(self, args) -> int:
+        """alopex-cli hotspot clients [-i IFACE]"""
+        hotspots = self.client.request("ListHotspotClients", interface=args.interface)
+        if self.json:
+            print_json(hotspots)
+            return 0
+        if not hotspots:
+            print("No hotspot running")
+            return 0
+
+        for hotspot in hotspots:
+            print(f"{hotspot['interface']}: {hotspot['ssid']}, {len(hotspot['clients'])} connected"
+                  + (", allow-listed stations only" if hotspot["filter"] == "allow" else "")
+                  + (", clients isolated" if hotspot["isolated"] else ""))
+            print_table(["MAC", "VENDOR", "HOSTNAME", "ADDRESS", "SIGNAL", "RX", "TX", "CONNECTED", "LIMIT"], [
+                [c["mac"], c["vendor"], c["hostname"], c["ip"],
+                 f"{c['signal']} dBm" if c["signal"] is not None else None,
+                 self.units.format_size(c["rx_bytes"]), self.units.format_size(c["tx_bytes"]),
+                 format_duration(c["connected_time"]) if c["connected_time"] is not None else None,
+                 format_limit(hotspot["limits"].get(c["mac"]))]
+                for c in hotspot["clients"]
+            ])
+            if hotspot["blocked"]:
+                print(f"Blocked: {', '.join(hotspot['blocked'])}")
+            connected = {c["mac"] for c in hotspot["clients"]}
+            waiting = [mac for mac in hotspot["allowed"] if mac not in connected]
+            if waiting:
+                print(f"Allowed, not connected: {', '.join(waiting)}")
+            away = [f"{mac} {format_limit(limit)}" for mac, limit in hotspot["limits"].items() if mac not in connected]
+            if away:
+                print(f"Limited, not connected: {', '.join(away)}")
+        return 0
+
+    def hotspot_kick(self, args) -> int:
+        """alopex-cli hotspot kick <mac> [-i IFACE]"""
+        result = self.client.request("KickHotspotClient", mac=args.mac, interface=args.interface)
+        self.emit(result, f"{result['mac']} disconnected from {result['interface']} "
+                          f"(it may reconnect; block it to keep it out)")
+        return 0
+
+    def hotspot_block(self, args) -> int:
+        """alopex-cli hotspot block|unblock <mac> [-i IFACE]"""
+        blocked = args.hotspot_command == "block"
+        result = self.client.request("BlockHotspotClient", mac=args.mac, interface=args.interface, blocked=blocked)
+        self.emit(result, f"{result['mac']} {'blocked' if blocked else 'allowed again'} on {result['interface']}")
+        return 0
+
+    def hotspot_allow(self, args) -> int:
+        """alopex-cli hotspot allow|disallow <mac> [-i IFACE]"""
+        allowed = args.hotspot_command == "allow"
+        result = self.client.request("AllowHotspotClient", mac=args.mac, interface=args.interface, allowed=allowed)
+        note = "" if result["filter"] == "allow" else " (takes effect with hotspot filter allow)"
+        self.emit(result, f"{result['mac']} {'on' if allowed else 'off'} the allow list of {result['interface']}{note}")
+        return 0
+
+    def hotspot_filter(self, args) -> int:
+        """alopex-cli hotspot filter [allow|deny] [-i IFACE]"""
+        if args.mode is None:
+            hotspot = self._hotspot(args.interface)
+            result = {"interface": hotspot["interface"], "filter": hotspot["filter"],
+                      "allowed_clients": hotspot["allowed"]}
+        else:
+            result = self.client.request("SetHotspotFilter", mode=args.mode, interface=args.interface)
+        if result["filter"] == "allow":
+            text = f"{result['interface']}: only {', '.join(result['allowed_clients'])} may join"
+        else:
+            text = f"{result['interface']}: everyone may join except blocked stations"
+        self.emit(result, text)
+        return 0
+
+    def hotspot_isolate(self, args) -> int:
+        """alopex-cli hotspot isolate on|off [-i IFACE]"""
+        result = self.client.request("SetHotspotIsolation", isolated=args.state == "on", interface=args.interface)
+        self.emit(result, f"{result['interface']}: " + ("stations can only reach the uplink, not each other"
+                                                        if result["isolated"] else "stations can reach each other"))
+        return 0
+
+    def hotspot_limit(self, args) -> int:
+        """alopex-cli hotspot limit <mac> [--down KBIT] [--up KBIT] [-i IFACE]"""
+        result = self.client.request("LimitHotspotClient", mac=args.mac, interface=args.interface,
+                                     down=args.down, up=args.up)
+        limit = format_limit(result["limit"])
+        self.emit(result, f"{result['mac']} on {result['interface']}: "
+                          f"{f'limited to {limit} kbit/s (down/up)' if limit else 'no limit'}")
+        return 0
+
+    def bluetooth_list(self, args) -> int:
+        """alopex-cli bluetooth list [--scan]"""
+        devices = self.run_job("ScanBluetooth")["devices"] if args.scan else self.client.request("ListBluetoothDevices")
+        if self.json:
+            print_json(devices)
+            return 0
+        if not devices:
+            print("No Bluetooth devices known" + ("" if args.scan else " (alopex-cli bluetooth list --scan)"))
+            return 0
+
+        print_table(["MAC", "NAME", "TYPE", "SIGNAL", "BATTERY", "STATE", "VENDOR"], [
+            [d["mac"], d["alias"] or d["name"], {"le": "LE", "dual": "dual"}.get(d["type"], d["type"]),
+             f"{d['rssi']} dBm" if d["rssi"] is not None else None,
+             f"{d['battery']}%" if d["battery"] is not None else None,
+             "connected" if d["connected"] else "paired" if d["paired"] else None, d["vendor"]]
+            for d in devices
+        ])
+        return 0
+
+    def bluetooth_info(self, args) -> int:
+        """alopex-cli bluetooth info <mac>"""
+        device = self.client.request("GetBluetoothDevice", mac=args.mac)
+        if self.json:
+            print_json(device)
+            return 0
+
+        battery = f"{device['battery']}%" if device["battery"] is not None else (
+            "connect to read it" if device["battery_service"] else None)
+        print_table(["FIELD", "VALUE"], [
+            ["name", device["alias"] or device["name"]],
+            ["type", {"le": "Low Energy", "classic": "classic (BR/EDR)", "dual": "dual mode"}.get(device["type"])],
+            ["address", f"{device['mac']} ({device['address_type']})" if device["address_type"] else device["mac"]],
+            ["vendor", device["vendor"]],
+            ["state", ", ".join(state for state in ("paired", "trusted", "connected") if device[state]) or None],
+            ["signal", f"{device['rssi']} dBm" if device["rssi"] is not None else None],
+            ["tx power", f"{device['tx_power']} dBm" if device["tx_power"] is not None else None],
+            ["battery", battery]
+        ] + [["service", service["name"] or service["uuid"]] for service in device["services"]])
+        return 0
+
+    def bluetooth_alias(self, args) -> int:
+        """alopex-cli bluetooth alias <mac> [name]"""
+        device = self.client.request("SetBluetoothAlias", mac=args.mac, alias=args.alias or "")
+        name = device["alias"] or device["name"] or "no name"
+        self.emit(device, f"{device['mac']}: {name}" if args.alias else f"{device['mac']}: alias removed ({name})")
+        return 0
+
+    def bluetooth_send(self, args) -> int:
+        """alopex-cli bluetooth send <mac> <file>"""
+        path = args.file.resolve()
+        if not path.is_file():
+            raise IpcError(f"No such file: {args.file}", ErrorCode.INVALID_REQUEST, {"path": str(args.file)})
+        sent = self.run_job("SendBluetoothFile", timeout=3600.0, mac=args.mac, path=str(path))
+        self.emit(sent, f"Sent {sent['file']} ({sent['size']} bytes) to {sent['device'] or sent['mac']}")
+        return 0
+
+    def bluetooth_connect(self, args) -> int:
+        """alopex-cli bluetooth connect|pair <mac>"""
+        request = "PairBluetooth" if args.bluetooth_command == "pair" else "ConnectBluetooth"
+        device = self.run_job(request, mac=args.mac)
+        name = device["alias"] or device["name"] or device["mac"]
+        battery = f", battery {device['battery']}%" if device["battery"] is not None else ""
+        self.emit(device, f"{'Paired with' if request == 'PairBluetooth' else 'Connected to'} {name}{battery}")
+        return 0
+
+    def lan_scan(self, args) -> int:
+        """alopex-cli lan scan [-i IFACE]"""
+        result = self.run_job("ScanLan", interface=args.interface)
+        if self.json:
+            print_json(result)
+            return 0
+
+        devices = result["devices"]
+        print(f"{result['interface']}: {len(devices)} devices on {result['network']}")
+        print_table(["ADDRESS", "MAC", "VENDOR", "HOSTNAME", "ROLE"], [
+            [d["ip"], d["mac"], d["vendor"] or ("(randomized)" if d["randomized_mac"] else None), d["hostname"],
+             d["role"]]
+            for d in devices
+        ])
+        return 0
+
+    def probe(self, args) -> int:
+        """alopex-cli probe <host:port> [-i IFACE] [-w SECONDS]"""
+        try:
+            host, port = parse_target(args.target)
+        except ValueError as e:
+            raise IpcError(str(e), ErrorCode.INVALID_REQUEST, {"target": args.target})
+        # The reply only comes once the connection is decided
+        client = self.client.with_timeout(self.client.timeout + args.wait)
+        result = client.request("ProbePort", host=host, port=port, timeout=args.wait, interface=args.interface)
+        self.emit(result, summary(result))
+        return 0 if result["state"] == "open" else 1
+
+    def diagnose(self, args) -> int:
+        """alopex-cli diagnose [TARGET...] [-w SECONDS]"""
+        diagnosis = self.run_job("DiagnoseConnectivity", targets=args.targets or None,
+                                 connect_timeout=args.wait)
+        self.emit(diagnosis, report(diagnosis))
+        return 0 if diagnosis["healthy"] else 1
+
+    def troubleshoot(self, args) -> int:
+        """alopex-cli troubleshoot [IFACE] [--target HOST:PORT]..."""
+        result = self.run_job("Troubleshoot", interface=args.interface, targets=args.target or None)
+        self.emit(result, "\n".join(format_steps(result) + ["", troubleshoot_summary(result)]))
+        return 1 if result["failed"] else 0
+
+    def cable_test(self, args) -> int:
+        """alopex-cli cable-test <iface>"""
+        iface = self._interface(args.interface)
+        result = self.run_job("RunCableTest", interface=iface["name"])
+        if self.json:
+            print_json(result)
+            return 0 if result["healthy"] else 1
+
+        print_table(["PAIR", "STATUS", "FAULT AT"], [
+            [p["pair"], STATUS_TEXT[p["status"]],
+             f"{p['fault_length_m']:g} m" if p["fault_length_m"] is not None else None]
+            for p in result["pairs"]
+        ])
+        print()
+        print(result["summary"])
+        return 0 if result["healthy"] else 1
+
+    def module(self, args) -> int:
+        """alopex-cli module <iface>"""
+        iface = self._interface(args.interface)
+        module = self.client.request("GetModuleInfo", interface=iface["name"])
+        if self.json:
+            print_json(module)
+            return 0
+
+        def channels(readings, unit):
+            return " / ".join("--" if r is None else f"{r:g}" for r in readings) + f" {unit}" if readings else None
+
+        print_table(["FIELD", "VALUE"], [
+            ["module", " ".join(filter(None, [module["identifier"], module["connector"]]))],
+            ["vendor", module["vendor"]],
+            ["part number", " rev ".join(filter(None, [module["part_number"], module["revision"]]))],
+            ["serial", module["serial"]],
+            ["type", module["type"]],
+            ["wavelength", f"{module['wavelength_nm']:g} nm" if module["wavelength_nm"] else None],
+            ["temperature", f"{module['temperature_c']:g} °C" if module["temperature_c"] is not None else None],
+            ["voltage", f"{module['voltage_v']:g} V" if module["voltage_v"] is not None else None],
+            ["bias", channels(module["bias_ma"], "mA")],
+            ["tx power", format_power(module["tx_power_dbm"])],
+            ["rx power", format_power(module["rx_power_dbm"])],
+            ["alarms", ", ".join(module["alarms"]) or ("none" if module["diagnostics"] else None)]
+        ])
+        return 0
+
+    def queues(self, args) -> int:
+        """alopex-cli queues <iface> [--spread]"""
+        iface = self._interface(args.interface)
+        request = "SpreadIrqs" if args.spread else "GetNicQueues"
+        result = self.client.request(request, interface=iface["name"])
+        if self.json:
+            print_json(result)
+            return 0
+
+        if args.spread:
+            moved = len(result["irqs"]) - len(result["failed"])
+            print(f"Moved {moved} IRQs" + (f"; the kernel kept {len(result['failed'])} where they were"
+                                           if result["failed"] else ""))
+            print()
+        print(f"{result['interface']}: {len(result['queues'])} queues, {len(result['irqs'])} IRQs on CPUs "
+              f"{format_cpu_list(result['irq_cpus']) or '--'} of {format_cpu_list(result['cpus'])}")
+        if result["queues"]:
+            print()
+            print_table(["QUEUE", "RX PACKETS", "TX PACKETS", "SHARE"], [
+                [q["queue"], q["rx_packets"], q["tx_packets"], f"{q['share']:.0%}" if q["share"] is not None else None]
+                for q in result["queues"]
+            ])
+        if result["irqs"]:
+            print()
+            print_table(["IRQ", "NAME", "INTERRUPTS", "CPUS"],
+                        [[i["irq"], i["name"], i["interrupts"], format_cpu_list(i["cpus"])] for i in result["irqs"]])
+        if result["warnings"]:
+            print()
+        for warning in result["warnings"]:
+            print(f"Warning: {warning}")
+        return 0
+
+    def flows(self, args) -> int:
+        """alopex-cli flows [--top N]"""
+        flows = self.client.request("GetFlows", top=args.top)
+        if self.json:
+            print_json(flows)
+            return 0
+
+        def size(nbytes):
+            return self.units.format_size(nbytes) if nbytes is not None else None
+
+        usage = f" of {flows['limit']} ({flows['usage']}%)" if flows["limit"] else ""
+        protocols = ", ".join(f"{count} {protocol}" for protocol, count in flows["protocols"].items())
+        print(f"{flows['total']} flows{usage}" + (f": {protocols}" if protocols else ""))
+        if flows["total"]:
+            print()
+            print_table(["DESTINATION", "FLOWS", "BYTES"],
+                        [[h["host"], h["flows"], size(h["bytes"])] for h in flows["top_hosts"]])
+            print()
+            print_table(["PORT", "FLOWS"], [[f"{p['port']}/{p['protocol']}", p["flows"]] for p in flows["top_ports"]])
+        for nat in flows["nat"]:
+            print()
+            print(f"{nat['interface']} sharing {nat['network']}: {nat['flows']} NAT flows")
+            print_table(["CLIENT", "FLOWS", "BYTES"],
+                        [[c["address"], c["flows"], size(c["bytes"])] for c in nat["clients"]])
+        return 0
+
+    def top(self, args) -> int:
+        """alopex-cli top [-n N]"""
+        talkers = self.client.request("GetTopTalkers", top=args.top)
+        if not talkers["enabled"]:
+            self.emit(talkers, "Unit traffic accounting is off; set accounting.enabled in enterprise.json")
+            return 0
+
+        def rate(value):
+            return self.units.format_rate(value) if value is not None else None
+
+        self.emit_table(talkers, ["UNIT", "RX", "TX", "RECEIVED", "SENT"], [
+            [u["unit"], rate(u["rx_rate"]), rate(u["tx_rate"]), self.units.format_size(u["rx_bytes"]),
+             self.units.format_size(u["tx_bytes"])]
+            for u in talkers["units"]
+        ])
+        if talkers["last_error"] and not self.json:
+            print(f"warning: {talkers['last_error']}", file=sys.stderr)
+        return 0
+
+    def containers(self, args) -> int:
+        """alopex-cli containers"""
+        result = self.client.request("GetContainerNetworks")
+        if not result["enabled"]:
+            self.emit(result, "Container network detection is off; set containers.enabled in enterprise.json")
+            return 0
+        self.emit_table(result, ["ENGINE", "NETWORK", "BRIDGE", "SUBNET", "CONTAINERS", "PORTS"], [
+            [n["engine"], n["name"], n["bridge"], ", ".join(n["subnets"]),
+             ", ".join(f"{c['name']} ({c['ip']})" if c["ip"] else c["name"] for c in n["containers"]),
+             len(n["interfaces"]) - 1]
+            for n in result["networks"]
+        ])
+        if not self.json:
+            for engine, status in result["engines"].items():
+                if status not in ("ok", "not running"):
+                    print(f"warning: {engine}: {status}", file=sys.stderr)
+        return 0
+
+    def virt(self, args) -> int:
+        """alopex-cli virt [-b BRIDGE]"""
+        result = self.client.request("GetVirtualNetworks", bridge=args.bridge)
+        if not result["enabled"]:
+            self.emit(result, "libvirt network detection is off; set libvirt.enabled in enterprise.json")
+            return 0
+        rows = []
+        for n in result["networks"]:
+            network = [n["name"] or "?", n["bridge"] if n["active"] else f"{n['bridge']} (inactive)",
+                       n["forward"], ", ".join(n["subnets"])]
+            for guest in n["guests"] or [None]:
+                guest = guest or {}
+                rows.append(network + [guest.get("domain") or guest.get("hostname"), guest.get("mac"), guest.get("ip")])
+        self.emit_table(result, ["NETWORK", "BRIDGE", "FORWARD", "SUBNET", "GUEST", "MAC", "ADDRESS"], rows)
+        if result["error"] and not self.json:
+            print(f"warning: {result['error']}", file=sys.stderr)
+        return 0
+
+    def notify_list(self, args) -> int:
+        """alopex-cli notify list"""
+        result = self.client.request("GetNotifications")
+        if not result["enabled"]:
+            self.emit(result, "Notifications are off; set notifications.enabled in enterprise.json")
+            return 0
+        self.emit_table(result, ["NAME", "KIND", "DESTINATION", "FOR", "LIMIT", "QUIET", "SENT", "FAILED", "HELD",
+                                 "LAST SENT"], [
+            [s["name"], s["kind"], s["url"], ", ".join(s["events"]), s["rate_limit"], s["quiet_hours"], s["sent"],
+             s["failures"], s["suppressed"],
+             time.strftime("%Y-%m-%d %H:%M:%S", time.localtime(s["last_sent"])) if s["last_sent"] else None]
+            for s in result["sinks"]
+        ])
+        if not self.json:
+            for sink in result["sinks"]:
+                if sink["last_error"]:
+                    print(f"warning: {sink['name']}: {sink['last_error']}", file=sys.stderr)
+        return 0
+
+    def notify_test(self, args) -> int:
+        """alopex-cli notify test [SINK]"""
+        # Mail relays can take a while to answer
+        client = self.client.with_timeout(self.client.timeout + SMTP_TIMEOUT)
+        results = client.request("TestNotification", sink=args.sink)
+        self.emit(results, "\n".join(
+            f"{r['name']} ({r['kind']}): {'sent' if r['sent'] else 'failed: ' + (r['error'] or 'unknown')}"
+            for r in results
+        ))
+        return 0 if all(r["sent"] for r in results) else 1
+
+    def quota(self, args) -> int:
+        """alopex-cli quota"""
+        quotas = self.client.request("GetQuotas")
+        if not quotas and not self.json:
+            print("No data quotas; add them under quota.limits in enterprise.json")
+            return 0
+        self.emit_table(quotas, ["INTERFACE", "PERIOD", "USED", "LIMIT", "PERCENT", "SINCE"], [
+            [q["interface"], q["period"], self.units.format_size(q["used"]), self.units.format_size(q["limit"]),
+             f"{q['percent']}%" + (" (over)" if q["exceeded"] else ""),
+             time.strftime("%Y-%m-%d", time.localtime(q["period_start"]))]
+            for q in quotas
+        ])
+        return 0
+
+    def rates(self, args) -> int:
+        """alopex-cli rates [-i IFACE]"""
+        rates = self.client.request("GetFastRates", interface=args.interface)
+        if not rates["enabled"]:
+            self.emit(rates, "eBPF counters are off; set ebpf_counters.enabled in enterprise.json")
+            return 0
+        if not rates["active"]:
+            self.emit(rates, f"eBPF counters unavailable: {rates['reason']}")
+            return 1
+
+        rows = []
+        for name, interface in rates["interfaces"].items():
+            for direction in ("rx", "tx"):
+                counted = interface.get(direction, {"bytes_per_s": 0, "protocols": {}})
+                rows.append([name, direction.upper(), self.units.format_rate(counted["bytes_per_s"])] + [
+                    self.units.format_rate(counted["protocols"].get(protocol, {}).get("bytes_per_s", 0))
+                    for protocol in ("tcp", "udp", "icmp", "other")
+                ] + [self.units.format_rate(interface["peak"][direction])])
+        self.emit_table(rates, ["INTERFACE", "DIR", "RATE", "TCP", "UDP", "ICMP", "OTHER",
+                                f"PEAK {rates['window']}S"], rows)
+        return 0
+
+    def import_connections(self, args) -> int:
+        """alopex-cli import <source> [--path PATH] [--dry-run] [--replace]"""
+        path = str(args.path.resolve()) if args.path else None
+        report = self.client.request("ImportConnections", source=args.source, path=path,
+                                     dry_run=args.dry_run, replace=args.replace)
+        if self.json:
+            print_json(report)
+            return 0
+
+        imported = set(report["imported"])
+        print_table(["NAME", "TYPE", "INTERFACE", "METHOD"],
+                    [[p["name"], p["connection_type"], p["interface"], p["method"]]
+                     for p in report["profiles"] if p["name"] in imported])
+        for entry in report["skipped"]:
+            print(f"skipped {entry['name']}: {entry['reason']}")
+        for entry in report["warnings"]:
+            print(f"warning {entry['name']}: {entry['message']}")
+        if not self.quiet:
+            verb = "Would import" if args.dry_run else "Imported"
+            print(f"{verb} {len(imported)} profiles from {report['source']}, skipped {len(report['skipped'])}")
+        return 0
+
+    def export_connections(self, args) -> int:
+        """alopex-cli export <target> [-o DIR] [-p PROFILE ...] [--secrets]"""
+        report = self.client.request("ExportConnections", target=args.target, profiles=args.profile,
+                                     secrets=args.secrets)
+        if self.json:
+            print_json(report)
+            return 0
+
+        if args.output:
+            args.output.mkdir(parents=True, exist_ok=True)
+            for exported in report["files"]:
+                path = args.output / exported["name"]
+                # Files holding WiFi passwords are nobody else's business
+                fd = os.open(path, os.O_WRONLY | os.O_CREAT | os.O_TRUNC, 0o600 if exported["secret"] else 0o644)
+                with os.fdopen(fd, "w") as f:
+                    f.write(exported["content"])
+        else:
+            # Only the configuration goes to stdout, so it can be redirected into a file
+            for exported in report["files"]:
+                if len(report["files"]) > 1:
+                    print(f"# {exported['name']}")
+                print(exported["content"], end="")
+        for entry in report["skipped"]:
+            print(f"skipped {entry['name']}: {entry['reason']}", file=sys.stderr)
+        for entry in report["warnings"]:
+            print(f"warning {entry['name']}: {entry['message']}", file=sys.stderr)
+        if args.output and not self.quiet:
+            print(f"Exported {len(report['exported'])} profiles to {len(report['files'])} files in {args.output}, "
+                  f"skipped {len(report['skipped'])}")
+        return 0
+
+    def _passphrase(self, confirm: bool) -> str:
+        """Backup passphrase from ALOPEX_BACKUP_PASSPHRASE, or asked for"""
+        passphrase = os.getenv("ALOPEX_BACKUP_PASSPHRASE")
+        if passphrase:
+            return passphrase
+        passphrase = getpass.getpass("Backup passphrase: ")
+        if confirm and getpass.getpass("Repeat passphrase: ") != passphrase:
+            raise IpcError("Passphrases do not match", ErrorCode.INVALID_REQUEST)
+        return passphrase
+
+    def backup(self, args) -> int:
+        """alopex-cli backup <file>"""
+        result = self.client.request("ExportProfiles", passphrase=self._passphrase(confirm=True))
+
+        # The archive is encrypted, but still nobody else's business
+        fd = os.open(args.file, os.O_WRONLY | os.O_CREAT | os.O_TRUNC, 0o600)
+        with os.fdopen(fd, "w") as f:
+            f.write(result["archive"])
+
+        self.emit({"file": str(args.file), "profiles": result["profiles"]},
+                  f"Saved {len(result['profiles'])} profiles to {args.file}")
+        return 0
+
+    def restore(self, args) -> int:
+        """alopex-cli restore <file> [--replace]"""
+        try:
+            archive = args.file.read_text()
+        except OSError as e:
+            raise IpcError(f"Cannot read {args.file}: {e.strerror}", ErrorCode.NOT_FOUND, {"file": str(args.file)})
+
+        result = self.client.request("ImportProfiles", archive=archive, passphrase=self._passphrase(confirm=False),
+                                     replace=args.replace)
+        if not self.json:
+            for entry in result["skipped"]:
+                print(f"skipped {entry['name']}: {entry['reason']}")
+        self.emit(result, f"Restored {len(result['imported'])} profiles from {result['hostname']}")
+        return 0
+
+    def snapshot(self, args) -> int:
+        """alopex-cli snapshot <file> [--events N] [--samples N]"""
+        snapshot = self.client.request("GetDiagnostics", events=args.events, samples=args.samples)
+        try:
+            write_snapshot(snapshot, args.file)
+        except OSError as e:
+            raise IpcError(f"Cannot write {args.file}: {e.strerror}", ErrorCode.PERMISSION_DENIED,
+                           {"file": str(args.file)})
+
+        self.emit({"file": str(args.file), "interfaces": len(snapshot["interfaces"]),
+                   "events": len(snapshot["events"]), "samples": len(snapshot["metrics"])},
+                  f"Wrote diagnostic snapshot to {args.file} (secrets redacted)")
+        return 0
+
+    def nm(self, args) -> int:
+        """alopex-cli nm <nmcli arguments>: run them through the nmcli compatibility layer"""
+        for candidate in (Path(__file__).resolve().parent / "nmcli-compat.py", Path(sys.path[0]) / "nmcli-compat.py"):
+            if candidate.exists():
+                break
+        else:
+            raise IpcError("nmcli compatibility layer is not installed", ErrorCode.NOT_FOUND)
+
+        spec = importlib.util.spec_from_file_location("nmcli_compat", candidate)
+        compat = importlib.util.module_from_spec(spec)
+        spec.loader.exec_module(compat)
+
+        # Exit statuses are nmcli's, not alopex-cli's, so existing scripts keep working
+        sys.argv = ["nmcli", *args.nmcli_args]
+        return compat.main(self.client.socket_path)
+
+def build_parser() -> argparse.ArgumentParser:
+    parser = argparse.ArgumentParser(prog="alopex-cli", description="Control alopexd from scripts and shells")
+    parser.add_argument("--socket", type=Path, default=SOCKET_PATH, help="alopexd control socket")
+    parser.add_argument("--timeout", type=float, default=5.0, help="Seconds to wait for each reply")
+    parser.add_argument("-H", "--host", default=os.getenv("ALOPEX_HOST"),
+                        help="Talk to this fleet.json host instead of the local daemon (default: $ALOPEX_HOST)")
+    parser.add_argument("-q", "--quiet", action="store_true", help="Only print requested data and errors")
+    parser.add_argument("--json", action="store_true", help="Print results and errors as JSON")
+    parser.add_argument("--units", dest="unit_style", choices=UNIT_STYLES,
+                        default=os.getenv("ALOPEX_UNITS", DEFAULT_UNITS),
+                        help="Units for rates and byte counts (default: $ALOPEX_UNITS or %(default)s)")
+    commands = parser.add_subparsers(dest="command", required=True)
+
+    list_parser = commands.add_parser("list", help="List interfaces and their connection state")
+    list_scope = list_parser.add_mutually_exclusive_group()
+    list_scope.add_argument("-g", "--group", help="Only interfaces in this group")
+    list_scope.add_argument("--fleet", action="store_true",
+                            help="Interfaces of every host behind the daemon, when it is a relay")
+    list_parser.add_argument("-a", "--all", action="store_true", help="Include container veths")
+    commands.add_parser("status", help="Show daemon status")
+    commands.add_parser("whoami", help="Your role on the daemon and the requests it refuses you")
+    commands.add_parser("hosts", help="The daemons in fleet.json and whether each answers (use one with --host)")
+    commands.add_parser("relay", help="The daemons behind this one, when it is a relay, and what each last reported")
+    events = commands.add_parser("events", help="What the daemon reported: connections, failures, roams, leases")
+    events.add_argument("--since", help="From this long ago (90m, 24h, 7d) or a local date/time")
+    events.add_argument("--filter", action="append", metavar="TERM",
+                        help="Event name pattern (Wifi*) or field=value (interface=wlan0); names are OR'd, "
+                             "fields must all match; repeatable")
+    events.add_argument("-n", "--count", type=int, default=100, help="How many recent events (default 100)")
+    undo = commands.add_parser("undo", help="Revert the last profile edit, DNS change or location switch")
+    undo.add_argument("--list", action="store_true", help="Show the changes that can be undone, newest first")
+
+    connect = commands.add_parser("connect", help="Activate a profile, or the best profile of an interface")
+    connect.add_argument("target", help="Profile name or interface")
+
+    disconnect = commands.add_parser("disconnect", help="Take an interface down")
+    disconnect.add_argument("interface")
+
+    wifi = commands.add_parser("wifi", help="WiFi scanning and association").add_subparsers(
+        dest="wifi_command", required=True)
+    scan = wifi.add_parser("scan", help="Scan for networks")
+    scan.add_argument("-i", "--interface", help="WiFi interface (default: first found)")
+    channels = wifi.add_parser("channels", help="Scan and show congestion per channel")
+    channels.add_argument("-i", "--interface", help="WiFi interface (default: first found)")
+    channels.add_argument("--band", choices=["2.4", "5"], help="Only this band")
+    country = wifi.add_parser("country", help="Show or set the regulatory domain and the channels it allows")
+    country.add_argument("country", nargs="?", help="ISO 3166 country code to set, or 00 for the world domain")
+    country.add_argument("-i", "--interface", help="WiFi interface (default: first found)")
+    powersave = wifi.add_parser("powersave", help="Show or switch radio power management")
+    powersave.add_argument("mode", nargs="?", choices=["on", "off", "default"],
+                           help="default clears the active profile's choice and leaves the radio as is")
+    powersave.add_argument("-i", "--interface", help="WiFi interface (default: first found)")
+    powersave.add_argument("--no-save", action="store_true", help="Don't remember it on the active profile")
+    alerts = wifi.add_parser("alerts", help="Rogue access points and deauthentication bursts seen")
+    alerts.add_argument("-n", "--count", type=int, default=20, help="How many recent alerts (default 20)")
+    alerts.add_argument("--relearn", nargs="?", const="", metavar="SSID",
+                        help="Forget the access points known for an SSID (all without one) and learn them again")
+    roams = wifi.add_parser("roams", help="Timeline of associations and roams between access points")
+    roams.add_argument("-i", "--interface", help="Only this WiFi interface")
+    roams.add_argument("-n", "--count", type=int, default=50, help="How many recent entries (default 50)")
+    wifi_connect = wifi.add_parser("connect", help="Connect to a network, saving it as a profile")
+    wifi_connect.add_argument("ssid")
+    wifi_connect.add_argument("-i", "--interface", help="WiFi interface (default: first found)")
+    wifi_connect.add_argument("--ask-pass", action="store_true", help="Prompt for the network password")
+    wifi_connect.add_argument("--eap", choices=["peap", "ttls", "tls"], help="Log in with 802.1X")
+    wifi_connect.add_argument("--identity", help="802.1X user name")
+    wifi_connect.add_argument("--ca-cert", help="Stored CA certificate ID to check the server against")
+    wifi_connect.add_argument("--client-cert", help="Stored client certificate ID (EAP-TLS)")
+
+    vpn = commands.add_parser("vpn", help="VPN tunnels").add_subparsers(dest="vpn_command", required=True)
+    vpn.add_parser("list", help="List VPN configurations")
+    vpn_up = vpn.add_parser("up", help="Bring a tunnel up")
+    vpn_up.add_argument("name")
+    vpn_down = vpn.add_parser("down", help="Take a tunnel down")
+    vpn_down.add_argument("name")
+
+    tunnel = commands.add_parser("tunnel", help="GRE, GRETAP and VXLAN overlay tunnels").add_subparsers(
+        dest="tunnel_command", required=True)
+    tunnel.add_parser("list", help="List tunnel profiles")
+    tunnel_add = tunnel.add_parser("add", help="Save a tunnel as a profile ('connect NAME' creates it)")
+    tunnel_add.add_argument("name", help="Interface name for the tunnel, e.g. gre-lab")
+    tunnel_add.add_argument("kind", choices=["gre", "gretap", "vxlan"], help="gretap and vxlan carry Ethernet")
+    tunnel_add.add_argument("remote", help="Far endpoint address")
+    tunnel_add.add_argument("--local", help="Our endpoint address (default: chosen by routing)")
+    tunnel_add.add_argument("--key", type=int, help="GRE key, to tell tunnels between the same hosts apart")
+    tunnel_add.add_argument("--vni", type=int, help="VXLAN network identifier (required for vxlan)")
+    tunnel_add.add_argument("--port", type=int, help="VXLAN UDP port (default: 4789)")
+    tunnel_add.add_argument("--dev", help="Underlay interface for VXLAN traffic")
+    tunnel_add.add_argument("--ttl", type=int, help="Outer packet TTL (default: inherit)")
+    tunnel_add.add_argument("--address", help="Static address on the tunnel (default: none, link up only)")
+    tunnel_add.add_argument("--no-auto", action="store_true", help="Don't bring it up automatically")
+    tunnel_add.add_argument("--replace", action="store_true", help="Overwrite an existing profile of that name")
+
+    profile = commands.add_parser("profile", help="Connection profiles").add_subparsers(
+        dest="profile_command", required=True)
+    profile.add_parser("list", help="List profiles")
+    profile_units = profile.add_parser("units", help="Set the systemd units started while a profile is active")
+    profile_units.add_argument("name")
+    profile_units.add_argument("units", nargs="*", help="Unit names; none clears the list")
+    profile_sysctl = profile.add_parser("sysctl", help="Set the network sysctls a profile applies while active")
+    profile_sysctl.add_argument("name")
+    profile_sysctl.add_argument("settings", nargs="*", metavar="SETTING=VALUE",
+                                help="e.g. rp_filter=2 tcp_congestion_control=bbr; none clears them")
+    profile_mptcp = profile.add_parser("mptcp", help="Add MPTCP endpoints for a profile's addresses while active")
+    profile_mptcp.add_argument("name")
+    profile_mptcp.add_argument("flags", nargs="*", metavar="FLAG",
+                               help="signal, subflow, backup or fullmesh; none removes the endpoints")
+    profile_qdisc = profile.add_parser("qdisc", help="Set the root qdisc a profile puts on its interface "
+                                       "(a common bufferbloat fix)")
+    profile_qdisc.add_argument("name")
+    profile_qdisc.add_argument("qdisc", choices=["fq", "fq_codel", "cake", "none"])
+    profile_qdisc.add_argument("--bandwidth", help="cake only: shape to this rate, e.g. 95mbit (a bit under "
+                               "the link's own)")
+    profile_congestion = profile.add_parser("congestion", help="Set TCP congestion control on a profile's "
+                                            "default routes")
+    profile_congestion.add_argument("name")
+    profile_congestion.add_argument("algorithm", help="e.g. bbr or cubic; none uses the host's")
+    profile_metered = profile.add_parser("metered", help="Mark a profile metered so desktops save data on it")
+    profile_metered.add_argument("name")
+    profile_metered.add_argument("state", choices=["yes", "no", "auto"])
+    profile_critical = profile.add_parser("critical",
+                                          help="Connect a profile first at boot and hold network-online.target for it")
+    profile_critical.add_argument("name")
+    profile_critical.add_argument("state", choices=["yes", "no"])
+    profile_metric = profile.add_parser("metric", help="Set a profile's default-route metric, or derive it (auto)")
+    profile_metric.add_argument("name")
+    profile_metric.add_argument("metric", help="0 and up, lower wins; auto goes by link type and priority")
+    profile_garp = profile.add_parser("garp", help="Announce a profile's addresses (gratuitous ARP, "
+                                      "unsolicited NA) when it comes up or takes over on failover")
+    profile_garp.add_argument("name")
+    profile_garp.add_argument("state", choices=["yes", "no"])
+    profile_proxy_arp = profile.add_parser("proxy-arp", help="Answer ARP and NDP for addresses behind a "
+                                           "profile's interface while it is active")
+    profile_proxy_arp.add_argument("name")
+    profile_proxy_arp.add_argument("state", choices=["yes", "no"])
+    profile.add_parser("retries", help="Show profiles auto-connect is backing off from or gave up on")
+    profile_retry = profile.add_parser("retry", help="Forget auto-connect failures and try again now")
+    profile_retry.add_argument("name", nargs="?", help="Only this profile (default: all)")
+    profile_certs = profile.add_parser("certs", help="Set the certificates a WiFi or VPN profile uses")
+    profile_certs.add_argument("name")
+    profile_certs.add_argument("--ca", help="CA certificate ID, or none")
+    profile_certs.add_argument("--client", help="Client certificate ID, or none")
+
+    cert = commands.add_parser("cert", help="Certificates and keys for 802.1X and VPNs").add_subparsers(
+        dest="cert_command", required=True)
+    cert.add_parser("list", help="List stored certificates and keys with their expiry")
+    cert_import = cert.add_parser("import", help="Store the certificates and keys in a PEM or DER file")
+    cert_import.add_argument("file", type=Path)
+    cert_import.add_argument("--name", help="Name to list it under (default: its common name)")
+    cert_import.add_argument("--ask-pass", action="store_true", help="Prompt for the key's password")
+    cert.add_parser("remove", help="Delete a certificate or key no profile uses").add_argument("id")
+
+    location = commands.add_parser("location", help="Named sets of profiles, DNS, proxy and firewall zone").add_subparsers(
+        dest="location_command", required=True)
+    location.add_parser("list", help="List locations")
+    location_set = location.add_parser("set", help="Create or replace a location")
+    location_set.add_argument("name")
+    location_set.add_argument("--profile", action="append", default=[], help="Preferred profile (repeatable)")
+    location_set.add_argument("--dns", action="append", default=[], help="DNS server (repeatable)")
+    location_set.add_argument("--proxy", help="HTTP(S) proxy URL")
+    location_set.add_argument("--zone", help="Firewall zone for connected interfaces")
+    location_set.add_argument("--ssid", action="append", default=[], help="Switch here when on this SSID (repeatable)")
+    location_set.add_argument("--gateway-mac", action="append", default=[],
+                              help="Switch here when the gateway has this MAC address (repeatable)")
+    location_set.add_argument("--priority", type=int, default=0, help="Preference when several locations match")
+    location_remove = location.add_parser("remove", help="Delete a location")
+    location_remove.add_argument("name")
+    location_switch = location.add_parser("switch", help="Apply a location now")
+    location_switch.add_argument("name")
+    location_auto = location.add_parser("auto", help="Switch automatically on detected SSID or gateway")
+    location_auto.add_argument("state", choices=["on", "off"])
+
+    metrics = commands.add_parser("metrics", help="Traffic counters for an interface")
+    metrics.add_argument("interface")
+    metrics.add_argument("--json", action="store_true", dest="metrics_json", help=argparse.SUPPRESS)
+
+    dns = commands.add_parser("dns", help="Resolvers and their health").add_subparsers(
+        dest="dns_command", required=True)
+    dns.add_parser("show", help="Show each resolver's latency and failures")
+    dns_set = dns.add_parser("set", help="Set the DNS servers of an interface's active profile, in order")
+    dns_set.add_argument("interface")
+    dns_set.add_argument("servers", nargs="*", help="Server addresses; none goes back to DHCP's")
+    dns.add_parser("cache", help="Show the local DNS forwarder's upstreams and cache")
+    dns.add_parser("flush", help="Empty the local DNS forwarder's cache")
+    dns_filter = dns.add_parser("filter", help="Show blocklist filtering, reload the lists or switch it per zone")
+    dns_filter.add_argument("--on", action="append", default=[], metavar="ZONE", help="Filter in this firewalld zone")
+    dns_filter.add_argument("--off", action="append", default=[], metavar="ZONE", help="Don't filter in this zone")
+    dns_filter.add_argument("--reload", action="store_true", help="Read the blocklists again")
+
+    dhcp = commands.add_parser("dhcp", help="DHCP leases").add_subparsers(dest="dhcp_command", required=True)
+    dhcp.add_parser("show", help="Show an interface's lease and received options").add_argument("interface")
+    dhcp.add_parser("renew", help="Renew the lease now").add_argument("interface")
+    dhcp.add_parser("release", help="Release the lease, leaving the interface without an address").add_argument(
+        "interface")
+    dhcp.add_parser("leases", help="Addresses alopexd's own DHCP server handed out (hotspots)").add_argument(
+        "interface", nargs="?", help="Only this interface")
+    dhcp_reserve = dhcp.add_parser("reserve", help="Always give a client the same address on a hotspot profile")
+    dhcp_reserve.add_argument("profile")
+    dhcp_reserve.add_argument("mac")
+    dhcp_reserve.add_argument("ip")
+    dhcp_unreserve = dhcp.add_parser("unreserve", help="Let a reserved client take any pool address again")
+    dhcp_unreserve.add_argument("profile")
+    dhcp_unreserve.add_argument("mac")
+
+    rename = commands.add_parser("rename", help="Give an interface a new kernel name, kept across reboots")
+    rename.add_argument("interface", help="Current name or label")
+    rename.add_argument("to", help="New name (up to 15 characters)")
+    rename.add_argument("--no-persist", action="store_true", help="Only until the next reboot; write no .link file")
+
+    sysctls = commands.add_parser("sysctls", help="Sysctls an interface's profile sets: wanted, in force and "
+                                  "before (exit 1 if one is not in force)")
+    sysctls.add_argument("interface", help="Interface name or label")
+
+    shaping = commands.add_parser("shaping", help="Queueing and congestion control").add_subparsers(
+        dest="shaping_command", required=True)
+    shaping_show = shaping.add_parser("show", help="Root qdisc and congestion control in force, and what the "
+                                      "profile wants (exit 1 if not in force)")
+    shaping_show.add_argument("interface", help="Interface name or label")
+    shaping_test = shaping.add_parser("test", help="Compare latency under load without shaping and with a qdisc "
+                                      "(the profile's unless given)")
+    shaping_test.add_argument("interface", help="Interface name or label")
+    shaping_test.add_argument("--qdisc", choices=["fq", "fq_codel", "cake"])
+    shaping_test.add_argument("--bandwidth", help="cake only, e.g. 95mbit")
+    shaping_test.add_argument("--seconds", type=float, help="Load per run (default 10)")
+    shaping_test.add_argument("--upload", action="store_true", help="Load the upstream instead")
+
+    bufferbloat = commands.add_parser("bufferbloat", help="Grade the latency a saturated link adds, down and up, "
+                                      "and suggest cake shaping (exit 1 for grade C or worse)")
+    bufferbloat.add_argument("interface", help="Interface name or label")
+    bufferbloat.add_argument("--seconds", type=float, help="Load per direction (default 10)")
+    bufferbloat.add_argument("--apply", action="store_true", help="Put the suggested cake shaping on the active "
+                             "profile")
+
+    mptcp = commands.add_parser("mptcp", help="Multipath TCP path manager").add_subparsers(
+        dest="mptcp_command", required=True)
+    mptcp.add_parser("list", help="Show endpoints, the profiles that added them, and the limits")
+    mptcp_limits = mptcp.add_parser("limits", help="Set how many subflows a connection may open and how many "
+                                    "addresses the peer may announce")
+    mptcp_limits.add_argument("subflows", type=int, help="Extra subflows per connection (0-8)")
+    mptcp_limits.add_argument("add_addr_accepted", type=int, help="Peer addresses accepted per connection (0-8)")
+
+    announce = commands.add_parser("announce", help="Send gratuitous ARP and unsolicited NA for an interface's "
+                                   "addresses so neighbours update their caches (exit 1 if some failed)")
+    announce.add_argument("interface", help="Interface name or label")
+
+    label = commands.add_parser("label", help="Friendly names and groups for interfaces").add_subparsers(
+        dest="label_command", required=True)
+    label.add_parser("list", help="Show labelled interfaces and their groups")
+    label_set = label.add_parser("set", help="Label an interface (usable wherever an interface is expected)")
+    label_set.add_argument("interface", help="Kernel name or current label")
+    label_set.add_argument("label", nargs="?", help="New label, e.g. WAN (default: keep the current one)")
+    label_set.add_argument("-g", "--group", action="append",
+                           help="Group to put it in; repeat for several (replaces its groups)")
+    label.add_parser("clear", help="Remove an interface's label and groups").add_argument(
+        "interface", help="Kernel name or label")
+
+    rfkill = commands.add_parser("rfkill", help="Radio kill switches").add_subparsers(
+        dest="rfkill_command", required=True)
+    rfkill.add_parser("list", help="Show each radio and whether it is soft or hard blocked")
+    for action in ("block", "unblock"):
+        rfkill.add_parser(action, help=f"Soft-{action} radios").add_argument(
+            "target", help="wifi, bluetooth, wwan, all, or an ID from rfkill list")
+
+    airplane = commands.add_parser("airplane", help="Show or switch airplane mode (all radios off)")
+    airplane.add_argument("state", nargs="?", choices=["on", "off"],
+                          help="off puts each radio back the way it was before")
+
+    hotspot = commands.add_parser("hotspot", help="Share a connection as a WiFi access point").add_subparsers(
+        dest="hotspot_command", required=True)
+    hotspot_start = hotspot.add_parser("start", help="Start sharing as an access point")
+    hotspot_start.add_argument("ssid")
+    hotspot_start.add_argument("-i", "--interface", help="WiFi interface (default: first found)")
+    hotspot_start.add_argument("--ask-pass", action="store_true", help="Prompt for a WPA2 password")
+    hotspot_start.add_argument("--channel", type=int, help="Channel to use (default: 6)")
+    hotspot_start.add_argument("--isolate", action="store_true", help="Keep stations from reaching each other")
+    hotspot_stop = hotspot.add_parser("stop", help="Stop sharing")
+    hotspot_stop.add_argument("-i", "--interface", help="Hotspot interface (default: the running one)")
+    hotspot_clients = hotspot.add_parser("clients", help="Show connected stations")
+    hotspot_clients.add_argument("-i", "--interface", help="Only this hotspot")
+    for action, text in (("kick", "Disconnect a station"), ("block", "Keep a station out"),
+                         ("unblock", "Let a blocked station join again"),
+                         ("allow", "Put a station on the allow list"),
+                         ("disallow", "Take a station off the allow list")):
+        hotspot_action = hotspot.add_parser(action, help=text)
+        hotspot_action.add_argument("mac", help="Station MAC address, from hotspot clients")
+        hotspot_action.add_argument("-i", "--interface", help="Hotspot interface (default: the running one)")
+    hotspot_filter = hotspot.add_parser("filter", help="Show or switch who may join: allow (only allow-listed "
+                                        "stations) or deny (everyone but blocked ones)")
+    hotspot_filter.add_argument("mode", nargs="?", choices=["allow", "deny"])
+    hotspot_filter.add_argument("-i", "--interface", help="Hotspot interface (default: the running one)")
+    hotspot_isolate = hotspot.add_parser("isolate", help="Keep stations from reaching each other, or let them")
+    hotspot_isolate.add_argument("state", choices=["on", "off"])
+    hotspot_isolate.add_argument("-i", "--interface", help="Hotspot interface (default: the running one)")
+    hotspot_limit = hotspot.add_parser("limit", help="Cap a station's rates; neither --down nor --up lifts the cap")
+    hotspot_limit.add_argument("mac", help="Station MAC address, from hotspot clients")
+    hotspot_limit.add_argument("--down", type=int, metavar="KBIT", help="Download (to the station) in kbit/s")
+    hotspot_limit.add_argument("--up", type=int, metavar="KBIT", help="Upload (from the station) in kbit/s")
+    hotspot_limit.add_argument("-i", "--interface", help="Hotspot interface (default: the running one)")
+
+    probe = commands.add_parser("probe", help="Check whether a TCP port answers (exit 1 if it doesn't)")
+    probe.add_argument("target", help="host:port, or [IPv6 address]:port")
+    probe.add_argument("-i", "--interface", help="Connect from this interface's address")
+    probe.add_argument("-w", "--wait", type=float, default=3.0, help="Seconds to wait for an answer (default: 3)")
+
+    diagnose = commands.add_parser("diagnose", help="Compare IPv4 and IPv6 to a few hosts (exit 1 on problems)")
+    diagnose.add_argument("targets", nargs="*", metavar="TARGET", help="host:port (default: from the config)")
+    diagnose.add_argument("-w", "--wait", type=float, help="Seconds to wait for each connection (default: 3)")
+
+    troubleshoot = commands.add_parser("troubleshoot", help="Why is the internet down? Check link, address, "
+                                       "gateway, DNS and beyond (exit 1 on a failure)")
+    troubleshoot.add_argument("interface", nargs="?", help="Interface to check (default: the primary one)")
+    troubleshoot.add_argument("--target", action="append", metavar="HOST:PORT",
+                              help="Address that stands for the internet (repeatable; default: from the config)")
+
+    cable_test = commands.add_parser("cable-test", help="Check a wired interface's pairs for opens and shorts "
+                                     "(drops the link briefly; exit 1 on a fault)")
+    cable_test.add_argument("interface", help="Interface name or label")
+
+    module = commands.add_parser("module", help="SFP/QSFP module vendor, type and optical diagnostics")
+    module.add_argument("interface", help="Interface name or label")
+
+    queues = commands.add_parser("queues", help="Per-queue traffic and IRQ CPU affinity of a multiqueue NIC")
+    queues.add_argument("interface", help="Interface name or label")
+    queues.add_argument("--spread", action="store_true", help="Pin the NIC's IRQs one per CPU first")
+
+    flows = commands.add_parser("flows", help="Active connections by destination and port, and hotspot NAT")
+    flows.add_argument("-n", "--top", type=int, default=10, help="Destinations and ports to list (default: 10)")
+
+    top = commands.add_parser("top", help="Systemd units by network traffic (needs accounting enabled)")
+    top.add_argument("-n", "--top", type=int, default=10, help="Units to list (default: 10)")
+
+    commands.add_parser("containers", help="Docker/Podman networks: bridges, subnets and containers")
+    virt = commands.add_parser("virt", help="libvirt networks and the guests on them")
+    virt.add_argument("-b", "--bridge", help="Only the network on this bridge, e.g. virbr0")
+
+    rates = commands.add_parser("rates", help="Per-protocol rates at 100ms resolution (needs ebpf_counters enabled)")
+    rates.add_argument("-i", "--interface", help="Only this interface, with its recent samples in --json output")
+
+    bt = commands.add_parser("bluetooth", help="Classic and Low Energy Bluetooth devices").add_subparsers(
+        dest="bluetooth_command", required=True)
+    bt_list = bt.add_parser("list", help="Known devices: connected, then paired, then nearby by signal")
+    bt_list.add_argument("--scan", action="store_true", help="Discover nearby devices first (10 seconds)")
+    bt_info = bt.add_parser("info", help="One device's type, services and battery")
+    bt_info.add_argument("mac")
+    bt_pair = bt.add_parser("pair", help="Pair with a device")
+    bt_pair.add_argument("mac")
+    bt_connect = bt.add_parser("connect", help="Connect a paired device, or an LE peripheral without pairing")
+    bt_connect.add_argument("mac")
+    bt_send = bt.add_parser("send", help="Send a file to a paired phone over OBEX")
+    bt_send.add_argument("mac")
+    bt_send.add_argument("file", type=Path)
+    bt_alias = bt.add_parser("alias", help="Name a device locally; without a name, use its own again")
+    bt_alias.add_argument("mac")
+    bt_alias.add_argument("alias", nargs="?")
+
+    lan = commands.add_parser("lan", help="Devices on the local network").add_subparsers(
+        dest="lan_command", required=True)
+    lan_scan = lan.add_parser("scan", help="Sweep the subnet for devices and their names")
+    lan_scan.add_argument("-i", "--interface", help="Interface whose subnet to sweep (default: the primary one)")
+
+    commands.add_parser("quota", help="Data used against each interface's quota this day, week or month")
+    notify = commands.add_parser("notify", help="Where alerts go: webhooks, ntfy and mail").add_subparsers(
+        dest="notify_command", required=True)
+    notify.add_parser("list", help="Show each sink, what it is for, its limits and its delivery counters")
+    notify.add_parser("test", help="Send a test notification now, past quiet hours and rate limits (exit 1 if "
+                                   "one fails)").add_argument("sink", nargs="?", help="Only this sink")
+
+    bandwidth = commands.add_parser("bandwidth", help="Per-minute traffic history").add_subparsers(
+        dest="bandwidth_command", required=True)
+    bandwidth_export = bandwidth.add_parser("export", help="Dump an interface's history as CSV or JSON")
+    bandwidth_export.add_argument("interface")
+    bandwidth_export.add_argument("--since", help="Start: 24h, 7d, 2026-10-01 or 2026-10-01T08:00 (default: all)")
+    bandwidth_export.add_argument("--until", help="End, in the same forms (default: now)")
+    bandwidth_export.add_argument("--format", choices=["csv", "json"],
+                                  help="Default: from the -o extension, else csv")
+    bandwidth_export.add_argument("-o", "--output", type=Path, help="Write to this file instead of stdout")
+
+    sla = commands.add_parser("sla", help="Loss, latency and jitter probes").add_subparsers(
+        dest="sla_command", required=True)
+    sla.add_parser("list", help="Show each probe's state and its last hour")
+    sla.add_parser("history", help="Show every round of a probe's last hour").add_argument("probe")
+
+    failover = commands.add_parser("failover", help="Backup links and when to go back to the primary").add_subparsers(
+        dest="failover_command", required=True)
+    failover.add_parser("list", help="Show each group's active link and its last decision")
+    failover_use = failover.add_parser("use", help="Switch a group to its primary or backup by hand")
+    failover_use.add_argument("group")
+    failover_use.add_argument("link", choices=["primary", "backup"])
+
+    session = commands.add_parser("session", help="Traffic since each connection came up").add_subparsers(
+        dest="session_command", required=True)
+    session.add_parser("list", help="Show per-connection totals")
+    session_reset = session.add_parser("reset", help="Start the totals from zero")
+    session_reset.add_argument("interface", nargs="?", help="Only this interface (default: all)")
+
+    import_parser = commands.add_parser("import", help="Import connections from another network stack")
+    import_parser.add_argument("source", choices=["networkmanager", "networkd", "netplan"], help="Where the connections come from")
+    import_parser.add_argument("--path", type=Path, help="Read from this directory instead of the system one")
+    import_parser.add_argument("--dry-run", action="store_true", help="Report what would be imported")
+    import_parser.add_argument("--replace", action="store_true", help="Overwrite profiles with the same name")
+
+    export_parser = commands.add_parser("export", help="Render profiles as systemd-networkd files or netplan YAML")
+    export_parser.add_argument("target", choices=["networkd", "netplan"], help="Configuration to write")
+    export_parser.add_argument("-o", "--output", type=Path,
+                               help="Write the files into this directory (default: print them)")
+    export_parser.add_argument("-p", "--profile", action="append", help="Only this profile (repeatable)")
+    export_parser.add_argument("--secrets", action="store_true", help="Include WiFi passwords (root only)")
+
+    backup = commands.add_parser("backup", help="Save all profiles and secrets to an encrypted archive")
+    backup.add_argument("file", type=Path)
+    restore = commands.add_parser("restore", help="Restore profiles from a backup archive")
+    restore.add_argument("file", type=Path)
+    restore.add_argument("--replace", action="store_true", help="Overwrite profiles with the same name")
+
+    snapshot = commands.add_parser("snapshot", help="Write a diagnostic snapshot (JSON, or .tar.gz) for a support ticket")
+    snapshot.add_argument("file", type=Path)
+    snapshot.add_argument("--events", type=int, default=200, help="Recent events to include")
+    snapshot.add_argument("--samples", type=int, help="Recent metrics samples to include (default: all kept)")
+
+    nm = commands.add_parser("nm", help="Run an nmcli command line (device status, con up, dev wifi connect...)")
+    nm.add_argument("nmcli_args", nargs=argparse.REMAINDER, help="nmcli arguments")
+
+    return parser
+
+def main() -> int:
+    parser = build_parser()
+    argv = sys.argv[1:]
+    args, unknown = parser.parse_known_args(argv)
+    if args.command == "nm":
+        # Everything after "nm" belongs to nmcli, options included
+        args.nmcli_args = argv[argv.index("nm") + 1:]
+    elif unknown:
+        parser.error(f"unrecognized arguments: {' '.join(unknown)}")
+    if args.unit_style not in UNIT_STYLES:
+        parser.error(f"ALOPEX_UNITS must be one of {', '.join(UNIT_STYLES)}")
+    host = None
+    if args.host or args.command == "hosts":
+        try:
+            fleet = load_fleet()
+        except ValueError as e:
+            parser.error(str(e))
+        host = find_host(fleet, args.host) if args.host else None
+        if args.host and not host:
+            parser.error(f"no host {args.host} in {FLEET_FILE} (have: {', '.join(h.name for h in fleet)})")
+    cli = AlopexCli(args.socket, args.timeout, args.quiet, args.json, UnitPreference.parse(args.unit_style), host)
+
+    handlers = {
+        "list": cli.list,
+        "rename": cli.rename,
+        "announce": cli.announce,
+        "sysctls": cli.sysctls,
+        "mptcp": {"list": cli.mptcp_list, "limits": cli.mptcp_limits},
+        "shaping": {"show": cli.shaping_show, "test": cli.shaping_test},
+        "bufferbloat": cli.bufferbloat,
+        "label": {"list": cli.label_list, "set": cli.label_set, "clear": cli.label_clear},
+        "status": cli.status,
+        "whoami": cli.whoami,
+        "hosts": cli.hosts,
+        "relay": cli.relay,
+        "events": cli.events,
+        "undo": cli.undo,
+        "connect": cli.connect,
+        "disconnect": cli.disconnect,
+        "metrics": cli.metrics,
+        "import": cli.import_connections,
+        "export": cli.export_connections,
+        "backup": cli.backup,
+        "restore": cli.restore,
+        "snapshot": cli.snapshot,
+        "nm": cli.nm,
+        "wifi": {"scan": cli.wifi_scan, "channels": cli.wifi_channels, "country": cli.wifi_country,
+                 "powersave": cli.wifi_powersave, "alerts": cli.wifi_alerts, "roams": cli.wifi_roams,
+                 "connect": cli.wifi_connect},
+        "vpn": {"list": cli.vpn_list, "up": cli.vpn_up, "down": cli.vpn_down},
+        "dns": {"show": cli.dns_show, "set": cli.dns_set, "cache": cli.dns_cache, "flush": cli.dns_flush,
+                "filter": cli.dns_filter},
+        "dhcp": {"show": cli.dhcp_show, "renew": cli.dhcp_renew, "release": cli.dhcp_release,
+                 "leases": cli.dhcp_leases, "reserve": cli.dhcp_reserve, "unreserve": cli.dhcp_reserve},
+        "rfkill": {"list": cli.rfkill_list, "block": cli.rfkill_block, "unblock": cli.rfkill_block},
+        "airplane": cli.airplane,
+        "hotspot": {"start": cli.hotspot_start, "stop": cli.hotspot_stop, "clients": cli.hotspot_clients,
+                    "kick": cli.hotspot_kick, "block": cli.hotspot_block, "unblock": cli.hotspot_block,
+                    "allow": cli.hotspot_allow, "disallow": cli.hotspot_allow, "filter": cli.hotspot_filter,
+                    "isolate": cli.hotspot_isolate,
+                    "limit": cli.hotspot_limit},
+        "probe": cli.probe,
+        "diagnose": cli.diagnose,
+        "troubleshoot": cli.troubleshoot,
+        "cable-test": cli.cable_test,
+        "module": cli.module,
+        "queues": cli.queues,
+        "flows": cli.flows,
+        "top": cli.top,
+        "rates": cli.rates,
+        "containers": cli.containers,
+        "virt": cli.virt,
+        "bluetooth": {"list": cli.bluetooth_list, "info": cli.bluetooth_info, "pair": cli.bluetooth_connect,
+                      "connect": cli.bluetooth_connect, "alias": cli.bluetooth_alias, "send": cli.bluetooth_send},
+        "lan": {"scan": cli.lan_scan},
+        "bandwidth": {"export": cli.bandwidth_export},
+        "quota": cli.quota,
+        "notify": {"list": cli.notify_list, "test": cli.notify_test},
+        "sla": {"list": cli.sla_list, "history": cli.sla_history},
+        "failover": {"list": cli.failover_list, "use": cli.failover_use},
+        "session": {"list": cli.session_list, "reset": cli.session_reset},
+        "tunnel": {"list": cli.tunnel_list, "add": cli.tunnel_add},
+        "profile": {"list": cli.profile_list, "units": cli.profile_units, "sysctl": cli.profile_sysctl,
+                    "mptcp": cli.profile_mptcp, "qdisc": cli.profile_qdisc, "congestion": cli.profile_congestion,
+                    "metered": cli.profile_metered,
+                    "critical": cli.profile_critical, "metric": cli.profile_metric,
+                    "garp": cli.profile_arp, "proxy-arp": cli.profile_arp,
+                    "retries": cli.profile_retries, "retry": cli.profile_retry,
+                    "certs": cli.profile_certs},
+        "cert": {"list": cli.cert_list, "import": cli.cert_import, "remove": cli.cert_remove},
+        "location": {"list": cli.location_list, "set": cli.location_set, "remove": cli.location_remove,
+                     "switch": cli.location_switch, "auto": cli.location_auto}
+    }
+    handler = handlers[args.command]
+    if isinstance(handler, dict):
+        handler = handler[getattr(args, f"{args.command}_command")]
+
+    try:
+        return handler(args)
+    except IpcError as e:
+        if args.json:
+            print_json({"error": e.to_reply()})
+        else:
+            print(f"Error: {e}", file=sys.stderr)
+        return EXIT_CODES.get(e.code, 1)
+    except KeyboardInterrupt:
+        return 130
+
+if __name__ == "__main__":
+    sys.exit(main())
@@ -0,0 +1,129 @@
+#!/usr/bin/env python3
+"""
+ALOPEX Desktop Notifier
+Follows alopexd events and raises freedesktop notifications when a connection
+or VPN drops on its own, so it gets noticed with no ALOPEX window open
+"""
+
+import os
+import sys
+import time
+import shutil
+import argparse
+import subprocess
+from pathlib import Path
+from typing import Dict, Optional, Tuple
+
+def _configure_sys_path():
+    """Find the ALOPEX network package (installed or in the source tree)"""
+    candidates = [
+        Path(os.getenv("ALOPEX_PYTHON_PATH", "")),
+        Path("/usr/lib/alopex"),
+        Path("/usr/local/lib/alopex"),
+        Path(__file__).parent.parent / "alopex-qt"
+    ]
+    for candidate in candidates:
+        if (candidate / "network").exists():
+            sys.path.insert(0, str(candidate))
+            return
+
+    print("alopex-notify: ALOPEX core modules not found (set ALOPEX_PYTHON_PATH)", file=sys.stderr)
+    sys.exit(1)
+
+_configure_sys_path()
+
+from network.ipc import IpcClient, IpcError, SOCKET_PATH
+
+RETRY_SECONDS = 5
+# At most one notification per interface in this window, so a flapping link doesn't bury the desktop
+QUIET_SECONDS = 30
+
+# A cancelled connect is the user's own doing; deliberate disconnects pass through
+# "deactivating" and never match below
+EXPECTED_REASONS = ("cancelled",)
+
+Notification = Tuple[str, str, str]  # summary, body, urgency
+
+def describe(event: Dict, profile_types: Dict[str, str]) -> Optional[Notification]:
+    """The notification an event deserves, or None"""
+    if event.get("event") == "WifiSecurityAlert":
+        summary = "Possible rogue access point" if event["alert"] != "deauth_burst" else "WiFi deauthentication attack"
+        return summary, f"{event['interface']}: {event['message']}", "critical"
+    if event.get("event") == "LinkSpeedDegraded":
+        return f"{event['interface']} is running slow", event["message"], "normal"
+    if event.get("event") != "InterfaceStateChanged":
+        return None
+
+    state, previous = event["state"], event.get("previous")
+    reason = event.get("reason") or ""
+    if state not in ("failed", "disconnected") or previous not in ("configured", "acquiring"):
+        return None
+    if any(expected in reason for expected in EXPECTED_REASONS):
+        return None
+
+    interface, profile = event["interface"], event.get("profile")
+    vpn = profile_types.get(profile) == "vpn"
+    if previous == "acquiring":
+        summary = f"{'VPN' if vpn else 'Connection'} {profile or interface} failed to connect"
+        return summary, reason, "normal"
+    summary = f"VPN {profile or interface} dropped" if vpn else f"{interface} disconnected"
+    body = f"{profile}: {reason}" if profile and not vpn else reason
+    return summary, body, "critical"
+
+def send(notification: Notification, print_only: bool):
+    """Raise a desktop notification (or with --print, write it to stdout)"""
+    summary, body, urgency = notification
+    if print_only or not shutil.which("notify-send"):
+        print(f"[{urgency}] {summary}" + (f": {body}" if body else ""), flush=True)
+        return
+    subprocess.run(["notify-send", "--app-name=ALOPEX", f"--urgency={urgency}",
+                    "--icon=network-error", summary, body], check=False)
+
+def follow(client: IpcClient, print_only: bool):
+    """Notify for events until the daemon goes away"""
+    profile_types = {p["name"]: p["connection_type"] for p in client.request("ListProfiles")}
+    last_sent: Dict[str, float] = {}
+    for event in client.subscribe(["InterfaceStateChanged", "ProfileUpdated", "WifiSecurityAlert",
+                                   "LinkSpeedDegraded"]):
+        if event["event"] == "ProfileUpdated":
+            profile_types = {p["name"]: p["connection_type"] for p in client.request("ListProfiles")}
+            continue
+
+        notification = describe(event, profile_types)
+        if notification is None:
+            continue
+        interface = event["interface"]
+        if time.monotonic() - last_sent.get(interface, -QUIET_SECONDS) < QUIET_SECONDS:
+            continue
+        last_sent[interface] = time.monotonic()
+        send(notification, print_only)
+
+def main() -> int:
+    parser = argparse.ArgumentParser(prog="alopex-notify", description=__doc__.strip().splitlines()[0])
+    parser.add_argument("--socket", type=Path, default=SOCKET_PATH, help="alopexd control socket")
+    parser.add_argument("--print", dest="print_only", action="store_true",
+                        help="Write notifications to stdout instead of the desktop")
+    parser.add_argument("--once", action="store_true", help="Exit when the daemon goes away instead of waiting")
+    args = parser.parse_args()
+
+    client = IpcClient(args.socket)
+    waiting = False
+    while True:
+        try:
+            follow(client, args.print_only)
+            waiting = False
+        except IpcError as e:
+            if args.once:
+                print(f"alopex-notify: {e}", file=sys.stderr)
+                return 3
+            if not waiting:
+                print(f"alopex-notify: {e}; retrying every {RETRY_SECONDS}s", file=sys.stderr)
+                waiting = True
+        except KeyboardInterrupt:
+            return 0
+        if args.once:
+            return 0
+        time.sleep(RETRY_SECONDS)
+
+if __name__ == "__main__":
+    sys.exit(main())
@@ -7,32 +7,99 @@ System-level network management service
 import sys
 import os
 import signal
+import argparse
 import asyncio
 import logging
 import json
 import time
+import base64
+import socket
+import tempfile
+import ipaddress
+from copy import deepcopy
 from pathlib import Path
-from typing import Dict, List, Optional
+from typing import Any, Dict, List, Optional
 from dataclasses import asdict
 
 # Add parent directory to path for imports
 sys.path.insert(0, str(Path(__file__).parent.parent / "alopex-qt"))
 
-from network.discovery import NetworkDiscovery, NetworkInterface
+from network.discovery import InterfaceType, NetworkDiscovery, NetworkInterface
 from network.system_integration import NetworkControl
-from network.wifi import WiFiManager  
+from network.wifi import WiFiManager, COUNTRY_CODE
 from network.vpn import VpnManager
-from network.connection_manager import ConnectionManager
+from network.connection_manager import ConnectionManager, ConnectionProfile
+from network.backends import load_backends
+from network.mock import FakeNetwork, MockDiscovery, mock_backends
+from network.ipc import IpcServer, IpcClient, IpcError, ErrorCode, REMOTE_PORT, SOCKET_PATH, server_tls_context
+from network.events import (
+    DEFAULT_EXCLUDE as EVENT_LOG_EXCLUDE, DEFAULT_RETENTION_DAYS as EVENT_LOG_RETENTION, EventBus, EventLog,
+    matches as event_matches, parse_filter as parse_event_filter
+)
+from network.jobs import JobManager
+from network.importers import IMPORTERS
+from network.exporters import EXPORTERS
+from network.locations import Location, LocationManager
+from network.services import ProfileServices
+from network.desktop import DesktopBridge
+from network.sessions import SessionCounters
+from network.carrier import CarrierTracker
+from network.dhcp_server import dhcp_range
+from network.dns_health import DnsHealthProber
+from network.dns_forwarder import DEFAULT_CACHE_SIZE, DnsForwarder, StubHost
+from network.dns_filter import DnsFilter
+from network.rfkill import AirplaneMode, RfkillMonitor, RADIO_TYPES, TYPE_ALIASES
+from network.certs import CertError, CertExpiryMonitor
+from network.link_speed import LinkSpeedMonitor
+from network.hotspot import MAX_RATE, HotspotMonitor, hotspot_network, normalize_mac
+from network.lan_scan import annotate
+from network.change_history import ChangeHistory
+from network.boot import BootCritical
+from network.retry import RetryTracker
+from network.failover import FailoverController
+from network.route_metrics import MAX_METRIC
+from network import (
+    backup, bluetooth, cable_test, certs, connectivity, link_names, load_test, mptcp, nic_queues, obex, oui,
+    port_probe, reconcile, shaping, sysctls, troubleshoot, tunnels
+)
+from network.diagnostics import MetricsHistory, redact
+from network.secret import Secret, revealed
+from network.bandwidth import DEFAULT_RETENTION_DAYS, BandwidthHistory
+from network.influx import DEFAULT_INTERVAL as INFLUX_INTERVAL, InfluxSink
+from network.remote_syslog import SyslogForwarder
+from network.quota import DEFAULT_INTERVAL as QUOTA_INTERVAL, QuotaMonitor
+from network.notifications import DEFAULT_RATE_LIMIT as NOTIFY_RATE_LIMIT, Notifier, load_sinks
+from network.access import AccessPolicy
+from network.relay import DEFAULT_INTERVAL as RELAY_INTERVAL, DEFAULT_TIMEOUT as RELAY_TIMEOUT, Relay
+from network.snmp import DEFAULT_PORT as SNMP_PORT, InterfaceMib, SnmpAgent, load_engine
+from network.sla import SlaMonitor
+from network.conntrack import DEFAULT_TOP as FLOWS_TOP, FlowMonitor
+from network.unit_traffic import DEFAULT_INTERVAL as ACCOUNTING_INTERVAL, DEFAULT_TOP as TALKERS_TOP, UnitTraffic
+from network.fast_counters import (
+    DEFAULT_INTERVAL_MS as COUNTERS_INTERVAL_MS, DEFAULT_OBJECT as COUNTERS_OBJECT, FastCounters
+)
+from network.containers import (
+    DEFAULT_INTERVAL as CONTAINERS_INTERVAL, DEFAULT_SOCKETS as CONTAINER_SOCKETS, ContainerNetworks
+)
+from network.libvirt import DEFAULT_INTERVAL as LIBVIRT_INTERVAL, DEFAULT_URI as LIBVIRT_URI, VirtualNetworks
+from network.wifi_watch import (
+    DEFAULT_BURST as DEAUTH_BURST, DEFAULT_INTERVAL as WIFI_WATCH_INTERVAL, DEFAULT_WINDOW as DEAUTH_WINDOW, WifiWatch
+)
+from network.roaming import DEFAULT_INTERVAL as ROAMING_INTERVAL, MAX_ENTRIES as ROAMING_ENTRIES, RoamingTimeline
+from network.labels import InterfaceLabels, LabelError
+from network.state_machine import LinkState, StateTransitionError
+from network.logs import configure_logging, log_context, set_log_level, get_log_levels, get_logs
 
 class AlopexDaemon:
     """Enterprise network management daemon"""
     
-    def __init__(self):
-        self.discovery = NetworkDiscovery()
-        self.connection_manager = ConnectionManager()
+    def __init__(self, mock_network: Optional[FakeNetwork] = None, state_dir: Optional[Path] = None,
+                 socket_path: Path = SOCKET_PATH):
+        self.mock_network = mock_network
         self.running = False
-        self.config_path = Path("/etc/alopex")
-        self.state_path = Path("/var/lib/alopex")
+        self.started_at = time.time()
+        self.config_path = state_dir or Path("/etc/alopex")
+        self.state_path = state_dir or Path("/var/lib/alopex")
         
         # Enterprise configuration
         self.enterprise_config = self._load_enterprise_config()
@@ -40,24 +107,274 @@ class AlopexDaemon:
         # Setup logging
         self._setup_logging()
         
+        if mock_network:
+            # Fake links, APs and tunnels; nothing on the host is touched
+            self.discovery = MockDiscovery(mock_network)
+            self.backends = mock_backends(mock_network)
+        else:
+            # Link-type backends selected by the "backends" config section
+            self.discovery = NetworkDiscovery()
+            self.backends = load_backends(self.enterprise_config.get("backends", {}))
+        
+        # Interface state changes and job progress are published as events
+        self.events = EventBus()
+        event_log_config = self.enterprise_config.get("event_log", {})
+        if event_log_config.get("enabled", True):
+            # Kept on disk for GetEvents, so what happened survives a restart or a crash
+            self.events.log = EventLog(
+                self.state_path, event_log_config.get("retention_days", EVENT_LOG_RETENTION),
+                event_log_config.get("exclude", EVENT_LOG_EXCLUDE)
+            )
+        self.jobs = JobManager(self.events)
+        
+        self.connection_manager = ConnectionManager(
+            backends=self.backends, discovery=self.discovery, config_path=self.state_path,
+            events=self.events
+        )
+        self.connection_manager.set_unmanaged_patterns(
+            self.enterprise_config.get("unmanaged_interfaces", [])
+        )
+        self.connection_manager.retry = RetryTracker.from_config(self.enterprise_config.get("auto_connect_retry"))
+        if mock_network:
+            self._seed_mock_profiles()
+        
+        # Named sets of profiles and host settings, switched by hand or by detection
+        self.locations = LocationManager(self.connection_manager, self.state_path)
+        
+        # systemd units that run only while their profile is active
+        self.profile_services = ProfileServices(self.connection_manager)
+        
+        # Metered state for desktops; never claim the real system bus in mock mode
+        self.desktop = DesktopBridge(self.connection_manager, export=mock_network is None)
+        
+        # Bytes moved since each connection came up
+        self.sessions = SessionCounters(self.connection_manager)
+        
+        # Per-interface uptime, counted from carrier; the mock has no netlink to follow
+        self.carrier = CarrierTracker(self.discovery, self.events, monitor=mock_network is None)
+        
+        # Caching stub resolver for hosts without systemd-resolved; resolv.conf points at it while it runs
+        forwarder_config = self.enterprise_config.get("dns_forwarder", {})
+        self.dns_forwarder = None
+        if forwarder_config.get("enabled"):
+            self.dns_forwarder = DnsForwarder(
+                self._dns_upstreams, forwarder_config.get("listen"),
+                forwarder_config.get("cache_size", DEFAULT_CACHE_SIZE), self.events
+            )
+            self.backends.host = StubHost(self.backends.host, self.dns_forwarder)
+            self.connection_manager.dns_forwarder = self.dns_forwarder
+            filter_config = forwarder_config.get("filter", {})
+            if filter_config.get("enabled"):
+                self.dns_forwarder.filter = DnsFilter(
+                    filter_config.get("lists", []), self.state_path, filter_config.get("zones"),
+                    filter_config.get("default", True), filter_config.get("allow"), self._dns_client_zone, self.events
+                )
+                self.dns_forwarder.filter.load()
+        
+        # Latency and failures of the resolvers in use
+        self.dns_health = DnsHealthProber(self.backends.host, self.discovery._get_dns_servers, self.events)
+        
+        # Loss, latency and jitter of chosen targets against their thresholds
+        sla_probes = self.enterprise_config.get("sla", {}).get("probes", [])
+        self.sla = SlaMonitor(self.backends.host, sla_probes, self.events)
+        
+        # Default route through a backup link while the primary is down or its SLA probe in breach
+        failover_config = self.enterprise_config.get("failover", {})
+        self.failover = FailoverController(
+            failover_config.get("groups", []), self._failover_links,
+            lambda probe: self.sla.probes[probe].state if probe in self.sla.probes else None,
+            self.backends.ethernet.apply_change, self.events, failover_config.get("interval", 2),
+            self.connection_manager.announce_addresses
+        )
+        
+        # Radio kill switches, so a flipped hardware switch shows up as such
+        self.rfkill = RfkillMonitor(self.backends.rfkill, self.events)
+        self.airplane = AirplaneMode(self.backends.rfkill, self.state_path, self.events)
+        
+        # Certificates profiles use, and warnings before they run out
+        self.cert_expiry = CertExpiryMonitor(self.connection_manager.certs, self.events)
+        
+        # Wired links that negotiated below what the NIC can do, usually a bad cable
+        self.link_speed = LinkSpeedMonitor(self.discovery, self.backends.host, self.events)
+        
+        # Stations joining and leaving the hotspots we run
+        self.hotspot = HotspotMonitor(self.backends.hotspot, self.connection_manager.active_hotspots,
+                                      self.backends.dhcp_server.leases, self.events)
+        
+        # Active connections from conntrack, with NAT on the networks we share
+        self.flows = FlowMonitor(self.backends.host, self._shared_networks)
+        
+        # Traffic per systemd unit; off unless asked for, since it adds firewall rules
+        accounting_config = self.enterprise_config.get("accounting", {})
+        self.unit_traffic = None
+        if accounting_config.get("enabled"):
+            self.unit_traffic = UnitTraffic(self.backends.accounting,
+                                            accounting_config.get("interval", ACCOUNTING_INTERVAL))
+        
+        # 100ms rates from eBPF counters on tc hooks; needs the object built with WITH_EBPF=1
+        counters_config = self.enterprise_config.get("ebpf_counters", {})
+        self.fast_counters = None
+        if counters_config.get("enabled"):
+            self.fast_counters = FastCounters(self.backends.counters, self._counted_interfaces,
+                                              counters_config.get("object", COUNTERS_OBJECT),
+                                              counters_config.get("interval_ms", COUNTERS_INTERVAL_MS))
+        
+        # Docker/Podman bridges and their veths, grouped by network and left alone unless asked
+        containers_config = self.enterprise_config.get("containers", {})
+        self.containers = None
+        if containers_config.get("enabled", True):
+            self.containers = ContainerNetworks(
+                self.backends.containers, self._interface_names, containers_config.get("sockets", CONTAINER_SOCKETS),
+                self.events,
+                self.connection_manager.set_implicit_unmanaged if containers_config.get("unmanaged", True) else None,
+                containers_config.get("interval", CONTAINERS_INTERVAL)
+            )
+        
+        # libvirt networks behind virbr bridges, with their guests' addresses
+        libvirt_config = self.enterprise_config.get("libvirt", {})
+        self.virtual_networks = None
+        if libvirt_config.get("enabled", True):
+            self.virtual_networks = VirtualNetworks(
+                self.backends.libvirt, self._interface_names, libvirt_config.get("uri", LIBVIRT_URI), self.events,
+                libvirt_config.get("interval", LIBVIRT_INTERVAL)
+            )
+        
+        # Evil-twin access points and deauthentication bursts, from scans already made and nl80211 events
+        wifi_watch_config = self.enterprise_config.get("wifi_watch", {})
+        self.wifi_watch = None
+        if wifi_watch_config.get("enabled", True):
+            self.wifi_watch = WifiWatch(
+                self.backends.wifi, self.backends.wifi.list_interfaces, self.state_path, self.events,
+                wifi_watch_config.get("interval", WIFI_WATCH_INTERVAL), wifi_watch_config.get("burst", DEAUTH_BURST),
+                wifi_watch_config.get("window", DEAUTH_WINDOW)
+            )
+        
+        # Associations, roams and disconnects per WiFi interface, with signal and time on each access point
+        roaming_config = self.enterprise_config.get("roaming", {})
+        self.roaming = None
+        if roaming_config.get("enabled", True):
+            self.roaming = RoamingTimeline(
+                self.backends.wifi, self.backends.wifi.list_interfaces, self.state_path, self.events,
+                roaming_config.get("interval", ROAMING_INTERVAL), roaming_config.get("entries", ROAMING_ENTRIES)
+            )
+            if self.wifi_watch:
+                # One nl80211 reader feeds both
+                self.wifi_watch.listeners.append(self.roaming.event)
+        
+        # Recent traffic counters for diagnostic snapshots
+        self.metrics_history = MetricsHistory()
+        
+        # Per-minute traffic kept for weeks, for exports
+        self.bandwidth_history = BandwidthHistory(
+            self.state_path / "bandwidth",
+            self.enterprise_config.get("bandwidth_history", {}).get("retention_days", DEFAULT_RETENTION_DAYS)
+        )
+        
+        # Counters pushed to InfluxDB or Telegraf, for push-based monitoring
+        influx_config = self.enterprise_config.get("influx", {})
+        self.influx = None
+        if influx_config.get("enabled"):
+            try:
+                self.influx = InfluxSink(
+                    influx_config.get("url", ""), self.discovery.discover_interfaces,
+                    influx_config.get("interval", INFLUX_INTERVAL), influx_config.get("token"),
+                    tags=influx_config.get("tags")
+                )
+            except ValueError as e:
+                self.logger.error(f"InfluxDB sink disabled: {e}")
+        
+        # Data caps per interface, counted from the bandwidth history
+        quota_config = self.enterprise_config.get("quota", {})
+        self.quota = None
+        if quota_config.get("limits"):
+            try:
+                self.quota = QuotaMonitor(self.bandwidth_history, quota_config["limits"], self.state_path,
+                                          self.events, quota_config.get("interval", QUOTA_INTERVAL))
+            except ValueError as e:
+                self.logger.error(f"Data quotas disabled: {e}")
+        
+        # Alerts and chosen events sent to webhooks (Slack, Matrix and the like), ntfy topics and mail
+        notifications_config = self.enterprise_config.get("notifications", {})
+        self.notifier = None
+        if notifications_config.get("enabled"):
+            try:
+                self.notifier = Notifier(self.events, load_sinks(notifications_config), self._profile_connection_type,
+                                         notifications_config.get("host"))
+            except ValueError as e:
+                self.logger.error(f"Notifications disabled: {e}")
+        
+        # Status and interfaces of the daemons downstream of this one, for fleet views that reach only the relay
+        relay_config = self.enterprise_config.get("relay", {})
+        self.relay = None
+        if relay_config.get("enabled"):
+            try:
+                self.relay = Relay(relay_config, self._ipc_get_status, self._ipc_get_interfaces, self.events)
+            except ValueError as e:
+                self.logger.error(f"Relay disabled: {e}")
+        
+        # Events and audit records for a fleet's syslog collector
+        syslog_config = self.enterprise_config.get("syslog", {})
+        self.syslog = None
+        if syslog_config.get("enabled"):
+            try:
+                self.syslog = SyslogForwarder(
+                    syslog_config.get("url", ""), self.events, syslog_config.get("facility", "daemon"),
+                    syslog_config.get("events"), syslog_config.get("ca_file"),
+                    syslog_config.get("cert_file"), syslog_config.get("key_file")
+                )
+            except (ValueError, OSError) as e:
+                self.logger.error(f"Syslog forwarding disabled: {e}")
+        audit = self.syslog.audit if self.syslog and syslog_config.get("audit", True) else None
+        
+        # Read-only IF-MIB for NMS tools that only speak SNMP
+        snmp_config = self.enterprise_config.get("snmp", {})
+        self.snmp = None
+        if snmp_config.get("enabled"):
+            mib = InterfaceMib(self.discovery.discover_interfaces, self.started_at, snmp_config.get("contact", ""),
+                               snmp_config.get("location", ""), kernel_indexes=mock_network is None)
+            engine_id, boots = load_engine(self.state_path / "snmp_engine.json")
+            self.snmp = SnmpAgent(mib, snmp_config.get("communities", []), snmp_config.get("users", []),
+                                  engine_id, boots)
+        
+        # Saved connections survive restarts
+        self.saved_connections = self._load_saved_connections()
+        
+        # Friendly names and groups for interfaces, accepted in place of kernel names
+        self.labels = InterfaceLabels(self.state_path)
+        self.bluetooth_aliases = bluetooth.BluetoothAliases(self.state_path)
+        
+        # Recent profile, DNS and location changes with what they replaced, for Undo
+        self.changes = ChangeHistory()
+        
+        # Boot-critical profiles: connected first and retried until up, then network-online.target may go
+        boot_config = self.enterprise_config.get("boot_critical", {})
+        self.boot = BootCritical(
+            self._connect_boot_critical,
+            self._boot_critical_up,
+            lambda names: self.events.publish("BootCriticalOnline", profiles=names),
+            boot_config.get("retry_initial", 1), boot_config.get("retry_max", 30)
+        )
+        
+        # Roles for control requests; off, every caller the socket lets in may do anything
+        access_config = self.enterprise_config.get("access", {})
+        self.access = None
+        if access_config.get("enabled"):
+            try:
+                self.access = AccessPolicy(access_config)
+            except ValueError as e:
+                # Failing open would hand every local user admin; only root gets in until the config is fixed
+                self.logger.error(f"Access control config is invalid, allowing root only: {e}")
+                self.access = AccessPolicy({"default_role": None})
+        
+        # Control socket for the GUI and CLI tools
+        self.ipc = IpcServer(socket_path, self.events, audit, self.labels.resolve, self.access)
+        self._register_ipc_handlers()
+        
     def _setup_logging(self):
-        """Configure enterprise-grade logging"""
-        log_format = "%(asctime)s [%(levelname)s] %(name)s: %(message)s"
-        
-        # Console logging
-        console_handler = logging.StreamHandler()
-        console_handler.setFormatter(logging.Formatter(log_format))
-        
-        # File logging
-        log_file = Path("/var/log/alopex/alopexd.log")
-        log_file.parent.mkdir(parents=True, exist_ok=True)
-        file_handler = logging.FileHandler(log_file)
-        file_handler.setFormatter(logging.Formatter(log_format))
-        
-        # Configure root logger
-        logging.basicConfig(
-            level=logging.INFO,
-            handlers=[console_handler, file_handler]
+        """Configure enterprise-grade logging from the [logging] config section"""
+        configure_logging(
+            self.enterprise_config.get("logging", {}),
+            log_file=None if self.mock_network else Path("/var/log/alopex/alopexd.log")
         )
         
         self.logger = logging.getLogger("alopexd")
@@ -70,12 +387,208 @@ class AlopexDaemon:
                 with open(config_file) as f:
                     return json.load(f)
             except Exception as e:
-                self.logger.warning(f"Failed to load enterprise config: {e}")
+                # Runs before logging is configured (logging settings live here)
+                logging.getLogger("alopexd").warning(f"Failed to load enterprise config: {e}")
         
         # Default enterprise configuration
         return {
             "auto_connect": True,
             "preferred_networks": [],
+            "unmanaged_interfaces": [],
+            "timeouts": {
+                "connect": 45,
+                "vpn": 60,
+                "scan": 20,
+                "location": 120
+            },
+            "wifi": {
+                # ISO 3166 country code set through nl80211 at startup; None leaves the kernel's
+                "regulatory_domain": None
+            },
+            "dns_forwarder": {
+                "enabled": False,
+                "listen": ["127.0.0.53"],
+                "cache_size": 1000,
+                "filter": {
+                    # Hosts-format blocklists; listed names get NXDOMAIN. zones switches filtering per
+                    # firewalld zone ({"home": false}); zones not named follow default
+                    "enabled": False,
+                    "lists": [],
+                    "allow": [],
+                    "zones": {},
+                    "default": True
+                }
+            },
+            "bandwidth_history": {
+                "retention_days": 30
+            },
+            "influx": {
+                "enabled": False,
+                # InfluxDB v1 /write, v2 /api/v2/write or Telegraf http_listener URL, or udp://host:port
+                "url": "http://localhost:8086/api/v2/write?org=home&bucket=alopex",
+                "token": None,
+                "interval": 10
+            },
+            "quota": {
+                # {interface, limit (e.g. "50GB"), period (day, week or month)}; QuotaExceeded once per period
+                "limits": [],
+                "interval": QUOTA_INTERVAL
+            },
+            "notifications": {
+                "enabled": False,
+                # Per sink unless it sets its own; quiet_hours is {start, end, allow (severities still sent)}
+                "rate_limit": NOTIFY_RATE_LIMIT,
+                "quiet_hours": None,
+                # {name, url, events (wan_down, vpn_down, quota_exceeded or event name patterns), body, method,
+                # headers}; body is a JSON template with {title}, {message}, {severity}, {host}, {time}, ...
+                "webhooks": [],
+                # {name, topic, server, token, events}
+                "ntfy": [],
+                # {name, host, port, tls (starttls, ssl, none), username, password, from, to, events}
+                "smtp": []
+            },
+            "syslog": {
+                "enabled": False,
+                # udp://, tcp:// or tls://host[:port]; ca_file, cert_file and key_file apply to tls
+                "url": "udp://localhost:514",
+                "facility": "daemon",
+                # Event kinds to forward; None forwards all but job progress
+                "events": None,
+                "audit": True
+            },
+            "event_log": {
+                # Every event in events.jsonl for GetEvents, dropped after retention_days; exclude takes
+                # event name patterns
+                "enabled": True,
+                "retention_days": EVENT_LOG_RETENTION,
+                "exclude": EVENT_LOG_EXCLUDE
+            },
+            "sla": {
+                # {name, target, method (icmp/tcp), port, interface, interval, count,
+                #  thresholds {loss %, rtt ms, jitter ms}, consecutive, script}
+                "probes": []
+            },
+            "connectivity": {
+                # host:port targets DiagnoseConnectivity tries over IPv4 and IPv6, and seconds per attempt
+                "targets": connectivity.DEFAULT_TARGETS,
+                "timeout": port_probe.DEFAULT_TIMEOUT
+            },
+            "troubleshoot": {
+                # What Troubleshoot resolves to test DNS, and the host:port addresses that stand for the internet
+                "name": troubleshoot.PROBE_NAME,
+                "targets": troubleshoot.DEFAULT_TARGETS,
+                "timeout": troubleshoot.DEFAULT_TIMEOUT
+            },
+            "accounting": {
+                # Bytes per systemd unit for GetTopTalkers, counted by nftables rules
+                "enabled": False,
+                "interval": ACCOUNTING_INTERVAL
+            },
+            "rename": {
+                # Where RenameInterface writes the .link files that make udev keep new names;
+                # None is /etc/systemd/network (the state directory in mock mode)
+                "link_dir": None
+            },
+            "ebpf_counters": {
+                # Per-interface, per-protocol rates for GetFastRates; falls back to off when unsupported
+                "enabled": False,
+                "interval_ms": COUNTERS_INTERVAL_MS,
+                "object": COUNTERS_OBJECT,
+                # Interfaces to attach to; None is every interface but loopback
+                "interfaces": None
+            },
+            "containers": {
+                # Group container bridges and veths by Docker/Podman network; unmanaged leaves them alone
+                # (SetUnmanaged still takes one over)
+                "enabled": True,
+                "unmanaged": True,
+                "sockets": CONTAINER_SOCKETS,
+                "interval": CONTAINERS_INTERVAL
+            },
+            "libvirt": {
+                # Network names and guests for virbr bridges, read with virsh
+                "enabled": True,
+                "uri": LIBVIRT_URI,
+                "interval": LIBVIRT_INTERVAL
+            },
+            "wifi_watch": {
+                # Warn when a known SSID shows up on a new access point with other security, or on
+                # deauth/disassoc bursts (burst frames within window seconds)
+                "enabled": True,
+                "interval": WIFI_WATCH_INTERVAL,
+                "burst": DEAUTH_BURST,
+                "window": DEAUTH_WINDOW
+            },
+            "roaming": {
+                # Timeline of associations and roams; interval is how often the signal is sampled
+                "enabled": True,
+                "interval": ROAMING_INTERVAL,
+                "entries": ROAMING_ENTRIES
+            },
+            "access": {
+                # Roles: observer (read state), operator (connect, scan, test, radios), admin (configuration).
+                # users maps names or uids, groups group names and certificates remote clients' common names to
+                # roles; root and the daemon's user are always admin. permissions maps request types or patterns
+                # (Set*) to the role they need instead of the built-in one
+                "enabled": False,
+                "default_role": "observer",
+                "users": {},
+                "groups": {},
+                "certificates": {},
+                "permissions": {}
+            },
+            "remote": {
+                # Control requests over mutual TLS, for fleet views on other machines; clients must present a
+                # certificate signed by ca_file, and when clients lists common names, one of those
+                "enabled": False,
+                "listen": "0.0.0.0",
+                "port": REMOTE_PORT,
+                "ca_file": None,
+                "cert_file": None,
+                "key_file": None,
+                "clients": None
+            },
+            "relay": {
+                # Downstream daemons polled over mutual TLS: {name, address (tls://host:port), ca_file, cert_file,
+                # key_file}; the certificate files here apply to hosts that don't name their own
+                "enabled": False,
+                "interval": RELAY_INTERVAL,
+                "timeout": RELAY_TIMEOUT,
+                "ca_file": None,
+                "cert_file": None,
+                "key_file": None,
+                "hosts": []
+            },
+            "snmp": {
+                "enabled": False,
+                "listen": "127.0.0.1",
+                "port": 161,
+                # v2c communities; v3 users are {name, auth (md5/sha/sha256), auth_password, priv (aes), priv_password}
+                "communities": [],
+                "users": [],
+                "contact": "",
+                "location": ""
+            },
+            "backends": {
+                "ethernet": "iproute2",
+                "wifi": "wpa_supplicant",
+                "vpn": "wireguard",
+                "bluetooth": "bluetoothctl",
+                "host": "system",
+                "services": "systemd",
+                "rfkill": "sysfs",
+                "hotspot": "hostapd",
+                "dhcp_server": "builtin",
+                "accounting": "nftables",
+                "counters": "ebpf",
+                "containers": "engine_api",
+                "libvirt": "virsh"
+            },
+            "logging": {
+                "level": "INFO",
+                "journald": False,
+                "modules": {}
+            },
             "enterprise_policies": {
                 "require_encryption": True,
                 "allow_adhoc": False,
@@ -88,6 +601,2124 @@ class AlopexDaemon:
             }
         }
     
+    def _register_ipc_handlers(self):
+        """Register control socket request handlers"""
+        self.ipc.register("GetUnmanaged", self._ipc_get_unmanaged)
+        self.ipc.register("SetUnmanaged", self._ipc_set_unmanaged)
+        self.ipc.register("ListInterfaceLabels", self.labels.list)
+        self.ipc.register("SetInterfaceLabel", self._ipc_set_interface_label)
+        self.ipc.register("RenameInterface", self._ipc_rename_interface)
+        self.ipc.register("GetLogLevels", get_log_levels)
+        self.ipc.register("SetLogLevel", self._ipc_set_log_level)
+        self.ipc.register("GetLogs", self._ipc_get_logs)
+        self.ipc.register("GetEvents", self._ipc_get_events)
+        self.ipc.register("GetStatus", self._ipc_get_status)
+        self.ipc.register("GetDiagnostics", self._ipc_get_diagnostics)
+        self.ipc.register("GetSessionCounters", self.sessions.list)
+        self.ipc.register("GetBandwidthHistory", self._ipc_get_bandwidth_history)
+        self.ipc.register("GetInfluxSink", self._ipc_get_influx_sink)
+        self.ipc.register("GetSyslogForwarder", self._ipc_get_syslog_forwarder)
+        self.ipc.register("GetNotifications", self._ipc_get_notifications)
+        self.ipc.register("TestNotification", self._ipc_test_notification)
+        self.ipc.register("GetQuotas", self._ipc_get_quotas)
+        self.ipc.register("GetRelayHosts", self._ipc_get_relay_hosts)
+        self.ipc.register("GetFleetInterfaces", self._ipc_get_fleet_interfaces)
+        self.ipc.register("RelayRequest", self._ipc_relay_request)
+        self.ipc.register("GetSnmpAgent", self._ipc_get_snmp_agent)
+        self.ipc.register("ResetSessionCounters", self._ipc_reset_session_counters)
+        self.ipc.register("GetInterfaces", self._ipc_get_interfaces)
+        self.ipc.register("GetUptimes", self._ipc_get_uptimes)
+        self.ipc.register("GetLinkSpeedWarnings", self._ipc_get_link_speed_warnings)
+        self.ipc.register("GetModuleInfo", self._ipc_get_module_info)
+        self.ipc.register("GetNicQueues", self._ipc_get_nic_queues)
+        self.ipc.register("SpreadIrqs", self._ipc_spread_irqs)
+        self.ipc.register("ListProfiles", self._ipc_list_profiles)
+        self.ipc.register("CreateProfile", self._ipc_create_profile)
+        self.ipc.register("UpdateProfile", self._ipc_update_profile)
+        self.ipc.register("DeleteProfile", self._ipc_delete_profile)
+        self.ipc.register("ListAutoConnectRetries", self.connection_manager.retry.status)
+        self.ipc.register("ResetAutoConnect", self._ipc_reset_auto_connect)
+        self.ipc.register("ListVpn", self._ipc_list_vpn)
+        self.ipc.register("ListLocations", self.locations.to_dict)
+        self.ipc.register("SetLocation", self._ipc_set_location)
+        self.ipc.register("DeleteLocation", self._ipc_delete_location)
+        self.ipc.register("SwitchLocation", self._ipc_switch_location)
+        self.ipc.register("SetLocationAutoSwitch", self._ipc_set_location_auto_switch)
+        self.ipc.register("ImportConnections", self._ipc_import_connections)
+        self.ipc.register("ExportConnections", self._ipc_export_connections)
+        self.ipc.register("ExportProfiles", self._ipc_export_profiles)
+        self.ipc.register("ImportProfiles", self._ipc_import_profiles)
+        self.ipc.register("Connect", self._ipc_connect)
+        self.ipc.register("Disconnect", self._ipc_disconnect)
+        self.ipc.register("ScanWifi", self._ipc_scan_wifi)
+        self.ipc.register("ScanLan", self._ipc_scan_lan)
+        self.ipc.register("ProbePort", self._ipc_probe_port)
+        self.ipc.register("DiagnoseConnectivity", self._ipc_diagnose_connectivity)
+        self.ipc.register("Troubleshoot", self._ipc_troubleshoot)
+        self.ipc.register("RunCableTest", self._ipc_run_cable_test)
+        self.ipc.register("ListBluetoothDevices", self._ipc_list_bluetooth_devices)
+        self.ipc.register("GetBluetoothDevice", self._ipc_get_bluetooth_device)
+        self.ipc.register("ScanBluetooth", self._ipc_scan_bluetooth)
+        self.ipc.register("PairBluetooth", self._ipc_pair_bluetooth)
+        self.ipc.register("ConnectBluetooth", self._ipc_connect_bluetooth)
+        self.ipc.register("SetBluetoothAlias", self._ipc_set_bluetooth_alias)
+        self.ipc.register("SendBluetoothFile", self._ipc_send_bluetooth_file)
+        self.ipc.register("GetDnsHealth", self.dns_health.list)
+        self.ipc.register("GetSlaProbes", self.sla.list)
+        self.ipc.register("GetSlaHistory", self._ipc_get_sla_history)
+        self.ipc.register("ListFailover", self.failover.list)
+        self.ipc.register("SetFailover", self._ipc_set_failover)
+        self.ipc.register("GetDnsForwarder", self._ipc_get_dns_forwarder)
+        self.ipc.register("FlushDnsCache", self._ipc_flush_dns_cache)
+        self.ipc.register("GetDnsFilter", self._ipc_get_dns_filter)
+        self.ipc.register("SetDnsFilter", self._ipc_set_dns_filter)
+        self.ipc.register("ReloadDnsFilter", self._ipc_reload_dns_filter)
+        self.ipc.register("GetRegulatoryDomain", self._ipc_get_regulatory_domain)
+        self.ipc.register("SetRegulatoryDomain", self._ipc_set_regulatory_domain)
+        self.ipc.register("GetPowerSave", self._ipc_get_power_save)
+        self.ipc.register("SetPowerSave", self._ipc_set_power_save)
+        self.ipc.register("ListRfkill", self._ipc_list_rfkill)
+        self.ipc.register("SetRfkill", self._ipc_set_rfkill)
+        self.ipc.register("GetAirplaneMode", self._ipc_get_airplane_mode)
+        self.ipc.register("SetAirplaneMode", self._ipc_set_airplane_mode)
+        self.ipc.register("ListHotspotClients", self._ipc_list_hotspot_clients)
+        self.ipc.register("KickHotspotClient", self._ipc_kick_hotspot_client)
+        self.ipc.register("BlockHotspotClient", self._ipc_block_hotspot_client)
+        self.ipc.register("AllowHotspotClient", self._ipc_allow_hotspot_client)
+        self.ipc.register("SetHotspotFilter", self._ipc_set_hotspot_filter)
+        self.ipc.register("SetHotspotIsolation", self._ipc_set_hotspot_isolation)
+        self.ipc.register("LimitHotspotClient", self._ipc_limit_hotspot_client)
+        self.ipc.register("GetFlows", self._ipc_get_flows)
+        self.ipc.register("GetTopTalkers", self._ipc_get_top_talkers)
+        self.ipc.register("GetFastRates", self._ipc_get_fast_rates)
+        self.ipc.register("GetContainerNetworks", self._ipc_get_container_networks)
+        self.ipc.register("GetVirtualNetworks", self._ipc_get_virtual_networks)
+        self.ipc.register("GetWifiAlerts", self._ipc_get_wifi_alerts)
+        self.ipc.register("ForgetWifiBaseline", self._ipc_forget_wifi_baseline)
+        self.ipc.register("GetRoamingTimeline", self._ipc_get_roaming_timeline)
+        self.ipc.register("ListCertificates", self.connection_manager.certs.list)
+        self.ipc.register("ImportCertificate", self._ipc_import_certificate)
+        self.ipc.register("RemoveCertificate", self._ipc_remove_certificate)
+        self.ipc.register("ListDhcpLeases", self._ipc_list_dhcp_leases)
+        self.ipc.register("SetDhcpReservation", self._ipc_set_dhcp_reservation)
+        self.ipc.register("SetDns", self._ipc_set_dns)
+        self.ipc.register("GetDhcpLease", self._ipc_get_dhcp_lease)
+        self.ipc.register("RenewDhcpLease", self._ipc_renew_dhcp_lease)
+        self.ipc.register("ReleaseDhcpLease", self._ipc_release_dhcp_lease)
+        self.ipc.register("AnnounceAddresses", self._ipc_announce_addresses)
+        self.ipc.register("GetSysctls", self._ipc_get_sysctls)
+        self.ipc.register("GetMptcp", self._ipc_get_mptcp)
+        self.ipc.register("GetShaping", self._ipc_get_shaping)
+        self.ipc.register("RunShapingTest", self._ipc_run_shaping_test)
+        self.ipc.register("RunBufferbloatTest", self._ipc_run_bufferbloat_test)
+        self.ipc.register("SetMptcpLimits", self._ipc_set_mptcp_limits)
+        self.ipc.register("ListChanges", self.changes.list)
+        self.ipc.register("Undo", self._ipc_undo)
+        self.ipc.register("ApplyInterfaces", self._ipc_apply_interfaces)
+        self.ipc.register("GetBootStatus", self.boot.status)
+        self.ipc.register("WaitBootOnline", self._ipc_wait_boot_online)
+        self.ipc.register("GetJob", self._ipc_get_job)
+        self.ipc.register("ListJobs", self.jobs.list)
+        self.ipc.register("WaitJob", self._ipc_wait_job)
+        self.ipc.register("CancelJob", self._ipc_cancel_job)
+        
+        if self.mock_network:
+            self.ipc.register("MockSetCarrier", self._ipc_mock_set_carrier)
+            self.ipc.register("MockSetLinkSpeed", self._ipc_mock_set_link_speed)
+            self.ipc.register("MockRoam", self._ipc_mock_roam)
+    
+    def _seed_mock_profiles(self):
+        """Create the scenario's profiles on first start of a mock state dir"""
+        for spec in self.mock_network.profiles:
+            spec = dict(spec)
+            name = spec.pop("name")
+            if not self.connection_manager.get_profile(name):
+                self.connection_manager.create_profile(name, **spec)
+    
+    async def _start_remote_ipc(self):
+        """Listen for other machines' fleet views, when the remote section asks for it"""
+        remote_config = self.enterprise_config.get("remote", {})
+        if not remote_config.get("enabled"):
+            return
+        if not remote_config.get("ca_file") or not remote_config.get("cert_file"):
+            self.logger.error("Remote control disabled: remote needs ca_file and cert_file")
+            return
+        try:
+            context = server_tls_context(remote_config["ca_file"], remote_config["cert_file"],
+                                         remote_config.get("key_file"))
+            await self.ipc.start_remote(remote_config.get("listen", "0.0.0.0"), remote_config.get("port", REMOTE_PORT),
+                                        context, remote_config.get("clients"))
+        except OSError as e:
+            self.logger.error(f"Remote control disabled: {e}")
+    
+    def _ipc_get_status(self) -> dict:
+        """Summarize daemon health for status queries"""
+        stats = self.connection_manager.get_connection_stats()
+        return {
+            "hostname": socket.gethostname(),
+            "pid": os.getpid(),
+            "started_at": self.started_at,
+            "uptime": time.time() - self.started_at,
+            "profiles": stats["total_profiles"],
+            "connected_interfaces": stats["connected_interfaces"],
+            "backends": self.backends.describe(),
+            "location": self.locations.active,
+            "active_profiles": {
+                name: state.profile_name for name, state in self.connection_manager.interface_states.items()
+                if state.status == LinkState.CONFIGURED
+            },
+            "metered": self.desktop.state["metered"],
+            "airplane_mode": self.airplane.active,
+            "link_warnings": [warning["message"] for warning in self.link_speed.warnings.values()],
+            "boot": self.boot.status(),
+            "mock": self.mock_network is not None
+        }
+    
+    def _ipc_get_diagnostics(self, events: int = 200, samples: Optional[int] = None, logs: int = 500) -> dict:
+        """Everything a support ticket needs, with secrets redacted"""
+        profiles = [asdict(profile) for profile in self.connection_manager.list_profiles()]
+        return {
+            "generated_at": time.time(),
+            "hostname": socket.gethostname(),
+            "status": self._ipc_get_status(),
+            "interfaces": self._ipc_get_interfaces(),
+            "routes": self.discovery.get_routes(),
+            "dns": self.discovery._get_dns_servers(),
+            "events": redact(self.events.recent(events)),
+            "metrics": self.metrics_history.recent(samples),
+            "logs": get_logs(limit=logs),
+            "config": redact({
+                "enterprise": self.enterprise_config,
+                "profiles": profiles,
+                "locations": self.locations.to_dict(),
+                "unmanaged": self._ipc_get_unmanaged()
+            })
+        }
+    
+    def _ipc_reset_session_counters(self, interface: Optional[str] = None) -> dict:
+        """Start the session totals of one interface (or all) from zero"""
+        if interface and interface not in self.sessions.sessions:
+            raise IpcError(f"No active session on {interface}", ErrorCode.NOT_FOUND, {"interface": interface})
+        return {"reset": self.sessions.reset(interface)}
+    
+    def _ipc_get_bandwidth_history(self, interface: str, since: Optional[float] = None,
+                                   until: Optional[float] = None) -> dict:
+        """Per-minute traffic of an interface between two timestamps (either end open)"""
+        known = self.bandwidth_history.interfaces()
+        if interface not in known:
+            raise IpcError(f"No bandwidth history for {interface}", ErrorCode.NOT_FOUND,
+                           {"interface": interface, "available": known})
+        return {
+            "interface": interface,
+            "since": since,
+            "until": until,
+            "bucket_seconds": self.bandwidth_history.bucket_seconds,
+            "samples": self.bandwidth_history.query(interface, since, until)
+        }
+    
+    def _ipc_get_influx_sink(self) -> dict:
+        """Endpoint, interval and delivery counters of the InfluxDB sink"""
+        return self.influx.stats() if self.influx else {"enabled": False}
+    
+    def _ipc_get_notifications(self) -> dict:
+        """Webhooks and their delivery counters"""
+        return self.notifier.stats() if self.notifier else {"enabled": False}
+    
+    async def _ipc_test_notification(self, sink: Optional[str] = None) -> list:
+        """Send a test notification through every sink, or one, ignoring quiet hours and rate limits"""
+        if not self.notifier:
+            raise IpcError("Notifications are disabled", ErrorCode.INVALID_CONFIG, {"section": "notifications"})
+        try:
+            return await self.notifier.test(sink)
+        except KeyError:
+            raise IpcError(f"No notification sink called {sink}", ErrorCode.NOT_FOUND, {"sink": sink})
+    
+    def _ipc_get_quotas(self) -> list:
+        """Each data quota with what the interface has used this period"""
+        return self.quota.check() if self.quota else []
+    
+    def _require_relay(self) -> Relay:
+        if not self.relay:
+            raise IpcError("This daemon is not a relay", ErrorCode.INVALID_CONFIG, {"section": "relay"})
+        return self.relay
+    
+    def _ipc_get_relay_hosts(self) -> list:
+        """This daemon and each downstream one: reachable or not, and what it last reported"""
+        return self._require_relay().hosts()
+    
+    def _ipc_get_fleet_interfaces(self, host: Optional[str] = None) -> list:
+        """Interfaces of every host behind the relay (or of one), each entry tagged with its host"""
+        try:
+            return self._require_relay().interfaces(host)
+        except KeyError:
+            raise IpcError(f"No host {host} behind this relay", ErrorCode.NOT_FOUND, {"host": host})
+    
+    async def _ipc_relay_request(self, host: str, relayed: str, params: Optional[dict] = None) -> Any:
+        """Pass a request that only reads state on to a downstream daemon"""
+        try:
+            return await self._require_relay().forward(host, relayed, params)
+        except KeyError:
+            raise IpcError(f"No host {host} behind this relay", ErrorCode.NOT_FOUND, {"host": host})
+    
+    def _profile_connection_type(self, name: Optional[str]) -> Optional[str]:
+        profile = self.connection_manager.get_profile(name) if name else None
+        return profile.connection_type if profile else None
+    
+    def _ipc_get_syslog_forwarder(self) -> dict:
+        """Collector, backlog and delivery counters of syslog forwarding"""
+        return self.syslog.stats() if self.syslog else {"enabled": False}
+    
+    def _ipc_get_sla_history(self, probe: str) -> list:
+        """Rounds of the last hour of one SLA probe"""
+        rounds = self.sla.history(probe)
+        if rounds is None:
+            raise IpcError(f"No SLA probe named {probe}", ErrorCode.NOT_FOUND,
+                           {"probe": probe, "available": sorted(self.sla.probes)})
+        return rounds
+    
+    def _failover_links(self) -> Dict[str, Optional[str]]:
+        """Interfaces that are up, with their gateways, for failover to judge"""
+        return {interface.name: interface.gateway for interface in self.discovery.discover_interfaces()
+                if interface.status == "Connected"}
+    
+    async def _ipc_set_failover(self, group: str, active: str) -> dict:
+        """Put a failover group on its primary or backup by hand"""
+        if active not in ("primary", "backup"):
+            raise IpcError("active must be primary or backup", ErrorCode.INVALID_REQUEST, {"active": active})
+        try:
+            state = await self.failover.set_active(group, active)
+        except ValueError as e:
+            raise IpcError(str(e), ErrorCode.BACKEND_UNAVAILABLE, {"group": group, "active": active})
+        if state is None:
+            raise IpcError(f"No failover group named {group}", ErrorCode.NOT_FOUND,
+                           {"group": group, "available": sorted(self.failover.groups)})
+        return state
+    
+    def _ipc_get_snmp_agent(self) -> dict:
+        """Listening address, users and request counters of the SNMP agent"""
+        return self.snmp.stats() if self.snmp else {"enabled": False}
+    
+    def _ipc_get_interfaces(self, group: Optional[str] = None) -> list:
+        """List interfaces (or a group's) with their labels, metrics and connection state"""
+        interfaces = self.discovery.discover_interfaces()
+        if group is not None:
+            members = self.labels.members(group)
+            if not members:
+                raise IpcError(f"No interfaces in group {group}", ErrorCode.NOT_FOUND, {"group": group})
+            interfaces = [iface for iface in interfaces if iface.name in members]
+        self.labels.annotate(interfaces)
+        if self.containers:
+            self.containers.annotate(interfaces)
+        if self.virtual_networks:
+            self.virtual_networks.annotate(interfaces)
+        self.discovery.update_speeds(interfaces)
+        self.sessions.update(interfaces)
+        self.carrier.update(interfaces)
+        self.link_speed.annotate(interfaces)
+        
+        result = []
+        for iface in interfaces:
+            iface.managed = not self.connection_manager.is_unmanaged(iface.name)
+            entry = asdict(iface)
+            state = self.connection_manager.get_interface_state(iface.name)
+            entry["connection"] = asdict(state) if state else None
+            result.append(entry)
+        return result
+    
+    async def _ipc_get_link_speed_warnings(self, refresh: bool = False) -> list:
+        """Wired links running below their NIC's speed or at half duplex; refresh checks them now"""
+        if refresh:
+            await self.link_speed.check()
+        return list(self.link_speed.warnings.values())
+    
+    async def _ipc_get_module_info(self, interface: str) -> dict:
+        """Vendor, type, wavelength and DOM readings of a wired interface's SFP/QSFP module"""
+        iface = next((i for i in self.discovery.discover_interfaces() if i.name == interface), None)
+        if not iface:
+            raise IpcError(f"No such interface: {interface}", ErrorCode.NOT_FOUND, {"interface": interface})
+        if iface.interface_type != InterfaceType.ETHERNET:
+            raise IpcError(f"{interface} is not a wired interface", ErrorCode.INVALID_REQUEST,
+                           {"interface": interface})
+        module = await self.backends.host.module_info(interface)
+        if not module:
+            raise IpcError(f"No pluggable module readable on {interface}", ErrorCode.NOT_FOUND,
+                           {"interface": interface})
+        return dict(module, interface=interface)
+    
+    async def _ipc_get_nic_queues(self, interface: str) -> dict:
+        """Per-queue packet shares of an interface and the CPUs its IRQs are pinned to"""
+        if interface not in {i.name for i in self.discovery.discover_interfaces()}:
+            raise IpcError(f"No such interface: {interface}", ErrorCode.NOT_FOUND, {"interface": interface})
+        return nic_queues.summarize(interface, await self.backends.host.nic_queues(interface))
+    
+    async def _ipc_spread_irqs(self, interface: str) -> dict:
+        """Pin an interface's IRQs one per CPU, round robin; returns the new distribution"""
+        raw = await self._ipc_get_nic_queues(interface)
+        if not raw["irqs"]:
+            raise IpcError(f"No IRQs found for {interface}", ErrorCode.NOT_FOUND, {"interface": interface})
+        
+        plan = nic_queues.spread_plan(raw["irqs"], raw["cpus"])
+        failed = [irq for irq, cpus in plan.items() if not self.backends.host.set_irq_affinity(irq, cpus)]
+        if len(failed) == len(plan):
+            raise IpcError(f"The kernel refused to move the IRQs of {interface}", ErrorCode.BACKEND_UNAVAILABLE,
+                           {"interface": interface, "irqs": failed})
+        self.logger.info(f"Spread {len(plan) - len(failed)} IRQs of {interface} over CPUs "
+                    f"{nic_queues.format_cpu_list([cpu for cpus in plan.values() for cpu in cpus])}")
+        self.events.publish("IrqsSpread", interface=interface, irqs=len(plan) - len(failed), failed=failed)
+        return dict(await self._ipc_get_nic_queues(interface), failed=failed)
+    
+    def _ipc_get_uptimes(self) -> dict:
+        """Seconds since each interface with carrier gained it"""
+        return {name: self.carrier.uptime(name) for name in self.carrier.carrier_up}
+    
+    def _ipc_list_profiles(self, interface: Optional[str] = None) -> list:
+        """List connection profiles without their secrets"""
+        profiles = []
+        for profile in self.connection_manager.list_profiles(interface):
+            entry = asdict(profile)
+            entry.pop("password", None)
+            profiles.append(entry)
+        return profiles
+    
+    def _ipc_create_profile(self, name: str, interface: str, connection_type: str, method: str = "dhcp",
+                            replace: bool = False, **fields) -> dict:
+        """Create (or with replace, overwrite) a connection profile"""
+        if connection_type not in ("ethernet", "wifi", "vpn", "hotspot"):
+            raise IpcError(f"Unknown connection type: {connection_type}", ErrorCode.INVALID_CONFIG,
+                           {"connection_type": connection_type})
+        if method not in ("dhcp", "static", "manual", "shared"):
+            raise IpcError(f"Unknown method: {method}", ErrorCode.INVALID_CONFIG, {"method": method})
+        if (method == "shared") != (connection_type == "hotspot"):
+            raise IpcError("Hotspot profiles, and only they, use method shared", ErrorCode.INVALID_CONFIG,
+                           {"method": method, "connection_type": connection_type})
+        if method == "static" and not fields.get("ip_address"):
+            raise IpcError("Static profiles need ip_address", ErrorCode.INVALID_CONFIG, {"field": "ip_address"})
+        if connection_type == "wifi" and not fields.get("ssid"):
+            raise IpcError("WiFi profiles need ssid", ErrorCode.INVALID_CONFIG, {"field": "ssid"})
+        if connection_type == "hotspot":
+            self._check_hotspot(fields)
+        self._check_certificates(connection_type, fields)
+        self._check_netdev(connection_type, fields)
+        if self.connection_manager.get_profile(name) and not replace:
+            raise IpcError(f"Profile {name} already exists", ErrorCode.INVALID_CONFIG, {"profile": name})
+        self._check_units(fields.get("units"))
+        self._check_route_metric(fields.get("route_metric"))
+        self._check_sysctls(fields)
+        self._check_mptcp_flags(fields)
+        self._check_shaping(fields)
+        
+        created = self.connection_manager.create_profile(name, interface, connection_type, method, **fields)
+        entry = asdict(created)
+        entry.pop("password", None)
+        return entry
+    
+    def _check_hotspot(self, fields: dict):
+        """Reject hotspot settings hostapd would refuse to start with"""
+        if not fields.get("ssid") or len(fields["ssid"].encode()) > 32:
+            raise IpcError("Hotspot profiles need an ssid of at most 32 bytes", ErrorCode.INVALID_CONFIG,
+                           {"field": "ssid"})
+        password = fields.get("password")
+        if password is not None and not 8 <= len(password) <= 63:
+            raise IpcError("A WPA2 password is 8 to 63 characters", ErrorCode.INVALID_CONFIG, {"field": "password"})
+        blocked = [normalize_mac(mac) for mac in fields.get("blocked_clients") or []]
+        if None in blocked:
+            raise IpcError("blocked_clients must be MAC addresses", ErrorCode.INVALID_CONFIG,
+                           {"field": "blocked_clients"})
+        fields["blocked_clients"] = blocked
+        allowed = [normalize_mac(mac) for mac in fields.get("allowed_clients") or []]
+        if None in allowed:
+            raise IpcError("allowed_clients must be MAC addresses", ErrorCode.INVALID_CONFIG,
+                           {"field": "allowed_clients"})
+        fields["allowed_clients"] = allowed
+        client_filter = fields.get("client_filter") or "deny"
+        if client_filter not in ("allow", "deny"):
+            raise IpcError("client_filter is allow or deny", ErrorCode.INVALID_CONFIG, {"field": "client_filter"})
+        if client_filter == "allow" and not allowed:
+            raise IpcError("An allow list needs allowed_clients, or nobody could join", ErrorCode.INVALID_CONFIG,
+                           {"field": "allowed_clients"})
+        fields["client_filter"] = client_filter
+        if not isinstance(fields.get("client_isolation", False), bool):
+            raise IpcError("client_isolation must be true or false", ErrorCode.INVALID_CONFIG,
+                           {"field": "client_isolation"})
+        limits = {}
+        for mac, limit in (fields.get("client_limits") or {}).items():
+            normalized = normalize_mac(str(mac))
+            if not normalized or not isinstance(limit, dict) or set(limit) - {"down", "up"}:
+                raise IpcError(f"Bad client limit for {mac}: needs a MAC and {{\"down\", \"up\"}} in kbit/s",
+                               ErrorCode.INVALID_CONFIG, {"field": "client_limits", "mac": mac})
+            checked = {direction: self._check_rate(limit.get(direction), direction) for direction in ("down", "up")}
+            if checked["down"] or checked["up"]:
+                limits[normalized] = checked
+        fields["client_limits"] = limits
+        
+        try:
+            network = hotspot_network(fields.get("ip_address"), fields.get("netmask"))
+        except ValueError:
+            raise IpcError("ip_address/netmask is not an IPv4 subnet", ErrorCode.INVALID_CONFIG,
+                           {"field": "ip_address"})
+        try:
+            dhcp_range(network)
+        except ValueError as e:
+            raise IpcError(str(e), ErrorCode.INVALID_CONFIG, {"field": "netmask"})
+        reservations = {}
+        for mac, address in (fields.get("dhcp_reservations") or {}).items():
+            normalized = normalize_mac(str(mac))
+            try:
+                valid = ipaddress.IPv4Address(address) in network.network and address != str(network.ip)
+            except ValueError:
+                valid = False
+            if not normalized or not valid or address in reservations.values():
+                raise IpcError(f"Bad DHCP reservation {mac} -> {address}: needs a MAC and a free address in "
+                               f"{network.network}", ErrorCode.INVALID_CONFIG, {"mac": mac, "ip": address})
+            reservations[normalized] = address
+        fields["dhcp_reservations"] = reservations
+    
+    @staticmethod
+    def _check_rate(rate, field: str) -> Optional[int]:
+        """A kbit/s rate limit, None or 0 for none"""
+        if rate is None or rate == 0:
+            return None
+        if isinstance(rate, bool) or not isinstance(rate, int) or not 1 <= rate <= MAX_RATE:
+            raise IpcError(f"{field} must be a rate from 1 to {MAX_RATE} kbit/s", ErrorCode.INVALID_CONFIG,
+                           {"field": field, "value": rate})
+        return rate
+    
+    def _check_certificates(self, connection_type: str, fields: dict):
+        """Reject 802.1X settings and certificate references that could never connect"""
+        eap = fields.get("eap")
+        if eap is not None and (connection_type != "wifi" or eap not in ("peap", "ttls", "tls")):
+            raise IpcError("eap is peap, ttls or tls, on WiFi profiles", ErrorCode.INVALID_CONFIG, {"eap": eap})
+        if (fields.get("ca_cert") or fields.get("client_cert")) and connection_type not in ("wifi", "vpn"):
+            raise IpcError("Only WiFi and VPN profiles use certificates", ErrorCode.INVALID_CONFIG,
+                           {"connection_type": connection_type})
+        if eap == "tls" and not fields.get("client_cert"):
+            raise IpcError("EAP-TLS needs a client_cert", ErrorCode.INVALID_CONFIG, {"field": "client_cert"})
+        
+        store = self.connection_manager.certs
+        for field, kind in (("ca_cert", "ca"), ("client_cert", "client")):
+            cert_id = fields.get(field)
+            if cert_id is None:
+                continue
+            cert = store.get(cert_id)
+            if not cert or cert.kind != kind:
+                raise IpcError(f"No {kind} certificate {cert_id} in the store (alopex-cli cert list)",
+                               ErrorCode.NOT_FOUND, {"field": field, "id": cert_id})
+            if kind == "client" and not store.key_for(cert_id):
+                raise IpcError(f"Import the private key of certificate {cert_id} first", ErrorCode.NOT_FOUND,
+                               {"field": field, "id": cert_id})
+    
+    def _check_netdev(self, connection_type: str, fields: dict):
+        """Reject tunnel endpoints, keys and VNIs the kernel would refuse, normalizing the rest"""
+        netdev = fields.get("netdev")
+        if not netdev or netdev.get("kind") not in tunnels.TUNNEL_KINDS:
+            return
+        if connection_type != "ethernet":
+            raise IpcError("Tunnels are created by ethernet profiles", ErrorCode.INVALID_CONFIG,
+                           {"connection_type": connection_type})
+        try:
+            fields["netdev"] = tunnels.check(netdev)
+        except tunnels.TunnelError as e:
+            raise IpcError(str(e), ErrorCode.INVALID_CONFIG, {"netdev": netdev})
+    
+    def _check_units(self, units: Optional[list]):
+        """Reject unit lists systemd would not accept"""
+        for unit in units or []:
+            if not isinstance(unit, str) or "." not in unit or "/" in unit:
+                raise IpcError(f"Not a systemd unit name: {unit}", ErrorCode.INVALID_CONFIG, {"unit": unit})
+    
+    def _check_route_metric(self, metric):
+        """None (derived) or a metric the kernel takes"""
+        if metric is not None and (not isinstance(metric, int) or isinstance(metric, bool)
+                                   or not 0 <= metric <= MAX_METRIC):
+            raise IpcError(f"route_metric must be 0 to {MAX_METRIC} or null", ErrorCode.INVALID_CONFIG,
+                           {"route_metric": metric})
+    
+    def _check_sysctls(self, fields: dict):
+        """Reject sysctls alopex does not manage or values the kernel would refuse, normalizing the rest"""
+        if "sysctls" not in fields:
+            return
+        if fields["sysctls"] is not None and not isinstance(fields["sysctls"], dict):
+            raise IpcError("sysctls must be an object of name: value", ErrorCode.INVALID_CONFIG,
+                           {"sysctls": fields["sysctls"]})
+        try:
+            fields["sysctls"] = sysctls.check(fields["sysctls"])
+        except ValueError as e:
+            raise IpcError(str(e), ErrorCode.INVALID_CONFIG,
+                           {"sysctls": fields["sysctls"], "supported": sorted(sysctls.SETTINGS)})
+    
+    def _check_mptcp_flags(self, fields: dict):
+        """Reject MPTCP endpoint flags the kernel would refuse, normalizing the rest"""
+        if "mptcp_flags" not in fields:
+            return
+        try:
+            fields["mptcp_flags"] = mptcp.check_flags(fields["mptcp_flags"])
+        except (TypeError, ValueError) as e:
+            raise IpcError(str(e), ErrorCode.INVALID_CONFIG,
+                           {"mptcp_flags": fields["mptcp_flags"], "supported": list(mptcp.FLAGS)})
+    
+    def _check_shaping(self, fields: dict, existing: Optional[ConnectionProfile] = None):
+        """Reject a qdisc, bandwidth or congestion control tc and ip would refuse, normalizing the rest; an
+        edit that only changes the qdisc keeps the bandwidth if it is still cake"""
+        if not {"qdisc", "qdisc_bandwidth", "congestion_control"} & fields.keys():
+            return
+        qdisc = fields.get("qdisc", existing.qdisc if existing else None)
+        bandwidth = fields.get("qdisc_bandwidth")
+        if "qdisc_bandwidth" not in fields and existing and qdisc == "cake":
+            bandwidth = existing.qdisc_bandwidth
+        try:
+            checked = shaping.check_qdisc(qdisc, bandwidth)
+            congestion_control = shaping.check_congestion_control(
+                fields.get("congestion_control", existing.congestion_control if existing else None))
+        except (TypeError, ValueError) as e:
+            raise IpcError(str(e), ErrorCode.INVALID_CONFIG,
+                           {key: fields[key] for key in ("qdisc", "qdisc_bandwidth", "congestion_control")
+                            if key in fields})
+        fields.update(qdisc=checked["qdisc"], qdisc_bandwidth=checked["bandwidth"],
+                      congestion_control=congestion_control)
+    
+    def _ipc_update_profile(self, name: str, **fields) -> dict:
+        """Change some fields of a profile, leaving the rest (and its secrets) alone"""
+        self._check_units(fields.get("units"))
+        self._check_route_metric(fields.get("route_metric"))
+        self._check_sysctls(fields)
+        self._check_mptcp_flags(fields)
+        self._check_shaping(fields, self.connection_manager.get_profile(name))
+        for flag in ("metered", "power_save", "boot_critical", "gratuitous_arp", "proxy_arp"):
+            if fields.get(flag) not in (True, False, None):
+                raise IpcError(f"{flag} must be true, false or null", ErrorCode.INVALID_CONFIG,
+                               {flag: fields[flag]})
+        existing = self.connection_manager.get_profile(name)
+        if existing and "netdev" in fields:
+            self._check_netdev(existing.connection_type, fields)
+        if existing and {"eap", "ca_cert", "client_cert"} & fields.keys():
+            self._check_certificates(existing.connection_type, dict(asdict(existing), **fields))
+        if existing and existing.connection_type == "hotspot":
+            merged = dict(asdict(existing), **fields)
+            self._check_hotspot(merged)
+            for key in ("blocked_clients", "allowed_clients", "client_filter", "client_limits", "dhcp_reservations"):
+                if key in fields:
+                    fields[key] = merged[key]
+        previous = {key: deepcopy(getattr(existing, key)) for key in fields if hasattr(existing, key)}
+        try:
+            updated = self.connection_manager.update_profile(name, **fields)
+        except TypeError as e:
+            raise IpcError(str(e), ErrorCode.INVALID_CONFIG, {"profile": name})
+        if not updated:
+            raise IpcError(f"No such profile: {name}", ErrorCode.NOT_FOUND, {"profile": name})
+        self.changes.record("profile", f"Edit profile {name} ({', '.join(sorted(previous))})",
+                            {"profile": name, "fields": previous})
+        state = self.connection_manager.get_interface_state(updated.interface)
+        if {"route_metric", "priority"} & fields.keys() and state and state.profile_name == name:
+            # Active now: move its default route instead of waiting for the next connect
+            asyncio.create_task(self.connection_manager.apply_route_metric(updated.interface))
+        if "proxy_arp" in fields and state and state.profile_name == name:
+            self.connection_manager.apply_proxy_arp(updated.interface)
+        if "sysctls" in fields and state and state.profile_name == name:
+            self.connection_manager.apply_sysctls(updated.interface)
+        if "mptcp_flags" in fields and state and state.profile_name == name:
+            asyncio.create_task(self.connection_manager.apply_mptcp(updated.interface))
+        if {"qdisc", "qdisc_bandwidth", "congestion_control"} & fields.keys() and state and state.profile_name == name:
+            asyncio.create_task(self.connection_manager.apply_shaping(updated.interface))
+        entry = asdict(updated)
+        entry.pop("password", None)
+        return entry
+    
+    def _ipc_delete_profile(self, name: str) -> dict:
+        """Remove a connection profile"""
+        if not self.connection_manager.delete_profile(name):
+            raise IpcError(f"No such profile: {name}", ErrorCode.NOT_FOUND, {"profile": name})
+        return {"profile": name, "deleted": True}
+    
+    def _ipc_reset_auto_connect(self, profile: Optional[str] = None) -> dict:
+        """Forget auto-connect failures (one profile or all) and try those interfaces again now"""
+        target = self.connection_manager.get_profile(profile) if profile else None
+        if profile and not target:
+            raise IpcError(f"No such profile: {profile}", ErrorCode.NOT_FOUND, {"profile": profile})
+        interfaces = ({target.interface} if target else
+                      {entry["interface"] for entry in self.connection_manager.retry.status()})
+        reset = self.connection_manager.reset_retries(profile)
+        if self.enterprise_config.get("auto_connect", True):
+            for interface in sorted(interfaces):
+                if self.connection_manager.get_link_state(interface) in (LinkState.DISCONNECTED, LinkState.FAILED):
+                    asyncio.create_task(self.connection_manager.auto_connect_interface(interface))
+        return {"reset": reset}
+    
+    def _ipc_import_connections(self, source: str, path: Optional[str] = None, dry_run: bool = False,
+                                replace: bool = False) -> dict:
+        """Convert another network stack's saved connections into profiles"""
+        importer = IMPORTERS.get(source)
+        if not importer:
+            raise IpcError(f"Unknown import source: {source}", ErrorCode.INVALID_REQUEST,
+                           {"source": source, "available": sorted(IMPORTERS)})
+        if path and not Path(path).exists():
+            raise IpcError(f"No such path: {path}", ErrorCode.NOT_FOUND, {"path": path})
+        
+        wifi_interfaces = self.backends.wifi.list_interfaces()
+        report = importer(Path(path) if path else None, wifi_interfaces[0] if wifi_interfaces else None)
+        
+        result = report.to_dict()
+        result["imported"] = []
+        for profile in report.profiles:
+            if self.connection_manager.get_profile(profile.name) and not replace:
+                result["skipped"].append({"file": None, "name": profile.name,
+                                          "reason": "a profile with this name already exists"})
+                continue
+            if not dry_run:
+                self.connection_manager.create_profile(**asdict(profile))
+            result["imported"].append(profile.name)
+        
+        self.logger.info(f"Imported {len(result['imported'])} profiles from {source}"
+                    f"{' (dry run)' if dry_run else ''}, skipped {len(result['skipped'])}")
+        return result
+    
+    def _ipc_export_connections(self, target: str, profiles: Optional[List[str]] = None,
+                                secrets: bool = False) -> dict:
+        """Render profiles (all by default) as networkd or netplan configuration; WiFi passwords only for root"""
+        exporter = EXPORTERS.get(target)
+        if not exporter:
+            raise IpcError(f"Unknown export target: {target}", ErrorCode.INVALID_REQUEST,
+                           {"target": target, "available": sorted(EXPORTERS)})
+        missing = [name for name in profiles or [] if not self.connection_manager.get_profile(name)]
+        if missing:
+            raise IpcError(f"No such profile: {', '.join(missing)}", ErrorCode.NOT_FOUND, {"profiles": missing})
+        # Plain-text passwords go to the daemon's own user (root) and nobody else
+        if secrets and log_context.get().get("client_uid") not in (0, os.getuid()):
+            raise IpcError("Only root can export WiFi passwords", ErrorCode.PERMISSION_DENIED)
+        
+        selected = [profile for profile in self.connection_manager.list_profiles()
+                    if profiles is None or profile.name in profiles]
+        report = exporter(selected, bool(secrets))
+        self.logger.info(f"Exported {len(report.exported)} profiles as {target}, skipped {len(report.skipped)}")
+        return report.to_dict()
+    
+    def _check_backup(self, passphrase: str):
+        if not passphrase:
+            raise IpcError("A passphrase is required", ErrorCode.INVALID_REQUEST, {"field": "passphrase"})
+        if not backup.available():
+            raise IpcError("Encrypted backups need the Python 'cryptography' package",
+                           ErrorCode.BACKEND_UNAVAILABLE, {"module": "cryptography"})
+    
+    async def _ipc_export_profiles(self, passphrase: str) -> dict:
+        """Seal every profile, secrets included, into an encrypted archive"""
+        self._check_backup(passphrase)
+        profiles = {p.name: revealed(asdict(p)) for p in self.connection_manager.list_profiles()}
+        
+        # Key derivation is deliberately slow; keep it off the event loop
+        archive = await asyncio.get_running_loop().run_in_executor(
+            None, backup.create_archive, profiles, passphrase)
+        
+        self.logger.info(f"Exported {len(profiles)} profiles")
+        return {"archive": archive, "profiles": sorted(profiles)}
+    
+    async def _ipc_import_profiles(self, archive: str, passphrase: str, replace: bool = False) -> dict:
+        """Restore profiles from an ExportProfiles archive"""
+        self._check_backup(passphrase)
+        try:
+            contents = await asyncio.get_running_loop().run_in_executor(
+                None, backup.open_archive, archive, passphrase)
+        except backup.BackupError as e:
+            raise IpcError(str(e), ErrorCode.INVALID_CONFIG)
+        
+        known_fields = set(ConnectionProfile.__dataclass_fields__)
+        result = {"imported": [], "skipped": [], "created_at": contents.get("created_at"),
+                  "hostname": contents.get("hostname")}
+        for name, data in contents.get("profiles", {}).items():
+            if self.connection_manager.get_profile(name) and not replace:
+                result["skipped"].append({"name": name, "reason": "a profile with this name already exists"})
+                continue
+            # Archives from newer versions may carry fields this one does not know
+            self.connection_manager.create_profile(**{k: v for k, v in data.items() if k in known_fields})
+            result["imported"].append(name)
+        
+        self.logger.info(f"Restored {len(result['imported'])} profiles from backup of {result['hostname']}")
+        return result
+    
+    def _ipc_list_vpn(self) -> list:
+        """VPN configurations known to the VPN backend"""
+        return [
+            {"name": config.name, "type": config.config_type, "path": str(config.path),
+             "active": self.backends.vpn.is_active(config)}
+            for config in self.backends.vpn.discover()
+        ]
+    
+    def _ipc_set_location(self, name: str, **fields) -> dict:
+        """Create or replace a location"""
+        missing = [p for p in fields.get("profiles", []) if not self.connection_manager.get_profile(p)]
+        if missing:
+            raise IpcError(f"No such profile: {', '.join(missing)}", ErrorCode.INVALID_CONFIG,
+                           {"profiles": missing})
+        try:
+            location = Location.from_dict(dict(fields, name=name))
+        except (TypeError, AttributeError) as e:
+            raise IpcError(f"Invalid location: {e}", ErrorCode.INVALID_CONFIG, {"location": name})
+        return asdict(self.locations.set(location))
+    
+    def _ipc_delete_location(self, name: str) -> dict:
+        """Remove a location"""
+        if not self.locations.delete(name):
+            raise IpcError(f"No such location: {name}", ErrorCode.NOT_FOUND, {"location": name})
+        return {"location": name, "deleted": True}
+    
+    def _ipc_switch_location(self, name: str) -> dict:
+        """Start switching to a location; returns the job"""
+        if not self.locations.get(name):
+            raise IpcError(f"No such location: {name}", ErrorCode.NOT_FOUND, {"location": name})
+        
+        async def switch(progress):
+            result = await self.locations.switch(name, progress=progress)
+            self.changes.record("location", f"Switch to location {name}",
+                                {"location": result["previous"], "zones": result["previous_zones"]})
+            return result
+        
+        return self.jobs.start(
+            "SwitchLocation", f"Switch to location {name}", switch, self._job_timeout("location")
+        ).to_dict()
+    
+    def _ipc_set_location_auto_switch(self, enabled: bool) -> dict:
+        """Turn switching on detected SSIDs and gateways on or off"""
+        self.locations.set_auto_switch(bool(enabled))
+        return {"auto_switch": self.locations.auto_switch}
+    
+    def _ipc_connect(self, profile: str) -> dict:
+        """Start activating a connection profile; returns the job"""
+        target = self.connection_manager.get_profile(profile)
+        if not target:
+            raise IpcError(f"No such profile: {profile}", ErrorCode.NOT_FOUND, {"profile": profile})
+        
+        backend = getattr(self.backends, target.connection_type, None)
+        if backend and not backend.is_available():
+            raise IpcError(f"{target.connection_type} backend '{backend.backend_name}' is not usable",
+                           ErrorCode.BACKEND_UNAVAILABLE,
+                           {"backend": backend.backend_name, "required_tools": list(backend.required_tools)})
+        
+        # Claim the interface now, not when the job first runs, so a second
+        # request arriving in between sees it busy
+        try:
+            self.connection_manager.begin_connect(profile)
+        except PermissionError:
+            raise IpcError(f"Interface {target.interface} is unmanaged", ErrorCode.PERMISSION_DENIED,
+                           {"interface": target.interface})
+        except StateTransitionError as e:
+            state = self.connection_manager.get_interface_state(target.interface)
+            raise IpcError(str(e), ErrorCode.BUSY,
+                           {"interface": target.interface, "state": e.current.value,
+                            "profile": state.profile_name if state else None})
+        
+        async def connect(progress):
+            if not await self.connection_manager.finish_connect(target, progress):
+                raise IpcError(target.last_error or f"Failed to connect {profile}", ErrorCode.BACKEND_UNAVAILABLE,
+                               {"profile": profile, "interface": target.interface})
+            return asdict(self.connection_manager.get_interface_state(target.interface))
+        
+        kind = "vpn" if target.connection_type == "vpn" else "connect"
+        return self.jobs.start(
+            "Connect", f"Connect {profile} on {target.interface}", connect, self._job_timeout(kind)
+        ).to_dict()
+    
+    async def _ipc_disconnect(self, interface: str) -> dict:
+        """Take an interface down"""
+        if self.connection_manager.is_unmanaged(interface):
+            raise IpcError(f"Interface {interface} is unmanaged", ErrorCode.PERMISSION_DENIED,
+                           {"interface": interface})
+        
+        if self.connection_manager.get_link_state(interface) != LinkState.DISCONNECTED:
+            try:
+                self.connection_manager.check_transition(interface, LinkState.DEACTIVATING)
+            except StateTransitionError as e:
+                raise IpcError(f"{e}; cancel the running job first", ErrorCode.BUSY,
+                               {"interface": interface, "state": e.current.value})
+        
+        if not await self.connection_manager.disconnect_interface(interface):
+            raise IpcError(f"Failed to disconnect {interface}", ErrorCode.BACKEND_UNAVAILABLE,
+                           {"interface": interface})
+        return {"interface": interface, "status": self.connection_manager.get_link_state(interface).value}
+    
+    async def _ipc_set_dns(self, interface: str, servers: List[str]) -> dict:
+        """Replace the DNS servers of an interface's active profile and apply them now"""
+        state = self.connection_manager.interface_states.get(interface)
+        profile = self.connection_manager.get_profile(state.profile_name) if state and state.profile_name else None
+        if not profile:
+            raise IpcError(f"No active profile on {interface}", ErrorCode.NOT_FOUND, {"interface": interface})
+        
+        invalid = []
+        for server in servers:
+            try:
+                ipaddress.ip_address(server.split("%")[0])
+            except ValueError:
+                invalid.append(server)
+        if invalid:
+            raise IpcError(f"Not an IP address: {', '.join(invalid)}", ErrorCode.INVALID_CONFIG,
+                           {"servers": invalid})
+        
+        self.changes.record("dns", f"Set DNS of {interface} to {', '.join(servers) or 'DHCP'}",
+                            {"interface": interface, "profile": profile.name,
+                             "servers": list(profile.dns_servers or [])})
+        return await self._apply_dns(interface, profile.name, servers)
+    
+    async def _apply_dns(self, interface: str, profile: str, servers: List[str]) -> dict:
+        """Save a profile's DNS servers, and switch the resolvers over if it is up on the interface"""
+        self.connection_manager.update_profile(profile, dns_servers=list(servers))
+        state = self.connection_manager.interface_states.get(interface)
+        applied = False
+        effective: List[str] = []
+        if state and state.profile_name == profile:
+            # An empty list hands resolving back to whatever DHCP gave out
+            effective = list(servers) or (state.dhcp_lease or {}).get("dns_servers", [])
+            applied = await self.backends.host.set_dns(effective) if effective else False
+        
+        # Probe the new servers now rather than up to an interval later
+        asyncio.create_task(self.dns_health.probe_all())
+        return {"interface": interface, "profile": profile, "dns_servers": list(servers),
+                "effective": effective, "applied": applied}
+    
+    def _dns_upstreams(self) -> List[str]:
+        """Upstreams for the forwarder: resolvers set by hand, the primary link's, other links', resolv.conf's"""
+        links = self.connection_manager.link_dns()
+        primary = self.desktop.primary()
+        servers = links.pop(primary.interface, []) if primary else []
+        for link_servers in links.values():
+            servers += link_servers
+        return self.backends.host.pinned + servers + self.backends.host.fallback
+    
+    def _ipc_get_dns_forwarder(self) -> dict:
+        """Listeners, upstreams and cache counters of the forwarder"""
+        if not self.dns_forwarder:
+            return {"enabled": False}
+        return self.dns_forwarder.stats()
+    
+    def _ipc_flush_dns_cache(self) -> dict:
+        """Forget every cached answer"""
+        if not self.dns_forwarder:
+            raise IpcError("The DNS forwarder is not enabled (dns_forwarder in enterprise.json)", ErrorCode.NOT_FOUND)
+        return {"flushed": self.dns_forwarder.flush()}
+    
+    def _dns_filter(self) -> DnsFilter:
+        if not self.dns_forwarder or not self.dns_forwarder.filter:
+            raise IpcError("DNS filtering is not enabled (dns_forwarder.filter in enterprise.json)",
+                           ErrorCode.NOT_FOUND)
+        return self.dns_forwarder.filter
+    
+    async def _dns_client_zone(self, client: str) -> Optional[str]:
+        """The firewall zone a DNS client asks from: its hotspot's for a station, the primary link's for this host"""
+        try:
+            address = ipaddress.ip_address(client)
+        except ValueError:
+            return None
+        interface = None
+        for hotspot in self.connection_manager.active_hotspots():
+            state = self.connection_manager.interface_states[hotspot]
+            profile = self.connection_manager.get_profile(state.profile_name)
+            if address in hotspot_network(profile.ip_address, profile.netmask).network:
+                interface = hotspot
+        if interface is None:
+            primary = self.desktop.primary()
+            interface = primary.interface if primary else None
+        return await self.backends.host.firewall_zone(interface) if interface else None
+    
+    def _ipc_get_dns_filter(self) -> dict:
+        """Blocklists, zones filtered and names blocked per client"""
+        if not self.dns_forwarder or not self.dns_forwarder.filter:
+            return {"enabled": False}
+        return self.dns_forwarder.filter.stats()
+    
+    def _ipc_set_dns_filter(self, zone: str, enabled: bool) -> dict:
+        """Switch filtering on or off for clients in a firewall zone"""
+        if not isinstance(enabled, bool):
+            raise IpcError("enabled must be true or false", ErrorCode.INVALID_REQUEST, {"enabled": enabled})
+        dns_filter = self._dns_filter()
+        dns_filter.set_zone(zone, enabled)
+        return dns_filter.stats()
+    
+    def _ipc_reload_dns_filter(self) -> dict:
+        """Read the blocklists again, after they were updated"""
+        dns_filter = self._dns_filter()
+        dns_filter.load()
+        return dns_filter.stats()
+    
+    def _apply_regulatory_domain(self):
+        """Set the country from the "wifi" config section, if it names one"""
+        country = str(self.enterprise_config.get("wifi", {}).get("regulatory_domain") or "").upper()
+        if not country:
+            return
+        if not COUNTRY_CODE.match(country):
+            self.logger.warning(f"Ignoring invalid regulatory_domain {country!r} (want an ISO 3166 code or 00)")
+        elif self.backends.wifi.set_regulatory_domain(country):
+            self.logger.info(f"Regulatory domain set to {country}")
+        else:
+            self.logger.warning(f"Could not set regulatory domain {country}")
+    
+    def _ipc_get_regulatory_domain(self, interface: Optional[str] = None) -> dict:
+        """Current country code and the channels one (or the first) WiFi interface may use"""
+        interfaces = self.backends.wifi.list_interfaces()
+        interface = interface or (interfaces[0] if interfaces else None)
+        if interface and interface not in interfaces:
+            raise IpcError(f"No such WiFi interface: {interface}", ErrorCode.NOT_FOUND,
+                           {"interface": interface, "available": interfaces})
+        
+        domain = self.backends.wifi.regulatory_domain() or {"country": None, "dfs_region": None}
+        return dict(domain, interface=interface,
+                    channels=self.backends.wifi.channels(interface) if interface else [])
+    
+    def _ipc_set_regulatory_domain(self, country: str) -> dict:
+        """Apply a country's WiFi rules (ISO 3166 alpha-2, or 00 for the world domain)"""
+        country = country.upper()
+        if not COUNTRY_CODE.match(country):
+            raise IpcError(f"Not a country code: {country}", ErrorCode.INVALID_CONFIG, {"country": country})
+        if not self.backends.wifi.set_regulatory_domain(country):
+            raise IpcError(f"Failed to set regulatory domain {country}", ErrorCode.BACKEND_UNAVAILABLE,
+                           {"country": country})
+        self.events.publish("RegulatoryDomainChanged", country=country)
+        return self._ipc_get_regulatory_domain()
+    
+    def _wifi_profile(self, interface: str):
+        """The profile a WiFi interface is connected with (None if it isn't), after checking the interface exists"""
+        interfaces = self.backends.wifi.list_interfaces()
+        if interface not in interfaces:
+            raise IpcError(f"No such WiFi interface: {interface}", ErrorCode.NOT_FOUND,
+                           {"interface": interface, "available": interfaces})
+        if self.connection_manager.get_link_state(interface) != LinkState.CONFIGURED:
+            return None
+        return self.connection_manager.get_profile(self.connection_manager.interface_states[interface].profile_name)
+    
+    def _ipc_get_power_save(self, interface: str) -> dict:
+        """Whether power save is on now, and what the active profile asks for"""
+        profile = self._wifi_profile(interface)
+        return {"interface": interface, "power_save": self.backends.wifi.power_save(interface),
+                "profile": profile.name if profile else None,
+                "profile_power_save": profile.power_save if profile else None}
+    
+    def _ipc_set_power_save(self, interface: str, enabled: Optional[bool], save: bool = True) -> dict:
+        """Switch power save now and (unless save is false) remember it on the active profile;
+        enabled null only clears the profile's choice"""
+        if enabled not in (True, False, None) or (enabled is None and not save):
+            raise IpcError("enabled must be true or false (or null when saving)", ErrorCode.INVALID_REQUEST,
+                           {"enabled": enabled})
+        profile = self._wifi_profile(interface)
+        if save and not profile:
+            raise IpcError(f"No active profile on {interface} to save power save to", ErrorCode.NOT_FOUND,
+                           {"interface": interface})
+        
+        if enabled is not None and not self.backends.wifi.set_power_save(interface, enabled):
+            raise IpcError(f"Failed to turn power save {'on' if enabled else 'off'} on {interface}",
+                           ErrorCode.BACKEND_UNAVAILABLE, {"interface": interface})
+        if save:
+            self.connection_manager.update_profile(profile.name, power_save=enabled)
+        self.events.publish("PowerSaveChanged", interface=interface,
+                            power_save=self.backends.wifi.power_save(interface))
+        return self._ipc_get_power_save(interface)
+    
+    def _ipc_list_rfkill(self) -> list:
+        """Every radio kill switch and how it is blocked"""
+        return [dict(asdict(device), block=device.block) for device in self.rfkill.poll()]
+    
+    def _ipc_set_rfkill(self, target: str, blocked: bool) -> list:
+        """Soft-block or unblock radios by type (wifi, bluetooth, wwan, all) or rfkill index;
+        a hard block stays whatever is asked"""
+        if not isinstance(blocked, bool):
+            raise IpcError("blocked must be true or false", ErrorCode.INVALID_REQUEST, {"blocked": blocked})
+        target = str(target).lower()
+        devices = self.backends.rfkill.devices()
+        if target.isdigit():
+            selected = [d for d in devices if d.index == int(target)]
+        elif target == "all":
+            selected = [d for d in devices if d.type in RADIO_TYPES]
+        elif target in TYPE_ALIASES:
+            selected = [d for d in devices if d.type == TYPE_ALIASES[target]]
+        else:
+            raise IpcError(f"Not a radio type or rfkill index: {target}", ErrorCode.INVALID_REQUEST,
+                           {"target": target, "types": sorted(TYPE_ALIASES) + ["all"]})
+        if not selected:
+            raise IpcError(f"No {target} radio found", ErrorCode.NOT_FOUND, {"target": target})
+        
+        failed = [d.name for d in selected if not self.backends.rfkill.set_soft_block(d.index, blocked)]
+        if failed:
+            raise IpcError(f"Failed to {'block' if blocked else 'unblock'} {', '.join(failed)}",
+                           ErrorCode.BACKEND_UNAVAILABLE, {"devices": failed})
+        # Publish the change now rather than on the next poll
+        indexes = {d.index for d in selected}
+        return [entry for entry in self._ipc_list_rfkill() if entry["index"] in indexes]
+    
+    def _ipc_get_airplane_mode(self) -> dict:
+        """Whether airplane mode is on, and the radios it covers"""
+        return {"enabled": self.airplane.active,
+                "radios": [entry for entry in self._ipc_list_rfkill() if entry["type"] in RADIO_TYPES]}
+    
+    def _ipc_set_airplane_mode(self, enabled: bool) -> dict:
+        """Soft-block every radio, or put each back the way it was before"""
+        if not isinstance(enabled, bool):
+            raise IpcError("enabled must be true or false", ErrorCode.INVALID_REQUEST, {"enabled": enabled})
+        failed = self.airplane.set(enabled)
+        if failed:
+            raise IpcError(f"Airplane mode is {'on' if enabled else 'off'} but {', '.join(failed)} "
+                           f"could not be switched", ErrorCode.BACKEND_UNAVAILABLE, {"devices": failed})
+        return self._ipc_get_airplane_mode()
+    
+    def _hotspot_profile(self, interface: Optional[str]):
+        """The profile of a running hotspot; interface may be left out while only one runs"""
+        running = self.connection_manager.active_hotspots()
+        if interface is None and len(running) == 1:
+            interface = running[0]
+        if interface not in running:
+            raise IpcError(f"No hotspot running on {interface}" if interface else "Which hotspot? Give an interface",
+                           ErrorCode.NOT_FOUND if interface or not running else ErrorCode.INVALID_REQUEST,
+                           {"interface": interface, "running": running})
+        return self.connection_manager.get_profile(self.connection_manager.interface_states[interface].profile_name)
+    
+    def _hotspot_mac(self, mac: str) -> str:
+        normalized = normalize_mac(str(mac))
+        if not normalized:
+            raise IpcError(f"Not a MAC address: {mac}", ErrorCode.INVALID_REQUEST, {"mac": mac})
+        return normalized
+    
+    def _ipc_list_hotspot_clients(self, interface: Optional[str] = None) -> list:
+        """Stations on each running hotspot (or just one), with what each profile blocks"""
+        if interface is not None:
+            self._hotspot_profile(interface)
+        stations = self.hotspot.poll()
+        result = []
+        for name in [interface] if interface else self.connection_manager.active_hotspots():
+            profile = self._hotspot_profile(name)
+            result.append({"interface": name, "profile": profile.name, "ssid": profile.ssid,
+                           "channel": profile.channel,
+                           "clients": [dict(c, vendor=oui.lookup_vendor(c["mac"])) for c in stations.get(name, [])],
+                           "blocked": list(profile.blocked_clients), "filter": profile.client_filter,
+                           "allowed": list(profile.allowed_clients), "isolated": profile.client_isolation,
+                           "limits": dict(profile.client_limits)})
+        return result
+    
+    def _shared_networks(self) -> dict:
+        """Subnets of the running hotspots by interface"""
+        networks = {}
+        for name in self.connection_manager.active_hotspots():
+            profile = self.connection_manager.get_profile(self.connection_manager.interface_states[name].profile_name)
+            networks[name] = hotspot_network(profile.ip_address, profile.netmask).network
+        return networks
+    
+    async def _ipc_get_flows(self, top: int = FLOWS_TOP) -> dict:
+        """Tracked connections summed by protocol, destination and port, and NAT per hotspot"""
+        if not isinstance(top, int) or top < 1:
+            raise IpcError("top must be a positive integer", ErrorCode.INVALID_REQUEST, {"top": top})
+        try:
+            return await self.flows.snapshot(top)
+        except OSError as e:
+            raise IpcError(f"Cannot read the connection tracking table: {e}", ErrorCode.BACKEND_UNAVAILABLE,
+                           {"module": "nf_conntrack"})
+    
+    def _ipc_get_top_talkers(self, top: int = TALKERS_TOP) -> dict:
+        """Systemd units by current traffic, with totals since each was first counted"""
+        if not isinstance(top, int) or top < 1:
+            raise IpcError("top must be a positive integer", ErrorCode.INVALID_REQUEST, {"top": top})
+        return self.unit_traffic.top(top) if self.unit_traffic else {"enabled": False}
+    
+    def _interface_names(self) -> List[str]:
+        return [iface.name for iface in self.discovery.discover_interfaces()]
+    
+    def _ipc_get_container_networks(self) -> dict:
+        """Docker/Podman bridge networks with their interfaces and containers, and how each engine answered"""
+        if not self.containers:
+            return {"enabled": False}
+        return dict(self.containers.snapshot(), enabled=True)
+    
+    def _ipc_get_virtual_networks(self, bridge: Optional[str] = None) -> dict:
+        """libvirt networks with their bridges and guests, or just the one behind a bridge"""
+        if not self.virtual_networks:
+            return {"enabled": False}
+        result = dict(self.virtual_networks.snapshot(), enabled=True)
+        if bridge is not None:
+            network = self.virtual_networks.network(bridge)
+            if not network:
+                raise IpcError(f"No libvirt network on {bridge}", ErrorCode.NOT_FOUND, {"bridge": bridge})
+            result["networks"] = [network]
+        return result
+    
+    def _ipc_get_wifi_alerts(self, limit: int = 20) -> dict:
+        """Recent rogue access point and deauthentication alerts, with the access points learned per SSID"""
+        if not isinstance(limit, int) or limit < 1:
+            raise IpcError("limit must be a positive integer", ErrorCode.INVALID_REQUEST, {"limit": limit})
+        if not self.wifi_watch:
+            return {"enabled": False}
+        return dict(self.wifi_watch.snapshot(limit), enabled=True)
+    
+    def _ipc_forget_wifi_baseline(self, ssid: Optional[str] = None) -> dict:
+        """Relearn an SSID's access points (all SSIDs without one), e.g. after the network was rebuilt"""
+        if not self.wifi_watch:
+            raise IpcError("WiFi watch is disabled", ErrorCode.INVALID_CONFIG, {"section": "wifi_watch"})
+        if ssid is not None and ssid not in self.wifi_watch.baseline:
+            raise IpcError(f"No access points learned for {ssid}", ErrorCode.NOT_FOUND, {"ssid": ssid})
+        return {"ssid": ssid, "forgotten": self.wifi_watch.forget(ssid)}
+    
+    def _ipc_get_roaming_timeline(self, interface: Optional[str] = None, since: Optional[float] = None,
+                                  limit: int = 100) -> dict:
+        """Associations, roams and disconnects oldest first, with the access point each interface is on now"""
+        if not isinstance(limit, int) or limit < 1:
+            raise IpcError("limit must be a positive integer", ErrorCode.INVALID_REQUEST, {"limit": limit})
+        if not self.roaming:
+            return {"enabled": False}
+        return dict(self.roaming.snapshot(interface, since, limit), enabled=True)
+    
+    def _counted_interfaces(self) -> List[str]:
+        """Interfaces the eBPF counters attach to: the configured ones that exist, else all but loopback"""
+        configured = self.enterprise_config.get("ebpf_counters", {}).get("interfaces")
+        present = [i.name for i in self.discovery.discover_interfaces() if i.interface_type != InterfaceType.LOOPBACK]
+        return [name for name in present if configured is None or name in configured]
+    
+    def _ipc_get_fast_rates(self, interface: Optional[str] = None) -> dict:
+        """Latest per-protocol rates and recent peaks; one interface also returns its sample series"""
+        if not self.fast_counters:
+            return {"enabled": False}
+        if interface and interface not in self.fast_counters.attached:
+            raise IpcError(f"Not counting {interface}", ErrorCode.NOT_FOUND, {"interface": interface})
+        return self.fast_counters.snapshot(interface)
+    
+    def _ipc_kick_hotspot_client(self, mac: str, interface: Optional[str] = None) -> dict:
+        """Disconnect a station; unless it is also blocked, it may come straight back"""
+        profile = self._hotspot_profile(interface)
+        mac = self._hotspot_mac(mac)
+        if not self.backends.hotspot.kick(profile.interface, mac):
+            raise IpcError(f"{mac} is not connected to {profile.ssid}", ErrorCode.NOT_FOUND,
+                           {"interface": profile.interface, "mac": mac})
+        self.hotspot.poll()
+        return {"interface": profile.interface, "mac": mac, "kicked": True}
+    
+    def _ipc_block_hotspot_client(self, mac: str, interface: Optional[str] = None, blocked: bool = True) -> dict:
+        """Refuse (or allow again) a station on a hotspot, remembered on its profile"""
+        if not isinstance(blocked, bool):
+            raise IpcError("blocked must be true or false", ErrorCode.INVALID_REQUEST, {"blocked": blocked})
+        profile = self._hotspot_profile(interface)
+        mac = self._hotspot_mac(mac)
+        
+        all_blocked = [entry for entry in profile.blocked_clients if entry != mac] + ([mac] if blocked else [])
+        if not self.backends.hotspot.set_blocked(profile.interface, mac, blocked, all_blocked):
+            raise IpcError(f"Failed to {'block' if blocked else 'unblock'} {mac}", ErrorCode.BACKEND_UNAVAILABLE,
+                           {"interface": profile.interface, "mac": mac})
+        self.connection_manager.update_profile(profile.name, blocked_clients=all_blocked)
+        self.events.publish("HotspotClientBlocked", interface=profile.interface, mac=mac, blocked=blocked)
+        self.hotspot.poll()
+        return {"interface": profile.interface, "mac": mac, "blocked": blocked, "blocked_clients": all_blocked}
+    
+    def _apply_hotspot_filter(self, profile, client_filter: str, allowed: List[str]):
+        if not self.backends.hotspot.set_filter(profile.interface, allowed if client_filter == "allow" else None,
+                                                profile.blocked_clients):
+            raise IpcError(f"Failed to update the access lists on {profile.interface}", ErrorCode.BACKEND_UNAVAILABLE,
+                           {"interface": profile.interface})
+        self.connection_manager.update_profile(profile.name, client_filter=client_filter, allowed_clients=allowed)
+        self.hotspot.poll()
+    
+    def _ipc_allow_hotspot_client(self, mac: str, interface: Optional[str] = None, allowed: bool = True) -> dict:
+        """Put a station on (or take it off) a hotspot's allow list, which only bites in allow mode"""
+        if not isinstance(allowed, bool):
+            raise IpcError("allowed must be true or false", ErrorCode.INVALID_REQUEST, {"allowed": allowed})
+        profile = self._hotspot_profile(interface)
+        mac = self._hotspot_mac(mac)
+        
+        all_allowed = [entry for entry in profile.allowed_clients if entry != mac] + ([mac] if allowed else [])
+        if profile.client_filter == "allow" and not all_allowed:
+            raise IpcError(f"{mac} is the last allowed station; switch the filter to deny first",
+                           ErrorCode.INVALID_CONFIG, {"interface": profile.interface, "mac": mac})
+        self._apply_hotspot_filter(profile, profile.client_filter, all_allowed)
+        self.events.publish("HotspotClientAllowed", interface=profile.interface, mac=mac, allowed=allowed)
+        return {"interface": profile.interface, "mac": mac, "allowed": allowed, "filter": profile.client_filter,
+                "allowed_clients": all_allowed}
+    
+    def _ipc_set_hotspot_filter(self, mode: str, interface: Optional[str] = None) -> dict:
+        """allow: only allow-listed stations may join; deny: everyone but the blocked ones"""
+        if mode not in ("allow", "deny"):
+            raise IpcError("mode is allow or deny", ErrorCode.INVALID_REQUEST, {"mode": mode})
+        profile = self._hotspot_profile(interface)
+        if mode == "allow" and not profile.allowed_clients:
+            raise IpcError("The allow list is empty; allow some stations first", ErrorCode.INVALID_CONFIG,
+                           {"interface": profile.interface})
+        self._apply_hotspot_filter(profile, mode, list(profile.allowed_clients))
+        self.events.publish("HotspotFilterChanged", interface=profile.interface, mode=mode)
+        return {"interface": profile.interface, "filter": mode, "allowed_clients": list(profile.allowed_clients)}
+    
+    async def _ipc_set_hotspot_isolation(self, isolated: bool, interface: Optional[str] = None) -> dict:
+        """Keep a hotspot's stations from reaching each other (or let them again), remembered on its profile"""
+        if not isinstance(isolated, bool):
+            raise IpcError("isolated must be true or false", ErrorCode.INVALID_REQUEST, {"isolated": isolated})
+        profile = self._hotspot_profile(interface)
+        if not await self.backends.hotspot.set_isolation(profile.interface, isolated):
+            raise IpcError(f"Failed to {'isolate' if isolated else 'stop isolating'} clients on {profile.interface}",
+                           ErrorCode.BACKEND_UNAVAILABLE, {"interface": profile.interface})
+        self.connection_manager.update_profile(profile.name, client_isolation=isolated)
+        self.events.publish("HotspotIsolationChanged", interface=profile.interface, isolated=isolated)
+        return {"interface": profile.interface, "isolated": isolated}
+    
+    async def _ipc_limit_hotspot_client(self, mac: str, interface: Optional[str] = None, down: Optional[int] = None,
+                                        up: Optional[int] = None) -> dict:
+        """Cap a station's download and upload in kbit/s (neither lifts the limit), remembered on its profile"""
+        profile = self._hotspot_profile(interface)
+        mac = self._hotspot_mac(mac)
+        limit = {"down": self._check_rate(down, "down"), "up": self._check_rate(up, "up")}
+        
+        limits = {entry: value for entry, value in profile.client_limits.items() if entry != mac}
+        if limit["down"] or limit["up"]:
+            limits[mac] = limit
+        if not await self.backends.hotspot.set_limits(profile.interface, limits):
+            raise IpcError(f"Failed to limit {mac}", ErrorCode.BACKEND_UNAVAILABLE,
+                           {"interface": profile.interface, "mac": mac})
+        self.connection_manager.update_profile(profile.name, client_limits=limits)
+        self.events.publish("HotspotClientLimited", interface=profile.interface, mac=mac, **limit)
+        return {"interface": profile.interface, "mac": mac, "limit": limits.get(mac), "client_limits": limits}
+    
+    def _ipc_import_certificate(self, data: str, name: Optional[str] = None,
+                                password: Optional[str] = None) -> list:
+        """Add the certificates and keys in data (base64 of a PEM bundle or DER file) to the store"""
+        if not certs.available():
+            raise IpcError("Certificates need the Python 'cryptography' package",
+                           ErrorCode.BACKEND_UNAVAILABLE, {"module": "cryptography"})
+        try:
+            raw = base64.b64decode(data, validate=True)
+        except ValueError:
+            raise IpcError("data must be base64", ErrorCode.INVALID_REQUEST, {"field": "data"})
+        store = self.connection_manager.certs
+        try:
+            imported = store.import_data(raw, name, Secret.wrap(password))
+        except CertError as e:
+            raise IpcError(str(e), ErrorCode.INVALID_CONFIG)
+        
+        # One already inside the warning window is announced straight away
+        self.cert_expiry.check()
+        return [store.to_dict(cert) for cert in imported]
+    
+    def _ipc_remove_certificate(self, id: str) -> dict:
+        """Delete a certificate or key no profile refers to"""
+        store = self.connection_manager.certs
+        cert = store.get(id)
+        if not cert:
+            raise IpcError(f"No certificate {id}", ErrorCode.NOT_FOUND, {"id": id})
+        # A key is in use wherever the certificate it belongs to is
+        referenced = {id} | {c.id for c in store.certs.values()
+                              if cert.kind == "key" and c.public_key == cert.fingerprint}
+        users = [p.name for p in self.connection_manager.list_profiles() if {p.ca_cert, p.client_cert} & referenced]
+        if users:
+            raise IpcError(f"{id} is in use by {', '.join(users)}", ErrorCode.BUSY, {"id": id, "profiles": users})
+        store.remove(id)
+        return {"id": id, "removed": True}
+    
+    def _ipc_list_dhcp_leases(self, interface: Optional[str] = None) -> list:
+        """Addresses our DHCP servers have handed out, with seconds left on each"""
+        running = self.backends.dhcp_server.running()
+        if interface is not None and interface not in running:
+            raise IpcError(f"No DHCP server on {interface}", ErrorCode.NOT_FOUND,
+                           {"interface": interface, "running": running})
+        now = time.time()
+        return [dict(lease, interface=name, expires_in=max(0.0, lease["expires"] - now))
+                for name in ([interface] if interface else running)
+                for lease in self.backends.dhcp_server.leases(name)]
+    
+    def _ipc_set_dhcp_reservation(self, profile: str, mac: str, ip: Optional[str] = None) -> dict:
+        """Always give mac the address ip on a hotspot profile's network; ip null drops the reservation"""
+        target = self.connection_manager.get_profile(profile)
+        if not target or target.connection_type != "hotspot":
+            raise IpcError(f"No such hotspot profile: {profile}", ErrorCode.NOT_FOUND, {"profile": profile})
+        mac = self._hotspot_mac(mac)
+        reservations = {owner: address for owner, address in target.dhcp_reservations.items() if owner != mac}
+        if ip is not None:
+            reservations[mac] = ip
+        updated = self._ipc_update_profile(profile, dhcp_reservations=reservations)
+        self.backends.dhcp_server.set_reservations(target.interface, updated["dhcp_reservations"])
+        return {"profile": profile, "interface": target.interface, "dhcp_reservations": updated["dhcp_reservations"]}
+    
+    def _dhcp_lease(self, interface: str) -> dict:
+        """The lease an interface holds, with its timers counted down from now"""
+        state = self.connection_manager.interface_states.get(interface)
+        # The one recorded at connect or renew knows when it was acquired; dhcpcd -U doesn't
+        lease = (state.dhcp_lease if state else None) or self.backends.ethernet.get_dhcp_lease(interface)
+        if not lease:
+            raise IpcError(f"No DHCP lease on {interface}", ErrorCode.NOT_FOUND, {"interface": interface})
+        
+        now = time.time()
+        acquired_at = lease.get("acquired_at") or now
+        lease_time = lease.get("lease_time")
+        # RFC 2131 defaults T1 to half the lease when the server doesn't say
+        renewal_time = lease.get("renewal_time") or (lease_time // 2 if lease_time else None)
+        return dict(
+            lease, interface=interface, renewal_time=renewal_time,
+            expires_in=max(0.0, acquired_at + lease_time - now) if lease_time else None,
+            renew_in=max(0.0, acquired_at + renewal_time - now) if renewal_time else None
+        )
+    
+    def _ipc_get_dhcp_lease(self, interface: str) -> dict:
+        """Server, timers and received options of an interface's DHCP lease"""
+        return self._dhcp_lease(interface)
+    
+    async def _ipc_renew_dhcp_lease(self, interface: str) -> dict:
+        """Renew an interface's DHCP lease now"""
+        if self.connection_manager.is_unmanaged(interface):
+            raise IpcError(f"Interface {interface} is unmanaged", ErrorCode.PERMISSION_DENIED,
+                           {"interface": interface})
+        self._dhcp_lease(interface)
+        
+        if not await self.connection_manager.renew_lease(interface):
+            raise IpcError(f"Failed to renew the DHCP lease on {interface}", ErrorCode.BACKEND_UNAVAILABLE,
+                           {"interface": interface})
+        return self._dhcp_lease(interface)
+    
+    async def _ipc_release_dhcp_lease(self, interface: str) -> dict:
+        """Release an interface's DHCP lease, leaving it up without an address"""
+        if self.connection_manager.is_unmanaged(interface):
+            raise IpcError(f"Interface {interface} is unmanaged", ErrorCode.PERMISSION_DENIED,
+                           {"interface": interface})
+        self._dhcp_lease(interface)
+        
+        if self.connection_manager.get_link_state(interface) != LinkState.DISCONNECTED:
+            try:
+                self.connection_manager.check_transition(interface, LinkState.DEACTIVATING)
+            except StateTransitionError as e:
+                raise IpcError(f"{e}; cancel the running job first", ErrorCode.BUSY,
+                               {"interface": interface, "state": e.current.value})
+        
+        if not await self.connection_manager.release_lease(interface):
+            raise IpcError(f"Failed to release the DHCP lease on {interface}", ErrorCode.BACKEND_UNAVAILABLE,
+                           {"interface": interface})
+        return {"interface": interface, "status": self.connection_manager.get_link_state(interface).value}
+    
+    async def _ipc_announce_addresses(self, interface: str) -> dict:
+        """Send gratuitous ARP / unsolicited NA for an interface's addresses now, whatever its profile says"""
+        if not any(i.name == interface for i in self.discovery.discover_interfaces()):
+            raise IpcError(f"No such interface: {interface}", ErrorCode.NOT_FOUND, {"interface": interface})
+        if self.connection_manager.is_unmanaged(interface):
+            raise IpcError(f"Interface {interface} is unmanaged", ErrorCode.PERMISSION_DENIED,
+                           {"interface": interface})
+        result = await self.connection_manager.announce_addresses(interface, force=True)
+        if not result:
+            raise IpcError(f"{interface} has no addresses to announce", ErrorCode.BACKEND_UNAVAILABLE,
+                           {"interface": interface})
+        if not result["sent"]:
+            raise IpcError(f"Could not announce the addresses of {interface}", ErrorCode.BACKEND_UNAVAILABLE,
+                           result)
+        return result
+    
+    def _ipc_get_sysctls(self, interface: str) -> dict:
+        """The sysctls the interface's profile sets: value in force, wanted, and what it was before"""
+        if not any(i.name == interface for i in self.discovery.discover_interfaces()):
+            raise IpcError(f"No such interface: {interface}", ErrorCode.NOT_FOUND, {"interface": interface})
+        state = self.connection_manager.get_interface_state(interface)
+        profile = self.connection_manager.get_profile(state.profile_name) if state and state.profile_name else None
+        return {"interface": interface, "profile": profile.name if profile else None,
+                "sysctls": self.connection_manager.sysctl_status(interface)}
+    
+    async def _ipc_get_shaping(self, interface: str) -> dict:
+        """The interface's root qdisc and congestion control, and what its profile wants"""
+        if not any(i.name == interface for i in self.discovery.discover_interfaces()):
+            raise IpcError(f"No such interface: {interface}", ErrorCode.NOT_FOUND, {"interface": interface})
+        return await self.connection_manager.shaping_status(interface)
+    
+    def _load_test_seconds(self, seconds: Optional[float]) -> float:
+        """How long a load test fills the link: as asked, else from the "load_test" config section"""
+        if seconds is None:
+            seconds = self.enterprise_config.get("load_test", {}).get("seconds", load_test.DEFAULT_SECONDS)
+        if (not isinstance(seconds, (int, float)) or isinstance(seconds, bool)
+                or not load_test.MIN_SECONDS <= seconds <= load_test.MAX_SECONDS):
+            raise IpcError(f"seconds must be {load_test.MIN_SECONDS} to {load_test.MAX_SECONDS}",
+                           ErrorCode.INVALID_REQUEST, {"seconds": seconds})
+        return seconds
+    
+    def _ipc_run_bufferbloat_test(self, interface: str, seconds: Optional[float] = None) -> dict:
+        """Grade the latency a full link adds, downstream then upstream, with whatever qdisc is in force; a
+        bloated upstream gets a suggested cake bandwidth. Returns the job"""
+        if not any(i.name == interface for i in self.discovery.discover_interfaces()):
+            raise IpcError(f"No such interface: {interface}", ErrorCode.NOT_FOUND, {"interface": interface})
+        seconds = self._load_test_seconds(seconds)
+        description = f"Bufferbloat test {interface}"
+        running = next((job for job in self.jobs.jobs.values()
+                        if job.description == description and not job.finished), None)
+        if running:
+            return running.to_dict()
+        config = self.enterprise_config.get("load_test", {})
+        target = config.get("target", load_test.DEFAULT_TARGET)
+        
+        async def test(progress):
+            results = {}
+            for percent, direction in ((0, "download"), (50, "upload")):
+                try:
+                    results[direction] = await load_test.measure(
+                        self.backends.host, interface, target, seconds, direction, config.get(f"{direction}_url"),
+                        lambda _, message, percent=percent: progress(percent, message))
+                except OSError as e:
+                    raise IpcError(str(e), ErrorCode.BACKEND_UNAVAILABLE, {"interface": interface})
+            result = load_test.assess(results["download"], results["upload"])
+            result["qdisc"] = (await self.backends.host.shaping_state(interface) or {}).get("qdisc")
+            state = self.connection_manager.get_interface_state(interface)
+            result["profile"] = state.profile_name if state else None
+            self.events.publish("BufferbloatTested", interface=interface, grade=result["grade"],
+                                suggestion=result["suggestion"])
+            return result
+        
+        return self.jobs.start("RunBufferbloatTest", description, test, self._job_timeout("load_test")).to_dict()
+    
+    def _ipc_run_shaping_test(self, interface: str, qdisc: Optional[str] = None, bandwidth: Optional[str] = None,
+                              seconds: Optional[float] = None, direction: str = "download") -> dict:
+        """Measure latency under load without shaping (A) and with a qdisc (B, the profile's unless given),
+        then put back what was there; returns the job"""
+        if not any(i.name == interface for i in self.discovery.discover_interfaces()):
+            raise IpcError(f"No such interface: {interface}", ErrorCode.NOT_FOUND, {"interface": interface})
+        config = self.enterprise_config.get("load_test", {})
+        if direction not in load_test.DIRECTIONS:
+            raise IpcError(f"direction must be one of {', '.join(load_test.DIRECTIONS)}", ErrorCode.INVALID_REQUEST,
+                           {"direction": direction})
+        seconds = self._load_test_seconds(seconds)
+        if qdisc is None:
+            state = self.connection_manager.get_interface_state(interface)
+            profile = self.connection_manager.get_profile(state.profile_name) if state and state.profile_name else None
+            qdisc, bandwidth = (profile.qdisc, profile.qdisc_bandwidth) if profile else (None, None)
+        try:
+            wanted = shaping.check_qdisc(qdisc, bandwidth)
+        except ValueError as e:
+            raise IpcError(str(e), ErrorCode.INVALID_REQUEST, {"qdisc": qdisc, "bandwidth": bandwidth})
+        if not wanted["qdisc"]:
+            raise IpcError(f"Nothing to compare: give a qdisc, or set one on the profile active on {interface}",
+                           ErrorCode.INVALID_REQUEST, {"interface": interface})
+        
+        description = f"Shaping test {interface}"
+        running = next((job for job in self.jobs.jobs.values()
+                        if job.description == description and not job.finished), None)
+        if running:
+            return running.to_dict()
+        target = config.get("target", load_test.DEFAULT_TARGET)
+        url = config.get("upload_url" if direction == "upload" else "download_url")
+        host = self.backends.host
+        
+        async def phase(progress, percent: int, kind: Optional[str], rate: Optional[str]) -> dict:
+            error = await host.set_qdisc(interface, kind, rate)
+            if error:
+                raise IpcError(f"Cannot set qdisc on {interface}: {error}", ErrorCode.BACKEND_UNAVAILABLE,
+                               {"interface": interface})
+            in_force = (await host.shaping_state(interface) or {}).get("qdisc")
+            label = "kernel default" if kind is None else " ".join(filter(None, (kind, rate)))
+            result = await load_test.measure(
+                host, interface, target, seconds, direction, url,
+                lambda _, message: progress(percent, f"{label}: {message}"))
+            result["qdisc"] = in_force
+            return result
+        
+        async def test(progress):
+            before = (await host.shaping_state(interface) or {}).get("qdisc")
+            a = None
+            try:
+                a = await phase(progress, 0, None, None)
+                b = await phase(progress, 50, wanted["qdisc"], wanted["bandwidth"])
+            except OSError as e:
+                raise IpcError(str(e), ErrorCode.BACKEND_UNAVAILABLE, {"interface": interface})
+            finally:
+                # Back to what was there: the profile's qdisc, one set by hand, or (as in A) the kernel's default
+                if before and (a is None or before != a["qdisc"]):
+                    await host.set_qdisc(interface, before["kind"], before["bandwidth"])
+                else:
+                    await host.set_qdisc(interface, None)
+            verdict = load_test.compare(a, b)
+            names = {"a": "kernel default", "b": " ".join(filter(None, wanted.values()))}
+            summary = (f"{interface}: +{a['increase_ms']} ms under load with {names['a']}, "
+                       f"+{b['increase_ms']} ms with {names['b']}")
+            if verdict["better"] in names:
+                summary += f"; {names[verdict['better']]} is better"
+            elif verdict["better"] == "same":
+                summary += "; no real difference"
+            self.events.publish("ShapingTested", interface=interface, qdisc=wanted["qdisc"],
+                                better=verdict["better"], difference_ms=verdict["difference_ms"])
+            return {"interface": interface, "direction": direction, "target": target, "a": a, "b": b,
+                    **verdict, "summary": summary}
+        
+        return self.jobs.start("RunShapingTest", description, test, self._job_timeout("load_test")).to_dict()
+    
+    async def _ipc_get_mptcp(self) -> dict:
+        """The MPTCP path manager's endpoints (with the profile that added each) and limits"""
+        try:
+            state = await self.backends.host.mptcp_state()
+        except OSError as e:
+            raise IpcError(f"MPTCP is unavailable: {e.strerror or e}", ErrorCode.BACKEND_UNAVAILABLE)
+        owners = {endpoint_id: state_entry.profile_name
+                  for state_entry in self.connection_manager.interface_states.values()
+                  for endpoint_id in state_entry.mptcp_endpoints}
+        for endpoint in state["endpoints"]:
+            endpoint["profile"] = owners.get(endpoint["id"])
+        return state
+    
+    async def _ipc_set_mptcp_limits(self, subflows: Optional[int] = None,
+                                    add_addr_accepted: Optional[int] = None) -> dict:
+        """Change how many extra subflows a connection may open and how many peer addresses it accepts"""
+        for name, value in (("subflows", subflows), ("add_addr_accepted", add_addr_accepted)):
+            if value is not None and (not isinstance(value, int) or isinstance(value, bool)
+                                      or not 0 <= value <= mptcp.MAX_LIMIT):
+                raise IpcError(f"{name} must be 0 to {mptcp.MAX_LIMIT}", ErrorCode.INVALID_CONFIG, {name: value})
+        state = await self._ipc_get_mptcp()
+        limits = state["limits"]
+        try:
+            await self.backends.host.set_mptcp_limits(
+                limits["subflows"] if subflows is None else subflows,
+                limits["add_addr_accepted"] if add_addr_accepted is None else add_addr_accepted)
+        except OSError as e:
+            raise IpcError(f"Could not set MPTCP limits: {e.strerror or e}", ErrorCode.BACKEND_UNAVAILABLE)
+        return (await self._ipc_get_mptcp())["limits"]
+    
+    async def _apply_mptcp_limits(self):
+        """Set the limits from the "mptcp" config section, if it has any"""
+        config = self.enterprise_config.get("mptcp", {})
+        if "subflows" not in config and "add_addr_accepted" not in config:
+            return
+        try:
+            limits = await self._ipc_set_mptcp_limits(config.get("subflows"), config.get("add_addr_accepted"))
+            self.logger.info(f"MPTCP limits: {limits['subflows']} subflows, "
+                             f"{limits['add_addr_accepted']} accepted addresses")
+        except IpcError as e:
+            self.logger.warning(f"Ignoring the mptcp config section: {e}")
+    
+    def _ipc_scan_wifi(self, interface: Optional[str] = None) -> dict:
+        """Start a WiFi scan on one (or the first) WiFi interface; returns the job"""
+        interfaces = self.backends.wifi.list_interfaces()
+        interface = interface or (interfaces[0] if interfaces else None)
+        if not interface or interface not in interfaces:
+            raise IpcError(f"No such WiFi interface: {interface}", ErrorCode.NOT_FOUND,
+                           {"interface": interface, "available": interfaces})
+        
+        # Concurrent scan requests share the scan already in progress
+        description = f"Scan WiFi on {interface}"
+        running = next((job for job in self.jobs.jobs.values()
+                        if job.description == description and not job.finished), None)
+        if running:
+            return running.to_dict()
+        
+        async def scan(progress):
+            progress(None, f"Scanning on {interface}")
+            found = await asyncio.get_running_loop().run_in_executor(None, self.backends.wifi.scan, interface)
+            networks = []
+            for network in found:
+                entry = asdict(network)
+                entry["security"] = network.security.value
+                networks.append(entry)
+            return networks
+        
+        return self.jobs.start("ScanWifi", description, scan, self._job_timeout("scan")).to_dict()
+    
+    def _ipc_scan_lan(self, interface: Optional[str] = None) -> dict:
+        """Start a sweep of the subnet on one (or the primary) interface; returns the job"""
+        interfaces = {i.name: i for i in self.discovery.discover_interfaces()
+                      if i.interface_type != InterfaceType.LOOPBACK}
+        if not interface:
+            primary = self.desktop.primary()
+            interface = primary.interface if primary else next(
+                (name for name in interfaces if self.backends.host.interface_address(name)), None)
+        address = self.backends.host.interface_address(interface) if interface in interfaces else None
+        if not address:
+            raise IpcError(f"No connected interface with an IPv4 address: {interface}", ErrorCode.NOT_FOUND,
+                           {"interface": interface})
+        
+        description = f"Scan LAN on {interface}"
+        running = next((job for job in self.jobs.jobs.values()
+                        if job.description == description and not job.finished), None)
+        if running:
+            return running.to_dict()
+        
+        async def scan(progress):
+            progress(None, f"Sweeping {address.network} on {interface}")
+            devices = await self.backends.host.scan_lan(interface, address)
+            own = interfaces[interface]
+            return {
+                "interface": interface,
+                "network": str(address.network),
+                "devices": annotate(devices, address, own.mac, own.gateway)
+            }
+        
+        return self.jobs.start("ScanLan", description, scan, self._job_timeout("lan_scan")).to_dict()
+    
+    async def _ipc_probe_port(self, host: str, port: int, timeout: float = port_probe.DEFAULT_TIMEOUT,
+                              interface: Optional[str] = None) -> dict:
+        """Try a TCP connection to host:port, from an interface's address if one is named"""
+        if not host or not isinstance(port, int) or not 0 < port < 65536:
+            raise IpcError("Expected a host and a port from 1 to 65535", ErrorCode.INVALID_REQUEST,
+                           {"host": host, "port": port})
+        if not 0 < timeout <= port_probe.MAX_TIMEOUT:
+            raise IpcError(f"Timeout must be between 0 and {port_probe.MAX_TIMEOUT:g} seconds",
+                           ErrorCode.INVALID_REQUEST, {"timeout": timeout})
+        source = None
+        if interface:
+            address = self.backends.host.interface_address(interface)
+            if not address:
+                raise IpcError(f"No connected interface with an IPv4 address: {interface}", ErrorCode.NOT_FOUND,
+                               {"interface": interface})
+            source = str(address.ip)
+        
+        result = await self.backends.host.probe_port(host, port, source, timeout)
+        return dict(result, host=host, port=port, interface=interface, source=source)
+    
+    def _ipc_diagnose_connectivity(self, targets: Optional[List[str]] = None,
+                                   connect_timeout: Optional[float] = None) -> dict:
+        """Start an IPv4 against IPv6 check of the configured (or given) targets; returns the job"""
+        config = self.enterprise_config.get("connectivity", {})
+        targets = targets or config.get("targets", connectivity.DEFAULT_TARGETS)
+        timeout = connect_timeout or config.get("timeout", port_probe.DEFAULT_TIMEOUT)
+        if not 0 < timeout <= port_probe.MAX_TIMEOUT:
+            raise IpcError(f"Timeout must be between 0 and {port_probe.MAX_TIMEOUT:g} seconds",
+                           ErrorCode.INVALID_REQUEST, {"connect_timeout": timeout})
+        try:
+            parsed = [port_probe.parse_target(target) for target in targets]
+        except ValueError as e:
+            raise IpcError(str(e), ErrorCode.INVALID_REQUEST, {"targets": targets})
+        
+        description = "Diagnose connectivity"
+        running = next((job for job in self.jobs.jobs.values()
+                        if job.description == description and not job.finished), None)
+        if running:
+            return running.to_dict()
+        
+        async def diagnose(progress):
+            progress(None, f"Connecting to {len(parsed)} targets over IPv4 and IPv6")
+            results = await asyncio.gather(*(self.backends.host.check_families(host, port, timeout)
+                                             for host, port in parsed))
+            return connectivity.diagnose([dict(result, target=target) for target, result in zip(targets, results)])
+        
+        return self.jobs.start("DiagnoseConnectivity", description, diagnose, self._job_timeout("diagnose")).to_dict()
+    
+    def _ipc_troubleshoot(self, interface: Optional[str] = None, targets: Optional[List[str]] = None) -> dict:
+        """Start a layer-by-layer check of one (or the primary) interface; returns the job"""
+        interfaces = {i.name: i for i in self.discovery.discover_interfaces()
+                      if i.interface_type != InterfaceType.LOOPBACK}
+        if interface and interface not in interfaces:
+            raise IpcError(f"No such interface: {interface}", ErrorCode.NOT_FOUND, {"interface": interface})
+        if not interface:
+            # The link carrying traffic, else one with carrier, else any wired or WiFi one to find out why not
+            primary = self.desktop.primary()
+            managed = [i for name, i in interfaces.items() if not self.connection_manager.is_unmanaged(name)]
+            candidate = next((i for i in managed if i.status == "Connected"), None) or next(
+                (i for i in managed if i.interface_type in (InterfaceType.ETHERNET, InterfaceType.WIFI)), None)
+            interface = primary.interface if primary else candidate.name if candidate else None
+        if not interface:
+            raise IpcError("No interface to troubleshoot", ErrorCode.NOT_FOUND)
+        
+        config = self.enterprise_config.get("troubleshoot", {})
+        try:
+            troubleshooter = troubleshoot.Troubleshooter(
+                self.backends.host, config.get("name", troubleshoot.PROBE_NAME),
+                targets or config.get("targets"), config.get("timeout", troubleshoot.DEFAULT_TIMEOUT))
+        except ValueError as e:
+            raise IpcError(str(e), ErrorCode.INVALID_REQUEST, {"targets": targets})
+        
+        description = f"Troubleshoot {interface}"
+        running = next((job for job in self.jobs.jobs.values()
+                        if job.description == description and not job.finished), None)
+        if running:
+            return running.to_dict()
+        
+        iface = interfaces[interface]
+        dns = self.connection_manager.link_dns().get(interface) or iface.dns or self.discovery._get_dns_servers()
+        return self.jobs.start("Troubleshoot", description, lambda progress: troubleshooter.run(iface, dns, progress),
+                               self._job_timeout("troubleshoot")).to_dict()
+    
+    def _ipc_run_cable_test(self, interface: str) -> dict:
+        """Start a TDR test of a wired interface's pairs; returns the job"""
+        iface = next((i for i in self.discovery.discover_interfaces() if i.name == interface), None)
+        if not iface:
+            raise IpcError(f"No such interface: {interface}", ErrorCode.NOT_FOUND, {"interface": interface})
+        if iface.interface_type != InterfaceType.ETHERNET:
+            raise IpcError(f"{interface} is not a wired interface", ErrorCode.INVALID_REQUEST,
+                           {"interface": interface})
+        
+        description = f"Cable test {interface}"
+        running = next((job for job in self.jobs.jobs.values()
+                        if job.description == description and not job.finished), None)
+        if running:
+            return running.to_dict()
+        
+        async def test(progress):
+            progress(None, f"Testing the cable on {interface}; the link drops while it runs")
+            try:
+                pairs = await self.backends.host.cable_test(interface)
+            except OSError as e:
+                raise IpcError(str(e), ErrorCode.BACKEND_UNAVAILABLE, {"interface": interface})
+            result = cable_test.report(interface, pairs)
+            self.events.publish("CableTested", interface=interface, healthy=result["healthy"],
+                                summary=result["summary"])
+            return result
+        
+        return self.jobs.start("RunCableTest", description, test, self._job_timeout("cable_test")).to_dict()
+    
+    @staticmethod
+    def _bluetooth_address(mac: str) -> str:
+        try:
+            return bluetooth.normalize_address(mac or "")
+        except ValueError as e:
+            raise IpcError(str(e), ErrorCode.INVALID_REQUEST, {"mac": mac})
+    
+    async def _bluetooth_call(self, call):
+        """Await a Bluetooth backend call; a missing bluetoothctl is a backend failure"""
+        try:
+            return await call
+        except OSError as e:
+            raise IpcError(f"Bluetooth unavailable: {e}", ErrorCode.BACKEND_UNAVAILABLE)
+    
+    async def _bluetooth_device(self, address: str) -> Optional[dict]:
+        """One device as BlueZ describes it, under its local alias"""
+        return self.bluetooth_aliases.apply(await self._bluetooth_call(self.backends.bluetooth.device_info(address)))
+    
+    def _bluetooth_list(self, devices: list) -> list:
+        """Devices under their local aliases, connected first, then paired, then nearby by signal"""
+        return bluetooth.sort_devices([self.bluetooth_aliases.apply(device) for device in devices])
+    
+    async def _ipc_list_bluetooth_devices(self) -> list:
+        """Paired and recently seen Bluetooth devices, without scanning"""
+        return self._bluetooth_list(await self._bluetooth_call(self.backends.bluetooth.devices()))
+    
+    async def _ipc_get_bluetooth_device(self, mac: str) -> dict:
+        """Type, signal, advertised services and battery of one device"""
+        address = self._bluetooth_address(mac)
+        device = await self._bluetooth_device(address)
+        if not device:
+            raise IpcError(f"Unknown Bluetooth device: {address}", ErrorCode.NOT_FOUND, {"mac": address})
+        return device
+    
+    async def _ipc_set_bluetooth_alias(self, mac: str, alias: Optional[str] = None) -> dict:
+        """Name a known device locally; an empty alias restores BlueZ's name"""
+        address = self._bluetooth_address(mac)
+        if alias is not None and not isinstance(alias, str):
+            raise IpcError("alias must be a string", ErrorCode.INVALID_REQUEST, {"alias": alias})
+        if not await self._bluetooth_call(self.backends.bluetooth.device_info(address)):
+            raise IpcError(f"Unknown Bluetooth device: {address}", ErrorCode.NOT_FOUND, {"mac": address})
+        try:
+            self.bluetooth_aliases.set(address, alias)
+        except ValueError as e:
+            raise IpcError(str(e), ErrorCode.INVALID_REQUEST, {"mac": address, "alias": alias})
+        device = await self._bluetooth_device(address)
+        self.events.publish("BluetoothAliasChanged", mac=address, alias=device["alias"])
+        return device
+    
+    def _ipc_scan_bluetooth(self) -> dict:
+        """Start discovering classic and LE devices; returns the job"""
+        description = "Scan Bluetooth"
+        running = next((job for job in self.jobs.jobs.values()
+                        if job.description == description and not job.finished), None)
+        if running:
+            return running.to_dict()
+        
+        async def scan(progress):
+            progress(None, f"Discovering for {bluetooth.SCAN_SECONDS} seconds")
+            return {"devices": self._bluetooth_list(await self._bluetooth_call(self.backends.bluetooth.scan()))}
+        
+        return self.jobs.start("ScanBluetooth", description, scan, self._job_timeout("bluetooth")).to_dict()
+    
+    def _ipc_pair_bluetooth(self, mac: str) -> dict:
+        """Start pairing with a device; returns the job"""
+        address = self._bluetooth_address(mac)
+        description = f"Pair Bluetooth {address}"
+        running = next((job for job in self.jobs.jobs.values()
+                        if job.description == description and not job.finished), None)
+        if running:
+            return running.to_dict()
+        
+        async def pair(progress):
+            progress(None, f"Pairing with {address}")
+            if not await self._bluetooth_call(self.backends.bluetooth.pair(address)):
+                raise IpcError(f"Pairing with {address} failed", ErrorCode.BACKEND_UNAVAILABLE, {"mac": address})
+            self.events.publish("BluetoothPaired", mac=address)
+            return await self._bluetooth_device(address)
+        
+        return self.jobs.start("PairBluetooth", description, pair, self._job_timeout("bluetooth")).to_dict()
+    
+    def _ipc_connect_bluetooth(self, mac: str) -> dict:
+        """Start connecting a paired classic device or any LE peripheral; returns the job"""
+        address = self._bluetooth_address(mac)
+        description = f"Connect Bluetooth {address}"
+        running = next((job for job in self.jobs.jobs.values()
+                        if job.description == description and not job.finished), None)
+        if running:
+            return running.to_dict()
+        
+        async def connect(progress):
+            device = await self._bluetooth_device(address)
+            if device and device["type"] == "classic" and not device["paired"]:
+                raise IpcError(f"Pair {address} before connecting to it", ErrorCode.INVALID_REQUEST, {"mac": address})
+            progress(None, f"Connecting to {address}")
+            if not await self._bluetooth_call(self.backends.bluetooth.connect(address)):
+                raise IpcError(f"Connecting to {address} failed", ErrorCode.BACKEND_UNAVAILABLE, {"mac": address})
+            device = await self._bluetooth_device(address)
+            self.events.publish("BluetoothConnected", mac=address,
+                                name=(device["alias"] or device["name"]) if device else None,
+                                battery=device["battery"] if device else None)
+            return device
+        
+        return self.jobs.start("ConnectBluetooth", description, connect, self._job_timeout("bluetooth")).to_dict()
+    
+    def _ipc_send_bluetooth_file(self, mac: str, path: str) -> dict:
+        """Start pushing a file to a paired device over OBEX, as the requesting user; returns the job"""
+        address = self._bluetooth_address(mac)
+        if not isinstance(path, str) or not os.path.isabs(path):
+            raise IpcError("path must be absolute", ErrorCode.INVALID_REQUEST, {"path": path})
+        # obexd runs on the requester's session bus and reads the file with their permissions
+        uid = log_context.get().get("client_uid")
+        name = os.path.basename(path)
+        description = f"Send {name} to {address}"
+        running = next((job for job in self.jobs.jobs.values()
+                        if job.description == description and not job.finished), None)
+        if running:
+            return running.to_dict()
+        
+        async def send(progress):
+            device = await self._bluetooth_device(address)
+            if not device:
+                raise IpcError(f"Unknown Bluetooth device: {address}", ErrorCode.NOT_FOUND, {"mac": address})
+            if not device["paired"]:
+                raise IpcError(f"Pair {address} before sending files to it", ErrorCode.INVALID_REQUEST,
+                               {"mac": address})
+            
+            def report(transferred: int, size: Optional[int]):
+                percent = min(100, transferred * 100 // size) if size else None
+                progress(percent, f"{transferred // 1024} of {size // 1024} KiB" if size else f"{transferred} bytes")
+                self.events.publish("BluetoothFileProgress", mac=address, file=name, transferred=transferred,
+                                    size=size, percent=percent)
+            
+            progress(None, f"Waiting for {device['alias'] or device['name'] or address} to accept {name}")
+            try:
+                sent = await self._bluetooth_call(self.backends.bluetooth.send_file(address, path, report, uid))
+            except obex.TransferError as e:
+                raise IpcError(str(e), ErrorCode.BACKEND_UNAVAILABLE, {"mac": address, "path": path})
+            result = dict(sent, mac=address, device=device["alias"] or device["name"])
+            self.events.publish("BluetoothFileSent", **result)
+            return result
+        
+        return self.jobs.start("SendBluetoothFile", description, send, self._job_timeout("obex")).to_dict()
+    
+    async def _ipc_undo(self) -> dict:
+        """Revert the most recent profile edit, DNS change or location switch; it stays on the history
+        until that succeeds, so a failed undo can be retried"""
+        change = self.changes.latest()
+        if not change:
+            raise IpcError("Nothing to undo", ErrorCode.NOT_FOUND)
+        previous = change.previous
+        if change.kind == "location":
+            result = await self.locations.restore(previous["location"], previous["zones"])
+        elif not self.connection_manager.get_profile(previous["profile"]):
+            raise IpcError(f"Cannot undo \"{change.summary}\": profile {previous['profile']} is gone",
+                           ErrorCode.NOT_FOUND, {"profile": previous["profile"]})
+        elif change.kind == "dns":
+            result = await self._apply_dns(previous["interface"], previous["profile"], previous["servers"])
+        else:
+            updated = self.connection_manager.update_profile(previous["profile"], **previous["fields"])
+            result = {key: value for key, value in asdict(updated).items() if key in previous["fields"]}
+            result.pop("password", None)
+        self.changes.remove(change)
+        self.logger.info(f"Undid change {change.id}: {change.summary}")
+        self.events.publish("ChangeUndone", change=change.to_dict())
+        return {"undone": change.to_dict(), "result": result}
+    
+    async def _ipc_apply_interfaces(self, interfaces: Optional[dict] = None, dry_run: bool = False) -> dict:
+        """Add and remove static addresses and routes until each interface matches the "interfaces" config section.
+        Without interfaces, enterprise.json is read again, so edits to it apply without a restart"""
+        if interfaces is None:
+            interfaces = self._load_enterprise_config().get("interfaces")
+            if interfaces is None:
+                raise IpcError("enterprise.json has no \"interfaces\" section", ErrorCode.NOT_FOUND)
+        try:
+            desired = reconcile.parse_interfaces(interfaces)
+        except ValueError as e:
+            raise IpcError(str(e), ErrorCode.INVALID_CONFIG)
+        
+        unmanaged = [name for name in desired if self.connection_manager.is_unmanaged(name)]
+        if unmanaged:
+            raise IpcError(f"Unmanaged: {', '.join(unmanaged)}", ErrorCode.PERMISSION_DENIED, {"interfaces": unmanaged})
+        # Every interface is read before anything changes, so a typo in one name changes nothing
+        changes = []
+        for name, wanted in desired.items():
+            actual = await self.backends.ethernet.address_state(name)
+            if actual is None:
+                raise IpcError(f"No such interface: {name}", ErrorCode.NOT_FOUND, {"interface": name})
+            changes += reconcile.plan(name, wanted, actual)
+        for change in changes:
+            change["text"] = reconcile.format_change(change)
+        
+        failed = []
+        if not dry_run:
+            for change in changes:
+                error = await self.backends.ethernet.apply_change(change)
+                if error:
+                    self.logger.warning(f"apply: {change['text']}: {error}")
+                    failed.append({"change": change["text"], "error": error})
+                else:
+                    self.logger.info(f"apply: {change['text']}")
+            if changes:
+                self.events.publish("InterfacesReconciled", applied=len(changes) - len(failed), failed=failed)
+        return {"changes": changes, "dry_run": bool(dry_run), "failed": failed}
+    
+    async def _connect_boot_critical(self, name: str) -> Optional[str]:
+        """One attempt at a boot-critical profile; None when it is up, else why not"""
+        profile = self.connection_manager.get_profile(name)
+        if not profile:
+            return "the profile was deleted"
+        try:
+            self.connection_manager.begin_connect(name)
+        except (PermissionError, StateTransitionError) as e:
+            # Most likely auto-connect got there first; its outcome shows on the next try
+            return str(e)
+        try:
+            connected = await asyncio.wait_for(self.connection_manager.finish_connect(profile),
+                                               self._job_timeout("connect"))
+        except asyncio.TimeoutError:
+            return f"Connecting {name} timed out"
+        if not connected:
+            return profile.last_error or f"Failed to connect {name}"
+        return None
+    
+    def _boot_critical_up(self, name: str) -> bool:
+        profile = self.connection_manager.get_profile(name)
+        state = self.connection_manager.get_interface_state(profile.interface) if profile else None
+        return bool(state and state.status == LinkState.CONFIGURED and state.profile_name == name)
+    
+    async def _ipc_wait_boot_online(self, timeout: Optional[float] = None) -> dict:
+        """Block until every boot-critical profile is up (or timeout passes); what alopexd wait-online asks"""
+        return await self.boot.wait(timeout)
+    
+    def _job_timeout(self, kind: str) -> Optional[float]:
+        """Daemon-side limit for a kind of job from the "timeouts" config section (0 disables)"""
+        defaults = {"connect": 45, "vpn": 60, "scan": 20, "lan_scan": 30, "location": 120, "diagnose": 30,
+                    "troubleshoot": 60, "cable_test": 30, "bluetooth": 30, "obex": 600, "load_test": 180}
+        return self.enterprise_config.get("timeouts", {}).get(kind, defaults[kind]) or None
+    
+    async def _ipc_cancel_job(self, id: int) -> dict:
+        """Abort a running job, e.g. a stuck WiFi association"""
+        return await self.jobs.cancel(id)
+    
+    def _ipc_get_job(self, id: int) -> dict:
+        """Current state of a job"""
+        return self.jobs.get(id).to_dict()
+    
+    async def _ipc_wait_job(self, id: int, timeout: Optional[float] = None) -> dict:
+        """Block until a job finishes (or timeout passes) and return its state"""
+        return await self.jobs.wait(id, timeout)
+    
+    def _ipc_mock_set_carrier(self, interface: str, up: bool) -> dict:
+        """Flap a fake link (mock mode only)"""
+        if not self.mock_network.set_carrier(interface, bool(up)):
+            raise IpcError(f"No such interface: {interface}", ErrorCode.NOT_FOUND, {"interface": interface})
+        return {"interface": interface, "up": bool(up)}
+    
+    def _ipc_mock_set_link_speed(self, interface: str, speed: int, partner_max_speed: Optional[int] = None) -> dict:
+        """Renegotiate a fake link at another speed (mock mode only)"""
+        if not self.mock_network.set_link_speed(interface, speed, partner_max_speed):
+            raise IpcError(f"No such interface: {interface}", ErrorCode.NOT_FOUND, {"interface": interface})
+        return {"interface": interface, "speed": speed, "partner_max_speed": partner_max_speed}
+    
+    def _ipc_mock_roam(self, interface: str, bssid: str, signal: Optional[int] = None) -> dict:
+        """Move an associated fake WiFi link to another access point (mock mode only)"""
+        if not self.mock_network.roam(interface, bssid, signal):
+            raise IpcError(f"{interface} is not associated", ErrorCode.NOT_FOUND, {"interface": interface})
+        return {"interface": interface, "bssid": bssid, "signal": signal}
+    
+    def _ipc_get_logs(self, since: Optional[float] = None, level: Optional[str] = None,
+                      limit: Optional[int] = None) -> list:
+        """Return recent log records from the in-memory ring buffer"""
+        try:
+            return get_logs(since, level, limit)
+        except ValueError as e:
+            raise IpcError(str(e), ErrorCode.INVALID_CONFIG, {"level": level})
+    
+    def _ipc_get_events(self, since: Optional[float] = None, filter=None, limit: Optional[int] = None) -> list:
+        """Events published after since, oldest first, from the on-disk log (the in-memory history when the
+        log is off); filter takes name patterns and field=value terms, as a list or comma-separated"""
+        if limit is not None and (not isinstance(limit, int) or limit < 1):
+            raise IpcError("limit must be a positive integer", ErrorCode.INVALID_REQUEST, {"limit": limit})
+        try:
+            if self.events.log:
+                return self.events.log.query(since, filter, limit)
+            kinds, fields = parse_event_filter(filter)
+        except ValueError as e:
+            raise IpcError(str(e), ErrorCode.INVALID_REQUEST, {"filter": filter})
+        events = [event for event in self.events.recent()
+                  if (since is None or event["timestamp"] > since) and event_matches(event, kinds, fields)]
+        return events[-limit:] if limit else events
+    
+    def _ipc_get_unmanaged(self) -> dict:
+        """List unmanaged patterns and the interfaces they currently cover"""
+        interfaces = [
+            iface.name for iface in self.discovery.discover_interfaces()
+            if self.connection_manager.is_unmanaged(iface.name)
+        ]
+        return {
+            "patterns": self.connection_manager.unmanaged_patterns,
+            "interfaces": interfaces
+        }
+    
+    def _ipc_set_unmanaged(self, interface: str, unmanaged: bool = True) -> dict:
+        """Toggle management of an interface until the daemon restarts"""
+        if not any(iface.name == interface for iface in self.discovery.discover_interfaces()):
+            raise IpcError(f"No such interface: {interface}", ErrorCode.NOT_FOUND, {"interface": interface})
+        
+        self.connection_manager.set_unmanaged(interface, bool(unmanaged))
+        return {"interface": interface, "unmanaged": bool(unmanaged)}
+    
+    def _ipc_set_interface_label(self, interface: str, label: Optional[str] = None,
+                                 groups: Optional[List[str]] = None) -> dict:
+        """Change an interface's label and groups; None keeps either, "" and [] clear them"""
+        present = [iface.name for iface in self.discovery.discover_interfaces()]
+        if interface not in present and interface not in {entry["interface"] for entry in self.labels.list()}:
+            raise IpcError(f"No such interface: {interface}", ErrorCode.NOT_FOUND, {"interface": interface})
+        if groups is not None and not (isinstance(groups, list) and all(isinstance(g, str) for g in groups)):
+            raise IpcError("groups must be a list of names", ErrorCode.INVALID_REQUEST, {"groups": groups})
+        if label is None:
+            label = self.labels.label(interface)
+        if groups is None:
+            groups = self.labels.groups(interface)
+        try:
+            entry = self.labels.set(interface, label, groups, known=present)
+        except LabelError as e:
+            raise IpcError(str(e), ErrorCode.INVALID_CONFIG, {"interface": interface, "label": label})
+        self.events.publish("InterfaceLabelChanged", **entry)
+        return entry
+    
+    async def _ipc_rename_interface(self, to: str, persist: bool = True, **params) -> dict:
+        """RenameInterface {from, to}: a new kernel name now, and with persist a .link file so it stays"""
+        if set(params) != {"from"} or not isinstance(params["from"], str):
+            raise IpcError("RenameInterface takes from and to", ErrorCode.INVALID_REQUEST, {"params": sorted(params)})
+        source = self.labels.resolve(params["from"])
+        interfaces = {iface.name: iface for iface in self.discovery.discover_interfaces()}
+        iface = interfaces.get(source)
+        if not iface:
+            raise IpcError(f"No such interface: {source}", ErrorCode.NOT_FOUND, {"interface": source})
+        if not isinstance(to, str) or not link_names.valid_name(to):
+            raise IpcError(f"Invalid interface name: {to!r} (up to 15 characters, no '/', ':' or spaces)",
+                           ErrorCode.INVALID_CONFIG, {"to": to})
+        if to in interfaces:
+            raise IpcError(f"{to} already exists", ErrorCode.INVALID_CONFIG, {"to": to})
+        if self.connection_manager.is_unmanaged(source):
+            raise IpcError(f"Interface {source} is unmanaged", ErrorCode.PERMISSION_DENIED, {"interface": source})
+        if self.connection_manager.get_link_state(source) not in (LinkState.DISCONNECTED, LinkState.FAILED):
+            raise IpcError(f"Disconnect {source} before renaming it", ErrorCode.BUSY,
+                           {"interface": source, "state": self.connection_manager.get_link_state(source).value})
+        mac = iface.permanent_mac or iface.mac
+        if persist and not mac:
+            raise IpcError(f"{source} has no MAC address to match at boot; rename it with persist false",
+                           ErrorCode.INVALID_REQUEST, {"interface": source})
+        
+        ok, message = await self.backends.ethernet.rename(source, to)
+        if not ok:
+            raise IpcError(f"Cannot rename {source}: {message}", ErrorCode.BACKEND_UNAVAILABLE, {"interface": source})
+        self.logger.info(f"Interface {source} renamed to {to}: {message}")
+        profiles = self.connection_manager.rename_interface(source, to)
+        self.labels.rename(source, to)
+        
+        result = {"from": source, "to": to, "message": message, "profiles": profiles, "link_file": None}
+        if persist:
+            link_dir = self.enterprise_config.get("rename", {}).get("link_dir")
+            link_dir = Path(link_dir) if link_dir else (
+                self.state_path / "systemd-network" if self.mock_network else link_names.LINK_DIR)
+            try:
+                path, _ = link_names.write_link_file(link_dir, to, mac, bool(iface.permanent_mac))
+                result["link_file"] = str(path)
+            except OSError as e:
+                # The rename itself stands; only the boot-time name is missing
+                self.logger.warning(f"Renamed {source} to {to} but could not persist it: {e}")
+                result["persist_error"] = str(e)
+        self.events.publish("InterfaceRenamed", **{"from": source, "to": to})
+        return result
+    
+    def _ipc_set_log_level(self, level: str, module: Optional[str] = None) -> dict:
+        """Change the daemon or a single module's log level at runtime"""
+        try:
+            set_log_level(level, module)
+        except ValueError as e:
+            raise IpcError(str(e), ErrorCode.INVALID_CONFIG, {"level": level})
+        
+        self.logger.info(f"Log level for {module or 'daemon'} set to {level.upper()}")
+        return get_log_levels()
+    
     def _load_saved_connections(self) -> Dict[str, dict]:
         """Load saved network connections"""
         connections_file = self.state_path / "connections.json"
@@ -186,13 +2817,18 @@ class AlopexDaemon:
                 current_interfaces = {
                     iface.name: iface for iface in self.discovery.discover_interfaces()
                 }
+                self.metrics_history.record(current_interfaces.values())
+                self.bandwidth_history.record(current_interfaces.values())
                 
                 # Detect new interfaces
                 for name, interface in current_interfaces.items():
+                    if self.connection_manager.is_unmanaged(name):
+                        continue
+                    
                     if name not in previous_interfaces:
                         self.logger.info(f"New interface detected: {name} ({interface.interface_type})")
                         # Attempt auto-connection for new interfaces
-                        if interface.interface_type in ["WiFi", "Ethernet"]:
+                        if interface.interface_type in (InterfaceType.WIFI, InterfaceType.ETHERNET):
                             await self.auto_connect_networks()
                     
                     # Detect status changes
@@ -227,7 +2863,7 @@ class AlopexDaemon:
                 }
                 
                 # Write to telemetry file for collection
-                telemetry_file = Path("/var/lib/alopex/telemetry.json")
+                telemetry_file = self.state_path / "telemetry.json"
                 with open(telemetry_file, 'w') as f:
                     json.dump(telemetry_data, f)
                 
@@ -253,15 +2889,88 @@ class AlopexDaemon:
         
         self.running = True
         
+        # Accept control requests before the (slow) initial connect
+        await self.ipc.start()
+        await self._start_remote_ipc()
+        
+        if self.snmp:
+            snmp_config = self.enterprise_config.get("snmp", {})
+            await self.snmp.start(snmp_config.get("listen", "127.0.0.1"), snmp_config.get("port", SNMP_PORT))
+        
+        # Adopt connections left up by a previous instance before touching anything
+        restored = await self.connection_manager.restore_states()
+        if restored:
+            summary = ", ".join(f"{name}={outcome}" for name, outcome in restored.items())
+            self.logger.info(f"Restored connection state: {summary}")
+        
+        # Channels and transmit power must be right before the first association
+        self._apply_regulatory_domain()
+        await self._apply_mptcp_limits()
+        
+        # Boot-critical profiles go before everything else that can wait
+        boot_critical = [{"name": p.name, "interface": p.interface}
+                         for p in self.connection_manager.list_profiles() if p.boot_critical]
+        boot_task = asyncio.create_task(self.boot.run(boot_critical))
+        
+        # Resolve through the stub before anything connects
+        if self.dns_forwarder:
+            failed = await self.dns_forwarder.start()
+            if failed:
+                self.logger.error(f"DNS forwarder could not listen on {', '.join(failed)}")
+        
+        # Container interfaces are known before anything is connected
+        if self.containers:
+            await self.containers.refresh()
+        
         # Initial network auto-connection
         await self.auto_connect_networks()
         
         # Start background tasks
         tasks = [
+            boot_task,
             asyncio.create_task(self.monitor_network_changes()),
             asyncio.create_task(self.export_telemetry()),
             asyncio.create_task(self.connection_manager.monitor_connections()),
+            asyncio.create_task(self.locations.run()),
+            asyncio.create_task(self.sessions.run()),
+            asyncio.create_task(self.carrier.run()),
+            asyncio.create_task(self.dns_health.run()),
+            asyncio.create_task(self.sla.run()),
+            asyncio.create_task(self.rfkill.run()),
+            asyncio.create_task(self.hotspot.run()),
+            asyncio.create_task(self.cert_expiry.run()),
+            asyncio.create_task(self.link_speed.run()),
+            asyncio.create_task(self.profile_services.run()),
+            asyncio.create_task(self.desktop.run()),
         ]
+        if self.failover.groups:
+            tasks.append(asyncio.create_task(self.failover.run()))
+        if self.influx:
+            tasks.append(asyncio.create_task(self.influx.run()))
+        if self.syslog:
+            tasks.append(asyncio.create_task(self.syslog.run()))
+        if self.quota:
+            tasks.append(asyncio.create_task(self.quota.run()))
+        if self.notifier:
+            tasks.append(asyncio.create_task(self.notifier.run()))
+        if self.relay:
+            tasks.append(asyncio.create_task(self.relay.run()))
+        if self.unit_traffic:
+            tasks.append(asyncio.create_task(self.unit_traffic.run()))
+        if self.fast_counters:
+            tasks.append(asyncio.create_task(self.fast_counters.run()))
+        if self.containers:
+            tasks.append(asyncio.create_task(self.containers.run()))
+        if self.virtual_networks:
+            tasks.append(asyncio.create_task(self.virtual_networks.run()))
+        if self.wifi_watch:
+            tasks.append(asyncio.create_task(self.wifi_watch.run()))
+        if self.roaming:
+            tasks.append(asyncio.create_task(self.roaming.run(watch=not self.wifi_watch)))
+        if self.dns_forwarder:
+            tasks.append(asyncio.create_task(self.backends.host.watch(self.discovery._get_dns_servers)))
+        if self.mock_network:
+            tasks.append(asyncio.create_task(self.mock_network.run_script()))
         
         # Main event loop
         try:
@@ -274,20 +2983,187 @@ class AlopexDaemon:
             self.logger.info("Shutting down ALOPEX daemon")
             for task in tasks:
                 task.cancel()
+            self.connection_manager.cancel_retries()
+            self.connection_manager.cancel_announcements()
             await asyncio.gather(*tasks, return_exceptions=True)
+            await self.failover.stop()
+            await self.ipc.stop()
+            if self.snmp:
+                self.snmp.stop()
+            if self.unit_traffic:
+                await self.unit_traffic.stop()
+            if self.fast_counters:
+                await self.fast_counters.stop()
+            
+            if self.dns_forwarder:
+                # Nothing answers on the stub any more; hand resolv.conf the upstreams themselves
+                upstreams = self.dns_forwarder.usable_upstreams()
+                await self.dns_forwarder.stop()
+                if upstreams:
+                    await self.backends.host.host.set_dns(upstreams)
             
             # Save state
+            self.bandwidth_history.flush()
             self._save_connections()
 
+def print_status(args) -> int:
+    """alopexd status [--logs] [--json]"""
+    client = IpcClient(args.socket)
+    try:
+        status = client.request("GetStatus")
+        if args.logs:
+            status["logs"] = client.request("GetLogs", level=args.level, limit=args.lines)
+    except IpcError as e:
+        if args.json:
+            print(json.dumps({"error": e.to_reply()}, indent=2))
+        elif e.code == ErrorCode.DAEMON_UNAVAILABLE:
+            print(f"alopexd is not running: {e}", file=sys.stderr)
+        else:
+            print(f"alopexd status failed: {e}", file=sys.stderr)
+        return 3 if e.code == ErrorCode.DAEMON_UNAVAILABLE else 1
+    
+    if args.json:
+        print(json.dumps(status, indent=2, sort_keys=True))
+        return 0
+    
+    uptime = int(status["uptime"])
+    mode = " [mock]" if status.get("mock") else ""
+    print(f"alopexd running{mode} (pid {status['pid']}, up {uptime // 3600}h {uptime % 3600 // 60}m)")
+    print(f"Profiles: {status['profiles']}  Connected interfaces: {status['connected_interfaces']}")
+    print("Backends: " + ", ".join(f"{kind}={name}" for kind, name in status["backends"].items()))
+    
+    boot = status.get("boot", {})
+    if boot.get("profiles"):
+        waiting = [entry["profile"] for entry in boot["profiles"] if not entry["up"]]
+        print("Boot-critical: " + ("online" if boot["online"] else "waiting for " + ", ".join(waiting)))
+    
+    if args.logs:
+        print("")
+        for record in status["logs"]:
+            timestamp = time.strftime("%Y-%m-%d %H:%M:%S", time.localtime(record["timestamp"]))
+            print(f"{timestamp} [{record['level']}] {record['target']}: {record['message']}")
+    
+    return 0
+
+def apply_interfaces(args) -> int:
+    """alopexd apply [--dry-run] [--config FILE] [--json]: print the diff, then make it"""
+    client = IpcClient(args.socket)
+    params = {}
+    if args.config:
+        try:
+            with open(args.config) as f:
+                params["interfaces"] = json.load(f).get("interfaces") or {}
+        except (OSError, ValueError) as e:
+            print(f"Cannot read {args.config}: {e}", file=sys.stderr)
+            return 2
+    try:
+        plan = client.request("ApplyInterfaces", dry_run=True, **params)
+        for change in plan["changes"]:
+            if not args.json:
+                print(change["text"])
+        if args.dry_run or not plan["changes"]:
+            result = plan
+        else:
+            result = client.request("ApplyInterfaces", **params)
+    except OSError as e:
+        print(f"Cannot reach alopexd: {e}", file=sys.stderr)
+        return 3
+    except IpcError as e:
+        if args.json:
+            print(json.dumps({"error": e.to_reply()}, indent=2))
+        elif e.code == ErrorCode.DAEMON_UNAVAILABLE:
+            print(f"alopexd is not running: {e}", file=sys.stderr)
+        else:
+            print(f"alopexd apply failed: {e}", file=sys.stderr)
+        return 3 if e.code == ErrorCode.DAEMON_UNAVAILABLE else 1
+    
+    if args.json:
+        print(json.dumps(result, indent=2, sort_keys=True))
+    elif not result["changes"]:
+        print("Interfaces already match the config")
+    elif not args.dry_run:
+        for failure in result["failed"]:
+            print(f"Failed: {failure['change']}: {failure['error']}", file=sys.stderr)
+        print(f"Applied {len(result['changes']) - len(result['failed'])} of {len(result['changes'])} changes")
+    # Like diff(1), a dry run with something to change exits 1, so CI can catch drift
+    return 1 if result["failed"] or args.dry_run and result["changes"] else 0
+
+def wait_online(args) -> int:
+    """alopexd wait-online [--timeout N]: block until the boot-critical profiles are up (what
+    alopex-wait-online.service runs, holding network-online.target back); 0 once online, 1 on timeout"""
+    deadline = time.monotonic() + args.timeout
+    while True:
+        remaining = deadline - time.monotonic()
+        # A little extra so the daemon's own timeout answers before the socket's does
+        client = IpcClient(args.socket, timeout=max(remaining, 0) + 5)
+        try:
+            boot = client.request("WaitBootOnline", timeout=max(remaining, 0))
+            break
+        except IpcError as e:
+            # The daemon may still be starting; keep trying until the deadline
+            if e.code != ErrorCode.DAEMON_UNAVAILABLE or remaining <= 0:
+                print(f"alopexd wait-online failed: {e}", file=sys.stderr)
+                return 1
+            time.sleep(min(0.5, remaining))
+    
+    if boot["online"]:
+        return 0
+    for entry in boot["profiles"]:
+        if not entry["up"]:
+            print(f"{entry['profile']} ({entry['interface']}) not up after {entry['attempts']} attempts: "
+                  f"{entry['last_error'] or 'connecting'}", file=sys.stderr)
+    return 1
+
 def main():
     """Main entry point"""
+    parser = argparse.ArgumentParser(prog="alopexd", description="ALOPEX network management daemon")
+    parser.add_argument("--socket", type=Path, default=SOCKET_PATH, help="Control socket path")
+    parser.add_argument("--mock", action="store_true",
+                        help="Run against an in-memory fake network (no root needed)")
+    parser.add_argument("--scenario", type=Path, help="Mock scenario JSON (links, wifi_networks, script...)")
+    parser.add_argument("--state-dir", type=Path,
+                        help="Mock mode config/state directory (default: a fresh temporary directory)")
+    commands = parser.add_subparsers(dest="command")
+    
+    status_parser = commands.add_parser("status", help="Show daemon status")
+    status_parser.add_argument("--logs", action="store_true", help="Include recent daemon log records")
+    status_parser.add_argument("--level", default=None, help="Minimum log level to show")
+    status_parser.add_argument("-n", "--lines", type=int, default=50, help="Number of log records to show")
+    status_parser.add_argument("--json", action="store_true", help="Print machine-readable JSON")
+    
+    apply_parser = commands.add_parser("apply", help="Reconcile addresses and routes with the \"interfaces\" config")
+    apply_parser.add_argument("--dry-run", action="store_true", help="Only print what would change")
+    apply_parser.add_argument("--config", type=Path,
+                              help="Read the interfaces section from this file instead of enterprise.json")
+    apply_parser.add_argument("--json", action="store_true", help="Print machine-readable JSON")
+    
+    wait_parser = commands.add_parser("wait-online", help="Wait until the boot-critical profiles are up")
+    wait_parser.add_argument("--timeout", type=float, default=120, help="Give up after this many seconds")
+    
+    args = parser.parse_args()
+    
+    if args.command == "status":
+        sys.exit(print_status(args))
+    if args.command == "apply":
+        sys.exit(apply_interfaces(args))
+    if args.command == "wait-online":
+        sys.exit(wait_online(args))
+    
+    if args.mock:
+        network = FakeNetwork.from_file(args.scenario) if args.scenario else FakeNetwork()
+        state_dir = args.state_dir or Path(tempfile.mkdtemp(prefix="alopex-mock-"))
+        daemon = AlopexDaemon(mock_network=network, state_dir=state_dir, socket_path=args.socket)
+        daemon.logger.info(f"Mock mode: state in {state_dir}, socket {args.socket}")
+        asyncio.run(daemon.run())
+        return
+    
     # Ensure we're running as root for network management
     if os.getuid() != 0:
         print("ALOPEX daemon must be run as root", file=sys.stderr)
         sys.exit(1)
     
     # Create daemon and run
-    daemon = AlopexDaemon()
+    daemon = AlopexDaemon(socket_path=args.socket)
     asyncio.run(daemon.run())
 
 if __name__ == "__main__":
@@ -0,0 +1,33 @@
+#!/usr/bin/env python3
+"""
+ALOPEX OUI Table Builder
+Compresses IEEE's MA-L, MA-M and MA-S registries into the vendor table that
+network/oui.py falls back to when the system ships no OUI list (make oui-table)
+"""
+
+import sys
+import argparse
+from pathlib import Path
+
+# Run from the source tree: the table lands next to the network package
+sys.path.insert(0, str(Path(__file__).parent.parent / "alopex-qt"))
+
+from network.oui import BUNDLED_TABLE, build_table
+
+def main() -> int:
+    parser = argparse.ArgumentParser(description="Generate the bundled OUI vendor table")
+    parser.add_argument("sources", nargs="+", type=Path,
+                        help="oui.csv, mam.csv, oui36.csv or an oui.txt; earlier files win on duplicates")
+    parser.add_argument("-o", "--output", type=Path, default=BUNDLED_TABLE, help=f"default: {BUNDLED_TABLE}")
+    args = parser.parse_args()
+
+    try:
+        count = build_table(args.sources, args.output)
+    except OSError as e:
+        print(f"build-oui-table: {e}", file=sys.stderr)
+        return 1
+    print(f"Wrote {count} prefixes to {args.output}")
+    return 0
+
+if __name__ == "__main__":
+    sys.exit(main())
@@ -9,7 +9,6 @@ import os
 import json
 import uuid
 import logging
-import asyncio
 import subprocess
 from pathlib import Path
 from typing import List, Dict, Optional, Tuple
@@ -31,6 +30,7 @@ class LinkState(Enum):
     CONNECTED = "connected"
     DISCONNECTED = "disconnected" 
     CONNECTING = "connecting"
+    DEACTIVATING = "deactivating"
     UNAVAILABLE = "unavailable"
     UNMANAGED = "unmanaged"
 
@@ -62,10 +62,8 @@ def _configure_sys_path():
 _configure_sys_path()
 
 try:
-    from network.discovery import NetworkDiscovery
-    from network.system_integration import NetworkControl
     from network.wifi import WiFiManager
-    from network.connection_manager import ConnectionManager
+    from network.ipc import IpcClient, IpcError, ErrorCode, SOCKET_PATH
 except ImportError as e:
     logger.exception("Failed to import ALOPEX core modules")
     print(f"ALOPEX nmcli shim: failed to import core modules: {e}", file=sys.stderr)
@@ -76,16 +74,46 @@ def deterministic_uuid_for_name(name: str) -> str:
     """Generate deterministic UUID for connection name (fixes hash() randomization)"""
     return str(uuid.uuid5(UUID_NAMESPACE, name))
 
-def _map_interface_state(status: str) -> str:
-    """Map ALOPEX interface status to nmcli states"""
+def _map_interface_state(iface: Dict) -> str:
+    """Map an ALOPEX interface (GetInterfaces entry) to its nmcli device state"""
+    if not iface["managed"]:
+        return LinkState.UNMANAGED.value
+    
+    connection = iface.get("connection") or {}
     state_mapping = {
-        "Connected": LinkState.CONNECTED.value,
-        "Disconnected": LinkState.DISCONNECTED.value,
-        "Connecting": LinkState.CONNECTING.value,
-        "Down": LinkState.UNAVAILABLE.value,
-        "Up": LinkState.DISCONNECTED.value,
+        "configured": LinkState.CONNECTED.value,
+        "acquiring": LinkState.CONNECTING.value,
+        "deactivating": LinkState.DEACTIVATING.value
     }
-    return state_mapping.get(status, LinkState.UNAVAILABLE.value)
+    if connection.get("status") in state_mapping:
+        return state_mapping[connection["status"]]
+    if iface["status"] == "Down":
+        return LinkState.UNAVAILABLE.value
+    return LinkState.DISCONNECTED.value
+
+# ALOPEX InterfaceType -> nmcli device TYPE
+DEVICE_TYPES = {
+    "Ethernet": "ethernet",
+    "WiFi": "wifi",
+    "Cellular": "gsm",
+    "VPN": "wireguard",
+    "Tun": "tun",
+    "Bridge": "bridge",
+    "Bond": "bond",
+    "Vlan": "vlan",
+    "Loopback": "loopback",
+    "Other": "generic"
+}
+
+def _device_type(iface: Dict) -> str:
+    return DEVICE_TYPES.get(iface["interface_type"], iface["interface_type"].lower())
+
+def _active_profile(iface: Dict) -> Optional[str]:
+    """Profile name nmcli would show in the CONNECTION column"""
+    connection = iface.get("connection") or {}
+    if connection.get("status") in ("configured", "acquiring", "deactivating"):
+        return connection.get("profile_name")
+    return None
 
 def _log_invocation():
     """Log nmcli shim invocation for telemetry"""
@@ -98,7 +126,7 @@ def _log_invocation():
         if skip_next:
             safe_args.append("[REDACTED]")
             skip_next = False
-        elif arg in ["--password", "-p"]:
+        elif arg in ["--password", "-p", "password"]:
             safe_args.append(arg)
             skip_next = True
         else:
@@ -109,246 +137,272 @@ def _log_invocation():
 class NmcliCompat:
     """TITANIUM-grade NetworkManager CLI compatibility layer"""
     
-    def __init__(self):
-        self.discovery = NetworkDiscovery()
-        self.control = NetworkControl()
+    def __init__(self, socket_path: Optional[Path] = None):
         self.wifi = WiFiManager()
-        self.conn_mgr = ConnectionManager()
+        self.daemon = IpcClient(socket_path or SOCKET_PATH, timeout=5.0)
         self.quiet = os.getenv("ALOPEX_NMCLI_QUIET") is not None
         self.debug = os.getenv("ALOPEX_DEBUG") is not None
     
+    def _error(self, message: str, code: int) -> int:
+        """Print an nmcli-style error and return its exit status"""
+        if not self.quiet:
+            print(f"Error: {message}", file=sys.stderr)
+        return code
+    
+    def _ipc_error(self, action: str, e: IpcError, default: int = 1) -> int:
+        """Report a daemon error with the exit status nmcli uses for it"""
+        if e.code == ErrorCode.DAEMON_UNAVAILABLE:
+            return self._error("alopexd is not running.", 8)
+        if e.code == ErrorCode.NOT_FOUND:
+            return self._error(f"{action}: {e}", 10)
+        if e.code == ErrorCode.TIMED_OUT:
+            return self._error(f"{action}: timeout expired.", 3)
+        if e.code in (ErrorCode.INVALID_REQUEST, ErrorCode.INVALID_CONFIG):
+            return self._error(f"{action}: {e}", 2)
+        return self._error(f"{action}: {e}", default)
+    
+    def _first_wifi_device(self) -> Optional[str]:
+        interfaces = self.daemon.request("GetInterfaces")
+        wifi_interfaces = [i["name"] for i in interfaces if _device_type(i) == "wifi"]
+        return wifi_interfaces[0] if wifi_interfaces else None
+    
     def device_status(self, args) -> int:
         """nmcli device status"""
         try:
-            interfaces = self.discovery.discover_interfaces()
-        except Exception as e:
-            logger.exception("Failed to discover interfaces")
-            if not self.quiet:
-                print(f"Error: failed to list devices: {e}", file=sys.stderr)
-            return 1
+            interfaces = self.daemon.request("GetInterfaces")
+        except IpcError as e:
+            return self._ipc_error("failed to list devices", e)
         
         if args.get('terse', False):
             # Terse output format - strict colon separation, no extras
             for iface in interfaces:
-                state = _map_interface_state(iface.status)
-                conn_name = iface.name if state == "connected" else ""
-                print(f"{iface.name}:{iface.interface_type.lower()}:{state}:{conn_name}")
+                state = _map_interface_state(iface)
+                conn_name = _active_profile(iface) or ""
+                print(f"{iface['name']}:{_device_type(iface)}:{state}:{conn_name}")
         else:
             # Human-readable format matching nmcli exactly
             print("DEVICE   TYPE      STATE         CONNECTION")
             
             for iface in interfaces:
-                state = _map_interface_state(iface.status)
-                conn_name = iface.name if state == "connected" else "--"
-                device_type = iface.interface_type.lower()
+                state = _map_interface_state(iface)
+                conn_name = _active_profile(iface) or "--"
+                device_type = _device_type(iface)
                 
-                print(f"{iface.name:<8} {device_type:<8} {state:<12} {conn_name}")
+                print(f"{iface['name']:<8} {device_type:<8} {state:<12} {conn_name}")
         
         return 0
     
     def device_wifi_list(self, args) -> int:
         """nmcli device wifi list"""
         device = args.get('device')
-        if not device:
-            # Find first WiFi device
-            try:
-                interfaces = self.discovery.discover_interfaces()
-                wifi_interfaces = [i for i in interfaces if i.interface_type.lower() == "wifi"]
-                if not wifi_interfaces:
-                    if not self.quiet:
-                        print("No Wi-Fi devices found", file=sys.stderr)
-                    return 1
-                device = wifi_interfaces[0].name
-            except Exception as e:
-                logger.exception("Failed to find WiFi devices")
-                if not self.quiet:
-                    print(f"Error: failed to list devices: {e}", file=sys.stderr)
-                return 1
-        
         try:
-            # Use real ALOPEX WiFi scanning
-            networks = self.wifi.scan_networks(device)
+            device = device or self._first_wifi_device()
+            if not device:
+                return self._error("No Wi-Fi device found.", 10)
             
-            if args.get('terse', False):
-                # Terse format: SSID:MODE:CHAN:RATE:SIGNAL:BARS:SECURITY
-                for net in networks:
-                    # Convert signal strength to bars (dBm to relative)
-                    signal_percent = max(0, min(100, (net.signal_strength + 100) * 2))
-                    bars = "*" * min(4, max(1, signal_percent // 25))
-                    freq_mhz = net.frequency if net.frequency else "2412"
-                    channel = self._freq_to_channel(freq_mhz)
-                    print(f"{net.ssid}:Infra:{channel}:54 Mbit/s:{signal_percent}:{bars}:{net.security}")
-            else:
-                # Human format matching nmcli exactly
-                print("*  SSID               MODE   CHAN  RATE        SIGNAL  BARS  SECURITY")
-                for net in networks:
-                    # Convert signal strength to bars and percentage
-                    signal_percent = max(0, min(100, (net.signal_strength + 100) * 2))
-                    bars = "*" * min(4, max(1, signal_percent // 25))
-                    active = "*" if net.connected else " "
-                    freq_mhz = net.frequency if net.frequency else "2412"
-                    channel = self._freq_to_channel(freq_mhz)
-                    print(f"{active}  {net.ssid:<17} {'Infra':<6} {channel:<4} {'54 Mbit/s':<11} {signal_percent:<6} {bars:<4}  {net.security}")
+            networks = self.daemon.run_job("ScanWifi", interface=device)
+        except IpcError as e:
+            return self._ipc_error(f"failed to scan Wi-Fi networks on '{device}'", e)
+        
+        if args.get('terse', False):
+            # Terse format: SSID:MODE:CHAN:RATE:SIGNAL:BARS:SECURITY
+            for net in networks:
+                signal_percent = net["quality_percent"]
+                bars = "*" * min(4, max(1, signal_percent // 25))
+                channel = net["channel"] or self._freq_to_channel(net["frequency"] or "2412")
+                print(f"{net['ssid']}:Infra:{channel}:54 Mbit/s:{signal_percent}:{bars}:{net['security']}")
+        else:
+            # Human format matching nmcli exactly
+            print("*  SSID               MODE   CHAN  RATE        SIGNAL  BARS  SECURITY")
+            for net in networks:
+                signal_percent = net["quality_percent"]
+                bars = "*" * min(4, max(1, signal_percent // 25))
+                active = "*" if net["connected"] else " "
+                channel = net["channel"] or self._freq_to_channel(net["frequency"] or "2412")
+                print(f"{active}  {net['ssid']:<17} {'Infra':<6} {channel:<4} {'54 Mbit/s':<11} {signal_percent:<6} {bars:<4}  {net['security']}")
+        
+        return 0
+    
+    def device_wifi_connect(self, args) -> int:
+        """nmcli device wifi connect <ssid> [password <password>] [ifname <device>] [name <name>]"""
+        ssid = args.get('ssid')
+        if not ssid:
+            return self._error("SSID required", 2)
+        
+        try:
+            device = args.get('device') or self._first_wifi_device()
+            if not device:
+                return self._error("No Wi-Fi device found.", 10)
             
-            return 0
+            password = args.get('password')
+            existing = next((p for p in self.daemon.request("ListProfiles", interface=device)
+                             if p["connection_type"] == "wifi" and p["ssid"] == ssid), None)
+            name = args.get('name') or (existing["name"] if existing else ssid)
+            if not existing or password is not None or name != existing["name"]:
+                self.daemon.request("CreateProfile", name=name, interface=device, connection_type="wifi",
+                                    method="dhcp", ssid=ssid, password=password, replace=True)
             
-        except Exception as e:
-            logger.exception(f"WiFi scan failed for device {device}")
-            if not self.quiet:
-                print(f"Error: failed to scan Wi-Fi networks on '{device}': {e}", file=sys.stderr)
-                if not self.quiet and not args.get('terse', False):
-                    print("Note: Use 'alopex-gui' for advanced WiFi management", file=sys.stderr)
-            return 1
+            self.daemon.run_job("Connect", profile=name)
+        except IpcError as e:
+            return self._ipc_error("Connection activation failed", e, default=4)
+        
+        if not args.get('quiet', False):
+            print(f"Device '{device}' successfully activated with '{deterministic_uuid_for_name(name)}'.")
+        return 0
     
     def device_connect(self, args) -> int:
         """nmcli device connect <device>"""
         device = args.get('device')
         if not device:
-            if not self.quiet:
-                print("Error: device name required", file=sys.stderr)
-            return 2
+            return self._error("device name required", 2)
         
         try:
-            # Use real ALOPEX connection management via asyncio
-            success = asyncio.run(self._async_connect_device(device))
-            msg = f"Device {device} connected successfully" if success else f"Failed to connect {device}"
-            if success:
-                if not args.get('quiet', False):
-                    print(f"Device '{device}' successfully connected.")
-                return 0
-            else:
-                if not self.quiet:
-                    print(f"Error: failed to connect device '{device}': {msg}", file=sys.stderr)
-                return 1
-        except Exception as e:
-            logger.exception(f"Device connect failed for {device}")
-            if not self.quiet:
-                print(f"Error: failed to connect device '{device}': {e}", file=sys.stderr)
-            return 1
+            # Highest-priority profile for the device; ListProfiles is sorted by priority
+            profiles = self.daemon.request("ListProfiles", interface=device)
+            if not profiles:
+                return self._error(f"Device '{device}' has no connection profile.", 10)
+            self.daemon.run_job("Connect", profile=profiles[0]["name"])
+        except IpcError as e:
+            return self._ipc_error(f"failed to connect device '{device}'", e, default=4)
+        
+        if not args.get('quiet', False):
+            print(f"Device '{device}' successfully activated with '{deterministic_uuid_for_name(profiles[0]['name'])}'.")
+        return 0
     
-    def connection_show(self, args) -> int:
-        """nmcli connection show"""
+    def device_disconnect(self, args) -> int:
+        """nmcli device disconnect <device>"""
+        device = args.get('device')
+        if not device:
+            return self._error("device name required", 2)
+        
         try:
-            # Use real ALOPEX connection management
-            profiles = self.conn_mgr.get_profiles()
-            connections = [(p.name, p.connection_type, p.interface) for p in profiles.values()]
-            
-            if args.get('terse', False):
-                # Terse format: NAME:UUID:TYPE:DEVICE
-                for name, conn_type, device in connections:
-                    conn_uuid = deterministic_uuid_for_name(name)
-                    device_str = device if device else ""
-                    print(f"{name}:{conn_uuid}:{conn_type}:{device_str}")
-            else:
-                # Human-readable format matching nmcli exactly
-                print("NAME                UUID                                  TYPE      DEVICE")
-                
-                for name, conn_type, device in connections:
-                    conn_uuid = deterministic_uuid_for_name(name)
-                    device_str = device if device else "--"
-                    print(f"{name:<18} {conn_uuid}  {conn_type:<8} {device_str}")
-            
-            if not connections and not args.get('terse', False) and not self.quiet:
-                if not self.quiet:
-                    print("Note: Use 'alopexctl' to create and manage connections", file=sys.stderr)
-            
+            self.daemon.request("Disconnect", interface=device)
+        except IpcError as e:
+            return self._ipc_error(f"Device '{device}' disconnect failed", e, default=6)
+        
+        if not args.get('quiet', False):
+            print(f"Device '{device}' successfully disconnected.")
+        return 0
+    
+    def device_set(self, args) -> int:
+        """nmcli device set <device> managed yes|no"""
+        device = args.get('device')
+        managed = args.get('managed')
+        if not device or managed not in ['yes', 'no', 'true', 'false', 'on', 'off']:
+            return self._error("usage: device set <device> managed yes|no", 2)
+        
+        try:
+            self.daemon.request("SetUnmanaged", interface=device,
+                                unmanaged=managed in ['no', 'false', 'off'])
             return 0
+        except IpcError as e:
+            if e.code == ErrorCode.NOT_FOUND:
+                return self._error(f"Device '{device}' not found.", 10)
+            return self._ipc_error(f"failed to set managed state of '{device}'", e)
+    
+    def connection_show(self, args) -> int:
+        """nmcli connection show [--active]"""
+        try:
+            profiles = self.daemon.request("ListProfiles")
+            active = {_active_profile(i): i["name"] for i in self.daemon.request("GetInterfaces")}
+        except IpcError as e:
+            return self._ipc_error("failed to list connections", e)
+        
+        connections = [(p["name"], p["connection_type"], active.get(p["name"])) for p in profiles]
+        if args.get('active', False):
+            connections = [c for c in connections if c[2]]
+        
+        if args.get('terse', False):
+            # Terse format: NAME:UUID:TYPE:DEVICE
+            for name, conn_type, device in connections:
+                conn_uuid = deterministic_uuid_for_name(name)
+                device_str = device if device else ""
+                print(f"{name}:{conn_uuid}:{conn_type}:{device_str}")
+        else:
+            # Human-readable format matching nmcli exactly
+            print("NAME                UUID                                  TYPE      DEVICE")
             
-        except Exception as e:
-            logger.exception("Failed to list connections")
-            if not self.quiet:
-                print(f"Error: failed to list connections: {e}", file=sys.stderr)
-            return 1
+            for name, conn_type, device in connections:
+                conn_uuid = deterministic_uuid_for_name(name)
+                device_str = device if device else "--"
+                print(f"{name:<18} {conn_uuid}  {conn_type:<8} {device_str}")
+        
+        if not connections and not args.get('terse', False) and not self.quiet:
+            print("Note: Use 'alopex-cli' to create and manage connections", file=sys.stderr)
+        
+        return 0
+    
+    def _find_profile(self, conn_name: str) -> Optional[Dict]:
+        """Look a profile up by name or by its nmcli UUID"""
+        return next((p for p in self.daemon.request("ListProfiles")
+                     if conn_name in (p["name"], deterministic_uuid_for_name(p["name"]))), None)
     
     def connection_up(self, args) -> int:
         """nmcli connection up <name>"""
         conn_name = args.get('connection')
         if not conn_name:
-            if not self.quiet:
-                print("Error: connection name required", file=sys.stderr)
-            return 2
+            return self._error("connection name required", 2)
         
         try:
-            # Use real ALOPEX connection management via asyncio
-            success = asyncio.run(self.conn_mgr.connect_profile(conn_name))
-            msg = f"Connection {conn_name} activated successfully" if success else f"Failed to activate {conn_name}"
-            if success:
-                if not args.get('quiet', False):
-                    print(f"Connection '{conn_name}' successfully activated.")
-                return 0
-            else:
-                if not self.quiet:
-                    print(f"Error: failed to activate connection '{conn_name}': {msg}", file=sys.stderr)
-                return 1
-        except Exception as e:
-            logger.exception(f"Connection activation failed for {conn_name}")
-            if not self.quiet:
-                print(f"Error: failed to activate connection '{conn_name}': {e}", file=sys.stderr)
-            return 1
+            profile = self._find_profile(conn_name)
+            if not profile:
+                return self._error(f"unknown connection '{conn_name}'.", 10)
+            self.daemon.run_job("Connect", profile=profile["name"])
+        except IpcError as e:
+            return self._ipc_error("Connection activation failed", e, default=4)
+        
+        if not args.get('quiet', False):
+            print(f"Connection '{profile['name']}' successfully activated.")
+        return 0
     
     def connection_down(self, args) -> int:
         """nmcli connection down <name>"""
         conn_name = args.get('connection')
         if not conn_name:
-            if not self.quiet:
-                print("Error: connection name required", file=sys.stderr)
-            return 2
+            return self._error("connection name required", 2)
         
         try:
-            # Use real ALOPEX connection management via asyncio  
-            # Find the profile to get interface name for disconnection
-            profiles = self.conn_mgr.get_profiles()
-            if conn_name in profiles:
-                interface = profiles[conn_name].interface
-                success = asyncio.run(self.conn_mgr.disconnect_interface(interface))
-                msg = f"Connection {conn_name} deactivated successfully" if success else f"Failed to deactivate {conn_name}"
-            else:
-                success = False
-                msg = f"Connection {conn_name} not found"
-            if success:
-                if not args.get('quiet', False):
-                    print(f"Connection '{conn_name}' successfully deactivated.")
-                return 0
-            else:
-                if not self.quiet:
-                    print(f"Error: failed to deactivate connection '{conn_name}': {msg}", file=sys.stderr)
-                return 1
-        except Exception as e:
-            logger.exception(f"Connection deactivation failed for {conn_name}")
-            if not self.quiet:
-                print(f"Error: failed to deactivate connection '{conn_name}': {e}", file=sys.stderr)
-            return 1
+            profile = self._find_profile(conn_name)
+            active = profile and any(_active_profile(i) == profile["name"]
+                                     for i in self.daemon.request("GetInterfaces"))
+            if not active:
+                return self._error(f"'{conn_name}' is not an active connection.", 10)
+            self.daemon.request("Disconnect", interface=profile["interface"])
+        except IpcError as e:
+            return self._ipc_error(f"failed to deactivate connection '{conn_name}'", e, default=5)
+        
+        if not args.get('quiet', False):
+            print(f"Connection '{profile['name']}' successfully deactivated.")
+        return 0
     
     def general_status(self, args) -> int:
         """nmcli general status"""
         try:
-            interfaces = self.discovery.list_interfaces()
-            connected_count = len([i for i in interfaces if _map_interface_state(i.status) == "connected"])
-            
-            if args.get('terse', False):
-                # Terse format: STATE:CONNECTIVITY:WIFI-HW:WIFI:WWAN-HW:WWAN
-                state = "connected" if connected_count > 0 else "disconnected"
-                print(f"{state}:limited:enabled:enabled:enabled:enabled")
-            else:
-                print("STATE         CONNECTIVITY  WIFI-HW  WIFI     WWAN-HW  WWAN")
-                
-                if connected_count > 0:
-                    state = "connected (local only)"  # Conservative - no internet check yet
-                else:
-                    state = "disconnected"
-                
-                print(f"{state:<13} limited       enabled  enabled  enabled  enabled")
-                
-                if not self.quiet and not args.get('terse', False):
-                    print("Note: Full connectivity status available in 'alopexctl status'", file=sys.stderr)
-            
-            return 0
+            interfaces = self.daemon.request("GetInterfaces")
+        except IpcError as e:
+            return self._ipc_error("failed to get networking status", e)
+        
+        states = [_map_interface_state(i) for i in interfaces]
+        if LinkState.CONNECTED.value in states:
+            state = "connected (local only)"  # Conservative - no internet check yet
+        elif LinkState.CONNECTING.value in states:
+            state = LinkState.CONNECTING.value
+        else:
+            state = LinkState.DISCONNECTED.value
+        wifi = "enabled" if any(_device_type(i) == "wifi" for i in interfaces) else "disabled"
+        wwan = "enabled" if any(_device_type(i) == "gsm" for i in interfaces) else "disabled"
+        
+        if args.get('terse', False):
+            # Terse format: STATE:CONNECTIVITY:WIFI-HW:WIFI:WWAN-HW:WWAN
+            print(f"{state.split()[0]}:limited:{wifi}:{wifi}:{wwan}:{wwan}")
+        else:
+            print("STATE                   CONNECTIVITY  WIFI-HW  WIFI     WWAN-HW  WWAN")
+            print(f"{state:<23} limited       {wifi:<8} {wifi:<8} {wwan:<8} {wwan}")
             
-        except Exception as e:
-            logger.exception("Failed to get general status")
             if not self.quiet:
-                print(f"Error: failed to get networking status: {e}", file=sys.stderr)
-            return 1
+                print("Note: Full connectivity status available in 'alopex-cli status'", file=sys.stderr)
+        
+        return 0
     
     def radio_wifi(self, args) -> int:
         """nmcli radio wifi [on|off]"""
@@ -407,7 +461,7 @@ class NmcliCompat:
         print("WARNING: This is a compatibility shim. Not all nmcli features are supported.")
         print("For full functionality, use native ALOPEX tools:")
         print("")
-        print("  alopexctl           - Native ALOPEX command-line tool")
+        print("  alopex-cli          - Native ALOPEX command-line tool")
         print("  alopex-gui          - Full graphical interface")
         print("  systemctl status alopexd - Daemon status")
         print("  journalctl -u alopexd    - View daemon logs")
@@ -415,14 +469,17 @@ class NmcliCompat:
         print("Supported nmcli commands:")
         print("  nmcli --version, -h, --help")
         print("  nmcli device status [--terse]")
-        print("  nmcli device wifi list [--terse] [device <dev>]")
-        print("  nmcli device connect <device>")
-        print("  nmcli connection show [--terse]")
-        print("  nmcli connection up <name>")
-        print("  nmcli connection down <name>")
+        print("  nmcli device wifi list [--terse] [ifname <dev>]")
+        print("  nmcli device wifi connect <ssid> [password <pw>] [ifname <dev>] [name <name>]")
+        print("  nmcli device connect|disconnect <device>")
+        print("  nmcli device set <device> managed yes|no")
+        print("  nmcli connection show [--terse] [--active]")
+        print("  nmcli connection up|down [id|uuid] <name>")
         print("  nmcli general status [--terse]")
         print("  nmcli radio wifi [on|off]")
         print("")
+        print("Commands may be abbreviated as with nmcli (dev, con, d wifi l...)")
+        print("")
         print("Environment variables:")
         print("  ALOPEX_NMCLI_QUIET=1     - Suppress notes and warnings")
         print("  ALOPEX_NMCLI_BYPASS=1    - Use real nmcli (if available)")
@@ -452,22 +509,6 @@ class NmcliCompat:
             pass
         return "1"  # Default channel
     
-    async def _async_connect_device(self, device: str) -> bool:
-        """Async device connection helper"""
-        try:
-            # Find profile for this device and connect
-            profiles = self.conn_mgr.get_profiles()
-            device_profiles = [p for p in profiles.values() if p.interface == device]
-            if device_profiles:
-                # Use first available profile for this device
-                return await self.conn_mgr.connect_profile(device_profiles[0].name)
-            else:
-                # Try auto-connecting the interface
-                return await self.conn_mgr.auto_connect_interface(device)
-        except Exception as e:
-            logger.exception(f"Async device connect failed for {device}")
-            return False
-    
     def _enable_wifi_interfaces(self) -> bool:
         """Enable WiFi interfaces using ip commands"""
         try:
@@ -513,6 +554,23 @@ def _parse_global_flags():
             compat = NmcliCompat()
             sys.exit(compat.show_help())
 
+def _expand(word: str, choices: List[str]) -> str:
+    """Resolve an nmcli-style abbreviation ("dev", "con", "l") to its full word"""
+    matches = [choice for choice in choices if choice.startswith(word)]
+    return matches[0] if word and matches else word
+
+def _keyword_args(args: List[str], keywords: List[str]) -> Dict[str, str]:
+    """Collect "<keyword> <value>" pairs such as "ifname wlan0 password secret" """
+    result = {}
+    i = 0
+    while i + 1 < len(args):
+        if args[i] in keywords:
+            result[args[i]] = args[i + 1]
+            i += 2
+        else:
+            i += 1
+    return result
+
 def parse_args():
     """Parse nmcli-style arguments with structured per-command parsing"""
     if len(sys.argv) < 2:
@@ -540,7 +598,7 @@ def parse_args():
             if i + 1 < len(sys.argv):
                 i += 1
         elif not args['command'] and not arg.startswith('-'):
-            args['command'] = arg
+            args['command'] = _expand(arg, ['device', 'connection', 'general', 'radio', 'help'])
             break
         
         i += 1
@@ -569,17 +627,29 @@ def _parse_device_args(args):
         result['subcommand'] = 'status'
         return result
     
-    subcommand = args[0]
+    subcommand = _expand(args[0], ['status', 'show', 'wifi', 'connect', 'disconnect', 'set'])
     result['subcommand'] = subcommand
     
-    if subcommand == 'wifi' and len(args) > 1:
-        if args[1] == 'list':
-            result['wifi_action'] = 'list'
-            # Look for device parameter
-            if len(args) > 2 and args[2] == 'device' and len(args) > 3:
-                result['device'] = args[3]
-    elif subcommand == 'connect' and len(args) > 1:
+    if subcommand == 'wifi':
+        action = _expand(args[1], ['list', 'connect', 'rescan']) if len(args) > 1 else 'list'
+        result['wifi_action'] = action
+        rest = args[2:]
+        if action == 'connect' and rest:
+            result['ssid'] = rest[0]
+            rest = rest[1:]
+        keywords = _keyword_args(rest, ['ifname', 'device', 'password', 'name'])
+        if 'ifname' in keywords or 'device' in keywords:
+            result['device'] = keywords.get('ifname', keywords.get('device'))
+        if 'password' in keywords:
+            result['password'] = keywords['password']
+        if 'name' in keywords:
+            result['name'] = keywords['name']
+    elif subcommand in ['connect', 'disconnect'] and len(args) > 1:
+        result['device'] = args[1]
+    elif subcommand == 'set' and len(args) > 1:
         result['device'] = args[1]
+        if len(args) > 3 and args[2] == 'managed':
+            result['managed'] = args[3].lower()
     
     return result
 
@@ -590,31 +660,36 @@ def _parse_connection_args(args):
         result['subcommand'] = 'show'
         return result
     
-    subcommand = args[0]
+    subcommand = _expand(args[0], ['show', 'up', 'down'])
     result['subcommand'] = subcommand
     
-    if subcommand in ['up', 'down'] and len(args) > 1:
-        result['connection'] = args[1]
+    if subcommand in ['up', 'down']:
+        # "up id <name>" and "up uuid <uuid>" name the connection the same way
+        names = [arg for arg in args[1:] if arg not in ['id', 'uuid']]
+        if names:
+            result['connection'] = names[0]
+    elif subcommand == 'show':
+        result['active'] = '--active' in args or '-a' in args
     
     return result
 
 def _parse_general_args(args):
     """Parse general subcommand arguments"""
     result = {'subcommand': 'status'}  # Default to status
-    if args and args[0] == 'status':
+    if args and _expand(args[0], ['status']) == 'status':
         result['subcommand'] = 'status'
     return result
 
 def _parse_radio_args(args):
     """Parse radio subcommand arguments"""
     result = {}
-    if args and args[0] == 'wifi':
+    if args and _expand(args[0], ['wifi']) == 'wifi':
         result['subcommand'] = 'wifi'
         if len(args) > 1 and args[1] in ['on', 'off']:
             result['action'] = args[1]
     return result
 
-def main():
+def main(socket_path: Optional[Path] = None):
     """TITANIUM-grade nmcli compatibility entry point"""
     # Check for bypass before doing anything else
     _check_bypass()
@@ -627,7 +702,7 @@ def main():
     
     try:
         args = parse_args()
-        compat = NmcliCompat()
+        compat = NmcliCompat(socket_path)
         
         # Override quiet from args if --quiet was passed
         if args.get('quiet'):
@@ -643,10 +718,16 @@ def main():
         elif command == 'device':
             if subcommand == 'status' or not subcommand:
                 return compat.device_status(args)
-            elif subcommand == 'wifi':
+            elif subcommand == 'wifi' and args.get('wifi_action') == 'connect':
+                return compat.device_wifi_connect(args)
+            elif subcommand == 'wifi' and args.get('wifi_action') in ['list', 'rescan']:
                 return compat.device_wifi_list(args)
             elif subcommand == 'connect':
                 return compat.device_connect(args)
+            elif subcommand == 'disconnect':
+                return compat.device_disconnect(args)
+            elif subcommand == 'set':
+                return compat.device_set(args)
             else:
                 if not compat.quiet:
                     print(f"Error: unknown device command '{subcommand}'", file=sys.stderr)
@@ -701,7 +782,7 @@ def main():
                 import traceback
                 traceback.print_exc()
             else:
-                print("For full functionality use 'alopexctl' or contact enterprise@onyxdigital.dev", file=sys.stderr)
+                print("For full functionality use 'alopex-cli' or contact enterprise@onyxdigital.dev", file=sys.stderr)
         return 1
 
 if __name__ == "__main__":
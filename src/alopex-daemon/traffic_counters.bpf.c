@@ -0,0 +1,118 @@
+/*
+ * ALOPEX eBPF Traffic Counters
+ * Packets and bytes per interface, direction and protocol from tc clsact hooks,
+ * read by alopexd (network/fast_counters.py) through the pinned map
+ */
+
+#include <linux/bpf.h>
+#include <linux/if_ether.h>
+#include <linux/in.h>
+#include <linux/in6.h>
+#include <linux/pkt_cls.h>
+#include <bpf/bpf_helpers.h>
+#include <bpf/bpf_endian.h>
+
+#define MAX_ENTRIES 4096
+
+/* Keep in step with DIRECTIONS and PROTOCOLS in fast_counters.py */
+enum alopex_direction {
+    ALOPEX_DIR_RX = 0,
+    ALOPEX_DIR_TX = 1
+};
+
+enum alopex_protocol {
+    ALOPEX_PROTO_TCP = 0,
+    ALOPEX_PROTO_UDP = 1,
+    ALOPEX_PROTO_ICMP = 2,
+    ALOPEX_PROTO_OTHER = 3
+};
+
+struct alopex_counter_key {
+    __u32 ifindex;
+    __u8 direction;
+    __u8 protocol;
+    __u16 pad;
+};
+
+struct alopex_counter_value {
+    __u64 packets;
+    __u64 bytes;
+};
+
+/* Per-CPU, so the hot path needs no atomics; userspace sums the CPUs */
+struct {
+    __uint(type, BPF_MAP_TYPE_PERCPU_HASH);
+    __uint(max_entries, MAX_ENTRIES);
+    __type(key, struct alopex_counter_key);
+    __type(value, struct alopex_counter_value);
+} alopex_counters SEC(".maps");
+
+/* Protocol class from the IP header, wherever the link layer puts it */
+static __always_inline __u8 classify(struct __sk_buff *skb)
+{
+    __u8 ipproto;
+    __u32 offset;
+
+    if (skb->protocol == bpf_htons(ETH_P_IP))
+        offset = 9;     /* protocol */
+    else if (skb->protocol == bpf_htons(ETH_P_IPV6))
+        offset = 6;     /* next header; extension headers count as other */
+    else
+        return ALOPEX_PROTO_OTHER;
+
+    /* Relative to the network header, so tun and WireGuard links (no L2) work too */
+    if (bpf_skb_load_bytes_relative(skb, offset, &ipproto, 1, BPF_HDR_START_NET) < 0)
+        return ALOPEX_PROTO_OTHER;
+
+    switch (ipproto) {
+    case IPPROTO_TCP:
+        return ALOPEX_PROTO_TCP;
+    case IPPROTO_UDP:
+        return ALOPEX_PROTO_UDP;
+    case IPPROTO_ICMP:
+    case IPPROTO_ICMPV6:
+        return ALOPEX_PROTO_ICMP;
+    default:
+        return ALOPEX_PROTO_OTHER;
+    }
+}
+
+static __always_inline int count(struct __sk_buff *skb, __u8 direction)
+{
+    struct alopex_counter_key key = {
+        .ifindex = skb->ifindex,
+        .direction = direction,
+        .protocol = classify(skb),
+    };
+    struct alopex_counter_value zero = {};
+    struct alopex_counter_value *value;
+
+    value = bpf_map_lookup_elem(&alopex_counters, &key);
+    if (!value) {
+        bpf_map_update_elem(&alopex_counters, &key, &zero, BPF_NOEXIST);
+        value = bpf_map_lookup_elem(&alopex_counters, &key);
+        if (!value)
+            return TC_ACT_OK;
+    }
+
+    value->packets += 1;
+    value->bytes += skb->len;
+
+    /* Only counting: every packet goes on as if we weren't here */
+    return TC_ACT_OK;
+}
+
+SEC("tc")
+int alopex_count_ingress(struct __sk_buff *skb)
+{
+    return count(skb, ALOPEX_DIR_RX);
+}
+
+SEC("tc")
+int alopex_count_egress(struct __sk_buff *skb)
+{
+    return count(skb, ALOPEX_DIR_TX);
+}
+
+/* License required for eBPF programs */
+char _license[] SEC("license") = "GPL";
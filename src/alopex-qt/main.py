@@ -4,18 +4,151 @@ ALOPEX Network Manager - Qt GUI
 Network management interface
 """
 
+import os
 import sys
+import json
+import time
 import asyncio
-from PyQt6.QtWidgets import QApplication
-from PyQt6.QtCore import Qt
-from PyQt6.QtGui import QIcon
+import argparse
+import threading
+from dataclasses import asdict
 
-from ui.main_window import AlopexMainWindow
-from ui.arctic_theme import ArcticTheme
+from network.discovery import InterfaceType, NetworkDiscovery
+from network.ipc import IpcClient, IpcError
+from network.units import UNIT_STYLES, DEFAULT_UNITS, UnitPreference
+from network.statusline import build_status, primary_interface
+from network.wifi import WiFiManager
+
+# --once measures rates over this long
+ONCE_SAMPLE_SECONDS = 1.0
+# --statusbar: a new line at least this often, and at once on connection events
+STATUSBAR_INTERVAL_MS = 2000
+STATUSBAR_EVENTS = ["InterfaceStateChanged", "CarrierChanged", "LocationChanged", "MeteredChanged",
+                    "AirplaneModeChanged"]
+
+def parse_args():
+    """Our options; anything else is left for Qt (-platform, -style, ...)"""
+    parser = argparse.ArgumentParser(description="ALOPEX Network Manager")
+    parser.add_argument("--interval", type=int, metavar="MS",
+                        help="Telemetry refresh interval in milliseconds (saved setting: telemetry/interval_ms)")
+    parser.add_argument("--units", choices=UNIT_STYLES,
+                        help="Rate and size units; 'u' cycles them at runtime (saved setting: display/units)")
+    parser.add_argument("--once", action="store_true",
+                        help="Print the current interfaces and rates to stdout and exit, without a window")
+    parser.add_argument("--json", action="store_true", help="With --once, print JSON")
+    parser.add_argument("--statusbar", action="store_true",
+                        help="Print a waybar custom-module JSON line whenever the connection changes, "
+                             "and every --interval ms (default 2000) for the rates")
+    return parser.parse_known_args()
+
+def _units(args) -> UnitPreference:
+    return UnitPreference.parse(args.units or os.getenv("ALOPEX_UNITS", DEFAULT_UNITS))
+
+def run_once(args) -> int:
+    """Headless snapshot for scripts, cron jobs and tmux status lines"""
+    discovery = NetworkDiscovery()
+    discovery.update_speeds(discovery.discover_interfaces())
+    time.sleep(ONCE_SAMPLE_SECONDS)
+    interfaces = discovery.discover_interfaces()
+    discovery.update_speeds(interfaces)
+    
+    # The daemon adds what only it knows; without it the snapshot is still useful
+    daemon = IpcClient(timeout=1.0)
+    status = daemon.try_request("GetStatus", default={})
+    profiles = status.get("active_profiles", {})
+    uptimes = daemon.try_request("GetUptimes", default={})
+    unmanaged = set(daemon.try_request("GetUnmanaged", default={}).get("interfaces", []))
+    for interface in interfaces:
+        interface.metrics.uptime = uptimes.get(interface.name)
+        interface.managed = interface.name not in unmanaged
+    
+    if args.json:
+        print(json.dumps([dict(asdict(i), profile=profiles.get(i.name)) for i in interfaces],
+                         indent=2, sort_keys=True, default=str))
+        return 0
+    
+    try:
+        units = _units(args)
+    except ValueError as e:
+        print(f"alopex: {e}", file=sys.stderr)
+        return 2
+    rows = [["DEVICE", "TYPE", "STATE", "PROFILE", "ADDRESS", "DOWN", "UP"]]
+    for i in interfaces:
+        rows.append([i.name, str(i.interface_type), i.status if i.managed else f"{i.status} (unmanaged)", profiles.get(i.name) or "--", i.ip or "--",
+                     units.format_rate(i.metrics.speed_down * 1024), units.format_rate(i.metrics.speed_up * 1024)])
+    widths = [max(len(row[column]) for row in rows) for column in range(len(rows[0]))]
+    for row in rows:
+        print("  ".join(cell.ljust(width) for cell, width in zip(row, widths)).rstrip())
+    return 0
+
+def _follow_events(daemon: IpcClient, wake: threading.Event):
+    """Wake the status bar loop on daemon events; keeps retrying while alopexd is down"""
+    while True:
+        try:
+            for _ in daemon.subscribe(STATUSBAR_EVENTS):
+                wake.set()
+        except IpcError:
+            pass
+        time.sleep(5)
+
+def run_statusbar(args) -> int:
+    """Endless waybar custom-module output (return-type json)"""
+    try:
+        units = _units(args)
+    except ValueError as e:
+        print(f"alopex: {e}", file=sys.stderr)
+        return 2
+    interval = (args.interval or STATUSBAR_INTERVAL_MS) / 1000
+    
+    daemon = IpcClient(timeout=1.0)
+    wake = threading.Event()
+    threading.Thread(target=_follow_events, args=(daemon, wake), daemon=True).start()
+    
+    discovery = NetworkDiscovery()
+    last_line = None
+    try:
+        while True:
+            interfaces = discovery.discover_interfaces()
+            discovery.update_speeds(interfaces)
+            status = daemon.try_request("GetStatus")
+            
+            primary = primary_interface(interfaces)
+            ssid = signal = None
+            if primary is not None and primary.interface_type == InterfaceType.WIFI:
+                ssid = WiFiManager.get_current_connection(primary.name)
+                signal = WiFiManager.get_signal_quality(primary.name)
+            
+            bar = build_status(
+                interfaces, units, (status or {}).get("active_profiles", {}), ssid, signal,
+                metered=bool((status or {}).get("metered")), daemon=status is not None,
+                airplane=bool((status or {}).get("airplane_mode"))
+            )
+            line = json.dumps(bar, ensure_ascii=False)
+            # Bars redraw on every line; skip the ones that change nothing
+            if line != last_line:
+                print(line, flush=True)
+                last_line = line
+            
+            wake.wait(interval)
+            wake.clear()
+    except (KeyboardInterrupt, BrokenPipeError):
+        return 0
 
 def main():
     """Main application entry point"""
-    app = QApplication(sys.argv)
+    args, qt_args = parse_args()
+    if args.once:
+        sys.exit(run_once(args))
+    if args.statusbar:
+        sys.exit(run_statusbar(args))
+    
+    # Qt only for the window, so --once works on machines without a display stack
+    from PyQt6.QtWidgets import QApplication
+    from PyQt6.QtCore import QSettings
+    from ui.main_window import AlopexMainWindow, DEFAULT_TELEMETRY_INTERVAL_MS
+    from ui.arctic_theme import ArcticTheme
+    
+    app = QApplication(sys.argv[:1] + qt_args)
     app.setApplicationName("ALOPEX Network Manager")
     app.setApplicationDisplayName("ALOPEX")
     app.setApplicationVersion("1.0.0")
@@ -27,8 +160,16 @@ def main():
     # Apply Arctic Terminal theme
     ArcticTheme.apply_to_app(app)
     
+    # Command line beats ~/.config/alopex/alopex-qt.conf
+    settings = QSettings("alopex", "alopex-qt")
+    interval = args.interval or settings.value("telemetry/interval_ms", DEFAULT_TELEMETRY_INTERVAL_MS, type=int)
+    try:
+        units = UnitPreference.parse(args.units or settings.value("display/units", DEFAULT_UNITS))
+    except ValueError:
+        units = UnitPreference.parse(DEFAULT_UNITS)
+    
     # Create main window
-    window = AlopexMainWindow()
+    window = AlopexMainWindow(telemetry_interval_ms=interval, units=units, settings=settings)
     window.show()
     
     # Run application
@@ -7,10 +7,14 @@ from .discovery import NetworkDiscovery, NetworkInterface, NetworkMetrics
 from .vpn import VpnManager, VpnConfig
 from .wifi import WiFiManager, WiFiNetwork
 from .system_integration import NetworkControl, BluetoothControl
+from .ipc import IpcClient, IpcServer, IpcError, ErrorCode
+from .backends import BackendSet, load_backends, register_backend
 
 __all__ = [
     'NetworkDiscovery', 'NetworkInterface', 'NetworkMetrics',
     'VpnManager', 'VpnConfig',
     'WiFiManager', 'WiFiNetwork', 
-    'NetworkControl', 'BluetoothControl'
+    'NetworkControl', 'BluetoothControl',
+    'IpcClient', 'IpcServer', 'IpcError', 'ErrorCode',
+    'BackendSet', 'load_backends', 'register_backend'
 ]
\ No newline at end of file
@@ -0,0 +1,132 @@
+"""
+Access - Who may send which control request
+Callers get a role: observer (read state, follow events), operator (connect,
+scan, test, switch radios and locations) or admin (change configuration:
+profiles, DNS, labels, certificates, hotspot policy). Local callers are mapped
+by uid or user name and by group, remote ones by certificate common name; root
+and the daemon's own user are always admin. Request types not listed here
+need admin, so new requests are safe until someone classifies them
+"""
+
+import os
+import grp
+import pwd
+import time
+import logging
+from fnmatch import fnmatchcase
+from typing import Dict, Iterable, List, Optional, Tuple
+
+from .ipc import READ_ONLY_PREFIXES
+
+# Configure logging
+logger = logging.getLogger(__name__)
+
+OBSERVER, OPERATOR, ADMIN = "observer", "operator", "admin"
+ROLES = (OBSERVER, OPERATOR, ADMIN)
+# Requests that only read state besides Get/List/Wait; a RelayRequest also needs the role of the one it carries
+OBSERVER_REQUESTS = {"Subscribe", "RelayRequest"}
+# Day-to-day actions that change what the machine is doing, not how it is configured
+OPERATOR_REQUESTS = {
+    "Connect", "Disconnect", "SwitchLocation", "ScanWifi", "ScanLan", "ScanBluetooth", "ProbePort",
+    "DiagnoseConnectivity", "Troubleshoot", "RunCableTest", "RunShapingTest", "RunBufferbloatTest",
+    "PairBluetooth", "ConnectBluetooth", "SetBluetoothAlias", "SendBluetoothFile", "FlushDnsCache",
+    "ReloadDnsFilter", "SetPowerSave", "SetRfkill", "SetAirplaneMode", "RenewDhcpLease", "ReleaseDhcpLease",
+    "ResetSessionCounters", "KickHotspotClient", "AnnounceAddresses", "ForgetWifiBaseline", "TestNotification",
+    "CancelJob", "MockSetCarrier", "MockSetLinkSpeed", "MockRoam"
+}
+# Group memberships are looked up again after this long
+MEMBERSHIP_TTL = 60.0
+
+def check_role(role, what: str) -> Optional[str]:
+    """role itself when it names a role (None allowed); raises ValueError naming what it was for"""
+    if role is not None and role not in ROLES:
+        raise ValueError(f"{what}: role must be one of {', '.join(ROLES)}, not {role!r}")
+    return role
+
+def builtin_role(request_type: str) -> str:
+    """The role a request type needs unless the permissions config says otherwise"""
+    if request_type.startswith(READ_ONLY_PREFIXES) or request_type in OBSERVER_REQUESTS:
+        return OBSERVER
+    return OPERATOR if request_type in OPERATOR_REQUESTS else ADMIN
+
+def rank(role: Optional[str]) -> int:
+    """Order of roles; no role ranks below observer"""
+    return ROLES.index(role) if role in ROLES else -1
+
+class AccessPolicy:
+    """Roles from the access config and the role each request type needs
+
+    config: {users: {name or uid: role}, groups: {name: role},
+    certificates: {common name: role}, default_role (for everyone else,
+    observer unless set; None refuses them), permissions: {request type or
+    pattern: role}}. A caller matching several entries gets the highest of
+    their roles.
+    """
+
+    def __init__(self, config: Dict, own_uid: Optional[int] = None):
+        self.users = {str(key): check_role(role, f"access user {key}")
+                      for key, role in (config.get("users") or {}).items()}
+        self.groups = {key: check_role(role, f"access group {key}")
+                       for key, role in (config.get("groups") or {}).items()}
+        self.certificates = {key: check_role(role, f"access certificate {key}")
+                             for key, role in (config.get("certificates") or {}).items()}
+        self.default_role = check_role(config.get("default_role", OBSERVER), "access default_role")
+        self.permissions = [(pattern, check_role(role, f"access permission {pattern}"))
+                            for pattern, role in (config.get("permissions") or {}).items()]
+        if any(role is None for _, role in self.permissions):
+            raise ValueError("access permissions need a role for each request")
+        self.own_uid = os.getuid() if own_uid is None else own_uid
+        # uid -> (looked up at, user name, group names)
+        self._memberships: Dict[int, Tuple[float, Optional[str], List[str]]] = {}
+
+    def _membership(self, uid: int) -> Tuple[Optional[str], List[str]]:
+        cached = self._memberships.get(uid)
+        if cached and time.monotonic() - cached[0] < MEMBERSHIP_TTL:
+            return cached[1], cached[2]
+        try:
+            user = pwd.getpwuid(uid)
+            gids = os.getgrouplist(user.pw_name, user.pw_gid)
+            name = user.pw_name
+        except (KeyError, OSError):
+            name, gids = None, []
+        groups = []
+        for gid in gids:
+            try:
+                groups.append(grp.getgrgid(gid).gr_name)
+            except KeyError:
+                pass
+        self._memberships[uid] = (time.monotonic(), name, groups)
+        return name, groups
+
+    def role(self, uid: Optional[int], certificate: Optional[str] = None) -> Optional[str]:
+        """The caller's role: a local uid, or a remote certificate's common name; None when it has none"""
+        if uid is None:
+            roles = [self.certificates.get(certificate)] if certificate else []
+        elif uid in (0, self.own_uid):
+            return ADMIN
+        elif not self.users and not self.groups:
+            roles = []
+        else:
+            name, groups = self._membership(uid)
+            roles = [self.users.get(str(uid)), self.users.get(name)] + [self.groups.get(group) for group in groups]
+        roles = [role for role in roles if role]
+        return max(roles, key=rank) if roles else self.default_role
+
+    def required(self, request_type: str) -> str:
+        """The role a request type needs: the first matching permission, else the built-in one"""
+        for pattern, role in self.permissions:
+            if fnmatchcase(request_type, pattern):
+                return role
+        return builtin_role(request_type)
+
+    def allows(self, role: Optional[str], request_type: str) -> bool:
+        return rank(role) >= rank(self.required(request_type))
+
+    def capabilities(self, role: Optional[str], request_types: Iterable[str]) -> Dict:
+        """What a role may do, for clients to hide the rest: a flag per role and the request types refused"""
+        return {
+            "observe": rank(role) >= rank(OBSERVER),
+            "operate": rank(role) >= rank(OPERATOR),
+            "administer": rank(role) >= rank(ADMIN),
+            "denied": sorted(request for request in request_types if not self.allows(role, request))
+        }
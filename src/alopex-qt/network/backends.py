@@ -0,0 +1,1216 @@
+"""
+Network Backends - Pluggable implementations behind the connection manager
+Each link type has an abstract backend; concrete ones register by name and
+are selected at daemon startup from the "backends" config section
+"""
+
+import os
+import re
+import json
+import shutil
+import asyncio
+import logging
+import ipaddress
+import subprocess
+import xml.etree.ElementTree as ElementTree
+from abc import ABC, abstractmethod
+from dataclasses import dataclass, asdict
+from pathlib import Path
+from typing import Callable, Dict, List, Optional, Tuple, Type
+
+from .system_integration import NetworkControl, BluetoothControl, communicate_or_terminate
+from .wifi import SYSTEM_CA_BUNDLE, WiFiManager, WiFiNetwork, WifiSecurity, parse_iw_event
+from .vpn import VpnManager, VpnConfig
+from .dns_health import query_server
+from . import (
+    bluetooth, cable_test, connectivity, containers, conntrack, fast_counters, lan_scan, libvirt, link_names,
+    link_speed, load_test, mptcp, neighbor_announce, nic_queues, obex, port_probe, reconcile, shaping, sysctls,
+    transceiver, unit_traffic
+)
+from .rfkill import RfkillDevice, read_devices, write_soft_block
+from .hotspot import HostapdHotspot
+from .dhcp_server import LEASE_DIR, DhcpServer, LeasePool
+
+# Configure logging
+logger = logging.getLogger(__name__)
+
+class Backend(ABC):
+    """Common base for all backends"""
+
+    # Executables the backend shells out to; checked by is_available()
+    required_tools: Tuple[str, ...] = ()
+
+    @classmethod
+    def is_available(cls) -> bool:
+        """Check whether the backend can run on this system"""
+        return all(shutil.which(tool) for tool in cls.required_tools)
+
+class EthernetBackend(Backend):
+    """Addressing and link control for wired (and post-association WiFi) links"""
+
+    @abstractmethod
+    async def configure_dhcp(self, interface: str) -> bool: ...
+
+    @abstractmethod
+    async def configure_static(self, interface: str, ip: str, gateway: Optional[str], dns: List[str]) -> bool: ...
+
+    @abstractmethod
+    async def bring_up(self, interface: str) -> bool: ...
+
+    @abstractmethod
+    async def bring_down(self, interface: str) -> bool: ...
+
+    @abstractmethod
+    async def ensure_dhcp(self, interface: str) -> bool: ...
+
+    @abstractmethod
+    def get_dhcp_lease(self, interface: str) -> Optional[Dict]: ...
+
+    @abstractmethod
+    def get_address(self, interface: str) -> Tuple[Optional[str], Optional[str]]: ...
+
+    async def create_netdev(self, interface: str, netdev: Dict) -> bool:
+        """Create a virtual device (netdev {"kind": vlan|bridge|bond|gre|gretap|vxlan, ...}) before it is addressed"""
+        logger.error(f"{self.backend_name} backend cannot create {netdev.get('kind')} devices")
+        return False
+
+    async def renew_dhcp(self, interface: str) -> bool:
+        """Renew the interface's lease with its DHCP server now"""
+        logger.error(f"{self.backend_name} backend cannot renew DHCP leases")
+        return False
+
+    async def release_dhcp(self, interface: str) -> bool:
+        """Hand the interface's lease back, leaving it without an address"""
+        logger.error(f"{self.backend_name} backend cannot release DHCP leases")
+        return False
+
+    async def rename(self, interface: str, new_name: str) -> Tuple[bool, str]:
+        """Give the link a new kernel name, downing it briefly if it has to be"""
+        return False, f"{self.backend_name} backend cannot rename interfaces"
+
+    async def address_state(self, interface: str) -> Optional[Dict]:
+        """{"addresses", "routes"} as reconcile.parse_addresses/parse_routes give them; None without the interface"""
+        logger.error(f"{self.backend_name} backend cannot read addresses and routes")
+        return None
+
+    async def apply_change(self, change: Dict) -> Optional[str]:
+        """Add or remove one address or route (a reconcile.plan change); None when it worked, else why not"""
+        return f"{self.backend_name} backend cannot change addresses and routes"
+
+class WifiBackend(Backend):
+    """WiFi scanning and association"""
+
+    @abstractmethod
+    def list_interfaces(self) -> List[str]: ...
+
+    @abstractmethod
+    def scan(self, interface: str) -> List[WiFiNetwork]: ...
+
+    @abstractmethod
+    async def connect(self, interface: str, ssid: str, password: Optional[str] = None,
+                      username: Optional[str] = None, security: Optional[WifiSecurity] = None,
+                      eap: Optional[Dict] = None) -> bool:
+        """Associate; eap (method, identity, password and certificate file paths) selects 802.1X"""
+
+    @abstractmethod
+    def disconnect(self, interface: str) -> bool: ...
+
+    @abstractmethod
+    def current_ssid(self, interface: str) -> Optional[str]: ...
+
+    # The regulatory domain and power save are nl80211's whichever supplicant associates,
+    # so these are shared
+
+    def regulatory_domain(self) -> Optional[Dict]:
+        """{"country", "dfs_region"} the kernel applies"""
+        return WiFiManager.get_regulatory_domain()
+
+    def set_regulatory_domain(self, country: str) -> bool:
+        """Apply a country's channel and power rules"""
+        return WiFiManager.set_regulatory_domain(country)
+
+    def channels(self, interface: str) -> List[Dict]:
+        """Channels the interface may use under the current domain"""
+        return WiFiManager.get_channels(interface)
+
+    def power_save(self, interface: str) -> Optional[bool]:
+        """Whether the radio's power management is on, None if unknown"""
+        return WiFiManager.get_power_save(interface)
+
+    def set_power_save(self, interface: str, enabled: bool) -> bool:
+        return WiFiManager.set_power_save(interface, enabled)
+
+    def cached_scan(self, interface: str) -> List[WiFiNetwork]:
+        """The last scan's results, without putting the radio off channel again"""
+        return WiFiManager.cached_networks(interface)
+
+    def link_info(self, interface: str) -> Optional[Dict]:
+        """{"bssid", "ssid", "frequency", "signal"} of the access point in use, None when not associated"""
+        return WiFiManager.get_link(interface)
+
+    async def watch_frames(self, handler: Callable[[Dict], None]):
+        """Call handler with every deauth/disassoc and connect/roam/disconnect nl80211 reports, until
+        cancelled; raises OSError"""
+        process = await asyncio.create_subprocess_exec(
+            'sudo', 'iw', 'event', '-t', stdout=asyncio.subprocess.PIPE, stderr=asyncio.subprocess.DEVNULL
+        )
+        try:
+            async for line in process.stdout:
+                event = parse_iw_event(line.decode(errors="replace"))
+                if event:
+                    handler(event)
+        finally:
+            if process.returncode is None:
+                process.terminate()
+        raise OSError(f"iw event exited with {await process.wait()}")
+
+class VpnBackend(Backend):
+    """VPN tunnel control"""
+
+    @abstractmethod
+    def discover(self) -> List[VpnConfig]: ...
+
+    @abstractmethod
+    async def connect(self, config: VpnConfig, certs: Optional[Dict[str, str]] = None) -> Tuple[bool, str]:
+        """Bring a tunnel up; certs ({"ca", "cert", "key"} file paths) override the config's own"""
+
+    @abstractmethod
+    async def disconnect(self, config: VpnConfig) -> bool: ...
+
+    @abstractmethod
+    def is_active(self, config: VpnConfig) -> bool: ...
+
+class BluetoothBackend(Backend):
+    """Bluetooth device control"""
+
+    @abstractmethod
+    async def scan(self) -> List[Dict]:
+        """Discover nearby devices, classic and LE; returns every known one as bluetooth.parse_info records"""
+
+    @abstractmethod
+    async def devices(self) -> List[Dict]:
+        """Known devices (paired or recently seen) without scanning"""
+
+    @abstractmethod
+    async def device_info(self, mac_address: str) -> Optional[Dict]:
+        """One device's record, or None if BlueZ doesn't know it"""
+
+    @abstractmethod
+    async def pair(self, mac_address: str) -> bool: ...
+
+    @abstractmethod
+    async def connect(self, mac_address: str) -> bool:
+        """Connect a paired classic device, or an LE peripheral without pairing"""
+
+    @abstractmethod
+    async def send_file(self, mac_address: str, path: str, progress: Callable[[int, Optional[int]], None],
+                        uid: Optional[int] = None) -> Dict:
+        """OBEX Object Push of a file as user uid; raises obex.TransferError when refused or dropped"""
+
+    @abstractmethod
+    def paired_devices(self) -> List[Dict]: ...
+
+class HostBackend(Backend):
+    """Host-wide settings a location applies: resolvers, proxy and firewall zones"""
+
+    @abstractmethod
+    async def set_dns(self, servers: List[str]) -> bool: ...
+
+    @abstractmethod
+    async def set_proxy(self, proxy: Optional[str]) -> bool: ...
+
+    @abstractmethod
+    async def set_firewall_zone(self, interface: str, zone: str) -> bool: ...
+
+    @abstractmethod
+    async def firewall_zone(self, interface: str) -> Optional[str]: ...
+
+    @abstractmethod
+    def gateway_mac(self, gateway: str) -> Optional[str]: ...
+
+    @abstractmethod
+    async def query_dns(self, server: str, name: str, timeout: float) -> float: ...
+
+    @abstractmethod
+    def interface_address(self, interface: str) -> Optional[ipaddress.IPv4Interface]: ...
+
+    @abstractmethod
+    async def scan_lan(self, interface: str, address: ipaddress.IPv4Interface) -> List[Dict]: ...
+
+    @abstractmethod
+    async def probe_port(self, host: str, port: int, source: Optional[str], timeout: float) -> Dict: ...
+
+    @abstractmethod
+    async def check_families(self, host: str, port: int, timeout: float) -> Dict:
+        """connectivity.check: probes over IPv4 and IPv6, and which family a Happy Eyeballs connection used"""
+
+    @abstractmethod
+    async def ping(self, host: str, count: int, interval: float, timeout: float,
+                   interface: Optional[str] = None) -> List[Optional[float]]: ...
+
+    @abstractmethod
+    async def link_modes(self, interface: str) -> Optional[Dict]:
+        """link_speed.parse_ethtool: the NIC's fastest mode and the other end's, or None if unknown"""
+
+    @abstractmethod
+    async def cable_test(self, interface: str) -> List[Dict]:
+        """cable_test.parse_cable_test results per pair; raises OSError when the NIC can't test"""
+
+    @abstractmethod
+    async def module_info(self, interface: str) -> Optional[Dict]:
+        """transceiver.parse_module_eeprom of the plugged SFP/QSFP module, or None without one"""
+
+    @abstractmethod
+    async def nic_queues(self, interface: str) -> Dict:
+        """nic_queues.read_queues: per-queue packets, the NIC's IRQs and their CPUs, and the online CPUs"""
+
+    @abstractmethod
+    def set_irq_affinity(self, irq: int, cpus: List[int]) -> bool:
+        """Pin an IRQ to the given CPUs"""
+
+    @abstractmethod
+    async def flows(self) -> Tuple[List[Dict], Optional[int]]:
+        """Tracked connections (conntrack.make_flow dicts) and how many the kernel will track"""
+
+    @abstractmethod
+    async def announce_addresses(self, interface: str, mac: str, addresses: List[str]) -> Dict:
+        """neighbor_announce.announce: one gratuitous ARP or unsolicited NA per address"""
+
+    @abstractmethod
+    def set_proxy_arp(self, interface: str, enabled: bool) -> bool:
+        """Turn proxy ARP (and proxy NDP) on or off for an interface"""
+
+    @abstractmethod
+    def read_sysctl(self, key: str) -> Optional[str]:
+        """A sysctl by dotted key (net.ipv4.conf.eth0.rp_filter); None when it does not exist"""
+
+    @abstractmethod
+    def write_sysctl(self, key: str, value: str) -> Optional[str]:
+        """Set a sysctl; None when it worked, else why not"""
+
+    @abstractmethod
+    async def mptcp_state(self) -> Dict:
+        """mptcp.read_state: whether MPTCP is on, the path manager's endpoints and limits; raises OSError
+        without MPTCP"""
+
+    @abstractmethod
+    async def add_mptcp_endpoint(self, address: str, interface: str, flags: List[str]) -> Dict:
+        """Make an address an MPTCP endpoint and return it with its ID; raises OSError when refused"""
+
+    @abstractmethod
+    async def remove_mptcp_endpoint(self, endpoint_id: int): ...
+
+    @abstractmethod
+    async def set_mptcp_limits(self, subflows: int, add_addr_accepted: int): ...
+
+    @abstractmethod
+    async def shaping_state(self, interface: str) -> Optional[Dict]:
+        """shaping.read_state: the root qdisc ({"kind", "bandwidth"}) and the default routes' congestion
+        control; None without the interface"""
+
+    @abstractmethod
+    async def set_qdisc(self, interface: str, qdisc: Optional[str], bandwidth: Optional[str] = None) -> Optional[str]:
+        """Put a root qdisc on the interface, or with None go back to the kernel's; None when it worked"""
+
+    @abstractmethod
+    async def set_congestion_control(self, interface: str, algorithm: Optional[str]) -> Optional[str]:
+        """Set (None: drop) congctl on the interface's default routes; None when it worked, else why not"""
+
+    @abstractmethod
+    async def load_link(self, interface: str, direction: str, seconds: float, url: Optional[str] = None) -> Dict:
+        """load_test.saturate: fill the link for seconds; raises OSError when no data moved"""
+
+class ServiceBackend(Backend):
+    """Starting and stopping the services that depend on a profile"""
+
+    @abstractmethod
+    async def start_unit(self, unit: str) -> Tuple[bool, str]: ...
+
+    @abstractmethod
+    async def stop_unit(self, unit: str) -> Tuple[bool, str]: ...
+
+class RfkillBackend(Backend):
+    """Radio kill switches: reading them and soft-blocking radios"""
+
+    @abstractmethod
+    def devices(self) -> List[RfkillDevice]: ...
+
+    @abstractmethod
+    def set_soft_block(self, index: int, blocked: bool) -> bool: ...
+
+    def interface_block(self, interface: str) -> Optional[str]:
+        """hard, soft or None for the radio behind a WiFi interface"""
+        return next((d.block for d in self.devices() if d.interface == interface), None)
+
+class HotspotBackend(Backend):
+    """Sharing a connection as a WiFi access point, and the stations that join it"""
+
+    @abstractmethod
+    async def start(self, interface: str, ssid: str, password: Optional[str], channel: int,
+                    network: ipaddress.IPv4Interface, blocked: List[str],
+                    allowed: Optional[List[str]] = None, isolate: bool = False) -> Tuple[bool, str]:
+        """Start the access point; with allowed, only those stations may join, with isolate they can't
+        reach each other"""
+
+    @abstractmethod
+    async def stop(self, interface: str, network: Optional[ipaddress.IPv4Interface] = None) -> bool: ...
+
+    @abstractmethod
+    def stations(self, interface: str) -> List[Dict]: ...
+
+    @abstractmethod
+    def kick(self, interface: str, mac: str) -> bool: ...
+
+    @abstractmethod
+    def set_blocked(self, interface: str, mac: str, blocked: bool, all_blocked: List[str]) -> bool: ...
+
+    @abstractmethod
+    def set_filter(self, interface: str, allowed: Optional[List[str]], blocked: List[str]) -> bool:
+        """Admit only the allowed stations, or everyone but the blocked ones when allowed is None"""
+
+    @abstractmethod
+    async def set_isolation(self, interface: str, isolate: bool) -> bool:
+        """Keep stations from reaching each other, or let them again"""
+
+    @abstractmethod
+    async def set_limits(self, interface: str, limits: Dict[str, Dict]) -> bool:
+        """Replace every client's rate limits: MAC -> {"down", "up"} in kbit/s"""
+
+class DhcpServerBackend(Backend):
+    """Hands out addresses on the networks we share a connection with"""
+
+    @abstractmethod
+    async def start(self, interface: str, network: ipaddress.IPv4Interface, dns: List[str],
+                    reservations: Dict[str, str]) -> Tuple[bool, str]: ...
+
+    @abstractmethod
+    async def stop(self, interface: str) -> bool: ...
+
+    @abstractmethod
+    def leases(self, interface: str) -> List[Dict]: ...
+
+    @abstractmethod
+    def set_reservations(self, interface: str, reservations: Dict[str, str]) -> bool: ...
+
+    @abstractmethod
+    def running(self) -> List[str]: ...
+
+class AccountingBackend(Backend):
+    """Byte counters per unit cgroup, for top talkers"""
+
+    @abstractmethod
+    def cgroups(self) -> Dict[str, int]:
+        """Unit cgroup paths (relative to the cgroup2 root) and their IDs"""
+
+    @abstractmethod
+    async def sync(self, cgroups: List[str]) -> Tuple[bool, str]:
+        """Count exactly these cgroups from now on, starting from zero"""
+
+    @abstractmethod
+    async def counters(self) -> Dict[str, Dict[str, int]]:
+        """Received and sent bytes per counted cgroup; raises OSError when they cannot be read"""
+
+    @abstractmethod
+    async def clear(self) -> bool: ...
+
+class CountersBackend(Backend):
+    """Packets and bytes per interface, direction and protocol, cheap enough to read every 100ms"""
+
+    @abstractmethod
+    def unavailable(self, object_path: str) -> Optional[str]:
+        """Why the counters cannot run on this system, or None"""
+
+    @abstractmethod
+    async def start(self, object_path: str) -> Tuple[bool, str]: ...
+
+    @abstractmethod
+    async def attach(self, interface: str) -> Tuple[bool, str]: ...
+
+    @abstractmethod
+    async def detach(self, interface: str) -> bool: ...
+
+    @abstractmethod
+    def read(self) -> Dict[str, Dict[str, Dict[str, Tuple[int, int]]]]:
+        """(packets, bytes) per attached interface, direction and protocol; raises OSError"""
+
+    @abstractmethod
+    async def stop(self): ...
+
+class ContainersBackend(Backend):
+    """Container engines' networks, and which bridge each interface is plugged into"""
+
+    @abstractmethod
+    async def networks(self, socket_path: str) -> List[Dict]:
+        """Bridge networks (containers.parse_network dicts) of the engine behind a socket;
+        raises FileNotFoundError when it isn't running, OSError when it can't be read"""
+
+    @abstractmethod
+    def masters(self) -> Dict[str, str]:
+        """Bridge (or bond) each enslaved interface belongs to"""
+
+class LibvirtBackend(Backend):
+    """libvirt's virtual networks and the guests plugged into them"""
+
+    @abstractmethod
+    async def networks(self, uri: str) -> List[Dict]:
+        """libvirt.attach_guests dicts, with "active", for every defined network; raises OSError"""
+
+BACKEND_KINDS: Dict[str, Type[Backend]] = {
+    "ethernet": EthernetBackend,
+    "wifi": WifiBackend,
+    "vpn": VpnBackend,
+    "bluetooth": BluetoothBackend,
+    "host": HostBackend,
+    "services": ServiceBackend,
+    "rfkill": RfkillBackend,
+    "hotspot": HotspotBackend,
+    "dhcp_server": DhcpServerBackend,
+    "accounting": AccountingBackend,
+    "counters": CountersBackend,
+    "containers": ContainersBackend,
+    "libvirt": LibvirtBackend
+}
+
+DEFAULT_BACKENDS = {
+    "ethernet": "iproute2",
+    "wifi": "wpa_supplicant",
+    "vpn": "wireguard",
+    "bluetooth": "bluetoothctl",
+    "host": "system",
+    "services": "systemd",
+    "rfkill": "sysfs",
+    "hotspot": "hostapd",
+    "dhcp_server": "builtin",
+    "accounting": "nftables",
+    "counters": "ebpf",
+    "containers": "engine_api",
+    "libvirt": "virsh"
+}
+
+BACKEND_REGISTRY: Dict[str, Dict[str, Type[Backend]]] = {kind: {} for kind in BACKEND_KINDS}
+
+def register_backend(kind: str, name: str) -> Callable:
+    """Class decorator adding a backend implementation to the registry"""
+    def decorator(cls: Type[Backend]) -> Type[Backend]:
+        if not issubclass(cls, BACKEND_KINDS[kind]):
+            raise TypeError(f"{cls.__name__} is not a {kind} backend")
+        BACKEND_REGISTRY[kind][name] = cls
+        cls.backend_name = name
+        return cls
+    return decorator
+
+@register_backend("ethernet", "iproute2")
+class Iproute2EthernetBackend(EthernetBackend):
+    """ip(8) plus dhcpcd"""
+
+    required_tools = ("ip", "dhcpcd")
+
+    async def configure_dhcp(self, interface: str) -> bool:
+        return await NetworkControl.configure_dhcp(interface)
+
+    async def configure_static(self, interface: str, ip: str, gateway: Optional[str], dns: List[str]) -> bool:
+        return await NetworkControl.configure_static_ip(interface, ip, gateway, dns)
+
+    async def bring_up(self, interface: str) -> bool:
+        return await NetworkControl.bring_interface_up(interface)
+
+    async def create_netdev(self, interface: str, netdev: Dict) -> bool:
+        return await NetworkControl.create_netdev(interface, netdev)
+
+    async def bring_down(self, interface: str) -> bool:
+        return await NetworkControl.bring_interface_down(interface)
+
+    async def ensure_dhcp(self, interface: str) -> bool:
+        return await NetworkControl.ensure_dhcp_client(interface)
+
+    def get_dhcp_lease(self, interface: str) -> Optional[Dict]:
+        return NetworkControl.get_dhcp_lease(interface)
+
+    async def renew_dhcp(self, interface: str) -> bool:
+        return await NetworkControl.renew_dhcp_lease(interface)
+
+    async def release_dhcp(self, interface: str) -> bool:
+        return await NetworkControl.release_dhcp_lease(interface)
+
+    async def rename(self, interface: str, new_name: str) -> Tuple[bool, str]:
+        # Straight over rtnetlink; the daemon has CAP_NET_ADMIN, so no sudo round trip
+        try:
+            downed = await asyncio.get_running_loop().run_in_executor(
+                None, link_names.rename_link, interface, new_name)
+        except OSError as e:
+            return False, str(e)
+        return True, "Renamed (link was down for the rename)" if downed else "Renamed"
+
+    async def address_state(self, interface: str) -> Optional[Dict]:
+        return await reconcile.read_state(interface)
+
+    async def apply_change(self, change: Dict) -> Optional[str]:
+        return await reconcile.apply_change(change)
+
+    def get_address(self, interface: str) -> Tuple[Optional[str], Optional[str]]:
+        ip_address = None
+        result = subprocess.run(['ip', 'addr', 'show', interface], capture_output=True, text=True)
+        if result.returncode == 0:
+            ip_match = re.search(r'inet (\d+\.\d+\.\d+\.\d+)', result.stdout)
+            if ip_match:
+                ip_address = ip_match.group(1)
+
+        gateway = None
+        result = subprocess.run(['ip', 'route', 'show', 'dev', interface], capture_output=True, text=True)
+        if result.returncode == 0:
+            for line in result.stdout.split('\n'):
+                if 'default via' in line:
+                    parts = line.split()
+                    gateway = parts[parts.index('via') + 1]
+                    break
+
+        return ip_address, gateway
+
+@register_backend("wifi", "wpa_supplicant")
+class WpaSupplicantWifiBackend(WifiBackend):
+    """iw for scanning, wpa_supplicant for association"""
+
+    required_tools = ("iw", "wpa_supplicant")
+
+    def list_interfaces(self) -> List[str]:
+        return WiFiManager.get_wifi_interfaces()
+
+    def scan(self, interface: str) -> List[WiFiNetwork]:
+        return WiFiManager.scan_networks(interface)
+
+    async def connect(self, interface: str, ssid: str, password: Optional[str] = None,
+                      username: Optional[str] = None, security: Optional[WifiSecurity] = None,
+                      eap: Optional[Dict] = None) -> bool:
+        return await WiFiManager.connect_to_network(interface, ssid, password, username, security, eap)
+
+    def disconnect(self, interface: str) -> bool:
+        return WiFiManager.disconnect(interface)
+
+    def current_ssid(self, interface: str) -> Optional[str]:
+        return WiFiManager.get_current_connection(interface)
+
+@register_backend("wifi", "iwd")
+class IwdWifiBackend(WifiBackend):
+    """iwd via iwctl, for systems that replaced wpa_supplicant"""
+
+    required_tools = ("iwctl",)
+    iwd_dir = Path("/var/lib/iwd")
+
+    def list_interfaces(self) -> List[str]:
+        return WiFiManager.get_wifi_interfaces()
+
+    def scan(self, interface: str) -> List[WiFiNetwork]:
+        # iwctl output is meant for humans; iw gives the same data in a stable format
+        return WiFiManager.scan_networks(interface)
+
+    async def connect(self, interface: str, ssid: str, password: Optional[str] = None,
+                      username: Optional[str] = None, security: Optional[WifiSecurity] = None,
+                      eap: Optional[Dict] = None) -> bool:
+        # iwd takes 802.1X settings only from a provisioning file, and a PSK goes in one too, not in argv
+        if eap:
+            if not self._provision(ssid, "8021x", self._eap_settings(eap)):
+                return False
+        elif password:
+            if not self._provision(ssid, "psk", ["[Security]", f"Passphrase={password}"]):
+                return False
+        cmd = ['iwctl', 'station', interface, 'connect', ssid]
+
+        try:
+            process = await asyncio.create_subprocess_exec(
+                *cmd, stdout=asyncio.subprocess.PIPE, stderr=asyncio.subprocess.PIPE
+            )
+            _, stderr = await communicate_or_terminate(process)
+            if process.returncode != 0:
+                logger.error(f"iwctl connect to {ssid} failed: {stderr.decode().strip()}")
+                return False
+        except Exception as e:
+            logger.error(f"iwctl connect error: {e}")
+            return False
+
+        # iwd only associates; addressing stays with dhcpcd unless iwd's own DHCP is on
+        return await NetworkControl.ensure_dhcp_client(interface)
+
+    @staticmethod
+    def _eap_settings(eap: Dict) -> List[str]:
+        method = (eap.get("method") or "peap").upper()
+        lines = ["[Security]", f"EAP-Method={method}", f"EAP-Identity={eap.get('identity') or ''}",
+                 f"EAP-{method}-CACert={eap.get('ca_cert') or SYSTEM_CA_BUNDLE}"]
+        if method == "TLS":
+            lines += [f"EAP-TLS-ClientCert={eap['client_cert']}", f"EAP-TLS-ClientKey={eap['private_key']}"]
+        else:
+            lines += [f"EAP-{method}-Phase2-Method=MSCHAPV2",
+                      f"EAP-{method}-Phase2-Identity={eap.get('identity') or ''}",
+                      f"EAP-{method}-Phase2-Password={eap.get('password') or ''}"]
+        return lines
+
+    def _provision(self, ssid: str, kind: str, lines: List[str]) -> bool:
+        """Write the iwd settings file of one network (kind psk or 8021x), readable by root only from the start"""
+        # Names other than plain alphanumerics are hex-encoded behind a "="
+        name = ssid if re.fullmatch(r"[A-Za-z0-9_-]+", ssid) else "=" + ssid.encode().hex()
+        try:
+            self.iwd_dir.mkdir(parents=True, exist_ok=True)
+            fd = os.open(self.iwd_dir / f"{name}.{kind}", os.O_WRONLY | os.O_CREAT | os.O_TRUNC, 0o600)
+            os.fchmod(fd, 0o600)
+            with os.fdopen(fd, 'w') as f:
+                f.write("\n".join(lines) + "\n")
+            return True
+        except OSError as e:
+            logger.error(f"Cannot write iwd settings for {ssid}: {e}")
+            return False
+
+    def disconnect(self, interface: str) -> bool:
+        try:
+            result = subprocess.run(['iwctl', 'station', interface, 'disconnect'], capture_output=True)
+            return result.returncode == 0
+        except Exception:
+            return False
+
+    def current_ssid(self, interface: str) -> Optional[str]:
+        return WiFiManager.get_current_connection(interface)
+
+@register_backend("vpn", "wireguard")
+class WireGuardVpnBackend(VpnBackend):
+    """wg-quick managed WireGuard tunnels"""
+
+    required_tools = ("wg", "wg-quick")
+
+    def discover(self) -> List[VpnConfig]:
+        return VpnManager.discover_configs()
+
+    async def connect(self, config: VpnConfig, certs: Optional[Dict[str, str]] = None) -> Tuple[bool, str]:
+        if certs:
+            # WireGuard peers authenticate with their own keys, not X.509
+            return False, "WireGuard tunnels do not use certificates"
+        return await VpnManager.connect_wireguard(config.path)
+
+    async def disconnect(self, config: VpnConfig) -> bool:
+        return await VpnManager.disconnect_wireguard(config.name)
+
+    def is_active(self, config: VpnConfig) -> bool:
+        return VpnManager.is_wireguard_active(config.name)
+
+@register_backend("vpn", "openvpn")
+class OpenVpnBackend(VpnBackend):
+    """OpenVPN client configs run as background daemons"""
+
+    required_tools = ("openvpn",)
+    search_paths = [Path("/etc/openvpn/client"), Path("/etc/openvpn")]
+    pid_dir = Path("/run/alopex/openvpn")
+
+    def discover(self) -> List[VpnConfig]:
+        configs = []
+        for search_path in self.search_paths:
+            if search_path.exists():
+                for config_file in sorted(search_path.glob("*.conf")) + sorted(search_path.glob("*.ovpn")):
+                    configs.append(VpnConfig(name=config_file.stem, path=config_file, config_type="openvpn"))
+        return configs
+
+    async def connect(self, config: VpnConfig, certs: Optional[Dict[str, str]] = None) -> Tuple[bool, str]:
+        self.pid_dir.mkdir(parents=True, exist_ok=True)
+        # Options given on the command line win over the same ones in the config file
+        cert_args = [arg for option in ("ca", "cert", "key") if (certs or {}).get(option)
+                     for arg in (f'--{option}', certs[option])]
+        try:
+            process = await asyncio.create_subprocess_exec(
+                'sudo', 'openvpn', '--config', str(config.path), '--daemon', f'alopex-{config.name}',
+                '--writepid', str(self._pid_file(config)), *cert_args,
+                stdout=asyncio.subprocess.PIPE, stderr=asyncio.subprocess.PIPE
+            )
+            _, stderr = await communicate_or_terminate(process)
+            if process.returncode != 0:
+                return False, f"Connection failed: {stderr.decode().strip()}"
+            return True, f"Started OpenVPN {config.name}"
+        except Exception as e:
+            return False, f"Exception: {e}"
+
+    async def disconnect(self, config: VpnConfig) -> bool:
+        pid_file = self._pid_file(config)
+        if not pid_file.exists():
+            return False
+        try:
+            result = subprocess.run(['sudo', 'kill', pid_file.read_text().strip()], capture_output=True)
+            pid_file.unlink(missing_ok=True)
+            return result.returncode == 0
+        except Exception:
+            return False
+
+    def is_active(self, config: VpnConfig) -> bool:
+        pid_file = self._pid_file(config)
+        try:
+            return pid_file.exists() and Path(f"/proc/{pid_file.read_text().strip()}").exists()
+        except OSError:
+            return False
+
+    def _pid_file(self, config: VpnConfig) -> Path:
+        return self.pid_dir / f"{config.name}.pid"
+
+@register_backend("bluetooth", "bluetoothctl")
+class BluetoothctlBackend(BluetoothBackend):
+    """BlueZ through bluetoothctl"""
+
+    required_tools = ("bluetoothctl",)
+
+    async def scan(self) -> List[Dict]:
+        return await bluetooth.scan()
+
+    async def devices(self) -> List[Dict]:
+        return await bluetooth.known_devices()
+
+    async def device_info(self, mac_address: str) -> Optional[Dict]:
+        return await bluetooth.device_info(mac_address)
+
+    async def pair(self, mac_address: str) -> bool:
+        return await bluetooth.pair(mac_address)
+
+    async def connect(self, mac_address: str) -> bool:
+        return await bluetooth.connect(mac_address)
+
+    async def send_file(self, mac_address: str, path: str, progress: Callable[[int, Optional[int]], None],
+                        uid: Optional[int] = None) -> Dict:
+        return await obex.send_file(mac_address, path, progress, uid)
+
+    def paired_devices(self) -> List[Dict]:
+        return BluetoothControl.get_paired_devices()
+
+@register_backend("host", "system")
+class SystemHostBackend(HostBackend):
+    """resolv.conf, a proxy environment file and firewalld"""
+
+    required_tools = ("ip", "firewall-cmd")
+
+    proxy_file = Path("/run/alopex/proxy.env")
+
+    async def set_dns(self, servers: List[str]) -> bool:
+        await NetworkControl._update_resolv_conf(servers)
+        return True
+
+    async def set_proxy(self, proxy: Optional[str]) -> bool:
+        return NetworkControl.write_proxy_env(self.proxy_file, proxy)
+
+    async def set_firewall_zone(self, interface: str, zone: str) -> bool:
+        if not shutil.which("firewall-cmd"):
+            logger.warning(f"firewalld is not installed, not moving {interface} to zone {zone}")
+            return False
+        return await NetworkControl.set_firewall_zone(interface, zone)
+
+    async def firewall_zone(self, interface: str) -> Optional[str]:
+        if not shutil.which("firewall-cmd"):
+            return None
+        return await NetworkControl.get_firewall_zone(interface)
+
+    def gateway_mac(self, gateway: str) -> Optional[str]:
+        return NetworkControl.get_neighbor_mac(gateway)
+
+    async def query_dns(self, server: str, name: str, timeout: float) -> float:
+        return await asyncio.get_running_loop().run_in_executor(None, query_server, server, name, timeout)
+
+    def interface_address(self, interface: str) -> Optional[ipaddress.IPv4Interface]:
+        return NetworkControl.get_interface_address(interface)
+
+    async def scan_lan(self, interface: str, address: ipaddress.IPv4Interface) -> List[Dict]:
+        return await asyncio.get_running_loop().run_in_executor(None, lan_scan.scan, interface, address)
+
+    async def probe_port(self, host: str, port: int, source: Optional[str], timeout: float) -> Dict:
+        return await port_probe.probe(host, port, source, timeout)
+
+    async def check_families(self, host: str, port: int, timeout: float) -> Dict:
+        return await connectivity.check(host, port, timeout)
+
+    async def ping(self, host: str, count: int, interval: float, timeout: float,
+                   interface: Optional[str] = None) -> List[Optional[float]]:
+        return await NetworkControl.ping_series(host, count, interval, timeout, interface)
+
+    async def link_modes(self, interface: str) -> Optional[Dict]:
+        return await asyncio.get_running_loop().run_in_executor(None, link_speed.read_link_modes, interface)
+
+    async def cable_test(self, interface: str) -> List[Dict]:
+        return await cable_test.run(interface)
+
+    async def module_info(self, interface: str) -> Optional[Dict]:
+        return await asyncio.get_running_loop().run_in_executor(None, transceiver.read_module, interface)
+
+    async def nic_queues(self, interface: str) -> Dict:
+        return await asyncio.get_running_loop().run_in_executor(None, nic_queues.read_queues, interface)
+
+    def set_irq_affinity(self, irq: int, cpus: List[int]) -> bool:
+        return nic_queues.write_affinity(irq, cpus)
+
+    async def flows(self) -> Tuple[List[Dict], Optional[int]]:
+        flows = await asyncio.get_running_loop().run_in_executor(None, conntrack.dump_flows)
+        return flows, conntrack.table_size()
+
+    async def announce_addresses(self, interface: str, mac: str, addresses: List[str]) -> Dict:
+        return await asyncio.get_running_loop().run_in_executor(
+            None, neighbor_announce.announce, interface, mac, addresses)
+
+    def set_proxy_arp(self, interface: str, enabled: bool) -> bool:
+        return neighbor_announce.set_proxy_arp(interface, enabled)
+
+    def read_sysctl(self, key: str) -> Optional[str]:
+        return sysctls.read(key)
+
+    def write_sysctl(self, key: str, value: str) -> Optional[str]:
+        return sysctls.write(key, value)
+
+    async def mptcp_state(self) -> Dict:
+        return await asyncio.get_running_loop().run_in_executor(None, mptcp.read_state)
+
+    async def add_mptcp_endpoint(self, address: str, interface: str, flags: List[str]) -> Dict:
+        return await asyncio.get_running_loop().run_in_executor(None, mptcp.add_endpoint, address, interface, flags)
+
+    async def remove_mptcp_endpoint(self, endpoint_id: int):
+        await asyncio.get_running_loop().run_in_executor(None, mptcp.remove_endpoint, endpoint_id)
+
+    async def set_mptcp_limits(self, subflows: int, add_addr_accepted: int):
+        await asyncio.get_running_loop().run_in_executor(None, mptcp.set_limits, subflows, add_addr_accepted)
+
+    async def shaping_state(self, interface: str) -> Optional[Dict]:
+        return await shaping.read_state(interface)
+
+    async def set_qdisc(self, interface: str, qdisc: Optional[str], bandwidth: Optional[str] = None) -> Optional[str]:
+        return await shaping.set_qdisc(interface, qdisc, bandwidth)
+
+    async def set_congestion_control(self, interface: str, algorithm: Optional[str]) -> Optional[str]:
+        return await shaping.set_congestion_control(interface, algorithm)
+
+    async def load_link(self, interface: str, direction: str, seconds: float, url: Optional[str] = None) -> Dict:
+        return await load_test.saturate(interface, direction, seconds, url)
+
+@register_backend("services", "systemd")
+class SystemdServiceBackend(ServiceBackend):
+    """systemd's Manager interface on the system D-Bus (dbus-python)"""
+
+    @classmethod
+    def is_available(cls) -> bool:
+        try:
+            import dbus  # noqa: F401
+        except ImportError:
+            return False
+        return Path("/run/systemd/system").exists()
+
+    async def start_unit(self, unit: str) -> Tuple[bool, str]:
+        return await self._manager_call("StartUnit", unit)
+
+    async def stop_unit(self, unit: str) -> Tuple[bool, str]:
+        return await self._manager_call("StopUnit", unit)
+
+    async def _manager_call(self, method: str, unit: str) -> Tuple[bool, str]:
+        # dbus-python is blocking; keep it off the event loop
+        return await asyncio.get_running_loop().run_in_executor(None, self._call, method, unit)
+
+    @staticmethod
+    def _call(method: str, unit: str) -> Tuple[bool, str]:
+        try:
+            import dbus
+        except ImportError:
+            return False, "dbus-python is not installed"
+        try:
+            bus = dbus.SystemBus()
+            manager = dbus.Interface(
+                bus.get_object("org.freedesktop.systemd1", "/org/freedesktop/systemd1"),
+                "org.freedesktop.systemd1.Manager"
+            )
+            job = getattr(manager, method)(unit, "replace")
+            return True, f"queued as {job}"
+        except dbus.DBusException as e:
+            return False, e.get_dbus_message() or e.get_dbus_name()
+
+@register_backend("rfkill", "sysfs")
+class SysfsRfkillBackend(RfkillBackend):
+    """/sys/class/rfkill, written directly (the daemon runs as root)"""
+
+    @classmethod
+    def is_available(cls) -> bool:
+        return Path("/sys/class/rfkill").is_dir()
+
+    def devices(self) -> List[RfkillDevice]:
+        return read_devices()
+
+    def set_soft_block(self, index: int, blocked: bool) -> bool:
+        return write_soft_block(index, blocked)
+
+@register_backend("hotspot", "hostapd")
+class HostapdHotspotBackend(HotspotBackend):
+    """hostapd for the access point and iptables to masquerade"""
+
+    required_tools = ("hostapd", "hostapd_cli", "iw", "iptables", "tc")
+
+    async def start(self, interface: str, ssid: str, password: Optional[str], channel: int,
+                    network: ipaddress.IPv4Interface, blocked: List[str],
+                    allowed: Optional[List[str]] = None, isolate: bool = False) -> Tuple[bool, str]:
+        return await HostapdHotspot.start(interface, ssid, password, channel, network, blocked, allowed, isolate)
+
+    async def stop(self, interface: str, network: Optional[ipaddress.IPv4Interface] = None) -> bool:
+        return await HostapdHotspot.stop(interface, network)
+
+    def stations(self, interface: str) -> List[Dict]:
+        return HostapdHotspot.stations(interface)
+
+    def kick(self, interface: str, mac: str) -> bool:
+        return HostapdHotspot.kick(interface, mac)
+
+    def set_blocked(self, interface: str, mac: str, blocked: bool, all_blocked: List[str]) -> bool:
+        return HostapdHotspot.set_blocked(interface, mac, blocked, all_blocked)
+
+    def set_filter(self, interface: str, allowed: Optional[List[str]], blocked: List[str]) -> bool:
+        return HostapdHotspot.set_filter(interface, allowed, blocked)
+
+    async def set_isolation(self, interface: str, isolate: bool) -> bool:
+        return await HostapdHotspot.set_isolation(interface, isolate)
+
+    async def set_limits(self, interface: str, limits: Dict[str, Dict]) -> bool:
+        return await HostapdHotspot.set_limits(interface, limits)
+
+@register_backend("dhcp_server", "builtin")
+class BuiltinDhcpServerBackend(DhcpServerBackend):
+    """alopexd answers DHCP itself, one server per interface, leases kept in LEASE_DIR"""
+
+    lease_dir: Optional[Path] = LEASE_DIR
+
+    def __init__(self):
+        self.servers: Dict[str, DhcpServer] = {}
+
+    async def start(self, interface: str, network: ipaddress.IPv4Interface, dns: List[str],
+                    reservations: Dict[str, str]) -> Tuple[bool, str]:
+        await self.stop(interface)
+        lease_file = self.lease_dir / f"{interface}.json" if self.lease_dir else None
+        server = DhcpServer(interface, LeasePool(network, reservations=reservations, lease_file=lease_file), dns)
+        try:
+            await self._listen(server)
+        except OSError as e:
+            logger.error(f"Cannot serve DHCP on {interface}: {e}")
+            return False, f"DHCP server failed: {e}"
+        self.servers[interface] = server
+        return True, f"Serving {server.pool.first}-{server.pool.last}"
+
+    async def _listen(self, server: DhcpServer):
+        await server.serve()
+
+    async def stop(self, interface: str) -> bool:
+        server = self.servers.pop(interface, None)
+        if server:
+            server.close()
+        return True
+
+    def leases(self, interface: str) -> List[Dict]:
+        server = self.servers.get(interface)
+        return [asdict(lease) for lease in server.pool.active()] if server else []
+
+    def set_reservations(self, interface: str, reservations: Dict[str, str]) -> bool:
+        server = self.servers.get(interface)
+        if server:
+            server.pool.set_reservations(reservations)
+        return True
+
+    def running(self) -> List[str]:
+        return list(self.servers)
+
+@register_backend("accounting", "nftables")
+class NftablesAccountingBackend(AccountingBackend):
+    """'socket cgroupv2' counters in an nftables table of our own"""
+
+    required_tools = ("nft",)
+
+    async def _nft(self, *args: str, script: Optional[str] = None) -> Tuple[bool, str]:
+        process = await asyncio.create_subprocess_exec(
+            'sudo', 'nft', *args, stdin=asyncio.subprocess.PIPE if script else None,
+            stdout=asyncio.subprocess.PIPE, stderr=asyncio.subprocess.PIPE
+        )
+        if script:
+            stdout, stderr = await process.communicate(script.encode())
+        else:
+            stdout, stderr = await communicate_or_terminate(process)
+        if process.returncode != 0:
+            return False, stderr.decode(errors="replace").strip()
+        return True, stdout.decode(errors="replace")
+
+    def cgroups(self) -> Dict[str, int]:
+        root = unit_traffic.cgroup2_root()
+        return unit_traffic.list_cgroups(root) if root else {}
+
+    async def sync(self, cgroups: List[str]) -> Tuple[bool, str]:
+        ok, output = await self._nft("-f", "-", script=unit_traffic.nft_ruleset(cgroups))
+        return ok, (output if not ok else f"Counting {len(cgroups)} units")
+
+    async def counters(self) -> Dict[str, Dict[str, int]]:
+        ok, output = await self._nft("-j", "list", "table", "inet", unit_traffic.NFT_TABLE)
+        if not ok:
+            raise OSError(output)
+        try:
+            return unit_traffic.parse_counters(json.loads(output))
+        except ValueError as e:
+            raise OSError(f"Unreadable nft output: {e}")
+
+    async def clear(self) -> bool:
+        ok, _ = await self._nft("delete", "table", "inet", unit_traffic.NFT_TABLE)
+        return ok
+
+@register_backend("counters", "ebpf")
+class EbpfCountersBackend(CountersBackend):
+    """traffic_counters.bpf.o pinned with bpftool and attached to clsact with tc"""
+
+    required_tools = ("bpftool", "tc")
+
+    def __init__(self):
+        self.map: Optional[fast_counters.BpfMap] = None
+        self.attached: Dict[int, str] = {}
+
+    async def _run(self, *args: str) -> Tuple[bool, str]:
+        process = await asyncio.create_subprocess_exec(
+            'sudo', *args, stdout=asyncio.subprocess.PIPE, stderr=asyncio.subprocess.PIPE
+        )
+        _, stderr = await communicate_or_terminate(process)
+        return process.returncode == 0, stderr.decode(errors="replace").strip()
+
+    def unavailable(self, object_path: str) -> Optional[str]:
+        if not self.is_available():
+            return "bpftool and tc are needed"
+        return fast_counters.unavailable_reason(Path(object_path))
+
+    async def start(self, object_path: str) -> Tuple[bool, str]:
+        # Pins left by a daemon that did not shut down cleanly
+        await self._run('rm', '-rf', str(fast_counters.PIN_DIR))
+        pin_maps = fast_counters.PIN_DIR / "maps"
+        ok, message = await self._run('bpftool', 'prog', 'loadall', object_path, str(fast_counters.PIN_DIR),
+                                      'pinmaps', str(pin_maps))
+        if not ok:
+            return False, message or "bpftool could not load the programs"
+        try:
+            fd = fast_counters.obj_get(pin_maps / fast_counters.MAP_NAME)
+        except OSError as e:
+            return False, f"Cannot open the counter map: {e}"
+        self.map = fast_counters.BpfMap(fd, fast_counters.KEY.size, fast_counters.VALUE.size)
+        return True, "Loaded"
+
+    async def attach(self, interface: str) -> Tuple[bool, str]:
+        ifindex = fast_counters.interface_index(interface)
+        if ifindex is None:
+            return False, "No such interface"
+        ok, message = await self._run('tc', 'qdisc', 'replace', 'dev', interface, 'clsact')
+        for hook, program in fast_counters.PROGRAMS.items():
+            if not ok:
+                break
+            ok, message = await self._run('tc', 'filter', 'replace', 'dev', interface, hook, 'protocol', 'all',
+                                          'pref', fast_counters.TC_PREF, 'handle', '1', 'bpf', 'da',
+                                          'object-pinned', str(fast_counters.PIN_DIR / program))
+        if not ok:
+            await self.detach(interface)
+            return False, message
+        self.attached[ifindex] = interface
+        return True, "Attached"
+
+    async def detach(self, interface: str) -> bool:
+        ok = True
+        for hook in fast_counters.PROGRAMS:
+            removed, _ = await self._run('tc', 'filter', 'del', 'dev', interface, hook, 'pref', fast_counters.TC_PREF)
+            ok = ok and removed
+        self.attached = {index: name for index, name in self.attached.items() if name != interface}
+        return ok
+
+    def read(self) -> Dict[str, Dict[str, Dict[str, Tuple[int, int]]]]:
+        if self.map is None:
+            raise OSError("Counters not loaded")
+        counters = fast_counters.read_counters(self.map)
+        return {name: counters.get(index, {}) for index, name in self.attached.items()}
+
+    async def stop(self):
+        for interface in list(self.attached.values()):
+            await self.detach(interface)
+        if self.map:
+            self.map.close()
+            self.map = None
+        await self._run('rm', '-rf', str(fast_counters.PIN_DIR))
+
+@register_backend("containers", "engine_api")
+class EngineApiContainersBackend(ContainersBackend):
+    """Docker-compatible API sockets (Docker, or Podman's service), and bridge ports from sysfs"""
+
+    async def networks(self, socket_path: str) -> List[Dict]:
+        return await asyncio.get_running_loop().run_in_executor(None, containers.engine_networks, socket_path)
+
+    def masters(self) -> Dict[str, str]:
+        masters = {}
+        for master in Path("/sys/class/net").glob("*/master"):
+            try:
+                masters[master.parent.name] = master.resolve().name
+            except OSError:
+                continue
+        return masters
+
+@register_backend("libvirt", "virsh")
+class VirshLibvirtBackend(LibvirtBackend):
+    """virsh against a libvirt URI"""
+
+    required_tools = ("virsh",)
+
+    async def _virsh(self, uri: str, *args: str) -> str:
+        if not shutil.which("virsh"):
+            raise FileNotFoundError("virsh is not installed")
+        process = await asyncio.create_subprocess_exec(
+            'sudo', 'virsh', '-c', uri, *args,
+            stdout=asyncio.subprocess.PIPE, stderr=asyncio.subprocess.PIPE
+        )
+        stdout, stderr = await communicate_or_terminate(process)
+        if process.returncode != 0:
+            raise OSError(stderr.decode(errors="replace").strip() or f"virsh {args[0]} failed")
+        return stdout.decode(errors="replace")
+
+    async def networks(self, uri: str) -> List[Dict]:
+        names = (await self._virsh(uri, "net-list", "--all", "--name")).split()
+        active = set((await self._virsh(uri, "net-list", "--name")).split())
+        domains = {}
+        for domain in (await self._virsh(uri, "list", "--name")).split():
+            domains[domain] = libvirt.parse_domiflist(await self._virsh(uri, "domiflist", domain))
+
+        networks = []
+        for name in names:
+            try:
+                network = libvirt.parse_network_xml(await self._virsh(uri, "net-dumpxml", name))
+            except ElementTree.ParseError as e:
+                logger.warning(f"Unreadable definition of libvirt network {name}: {e}")
+                continue
+            leases = libvirt.parse_leases(await self._virsh(uri, "net-dhcp-leases", name)) if name in active else []
+            networks.append(dict(libvirt.attach_guests(network, leases, domains), active=name in active))
+        return networks
+
+@dataclass
+class BackendSet:
+    """The backends in use by one daemon instance"""
+    ethernet: EthernetBackend
+    wifi: WifiBackend
+    vpn: VpnBackend
+    bluetooth: BluetoothBackend
+    host: HostBackend
+    services: ServiceBackend
+    rfkill: RfkillBackend
+    hotspot: HotspotBackend
+    dhcp_server: DhcpServerBackend
+    accounting: AccountingBackend
+    counters: CountersBackend
+    containers: ContainersBackend
+    libvirt: LibvirtBackend
+
+    def describe(self) -> Dict[str, str]:
+        """Backend name per kind, for status output"""
+        return {kind: getattr(self, kind).backend_name for kind in BACKEND_KINDS}
+
+def load_backends(config: Optional[Dict[str, str]] = None) -> BackendSet:
+    """Instantiate backends named in config, falling back to the defaults"""
+    config = config or {}
+    selected = {}
+
+    for kind in BACKEND_KINDS:
+        name = config.get(kind, DEFAULT_BACKENDS[kind])
+        backend_cls = BACKEND_REGISTRY[kind].get(name)
+
+        if backend_cls is None:
+            logger.error(f"Unknown {kind} backend '{name}', using {DEFAULT_BACKENDS[kind]}")
+            backend_cls = BACKEND_REGISTRY[kind][DEFAULT_BACKENDS[kind]]
+        elif not backend_cls.is_available():
+            missing = ', '.join(backend_cls.required_tools) or "its runtime dependencies"
+            logger.warning(f"{kind} backend '{name}' is missing {missing}")
+
+        selected[kind] = backend_cls()
+        logger.info(f"Using {kind} backend: {backend_cls.backend_name}")
+
+    return BackendSet(**selected)
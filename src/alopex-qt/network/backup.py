@@ -0,0 +1,96 @@
+"""
+Profile Backup - Encrypted archives of profiles and their secrets
+A passphrase-derived key (scrypt) seals the archive with AES-256-GCM
+"""
+
+import json
+import time
+import base64
+import socket
+import hashlib
+import secrets
+from typing import Dict
+
+ARCHIVE_FORMAT = "alopex-backup"
+ARCHIVE_VERSION = 1
+
+# scrypt cost; stored in the archive so it can be raised later
+SCRYPT_N = 2 ** 15
+SCRYPT_R = 8
+SCRYPT_P = 1
+
+class BackupError(Exception):
+    """Archive could not be written or read"""
+
+def available() -> bool:
+    """Check whether the cipher implementation is installed"""
+    try:
+        import cryptography  # noqa: F401
+        return True
+    except ImportError:
+        return False
+
+def _aesgcm(key: bytes):
+    try:
+        from cryptography.hazmat.primitives.ciphers.aead import AESGCM
+    except ImportError:
+        raise BackupError("Encrypted backups need the Python 'cryptography' package")
+    return AESGCM(key)
+
+def _derive_key(passphrase: str, salt: bytes, n: int, r: int, p: int) -> bytes:
+    return hashlib.scrypt(passphrase.encode(), salt=salt, n=n, r=r, p=p, maxmem=2 * 128 * n * r, dklen=32)
+
+def create_archive(profiles: Dict[str, Dict], passphrase: str) -> str:
+    """Seal profiles (name -> profile dict, secrets included) into an archive string"""
+    if not passphrase:
+        raise BackupError("A passphrase is required")
+
+    payload = json.dumps({
+        "created_at": time.time(),
+        "hostname": socket.gethostname(),
+        "profiles": profiles
+    }).encode()
+
+    salt = secrets.token_bytes(16)
+    nonce = secrets.token_bytes(12)
+    header = {
+        "format": ARCHIVE_FORMAT,
+        "version": ARCHIVE_VERSION,
+        "kdf": {"name": "scrypt", "salt": base64.b64encode(salt).decode(), "n": SCRYPT_N, "r": SCRYPT_R, "p": SCRYPT_P},
+        "cipher": "AES-256-GCM",
+        "nonce": base64.b64encode(nonce).decode()
+    }
+    key = _derive_key(passphrase, salt, SCRYPT_N, SCRYPT_R, SCRYPT_P)
+    # The header is authenticated too, so its parameters cannot be swapped
+    ciphertext = _aesgcm(key).encrypt(nonce, payload, json.dumps(header, sort_keys=True).encode())
+
+    return json.dumps(dict(header, data=base64.b64encode(ciphertext).decode()), indent=2)
+
+def open_archive(archive: str, passphrase: str) -> Dict:
+    """Decrypt an archive, returning {"created_at", "hostname", "profiles"}"""
+    try:
+        document = json.loads(archive)
+        header = {key: document[key] for key in ("format", "version", "kdf", "cipher", "nonce")}
+        ciphertext = base64.b64decode(document["data"])
+        salt = base64.b64decode(header["kdf"]["salt"])
+        nonce = base64.b64decode(header["nonce"])
+    except (ValueError, KeyError, TypeError) as e:
+        raise BackupError(f"Not an alopex backup archive: {e}")
+
+    if header["format"] != ARCHIVE_FORMAT or header["version"] > ARCHIVE_VERSION:
+        raise BackupError(f"Unsupported archive format {header['format']} v{header['version']}")
+    if header["kdf"].get("name") != "scrypt" or header["cipher"] != "AES-256-GCM":
+        raise BackupError("Unsupported archive encryption")
+
+    kdf = header["kdf"]
+    if kdf["n"] > 2 ** 20 or kdf["r"] > 16 or kdf["p"] > 4:
+        raise BackupError("Archive asks for unreasonable key derivation cost")
+    cipher = _aesgcm(_derive_key(passphrase, salt, kdf["n"], kdf["r"], kdf["p"]))
+
+    from cryptography.exceptions import InvalidTag
+    try:
+        payload = cipher.decrypt(nonce, ciphertext, json.dumps(header, sort_keys=True).encode())
+    except InvalidTag:
+        raise BackupError("Wrong passphrase or damaged archive")
+
+    return json.loads(payload)
@@ -0,0 +1,163 @@
+"""
+Bandwidth History - Per-minute traffic of every interface, kept on disk
+One JSON line per interface and minute in /var/lib/alopex/bandwidth/<iface>.jsonl,
+pruned to the retention period once a day; exported as CSV or JSON
+"""
+
+import io
+import os
+import re
+import csv
+import json
+import time
+import logging
+from datetime import datetime
+from pathlib import Path
+from typing import Dict, Iterable, List, Optional, Tuple
+
+from .discovery import InterfaceType, NetworkInterface
+
+# Configure logging
+logger = logging.getLogger(__name__)
+
+BUCKET_SECONDS = 60
+DEFAULT_RETENTION_DAYS = 30
+PRUNE_INTERVAL = 86400.0
+
+CSV_FIELDS = ["time", "timestamp", "interface", "rx_bytes", "tx_bytes", "rx_rate", "tx_rate"]
+
+# "90m", "24h", "7d": that long before now
+RELATIVE_TIME = re.compile(r"^(\d+(?:\.\d+)?)\s*([smhdw])$")
+TIME_UNITS = {"s": 1, "m": 60, "h": 3600, "d": 86400, "w": 604800}
+
+def parse_time(text: str, now: Optional[float] = None) -> float:
+    """Timestamp from "now", a span before now ("24h") or a local ISO date/time; raises ValueError"""
+    now = time.time() if now is None else now
+    text = text.strip().lower()
+    if text == "now":
+        return now
+    match = RELATIVE_TIME.match(text)
+    if match:
+        return now - float(match.group(1)) * TIME_UNITS[match.group(2)]
+    try:
+        return datetime.fromisoformat(text.upper()).timestamp()
+    except ValueError:
+        raise ValueError(f"Expected a time like 24h, 7d, 2026-10-01 or 2026-10-01T08:00, got {text!r}")
+
+def to_csv(samples: List[Dict]) -> str:
+    """Samples as CSV with a local time column, for spreadsheets"""
+    output = io.StringIO()
+    writer = csv.DictWriter(output, CSV_FIELDS, extrasaction="ignore", lineterminator="\n")
+    writer.writeheader()
+    for sample in samples:
+        stamp = time.strftime("%Y-%m-%d %H:%M:%S", time.localtime(sample["timestamp"]))
+        writer.writerow(dict(sample, time=stamp))
+    return output.getvalue()
+
+class BandwidthHistory:
+    """Bytes moved per interface per minute, from the counter samples the daemon already takes"""
+
+    def __init__(self, path: Path, retention_days: float = DEFAULT_RETENTION_DAYS,
+                 bucket_seconds: int = BUCKET_SECONDS):
+        self.path = path
+        self.retention = retention_days * 86400
+        self.bucket_seconds = bucket_seconds
+        # Last counters seen, and the minute being filled, per interface
+        self.counters: Dict[str, Tuple[int, int]] = {}
+        self.pending: Dict[str, Dict] = {}
+        self.last_prune = 0.0
+
+    def _file(self, interface: str) -> Path:
+        return self.path / f"{interface}.jsonl"
+
+    def record(self, interfaces: Iterable[NetworkInterface], now: Optional[float] = None):
+        """Add the traffic since the previous sample to the current minute of each interface"""
+        now = time.time() if now is None else now
+        start = now - now % self.bucket_seconds
+        for name in [name for name, bucket in self.pending.items() if bucket["timestamp"] != start]:
+            self._write(name, self.pending.pop(name))
+
+        for interface in interfaces:
+            if interface.interface_type == InterfaceType.LOOPBACK:
+                continue
+            rx, tx = interface.metrics.bytes_rx, interface.metrics.bytes_tx
+            previous = self.counters.get(interface.name)
+            self.counters[interface.name] = (rx, tx)
+            if previous is None:
+                continue
+            bucket = self.pending.setdefault(interface.name, {"timestamp": start, "rx_bytes": 0, "tx_bytes": 0})
+            # Counters that went backwards (device re-created) started again from zero
+            bucket["rx_bytes"] += rx - previous[0] if rx >= previous[0] else rx
+            bucket["tx_bytes"] += tx - previous[1] if tx >= previous[1] else tx
+
+        if now - self.last_prune >= PRUNE_INTERVAL:
+            self.prune(now)
+
+    def _sample(self, interface: str, bucket: Dict, seconds: float) -> Dict:
+        seconds = max(1.0, seconds)
+        return dict(bucket, interface=interface, rx_rate=round(bucket["rx_bytes"] / seconds),
+                    tx_rate=round(bucket["tx_bytes"] / seconds))
+
+    def _write(self, interface: str, bucket: Dict):
+        """Append a finished minute; minutes without traffic are left out"""
+        if not bucket["rx_bytes"] and not bucket["tx_bytes"]:
+            return
+        try:
+            self.path.mkdir(parents=True, exist_ok=True)
+            with open(self._file(interface), "a") as f:
+                f.write(json.dumps(self._sample(interface, bucket, self.bucket_seconds)) + "\n")
+        except OSError as e:
+            logger.error(f"Failed to save bandwidth history of {interface}: {e}")
+
+    def flush(self):
+        """Write the minutes still being filled, e.g. on shutdown"""
+        for name, bucket in self.pending.items():
+            self._write(name, bucket)
+        self.pending.clear()
+
+    def interfaces(self) -> List[str]:
+        """Interfaces with any history"""
+        stored = {path.stem for path in self.path.glob("*.jsonl")} if self.path.is_dir() else set()
+        return sorted(stored | set(self.pending))
+
+    def query(self, interface: str, since: Optional[float] = None, until: Optional[float] = None) -> List[Dict]:
+        """Minutes of interface from since up to until, oldest first, the current one included"""
+        samples = []
+        path = self._file(interface)
+        if path.exists():
+            with open(path) as f:
+                for line in f:
+                    try:
+                        samples.append(json.loads(line))
+                    except ValueError:
+                        continue
+        bucket = self.pending.get(interface)
+        if bucket and (bucket["rx_bytes"] or bucket["tx_bytes"]):
+            samples.append(self._sample(interface, bucket, time.time() - bucket["timestamp"]))
+        # A minute counts when any of it falls in the range
+        return [s for s in samples if (since is None or s["timestamp"] + self.bucket_seconds > since)
+                and (until is None or s["timestamp"] < until)]
+
+    def prune(self, now: Optional[float] = None):
+        """Drop minutes older than the retention period"""
+        now = time.time() if now is None else now
+        self.last_prune = now
+        if not self.path.is_dir():
+            return
+        cutoff = now - self.retention
+        for path in self.path.glob("*.jsonl"):
+            try:
+                with open(path) as f:
+                    lines = f.readlines()
+                kept = [line for line in lines if json.loads(line).get("timestamp", 0) >= cutoff]
+                if len(kept) == len(lines):
+                    continue
+                if not kept:
+                    path.unlink()
+                    continue
+                temp_path = path.with_suffix(".jsonl.tmp")
+                with open(temp_path, "w") as f:
+                    f.writelines(kept)
+                os.replace(temp_path, path)
+            except (OSError, ValueError) as e:
+                logger.error(f"Failed to prune {path}: {e}")
@@ -0,0 +1,198 @@
+"""
+Bluetooth - Nearby and known devices as BlueZ sees them, through bluetoothctl
+Tells Low Energy peripherals from classic devices, lists the services they
+advertise, and reads the battery level BlueZ takes from the GATT Battery
+Service (0x180F, Battery Level 0x2A19) once an LE device is connected. Local
+aliases live with the daemon, so renaming a device doesn't touch BlueZ
+"""
+
+import os
+import re
+import json
+import asyncio
+import logging
+from pathlib import Path
+from typing import Dict, List, Optional, Tuple
+
+from .oui import lookup_vendor
+from .system_integration import communicate_or_terminate
+
+# Configure logging
+logger = logging.getLogger(__name__)
+
+SCAN_SECONDS = 10
+# How long an unknown LE peripheral gets to show up before connecting to it
+CONNECT_SCAN_SECONDS = 5
+MAC = re.compile(r"^[0-9A-F]{2}(:[0-9A-F]{2}){5}$")
+DEVICE_LINE = re.compile(r"^Device ([0-9A-Fa-f:]{17})(?: \((public|random)\))?(?: (.*))?$")
+UUID_LINE = re.compile(r"^(.*?)\s*\(([0-9a-fA-F-]{36})\)$")
+# GATT's own services, which only LE (or dual-mode) devices expose
+GATT_UUIDS = ("00001800-0000-1000-8000-00805f9b34fb", "00001801-0000-1000-8000-00805f9b34fb")
+BATTERY_SERVICE = "0000180f-0000-1000-8000-00805f9b34fb"
+ALIAS_LENGTH = 48
+
+def normalize_address(mac: str) -> str:
+    """AA:BB:CC:DD:EE:FF; raises ValueError for anything else"""
+    address = mac.strip().upper().replace("-", ":")
+    if not MAC.match(address):
+        raise ValueError(f"Invalid Bluetooth address: {mac}")
+    return address
+
+def _number(value: str) -> Optional[int]:
+    """"0xffffffb5 (-75)" (BlueZ 5.6x) or "-75" (older) -> -75"""
+    match = re.search(r"\((-?\d+)\)", value) or re.match(r"^(-?\d+)$", value.strip())
+    return int(match.group(1)) if match else None
+
+def parse_devices(text: str) -> List[Tuple[str, str]]:
+    """(address, name) pairs from `bluetoothctl devices`"""
+    devices = []
+    for line in text.splitlines():
+        match = DEVICE_LINE.match(line.strip())
+        if match:
+            devices.append((match.group(1).upper(), match.group(3) or match.group(1).upper()))
+    return devices
+
+def blank(address: str, name: Optional[str] = None) -> Dict:
+    """The record of a device BlueZ lists but won't describe"""
+    return {"mac": address, "name": name, "alias": None, "type": None, "address_type": None, "icon": None,
+            "paired": False, "trusted": False, "connected": False, "rssi": None, "tx_power": None, "battery": None,
+            "battery_service": False, "services": [], "vendor": lookup_vendor(address)}
+
+def parse_info(text: str) -> Optional[Dict]:
+    """A device record from `bluetoothctl info ADDRESS`, or None if BlueZ doesn't know it"""
+    lines = [line.strip() for line in text.splitlines() if line.strip()]
+    header = next((DEVICE_LINE.match(line) for line in lines if DEVICE_LINE.match(line)), None)
+    if not header:
+        return None
+    fields: Dict[str, str] = {}
+    services = []
+    for line in lines:
+        key, colon, value = line.partition(":")
+        if not colon or key.startswith("Device "):
+            continue
+        if key == "UUID":
+            match = UUID_LINE.match(value.strip())
+            if match:
+                services.append({"uuid": match.group(2).lower(), "name": match.group(1).strip() or None})
+        else:
+            fields.setdefault(key.strip(), value.strip())
+    # "Device ... not available" has the header and nothing else
+    if not fields:
+        return None
+
+    address, address_type = header.group(1).upper(), header.group(2)
+    classic = "Class" in fields
+    le = address_type == "random" or "Appearance" in fields or any(s["uuid"] in GATT_UUIDS for s in services)
+    battery = fields.get("Battery Percentage")
+    return {
+        "mac": address,
+        "name": fields.get("Name"),
+        "alias": fields.get("Alias"),
+        "type": "dual" if classic and le else "le" if le else "classic" if classic else None,
+        "address_type": address_type,
+        "icon": fields.get("Icon"),
+        "paired": fields.get("Paired") == "yes",
+        "trusted": fields.get("Trusted") == "yes",
+        "connected": fields.get("Connected") == "yes",
+        "rssi": _number(fields["RSSI"]) if "RSSI" in fields else None,
+        "tx_power": _number(fields["TxPower"]) if "TxPower" in fields else None,
+        "battery": _number(battery) if battery else None,
+        # Advertised, so connecting will get a battery reading
+        "battery_service": any(s["uuid"] == BATTERY_SERVICE for s in services),
+        "services": services,
+        # Random addresses are made up by the device; only public ones carry an OUI
+        "vendor": lookup_vendor(address) if address_type != "random" else None,
+    }
+
+def sort_devices(devices: List[Dict]) -> List[Dict]:
+    """Connected, then paired, then merely nearby; strongest signal first within each, unheard ones last"""
+    def key(device):
+        group = 0 if device["connected"] else 1 if device["paired"] else 2
+        return (group, device["rssi"] is None, -(device["rssi"] or 0),
+                (device["alias"] or device["name"] or device["mac"]).lower())
+    return sorted(devices, key=key)
+
+class BluetoothAliases:
+    """Local names for Bluetooth devices by address, kept in bluetooth-aliases.json"""
+
+    def __init__(self, config_path: Path):
+        self.aliases_file = config_path / "bluetooth-aliases.json"
+        self.aliases: Dict[str, str] = {}
+        self._load()
+
+    def _load(self):
+        if not self.aliases_file.exists():
+            return
+        try:
+            with open(self.aliases_file) as f:
+                self.aliases = {address.upper(): alias for address, alias in json.load(f).get("devices", {}).items()}
+            logger.info(f"Loaded aliases for {len(self.aliases)} Bluetooth devices")
+        except Exception as e:
+            logger.error(f"Failed to load Bluetooth aliases: {e}")
+
+    def _save(self):
+        """Save aliases via a temp file"""
+        self.aliases_file.parent.mkdir(parents=True, exist_ok=True)
+        temp_file = self.aliases_file.with_suffix(".tmp")
+        with open(temp_file, "w") as f:
+            json.dump({"devices": self.aliases}, f, indent=2)
+        os.replace(temp_file, self.aliases_file)
+
+    def set(self, address: str, alias: Optional[str]):
+        """Name a device; an empty alias goes back to what BlueZ calls it. Raises ValueError for unusable names"""
+        alias = " ".join(alias.split()) if alias else None
+        if alias and (len(alias) > ALIAS_LENGTH or not alias.isprintable()):
+            raise ValueError(f"Invalid alias: {alias!r} (printable, up to {ALIAS_LENGTH} characters)")
+        if alias:
+            self.aliases[address] = alias
+        elif self.aliases.pop(address, None) is None:
+            return
+        self._save()
+
+    def apply(self, device: Optional[Dict]) -> Optional[Dict]:
+        """The device record with its local alias, if it has one"""
+        if device and device["mac"] in self.aliases:
+            device = dict(device, alias=self.aliases[device["mac"]])
+        return device
+
+async def _bluetoothctl(*args: str) -> Tuple[int, str]:
+    """Exit code and output of one bluetoothctl command; raises OSError without bluetoothctl"""
+    process = await asyncio.create_subprocess_exec(
+        'bluetoothctl', *args, stdout=asyncio.subprocess.PIPE, stderr=asyncio.subprocess.STDOUT
+    )
+    stdout, _ = await communicate_or_terminate(process)
+    return process.returncode, stdout.decode(errors="replace")
+
+async def device_info(address: str) -> Optional[Dict]:
+    returncode, output = await _bluetoothctl('info', address)
+    return parse_info(output) if returncode == 0 else None
+
+async def known_devices() -> List[Dict]:
+    """Every device BlueZ knows: paired ones and those seen by a recent scan"""
+    _, output = await _bluetoothctl('devices')
+    devices = []
+    for address, name in parse_devices(output):
+        info = await device_info(address)
+        devices.append(info or blank(address, name))
+    return devices
+
+async def scan(seconds: int = SCAN_SECONDS) -> List[Dict]:
+    """Discover for a while (classic and LE alike), then list what BlueZ knows"""
+    returncode, output = await _bluetoothctl('--timeout', str(seconds), 'scan', 'on')
+    if returncode != 0:
+        raise OSError(output.strip().splitlines()[-1] if output.strip() else "bluetoothctl scan failed")
+    return await known_devices()
+
+async def connect(address: str) -> bool:
+    """Connect; an LE peripheral BlueZ hasn't seen yet gets a short LE scan first, no pairing needed"""
+    if not await device_info(address):
+        await _bluetoothctl('--timeout', str(CONNECT_SCAN_SECONDS), 'scan', 'le')
+    returncode, output = await _bluetoothctl('connect', address)
+    if returncode != 0 or "Connection successful" not in output:
+        logger.warning(f"Bluetooth connect to {address} failed: {(output.strip().splitlines() or [''])[-1]}")
+        return False
+    return True
+
+async def pair(address: str) -> bool:
+    returncode, output = await _bluetoothctl('pair', address)
+    return returncode == 0 and "Pairing successful" in output
@@ -0,0 +1,79 @@
+"""
+Boot - Bring boot-critical profiles up first and keep at them until they connect
+Profiles flagged boot_critical are connected as soon as the daemon starts,
+each retried with exponential backoff. `alopexd wait-online` blocks on them,
+which is what holds network-online.target back until they are up
+"""
+
+import time
+import asyncio
+import logging
+from typing import Awaitable, Callable, Dict, List, Optional
+
+from .retry import RETRY_INITIAL, RETRY_MAX, backoff
+
+# Configure logging
+logger = logging.getLogger(__name__)
+
+class BootCritical:
+    """Connect a fixed set of profiles, retrying each until it is up; online once all of them are.
+    connect(name) returns None once the profile is up, else why it isn't"""
+
+    def __init__(self, connect: Callable[[str], Awaitable[Optional[str]]], is_up: Callable[[str], bool],
+                 on_online: Optional[Callable[[List[str]], None]] = None,
+                 initial: float = RETRY_INITIAL, maximum: float = RETRY_MAX):
+        self.connect = connect
+        self.is_up = is_up
+        self.on_online = on_online
+        self.initial = initial
+        self.maximum = maximum
+        self.profiles: Dict[str, Dict] = {}
+        self.online = asyncio.Event()
+        self.online_at: Optional[float] = None
+
+    async def run(self, profiles: List[Dict]):
+        """Bring up profiles ({"name", "interface"}) concurrently; returns once every one is up"""
+        self.profiles = {profile["name"]: {"profile": profile["name"], "interface": profile["interface"],
+                                           "up": False, "attempts": 0, "last_error": None, "next_retry": None}
+                         for profile in profiles}
+        if self.profiles:
+            logger.info(f"Boot-critical: {', '.join(self.profiles)}")
+        await asyncio.gather(*(self._bring_up(name) for name in self.profiles))
+        self._set_online()
+
+    async def _bring_up(self, name: str):
+        entry = self.profiles[name]
+        # Already up, e.g. adopted from a previous daemon instance
+        while not self.is_up(name):
+            entry["attempts"] += 1
+            try:
+                entry["last_error"] = await self.connect(name)
+            except Exception as e:
+                entry["last_error"] = str(e)
+            if entry["last_error"] is None or self.is_up(name):
+                break
+            delay = backoff(entry["attempts"], self.initial, self.maximum)
+            entry["next_retry"] = time.time() + delay
+            logger.warning(f"Boot-critical {name} not up after attempt {entry['attempts']}, retrying in {delay:g}s")
+            await asyncio.sleep(delay)
+        entry.update(up=True, next_retry=None, last_error=None)
+        logger.info(f"Boot-critical {name} is up after {entry['attempts']} attempts")
+
+    def _set_online(self):
+        self.online_at = time.time()
+        self.online.set()
+        if self.profiles and self.on_online:
+            self.on_online(list(self.profiles))
+
+    def status(self) -> Dict:
+        """{"online", "online_at", "profiles"}; online with no boot-critical profiles at all"""
+        return {"online": self.online.is_set(), "online_at": self.online_at,
+                "profiles": [dict(entry) for entry in self.profiles.values()]}
+
+    async def wait(self, timeout: Optional[float] = None) -> Dict:
+        """Status once online, or when timeout runs out"""
+        try:
+            await asyncio.wait_for(self.online.wait(), timeout)
+        except asyncio.TimeoutError:
+            pass
+        return self.status()
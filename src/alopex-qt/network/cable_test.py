@@ -0,0 +1,70 @@
+"""
+Cable Test - Time-domain reflectometry through `ethtool --cable-test`
+The PHY sends a pulse down each twisted pair and times any reflection, which
+tells an open or shorted pair and how far along the cable the fault is.
+Needs a PHY and driver that support it (kernel 5.9+); the link drops while it runs
+"""
+
+import re
+import asyncio
+import logging
+from typing import Dict, List
+
+from .system_integration import communicate_or_terminate
+
+# Configure logging
+logger = logging.getLogger(__name__)
+
+PAIRS = ("A", "B", "C", "D")
+# ethtool's wording for each result code
+CODES = {"OK": "ok", "Open Circuit": "open", "Short within Pair": "short", "Short to another pair": "cross_short"}
+STATUS_TEXT = {"ok": "OK", "open": "open circuit", "short": "shorted within the pair",
+               "cross_short": "shorted to another pair", "unknown": "no result"}
+CODE_LINE = re.compile(r"^Pair ([A-D]) code (.+)$")
+LENGTH_LINE = re.compile(r"^Pair ([A-D]), fault length: ([\d.]+)m$")
+
+def parse_cable_test(text: str) -> List[Dict]:
+    """[{"pair", "status", "fault_length_m"}] from `ethtool --cable-test` output, pairs A to D"""
+    pairs = {}
+    for line in text.splitlines():
+        code = CODE_LINE.match(line.strip())
+        if code:
+            pairs.setdefault(code.group(1), {})["status"] = CODES.get(code.group(2).strip(), "unknown")
+            continue
+        length = LENGTH_LINE.match(line.strip())
+        if length:
+            pairs.setdefault(length.group(1), {})["fault_length_m"] = float(length.group(2))
+    return [{"pair": pair, "status": pairs[pair].get("status", "unknown"),
+             "fault_length_m": pairs[pair].get("fault_length_m")}
+            for pair in PAIRS if pair in pairs]
+
+def describe(pair: Dict) -> str:
+    """"open circuit at 1.6 m", "OK" and the like"""
+    text = STATUS_TEXT[pair["status"]]
+    return text + (f" at {pair['fault_length_m']:g} m" if pair["fault_length_m"] is not None else "")
+
+def report(interface: str, pairs: List[Dict]) -> Dict:
+    """The job result: pairs, whether all are fine, and a one-line summary"""
+    faults = [pair for pair in pairs if pair["status"] != "ok"]
+    if not pairs:
+        summary = f"{interface}: the PHY reported no pairs"
+    elif not faults:
+        summary = f"{interface}: all {len(pairs)} pairs OK"
+    else:
+        summary = f"{interface}: " + ", ".join(f"pair {pair['pair']} {describe(pair)}" for pair in faults)
+    return {"interface": interface, "pairs": pairs, "healthy": bool(pairs) and not faults, "summary": summary}
+
+async def run(interface: str) -> List[Dict]:
+    """Run the test; raises OSError when the NIC can't or ethtool is missing"""
+    process = await asyncio.create_subprocess_exec(
+        'sudo', 'ethtool', '--cable-test', interface, stdout=asyncio.subprocess.PIPE, stderr=asyncio.subprocess.PIPE
+    )
+    stdout, stderr = await communicate_or_terminate(process)
+    if process.returncode != 0:
+        error = stderr.decode(errors="replace").strip()
+        if "not supported" in error.lower():
+            raise OSError(f"{interface} does not support cable testing")
+        raise OSError(error or "ethtool failed")
+    pairs = parse_cable_test(stdout.decode(errors="replace"))
+    logger.info(f"Cable test on {interface}: " + ", ".join(f"{pair['pair']} {pair['status']}" for pair in pairs))
+    return pairs
@@ -0,0 +1,105 @@
+"""
+Carrier Tracking - When each interface last gained carrier
+Follows `ip -o monitor link` so uptime counts from the cable or association
+coming up rather than from when a client first looked; polls where it can't
+"""
+
+import re
+import time
+import asyncio
+import logging
+from typing import Dict, Iterable, Optional, Tuple
+
+from .discovery import NetworkInterface
+
+# Configure logging
+logger = logging.getLogger(__name__)
+
+POLL_INTERVAL = 2.0
+
+# "3: wlan0: <BROADCAST,MULTICAST,UP,LOWER_UP> mtu 1500 ..." or "Deleted 5: veth1@if4: <...> ..."
+MONITOR_LINE = re.compile(r"^(Deleted\s+)?(?:\d+:\s+)?([^\s:@]+)(?:@\S+)?:\s+<([^>]*)>")
+
+def parse_monitor_line(line: str) -> Optional[Tuple[str, bool]]:
+    """(interface, has carrier) from one line of `ip -o monitor link`, or None"""
+    match = MONITOR_LINE.match(line.strip())
+    if not match:
+        return None
+    deleted, name, flags = match.groups()
+    return name, not deleted and "LOWER_UP" in flags.split(",")
+
+class CarrierTracker:
+    """Carrier-up timestamps per interface, turned into NetworkMetrics.uptime"""
+
+    def __init__(self, discovery, events=None, monitor: bool = True):
+        self.discovery = discovery
+        self.events = events
+        self.monitor = monitor
+        self.carrier_up: Dict[str, float] = {}
+
+    def observe(self, interface: str, up: bool, at: Optional[float] = None):
+        """Record a carrier state; only transitions move the timestamp"""
+        if up and interface not in self.carrier_up:
+            self.carrier_up[interface] = at or time.time()
+        elif not up and interface in self.carrier_up:
+            del self.carrier_up[interface]
+        else:
+            return
+        logger.debug(f"Carrier {'up' if up else 'down'} on {interface}")
+        if self.events:
+            self.events.publish("CarrierChanged", interface=interface, carrier=up)
+
+    def _poll(self):
+        interfaces = self.discovery.discover_interfaces()
+        for interface in interfaces:
+            self.observe(interface.name, interface.status == "Connected")
+        for name in set(self.carrier_up) - {i.name for i in interfaces}:
+            self.observe(name, False)
+
+    def uptime(self, interface: str) -> Optional[float]:
+        """Seconds since interface gained carrier, None while it has none"""
+        since = self.carrier_up.get(interface)
+        return time.time() - since if since is not None else None
+
+    def update(self, interfaces: Iterable[NetworkInterface]):
+        """Fill in each interface's uptime"""
+        for interface in interfaces:
+            interface.metrics.uptime = self.uptime(interface.name)
+
+    async def run(self):
+        """Follow netlink link messages, falling back to polling discovery"""
+        # Links already up when the daemon starts count from then; the kernel keeps no timestamp
+        self._poll()
+        if self.monitor:
+            await self._follow_monitor()
+        while True:
+            await asyncio.sleep(POLL_INTERVAL)
+            self._poll()
+
+    async def _follow_monitor(self):
+        """Read `ip -o monitor link` until cancelled or it exits"""
+        try:
+            process = await asyncio.create_subprocess_exec(
+                'ip', '-o', 'monitor', 'link',
+                stdout=asyncio.subprocess.PIPE, stderr=asyncio.subprocess.DEVNULL
+            )
+        except OSError as e:
+            logger.warning(f"Cannot follow link changes ({e}), polling carrier instead")
+            return
+
+        try:
+            # Catch anything that changed between the initial poll and the monitor starting
+            self._poll()
+            while True:
+                line = await process.stdout.readline()
+                if not line:
+                    break
+                parsed = parse_monitor_line(line.decode(errors="replace"))
+                if parsed:
+                    self.observe(*parsed)
+        finally:
+            if process.returncode is None:
+                process.terminate()
+                await process.wait()
+
+        logger.warning(f"ip monitor exited ({process.returncode}), polling carrier instead")
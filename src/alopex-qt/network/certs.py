@@ -0,0 +1,277 @@
+"""
+Certificates - CA and client certificates (with their keys) for 802.1X and VPNs
+Imported once into /var/lib/alopex/certs and referenced from profiles by ID;
+CertificateExpiring goes out 30 days before one runs out
+"""
+
+import os
+import re
+import json
+import time
+import asyncio
+import hashlib
+import logging
+from dataclasses import dataclass, asdict, fields
+from pathlib import Path
+from typing import Dict, List, Optional, Tuple, Union
+
+from .secret import Secret
+
+# Configure logging
+logger = logging.getLogger(__name__)
+
+EXPIRY_WARNING_DAYS = 30
+CHECK_INTERVAL = 3600.0
+
+PEM_BLOCK = re.compile(rb"-----BEGIN ([A-Z0-9 ]+)-----.+?-----END \1-----", re.DOTALL)
+
+class CertError(Exception):
+    """Certificate or key could not be imported"""
+
+def available() -> bool:
+    """Check whether the X.509 parser is installed"""
+    try:
+        import cryptography  # noqa: F401
+        return True
+    except ImportError:
+        return False
+
+@dataclass
+class StoredCert:
+    """One certificate or private key in the store"""
+    id: str
+    kind: str  # ca, client or key
+    name: str
+    # SHA-256 of the DER certificate, or of a key's public half
+    fingerprint: str
+    # SHA-256 of the certificate's public key, which matches its key's fingerprint
+    public_key: Optional[str] = None
+    subject: Optional[str] = None
+    issuer: Optional[str] = None
+    not_before: Optional[float] = None
+    not_after: Optional[float] = None
+    imported_at: Optional[float] = None
+    # expiring or expired, once the event for it has gone out
+    warned: Optional[str] = None
+
+    @property
+    def days_left(self) -> Optional[float]:
+        if self.not_after is None:
+            return None
+        return round((self.not_after - time.time()) / 86400, 1)
+
+def _serialization():
+    try:
+        from cryptography import x509
+        from cryptography.hazmat.primitives import serialization
+    except ImportError:
+        raise CertError("Certificates need the Python 'cryptography' package")
+    return x509, serialization
+
+def _public_fingerprint(public_key) -> str:
+    _, serialization = _serialization()
+    der = public_key.public_bytes(serialization.Encoding.DER, serialization.PublicFormat.SubjectPublicKeyInfo)
+    return hashlib.sha256(der).hexdigest()
+
+def _sha256():
+    from cryptography.hazmat.primitives import hashes
+    return hashes.SHA256()
+
+def _is_ca(certificate) -> bool:
+    x509, _ = _serialization()
+    try:
+        return certificate.extensions.get_extension_for_class(x509.BasicConstraints).value.ca
+    except x509.ExtensionNotFound:
+        # Old roots predate the extension; they are self-issued
+        return certificate.subject == certificate.issuer
+
+def parse(data: bytes, password: Union[str, Secret, None] = None) -> List[Tuple[StoredCert, Union[bytes, Secret]]]:
+    """Certificates and keys in a PEM bundle or a single DER object, each with the PEM to store (a Secret for keys)"""
+    x509, serialization = _serialization()
+    secret = Secret.wrap(password).reveal_bytes() if password else None
+    blocks = [(m.group(1).decode(), m.group(0)) for m in PEM_BLOCK.finditer(data)]
+    if not blocks:
+        blocks = [("DER", data)]
+
+    parsed = []
+    for label, block in blocks:
+        try:
+            if label == "CERTIFICATE":
+                certificate = x509.load_pem_x509_certificate(block)
+            elif label.endswith("PRIVATE KEY"):
+                certificate, key = None, serialization.load_pem_private_key(block, secret)
+            elif label == "DER":
+                try:
+                    certificate = x509.load_der_x509_certificate(block)
+                except ValueError:
+                    certificate, key = None, serialization.load_der_private_key(block, secret)
+            else:
+                continue
+        except TypeError:
+            raise CertError("The key is encrypted; give its password" if not secret else "Wrong key password")
+        except ValueError as e:
+            raise CertError(f"Not a certificate or private key: {e}")
+
+        if certificate is not None:
+            fingerprint = certificate.fingerprint(_sha256()).hex()
+            parsed.append((StoredCert(
+                id=fingerprint[:12],
+                kind="ca" if _is_ca(certificate) else "client",
+                name=_common_name(certificate) or fingerprint[:12],
+                fingerprint=fingerprint,
+                public_key=_public_fingerprint(certificate.public_key()),
+                subject=certificate.subject.rfc4514_string(),
+                issuer=certificate.issuer.rfc4514_string(),
+                not_before=certificate.not_valid_before_utc.timestamp(),
+                not_after=certificate.not_valid_after_utc.timestamp()
+            ), certificate.public_bytes(serialization.Encoding.PEM)))
+        else:
+            fingerprint = _public_fingerprint(key.public_key())
+            # Stored decrypted (0600, root only) so supplicants can load it without a prompt
+            pem = key.private_bytes(serialization.Encoding.PEM, serialization.PrivateFormat.PKCS8,
+                                    serialization.NoEncryption())
+            parsed.append((StoredCert(id=fingerprint[:12], kind="key", name=f"key {fingerprint[:12]}",
+                                      fingerprint=fingerprint), Secret(pem)))
+    if not parsed:
+        raise CertError("No certificate or private key found")
+    return parsed
+
+def _common_name(certificate) -> Optional[str]:
+    x509, _ = _serialization()
+    names = certificate.subject.get_attributes_for_oid(x509.NameOID.COMMON_NAME)
+    return str(names[0].value) if names else None
+
+class CertStore:
+    """PEM files named by ID plus an index with what listings need"""
+
+    def __init__(self, store_path: Path):
+        self.store_path = store_path
+        self.index_file = store_path / "index.json"
+        self.certs: Dict[str, StoredCert] = {}
+        self._load()
+
+    def _load(self):
+        if not self.index_file.exists():
+            return
+        try:
+            with open(self.index_file) as f:
+                known = {field.name for field in fields(StoredCert)}
+                self.certs = {entry["id"]: StoredCert(**{k: v for k, v in entry.items() if k in known})
+                              for entry in json.load(f)}
+            logger.info(f"Loaded {len(self.certs)} certificates and keys")
+        except Exception as e:
+            logger.error(f"Failed to load certificate index: {e}")
+
+    def _save(self):
+        """Write the index via a temp file"""
+        try:
+            self.store_path.mkdir(parents=True, exist_ok=True)
+            temp_path = self.index_file.with_suffix(".json.tmp")
+            with open(temp_path, 'w') as f:
+                json.dump([asdict(cert) for cert in self.certs.values()], f, indent=2)
+            os.replace(temp_path, self.index_file)
+        except Exception as e:
+            logger.error(f"Failed to save certificate index: {e}")
+
+    def path(self, cert_id: str) -> Path:
+        return self.store_path / f"{cert_id}.pem"
+
+    def get(self, cert_id: str) -> Optional[StoredCert]:
+        return self.certs.get(cert_id)
+
+    def key_for(self, cert_id: str) -> Optional[StoredCert]:
+        """The stored private key belonging to a client certificate"""
+        cert = self.certs.get(cert_id)
+        if not cert or not cert.public_key:
+            return None
+        return next((c for c in self.certs.values() if c.kind == "key" and c.fingerprint == cert.public_key), None)
+
+    def import_data(self, data: bytes, name: Optional[str] = None,
+                    password: Union[str, Secret, None] = None) -> List[StoredCert]:
+        """Add every certificate and key in data; importing one again only renames it"""
+        parsed = parse(data, password)
+        self.store_path.mkdir(parents=True, exist_ok=True)
+        self.store_path.chmod(0o700)
+        imported = []
+        for cert, pem in parsed:
+            existing = self.certs.get(cert.id)
+            if existing:
+                cert = existing
+            else:
+                cert.imported_at = time.time()
+                temp_path = self.path(cert.id).with_suffix(".pem.tmp")
+                fd = os.open(temp_path, os.O_WRONLY | os.O_CREAT | os.O_TRUNC, 0o600)
+                with os.fdopen(fd, 'wb') as f:
+                    f.write(pem.reveal_bytes() if isinstance(pem, Secret) else pem)
+                os.replace(temp_path, self.path(cert.id))
+                self.certs[cert.id] = cert
+            # A name given for a bundle goes on its certificates; keys are named after them
+            if name and (cert.kind != "key" or len(parsed) == 1):
+                cert.name = name
+            imported.append(cert)
+
+        for key in (c for c in imported if c.kind == "key"):
+            owner = next((c for c in self.certs.values() if c.public_key == key.fingerprint), None)
+            if owner and key.name.startswith("key "):
+                key.name = f"{owner.name} key"
+        self._save()
+        logger.info(f"Imported {', '.join(f'{c.kind} {c.id}' for c in imported)}")
+        return imported
+
+    def remove(self, cert_id: str) -> bool:
+        if self.certs.pop(cert_id, None) is None:
+            return False
+        self.path(cert_id).unlink(missing_ok=True)
+        self._save()
+        logger.info(f"Removed certificate {cert_id}")
+        return True
+
+    def to_dict(self, cert: StoredCert) -> Dict:
+        key = self.key_for(cert.id)
+        return dict(asdict(cert), days_left=cert.days_left, key_id=key.id if key else None,
+                    path=str(self.path(cert.id)))
+
+    def list(self) -> List[Dict]:
+        """CAs, then client certificates, then keys; soonest to expire first within each"""
+        order = {"ca": 0, "client": 1, "key": 2}
+        certs = sorted(self.certs.values(), key=lambda c: (order.get(c.kind, 3), c.not_after or float("inf"), c.name))
+        return [self.to_dict(cert) for cert in certs]
+
+class CertExpiryMonitor:
+    """Publishes CertificateExpiring 30 days out and CertificateExpired once it is too late, once each"""
+
+    def __init__(self, store: CertStore, events=None, interval: float = CHECK_INTERVAL,
+                 warning_days: int = EXPIRY_WARNING_DAYS):
+        self.store = store
+        self.events = events
+        self.interval = interval
+        self.warning_days = warning_days
+
+    def check(self) -> List[StoredCert]:
+        """Certificates whose warning state moved, after publishing for each"""
+        changed = []
+        for cert in self.store.certs.values():
+            if cert.days_left is None:
+                continue
+            state = "expired" if cert.days_left <= 0 else "expiring" if cert.days_left <= self.warning_days else None
+            if state is None or state == cert.warned:
+                continue
+            cert.warned = state
+            changed.append(cert)
+            logger.warning(f"Certificate {cert.name} ({cert.id}) {'has expired' if state == 'expired' else 'expires'}"
+                           f" {time.strftime('%Y-%m-%d', time.localtime(cert.not_after))}")
+            if self.events:
+                self.events.publish("CertificateExpired" if state == "expired" else "CertificateExpiring",
+                                    id=cert.id, name=cert.name, type=cert.kind, not_after=cert.not_after,
+                                    days_left=cert.days_left)
+        if changed:
+            self.store._save()
+        return changed
+
+    async def run(self):
+        while True:
+            try:
+                self.check()
+            except Exception as e:
+                logger.error(f"Certificate expiry check failed: {e}")
+            await asyncio.sleep(self.interval)
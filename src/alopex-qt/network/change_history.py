@@ -0,0 +1,61 @@
+"""
+Change History - The last configuration changes alopexd applied, for Undo
+Each entry keeps what the change replaced (a profile's previous fields, an
+interface's previous DNS servers, the firewall zones before a location
+switch) so the most recent one can be put back. Kept in memory only
+"""
+
+import time
+import logging
+from collections import deque
+from dataclasses import dataclass, field
+from typing import Any, Deque, Dict, List, Optional
+
+# Configure logging
+logger = logging.getLogger(__name__)
+
+HISTORY_SIZE = 20
+
+@dataclass
+class Change:
+    """One applied change and the state it replaced"""
+    id: int
+    kind: str  # profile, dns or location
+    summary: str
+    previous: Dict[str, Any] = field(default_factory=dict)
+    applied_at: float = field(default_factory=time.time)
+
+    def to_dict(self) -> Dict:
+        """Without the previous state, which may hold secrets"""
+        return {"id": self.id, "kind": self.kind, "summary": self.summary, "applied_at": self.applied_at}
+
+class ChangeHistory:
+    """Newest change last; the oldest fall off beyond HISTORY_SIZE"""
+
+    def __init__(self, size: int = HISTORY_SIZE):
+        self.changes: Deque[Change] = deque(maxlen=size)
+        self.next_id = 1
+
+    def record(self, kind: str, summary: str, previous: Dict[str, Any]) -> Change:
+        change = Change(self.next_id, kind, summary, previous)
+        self.next_id += 1
+        self.changes.append(change)
+        logger.debug(f"Recorded change {change.id}: {summary}")
+        return change
+
+    def latest(self) -> Optional[Change]:
+        """The most recent change, left on the history"""
+        return self.changes[-1] if self.changes else None
+
+    def pop(self) -> Optional[Change]:
+        """Take the most recent change off the history"""
+        return self.changes.pop() if self.changes else None
+
+    def remove(self, change: Change):
+        """Drop a change once it is undone; changes recorded meanwhile stay"""
+        if change in self.changes:
+            self.changes.remove(change)
+
+    def list(self) -> List[Dict]:
+        """Most recent first, the order Undo takes them in"""
+        return [change.to_dict() for change in reversed(self.changes)]
@@ -3,25 +3,44 @@ Connection State Management - Persistent network configuration
 Enterprise-grade connection handling that NetworkManager wishes it had
 """
 
+import os
 import json
 import asyncio
+import fnmatch
+import ipaddress
 import logging
 import time
 from pathlib import Path
-from typing import Dict, List, Optional, Tuple
+from typing import Callable, Dict, List, Optional, Set, Tuple
 from dataclasses import dataclass, asdict
 
-from .discovery import NetworkInterface, NetworkDiscovery
-from .system_integration import NetworkControl
-from .wifi import WiFiManager
+from .discovery import InterfaceType, NetworkInterface, NetworkDiscovery
+from .backends import BackendSet, load_backends
+from .events import EventBus
+from .rfkill import BLOCK_REASONS
+from .certs import CertStore
+from .hotspot import DEFAULT_CHANNEL, hotspot_network
+from .neighbor_announce import ANNOUNCE_INTERVAL, ANNOUNCE_ROUNDS, announce_targets
+from .retry import RetryTracker
+from .secret import Secret, reveal, revealed
+from .route_metrics import metric_changes, route_metric
+from . import mptcp, sysctls
+from .state_machine import LinkState, StateTransitionError, can_transition, parse_state
+
+# Optional progress hook for long operations: (percent or None, message)
+Progress = Optional[Callable[[Optional[int], str], None]]
+
+def _report(progress: Progress, percent: Optional[int], message: str):
+    if progress:
+        progress(percent, message)
 
 @dataclass
 class ConnectionProfile:
     """Persistent connection configuration"""
     name: str
     interface: str
-    connection_type: str  # ethernet, wifi, vpn
-    method: str  # dhcp, static, manual
+    connection_type: str  # ethernet, wifi, vpn, hotspot
+    method: str  # dhcp, static, manual; shared for hotspots
     
     # Static IP configuration
     ip_address: Optional[str] = None
@@ -31,8 +50,63 @@ class ConnectionProfile:
     
     # WiFi configuration
     ssid: Optional[str] = None
-    password: Optional[str] = None
+    password: Optional[Secret] = None  # given as a str, kept as a Secret
     security: Optional[str] = None
+    # Radio power management; None leaves the driver default
+    power_save: Optional[bool] = None
+    
+    # 802.1X: EAP method (peap, ttls or tls) and the identity to log in as, with password above
+    eap: Optional[str] = None
+    identity: Optional[str] = None
+    
+    # Certificate store IDs, for 802.1X and OpenVPN; a client certificate brings its key along
+    ca_cert: Optional[str] = None
+    client_cert: Optional[str] = None
+    
+    # Hotspot: ssid/password above are the network offered, ip_address/netmask its subnet
+    channel: Optional[int] = None
+    blocked_clients: List[str] = None  # MACs refused by the access point
+    client_filter: str = "deny"  # "allow" admits only allowed_clients
+    allowed_clients: List[str] = None
+    client_isolation: bool = False  # stations can't reach each other, only the uplink
+    client_limits: Dict[str, Dict] = None  # MAC -> {"down", "up"} rate limits in kbit/s
+    dhcp_reservations: Dict[str, str] = None  # MAC -> address the DHCP server always gives it
+    
+    # Virtual device created before addressing, e.g.
+    # {"kind": "vlan", "parent": "eth0", "id": 10}, {"kind": "bridge", "ports": ["eth1"]},
+    # {"kind": "bond", "mode": "802.3ad", "ports": ["eth2", "eth3"]},
+    # {"kind": "gre", "remote": "203.0.113.7", "local": "198.51.100.2", "key": 42},
+    # {"kind": "vxlan", "vni": 100, "remote": "192.168.1.20", "parent": "eth0"}
+    netdev: Optional[Dict] = None
+    
+    # systemd units started while the profile is active, e.g. ["syncthing.service"]
+    units: List[str] = None
+    
+    # Metered (mobile hotspot, capped plan); None lets desktops guess
+    metered: Optional[bool] = None
+    
+    # Connected first when the daemon starts and retried until up; network-online.target waits for it
+    boot_critical: bool = False
+    
+    # Default-route metric; None derives it from the link type and priority
+    route_metric: Optional[int] = None
+    
+    # Gratuitous ARP / unsolicited NA once addresses are up, so neighbours drop stale MACs
+    gratuitous_arp: bool = True
+    # Answer ARP and NDP for addresses behind the interface (bridged or shared setups)
+    proxy_arp: bool = False
+    
+    # Network sysctls while active, by sysctls.SETTINGS name, e.g. {"rp_filter": "2", "tcp_congestion_control": "bbr"}
+    sysctls: Dict[str, str] = None
+    
+    # MPTCP endpoint flags for its addresses (subflow, signal, backup, fullmesh); empty leaves MPTCP alone
+    mptcp_flags: List[str] = None
+    
+    # Root qdisc while active (fq, fq_codel, cake; None leaves the kernel's), cake's bandwidth as tc takes it
+    qdisc: Optional[str] = None
+    qdisc_bandwidth: Optional[str] = None
+    # TCP congestion control on the interface's default routes; None uses the host-wide one
+    congestion_control: Optional[str] = None
     
     # Connection metadata
     auto_connect: bool = True
@@ -42,41 +116,93 @@ class ConnectionProfile:
     last_error: Optional[str] = None
     
     def __post_init__(self):
+        self.password = Secret.wrap(self.password)
         if self.dns_servers is None:
             self.dns_servers = []
+        if self.units is None:
+            self.units = []
+        if self.blocked_clients is None:
+            self.blocked_clients = []
+        if self.allowed_clients is None:
+            self.allowed_clients = []
+        if self.client_limits is None:
+            self.client_limits = {}
+        if self.dhcp_reservations is None:
+            self.dhcp_reservations = {}
+        if self.sysctls is None:
+            self.sysctls = {}
+        if self.mptcp_flags is None:
+            self.mptcp_flags = []
 
 @dataclass 
 class ConnectionState:
     """Current interface connection state"""
     interface: str
     profile_name: Optional[str] = None
-    status: str = "disconnected"  # disconnected, connecting, connected, failed
+    status: str = LinkState.DISCONNECTED.value  # see state_machine.LinkState
     ip_address: Optional[str] = None
     gateway: Optional[str] = None
     dns_servers: List[str] = None
     connected_at: Optional[float] = None
     last_seen: Optional[float] = None
     error_count: int = 0
+    dhcp_lease: Optional[Dict] = None
+    route_metric: Optional[int] = None
+    proxy_arp: bool = False  # turned on by alopex for the active profile
+    announced_at: Optional[float] = None
+    # Sysctls written for the active profile: key -> {"name", "value", "previous"}
+    sysctls: Dict[str, Dict] = None
+    # IDs of the MPTCP endpoints added for the active profile
+    mptcp_endpoints: List[int] = None
+    # Root qdisc ({"kind", "bandwidth"}) and congestion control alopex set for the active profile
+    qdisc: Optional[Dict] = None
+    congestion_control: Optional[str] = None
     
     def __post_init__(self):
         if self.dns_servers is None:
             self.dns_servers = []
+        if self.sysctls is None:
+            self.sysctls = {}
+        if self.mptcp_endpoints is None:
+            self.mptcp_endpoints = []
 
 class ConnectionManager:
     """Enterprise connection state management"""
     
-    def __init__(self):
-        self.config_path = Path("/var/lib/alopex")
+    def __init__(self, backends: Optional[BackendSet] = None,
+                 discovery: Optional[NetworkDiscovery] = None,
+                 config_path: Optional[Path] = None,
+                 events: Optional[EventBus] = None):
+        self.config_path = config_path or Path("/var/lib/alopex")
         self.profiles_file = self.config_path / "connection-profiles.json"
         self.state_file = self.config_path / "connection-state.json"
         
-        self.discovery = NetworkDiscovery()
-        self.wifi = WiFiManager()
+        self.discovery = discovery or NetworkDiscovery()
+        self.backends = backends or load_backends()
+        self.events = events or EventBus()
         
         # In-memory state
         self.profiles: Dict[str, ConnectionProfile] = {}
         self.interface_states: Dict[str, ConnectionState] = {}
         
+        # Interfaces owned by other tools (docker, libvirt, networkd...)
+        self.unmanaged_patterns: List[str] = []
+        self.unmanaged_overrides: Dict[str, bool] = {}
+        # Interfaces other software owns (container networks); unmanaged unless overridden
+        self.implicit_unmanaged: Set[str] = set()
+        
+        # CA and client certificates profiles refer to by ID
+        self.certs = CertStore(self.config_path / "certs")
+        
+        # The daemon's caching DNS forwarder, when enabled; hotspot clients resolve through it
+        self.dns_forwarder = None
+        
+        # Backoff and give-up for profiles auto-connect keeps failing on; the daemon sets it from its config
+        self.retry = RetryTracker()
+        self.retry_tasks: Dict[str, asyncio.Task] = {}
+        # Repeat announcements still to go out, per interface
+        self.announce_tasks: Dict[str, asyncio.Task] = {}
+        
         # Monitoring
         self.monitoring = True
         self.reconnect_interval = 30  # seconds
@@ -107,9 +233,8 @@ class ConnectionManager:
         self.config_path.mkdir(parents=True, exist_ok=True)
         
         try:
-            data = {name: asdict(profile) for name, profile in self.profiles.items()}
-            with open(self.profiles_file, 'w') as f:
-                json.dump(data, f, indent=2)
+            data = {name: revealed(asdict(profile)) for name, profile in self.profiles.items()}
+            self._write_atomic(self.profiles_file, data)
         except Exception as e:
             self.logger.error(f"Failed to save profiles: {e}")
     
@@ -121,7 +246,9 @@ class ConnectionManager:
                     data = json.load(f)
                     
                 for interface, state_data in data.items():
-                    self.interface_states[interface] = ConnectionState(**state_data)
+                    state = ConnectionState(**state_data)
+                    state.status = parse_state(state.status).value
+                    self.interface_states[interface] = state
                     
             except Exception as e:
                 self.logger.error(f"Failed to load states: {e}")
@@ -132,11 +259,146 @@ class ConnectionManager:
         
         try:
             data = {iface: asdict(state) for iface, state in self.interface_states.items()}
-            with open(self.state_file, 'w') as f:
-                json.dump(data, f, indent=2)
+            self._write_atomic(self.state_file, data)
         except Exception as e:
             self.logger.error(f"Failed to save states: {e}")
     
+    @staticmethod
+    def _write_atomic(path: Path, data):
+        """Write JSON via a temp file so a crash never leaves a truncated file; 0600, as profiles hold PSKs"""
+        temp_path = path.with_suffix(path.suffix + ".tmp")
+        fd = os.open(temp_path, os.O_WRONLY | os.O_CREAT | os.O_TRUNC, 0o600)
+        with os.fdopen(fd, 'w') as f:
+            json.dump(data, f, indent=2)
+            f.flush()
+            os.fsync(f.fileno())
+        os.replace(temp_path, path)
+    
+    async def restore_states(self) -> Dict[str, str]:
+        """Reconcile persisted connection states with the kernel after a restart
+        
+        Links that are still up with the expected address are adopted as-is so
+        traffic is not interrupted; links the kernel lost are reconnected.
+        """
+        kernel_interfaces = {i.name: i for i in self.discovery.discover_interfaces()}
+        results = {}
+        now = time.time()
+        
+        for name, state in list(self.interface_states.items()):
+            if state.status not in (LinkState.CONFIGURED, LinkState.ACQUIRING):
+                continue
+            
+            if self.is_unmanaged(name):
+                results[name] = "unmanaged"
+                continue
+            
+            iface = kernel_interfaces.get(name)
+            if iface is None:
+                self._transition(name, LinkState.DISCONNECTED, "interface missing after restart")
+                results[name] = "missing"
+                continue
+            
+            profile = self.profiles.get(state.profile_name) if state.profile_name else None
+            address_matches = iface.ip and (not state.ip_address or iface.ip == state.ip_address)
+            
+            if iface.status == "Connected" and address_matches:
+                if state.status != LinkState.CONFIGURED:
+                    self._transition(name, LinkState.CONFIGURED, "adopted after restart")
+                state.ip_address = iface.ip
+                state.last_seen = now
+                
+                # Keep renewing the existing lease rather than requesting a new one
+                if profile and profile.method == "dhcp":
+                    await self.backends.ethernet.ensure_dhcp(name)
+                    state.dhcp_lease = self.backends.ethernet.get_dhcp_lease(name) or state.dhcp_lease
+                
+                results[name] = "adopted"
+                self.logger.info(f"Adopted existing connection on {name} ({iface.ip})")
+            elif profile:
+                results[name] = "reconnecting"
+                self.logger.info(f"Restoring {state.profile_name} on {name}")
+                self._transition(name, LinkState.FAILED, "lost across restart")
+                await self.connect_profile(state.profile_name)
+            else:
+                self._transition(name, LinkState.DISCONNECTED, "no profile to restore")
+                results[name] = "dropped"
+        
+        self._save_states()
+        return results
+    
+    def get_link_state(self, interface: str) -> LinkState:
+        """Current state machine state of an interface"""
+        state = self.interface_states.get(interface)
+        return LinkState(state.status) if state else LinkState.DISCONNECTED
+    
+    def check_transition(self, interface: str, target: LinkState):
+        """Raise StateTransitionError unless interface may move to target now"""
+        current = self.get_link_state(interface)
+        if not can_transition(current, target):
+            raise StateTransitionError(interface, current, target)
+    
+    def _transition(self, interface: str, target: LinkState, reason: Optional[str] = None) -> ConnectionState:
+        """Move an interface to a new state, emitting InterfaceStateChanged
+        
+        Only legal transitions are applied; there is no await between the
+        check and the change, so concurrent requests cannot interleave here.
+        """
+        self.check_transition(interface, target)
+        
+        state = self.interface_states.setdefault(interface, ConnectionState(interface=interface))
+        previous = state.status
+        state.status = target.value
+        state.last_seen = time.time()
+        
+        if target == LinkState.CONFIGURED:
+            state.connected_at = state.connected_at or time.time()
+        elif target in (LinkState.DISCONNECTED, LinkState.FAILED):
+            state.connected_at = None
+        
+        self._save_states()
+        self.logger.debug(f"{interface}: {previous} -> {target.value}" + (f" ({reason})" if reason else ""),
+                          extra={"interface": interface})
+        self.events.publish(
+            "InterfaceStateChanged", interface=interface, previous=previous, state=target.value,
+            profile=state.profile_name, reason=reason
+        )
+        return state
+    
+    def set_unmanaged_patterns(self, patterns: List[str]):
+        """Set glob patterns for interfaces the daemon must never touch"""
+        self.unmanaged_patterns = list(patterns)
+    
+    def set_implicit_unmanaged(self, interfaces: Set[str]):
+        """Replace the interfaces left alone by default because something else manages them"""
+        self.implicit_unmanaged = set(interfaces)
+    
+    def set_unmanaged(self, interface: str, unmanaged: bool):
+        """Mark a single interface managed/unmanaged at runtime"""
+        self.unmanaged_overrides[interface] = unmanaged
+        self.logger.info(f"Interface {interface} {'unmanaged' if unmanaged else 'managed'} by runtime request")
+    
+    def rename_interface(self, old: str, new: str) -> List[str]:
+        """Follow a kernel rename: profiles and state move to the new name; returns the profiles changed"""
+        moved = [profile.name for profile in self.profiles.values() if profile.interface == old]
+        for name in moved:
+            self.update_profile(name, interface=new)
+        state = self.interface_states.pop(old, None)
+        if state:
+            state.interface = new
+            self.interface_states[new] = state
+            self._save_states()
+        if old in self.unmanaged_overrides:
+            self.unmanaged_overrides[new] = self.unmanaged_overrides.pop(old)
+        return moved
+    
+    def is_unmanaged(self, interface: str) -> bool:
+        """Check whether an interface is excluded from management"""
+        if interface in self.unmanaged_overrides:
+            return self.unmanaged_overrides[interface]
+        if interface in self.implicit_unmanaged:
+            return True
+        return any(fnmatch.fnmatch(interface, pattern) for pattern in self.unmanaged_patterns)
+    
     def create_profile(self, name: str, interface: str, connection_type: str, 
                       method: str = "dhcp", **kwargs) -> ConnectionProfile:
         """Create a new connection profile"""
@@ -154,6 +416,24 @@ class ConnectionManager:
         self.logger.info(f"Created connection profile: {name}")
         return profile
     
+    def update_profile(self, name: str, **fields) -> Optional[ConnectionProfile]:
+        """Change some fields of an existing profile"""
+        profile = self.profiles.get(name)
+        if not profile:
+            return None
+        
+        for key, value in fields.items():
+            if not hasattr(profile, key) or key == "name":
+                raise TypeError(f"Unknown profile field: {key}")
+            setattr(profile, key, Secret.wrap(value) if key == "password" else value)
+        # A fixed password or address deserves a fresh set of attempts
+        self.retry.reset(name)
+        
+        self._save_profiles()
+        self.logger.info(f"Updated connection profile: {name} ({', '.join(fields)})")
+        self.events.publish("ProfileUpdated", profile=name, fields=list(fields))
+        return profile
+    
     def get_profile(self, name: str) -> Optional[ConnectionProfile]:
         """Get connection profile by name"""
         return self.profiles.get(name)
@@ -169,102 +449,201 @@ class ConnectionManager:
         """Delete a connection profile"""
         if name in self.profiles:
             del self.profiles[name]
+            self.retry.reset(name)
             self._save_profiles()
             self.logger.info(f"Deleted connection profile: {name}")
             return True
         return False
     
-    async def connect_profile(self, name: str) -> bool:
-        """Connect using a specific profile"""
+    def begin_connect(self, name: str) -> ConnectionProfile:
+        """Claim the profile's interface for a connect (moves it to acquiring)
+        
+        Raises KeyError for unknown profiles, PermissionError for unmanaged
+        interfaces and StateTransitionError if the interface is busy.
+        """
         profile = self.get_profile(name)
         if not profile:
+            raise KeyError(name)
+        
+        if self.is_unmanaged(profile.interface):
+            raise PermissionError(f"{profile.interface} is unmanaged")
+        
+        self.check_transition(profile.interface, LinkState.ACQUIRING)
+        state = self.interface_states.setdefault(profile.interface, ConnectionState(interface=profile.interface))
+        state.profile_name = name
+        self._transition(profile.interface, LinkState.ACQUIRING, f"activating {name}")
+        return profile
+    
+    async def connect_profile(self, name: str, progress: Progress = None) -> bool:
+        """Connect using a specific profile"""
+        try:
+            profile = self.begin_connect(name)
+        except KeyError:
             self.logger.error(f"Profile not found: {name}")
             return False
+        except (PermissionError, StateTransitionError) as e:
+            self.logger.warning(f"Refusing to activate {name}: {e}")
+            return False
         
-        # Update interface state
-        if profile.interface not in self.interface_states:
-            self.interface_states[profile.interface] = ConnectionState(
-                interface=profile.interface
-            )
-        
+        return await self.finish_connect(profile, progress)
+    
+    async def finish_connect(self, profile: ConnectionProfile, progress: Progress = None) -> bool:
+        """Bring up a profile whose interface begin_connect() already claimed"""
+        name = profile.name
         state = self.interface_states[profile.interface]
-        state.profile_name = name
-        state.status = "connecting"
-        state.last_seen = time.time()
-        self._save_states()
         
         try:
             # Update connection attempt count
             profile.connection_attempts += 1
             
+            _report(progress, 10, f"Activating {name} on {profile.interface}")
+            
             success = False
             if profile.connection_type == "ethernet":
-                success = await self._connect_ethernet(profile)
+                success = await self._connect_ethernet(profile, progress)
             elif profile.connection_type == "wifi":
-                success = await self._connect_wifi(profile)
+                success = await self._connect_wifi(profile, progress)
+            elif profile.connection_type == "vpn":
+                success = await self._connect_vpn(profile, progress)
+            elif profile.connection_type == "hotspot":
+                success = await self._connect_hotspot(profile, progress)
             else:
                 self.logger.error(f"Unsupported connection type: {profile.connection_type}")
-                return False
+                profile.last_error = f"Unsupported connection type: {profile.connection_type}"
+            
+            if success and profile.dns_servers and profile.method == "dhcp":
+                # Resolvers set on the profile win over the ones DHCP handed out
+                await self.backends.host.set_dns(profile.dns_servers)
             
             if success:
-                state.status = "connected"
-                state.connected_at = time.time()
+                # Update network information
+                _report(progress, 90, "Reading addresses")
+                await self._update_connection_info(profile.interface)
+                await self.apply_route_metric(profile.interface, profile)
+                self.apply_proxy_arp(profile.interface, profile)
+                self.apply_sysctls(profile.interface, profile)
+                await self.apply_mptcp(profile.interface, profile)
+                await self.apply_shaping(profile.interface, profile)
+                await self.announce_addresses(profile.interface, profile)
+                
                 state.error_count = 0
                 profile.last_connected = time.time()
                 profile.last_error = None
+                self.retry.reset(name)
+                self._transition(profile.interface, LinkState.CONFIGURED, f"{name} active")
                 
-                # Update network information
-                await self._update_connection_info(profile.interface)
-                
-                self.logger.info(f"Connected to profile: {name}")
+                self.logger.info(f"Connected to profile: {name}", extra={"interface": profile.interface})
             else:
-                state.status = "failed"
                 state.error_count += 1
-                profile.last_error = f"Connection failed at {time.ctime()}"
-                self.logger.error(f"Failed to connect to profile: {name}")
+                profile.last_error = profile.last_error or f"Connection failed at {time.ctime()}"
+                self._transition(profile.interface, LinkState.FAILED, profile.last_error)
+                self.logger.error(f"Failed to connect to profile: {name}", extra={"interface": profile.interface})
             
             self._save_profiles()
-            self._save_states()
             return success
             
+        except asyncio.CancelledError:
+            # Cancelled by CancelJob or a job timeout: undo the half-made connection
+            profile.last_error = "Cancelled"
+            await self._abort_connect(profile)
+            state.profile_name = None
+            self._transition(profile.interface, LinkState.DISCONNECTED, f"activating {name} cancelled")
+            
+            self._save_profiles()
+            
+            self.logger.warning(f"Connecting {name} cancelled", extra={"interface": profile.interface})
+            raise
+            
         except Exception as e:
-            state.status = "failed"
             state.error_count += 1
             profile.last_error = str(e)
+            self._transition(profile.interface, LinkState.FAILED, str(e))
             
             self._save_profiles()
-            self._save_states()
             
-            self.logger.error(f"Exception connecting to {name}: {e}")
+            self.logger.error(f"Exception connecting to {name}: {e}", extra={"interface": profile.interface})
             return False
     
-    async def _connect_ethernet(self, profile: ConnectionProfile) -> bool:
+    async def _abort_connect(self, profile: ConnectionProfile):
+        """Tear down whatever a cancelled connect left behind"""
+        try:
+            if profile.connection_type == "wifi":
+                self.backends.wifi.disconnect(profile.interface)
+            elif profile.connection_type == "vpn":
+                config = next((c for c in self.backends.vpn.discover() if c.name == profile.interface), None)
+                if config and self.backends.vpn.is_active(config):
+                    await self.backends.vpn.disconnect(config)
+            elif profile.connection_type == "hotspot":
+                await self._stop_hotspot(profile)
+        except Exception as e:
+            self.logger.error(f"Cleanup after cancelled connect of {profile.name} failed: {e}")
+    
+    async def _connect_ethernet(self, profile: ConnectionProfile, progress: Progress = None) -> bool:
         """Connect ethernet interface"""
+        if profile.netdev:
+            _report(progress, 10, f"Creating {profile.netdev.get('kind')} {profile.interface}")
+            if not await self.backends.ethernet.create_netdev(profile.interface, profile.netdev):
+                profile.last_error = f"Could not create {profile.netdev.get('kind')} {profile.interface}"
+                return False
+        
         if profile.method == "dhcp":
-            return await NetworkControl.configure_dhcp(profile.interface)
+            _report(progress, 30, "Requesting DHCP lease")
+            return await self.backends.ethernet.configure_dhcp(profile.interface)
         elif profile.method == "static":
-            return await NetworkControl.configure_static_ip(
+            _report(progress, 30, f"Applying static address {profile.ip_address}")
+            return await self.backends.ethernet.configure_static(
                 profile.interface,
                 profile.ip_address,
                 profile.gateway,
                 profile.dns_servers
             )
+        elif profile.method == "manual":
+            # Link up without addressing (bridge/bond ports, addressing done elsewhere)
+            return await self.backends.ethernet.bring_up(profile.interface)
         return False
     
-    async def _connect_wifi(self, profile: ConnectionProfile) -> bool:
+    async def _connect_wifi(self, profile: ConnectionProfile, progress: Progress = None) -> bool:
         """Connect WiFi interface"""
         if not profile.ssid:
             return False
         
-        success = await self.wifi.connect_to_network(
+        # Associating would only time out; say why instead
+        blocked = self.backends.rfkill.interface_block(profile.interface)
+        if blocked:
+            profile.last_error = BLOCK_REASONS[blocked]
+            return False
+        
+        eap = None
+        if profile.eap:
+            certs = self._certificate_paths(profile)
+            if certs is None:
+                return False
+            eap = {
+                "method": profile.eap,
+                "identity": profile.identity,
+                "password": reveal(profile.password),
+                "ca_cert": certs.get("ca"),
+                "client_cert": certs.get("cert"),
+                "private_key": certs.get("key")
+            }
+        
+        _report(progress, 20, f"Associating with {profile.ssid}")
+        success = await self.backends.wifi.connect(
             profile.interface,
             profile.ssid,
-            profile.password
+            reveal(profile.password),
+            eap=eap
         )
         
+        if success and profile.power_save is not None:
+            if not self.backends.wifi.set_power_save(profile.interface, profile.power_save):
+                self.logger.warning(f"Could not turn power save {'on' if profile.power_save else 'off'} "
+                                    f"on {profile.interface}")
+        
         # If WiFi connection succeeds, configure IP
         if success and profile.method == "static":
-            return await NetworkControl.configure_static_ip(
+            _report(progress, 60, f"Applying static address {profile.ip_address}")
+            return await self.backends.ethernet.configure_static(
                 profile.interface,
                 profile.ip_address,
                 profile.gateway,
@@ -273,79 +652,471 @@ class ConnectionManager:
         
         return success
     
+    async def _connect_vpn(self, profile: ConnectionProfile, progress: Progress = None) -> bool:
+        """Bring up the VPN config whose name matches the profile's interface"""
+        config = next((c for c in self.backends.vpn.discover() if c.name == profile.interface), None)
+        if not config:
+            self.logger.error(f"No VPN configuration named {profile.interface}")
+            return False
+        
+        certs = self._certificate_paths(profile)
+        if certs is None:
+            return False
+        
+        _report(progress, 30, f"Negotiating {config.config_type} tunnel {config.name}")
+        success, message = await self.backends.vpn.connect(config, certs or None)
+        if not success:
+            profile.last_error = message
+        return success
+    
+    def _certificate_paths(self, profile: ConnectionProfile) -> Optional[Dict[str, str]]:
+        """Files of the profile's CA and client certificate ("ca", "cert", "key"); None if one is gone"""
+        paths = {}
+        if profile.ca_cert:
+            if not self.certs.get(profile.ca_cert):
+                profile.last_error = f"CA certificate {profile.ca_cert} is not in the certificate store"
+                return None
+            paths["ca"] = str(self.certs.path(profile.ca_cert))
+        if profile.client_cert:
+            key = self.certs.key_for(profile.client_cert)
+            if not self.certs.get(profile.client_cert) or not key:
+                profile.last_error = (f"Client certificate {profile.client_cert} or its key is not in the "
+                                      f"certificate store")
+                return None
+            paths["cert"] = str(self.certs.path(profile.client_cert))
+            paths["key"] = str(self.certs.path(key.id))
+        return paths
+    
+    async def _connect_hotspot(self, profile: ConnectionProfile, progress: Progress = None) -> bool:
+        """Offer the profile's SSID as an access point and share the uplink through it"""
+        if not profile.ssid:
+            return False
+        
+        blocked = self.backends.rfkill.interface_block(profile.interface)
+        if blocked:
+            profile.last_error = BLOCK_REASONS[blocked]
+            return False
+        
+        channel = profile.channel or DEFAULT_CHANNEL
+        network = hotspot_network(profile.ip_address, profile.netmask)
+        _report(progress, 20, f"Starting access point {profile.ssid} on channel {channel}")
+        success, message = await self.backends.hotspot.start(
+            profile.interface,
+            profile.ssid,
+            reveal(profile.password),
+            channel,
+            network,
+            profile.blocked_clients,
+            profile.allowed_clients if profile.client_filter == "allow" else None,
+            profile.client_isolation
+        )
+        if not success:
+            profile.last_error = message
+            return False
+        if profile.client_limits and not await self.backends.hotspot.set_limits(profile.interface,
+                                                                                 profile.client_limits):
+            self.logger.warning(f"Could not apply client rate limits on {profile.interface}; sharing without them")
+        
+        # Clients ask the forwarder on the hotspot's own address when there is one
+        dns = self._upstream_dns()
+        if self.dns_forwarder and await self.dns_forwarder.add_listener(str(network.ip)):
+            dns = [str(network.ip)]
+        
+        _report(progress, 60, f"Serving addresses in {network.network}")
+        success, message = await self.backends.dhcp_server.start(
+            profile.interface, network, dns, profile.dhcp_reservations
+        )
+        if not success:
+            profile.last_error = message
+            await self._stop_hotspot(profile)
+        return success
+    
+    async def _stop_hotspot(self, profile: ConnectionProfile) -> bool:
+        """Stop serving addresses and names on a hotspot, then the access point itself"""
+        network = hotspot_network(profile.ip_address, profile.netmask)
+        if self.dns_forwarder:
+            await self.dns_forwarder.remove_listener(str(network.ip))
+        await self.backends.dhcp_server.stop(profile.interface)
+        return await self.backends.hotspot.stop(profile.interface, network)
+    
+    def link_dns(self) -> Dict[str, List[str]]:
+        """Resolvers of each configured link, the profile's own over those DHCP handed out"""
+        servers = {}
+        for interface, state in self.interface_states.items():
+            profile = self.get_profile(state.profile_name) if state.profile_name else None
+            if state.status != LinkState.CONFIGURED or not profile or profile.connection_type == "hotspot":
+                continue
+            servers[interface] = list(profile.dns_servers or (state.dhcp_lease or {}).get("dns_servers") or [])
+        return servers
+    
+    def _upstream_dns(self) -> List[str]:
+        """Resolvers to hand to clients: ours, minus local stubs they cannot reach"""
+        servers = []
+        for server in self.discovery._get_dns_servers():
+            try:
+                address = ipaddress.ip_address(server.split("%")[0])
+            except ValueError:
+                continue
+            if address.version == 4 and not address.is_loopback:
+                servers.append(server)
+        return servers
+    
+    def active_hotspots(self) -> List[str]:
+        """Interfaces currently sharing a connection as an access point"""
+        return [
+            interface for interface, state in self.interface_states.items()
+            if state.status == LinkState.CONFIGURED and state.profile_name
+            and getattr(self.get_profile(state.profile_name), "connection_type", None) == "hotspot"
+        ]
+    
     async def disconnect_interface(self, interface: str) -> bool:
         """Disconnect an interface"""
-        if interface in self.interface_states:
+        if self.is_unmanaged(interface):
+            self.logger.warning(f"Refusing to disconnect unmanaged interface {interface}")
+            return False
+        
+        # Interfaces we never brought up are simply taken down; anything else
+        # goes through deactivating so a connect cannot interleave
+        tracked = self.get_link_state(interface) != LinkState.DISCONNECTED
+        if tracked:
+            try:
+                self._transition(interface, LinkState.DEACTIVATING, "disconnect requested")
+            except StateTransitionError as e:
+                self.logger.warning(f"Refusing to disconnect: {e}")
+                return False
+        
+        success = await self._bring_down(interface)
+        
+        if tracked and success:
+            self.interface_states[interface].profile_name = None
+            self.apply_proxy_arp(interface)
+            self.revert_sysctls(interface)
+            await self.remove_mptcp(interface)
+            await self.apply_shaping(interface)
+            self._transition(interface, LinkState.DISCONNECTED, "disconnected")
+        elif tracked:
+            self._transition(interface, LinkState.FAILED, "disconnect failed")
+        return success
+    
+    async def renew_lease(self, interface: str) -> Optional[Dict]:
+        """Renew an interface's DHCP lease now; returns the new lease, or None on failure"""
+        if not await self.backends.ethernet.renew_dhcp(interface):
+            return None
+        await self._update_connection_info(interface)
+        return self.backends.ethernet.get_dhcp_lease(interface)
+    
+    async def release_lease(self, interface: str) -> bool:
+        """Give an interface's DHCP lease back; like a disconnect, but the link stays up"""
+        tracked = self.get_link_state(interface) != LinkState.DISCONNECTED
+        if tracked:
+            try:
+                self._transition(interface, LinkState.DEACTIVATING, "lease release requested")
+            except StateTransitionError as e:
+                self.logger.warning(f"Refusing to release lease: {e}")
+                return False
+        
+        success = await self.backends.ethernet.release_dhcp(interface)
+        
+        if tracked and success:
             state = self.interface_states[interface]
-            state.status = "disconnected"
             state.profile_name = None
-            state.connected_at = None
-            self._save_states()
-        
-        # Determine interface type and disconnect appropriately
+            state.ip_address = None
+            state.dhcp_lease = None
+            self.apply_proxy_arp(interface)
+            self.revert_sysctls(interface)
+            await self.remove_mptcp(interface)
+            await self.apply_shaping(interface)
+            self._transition(interface, LinkState.DISCONNECTED, "lease released")
+        elif tracked:
+            self._transition(interface, LinkState.FAILED, "lease release failed")
+        return success
+    
+    async def _bring_down(self, interface: str) -> bool:
+        """Take an interface down with the backend that owns it"""
         interfaces = self.discovery.discover_interfaces()
         iface = next((i for i in interfaces if i.name == interface), None)
         
-        if iface and iface.interface_type == "WiFi":
-            return self.wifi.disconnect(interface)
-        else:
-            # Bring down ethernet interface
-            try:
-                import subprocess
-                result = subprocess.run([
-                    'sudo', 'ip', 'link', 'set', interface, 'down'
-                ], capture_output=True)
-                return result.returncode == 0
-            except:
-                return False
+        state = self.interface_states.get(interface)
+        profile = self.get_profile(state.profile_name) if state and state.profile_name else None
+        if profile and profile.connection_type == "hotspot":
+            return await self._stop_hotspot(profile)
+        
+        if iface and iface.interface_type == InterfaceType.WIFI:
+            return self.backends.wifi.disconnect(interface)
+        
+        vpn_config = next((c for c in self.backends.vpn.discover() if c.name == interface), None)
+        if vpn_config and self.backends.vpn.is_active(vpn_config):
+            return await self.backends.vpn.disconnect(vpn_config)
+        
+        # Bring down ethernet interface
+        return await self.backends.ethernet.bring_down(interface)
     
     async def _update_connection_info(self, interface: str):
         """Update connection information from system"""
         try:
-            import subprocess
-            
-            # Get IP address
-            result = subprocess.run([
-                'ip', 'addr', 'show', interface
-            ], capture_output=True, text=True)
-            
-            ip_address = None
-            if result.returncode == 0:
-                import re
-                ip_match = re.search(r'inet (\d+\.\d+\.\d+\.\d+)', result.stdout)
-                if ip_match:
-                    ip_address = ip_match.group(1)
-            
-            # Get gateway
-            gateway = None
-            result = subprocess.run([
-                'ip', 'route', 'show', 'dev', interface
-            ], capture_output=True, text=True)
-            
-            if result.returncode == 0:
-                for line in result.stdout.split('\n'):
-                    if 'default via' in line:
-                        parts = line.split()
-                        if 'via' in parts:
-                            gateway = parts[parts.index('via') + 1]
-                        break
+            ip_address, gateway = self.backends.ethernet.get_address(interface)
             
             # Update state
             if interface in self.interface_states:
                 state = self.interface_states[interface]
+                previous_address, previous_lease = state.ip_address, state.dhcp_lease
                 state.ip_address = ip_address
                 state.gateway = gateway
+                state.dhcp_lease = self.backends.ethernet.get_dhcp_lease(interface)
                 self._save_states()
                 
+                lease = state.dhcp_lease
+                if lease and lease.get("acquired_at") != (previous_lease or {}).get("acquired_at"):
+                    self.events.publish(
+                        "DhcpLeaseChanged", interface=interface, address=lease.get("ip_address"),
+                        previous_address=previous_address, server=lease.get("server"),
+                        lease_time=lease.get("lease_time"),
+                        renewed=(previous_lease or {}).get("ip_address") == lease.get("ip_address")
+                    )
+                
         except Exception as e:
             self.logger.error(f"Failed to update connection info for {interface}: {e}")
     
+    async def apply_route_metric(self, interface: str, profile: Optional[ConnectionProfile] = None) -> Optional[int]:
+        """Move the interface's default routes to the metric its profile should have; that metric, or None
+        when there is nothing to set (no profile, a VPN or hotspot, no backend support)"""
+        state = self.interface_states.get(interface)
+        if profile is None:
+            profile = self.get_profile(state.profile_name) if state and state.profile_name else None
+        if not state or not profile or profile.connection_type not in ("ethernet", "wifi"):
+            return None
+        link = next((i for i in self.discovery.discover_interfaces() if i.name == interface), None)
+        interface_type = link.interface_type if link else (
+            InterfaceType.WIFI if profile.connection_type == "wifi" else InterfaceType.ETHERNET)
+        metric = route_metric(interface_type, profile.priority, profile.route_metric)
+        current = await self.backends.ethernet.address_state(interface) if metric is not None else None
+        if current is None:
+            return None
+        for change in metric_changes(interface, metric, current["routes"]):
+            error = await self.backends.ethernet.apply_change(change)
+            if error:
+                self.logger.warning(f"Route metric on {interface}: {error}", extra={"interface": interface})
+        state.route_metric = metric
+        self._save_states()
+        return metric
+    
+    def apply_proxy_arp(self, interface: str, profile: Optional[ConnectionProfile] = None) -> bool:
+        """Proxy ARP on the interface as its active profile asks; only ever turned off where alopex turned
+        it on, so a sysctl set by hand stays. Returns whether alopex has it on"""
+        state = self.interface_states.get(interface)
+        if not state:
+            return False
+        if profile is None:
+            profile = self.get_profile(state.profile_name) if state.profile_name else None
+        wanted = bool(profile and profile.proxy_arp and profile.connection_type != "vpn")
+        if wanted != state.proxy_arp and self.backends.host.set_proxy_arp(interface, wanted):
+            state.proxy_arp = wanted
+            self.logger.info(f"Proxy ARP {'on' if wanted else 'off'} on {interface}", extra={"interface": interface})
+            self._save_states()
+        return state.proxy_arp
+    
+    def apply_sysctls(self, interface: str, profile: Optional[ConnectionProfile] = None) -> Dict[str, str]:
+        """Write the active profile's sysctls, remembering what each was; returns {name: error} for the ones
+        the kernel refused. Whatever an earlier activation wrote is put back first"""
+        state = self.interface_states.get(interface)
+        if not state:
+            return {}
+        if profile is None:
+            profile = self.get_profile(state.profile_name) if state.profile_name else None
+        self.revert_sysctls(interface)
+        errors = {}
+        for name, value in (profile.sysctls if profile else {}).items():
+            key = sysctls.sysctl_key(name, interface)
+            previous = self.backends.host.read_sysctl(key)
+            error = "no such sysctl here" if previous is None else self.backends.host.write_sysctl(key, value)
+            if error:
+                errors[name] = error
+                self.logger.warning(f"Cannot set {key} to {value}: {error}", extra={"interface": interface})
+                continue
+            # A host-wide key another connection already set: what it was before them is the one to go back to
+            holder = self._sysctl_holder(key, interface)
+            if holder:
+                previous = holder["previous"]
+            state.sysctls[key] = {"name": name, "value": value, "previous": previous}
+        if state.sysctls or errors:
+            self.logger.info(f"Sysctls for {profile.name}: {len(state.sysctls)} set, {len(errors)} refused",
+                             extra={"interface": interface})
+        self._save_states()
+        return errors
+    
+    def revert_sysctls(self, interface: str):
+        """Put back what apply_sysctls changed, leaving keys someone has set to something else since. A host-wide
+        key another active connection set too goes to that one's value instead; it puts it back when it goes"""
+        state = self.interface_states.get(interface)
+        if not state or not state.sysctls:
+            return
+        for key, entry in state.sysctls.items():
+            holder = self._sysctl_holder(key, interface)
+            current = self.backends.host.read_sysctl(key)
+            if holder:
+                if current == entry["value"] != holder["value"]:
+                    self.backends.host.write_sysctl(key, holder["value"])
+                continue
+            if current != entry["value"]:
+                self.logger.info(f"Leaving {key} at {current}, changed since it was set to {entry['value']}",
+                                 extra={"interface": interface})
+                continue
+            error = self.backends.host.write_sysctl(key, entry["previous"])
+            if error:
+                self.logger.warning(f"Cannot put {key} back to {entry['previous']}: {error}",
+                                    extra={"interface": interface})
+        state.sysctls = {}
+        self._save_states()
+    
+    def _sysctl_holder(self, key: str, interface: str) -> Optional[Dict]:
+        """Another interface's record of having set this (host-wide) key"""
+        return next((other.sysctls[key] for name, other in self.interface_states.items()
+                     if name != interface and other.sysctls and key in other.sysctls), None)
+    
+    def sysctl_status(self, interface: str) -> List[Dict]:
+        """sysctls.status for the interface: each setting's value in force, wanted and from before"""
+        state = self.interface_states.get(interface)
+        profile = self.get_profile(state.profile_name) if state and state.profile_name else None
+        return sysctls.status(profile.sysctls if profile else {}, state.sysctls if state else {}, interface,
+                              self.backends.host.read_sysctl)
+    
+    async def apply_mptcp(self, interface: str, profile: Optional[ConnectionProfile] = None) -> Dict[str, str]:
+        """Make the interface's addresses MPTCP endpoints with its profile's flags, replacing the ones an
+        earlier activation added; returns {address: error} for those the kernel refused"""
+        state = self.interface_states.get(interface)
+        if not state:
+            return {}
+        if profile is None:
+            profile = self.get_profile(state.profile_name) if state.profile_name else None
+        await self.remove_mptcp(interface)
+        if not profile or not profile.mptcp_flags:
+            return {}
+        current = await self.backends.ethernet.address_state(interface)
+        errors = {}
+        for address in mptcp.endpoint_addresses(current["addresses"] if current else []):
+            try:
+                endpoint = await self.backends.host.add_mptcp_endpoint(address, interface, profile.mptcp_flags)
+            except OSError as e:
+                errors[address] = e.strerror or str(e)
+                self.logger.warning(f"Cannot make {address} an MPTCP endpoint: {errors[address]}",
+                                    extra={"interface": interface})
+                continue
+            state.mptcp_endpoints.append(endpoint["id"])
+        if state.mptcp_endpoints:
+            self.logger.info(f"MPTCP endpoints {', '.join(map(str, state.mptcp_endpoints))} "
+                             f"({', '.join(profile.mptcp_flags)}) on {interface}", extra={"interface": interface})
+        self._save_states()
+        return errors
+    
+    async def remove_mptcp(self, interface: str):
+        """Take out the MPTCP endpoints apply_mptcp added; ones already gone (removed by hand, the address
+        dropped) are just forgotten"""
+        state = self.interface_states.get(interface)
+        if not state or not state.mptcp_endpoints:
+            return
+        for endpoint_id in state.mptcp_endpoints:
+            try:
+                await self.backends.host.remove_mptcp_endpoint(endpoint_id)
+            except OSError as e:
+                self.logger.debug(f"MPTCP endpoint {endpoint_id} on {interface}: {e}")
+        state.mptcp_endpoints = []
+        self._save_states()
+    
+    async def apply_shaping(self, interface: str, profile: Optional[ConnectionProfile] = None) -> Dict[str, str]:
+        """Put the active profile's root qdisc and congestion control on the interface; only ever resets what
+        alopex set, so a qdisc put there by hand stays. Returns {"qdisc" or "congestion_control": error}"""
+        state = self.interface_states.get(interface)
+        if not state:
+            return {}
+        if profile is None:
+            profile = self.get_profile(state.profile_name) if state.profile_name else None
+        errors = {}
+        qdisc = {"kind": profile.qdisc, "bandwidth": profile.qdisc_bandwidth} if profile and profile.qdisc else None
+        if qdisc != state.qdisc:
+            error = await self.backends.host.set_qdisc(interface, qdisc["kind"] if qdisc else None,
+                                                       qdisc["bandwidth"] if qdisc else None)
+            if error:
+                errors["qdisc"] = error
+                self.logger.warning(f"Cannot set qdisc on {interface}: {error}", extra={"interface": interface})
+            else:
+                state.qdisc = qdisc
+                self.logger.info(f"Qdisc on {interface}: " + (" ".join(filter(None, qdisc.values())) if qdisc
+                                                               else "kernel default"), extra={"interface": interface})
+        
+        algorithm = profile.congestion_control if profile else None
+        current = await self.backends.host.shaping_state(interface)
+        # Checked against the routes, since a DHCP renewal or a metric change puts them back without it
+        in_force = current["congestion_control"] if current else None
+        if (algorithm or state.congestion_control) and in_force != algorithm:
+            error = await self.backends.host.set_congestion_control(interface, algorithm)
+            if error:
+                errors["congestion_control"] = error
+                self.logger.warning(f"Cannot set congestion control {algorithm} on {interface}: {error}",
+                                    extra={"interface": interface})
+        if "congestion_control" not in errors:
+            state.congestion_control = algorithm
+        self._save_states()
+        return errors
+    
+    async def shaping_status(self, interface: str) -> Dict:
+        """What the active profile wants against what is in force on the interface"""
+        state = self.interface_states.get(interface)
+        profile = self.get_profile(state.profile_name) if state and state.profile_name else None
+        current = await self.backends.host.shaping_state(interface) or {"qdisc": None, "congestion_control": None}
+        wanted = {"qdisc": profile.qdisc if profile else None,
+                  "bandwidth": profile.qdisc_bandwidth if profile else None,
+                  "congestion_control": profile.congestion_control if profile else None}
+        qdisc = current["qdisc"] or {}
+        in_effect = ((not wanted["qdisc"] or (qdisc.get("kind"), qdisc.get("bandwidth"))
+                      == (wanted["qdisc"], wanted["bandwidth"]))
+                     and (not wanted["congestion_control"]
+                          or current["congestion_control"] == wanted["congestion_control"]))
+        return {"interface": interface, "profile": profile.name if profile else None, "wanted": wanted,
+                "qdisc": current["qdisc"], "congestion_control": current["congestion_control"],
+                "set_by_alopex": {"qdisc": bool(state and state.qdisc),
+                                  "congestion_control": bool(state and state.congestion_control)},
+                "in_effect": in_effect}
+    
+    async def announce_addresses(self, interface: str, profile: Optional[ConnectionProfile] = None,
+                                 rounds: int = ANNOUNCE_ROUNDS, force: bool = False) -> Optional[Dict]:
+        """Tell neighbours the interface's addresses live here now (gratuitous ARP, unsolicited NA). Returns
+        the first round's result, the other rounds following in the background; None when the profile turned
+        announcing off (unless forced) or there is nothing to announce"""
+        state = self.interface_states.get(interface)
+        if profile is None:
+            profile = self.get_profile(state.profile_name) if state and state.profile_name else None
+        if profile and not profile.gratuitous_arp and not force:
+            return None
+        link = next((i for i in self.discovery.discover_interfaces() if i.name == interface), None)
+        current = await self.backends.ethernet.address_state(interface) if link and link.mac else None
+        addresses = announce_targets(current["addresses"]) if current else []
+        if not addresses:
+            return None
+        result = await self.backends.host.announce_addresses(interface, link.mac, addresses)
+        previous = self.announce_tasks.pop(interface, None)
+        if previous:
+            previous.cancel()
+        if result["sent"] and rounds > 1:
+            self.announce_tasks[interface] = asyncio.create_task(
+                self._announce_again(interface, link.mac, result["sent"], rounds - 1))
+        if state and result["sent"]:
+            state.announced_at = result["timestamp"]
+        self.events.publish("AddressesAnnounced", interface=interface, addresses=result["sent"],
+                            errors=result["errors"])
+        return result
+    
+    async def _announce_again(self, interface: str, mac: str, addresses: List[str], rounds: int):
+        for _ in range(rounds):
+            await asyncio.sleep(ANNOUNCE_INTERVAL)
+            await self.backends.host.announce_addresses(interface, mac, addresses)
+        self.announce_tasks.pop(interface, None)
+    
     async def auto_connect_all(self):
         """Auto-connect all interfaces with auto-connect profiles"""
         interfaces = self.discovery.discover_interfaces()
         
         for interface in interfaces:
-            if interface.status != "Connected":
+            if interface.status != "Connected" and not self.is_unmanaged(interface.name):
                 await self.auto_connect_interface(interface.name)
     
     async def auto_connect_interface(self, interface: str):
@@ -355,12 +1126,74 @@ class ConnectionManager:
         auto_profiles = [p for p in profiles if p.auto_connect]
         
         for profile in auto_profiles:
+            if not self.retry.due(profile.name):
+                # Backing off after failures, or given up on
+                continue
             self.logger.info(f"Attempting auto-connect: {profile.name}")
-            if await self.connect_profile(profile.name):
+            if await self._auto_connect(profile):
                 return True
         
+        self._schedule_retry(interface)
         return False
     
+    async def _auto_connect(self, profile: ConnectionProfile) -> bool:
+        """Connect on auto-connect's behalf; failures count towards the profile's backoff and give-up"""
+        try:
+            self.begin_connect(profile.name)
+        except (KeyError, PermissionError, StateTransitionError) as e:
+            # Gone, unmanaged or busy with another connect: not the profile's failure
+            self.logger.debug(f"Not auto-connecting {profile.name}: {e}")
+            return False
+        if await self.finish_connect(profile):
+            return True
+        
+        entry = self.retry.failed(profile.name, profile.interface, profile.last_error)
+        if entry["gave_up"]:
+            self.logger.error(f"Giving up auto-connecting {profile.name} after {entry['failures']} attempts: "
+                              f"{entry['last_error']}", extra={"interface": profile.interface})
+            self.events.publish("AutoConnectGaveUp", profile=profile.name, interface=profile.interface,
+                                attempts=entry["failures"], error=entry["last_error"])
+        else:
+            delay = round(entry["next_attempt"] - time.time(), 1)
+            self.logger.warning(f"Auto-connecting {profile.name} failed ({entry['failures']} so far), "
+                                f"retrying in {delay:g}s", extra={"interface": profile.interface})
+            self.events.publish("AutoConnectRetrying", profile=profile.name, interface=profile.interface,
+                                attempts=entry["failures"], retry_in=delay, error=entry["last_error"])
+        return False
+    
+    def _schedule_retry(self, interface: str):
+        """Come back to the interface when its next backed-off profile is due"""
+        previous = self.retry_tasks.pop(interface, None)
+        if previous:
+            previous.cancel()
+        when = self.retry.next_attempt([p.name for p in self.list_profiles(interface) if p.auto_connect])
+        if when is not None:
+            self.retry_tasks[interface] = asyncio.create_task(self._retry_later(interface, when))
+    
+    async def _retry_later(self, interface: str, when: float):
+        await asyncio.sleep(max(0.0, when - time.time()))
+        self.retry_tasks.pop(interface, None)
+        # Connected or being connected some other way in the meantime
+        if self.get_link_state(interface) in (LinkState.DISCONNECTED, LinkState.FAILED):
+            await self.auto_connect_interface(interface)
+    
+    def reset_retries(self, name: Optional[str] = None) -> List[str]:
+        """Clear the backoff for one profile or all of them; the profiles whose failures were forgotten"""
+        names = [name] if name else [entry["profile"] for entry in self.retry.status()]
+        return [name for name in names if self.retry.reset(name)]
+    
+    def cancel_retries(self):
+        """Stop the pending retries, e.g. on shutdown"""
+        for task in self.retry_tasks.values():
+            task.cancel()
+        self.retry_tasks.clear()
+    
+    def cancel_announcements(self):
+        """Drop the repeat announcements still to go out, e.g. on shutdown"""
+        for task in self.announce_tasks.values():
+            task.cancel()
+        self.announce_tasks.clear()
+    
     async def monitor_connections(self):
         """Monitor connections and handle reconnection"""
         while self.monitoring:
@@ -378,7 +1211,7 @@ class ConnectionManager:
     
     async def _check_interface_health(self, interface: NetworkInterface):
         """Check health of a specific interface"""
-        if interface.name not in self.interface_states:
+        if interface.name not in self.interface_states or self.is_unmanaged(interface.name):
             return
         
         state = self.interface_states[interface.name]
@@ -388,16 +1221,18 @@ class ConnectionManager:
         state.last_seen = now
         
         # Check if previously connected interface is now disconnected
-        if (state.status == "connected" and interface.status != "Connected" and 
+        if (state.status == LinkState.CONFIGURED and interface.status != "Connected" and 
             state.connected_at and (now - state.connected_at) > 30):
             
-            self.logger.warning(f"Interface {interface.name} unexpectedly disconnected")
-            state.status = "disconnected"
+            self.logger.warning(f"Interface {interface.name} unexpectedly disconnected",
+                                extra={"interface": interface.name})
+            self._transition(interface.name, LinkState.FAILED, "link lost")
             
             # Attempt reconnection if we have a profile
-            if state.profile_name:
-                self.logger.info(f"Attempting reconnection: {state.profile_name}")
-                asyncio.create_task(self.connect_profile(state.profile_name))
+            profile = self.get_profile(state.profile_name) if state.profile_name else None
+            if profile and self.retry.due(profile.name):
+                self.logger.info(f"Attempting reconnection: {profile.name}")
+                asyncio.create_task(self._auto_connect(profile))
         
         self._save_states()
     
@@ -409,7 +1244,7 @@ class ConnectionManager:
         """Get connection statistics"""
         total_profiles = len(self.profiles)
         connected_interfaces = len([s for s in self.interface_states.values() 
-                                  if s.status == "connected"])
+                                  if s.status == LinkState.CONFIGURED])
         auto_profiles = len([p for p in self.profiles.values() if p.auto_connect])
         
         return {
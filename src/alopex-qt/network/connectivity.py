@@ -0,0 +1,141 @@
+"""
+Connectivity - IPv4 against IPv6 to a few well-known hosts
+Connects to each target over each family on its own, then the way browsers do
+(Happy Eyeballs, RFC 8305), and sums up which family works, which one is
+actually used and what is broken
+"""
+
+import time
+import socket
+import asyncio
+import logging
+import statistics
+from typing import Dict, List, Optional
+
+from . import port_probe
+
+# Configure logging
+logger = logging.getLogger(__name__)
+
+DEFAULT_TARGETS = ["www.google.com:443", "www.cloudflare.com:443", "www.wikipedia.org:443"]
+# How long IPv6 gets before IPv4 is tried alongside it (RFC 8305 recommends 250 ms)
+HAPPY_EYEBALLS_DELAY = 0.25
+# IPv6 slower than this many times IPv4 (and by at least SLOW_MARGIN_MS) is worth a mention
+SLOW_FACTOR = 1.5
+SLOW_MARGIN_MS = 20.0
+FAMILIES = {4: socket.AF_INET, 6: socket.AF_INET6}
+
+async def race(host: str, port: int, timeout: float, delay: float = HAPPY_EYEBALLS_DELAY) -> Dict:
+    """{"family", "address", "latency_ms", "error"} of one Happy Eyeballs connection"""
+    started = time.monotonic()
+    writer = None
+    try:
+        _, writer = await asyncio.wait_for(asyncio.open_connection(host, port, happy_eyeballs_delay=delay), timeout)
+        address = writer.get_extra_info("peername")[0]
+    except asyncio.TimeoutError:
+        return {"family": None, "address": None, "latency_ms": None, "error": f"no answer in {timeout:g}s"}
+    except OSError as e:
+        return {"family": None, "address": None, "latency_ms": None, "error": e.strerror or str(e)}
+    finally:
+        if writer:
+            writer.close()
+    return {"family": 6 if ":" in address else 4, "address": address,
+            "latency_ms": (time.monotonic() - started) * 1000, "error": None}
+
+async def check(host: str, port: int, timeout: float) -> Dict:
+    """Probe one target over IPv4, over IPv6, then let the two race"""
+    ipv4, ipv6 = await asyncio.gather(*(port_probe.probe(host, port, timeout=timeout, family=FAMILIES[family])
+                                        for family in (4, 6)))
+    # After the separate probes, so the race doesn't compete with them
+    used = await race(host, port, timeout)
+    return {"ipv4": ipv4, "ipv6": ipv6, "used": used}
+
+def race_outcome(ipv4: Dict, ipv6: Dict, delay: float = HAPPY_EYEBALLS_DELAY) -> Dict:
+    """The race that separate probe results imply: IPv6 first, IPv4 once it fails or the delay passes"""
+    v4, v6 = (probe["latency_ms"] if probe["state"] == "open" else None for probe in (ipv4, ipv6))
+    # IPv6 that fails straight away (no address, no route) lets IPv4 start at once
+    head_start = delay * 1000 if ipv6["state"] in ("open", "filtered") else 0.0
+    if v6 is not None and (v4 is None or v6 <= v4 + head_start):
+        return {"family": 6, "address": ipv6["address"], "latency_ms": v6, "error": None}
+    if v4 is not None:
+        return {"family": 4, "address": ipv4["address"], "latency_ms": v4 + (head_start if v6 is None else 0.0),
+                "error": None}
+    return {"family": None, "address": None, "latency_ms": None, "error": ipv4["error"] or ipv6["error"]}
+
+def family_status(probes: List[Dict]) -> str:
+    """working, partial (some targets), broken (addresses that time out) or unavailable (none to try, or no route)"""
+    # A refused connection still reached the host
+    reached = sum(1 for probe in probes if probe["state"] in ("open", "closed"))
+    resolved = sum(1 for probe in probes if probe["state"] != "unresolved")
+    if reached and reached == resolved:
+        return "working"
+    if reached:
+        return "partial"
+    if any(probe["state"] == "filtered" for probe in probes):
+        return "broken"
+    return "unavailable"
+
+def _median(values: List[Optional[float]]) -> Optional[float]:
+    values = [value for value in values if value is not None]
+    return statistics.median(values) if values else None
+
+def diagnose(results: List[Dict]) -> Dict:
+    """Sum up check() results (each with its "target") into per-family status and findings"""
+    families = {}
+    for family in (4, 6):
+        probes = [result[f"ipv{family}"] for result in results]
+        families[f"ipv{family}"] = {
+            "status": family_status(probes),
+            "latency_ms": _median([probe["latency_ms"] for probe in probes if probe["state"] == "open"]),
+            "failing": [result["target"] for result, probe in zip(results, probes)
+                        if probe["state"] in ("filtered", "unreachable")]
+        }
+    used = [result["used"]["family"] for result in results if result["used"]["family"]]
+    preferred = max((4, 6), key=used.count) if used else None
+    ipv4, ipv6 = families["ipv4"], families["ipv6"]
+
+    findings = []
+    if ipv4["status"] == "unavailable" and ipv6["status"] == "unavailable" and all(
+            result[f"ipv{family}"]["state"] == "unresolved" for result in results for family in (4, 6)):
+        findings.append("No target resolved; check DNS before anything else")
+    elif not used:
+        findings.append("Neither IPv4 nor IPv6 reaches any target")
+    for name, family in (("IPv4", ipv4), ("IPv6", ipv6)):
+        if family["status"] == "broken":
+            findings.append(f"{name} is broken: every target has {name} addresses but connections time out")
+        elif family["status"] == "partial":
+            findings.append(f"{name} fails for {', '.join(family['failing'])}")
+    if ipv6["status"] in ("broken", "partial") and ipv4["status"] != "unavailable":
+        findings.append("Browsers fall back to IPv4 after a short delay; programs without Happy Eyeballs may hang")
+    if ipv6["status"] == "unavailable" and ipv4["status"] == "working":
+        findings.append("No IPv6 connectivity; everything goes over IPv4")
+    if ipv4["status"] in ("broken", "unavailable") and ipv6["status"] in ("working", "partial"):
+        findings.append("Only IPv6 works; IPv4-only sites are out of reach")
+    if ipv4["latency_ms"] and ipv6["latency_ms"] and ipv6["latency_ms"] > ipv4["latency_ms"] * SLOW_FACTOR \
+            and ipv6["latency_ms"] - ipv4["latency_ms"] >= SLOW_MARGIN_MS:
+        findings.append(f"IPv6 is slower than IPv4 ({ipv6['latency_ms']:.0f} ms against {ipv4['latency_ms']:.0f} ms)")
+    healthy = bool(used) and not any(family["status"] in ("broken", "partial") for family in families.values())
+    if healthy and not findings:
+        findings.append(f"IPv4 and IPv6 both work; connections use IPv{preferred}")
+
+    return dict(families, targets=results, preferred=preferred, healthy=healthy, findings=findings)
+
+def _probe_text(probe: Dict) -> str:
+    if probe["state"] == "open":
+        return f"{probe['latency_ms']:.0f} ms"
+    return {"unresolved": "no address", "filtered": "timed out", "unreachable": "no route"}.get(probe["state"],
+                                                                                             probe["state"])
+
+def report(diagnosis: Dict) -> str:
+    """The diagnosis as text for people: findings first, then target by target"""
+    lines = [("Healthy" if diagnosis["healthy"] else "Problems found") + ":"]
+    lines += [f"  {finding}" for finding in diagnosis["findings"]]
+    lines += ["", f"  {'IPv4':<8}{diagnosis['ipv4']['status']}", f"  {'IPv6':<8}{diagnosis['ipv6']['status']}"]
+    if diagnosis["preferred"]:
+        lines.append(f"  {'Used':<8}IPv{diagnosis['preferred']}")
+    lines += ["", f"  {'TARGET':<28}{'IPV4':<14}{'IPV6':<14}USED"]
+    for result in diagnosis["targets"]:
+        used = result["used"]
+        lines.append(f"  {result['target']:<28}{_probe_text(result['ipv4']):<14}{_probe_text(result['ipv6']):<14}"
+                     + (f"IPv{used['family']} ({used['address']})" if used["family"] else "none"))
+    return "\n".join(lines)
@@ -0,0 +1,261 @@
+"""
+Conntrack - Active flows from the kernel's connection tracking table
+Dumps nf_conntrack over ctnetlink (falling back to /proc/net/nf_conntrack) and
+sums flows by protocol, destination host and port, and NAT per shared network
+"""
+
+import time
+import socket
+import struct
+import logging
+import ipaddress
+from collections import Counter, defaultdict
+from pathlib import Path
+from typing import Callable, Dict, List, Optional, Tuple
+
+from .netlink import NLM_F_DUMP, NLM_F_REQUEST, NLMSG_DONE, NLMSG_ERROR, attributes, check_error, message, messages
+
+# Configure logging
+logger = logging.getLogger(__name__)
+
+NETLINK_NETFILTER = 12
+NFNL_SUBSYS_CTNETLINK = 1
+IPCTNL_MSG_CT_GET = 1
+
+# Attribute types from linux/netfilter/nfnetlink_conntrack.h
+CTA_TUPLE_ORIG = 1
+CTA_TUPLE_REPLY = 2
+CTA_PROTOINFO = 4
+CTA_COUNTERS_ORIG = 9
+CTA_COUNTERS_REPLY = 10
+CTA_TUPLE_IP = 1
+CTA_TUPLE_PROTO = 2
+CTA_IP_V4_SRC, CTA_IP_V4_DST, CTA_IP_V6_SRC, CTA_IP_V6_DST = 1, 2, 3, 4
+CTA_PROTO_NUM, CTA_PROTO_SRC_PORT, CTA_PROTO_DST_PORT = 1, 2, 3
+CTA_PROTOINFO_TCP = 1
+CTA_PROTOINFO_TCP_STATE = 1
+CTA_COUNTERS_PACKETS, CTA_COUNTERS_BYTES = 1, 2
+
+PROTOCOLS = {1: "icmp", 6: "tcp", 17: "udp", 33: "dccp", 47: "gre", 58: "icmpv6", 132: "sctp", 136: "udplite"}
+TCP_STATES = ["NONE", "SYN_SENT", "SYN_RECV", "ESTABLISHED", "FIN_WAIT", "CLOSE_WAIT", "LAST_ACK", "TIME_WAIT",
+              "CLOSE", "SYN_SENT2"]
+
+PROC_TABLE = Path("/proc/net/nf_conntrack")
+MAX_FILE = Path("/proc/sys/net/netfilter/nf_conntrack_max")
+RECV_SIZE = 1 << 20
+RECV_TIMEOUT = 5.0
+# Dumping a big table costs real CPU, so back-to-back requests share one
+CACHE_SECONDS = 2.0
+DEFAULT_TOP = 10
+
+def _tuple(data: bytes) -> Dict:
+    attrs = attributes(data)
+    ip = attributes(attrs.get(CTA_TUPLE_IP, b""))
+    proto = attributes(attrs.get(CTA_TUPLE_PROTO, b""))
+    if CTA_IP_V4_SRC in ip:
+        src, dst = (str(ipaddress.IPv4Address(ip[k])) for k in (CTA_IP_V4_SRC, CTA_IP_V4_DST))
+    else:
+        src, dst = (str(ipaddress.IPv6Address(ip[k])) for k in (CTA_IP_V6_SRC, CTA_IP_V6_DST))
+    return {
+        "src": src,
+        "dst": dst,
+        "protocol": proto[CTA_PROTO_NUM][0] if CTA_PROTO_NUM in proto else 0,
+        "sport": struct.unpack("!H", proto[CTA_PROTO_SRC_PORT])[0] if CTA_PROTO_SRC_PORT in proto else None,
+        "dport": struct.unpack("!H", proto[CTA_PROTO_DST_PORT])[0] if CTA_PROTO_DST_PORT in proto else None
+    }
+
+def _counters(data: Optional[bytes]) -> Tuple[Optional[int], Optional[int]]:
+    if data is None:
+        return None, None
+    attrs = attributes(data)
+    return tuple(struct.unpack("!Q", attrs[k])[0] if k in attrs else None
+                 for k in (CTA_COUNTERS_PACKETS, CTA_COUNTERS_BYTES))
+
+def _add(a: Optional[int], b: Optional[int]) -> Optional[int]:
+    return None if a is None and b is None else (a or 0) + (b or 0)
+
+def make_flow(orig: Dict, reply: Dict, state: Optional[str] = None,
+              packets: Optional[int] = None, nbytes: Optional[int] = None) -> Dict:
+    """One flow as reported over IPC; NAT shows as a reply tuple that doesn't mirror the original"""
+    return {
+        "family": 6 if ":" in orig["src"] else 4,
+        "protocol": PROTOCOLS.get(orig["protocol"], str(orig["protocol"])),
+        "src": orig["src"],
+        "dst": orig["dst"],
+        "sport": orig["sport"],
+        "dport": orig["dport"],
+        "state": state,
+        # The address the source was rewritten to, and where the destination really is
+        "snat": reply["dst"] if reply["dst"] != orig["src"] else None,
+        "dnat": reply["src"] if reply["src"] != orig["dst"] else None,
+        "packets": packets,
+        "bytes": nbytes
+    }
+
+def build_dump_request(seq: int = 1) -> bytes:
+    """IPCTNL_MSG_CT_GET dump of every family"""
+    nfgenmsg = struct.pack("=BBH", socket.AF_UNSPEC, 0, 0)
+    return message((NFNL_SUBSYS_CTNETLINK << 8) | IPCTNL_MSG_CT_GET, NLM_F_REQUEST | NLM_F_DUMP, nfgenmsg, seq)
+
+def parse_messages(data: bytes) -> Tuple[List[Dict], bool]:
+    """Flows in a buffer of ctnetlink replies, and whether the dump is done; raises OSError for an error reply"""
+    flows = []
+    for kind, body in messages(data):
+        if kind == NLMSG_DONE:
+            return flows, True
+        if kind == NLMSG_ERROR:
+            check_error(body, "ctnetlink")
+            continue
+        if kind >> 8 != NFNL_SUBSYS_CTNETLINK:
+            continue
+        # Skip the 4-byte nfgenmsg
+        attrs = attributes(body[4:])
+        if CTA_TUPLE_ORIG not in attrs or CTA_TUPLE_REPLY not in attrs:
+            continue
+        orig, reply = _tuple(attrs[CTA_TUPLE_ORIG]), _tuple(attrs[CTA_TUPLE_REPLY])
+        state = None
+        tcp = attributes(attributes(attrs.get(CTA_PROTOINFO, b"")).get(CTA_PROTOINFO_TCP, b""))
+        if CTA_PROTOINFO_TCP_STATE in tcp and tcp[CTA_PROTOINFO_TCP_STATE][0] < len(TCP_STATES):
+            state = TCP_STATES[tcp[CTA_PROTOINFO_TCP_STATE][0]]
+        orig_packets, orig_bytes = _counters(attrs.get(CTA_COUNTERS_ORIG))
+        reply_packets, reply_bytes = _counters(attrs.get(CTA_COUNTERS_REPLY))
+        flows.append(make_flow(orig, reply, state, _add(orig_packets, reply_packets), _add(orig_bytes, reply_bytes)))
+    return flows, False
+
+def dump_netlink() -> List[Dict]:
+    """Every flow over ctnetlink; needs CAP_NET_ADMIN and raises OSError without it"""
+    with socket.socket(socket.AF_NETLINK, socket.SOCK_RAW, NETLINK_NETFILTER) as sock:
+        sock.settimeout(RECV_TIMEOUT)
+        sock.setsockopt(socket.SOL_SOCKET, socket.SO_RCVBUF, RECV_SIZE)
+        sock.bind((0, 0))
+        sock.send(build_dump_request())
+        flows = []
+        done = False
+        while not done:
+            batch, done = parse_messages(sock.recv(RECV_SIZE))
+            flows.extend(batch)
+        return flows
+
+def parse_proc_line(line: str) -> Optional[Dict]:
+    """One /proc/net/nf_conntrack entry, e.g. 'ipv4 2 tcp 6 431999 ESTABLISHED src=... [ASSURED] ...'"""
+    fields = line.split()
+    if len(fields) < 5:
+        return None
+    # The first key=value pairs describe the original direction, the repeats the reply
+    tuples: List[Dict] = [{}, {}]
+    counters: List[Dict] = [{}, {}]
+    state = None
+    for field in fields[5:]:
+        if "=" not in field:
+            if not field.startswith("[") and fields[2] == "tcp":
+                state = field
+            continue
+        key, value = field.split("=", 1)
+        if key in ("src", "dst", "sport", "dport"):
+            target = tuples[0] if key not in tuples[0] else tuples[1]
+        elif key in ("packets", "bytes"):
+            target = counters[0] if key not in counters[0] else counters[1]
+        else:
+            continue
+        target[key] = value
+    orig, reply = tuples
+    if not {"src", "dst"} <= set(orig) or not {"src", "dst"} <= set(reply):
+        return None
+    for direction in tuples:
+        direction["protocol"] = int(fields[3])
+        for key in ("sport", "dport"):
+            direction[key] = int(direction[key]) if key in direction else None
+    packets = _add(*(int(c["packets"]) if "packets" in c else None for c in counters))
+    nbytes = _add(*(int(c["bytes"]) if "bytes" in c else None for c in counters))
+    return make_flow(orig, reply, state, packets, nbytes)
+
+def read_proc(path: Path = PROC_TABLE) -> List[Dict]:
+    """Every flow from the procfs table, where the kernel still provides it"""
+    with open(path) as f:
+        return [flow for flow in map(parse_proc_line, f) if flow]
+
+def dump_flows() -> List[Dict]:
+    """Every tracked flow; raises OSError when neither ctnetlink nor procfs can be read"""
+    try:
+        return dump_netlink()
+    except OSError as e:
+        logger.debug(f"ctnetlink dump failed ({e}), trying {PROC_TABLE}")
+    try:
+        return read_proc()
+    except FileNotFoundError:
+        raise OSError("connection tracking unavailable: ctnetlink refused and "
+                      f"{PROC_TABLE} missing (is nf_conntrack loaded, and are we root?)")
+
+def table_size() -> Optional[int]:
+    """nf_conntrack_max, the number of flows the kernel will track"""
+    try:
+        return int(MAX_FILE.read_text())
+    except (OSError, ValueError):
+        return None
+
+def _in(address: str, network: ipaddress.IPv4Network) -> bool:
+    try:
+        return ipaddress.ip_address(address) in network
+    except ValueError:
+        return False
+
+def summarize(flows: List[Dict], shared: Dict[str, ipaddress.IPv4Network], limit: Optional[int] = None,
+              top: int = DEFAULT_TOP) -> Dict:
+    """Counts by protocol and TCP state, the busiest destinations and ports, and NAT for each shared network"""
+    hosts: Dict[str, Dict] = defaultdict(lambda: {"flows": 0, "bytes": None})
+    for flow in flows:
+        host = hosts[flow["dst"]]
+        host["flows"] += 1
+        host["bytes"] = _add(host["bytes"], flow["bytes"])
+    ports = Counter((flow["protocol"], flow["dport"]) for flow in flows
+                    if flow["dport"] is not None and flow["protocol"] in ("tcp", "udp", "sctp", "udplite"))
+
+    nat = []
+    for interface, network in sorted(shared.items()):
+        clients: Dict[str, Dict] = defaultdict(lambda: {"flows": 0, "bytes": None})
+        for flow in flows:
+            if flow["snat"] and _in(flow["src"], network):
+                client = clients[flow["src"]]
+                client["flows"] += 1
+                client["bytes"] = _add(client["bytes"], flow["bytes"])
+        nat.append({
+            "interface": interface,
+            "network": str(network),
+            "flows": sum(c["flows"] for c in clients.values()),
+            "clients": [dict(address=address, **counts) for address, counts in
+                        sorted(clients.items(), key=lambda item: -item[1]["flows"])]
+        })
+
+    return {
+        "total": len(flows),
+        "limit": limit,
+        "usage": round(100.0 * len(flows) / limit, 1) if limit else None,
+        "protocols": dict(Counter(flow["protocol"] for flow in flows).most_common()),
+        "tcp_states": dict(Counter(flow["state"] for flow in flows if flow["state"]).most_common()),
+        "snat": sum(1 for flow in flows if flow["snat"]),
+        "dnat": sum(1 for flow in flows if flow["dnat"]),
+        "top_hosts": [dict(host=address, **counts) for address, counts in
+                      sorted(hosts.items(), key=lambda item: -item[1]["flows"])[:top]],
+        "top_ports": [{"protocol": protocol, "port": port, "flows": count}
+                      for (protocol, port), count in ports.most_common(top)],
+        "nat": nat
+    }
+
+class FlowMonitor:
+    """Summaries of the conntrack table on request, one dump shared by requests close together"""
+
+    def __init__(self, host, shared_networks: Callable[[], Dict[str, ipaddress.IPv4Network]]):
+        self.host = host
+        self.shared_networks = shared_networks
+        self.flows: List[Dict] = []
+        self.limit: Optional[int] = None
+        self.dumped_at = 0.0
+
+    async def snapshot(self, top: int = DEFAULT_TOP) -> Dict:
+        """Summary of the current table; raises OSError when it cannot be read"""
+        if time.monotonic() - self.dumped_at > CACHE_SECONDS:
+            self.flows, self.limit = await self.host.flows()
+            self.dumped_at = time.monotonic()
+        summary = summarize(self.flows, self.shared_networks(), self.limit, top)
+        summary["timestamp"] = time.time()
+        return summary
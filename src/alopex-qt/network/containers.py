@@ -0,0 +1,182 @@
+"""
+Containers - Docker and Podman networks behind bridges and veths
+Groups container bridges and the veths plugged into them under the network that
+owns them, asking the engines over their API sockets when they are running
+"""
+
+import re
+import json
+import socket
+import asyncio
+import logging
+from typing import Callable, Dict, List, Optional, Set
+
+# Configure logging
+logger = logging.getLogger(__name__)
+
+DEFAULT_SOCKETS = ["/var/run/docker.sock", "/run/podman/podman.sock"]
+DEFAULT_INTERVAL = 10.0
+API_TIMEOUT = 2.0
+BRIDGE_OPTION = "com.docker.network.bridge.name"
+# Bridges the engines create, recognized even when their socket can't be reached
+BRIDGE_PATTERNS = [
+    (re.compile(r"^docker0$"), "docker", "bridge"),
+    (re.compile(r"^br-[0-9a-f]{12}$"), "docker", None),
+    (re.compile(r"^cni-podman\d+$"), "podman", None),
+    (re.compile(r"^podman\d+$"), "podman", None),
+]
+
+def engine_name(socket_path: str) -> str:
+    return "podman" if "podman" in socket_path else "docker"
+
+def api_get(socket_path: str, path: str, timeout: float = API_TIMEOUT):
+    """GET a Docker-compatible API path over a unix socket and decode the JSON; raises OSError"""
+    with socket.socket(socket.AF_UNIX, socket.SOCK_STREAM) as sock:
+        sock.settimeout(timeout)
+        sock.connect(socket_path)
+        # HTTP/1.0, so the reply is never chunked and ends when the engine closes the connection
+        sock.sendall(f"GET {path} HTTP/1.0\r\nHost: localhost\r\n\r\n".encode())
+        chunks = []
+        while True:
+            chunk = sock.recv(65536)
+            if not chunk:
+                break
+            chunks.append(chunk)
+    head, _, body = b"".join(chunks).partition(b"\r\n\r\n")
+    status = head.split(b"\r\n", 1)[0].split()
+    if len(status) < 2 or status[1] != b"200":
+        raise OSError(f"{socket_path}{path}: {b' '.join(status[1:]).decode(errors='replace') or 'no reply'}")
+    try:
+        return json.loads(body)
+    except ValueError as e:
+        raise OSError(f"{socket_path}{path}: unreadable reply ({e})")
+
+def parse_network(engine: str, network: Dict) -> Optional[Dict]:
+    """One bridge network from a network inspect reply; other drivers have no bridge of ours to show"""
+    if network.get("Driver") != "bridge":
+        return None
+    options = network.get("Options") or {}
+    bridge = options.get(BRIDGE_OPTION) or f"br-{network.get('Id', '')[:12]}"
+    containers = []
+    for endpoint in (network.get("Containers") or {}).values():
+        containers.append({
+            "name": endpoint.get("Name"),
+            "ip": (endpoint.get("IPv4Address") or "").split("/")[0] or None,
+            "mac": endpoint.get("MacAddress") or None
+        })
+    return {
+        "name": network.get("Name"),
+        "engine": engine,
+        "id": (network.get("Id") or "")[:12] or None,
+        "driver": "bridge",
+        "bridge": bridge,
+        "subnets": [c["Subnet"] for c in (network.get("IPAM") or {}).get("Config") or [] if c.get("Subnet")],
+        "containers": sorted(containers, key=lambda c: c["name"] or "")
+    }
+
+def engine_networks(socket_path: str) -> List[Dict]:
+    """Bridge networks of the engine behind a socket, with their containers"""
+    engine = engine_name(socket_path)
+    networks = []
+    # The listing leaves containers out, so each bridge network is inspected too
+    for summary in api_get(socket_path, "/networks"):
+        if summary.get("Driver") == "bridge":
+            parsed = parse_network(engine, api_get(socket_path, f"/networks/{summary['Id']}"))
+            if parsed:
+                networks.append(parsed)
+    return networks
+
+def guess_network(bridge: str) -> Optional[Dict]:
+    """A network for a bridge that looks like an engine's, when no engine told us about it"""
+    for pattern, engine, name in BRIDGE_PATTERNS:
+        if pattern.match(bridge):
+            return {"name": name or bridge, "engine": engine, "id": None, "driver": "bridge",
+                    "bridge": bridge, "subnets": [], "containers": []}
+    return None
+
+def group_interfaces(networks: List[Dict], masters: Dict[str, str], present: List[str]) -> List[Dict]:
+    """Networks whose bridge exists, each with the bridge and the ports plugged into it"""
+    by_bridge = {network["bridge"]: network for network in networks}
+    for bridge in present:
+        if bridge not in by_bridge:
+            guessed = guess_network(bridge)
+            if guessed:
+                by_bridge[bridge] = guessed
+    grouped = []
+    for bridge, network in sorted(by_bridge.items()):
+        if bridge not in present:
+            continue
+        ports = sorted(name for name, master in masters.items() if master == bridge and name in present)
+        grouped.append(dict(network, interfaces=[bridge] + ports))
+    return grouped
+
+def network_label(network: Dict) -> str:
+    """How an owned interface names its network, e.g. "docker/bridge" """
+    return f"{network['engine']}/{network['name']}"
+
+class ContainerNetworks:
+    """Keeps track of which interfaces belong to container networks"""
+
+    def __init__(self, backend, interfaces: Callable[[], List[str]], sockets: Optional[List[str]] = None,
+                 events=None, on_change: Optional[Callable[[Set[str]], None]] = None,
+                 interval: float = DEFAULT_INTERVAL):
+        self.backend = backend
+        self.interfaces = interfaces
+        self.sockets = list(DEFAULT_SOCKETS if sockets is None else sockets)
+        self.events = events
+        self.on_change = on_change
+        self.interval = interval
+        self.networks: List[Dict] = []
+        self.owners: Dict[str, str] = {}
+        # Per socket: "ok", "not running" or why it could not be read
+        self.engines: Dict[str, str] = {}
+
+    async def refresh(self) -> bool:
+        """Ask the engines and sysfs again; returns whether the owned interfaces changed"""
+        reported = []
+        for path in self.sockets:
+            try:
+                reported.extend(await self.backend.networks(path))
+                status = "ok"
+            except FileNotFoundError:
+                status = "not running"
+            except OSError as e:
+                status = str(e) or e.__class__.__name__
+            if status != self.engines.get(path) and status not in ("ok", "not running"):
+                logger.warning(f"Could not list {engine_name(path)} networks: {status}")
+            self.engines[path] = status
+
+        self.networks = group_interfaces(reported, self.backend.masters(), self.interfaces())
+        owners = {name: network_label(network) for network in self.networks for name in network["interfaces"]}
+        changed = owners != self.owners
+        self.owners = owners
+        if changed:
+            if self.on_change:
+                self.on_change(set(owners))
+            if self.events:
+                self.events.publish("ContainerNetworksChanged",
+                                    networks=[network_label(network) for network in self.networks],
+                                    interfaces=len(owners))
+        return changed
+
+    def owner(self, interface: str) -> Optional[str]:
+        return self.owners.get(interface)
+
+    def annotate(self, interfaces):
+        """Fill in the owning network on discovered interfaces"""
+        for interface in interfaces:
+            interface.container_network = self.owner(interface.name)
+
+    def snapshot(self) -> Dict:
+        return {
+            "engines": {engine_name(path): status for path, status in self.engines.items()},
+            "networks": self.networks
+        }
+
+    async def run(self):
+        while True:
+            await asyncio.sleep(self.interval)
+            try:
+                await self.refresh()
+            except Exception as e:
+                logger.error(f"Container network refresh failed: {e}")
@@ -0,0 +1,148 @@
+"""
+Desktop Integration - Connection properties desktops expect from NetworkManager
+Tracks whether the primary connection is metered and, when dbus-next is
+installed and the name is free, exports NetworkManager's Metered, Connectivity
+and State properties on the system bus so browsers and updaters can read them
+"""
+
+import logging
+from typing import Dict, Optional
+
+from .connection_manager import ConnectionManager, ConnectionProfile
+from .state_machine import LinkState
+
+# Configure logging
+logger = logging.getLogger(__name__)
+
+NM_BUS_NAME = "org.freedesktop.NetworkManager"
+NM_OBJECT_PATH = "/org/freedesktop/NetworkManager"
+
+# NMMetered, NMConnectivityState and NMState values
+NM_METERED_UNKNOWN = 0
+NM_METERED_YES = 1
+NM_METERED_NO = 2
+NM_METERED_GUESS_NO = 4
+NM_CONNECTIVITY_NONE = 1
+NM_CONNECTIVITY_FULL = 4
+NM_STATE_DISCONNECTED = 20
+NM_STATE_CONNECTED_GLOBAL = 70
+
+def nm_metered(profile: Optional[ConnectionProfile]) -> int:
+    """NMMetered value for a profile; unmarked wired and WiFi links are guessed unmetered"""
+    if profile is None:
+        return NM_METERED_UNKNOWN
+    if profile.metered is None:
+        return NM_METERED_GUESS_NO
+    return NM_METERED_YES if profile.metered else NM_METERED_NO
+
+def _nm_interface(bridge: "DesktopBridge"):
+    """Build the exported org.freedesktop.NetworkManager interface (needs dbus-next)"""
+    from dbus_next.service import ServiceInterface, PropertyAccess, dbus_property
+
+    class NetworkManagerInterface(ServiceInterface):
+        def __init__(self):
+            super().__init__(NM_BUS_NAME)
+
+        @dbus_property(access=PropertyAccess.READ)
+        def Metered(self) -> 'u':  # noqa: F821 (D-Bus signature)
+            return bridge.state["nm_metered"]
+
+        @dbus_property(access=PropertyAccess.READ)
+        def Connectivity(self) -> 'u':  # noqa: F821 (D-Bus signature)
+            return NM_CONNECTIVITY_FULL if bridge.state["profile"] else NM_CONNECTIVITY_NONE
+
+        @dbus_property(access=PropertyAccess.READ)
+        def State(self) -> 'u':  # noqa: F821 (D-Bus signature)
+            return NM_STATE_CONNECTED_GLOBAL if bridge.state["profile"] else NM_STATE_DISCONNECTED
+
+    return NetworkManagerInterface()
+
+class DesktopBridge:
+    """Recomputes the metered state on every change and announces it"""
+
+    def __init__(self, connections: ConnectionManager, export: bool = True):
+        self.connections = connections
+        self.events = connections.events
+        self.export = export
+        self.nm = None
+        self.state: Dict = {"profile": None, "interface": None, "metered": None, "nm_metered": NM_METERED_UNKNOWN}
+
+    def primary(self) -> Optional[ConnectionProfile]:
+        """The profile carrying traffic: wired over WiFi, then by priority; tunnels ride on it and hotspots share it"""
+        candidates = []
+        for state in self.connections.interface_states.values():
+            profile = self.connections.get_profile(state.profile_name) if state.profile_name else None
+            if state.status == LinkState.CONFIGURED and profile and profile.connection_type not in ("vpn", "hotspot"):
+                candidates.append(profile)
+        if not candidates:
+            return None
+        return max(candidates, key=lambda p: (p.connection_type == "ethernet", p.priority))
+
+    def update(self) -> bool:
+        """Recompute; publish MeteredChanged (and D-Bus PropertiesChanged) if the answer moved"""
+        profile = self.primary()
+        state = {
+            "profile": profile.name if profile else None,
+            "interface": profile.interface if profile else None,
+            "metered": profile.metered if profile else None,
+            "nm_metered": nm_metered(profile)
+        }
+        if state == self.state:
+            return False
+
+        changed_nm = state["nm_metered"] != self.state["nm_metered"]
+        had_profile = self.state["profile"] is not None
+        self.state = state
+        self.events.publish("MeteredChanged", **state)
+        logger.info(f"Primary connection {state['profile'] or 'none'}, metered: {state['metered']}")
+
+        if self.nm:
+            changes = {}
+            if changed_nm:
+                changes["Metered"] = state["nm_metered"]
+            if had_profile != (state["profile"] is not None):
+                changes["Connectivity"] = self.nm.Connectivity
+                changes["State"] = self.nm.State
+            if changes:
+                self.nm.emit_properties_changed(changes)
+        return True
+
+    async def _export(self):
+        """Claim NetworkManager's bus name unless NetworkManager itself (or anyone) holds it"""
+        try:
+            from dbus_next.aio import MessageBus
+            from dbus_next.constants import BusType, NameFlag, RequestNameReply
+        except ImportError:
+            logger.info("dbus-next is not installed; metered state is only published as alopex events")
+            return
+
+        try:
+            bus = await MessageBus(bus_type=BusType.SYSTEM).connect()
+            interface = _nm_interface(self)
+            bus.export(NM_OBJECT_PATH, interface)
+            reply = await bus.request_name(NM_BUS_NAME, NameFlag.DO_NOT_QUEUE)
+        except Exception as e:
+            logger.warning(f"Cannot export metered state on the system bus: {e}")
+            return
+
+        if reply not in (RequestNameReply.PRIMARY_OWNER, RequestNameReply.ALREADY_OWNER):
+            logger.warning(f"{NM_BUS_NAME} is owned by another service; not exporting metered state")
+            bus.disconnect()
+            return
+
+        self.nm = interface
+        logger.info(f"Exporting metered state as {NM_BUS_NAME}")
+
+    async def run(self):
+        """Follow connection and profile changes"""
+        subscription = self.events.subscribe(["InterfaceStateChanged", "ProfileUpdated"])
+        try:
+            if self.export:
+                await self._export()
+            self.update()
+
+            while True:
+                await subscription.get()
+                self.update()
+        finally:
+            self.events.unsubscribe(subscription)
@@ -0,0 +1,360 @@
+"""
+DHCP Server - A small DHCPv4 server for the networks alopexd shares
+Hands out a pool of addresses (plus static reservations) with the router and
+DNS options, keeping leases in a JSON file so clients keep their address
+"""
+
+import os
+import json
+import time
+import socket
+import struct
+import asyncio
+import logging
+import ipaddress
+from dataclasses import dataclass, field, asdict
+from pathlib import Path
+from typing import Dict, List, Optional, Tuple
+
+# Configure logging
+logger = logging.getLogger(__name__)
+
+LEASE_DIR = Path("/var/lib/alopex/dhcp")
+LEASE_TIME = 12 * 3600
+# How long an offered address stays set aside for the client it was offered to
+OFFER_HOLD = 60
+SERVER_PORT = 67
+CLIENT_PORT = 68
+
+BOOTREQUEST, BOOTREPLY = 1, 2
+MAGIC_COOKIE = b"\x63\x82\x53\x63"
+# Fixed BOOTP header: op, htype, hlen, hops, xid, secs, flags, ciaddr, yiaddr, siaddr, giaddr, chaddr, sname, file
+HEADER = struct.Struct("!BBBBIHH4s4s4s4s16s64s128s")
+BROADCAST_FLAG = 0x8000
+
+DISCOVER, OFFER, REQUEST, DECLINE, ACK, NAK, RELEASE, INFORM = range(1, 9)
+MESSAGE_TYPES = {DISCOVER: "DISCOVER", OFFER: "OFFER", REQUEST: "REQUEST", DECLINE: "DECLINE", ACK: "ACK",
+                 NAK: "NAK", RELEASE: "RELEASE", INFORM: "INFORM"}
+
+# Option codes (RFC 2132)
+OPT_PAD = 0
+OPT_SUBNET_MASK = 1
+OPT_ROUTER = 3
+OPT_DNS = 6
+OPT_HOSTNAME = 12
+OPT_REQUESTED_IP = 50
+OPT_LEASE_TIME = 51
+OPT_MESSAGE_TYPE = 53
+OPT_SERVER_ID = 54
+OPT_RENEWAL_TIME = 58
+OPT_REBINDING_TIME = 59
+OPT_END = 255
+
+UNSPECIFIED = "0.0.0.0"
+
+def dhcp_range(network: ipaddress.IPv4Interface) -> Tuple[str, str]:
+    """First and last address handed out; the ten lowest besides the server's stay free for static use.
+    Raises ValueError for a network with no address left over for clients (/31 and /32)"""
+    hosts = [host for host in network.network.hosts() if host != network.ip]
+    if network.network.prefixlen > 30 or not hosts:
+        raise ValueError(f"{network.network} leaves no addresses to hand out; use a /30 or larger network")
+    return str(hosts[min(9, len(hosts) - 1)]), str(hosts[-1])
+
+@dataclass
+class DhcpPacket:
+    """One DHCP message; options are raw bytes by code"""
+    op: int
+    xid: int
+    mac: str
+    ciaddr: str = UNSPECIFIED
+    yiaddr: str = UNSPECIFIED
+    siaddr: str = UNSPECIFIED
+    giaddr: str = UNSPECIFIED
+    flags: int = 0
+    options: Dict[int, bytes] = field(default_factory=dict)
+
+    @property
+    def message_type(self) -> Optional[int]:
+        value = self.options.get(OPT_MESSAGE_TYPE)
+        return value[0] if value else None
+
+    def address_option(self, code: int) -> Optional[str]:
+        value = self.options.get(code)
+        return socket.inet_ntoa(value[:4]) if value and len(value) >= 4 else None
+
+    @property
+    def hostname(self) -> Optional[str]:
+        value = self.options.get(OPT_HOSTNAME, b"").decode(errors="replace").strip("\x00")
+        return value or None
+
+    def encode(self) -> bytes:
+        chaddr = bytes.fromhex(self.mac.replace(":", "")).ljust(16, b"\x00")
+        header = HEADER.pack(self.op, 1, 6, 0, self.xid, 0, self.flags, socket.inet_aton(self.ciaddr),
+                             socket.inet_aton(self.yiaddr), socket.inet_aton(self.siaddr),
+                             socket.inet_aton(self.giaddr), chaddr, b"", b"")
+        options = b"".join(bytes([code, len(value)]) + value for code, value in self.options.items())
+        return header + MAGIC_COOKIE + options + bytes([OPT_END])
+
+    @classmethod
+    def decode(cls, data: bytes) -> "DhcpPacket":
+        """Parse a message; raises ValueError if it isn't a well-formed DHCP one"""
+        if len(data) < HEADER.size + 4 or data[HEADER.size:HEADER.size + 4] != MAGIC_COOKIE:
+            raise ValueError("not a DHCP message")
+        op, htype, hlen, _, xid, _, flags, ciaddr, yiaddr, siaddr, giaddr, chaddr, _, _ = HEADER.unpack_from(data)
+        if htype != 1 or hlen != 6:
+            raise ValueError(f"unsupported hardware type {htype}/{hlen}")
+
+        options: Dict[int, bytes] = {}
+        position = HEADER.size + 4
+        while position < len(data):
+            code = data[position]
+            if code == OPT_END:
+                break
+            if code == OPT_PAD:
+                position += 1
+                continue
+            if position + 1 >= len(data):
+                raise ValueError("truncated option")
+            length = data[position + 1]
+            # Repeated options concatenate (RFC 3396)
+            options[code] = options.get(code, b"") + data[position + 2:position + 2 + length]
+            position += 2 + length
+
+        return cls(op=op, xid=xid, mac=":".join(f"{b:02x}" for b in chaddr[:6]),
+                   ciaddr=socket.inet_ntoa(ciaddr), yiaddr=socket.inet_ntoa(yiaddr), siaddr=socket.inet_ntoa(siaddr),
+                   giaddr=socket.inet_ntoa(giaddr), flags=flags, options=options)
+
+@dataclass
+class Lease:
+    """An address bound to a client"""
+    mac: str
+    ip: str
+    hostname: Optional[str] = None
+    expires: float = 0.0
+    reserved: bool = False
+
+class LeasePool:
+    """The addresses of one subnet: who holds which, and what is still free"""
+
+    def __init__(self, network: ipaddress.IPv4Interface, lease_time: int = LEASE_TIME,
+                 reservations: Optional[Dict[str, str]] = None, lease_file: Optional[Path] = None):
+        self.network = network
+        self.lease_time = lease_time
+        self.reservations = dict(reservations or {})
+        self.lease_file = lease_file
+        first, last = dhcp_range(network)
+        self.first = ipaddress.IPv4Address(first)
+        self.last = ipaddress.IPv4Address(last)
+        self.leases: Dict[str, Lease] = {}
+        # mac -> (address, offered until)
+        self.offers: Dict[str, Tuple[str, float]] = {}
+        # address -> when a client that found it in use stops us handing it out
+        self.declined: Dict[str, float] = {}
+        self._load()
+
+    def _load(self):
+        if not self.lease_file or not self.lease_file.exists():
+            return
+        try:
+            with open(self.lease_file) as f:
+                self.leases = {entry["mac"]: Lease(**entry) for entry in json.load(f).get("leases", [])}
+        except Exception as e:
+            logger.error(f"Failed to load DHCP leases from {self.lease_file}: {e}")
+
+    def _save(self):
+        if not self.lease_file:
+            return
+        try:
+            self.lease_file.parent.mkdir(parents=True, exist_ok=True)
+            temp_path = self.lease_file.with_suffix(".json.tmp")
+            with open(temp_path, 'w') as f:
+                json.dump({"leases": [asdict(lease) for lease in self.leases.values()]}, f, indent=2)
+            os.replace(temp_path, self.lease_file)
+        except Exception as e:
+            logger.error(f"Failed to save DHCP leases to {self.lease_file}: {e}")
+
+    def _usable(self, mac: str, ip: str, now: float) -> bool:
+        """Whether ip may go to mac: in the subnet and nobody else's, by lease, offer or reservation"""
+        try:
+            address = ipaddress.IPv4Address(ip)
+        except ValueError:
+            return False
+        if address not in self.network.network or address in (self.network.ip, self.network.network.network_address,
+                                                               self.network.network.broadcast_address):
+            return False
+        if self.declined.get(ip, 0) > now:
+            return False
+        if any(owner != mac and reserved == ip for owner, reserved in self.reservations.items()):
+            return False
+        if any(lease.mac != mac and lease.ip == ip and lease.expires > now for lease in self.leases.values()):
+            return False
+        return not any(owner != mac and offered == ip and until > now
+                       for owner, (offered, until) in self.offers.items())
+
+    def offer(self, mac: str, requested: Optional[str] = None, now: Optional[float] = None) -> Optional[str]:
+        """The address to offer mac: its reservation, its last one, the one it asks for, or the first free"""
+        now = now if now is not None else time.time()
+        previous = self.leases.get(mac)
+        candidates = [self.reservations.get(mac)]
+        if mac not in self.reservations:
+            candidates += [previous.ip if previous else None, requested]
+            # Never-leased addresses first, then the ones whose lease ran out longest ago
+            expired = {lease.ip: lease.expires for lease in self.leases.values()}
+            pool = (str(ipaddress.IPv4Address(n)) for n in range(int(self.first), int(self.last) + 1))
+            candidates += sorted(pool, key=lambda ip: expired.get(ip, 0))
+        for candidate in candidates:
+            if candidate and self._usable(mac, candidate, now) and (mac in self.reservations
+                                                                    or self._in_pool(candidate)):
+                self.offers[mac] = (candidate, now + OFFER_HOLD)
+                return candidate
+        return None
+
+    def _in_pool(self, ip: str) -> bool:
+        return self.first <= ipaddress.IPv4Address(ip) <= self.last
+
+    def commit(self, mac: str, ip: str, hostname: Optional[str] = None, now: Optional[float] = None) -> Optional[Lease]:
+        """Bind ip to mac if it may have it; None means the client has to start over"""
+        now = now if now is not None else time.time()
+        reserved = self.reservations.get(mac)
+        if not self._usable(mac, ip, now) or ip != (reserved or ip) or not (reserved or self._in_pool(ip)):
+            return None
+        self.offers.pop(mac, None)
+        previous = self.leases.get(mac)
+        lease = Lease(mac=mac, ip=ip, hostname=hostname or (previous.hostname if previous else None),
+                      expires=now + self.lease_time, reserved=bool(reserved))
+        self.leases[mac] = lease
+        self._save()
+        return lease
+
+    def release(self, mac: str, ip: str):
+        lease = self.leases.get(mac)
+        if lease and lease.ip == ip:
+            # Keep the record so the client gets the same address back, but free it now
+            lease.expires = 0.0
+            self._save()
+
+    def decline(self, mac: str, ip: str, now: Optional[float] = None):
+        """The client found ip already in use: set it aside for a lease time"""
+        now = now if now is not None else time.time()
+        self.declined[ip] = now + self.lease_time
+        self.offers.pop(mac, None)
+        self.release(mac, ip)
+
+    def set_reservations(self, reservations: Dict[str, str]):
+        self.reservations = dict(reservations)
+
+    def active(self, now: Optional[float] = None) -> List[Lease]:
+        now = now if now is not None else time.time()
+        return sorted((lease for lease in self.leases.values() if lease.expires > now),
+                      key=lambda lease: ipaddress.IPv4Address(lease.ip))
+
+class DhcpServer:
+    """Answers DHCP requests on one interface from one pool"""
+
+    def __init__(self, interface: str, pool: LeasePool, dns: Optional[List[str]] = None):
+        self.interface = interface
+        self.pool = pool
+        self.server_ip = str(pool.network.ip)
+        self.dns = list(dns or [])
+        self.transport = None
+
+    def handle(self, request: DhcpPacket, now: Optional[float] = None) -> Optional[DhcpPacket]:
+        """The reply to request, if it gets one"""
+        kind = request.message_type
+        if request.op != BOOTREQUEST or kind is None:
+            return None
+        mac = request.mac
+        logger.debug(f"DHCP{MESSAGE_TYPES.get(kind, kind)} from {mac} on {self.interface}")
+
+        if kind == DISCOVER:
+            ip = self.pool.offer(mac, request.address_option(OPT_REQUESTED_IP), now)
+            if not ip:
+                logger.warning(f"DHCP pool on {self.interface} is exhausted; no address for {mac}")
+                return None
+            return self._reply(request, OFFER, ip)
+
+        if kind == REQUEST:
+            server_id = request.address_option(OPT_SERVER_ID)
+            if server_id and server_id != self.server_ip:
+                # The client took another server's offer
+                self.pool.offers.pop(mac, None)
+                return None
+            ip = request.address_option(OPT_REQUESTED_IP) or request.ciaddr
+            lease = self.pool.commit(mac, ip, request.hostname, now) if ip != UNSPECIFIED else None
+            if not lease:
+                logger.info(f"DHCPNAK {ip} to {mac} on {self.interface}")
+                return self._reply(request, NAK, UNSPECIFIED)
+            logger.info(f"DHCPACK {ip} to {mac} ({lease.hostname or 'no hostname'}) on {self.interface}")
+            return self._reply(request, ACK, ip)
+
+        if kind == RELEASE:
+            self.pool.release(mac, request.ciaddr)
+        elif kind == DECLINE:
+            declined = request.address_option(OPT_REQUESTED_IP)
+            if declined:
+                logger.warning(f"{mac} says {declined} is already in use on {self.interface}")
+                self.pool.decline(mac, declined, now)
+        elif kind == INFORM:
+            # Already configured; it only wants the options
+            return self._reply(request, ACK, UNSPECIFIED)
+        return None
+
+    def _reply(self, request: DhcpPacket, kind: int, ip: str) -> DhcpPacket:
+        options = {OPT_MESSAGE_TYPE: bytes([kind]), OPT_SERVER_ID: socket.inet_aton(self.server_ip)}
+        if kind != NAK:
+            options[OPT_SUBNET_MASK] = socket.inet_aton(str(self.pool.network.netmask))
+            options[OPT_ROUTER] = socket.inet_aton(self.server_ip)
+            if self.dns:
+                options[OPT_DNS] = b"".join(socket.inet_aton(server) for server in self.dns)
+        if kind in (OFFER, ACK) and ip != UNSPECIFIED:
+            lease_time = self.pool.lease_time
+            options[OPT_LEASE_TIME] = struct.pack("!I", lease_time)
+            options[OPT_RENEWAL_TIME] = struct.pack("!I", lease_time // 2)
+            options[OPT_REBINDING_TIME] = struct.pack("!I", lease_time * 7 // 8)
+        return DhcpPacket(op=BOOTREPLY, xid=request.xid, mac=request.mac, yiaddr=ip, siaddr=self.server_ip,
+                          giaddr=request.giaddr, flags=request.flags, options=options)
+
+    @staticmethod
+    def destination(request: DhcpPacket, reply: DhcpPacket) -> Tuple[str, int]:
+        """Where a reply goes (RFC 2131 4.1): the relay, the client's address, or broadcast"""
+        if request.giaddr != UNSPECIFIED:
+            return request.giaddr, SERVER_PORT
+        if request.ciaddr != UNSPECIFIED and reply.message_type != NAK:
+            return request.ciaddr, CLIENT_PORT
+        # Without an address (or ARP entry) the client can only hear broadcasts
+        return "255.255.255.255", CLIENT_PORT
+
+    def datagram_received(self, data: bytes, addr):
+        try:
+            request = DhcpPacket.decode(data)
+        except ValueError as e:
+            logger.debug(f"Ignoring packet from {addr} on {self.interface}: {e}")
+            return
+        reply = self.handle(request)
+        if reply and self.transport:
+            self.transport.sendto(reply.encode(), self.destination(request, reply))
+
+    async def serve(self):
+        """Listen on port 67 of this interface only (needs root)"""
+        sock = socket.socket(socket.AF_INET, socket.SOCK_DGRAM)
+        try:
+            sock.setsockopt(socket.SOL_SOCKET, socket.SO_REUSEADDR, 1)
+            sock.setsockopt(socket.SOL_SOCKET, socket.SO_BROADCAST, 1)
+            sock.setsockopt(socket.SOL_SOCKET, socket.SO_BINDTODEVICE, self.interface.encode())
+            sock.bind(("0.0.0.0", SERVER_PORT))
+        except OSError:
+            sock.close()
+            raise
+        server = self
+
+        class Protocol(asyncio.DatagramProtocol):
+            def datagram_received(self, data, addr):
+                server.datagram_received(data, addr)
+
+        self.transport, _ = await asyncio.get_running_loop().create_datagram_endpoint(Protocol, sock=sock)
+        logger.info(f"DHCP server on {self.interface}: {self.pool.first}-{self.pool.last}")
+
+    def close(self):
+        if self.transport:
+            self.transport.close()
+            self.transport = None
@@ -0,0 +1,91 @@
+"""
+Diagnostics - Support snapshots of the daemon and the network it manages
+Keeps a short history of traffic counters, scrubs secrets from configuration,
+and writes GetDiagnostics results as JSON or a tarball to attach to tickets
+"""
+
+import io
+import os
+import re
+import json
+import time
+import tarfile
+from collections import deque
+from pathlib import Path
+from typing import Any, Dict, Iterable, List, Optional
+
+from .discovery import NetworkInterface
+from .secret import REDACTED, Secret
+
+# Keys whose values never leave the machine in a snapshot
+SECRET_KEY = re.compile(r"pass|secret|psk|token|private|(^|_)key$", re.IGNORECASE)
+
+DEFAULT_SAMPLES = 120  # one per monitor pass (5 s): the last ten minutes
+
+def redact(data: Any) -> Any:
+    """Copy of data with secret-looking values replaced"""
+    if isinstance(data, dict):
+        return {
+            key: REDACTED if SECRET_KEY.search(str(key)) and value not in (None, "") else redact(value)
+            for key, value in data.items()
+        }
+    if isinstance(data, list):
+        return [redact(item) for item in data]
+    return REDACTED if isinstance(data, Secret) else data
+
+class MetricsHistory:
+    """Ring buffer of per-interface counter samples"""
+
+    def __init__(self, size: int = DEFAULT_SAMPLES):
+        self.samples = deque(maxlen=size)
+
+    def record(self, interfaces: Iterable[NetworkInterface]):
+        sample = {"timestamp": time.time(), "interfaces": {}}
+        for interface in interfaces:
+            metrics = interface.metrics
+            sample["interfaces"][interface.name] = {
+                "status": interface.status,
+                "rx_bytes": metrics.bytes_rx, "tx_bytes": metrics.bytes_tx,
+                "rx_packets": metrics.packets_rx, "tx_packets": metrics.packets_tx,
+                "rx_errors": metrics.errors_rx, "tx_errors": metrics.errors_tx,
+                "rx_dropped": metrics.dropped_rx, "tx_dropped": metrics.dropped_tx
+            }
+        self.samples.append(sample)
+
+    def recent(self, limit: Optional[int] = None) -> List[Dict]:
+        samples = list(self.samples)
+        return samples[-limit:] if limit else samples
+
+def _format_logs(records: List[Dict]) -> str:
+    lines = []
+    for record in records:
+        stamp = time.strftime("%Y-%m-%d %H:%M:%S", time.localtime(record["timestamp"]))
+        lines.append(f"{stamp} [{record['level']}] {record['target']}: {record['message']}")
+    return "\n".join(lines) + "\n"
+
+def write_snapshot(snapshot: Dict, path: Path) -> Path:
+    """Write a snapshot as JSON, or as a tarball when path ends in .tar, .tar.gz or .tgz"""
+    name = path.name
+    # Addresses and topology are nobody else's business either
+    fd = os.open(path, os.O_WRONLY | os.O_CREAT | os.O_TRUNC, 0o600)
+    with os.fdopen(fd, "wb") as f:
+        if not name.endswith((".tar", ".tar.gz", ".tgz")):
+            f.write(json.dumps(snapshot, indent=2).encode())
+            return path
+
+        mode = "w" if name.endswith(".tar") else "w:gz"
+        folder = f"alopex-snapshot-{time.strftime('%Y%m%d-%H%M%S', time.localtime(snapshot['generated_at']))}"
+        files = {"snapshot.json": json.dumps(snapshot, indent=2)}
+        files.update({f"{section}.json": json.dumps(snapshot[section], indent=2)
+                      for section in ("interfaces", "routes", "events", "metrics", "config")})
+        files["daemon.log"] = _format_logs(snapshot.get("logs", []))
+
+        with tarfile.open(fileobj=f, mode=mode) as tar:
+            for file_name, text in files.items():
+                data = text.encode()
+                info = tarfile.TarInfo(f"{folder}/{file_name}")
+                info.size = len(data)
+                info.mtime = int(snapshot["generated_at"])
+                info.mode = 0o600
+                tar.addfile(info, io.BytesIO(data))
+    return path
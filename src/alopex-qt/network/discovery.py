@@ -4,12 +4,76 @@ Port of our Rust network logic to Python
 """
 
 import os
+import json
 import subprocess
 import time
+from enum import Enum
 from typing import Dict, List, Optional, Tuple
-from dataclasses import dataclass
+from dataclasses import dataclass, fields
 from pathlib import Path
 
+from .rfkill import BLOCK_STATUS, interface_block
+
+class InterfaceType(str, Enum):
+    """Kind of network interface, in display and sort order"""
+    ETHERNET = "Ethernet"
+    WIFI = "WiFi"
+    CELLULAR = "Cellular"
+    VPN = "VPN"
+    TUN = "Tun"
+    BRIDGE = "Bridge"
+    BOND = "Bond"
+    VLAN = "Vlan"
+    LOOPBACK = "Loopback"
+    OTHER = "Other"  # NetworkInterface.type_detail names it when known
+
+    def __str__(self) -> str:
+        return self.value
+
+def parse_interface_type(value: str) -> InterfaceType:
+    """Read a type name, mapping "Unknown" (older daemons, mock scenarios) to Other"""
+    try:
+        return InterfaceType(value)
+    except ValueError:
+        return InterfaceType.OTHER
+
+SYS_CLASS_NET = Path("/sys/class/net")
+
+# ARPHRD_* link types from /sys/class/net/<if>/type
+ARPHRD_ETHER = "1"
+ARPHRD_PPP = "512"
+ARPHRD_RAWIP = "519"
+ARPHRD_LOOPBACK = "772"
+
+# addr_assign_type of an address that came from the hardware
+NET_ADDR_PERM = "0"
+
+# DEVTYPE= in /sys/class/net/<if>/uevent
+DEVTYPE_TYPES = {
+    "wlan": InterfaceType.WIFI,
+    "wwan": InterfaceType.CELLULAR,
+    "ppp": InterfaceType.CELLULAR,
+    "wireguard": InterfaceType.VPN,
+    "bridge": InterfaceType.BRIDGE,
+    "bond": InterfaceType.BOND,
+    "vlan": InterfaceType.VLAN,
+}
+
+# USB modem and phone tethering drivers (basename of device/driver)
+CELLULAR_DRIVERS = {"qmi_wwan", "cdc_mbim", "huawei_cdc_ncm", "sierra_net", "option", "GobiNet"}
+TETHERING_DRIVERS = {"rndis_host", "cdc_ether", "ipheth"}
+
+def _read_sysfs(path: Path) -> Optional[str]:
+    try:
+        return path.read_text().strip()
+    except OSError:
+        return None
+
+def _read_uevent(device: Path) -> Dict[str, str]:
+    """KEY=value pairs of a device's uevent file"""
+    text = _read_sysfs(device / "uevent") or ""
+    return dict(line.split("=", 1) for line in text.splitlines() if "=" in line)
+
 @dataclass
 class NetworkMetrics:
     """Comprehensive network metrics"""
@@ -29,23 +93,61 @@ class NetworkMetrics:
     duplex: Optional[str] = None
     mtu: Optional[int] = None
     uptime: Optional[float] = None
+    # Filled in by the daemon: bytes since the current connection came up
+    total_session_tx: int = 0
+    total_session_rx: int = 0
+    session_started: Optional[float] = None
+    max_link_speed: Optional[int] = None  # Mbps, the fastest mode the NIC supports
+    link_warning: Optional[str] = None  # set while a wired link negotiated below what it can do
 
 @dataclass 
 class NetworkInterface:
     """Network interface representation"""
     name: str
-    interface_type: str
+    interface_type: InterfaceType
     status: str
     ip: Optional[str] = None
     gateway: Optional[str] = None
     dns: List[str] = None
     metrics: NetworkMetrics = None
+    managed: bool = True
+    type_detail: Optional[str] = None  # e.g. "veth" for Other, "tap" for Tun, "tethered (ipheth)"
+    mac: Optional[str] = None  # current address
+    permanent_mac: Optional[str] = None  # burned-in; differs from mac when spoofed or randomized
+    rfkill: Optional[str] = None  # "soft" or "hard" while the radio is blocked
+    label: Optional[str] = None  # friendly name given through the daemon, e.g. "WAN"
+    groups: List[str] = None
+    container_network: Optional[str] = None  # owning Docker/Podman network, e.g. "docker/bridge"
+    virtual_network: Optional[str] = None  # libvirt network behind a virbr bridge, e.g. "default"
     
     def __post_init__(self):
         if self.dns is None:
             self.dns = []
+        if self.groups is None:
+            self.groups = []
         if self.metrics is None:
             self.metrics = NetworkMetrics()
+    
+    @property
+    def status_text(self) -> str:
+        """Status for display, naming the kill switch when that is what keeps the link down"""
+        return BLOCK_STATUS.get(self.rfkill, self.status)
+    
+    @classmethod
+    def from_dict(cls, entry: Dict) -> "NetworkInterface":
+        """Rebuild an interface from a GetInterfaces entry, ignoring keys this version doesn't know"""
+        known = {f.name for f in fields(cls)}
+        data = {key: value for key, value in entry.items() if key in known}
+        data["interface_type"] = parse_interface_type(data["interface_type"])
+        metric_fields = {f.name for f in fields(NetworkMetrics)}
+        data["metrics"] = NetworkMetrics(**{key: value for key, value in (entry.get("metrics") or {}).items()
+                                            if key in metric_fields})
+        return cls(**data)
+    
+    @property
+    def display_name(self) -> str:
+        """Label with the kernel name beside it, e.g. "WAN (enp3s0)", or just the kernel name"""
+        return f"{self.label} ({self.name})" if self.label and self.label != self.name else self.name
 
 class NetworkDiscovery:
     """Network interface discovery and monitoring"""
@@ -58,7 +160,7 @@ class NetworkDiscovery:
     def discover_interfaces() -> List[NetworkInterface]:
         """Discover all network interfaces"""
         interfaces = []
-        net_path = Path("/sys/class/net")
+        net_path = SYS_CLASS_NET
         
         if not net_path.exists():
             return interfaces
@@ -74,21 +176,23 @@ class NetworkDiscovery:
         return interfaces
     
     @staticmethod
-    def _type_priority(interface_type: str) -> int:
+    def _type_priority(interface_type: InterfaceType) -> int:
         """Get sorting priority for interface type"""
-        priorities = {"Ethernet": 0, "WiFi": 1, "VPN": 2}
-        return priorities.get(interface_type, 3)
+        return list(InterfaceType).index(parse_interface_type(interface_type))
     
     @staticmethod
     def _get_interface_info(name: str) -> Optional[NetworkInterface]:
         """Get detailed information for a network interface"""
         try:
-            interface_type = NetworkDiscovery._detect_interface_type(name)
+            interface_type, type_detail = NetworkDiscovery._detect_interface_type(name)
             status = NetworkDiscovery._get_interface_status(name)
             ip = NetworkDiscovery._get_interface_ip(name)
             gateway = NetworkDiscovery._get_default_gateway()
             dns = NetworkDiscovery._get_dns_servers()
             metrics = NetworkDiscovery._get_interface_metrics(name)
+            mac = NetworkDiscovery._get_mac(name)
+            permanent_mac = NetworkDiscovery._get_permanent_mac(name, mac)
+            rfkill = interface_block(name) if interface_type == InterfaceType.WIFI else None
             
             return NetworkInterface(
                 name=name,
@@ -97,23 +201,196 @@ class NetworkDiscovery:
                 ip=ip,
                 gateway=gateway,
                 dns=dns,
-                metrics=metrics
+                metrics=metrics,
+                type_detail=type_detail,
+                mac=mac,
+                permanent_mac=permanent_mac,
+                rfkill=rfkill
             )
         except Exception as e:
             print(f"Error getting info for {name}: {e}")
             return None
     
     @staticmethod
-    def _detect_interface_type(name: str) -> str:
-        """Detect interface type from name and sysfs"""
-        if name.startswith(("eth", "en")):
-            return "Ethernet"
+    def _detect_interface_type(name: str, net_path: Path = SYS_CLASS_NET) -> Tuple[InterfaceType, Optional[str]]:
+        """Detect interface type (and detail) from sysfs; the name is only a last resort"""
+        device = net_path / name
+        arp_type = _read_sysfs(device / "type")
+        if arp_type is None:
+            return NetworkDiscovery._type_from_name(name)
+
+        if arp_type == ARPHRD_LOOPBACK:
+            return InterfaceType.LOOPBACK, None
+
+        devtype = _read_uevent(device).get("DEVTYPE")
+        if devtype in DEVTYPE_TYPES:
+            return DEVTYPE_TYPES[devtype], None
+        if (device / "wireless").is_dir() or (device / "phy80211").exists():
+            return InterfaceType.WIFI, None
+        if (device / "bridge").is_dir():
+            return InterfaceType.BRIDGE, None
+        if (device / "bonding").is_dir():
+            return InterfaceType.BOND, None
+        if (device / "tun_flags").exists():
+            return InterfaceType.TUN, "tap" if arp_type == ARPHRD_ETHER else "tun"
+
+        driver_link = device / "device" / "driver"
+        if driver_link.exists():
+            driver = os.path.basename(os.path.realpath(driver_link))
+            if driver in CELLULAR_DRIVERS:
+                return InterfaceType.CELLULAR, driver
+            if driver in TETHERING_DRIVERS:
+                return InterfaceType.ETHERNET, f"tethered ({driver})"
+
+        if arp_type in (ARPHRD_PPP, ARPHRD_RAWIP):
+            return InterfaceType.CELLULAR, None
+        if arp_type != ARPHRD_ETHER:
+            return InterfaceType.OTHER, devtype
+        if (device / "device").exists():
+            return InterfaceType.ETHERNET, None
+
+        # Virtual Ethernet-like device: the link kind is its driver
+        kind = devtype or NetworkDiscovery._link_kind(name)
+        return InterfaceType.OTHER, kind
+
+    @staticmethod
+    def _ip_details(name: str) -> Dict:
+        """Everything `ip -d -j addr` reports for one link, addresses included"""
+        try:
+            result = subprocess.run(
+                ["ip", "-d", "-j", "addr", "show", "dev", name],
+                capture_output=True, text=True, check=True
+            )
+            links = json.loads(result.stdout)
+            return links[0] if links else {}
+        except Exception:
+            return {}
+
+    @staticmethod
+    def _link_kind(name: str) -> Optional[str]:
+        """rtnetlink kind of a virtual link (veth, dummy, macvlan, ...)"""
+        return NetworkDiscovery._ip_details(name).get("linkinfo", {}).get("info_kind")
+
+    @staticmethod
+    def _get_mac(name: str, net_path: Path = SYS_CLASS_NET) -> Optional[str]:
+        """Current hardware address, None for links without one (tun, wireguard)"""
+        address = _read_sysfs(net_path / name / "address")
+        if not address or address.strip("0:") == "":
+            return None
+        return address.lower()
+
+    @staticmethod
+    def _get_permanent_mac(name: str, mac: Optional[str], net_path: Path = SYS_CLASS_NET) -> Optional[str]:
+        """Burned-in address; ip reports permaddr only when it differs from the current one"""
+        if mac is None:
+            return None
+        permaddr = NetworkDiscovery._ip_details(name).get("permaddr")
+        if permaddr:
+            return permaddr.lower()
+        if _read_sysfs(net_path / name / "addr_assign_type") == NET_ADDR_PERM:
+            return mac
+        return None
+
+    @staticmethod
+    def _type_from_name(name: str) -> Tuple[InterfaceType, Optional[str]]:
+        """Guess from common naming schemes when sysfs is unavailable"""
+        if name == "lo":
+            return InterfaceType.LOOPBACK, None
+        elif "." in name:
+            return InterfaceType.VLAN, None
+        elif name.startswith(("br", "virbr", "docker")):
+            return InterfaceType.BRIDGE, None
+        elif name.startswith("bond"):
+            return InterfaceType.BOND, None
+        elif name.startswith(("eth", "en")):
+            return InterfaceType.ETHERNET, None
         elif name.startswith(("wlan", "wl")):
-            return "WiFi"
-        elif name.startswith(("tun", "wg")):
-            return "VPN"
+            return InterfaceType.WIFI, None
+        elif name.startswith(("wwan", "ppp")):
+            return InterfaceType.CELLULAR, None
+        elif name.startswith("wg"):
+            return InterfaceType.VPN, None
+        elif name.startswith(("tun", "tap")):
+            return InterfaceType.TUN, None
+        elif name.startswith(("veth", "dummy")):
+            return InterfaceType.OTHER, name.rstrip("0123456789")
         else:
-            return "Unknown"
+            return InterfaceType.OTHER, None
+    
+    @staticmethod
+    def get_routes(interface: Optional[str] = None) -> List[Dict]:
+        """IPv4 and IPv6 routes of the main table (as `ip -j route` reports them), optionally for one device"""
+        routes = []
+        for family in ("-4", "-6"):
+            command = ["ip", "-j", family, "route", "show"] + (["dev", interface] if interface else [])
+            try:
+                result = subprocess.run(command, capture_output=True, text=True, check=True)
+                routes.extend(json.loads(result.stdout or "[]"))
+            except Exception:
+                pass
+        return routes
+
+    @staticmethod
+    def describe_interface(interface: NetworkInterface, net_path: Path = SYS_CLASS_NET) -> Dict:
+        """Everything known about an interface, as plain JSON-ready data for bug reports"""
+        name = interface.name
+        details = NetworkDiscovery._ip_details(name)
+        device = net_path / name
+
+        driver_link = device / "device" / "driver"
+        driver = os.path.basename(os.path.realpath(driver_link)) if driver_link.exists() else None
+
+        addresses = [
+            {
+                "address": f"{info['local']}/{info['prefixlen']}",
+                "family": "ipv6" if info.get("family") == "inet6" else "ipv4",
+                "scope": info.get("scope"),
+                "dynamic": info.get("dynamic", False),
+                "valid_lifetime": info.get("valid_life_time")
+            }
+            for info in details.get("addr_info", []) if "local" in info
+        ]
+
+        statistics = {}
+        stats_dir = device / "statistics"
+        if stats_dir.is_dir():
+            for stat in sorted(stats_dir.iterdir()):
+                value = _read_sysfs(stat)
+                if value and value.isdigit():
+                    statistics[stat.name] = int(value)
+
+        return {
+            "name": name,
+            "type": str(interface.interface_type),
+            "type_detail": interface.type_detail,
+            "status": interface.status,
+            "operstate": details.get("operstate"),
+            "flags": details.get("flags", []),
+            "managed": interface.managed,
+            "container_network": interface.container_network,
+            "virtual_network": interface.virtual_network,
+            "mac": interface.mac,
+            "permanent_mac": interface.permanent_mac,
+            "driver": driver,
+            "kind": details.get("linkinfo", {}).get("info_kind"),
+            "bus": details.get("parentbus"),
+            "mtu": details.get("mtu", interface.metrics.mtu),
+            "link_speed": interface.metrics.link_speed,
+            "duplex": interface.metrics.duplex,
+            "qdisc": details.get("qdisc"),
+            "queues": {"tx": details.get("num_tx_queues"), "rx": details.get("num_rx_queues"),
+                       "txqlen": details.get("txqlen")},
+            "addresses": addresses,
+            "gateway": interface.gateway,
+            "routes": NetworkDiscovery.get_routes(name),
+            "dns": list(interface.dns),
+            "statistics": statistics or {
+                "rx_bytes": interface.metrics.bytes_rx, "tx_bytes": interface.metrics.bytes_tx,
+                "rx_packets": interface.metrics.packets_rx, "tx_packets": interface.metrics.packets_tx,
+                "rx_errors": interface.metrics.errors_rx, "tx_errors": interface.metrics.errors_tx,
+                "rx_dropped": interface.metrics.dropped_rx, "tx_dropped": interface.metrics.dropped_tx
+            }
+        }
     
     @staticmethod
     def _get_interface_status(name: str) -> str:
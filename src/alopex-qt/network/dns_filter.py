@@ -0,0 +1,142 @@
+"""
+DNS Filter - Blocklists for the DNS forwarder
+Loads hosts-format lists (ads, malware), answers NXDOMAIN for the names on them
+and counts what each client had blocked; filtering can be switched per firewall zone
+"""
+
+import os
+import json
+import time
+import logging
+from collections import Counter
+from pathlib import Path
+from typing import Awaitable, Callable, Dict, List, Optional, Set, Tuple
+
+# Configure logging
+logger = logging.getLogger(__name__)
+
+# How long a client's zone is trusted before asking again
+ZONE_TTL = 30.0
+TOP_NAMES = 10
+# Hosts files map these to the loopback for their own sake, not to block them
+LOCAL_NAMES = {"localhost", "localhost.localdomain", "local", "broadcasthost", "ip6-localhost", "ip6-loopback",
+               "0.0.0.0"}
+
+def _normalize(name: str) -> str:
+    return name.strip().rstrip(".").lower()
+
+def parse_hosts(text: str) -> Set[str]:
+    """Names from a hosts-format list ("0.0.0.0 ads.example.com"); bare domain lists are read too"""
+    names = set()
+    for line in text.splitlines():
+        fields = line.split("#", 1)[0].split()
+        if not fields:
+            continue
+        # "address name [name...]", or just "name"
+        for name in fields[1:] if len(fields) > 1 else fields:
+            name = _normalize(name)
+            if name and name not in LOCAL_NAMES and "." in name:
+                names.add(name)
+    return names
+
+class DnsFilter:
+    """Blocked names from the lists, and which zones they apply in"""
+
+    def __init__(self, lists: List[str], state_path: Path, zones: Optional[Dict[str, bool]] = None,
+                 default: bool = True, allow: Optional[List[str]] = None,
+                 zone_of: Optional[Callable[[str], Awaitable[Optional[str]]]] = None, events=None):
+        self.lists = list(lists)
+        self.state_file = state_path / "dns_filter.json"
+        self.zones: Dict[str, bool] = dict(zones or {})
+        self.default = default
+        self.allow = {_normalize(name) for name in allow or []}
+        self.zone_of = zone_of
+        self.events = events
+        self.names: Set[str] = set()
+        self.loaded: List[Dict] = []
+        self.blocked = 0
+        self.clients: Dict[str, Counter] = {}
+        self._zones: Dict[str, Tuple[float, Optional[str]]] = {}
+        self._load_state()
+
+    def _load_state(self):
+        """Zones switched over IPC override the config's"""
+        if not self.state_file.exists():
+            return
+        try:
+            with open(self.state_file) as f:
+                self.zones.update(json.load(f).get("zones", {}))
+        except Exception as e:
+            logger.error(f"Failed to load DNS filter state: {e}")
+
+    def _save_state(self):
+        """Save zone switches via a temp file"""
+        self.state_file.parent.mkdir(parents=True, exist_ok=True)
+        temp_file = self.state_file.with_suffix(".tmp")
+        with open(temp_file, "w") as f:
+            json.dump({"zones": self.zones}, f, indent=2)
+        os.replace(temp_file, self.state_file)
+
+    def load(self) -> int:
+        """Read every list again; returns how many names are blocked"""
+        names = set()
+        self.loaded = []
+        for path in self.lists:
+            try:
+                listed = parse_hosts(Path(path).read_text(errors="replace"))
+                self.loaded.append({"path": path, "names": len(listed), "error": None})
+                names |= listed
+            except OSError as e:
+                logger.warning(f"Cannot read blocklist {path}: {e}")
+                self.loaded.append({"path": path, "names": 0, "error": e.strerror or str(e)})
+        self.names = names - self.allow
+        logger.info(f"DNS filter blocks {len(self.names)} names from {len(self.lists)} lists")
+        if self.events:
+            self.events.publish("DnsFilterLoaded", names=len(self.names), lists=len(self.lists))
+        return len(self.names)
+
+    def set_zone(self, zone: str, enabled: bool):
+        self.zones[zone] = enabled
+        self._zones.clear()
+        self._save_state()
+
+    def applies(self, zone: Optional[str]) -> bool:
+        return self.zones.get(zone, self.default) if zone else self.default
+
+    async def _client_zone(self, client: str) -> Optional[str]:
+        cached = self._zones.get(client)
+        if cached and time.monotonic() - cached[0] < ZONE_TTL:
+            return cached[1]
+        zone = await self.zone_of(client) if self.zone_of else None
+        self._zones[client] = (time.monotonic(), zone)
+        return zone
+
+    async def blocks(self, client: Optional[str], name: str) -> bool:
+        """Whether to refuse a client's query for name, counting it if so"""
+        name = _normalize(name)
+        if name not in self.names:
+            return False
+        # Without per-zone switches there is no need to find the zone
+        if self.zones and client:
+            if not self.applies(await self._client_zone(client)):
+                return False
+        elif not self.default:
+            return False
+        self.blocked += 1
+        self.clients.setdefault(client or "local", Counter())[name] += 1
+        return True
+
+    def stats(self) -> Dict:
+        return {
+            "enabled": True,
+            "names": len(self.names),
+            "lists": self.loaded,
+            "allow": sorted(self.allow),
+            "zones": dict(sorted(self.zones.items())),
+            "default": self.default,
+            "blocked": self.blocked,
+            "clients": [
+                {"client": client, "blocked": sum(names.values()), "top": names.most_common(TOP_NAMES)}
+                for client, names in sorted(self.clients.items(), key=lambda item: -sum(item[1].values()))
+            ]
+        }
@@ -0,0 +1,367 @@
+"""
+DNS Forwarder - A caching stub resolver for systems without systemd-resolved
+Listens on 127.0.0.53 (and on the address of each running hotspot), answers
+from a cache and forwards misses to the resolvers of the links that are up;
+names on the blocklists of an optional filter get NXDOMAIN
+"""
+
+import time
+import random
+import struct
+import asyncio
+import logging
+import ipaddress
+from collections import OrderedDict
+from typing import Callable, Dict, List, Optional, Tuple
+
+# Configure logging
+logger = logging.getLogger(__name__)
+
+DEFAULT_LISTEN = ["127.0.0.53"]
+DEFAULT_CACHE_SIZE = 1000
+UPSTREAM_TIMEOUT = 2.0
+WATCH_INTERVAL = 5.0
+
+# Answers are kept no longer than this, whatever their TTL says
+MAX_TTL = 3600
+# NXDOMAIN and empty answers without an SOA to take a TTL from
+NEGATIVE_TTL = 60
+
+RCODE_SERVFAIL = 2
+RCODE_NXDOMAIN = 3
+RTYPE_OPT = 41
+FLAG_TC = 0x0200
+
+def _skip_name(message: bytes, offset: int) -> int:
+    """Offset just past the (possibly compressed) name starting at offset"""
+    while True:
+        length = message[offset]
+        if length & 0xC0 == 0xC0:
+            return offset + 2
+        offset += 1 + length
+        if length == 0:
+            return offset
+
+def question(message: bytes) -> Optional[Tuple[str, int, int]]:
+    """(lower-cased name, qtype, qclass) of a message's single question, None if it has no usable one"""
+    try:
+        if struct.unpack("!H", message[4:6])[0] != 1:
+            return None
+        labels, offset = [], 12
+        while message[offset]:
+            length = message[offset]
+            if length & 0xC0:
+                return None
+            labels.append(message[offset + 1:offset + 1 + length].decode("ascii", "replace").lower())
+            offset += 1 + length
+        qtype, qclass = struct.unpack("!HH", message[offset + 1:offset + 5])
+        return ".".join(labels) + ".", qtype, qclass
+    except (IndexError, struct.error):
+        return None
+
+def _records(message: bytes) -> List[Tuple[int, int]]:
+    """(offset of the TTL field, record type) of every resource record after the question"""
+    qdcount, ancount, nscount, arcount = struct.unpack("!HHHH", message[4:12])
+    offset = 12
+    for _ in range(qdcount):
+        offset = _skip_name(message, offset) + 4
+    records = []
+    for _ in range(ancount + nscount + arcount):
+        offset = _skip_name(message, offset)
+        rtype, _, _, rdlength = struct.unpack("!HHIH", message[offset:offset + 10])
+        records.append((offset + 4, rtype))
+        offset += 10 + rdlength
+    return records
+
+def answer_ttl(message: bytes) -> Optional[int]:
+    """How long a response may be cached: the lowest TTL among its records, None if it must not be"""
+    try:
+        rcode = message[3] & 0x0F
+        if rcode not in (0, 3) or struct.unpack("!H", message[2:4])[0] & FLAG_TC:
+            return None
+        # The OPT pseudo-record's "TTL" carries EDNS flags
+        ttls = [struct.unpack("!I", message[offset:offset + 4])[0]
+                for offset, rtype in _records(message) if rtype != RTYPE_OPT]
+        if struct.unpack("!H", message[6:8])[0] == 0 or rcode == 3:
+            return min(ttls + [NEGATIVE_TTL])
+        return min(ttls + [MAX_TTL])
+    except (IndexError, struct.error):
+        return None
+
+def age_ttls(message: bytes, seconds: int) -> bytes:
+    """message with every TTL lowered by seconds, so cached answers expire downstream on time too"""
+    aged = bytearray(message)
+    for offset, rtype in _records(message):
+        if rtype != RTYPE_OPT:
+            ttl = struct.unpack("!I", message[offset:offset + 4])[0]
+            aged[offset:offset + 4] = struct.pack("!I", max(ttl - seconds, 0))
+    return bytes(aged)
+
+def _error_reply(query: bytes, rcode: int) -> bytes:
+    """An empty reply with rcode, echoing query's ID, recursion flag and question"""
+    flags = 0x8000 | (struct.unpack("!H", query[2:4])[0] & 0x0100) | 0x0080 | rcode
+    end = _skip_name(query, 12) + 4
+    return query[:2] + struct.pack("!HHHHH", flags, 1, 0, 0, 0) + query[12:end]
+
+def servfail(query: bytes) -> bytes:
+    return _error_reply(query, RCODE_SERVFAIL)
+
+def nxdomain(query: bytes) -> bytes:
+    return _error_reply(query, RCODE_NXDOMAIN)
+
+class DnsCache:
+    """Responses by question, least recently used dropped first; stored without their ID"""
+
+    def __init__(self, size: int = DEFAULT_CACHE_SIZE):
+        self.size = size
+        self.entries: "OrderedDict[Tuple[str, int, int], Tuple[float, float, bytes]]" = OrderedDict()
+        self.hits = 0
+        self.misses = 0
+
+    def get(self, key: Tuple[str, int, int], query_id: bytes) -> Optional[bytes]:
+        """The cached response under query_id, its TTLs counted down by the time it spent here"""
+        entry = self.entries.get(key)
+        now = time.monotonic()
+        if entry and entry[1] > now:
+            self.entries.move_to_end(key)
+            self.hits += 1
+            return age_ttls(query_id + entry[2], int(now - entry[0]))
+        if entry:
+            del self.entries[key]
+        self.misses += 1
+        return None
+
+    def put(self, key: Tuple[str, int, int], response: bytes, ttl: int):
+        if ttl <= 0 or self.size <= 0:
+            return
+        now = time.monotonic()
+        self.entries[key] = (now, now + min(ttl, MAX_TTL), response[2:])
+        self.entries.move_to_end(key)
+        while len(self.entries) > self.size:
+            self.entries.popitem(last=False)
+
+    def flush(self) -> int:
+        """Drop everything; returns how many entries there were"""
+        count = len(self.entries)
+        self.entries.clear()
+        return count
+
+class _UdpListener(asyncio.DatagramProtocol):
+    def __init__(self, forwarder: "DnsForwarder"):
+        self.forwarder = forwarder
+        self.transport = None
+
+    def connection_made(self, transport):
+        self.transport = transport
+
+    def datagram_received(self, data: bytes, addr):
+        asyncio.ensure_future(self._reply(data, addr))
+
+    async def _reply(self, data: bytes, addr):
+        response = await self.forwarder.resolve(data, addr[0])
+        if response and self.transport and not self.transport.is_closing():
+            self.transport.sendto(response, addr)
+
+class DnsForwarder:
+    """Caching forwarder; upstreams() is asked for the resolvers to use on every cache miss"""
+
+    def __init__(self, upstreams: Callable[[], List[str]], listen: Optional[List[str]] = None,
+                 cache_size: int = DEFAULT_CACHE_SIZE, events=None, port: int = 53, upstream_port: int = 53):
+        self.upstreams = upstreams
+        self.listen = list(listen or DEFAULT_LISTEN)
+        self.cache = DnsCache(cache_size)
+        self.events = events
+        self.port = port
+        self.upstream_port = upstream_port
+        self.listeners: Dict[str, Tuple[asyncio.DatagramTransport, asyncio.AbstractServer]] = {}
+        self.queries = 0
+        self.failures = 0
+        self.last_upstream: Optional[str] = None
+        # A DnsFilter when blocklists are in use
+        self.filter = None
+
+    def usable_upstreams(self) -> List[str]:
+        """upstreams() without duplicates or our own addresses, which would loop"""
+        servers = []
+        for server in dict.fromkeys(self.upstreams()):
+            try:
+                ipaddress.ip_address(server.split("%")[0])
+            except ValueError:
+                continue
+            if server not in self.listeners and server not in self.listen:
+                servers.append(server)
+        return servers
+
+    async def start(self) -> List[str]:
+        """Listen on the configured addresses; returns those that could not be bound"""
+        return [address for address in self.listen if not await self.add_listener(address)]
+
+    async def add_listener(self, address: str) -> bool:
+        """Serve UDP and TCP queries on address too (a hotspot's own address, say)"""
+        if address in self.listeners:
+            return True
+        loop = asyncio.get_running_loop()
+        try:
+            transport, _ = await loop.create_datagram_endpoint(
+                lambda: _UdpListener(self), local_addr=(address, self.port)
+            )
+        except OSError as e:
+            logger.error(f"DNS forwarder cannot listen on {address}:{self.port}: {e}")
+            return False
+        try:
+            server = await asyncio.start_server(self._serve_tcp, address, self.port)
+        except OSError as e:
+            logger.error(f"DNS forwarder cannot listen on {address}:{self.port}/tcp: {e}")
+            transport.close()
+            return False
+        self.listeners[address] = (transport, server)
+        logger.info(f"DNS forwarder listening on {address}:{self.port}")
+        return True
+
+    async def remove_listener(self, address: str):
+        listener = self.listeners.pop(address, None)
+        if listener:
+            transport, server = listener
+            transport.close()
+            server.close()
+            await server.wait_closed()
+            logger.info(f"DNS forwarder stopped listening on {address}")
+
+    async def stop(self):
+        for address in list(self.listeners):
+            await self.remove_listener(address)
+
+    async def _serve_tcp(self, reader: asyncio.StreamReader, writer: asyncio.StreamWriter):
+        client = (writer.get_extra_info("peername") or [None])[0]
+        try:
+            while True:
+                length = struct.unpack("!H", await reader.readexactly(2))[0]
+                response = await self.resolve(await reader.readexactly(length), client)
+                if not response:
+                    break
+                writer.write(struct.pack("!H", len(response)) + response)
+                await writer.drain()
+        except (asyncio.IncompleteReadError, ConnectionError):
+            pass
+        finally:
+            writer.close()
+
+    async def resolve(self, query: bytes, client: Optional[str] = None) -> Optional[bytes]:
+        """The response to a query: blocked, cached, forwarded, or SERVFAIL when no upstream answers"""
+        key = question(query)
+        # Ignore responses and anything without exactly one question
+        if key is None or query[2] & 0x80:
+            return None
+        self.queries += 1
+        if self.filter and await self.filter.blocks(client, key[0]):
+            return nxdomain(query)
+        cached = self.cache.get(key, query[:2])
+        if cached:
+            return cached
+
+        for server in self.usable_upstreams():
+            try:
+                response = await self._forward(server, query)
+            except (OSError, asyncio.TimeoutError) as e:
+                logger.debug(f"Upstream {server} did not answer {key[0]}: {e}")
+                continue
+            self.last_upstream = server
+            ttl = answer_ttl(response)
+            if ttl is not None:
+                self.cache.put(key, response, ttl)
+            return response
+
+        self.failures += 1
+        logger.warning(f"No upstream resolver answered {key[0]}")
+        return servfail(query)
+
+    async def _forward(self, server: str, query: bytes) -> bytes:
+        """Ask one upstream over UDP under a fresh ID, retrying over TCP if the answer was truncated"""
+        query_id = struct.pack("!H", random.randrange(1 << 16))
+        loop = asyncio.get_running_loop()
+        reply = loop.create_future()
+
+        class Upstream(asyncio.DatagramProtocol):
+            def datagram_received(self, data, addr):
+                if data[:2] == query_id and not reply.done():
+                    reply.set_result(data)
+
+            def error_received(self, exc):
+                if not reply.done():
+                    reply.set_exception(exc)
+
+        transport, _ = await loop.create_datagram_endpoint(Upstream, remote_addr=(server, self.upstream_port))
+        try:
+            transport.sendto(query_id + query[2:])
+            response = await asyncio.wait_for(reply, UPSTREAM_TIMEOUT)
+        finally:
+            transport.close()
+
+        if struct.unpack("!H", response[2:4])[0] & FLAG_TC:
+            response = await asyncio.wait_for(self._forward_tcp(server, query_id + query[2:]), UPSTREAM_TIMEOUT)
+        return query[:2] + response[2:]
+
+    async def _forward_tcp(self, server: str, query: bytes) -> bytes:
+        reader, writer = await asyncio.open_connection(server, self.upstream_port)
+        try:
+            writer.write(struct.pack("!H", len(query)) + query)
+            await writer.drain()
+            length = struct.unpack("!H", await reader.readexactly(2))[0]
+            return await reader.readexactly(length)
+        finally:
+            writer.close()
+
+    def flush(self) -> int:
+        count = self.cache.flush()
+        logger.info(f"Flushed {count} cached DNS answers")
+        if self.events:
+            self.events.publish("DnsCacheFlushed", entries=count)
+        return count
+
+    def stats(self) -> Dict:
+        lookups = self.cache.hits + self.cache.misses
+        return {
+            "enabled": True,
+            "listening": list(self.listeners),
+            "upstreams": self.usable_upstreams(),
+            "last_upstream": self.last_upstream,
+            "queries": self.queries,
+            "failures": self.failures,
+            "cache_entries": len(self.cache.entries),
+            "cache_size": self.cache.size,
+            "cache_hits": self.cache.hits,
+            "hit_rate": round(self.cache.hits / lookups * 100, 1) if lookups else None
+        }
+
+class StubHost:
+    """The host backend while the forwarder runs: resolvers given to set_dns become upstreams instead"""
+
+    def __init__(self, host, forwarder: DnsForwarder):
+        self.host = host
+        self.forwarder = forwarder
+        # Set through set_dns (a location, `dns set`); these go before the links' own
+        self.pinned: List[str] = []
+        # Whatever resolv.conf listed before we replaced it, tried last
+        self.fallback: List[str] = []
+
+    def __getattr__(self, name):
+        return getattr(self.host, name)
+
+    async def set_dns(self, servers: List[str]) -> bool:
+        self.pinned = [server for server in servers if server not in self.forwarder.listen]
+        return await self.host.set_dns(self.forwarder.listen[:1])
+
+    async def watch(self, resolv_servers: Callable[[], List[str]], interval: float = WATCH_INTERVAL):
+        """Point resolv.conf back at the stub whenever something else rewrites it"""
+        while True:
+            try:
+                servers = resolv_servers()
+                if servers != self.forwarder.listen[:1]:
+                    others = [server for server in servers if server not in self.forwarder.listen]
+                    if others:
+                        self.fallback = others
+                    logger.info(f"resolv.conf listed {', '.join(servers) or 'nothing'}; pointing it at the stub")
+                    await self.host.set_dns(self.forwarder.listen[:1])
+            except Exception as e:
+                logger.error(f"Cannot check resolv.conf: {e}")
+            await asyncio.sleep(interval)
@@ -0,0 +1,115 @@
+"""
+DNS Health - Latency and failures of each configured resolver
+Sends a small A query to every server in use on an interval, so a dead or slow
+resolver shows up before the user blames the connection
+"""
+
+import time
+import random
+import socket
+import struct
+import asyncio
+import logging
+from dataclasses import dataclass, asdict
+from typing import Callable, Dict, List, Optional
+
+# Configure logging
+logger = logging.getLogger(__name__)
+
+PROBE_INTERVAL = 10.0
+PROBE_TIMEOUT = 2.0
+PROBE_NAME = "example.com"
+SLOW_MS = 200.0
+
+# Reply codes that mean the server answered but could not resolve anything
+FAILING_RCODES = {2: "SERVFAIL", 5: "REFUSED"}
+
+def build_query(name: str, query_id: int) -> bytes:
+    """A recursive A query for name"""
+    header = struct.pack("!HHHHHH", query_id, 0x0100, 1, 0, 0, 0)
+    labels = b"".join(bytes([len(part)]) + part.encode("idna") for part in name.rstrip(".").split("."))
+    return header + labels + b"\x00" + struct.pack("!HH", 1, 1)
+
+def query_server(server: str, name: str = PROBE_NAME, timeout: float = PROBE_TIMEOUT) -> float:
+    """Milliseconds until server answers a query for name; raises OSError if it doesn't"""
+    query_id = random.randrange(1 << 16)
+    address = server.split("%")[0]
+    family = socket.AF_INET6 if ":" in address else socket.AF_INET
+    with socket.socket(family, socket.SOCK_DGRAM) as sock:
+        sock.settimeout(timeout)
+        started = time.monotonic()
+        sock.sendto(build_query(name, query_id), (server, 53))
+        while True:
+            reply = sock.recv(512)
+            if len(reply) >= 12 and struct.unpack("!H", reply[:2])[0] == query_id:
+                break
+        elapsed = (time.monotonic() - started) * 1000
+
+    rcode = reply[3] & 0x0F
+    if rcode in FAILING_RCODES:
+        raise OSError(f"{FAILING_RCODES[rcode]} from {server}")
+    return elapsed
+
+@dataclass
+class ServerHealth:
+    """Probe results for one resolver"""
+    server: str
+    status: str = "unknown"  # unknown, ok, slow, failing
+    latency_ms: Optional[float] = None
+    checks: int = 0
+    failures: int = 0
+    last_error: Optional[str] = None
+    checked_at: Optional[float] = None
+
+class DnsHealthProber:
+    """Probes the resolvers servers() returns and keeps the latest result for each"""
+
+    def __init__(self, host, servers: Callable[[], List[str]], events=None, interval: float = PROBE_INTERVAL):
+        self.host = host
+        self.servers = servers
+        self.events = events
+        self.interval = interval
+        self.health: Dict[str, ServerHealth] = {}
+
+    async def probe(self, server: str) -> ServerHealth:
+        """Query one server and record the outcome"""
+        health = self.health.setdefault(server, ServerHealth(server))
+        previous = health.status
+        health.checks += 1
+        health.checked_at = time.time()
+        try:
+            health.latency_ms = round(await self.host.query_dns(server, PROBE_NAME, PROBE_TIMEOUT), 1)
+            health.status = "slow" if health.latency_ms > SLOW_MS else "ok"
+            health.last_error = None
+        except OSError as e:
+            health.failures += 1
+            health.latency_ms = None
+            health.status = "failing"
+            health.last_error = str(e) or "no answer"
+
+        if health.status != previous:
+            logger.info(f"DNS server {server} is {health.status}")
+            if self.events:
+                self.events.publish("DnsHealthChanged", server=server, status=health.status,
+                                    previous=previous, latency_ms=health.latency_ms)
+        return health
+
+    async def probe_all(self):
+        """Probe every server in use at once, forgetting ones no longer configured"""
+        servers = list(dict.fromkeys(self.servers()))
+        for server in set(self.health) - set(servers):
+            del self.health[server]
+        await asyncio.gather(*(self.probe(server) for server in servers))
+
+    def list(self) -> List[Dict]:
+        """Results in the order the servers are configured"""
+        order = {server: index for index, server in enumerate(dict.fromkeys(self.servers()))}
+        return [asdict(h) for h in sorted(self.health.values(), key=lambda h: order.get(h.server, len(order)))]
+
+    async def run(self):
+        while True:
+            try:
+                await self.probe_all()
+            except Exception as e:
+                logger.error(f"DNS probe failed: {e}")
+            await asyncio.sleep(self.interval)
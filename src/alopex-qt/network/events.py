@@ -0,0 +1,167 @@
+"""
+Daemon Events - In-process publish/subscribe for state changes
+Subscribers (IPC clients in Subscribe mode) get their own bounded queue; the
+most recent events are also kept for diagnostic snapshots, and every event is
+appended to an on-disk log that GetEvents queries after the fact
+"""
+
+import json
+import time
+import asyncio
+import fnmatch
+import logging
+from collections import deque
+from pathlib import Path
+from typing import Dict, Iterable, List, Optional, Tuple, Union
+
+# Configure logging
+logger = logging.getLogger(__name__)
+
+QUEUE_SIZE = 256
+HISTORY_SIZE = 500
+DEFAULT_RETENTION_DAYS = 14
+PRUNE_INTERVAL = 86400.0
+# Progress ticks say nothing after the fact; the job's start and end are kept
+DEFAULT_EXCLUDE = ["JobProgress", "BluetoothFileProgress"]
+
+class Subscription:
+    """One subscriber's queue and event filter"""
+
+    def __init__(self, kinds: Optional[Iterable[str]] = None):
+        self.kinds = set(kinds) if kinds else None
+        self.queue: asyncio.Queue = asyncio.Queue(maxsize=QUEUE_SIZE)
+        self.dropped = 0
+
+    def wants(self, kind: str) -> bool:
+        return self.kinds is None or kind in self.kinds
+
+    def offer(self, event: Dict):
+        """Queue an event without blocking the publisher"""
+        try:
+            self.queue.put_nowait(event)
+        except asyncio.QueueFull:
+            self.dropped += 1
+
+    async def get(self) -> Dict:
+        return await self.queue.get()
+
+class EventBus:
+    """Fan events out to every interested subscriber"""
+
+    def __init__(self, history_size: int = HISTORY_SIZE):
+        self.subscriptions: List[Subscription] = []
+        self.history = deque(maxlen=history_size)
+        self.log: Optional["EventLog"] = None
+
+    def subscribe(self, kinds: Optional[Iterable[str]] = None) -> Subscription:
+        """Register a subscriber; kinds limits which events it receives"""
+        subscription = Subscription(kinds)
+        self.subscriptions.append(subscription)
+        return subscription
+
+    def unsubscribe(self, subscription: Subscription):
+        """Remove a subscriber"""
+        if subscription in self.subscriptions:
+            self.subscriptions.remove(subscription)
+        if subscription.dropped:
+            logger.warning(f"Event subscriber fell behind, {subscription.dropped} events dropped")
+
+    def publish(self, kind: str, **data) -> Dict:
+        """Deliver an event to subscribers and return it"""
+        event = dict(data, event=kind, timestamp=time.time())
+        self.history.append(event)
+        if self.log:
+            self.log.append(event)
+        for subscription in list(self.subscriptions):
+            if subscription.wants(kind):
+                subscription.offer(event)
+        return event
+
+    def recent(self, limit: Optional[int] = None) -> List[Dict]:
+        """The last published events, oldest first"""
+        events = list(self.history)
+        return events[-limit:] if limit else events
+
+def parse_filter(terms: Union[None, str, Iterable[str]]) -> Tuple[List[str], Dict[str, str]]:
+    """Event name patterns ("Wifi*", "InterfaceStateChanged") and field=value terms ("interface=wlan0") from
+    a comma-separated string or a list; raises ValueError on an empty term"""
+    if terms is None:
+        return [], {}
+    if isinstance(terms, str):
+        terms = terms.split(",")
+    kinds, fields = [], {}
+    for term in (str(term).strip() for term in terms):
+        if not term:
+            raise ValueError("empty filter term")
+        key, separator, value = term.partition("=")
+        if separator:
+            if not key.strip():
+                raise ValueError(f"filter term {term!r} names no field")
+            fields[key.strip()] = value.strip()
+        else:
+            kinds.append(term)
+    return kinds, fields
+
+def matches(event: Dict, kinds: List[str], fields: Dict[str, str]) -> bool:
+    """Any of the name patterns (all names without any), and every field term"""
+    if kinds and not any(fnmatch.fnmatchcase(event.get("event", ""), kind) for kind in kinds):
+        return False
+    return all(key in event and str(event[key]) == value for key, value in fields.items())
+
+class EventLog:
+    """Every published event as one JSON line in events.jsonl, pruned to the retention period once a day"""
+
+    def __init__(self, path: Path, retention_days: float = DEFAULT_RETENTION_DAYS,
+                 exclude: Optional[Iterable[str]] = None):
+        self.file = path / "events.jsonl"
+        self.retention = retention_days * 86400
+        self.exclude = list(DEFAULT_EXCLUDE if exclude is None else exclude)
+        self.last_prune = 0.0
+
+    def append(self, event: Dict):
+        if any(fnmatch.fnmatchcase(event["event"], pattern) for pattern in self.exclude):
+            return
+        try:
+            self.file.parent.mkdir(parents=True, exist_ok=True)
+            with open(self.file, "a") as f:
+                # Events carry what the publisher had; anything JSON can't hold is written as text
+                f.write(json.dumps(event, default=str) + "\n")
+        except OSError as e:
+            logger.error(f"Failed to save event {event['event']}: {e}")
+        if event["timestamp"] - self.last_prune >= PRUNE_INTERVAL:
+            self.prune(event["timestamp"])
+
+    def _read(self) -> List[Dict]:
+        events = []
+        if not self.file.exists():
+            return events
+        with open(self.file) as f:
+            for line in f:
+                try:
+                    events.append(json.loads(line))
+                except ValueError:
+                    # A line cut short by a crash
+                    continue
+        return events
+
+    def prune(self, now: Optional[float] = None):
+        """Drop events older than the retention period"""
+        now = time.time() if now is None else now
+        self.last_prune = now
+        try:
+            kept = [event for event in self._read() if event.get("timestamp", 0) >= now - self.retention]
+            temp_file = self.file.with_suffix(".tmp")
+            with open(temp_file, "w") as f:
+                f.writelines(json.dumps(event, default=str) + "\n" for event in kept)
+            temp_file.replace(self.file)
+        except OSError as e:
+            logger.error(f"Failed to prune the event log: {e}")
+
+    def query(self, since: Optional[float] = None, terms: Union[None, str, Iterable[str]] = None,
+              limit: Optional[int] = None) -> List[Dict]:
+        """Events after since matching the filter terms, oldest first; the last limit of them with one.
+        Raises ValueError for a malformed filter"""
+        kinds, fields = parse_filter(terms)
+        events = [event for event in self._read()
+                  if (since is None or event.get("timestamp", 0) > since) and matches(event, kinds, fields)]
+        return events[-limit:] if limit else events
@@ -0,0 +1,265 @@
+"""
+Profile Exporters - Render profiles as another network stack's configuration
+The reverse of the importers: systemd-networkd .network/.netdev files or one
+netplan YAML file, for leaving alopex or pre-rendering early-boot configs.
+What the target cannot express is reported rather than silently dropped
+"""
+
+import re
+import json
+import logging
+import ipaddress
+from dataclasses import dataclass, field
+from typing import Callable, Dict, List, Optional, Tuple
+
+from .connection_manager import ConnectionProfile
+from .secret import reveal
+from .tunnels import TUNNEL_KINDS, VXLAN_PORT, link_type
+
+# Configure logging
+logger = logging.getLogger(__name__)
+
+NETPLAN_FILE = "90-alopex.yaml"
+
+@dataclass
+class ExportReport:
+    """Rendered files for one target, plus the profiles that could not be carried over"""
+    target: str
+    files: Dict[str, str] = field(default_factory=dict)  # file name -> contents
+    secret: List[str] = field(default_factory=list)      # files holding passwords, to be written 0600
+    exported: List[str] = field(default_factory=list)
+    skipped: List[Dict] = field(default_factory=list)    # {"name", "reason"}
+    warnings: List[Dict] = field(default_factory=list)   # {"name", "message"}
+
+    def skip(self, name: str, reason: str):
+        self.skipped.append({"name": name, "reason": reason})
+        logger.info(f"Export {self.target}: skipped {name} ({reason})")
+
+    def warn(self, name: str, message: str):
+        self.warnings.append({"name": name, "message": message})
+
+    def to_dict(self) -> Dict:
+        files = [{"name": name, "content": content, "secret": name in self.secret}
+                 for name, content in sorted(self.files.items())]
+        return {"target": self.target, "files": files, "exported": self.exported, "skipped": self.skipped,
+                "warnings": self.warnings}
+
+def file_slug(name: str) -> str:
+    """A profile name as a file name part: "Office wired" -> "office-wired" """
+    return re.sub(r"[^a-z0-9_.]+", "-", name.lower()).strip("-.") or "profile"
+
+def _address(profile: ConnectionProfile) -> Optional[str]:
+    """"192.0.2.10/24" from ip_address and netmask; /24 when no netmask is set, as static profiles get"""
+    if not profile.ip_address:
+        return None
+    return str(ipaddress.ip_interface(f"{profile.ip_address}/{profile.netmask or 24}"))
+
+def _exportable(profiles: List[ConnectionProfile], report: ExportReport) -> List[ConnectionProfile]:
+    """Drop what neither target can express, and all but the preferred ethernet profile per interface"""
+    kept, wired = [], {}
+    # Preferred first: what alopex would pick when several profiles want one interface
+    for profile in sorted(profiles, key=lambda p: (-p.priority, not p.auto_connect, p.name)):
+        if profile.connection_type == "vpn":
+            report.skip(profile.name, "VPN profiles only point at the VPN backend's own config")
+        elif profile.connection_type == "hotspot" or profile.method == "shared":
+            report.skip(profile.name, "hotspots are not supported")
+        elif profile.eap:
+            report.skip(profile.name, "802.1X is not exported")
+        elif profile.connection_type == "ethernet" and profile.interface in wired:
+            report.skip(profile.name, f"only one profile per interface can be exported; "
+                                      f"{wired[profile.interface]} was, as the preferred one")
+        else:
+            if profile.connection_type == "ethernet":
+                wired[profile.interface] = profile.name
+            if profile.units:
+                report.warn(profile.name, "systemd units started with the profile were not exported")
+            if profile.metered:
+                report.warn(profile.name, "the metered flag was not exported")
+            kept.append(profile)
+    return kept
+
+# systemd-networkd
+
+def render_unit(sections: List[Tuple[str, List[Tuple[str, object]]]]) -> str:
+    """[Section] blocks of Key=Value lines; keys may repeat, None values are left out"""
+    blocks = []
+    for section, entries in sections:
+        lines = [f"{key}={value}" for key, value in entries if value is not None]
+        blocks.append("\n".join([f"[{section}]"] + lines))
+    return "\n\n".join(blocks) + "\n"
+
+def _netdev_unit(interface: str, netdev: Dict) -> str:
+    kind = netdev["kind"]
+    sections = [("NetDev", [("Name", interface), ("Kind", link_type(netdev) if kind in TUNNEL_KINDS else kind)])]
+    if kind == "vlan":
+        sections.append(("VLAN", [("Id", netdev["id"])]))
+    elif kind == "bond":
+        sections.append(("Bond", [("Mode", netdev.get("mode", "balance-rr"))]))
+    elif kind == "vxlan":
+        sections.append(("VXLAN", [("VNI", netdev["vni"]), ("Remote", netdev["remote"]),
+                                   ("Local", netdev.get("local")), ("TTL", netdev.get("ttl")),
+                                   ("DestinationPort", netdev.get("port", VXLAN_PORT)),
+                                   ("Independent", None if netdev.get("parent") else "yes")]))
+    elif kind in TUNNEL_KINDS:
+        sections.append(("Tunnel", [("Remote", netdev["remote"]), ("Local", netdev.get("local")),
+                                    ("Key", netdev.get("key")), ("TTL", netdev.get("ttl")), ("Independent", "yes")]))
+    return render_unit(sections)
+
+def export_networkd(profiles: List[ConnectionProfile], secrets: bool = False) -> ExportReport:
+    """One .network per profile, a .netdev per virtual device; WiFi profiles match their SSID"""
+    report = ExportReport(target="networkd")
+    networks: Dict[str, Tuple[str, List, List]] = {}  # file -> (interface, [Match], [Network])
+    lower: Dict[str, List[Tuple[str, str]]] = {}  # interface -> Bridge=/Bond=/VLAN=/VXLAN= lines it needs
+
+    for profile in _exportable(profiles, report):
+        match = [("Name", profile.interface)]
+        network: List[Tuple[str, object]] = []
+        if profile.connection_type == "wifi":
+            match.append(("SSID", profile.ssid))
+            report.warn(profile.name, f"networkd does not join WiFi networks; wpa_supplicant or iwd has to "
+                                      f"associate with {profile.ssid}")
+        if profile.method == "dhcp":
+            network.append(("DHCP", "ipv4"))
+        elif profile.method == "static":
+            network += [("Address", _address(profile)), ("Gateway", profile.gateway)]
+        else:
+            network.append(("LinkLocalAddressing", "no"))
+        network += [("DNS", server) for server in profile.dns_servers]
+
+        netdev = profile.netdev
+        if netdev:
+            report.files[f"10-alopex-{file_slug(profile.name)}.netdev"] = _netdev_unit(profile.interface, netdev)
+            if netdev["kind"] in ("bridge", "bond"):
+                for port in netdev.get("ports", []):
+                    lower.setdefault(port, []).append((netdev["kind"].capitalize(), profile.interface))
+            elif netdev.get("parent"):
+                lower.setdefault(netdev["parent"], []).append((netdev["kind"].upper(), profile.interface))
+        networks[f"10-alopex-{file_slug(profile.name)}.network"] = (profile.interface, match, network)
+        report.exported.append(profile.name)
+
+    # Lower links declare what sits on them; ones without a profile of their own get a file just for that
+    for interface, lines in lower.items():
+        owner = next((name for name, (owned, match, _) in networks.items()
+                      if owned == interface and len(match) == 1), None)
+        if owner:
+            networks[owner][2].extend(lines)
+        else:
+            networks[f"10-alopex-{file_slug(interface)}.network"] = (interface, [("Name", interface)],
+                                                                      [("LinkLocalAddressing", "no")] + lines)
+
+    for name, (_, match, network) in networks.items():
+        report.files[name] = render_unit([("Match", match), ("Network", network)])
+    return report
+
+# netplan
+
+# Keys YAML reads back as the same string without quotes; SSIDs and the like get quoted
+PLAIN_KEY = re.compile(r"[A-Za-z][A-Za-z0-9_.-]*")
+
+def render_yaml(value, indent: int = 0) -> str:
+    """Block-style YAML for nested dicts and lists; strings are JSON-quoted, which YAML reads as-is"""
+    pad = "  " * indent
+    if isinstance(value, dict):
+        if not value:
+            return "{}"
+        lines = []
+        for key, item in value.items():
+            key = str(key) if PLAIN_KEY.fullmatch(str(key)) else json.dumps(str(key))
+            nested = isinstance(item, (dict, list)) and item
+            lines.append(f"{pad}{key}:" + (f"\n{render_yaml(item, indent + 1)}" if nested else f" {render_yaml(item)}"))
+        return "\n".join(lines)
+    if isinstance(value, list):
+        if not value:
+            return "[]"
+        lines = []
+        for item in value:
+            nested = isinstance(item, (dict, list)) and item
+            lines.append(f"{pad}- " + (render_yaml(item, indent + 1).lstrip() if nested else render_yaml(item)))
+        return "\n".join(lines)
+    return json.dumps(value)
+
+def _netplan_addressing(profile: ConnectionProfile) -> Dict:
+    if profile.method == "dhcp":
+        config: Dict = {"dhcp4": True}
+    elif profile.method == "static":
+        config = {"dhcp4": False, "addresses": [_address(profile)]}
+        if profile.gateway:
+            config["routes"] = [{"to": "default", "via": profile.gateway}]
+    else:
+        config = {"dhcp4": False, "link-local": []}
+    if profile.dns_servers:
+        config["nameservers"] = {"addresses": list(profile.dns_servers)}
+    return config
+
+def _netplan_tunnel(netdev: Dict) -> Dict:
+    config = {"mode": link_type(netdev), "remote": netdev["remote"]}
+    if netdev.get("local"):
+        config["local"] = netdev["local"]
+    if netdev.get("ttl") is not None:
+        config["ttl"] = netdev["ttl"]
+    if netdev["kind"] == "vxlan":
+        config.update(id=netdev["vni"], port=netdev.get("port", VXLAN_PORT))
+        if netdev.get("parent"):
+            config["link"] = netdev["parent"]
+    elif netdev.get("key") is not None:
+        config["key"] = netdev["key"]
+    return config
+
+def export_netplan(profiles: List[ConnectionProfile], secrets: bool = False) -> ExportReport:
+    """One YAML file; a WiFi interface's profiles become its access points, sharing the first one's addressing"""
+    report = ExportReport(target="netplan")
+    devices: Dict[str, Dict[str, Dict]] = {}
+    ports = set()
+
+    for profile in _exportable(profiles, report):
+        netdev = profile.netdev or {}
+        config = _netplan_addressing(profile)
+        if profile.connection_type == "wifi":
+            wifi = devices.setdefault("wifis", {}).setdefault(profile.interface, dict(config, **{"access-points": {}}))
+            if _netplan_addressing(profile) != {key: wifi[key] for key in wifi if key != "access-points"}:
+                report.warn(profile.name, f"netplan shares one addressing per interface; "
+                                          f"{profile.interface} keeps its preferred profile's")
+            access_point = {}
+            if profile.password and profile.security != "Open":
+                if secrets:
+                    access_point["password"] = reveal(profile.password)
+                else:
+                    report.warn(profile.name, "the WiFi password was left out; export with secrets to include it")
+            wifi["access-points"][profile.ssid] = access_point
+        elif netdev.get("kind") == "vlan":
+            devices.setdefault("vlans", {})[profile.interface] = dict(config, id=netdev["id"], link=netdev["parent"])
+            ports.add(netdev["parent"])
+        elif netdev.get("kind") in ("bridge", "bond"):
+            config["interfaces"] = list(netdev.get("ports", []))
+            if netdev["kind"] == "bond":
+                config["parameters"] = {"mode": netdev.get("mode", "balance-rr")}
+            devices.setdefault(f"{netdev['kind']}s", {})[profile.interface] = config
+            ports.update(netdev.get("ports", []))
+        elif netdev.get("kind") in TUNNEL_KINDS:
+            devices.setdefault("tunnels", {})[profile.interface] = dict(config, **_netplan_tunnel(netdev))
+            if netdev.get("parent"):
+                ports.add(netdev["parent"])
+        else:
+            devices.setdefault("ethernets", {})[profile.interface] = config
+        report.exported.append(profile.name)
+
+    # netplan only links devices it defines, so ports and parents need an entry even without addressing
+    for port in sorted(ports):
+        if not any(port in section for section in devices.values()):
+            devices.setdefault("ethernets", {})[port] = {}
+
+    if report.exported:
+        order = ("ethernets", "wifis", "bonds", "bridges", "vlans", "tunnels")
+        network = {"version": 2, "renderer": "networkd"}
+        network.update((section, devices[section]) for section in order if section in devices)
+        report.files[NETPLAN_FILE] = f"# Exported from alopex\n{render_yaml({'network': network})}\n"
+        if secrets and any("password" in access_point for wifi in devices.get("wifis", {}).values()
+                           for access_point in wifi["access-points"].values()):
+            report.secret.append(NETPLAN_FILE)
+    return report
+
+# target name -> exporter(profiles, secrets)
+EXPORTERS: Dict[str, Callable[[List[ConnectionProfile], bool], ExportReport]] = {
+    "networkd": export_networkd,
+    "netplan": export_netplan
+}
@@ -0,0 +1,235 @@
+"""
+Failover - Move the default route to a backup link while the primary is unhealthy
+The primary is unhealthy while its link is down or its SLA probe is in breach.
+Failover adds a default route through the backup with a metric below the
+primary's; failback removes it again. When that happens is the group's
+failback policy: "immediate", "stable" (once the primary has stayed healthy for
+stable_minutes, so a flapping link doesn't churn routes) or "never" (stay on
+the backup until switched back by hand). After each switch the link taking over
+announces its addresses so neighbours stop sending to the old one. Every
+decision is published as a FailoverDecision event
+"""
+
+import time
+import asyncio
+import logging
+from dataclasses import dataclass
+from typing import Awaitable, Callable, Dict, List, Optional, Tuple
+
+# Configure logging
+logger = logging.getLogger(__name__)
+
+FAILBACK_POLICIES = ("immediate", "stable", "never")
+DEFAULT_INTERVAL = 2.0
+# Below the 100 and 600 DHCP clients usually give wired and wireless default routes
+DEFAULT_METRIC = 10
+
+@dataclass
+class FailoverGroup:
+    """A primary link, the backup that takes over from it, and when to go back"""
+    name: str
+    primary: str
+    backup: str
+    # SLA probe whose breach counts as the primary being unhealthy; None goes by the link alone
+    probe: Optional[str] = None
+    failback: str = "stable"
+    stable_minutes: float = 5.0
+    metric: int = DEFAULT_METRIC
+
+    @classmethod
+    def from_config(cls, config: Dict) -> "FailoverGroup":
+        """Group from its config entry; raises ValueError for one that cannot work"""
+        try:
+            group = cls(**config)
+        except TypeError as e:
+            raise ValueError(str(e))
+        if not group.name or not group.primary or not group.backup:
+            raise ValueError("a failover group needs a name, a primary and a backup")
+        if group.primary == group.backup:
+            raise ValueError("primary and backup must be different interfaces")
+        if group.failback not in FAILBACK_POLICIES:
+            raise ValueError(f"failback must be one of {', '.join(FAILBACK_POLICIES)}")
+        group.stable_minutes = max(0.0, float(group.stable_minutes))
+        return group
+
+class GroupState:
+    """Which link a group is on, and since when the primary has looked healthy again"""
+
+    def __init__(self, group: FailoverGroup):
+        self.group = group
+        self.active = "primary"
+        self.changed_at: Optional[float] = None
+        # While on the backup: when the primary last became healthy, None while it isn't
+        self.healthy_since: Optional[float] = None
+        # The default route failover added, removed again on failback
+        self.route: Optional[Dict] = None
+        self.last_decision: Optional[Dict] = None
+
+    def to_dict(self) -> Dict:
+        group = self.group
+        failback_at = None
+        if self.active == "backup" and self.healthy_since is not None and group.failback == "stable":
+            failback_at = self.healthy_since + group.stable_minutes * 60
+        return {
+            "name": group.name, "primary": group.primary, "backup": group.backup, "probe": group.probe,
+            "failback": group.failback, "stable_minutes": group.stable_minutes,
+            "active": self.active, "interface": group.backup if self.active == "backup" else group.primary,
+            "changed_at": self.changed_at, "healthy_since": self.healthy_since, "failback_at": failback_at,
+            "last_decision": self.last_decision
+        }
+
+def decide(state: GroupState, primary_ok: bool, backup_ok: bool, now: float) -> Optional[Tuple[str, str]]:
+    """What to do about one group now: (decision, reason), or None to carry on as is.
+    Decisions are failover, failback, failback_pending, failback_cancelled, failback_held and no_backup"""
+    group = state.group
+    if state.active == "primary":
+        if primary_ok:
+            return None
+        if not backup_ok:
+            return "no_backup", f"{group.primary} is unhealthy but {group.backup} is down too"
+        return "failover", f"{group.primary} is unhealthy"
+
+    if not primary_ok:
+        if state.healthy_since is None:
+            return None
+        state.healthy_since = None
+        return "failback_cancelled", f"{group.primary} went unhealthy again"
+    if not backup_ok:
+        # Whatever the policy, a healthy primary beats a dead backup
+        return "failback", f"{group.backup} went down"
+    if group.failback == "immediate":
+        return "failback", f"{group.primary} is healthy again"
+
+    first = state.healthy_since is None
+    if first:
+        state.healthy_since = now
+    if group.failback == "never":
+        return ("failback_held", f"{group.primary} is healthy again; failback is set to never") if first else None
+    if now - state.healthy_since >= group.stable_minutes * 60:
+        return "failback", f"{group.primary} stable for {group.stable_minutes:g} minutes"
+    if first:
+        return "failback_pending", f"{group.primary} is healthy again; failing back once stable " \
+                                   f"for {group.stable_minutes:g} minutes"
+    return None
+
+class FailoverController:
+    """Checks every group on an interval and moves default routes as decide() says.
+    links() maps the interfaces that are up to their gateways; probe_state(name) is an SLA probe's state;
+    apply(change) makes a route change as reconcile describes them and returns None or the error;
+    announce(interface) sends gratuitous ARP / unsolicited NA for the link that took over"""
+
+    def __init__(self, groups: List[Dict], links: Callable[[], Dict[str, Optional[str]]],
+                 probe_state: Callable[[str], Optional[str]], apply: Callable[[Dict], Awaitable[Optional[str]]],
+                 events=None, interval: float = DEFAULT_INTERVAL,
+                 announce: Optional[Callable[[str], Awaitable]] = None):
+        self.links = links
+        self.probe_state = probe_state
+        self.apply = apply
+        self.events = events
+        self.announce = announce
+        self.interval = interval
+        self.groups: Dict[str, GroupState] = {}
+        for config in groups:
+            try:
+                group = FailoverGroup.from_config(dict(config))
+            except ValueError as e:
+                logger.error(f"Failover group {config.get('name')!r} skipped: {e}")
+                continue
+            self.groups[group.name] = GroupState(group)
+
+    async def check(self, now: Optional[float] = None):
+        """One pass over every group"""
+        now = time.time() if now is None else now
+        links = self.links()
+        for state in self.groups.values():
+            group = state.group
+            primary_ok = group.primary in links and (not group.probe or self.probe_state(group.probe) != "breach")
+            backup_ok = bool(links.get(group.backup))
+            decision = decide(state, primary_ok, backup_ok, now)
+            if not decision:
+                continue
+            if decision[0] == "no_backup" and (state.last_decision or {}).get("decision") == "no_backup":
+                continue
+            await self._act(state, *decision, gateway=links.get(group.backup), now=now)
+
+    async def _act(self, state: GroupState, decision: str, reason: str, gateway: Optional[str] = None,
+                   now: Optional[float] = None) -> Dict:
+        group = state.group
+        error = None
+        if decision == "failover":
+            route = {"to": "default", "via": gateway, "metric": group.metric}
+            error = await self.apply({"interface": group.backup, "action": "add", "kind": "route", "route": route})
+            if not error:
+                state.route = route
+        elif decision == "failback" and state.route:
+            # Gone already if the backup went down; the primary takes over either way
+            removed = await self.apply({"interface": group.backup, "action": "remove", "kind": "route",
+                                        "route": state.route})
+            if removed:
+                logger.debug(f"Failover route on {group.backup}: {removed}")
+            state.route = None
+        if decision in ("failover", "failback") and not error:
+            state.active = "backup" if decision == "failover" else "primary"
+            state.changed_at = now or time.time()
+            state.healthy_since = None
+            if self.announce:
+                try:
+                    await self.announce(group.backup if state.active == "backup" else group.primary)
+                except Exception as e:
+                    logger.warning(f"Failover group {group.name}: announcing addresses failed: {e}")
+        return self._publish(state, decision, reason, error)
+
+    def _publish(self, state: GroupState, decision: str, reason: str, error: Optional[str] = None) -> Dict:
+        group = state.group
+        interface = group.backup if state.active == "backup" else group.primary
+        last = state.last_decision or {}
+        if error and (last.get("decision"), last.get("error")) == (decision, error):
+            # Tried again on every check; said once is enough
+            logger.debug(f"Failover group {group.name}: {decision} still failing: {error}")
+            return state.to_dict()
+        if error:
+            logger.error(f"Failover group {group.name}: {decision} failed: {error}")
+        elif decision in ("failover", "failback"):
+            logger.warning(f"Failover group {group.name}: {decision} to {interface} ({reason})")
+        else:
+            logger.info(f"Failover group {group.name}: {decision} ({reason})")
+        state.last_decision = {"decision": decision, "reason": reason, "error": error, "timestamp": time.time()}
+        if self.events:
+            self.events.publish("FailoverDecision", group=group.name, decision=decision, reason=reason,
+                                active=state.active, interface=interface, error=error)
+        return state.to_dict()
+
+    async def set_active(self, name: str, active: str) -> Optional[Dict]:
+        """Switch a group by hand (how a "never" group goes back); None for an unknown group"""
+        state = self.groups.get(name)
+        if not state:
+            return None
+        if state.active == active:
+            return state.to_dict()
+        decision = "failover" if active == "backup" else "failback"
+        gateway = self.links().get(state.group.backup)
+        if active == "backup" and not gateway:
+            raise ValueError(f"{state.group.backup} is down")
+        result = await self._act(state, decision, "switched by hand", gateway)
+        if result["last_decision"]["error"]:
+            raise ValueError(result["last_decision"]["error"])
+        return result
+
+    def list(self) -> List[Dict]:
+        return [state.to_dict() for state in self.groups.values()]
+
+    async def run(self):
+        while True:
+            try:
+                await self.check()
+            except Exception as e:
+                logger.error(f"Failover check failed: {e}")
+            await asyncio.sleep(self.interval)
+
+    async def stop(self):
+        """Take the failover routes back out, so the primary wins again once alopexd is gone"""
+        for state in self.groups.values():
+            if state.route:
+                await self.apply({"interface": state.group.backup, "action": "remove", "kind": "route",
+                                  "route": state.route})
+                state.route = None
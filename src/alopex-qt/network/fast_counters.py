@@ -0,0 +1,287 @@
+"""
+Fast Counters - 100ms traffic rates from eBPF tc counters
+Loads traffic_counters.bpf.o onto each interface's clsact hooks and reads its
+per-CPU map through the bpf() syscall, so sampling costs no /proc parsing
+"""
+
+import os
+import time
+import ctypes
+import struct
+import asyncio
+import logging
+import platform
+from collections import deque
+from pathlib import Path
+from typing import Callable, Deque, Dict, List, Optional, Tuple
+
+# Configure logging
+logger = logging.getLogger(__name__)
+
+DEFAULT_OBJECT = "/usr/lib/alopex/bpf/traffic_counters.bpf.o"
+PIN_DIR = Path("/sys/fs/bpf/alopex")
+MAP_NAME = "alopex_counters"
+# tc hook and the program in the object that counts it
+PROGRAMS = {"ingress": "alopex_count_ingress", "egress": "alopex_count_egress"}
+# Filter priority on the clsact hooks, so we only ever replace or remove our own filter
+TC_PREF = "49"
+DEFAULT_INTERVAL_MS = 100
+MIN_INTERVAL_MS = 20
+HISTORY_SECONDS = 10
+# How often the attached set is checked against the interfaces present
+SYNC_INTERVAL = 5.0
+
+# Indexes used by traffic_counters.bpf.c
+DIRECTIONS = ("rx", "tx")
+PROTOCOLS = ("tcp", "udp", "icmp", "other")
+KEY = struct.Struct("=IBBH")       # ifindex, direction, protocol, pad
+VALUE = struct.Struct("=QQ")       # packets, bytes
+
+# bpf(2) commands and the attribute layouts we use from union bpf_attr
+BPF_MAP_CREATE = 0
+BPF_MAP_LOOKUP_ELEM = 1
+BPF_MAP_UPDATE_ELEM = 2
+BPF_MAP_GET_NEXT_KEY = 4
+BPF_OBJ_GET = 7
+BPF_MAP_TYPE_PERCPU_HASH = 5
+MAP_CREATE_ATTR = struct.Struct("=IIIII")   # map_type, key_size, value_size, max_entries, map_flags
+ELEM_ATTR = struct.Struct("=IIQQQ")         # map_fd, pad, key, value/next_key, flags
+OBJ_ATTR = struct.Struct("=QII")            # pathname, bpf_fd, file_flags
+SYS_BPF = {"x86_64": 321, "aarch64": 280, "armv7l": 386, "armv6l": 386, "riscv64": 280, "ppc64le": 361}
+
+# Capability bits from linux/capability.h
+CAP_NET_ADMIN = 12
+CAP_SYS_ADMIN = 21
+CAP_BPF = 39
+
+_libc = ctypes.CDLL(None, use_errno=True)
+
+def bpf(command: int, attr: bytes) -> int:
+    """Issue one bpf() call; raises OSError with the kernel's errno"""
+    number = SYS_BPF.get(platform.machine())
+    if number is None:
+        raise OSError(f"No bpf() syscall number known for {platform.machine()}")
+    buffer = ctypes.create_string_buffer(attr, len(attr))
+    result = _libc.syscall(number, command, buffer, len(attr))
+    if result < 0:
+        errno = ctypes.get_errno()
+        raise OSError(errno, os.strerror(errno))
+    return result
+
+def possible_cpus(path: Path = Path("/sys/devices/system/cpu/possible")) -> int:
+    """Slots in a per-CPU value: every possible CPU, not just the online ones"""
+    count = 0
+    for part in path.read_text().strip().split(","):
+        first, _, last = part.partition("-")
+        count += int(last or first) - int(first) + 1
+    return count
+
+def obj_get(path: Path) -> int:
+    """Open a pinned map and return its file descriptor"""
+    name = ctypes.create_string_buffer(str(path).encode())
+    return bpf(BPF_OBJ_GET, OBJ_ATTR.pack(ctypes.addressof(name), 0, 0))
+
+class BpfMap:
+    """Read-only walk over a per-CPU hash map"""
+
+    def __init__(self, fd: int, key_size: int, value_size: int, cpus: Optional[int] = None):
+        self.fd = fd
+        self.key_size = key_size
+        # The kernel copies each CPU's value rounded up to 8 bytes
+        self.slot = (value_size + 7) & ~7
+        self.value_size = value_size
+        self.cpus = cpus or possible_cpus()
+
+    def _elem(self, command: int, key: Optional[ctypes.Array], out: ctypes.Array) -> bool:
+        attr = ELEM_ATTR.pack(self.fd, 0, ctypes.addressof(key) if key is not None else 0, ctypes.addressof(out), 0)
+        try:
+            bpf(command, attr)
+        except OSError as e:
+            if e.errno == 2:    # ENOENT: end of the walk, or deleted under us
+                return False
+            raise
+        return True
+
+    def items(self) -> List[Tuple[bytes, List[bytes]]]:
+        """Every key with its value on each CPU"""
+        items = []
+        key = None
+        while True:
+            next_key = ctypes.create_string_buffer(self.key_size)
+            if not self._elem(BPF_MAP_GET_NEXT_KEY, key, next_key):
+                return items
+            value = ctypes.create_string_buffer(self.slot * self.cpus)
+            if self._elem(BPF_MAP_LOOKUP_ELEM, next_key, value):
+                raw = value.raw
+                items.append((next_key.raw, [raw[i * self.slot:i * self.slot + self.value_size]
+                                             for i in range(self.cpus)]))
+            key = next_key
+
+    def close(self):
+        os.close(self.fd)
+
+def read_counters(bpf_map: BpfMap) -> Dict[int, Dict[str, Dict[str, Tuple[int, int]]]]:
+    """Packets and bytes per ifindex, direction and protocol, summed over CPUs"""
+    counters: Dict[int, Dict[str, Dict[str, Tuple[int, int]]]] = {}
+    for key, values in bpf_map.items():
+        ifindex, direction, protocol, _ = KEY.unpack(key)
+        if direction >= len(DIRECTIONS) or protocol >= len(PROTOCOLS):
+            continue
+        packets = sum(VALUE.unpack(value)[0] for value in values)
+        nbytes = sum(VALUE.unpack(value)[1] for value in values)
+        counters.setdefault(ifindex, {}).setdefault(DIRECTIONS[direction], {})[PROTOCOLS[protocol]] = (packets, nbytes)
+    return counters
+
+def effective_capabilities(status: Path = Path("/proc/self/status")) -> int:
+    for line in status.read_text().splitlines():
+        if line.startswith("CapEff:"):
+            return int(line.split()[1], 16)
+    return 0
+
+def unavailable_reason(object_path: Path, mounts: Path = Path("/proc/self/mounts"),
+                       status: Path = Path("/proc/self/status")) -> Optional[str]:
+    """Why the eBPF path cannot run here, or None when it can"""
+    if platform.machine() not in SYS_BPF:
+        return f"no bpf() syscall number known for {platform.machine()}"
+    if not object_path.is_file():
+        return f"{object_path} not found (build with 'make WITH_EBPF=1')"
+    try:
+        capabilities = effective_capabilities(status)
+        bpffs = any(line.split()[1:3] == [str(PIN_DIR.parent), "bpf"] for line in mounts.read_text().splitlines())
+    except OSError as e:
+        return str(e)
+    if not capabilities & ((1 << CAP_BPF) | (1 << CAP_SYS_ADMIN)):
+        return "CAP_BPF (or CAP_SYS_ADMIN) is needed"
+    if not capabilities & (1 << CAP_NET_ADMIN):
+        return "CAP_NET_ADMIN is needed"
+    if not bpffs:
+        return f"no bpf filesystem mounted at {PIN_DIR.parent}"
+    return None
+
+def interface_index(interface: str) -> Optional[int]:
+    try:
+        return int(Path(f"/sys/class/net/{interface}/ifindex").read_text())
+    except (OSError, ValueError):
+        return None
+
+class FastCounters:
+    """Samples the counters every interval and keeps a few seconds of rates per interface"""
+
+    def __init__(self, backend, interfaces: Callable[[], List[str]], object_path: str = DEFAULT_OBJECT,
+                 interval_ms: int = DEFAULT_INTERVAL_MS):
+        self.backend = backend
+        self.interfaces = interfaces
+        self.object_path = object_path
+        self.interval = max(MIN_INTERVAL_MS, int(interval_ms)) / 1000
+        self.attached: List[str] = []
+        self.active = False
+        self.reason: Optional[str] = None
+        self.last_error: Optional[str] = None
+        self.previous: Dict[str, Dict[str, Dict[str, Tuple[int, int]]]] = {}
+        self.sampled_at: Optional[float] = None
+        self.rates: Dict[str, Dict] = {}
+        self.series: Dict[str, Deque[Tuple[float, float, float]]] = {}
+
+    async def start(self) -> bool:
+        """Load the programs unless something this system lacks rules it out"""
+        self.reason = self.backend.unavailable(self.object_path)
+        if self.reason is None:
+            ok, message = await self.backend.start(self.object_path)
+            if not ok:
+                self.reason = message
+        if self.reason is not None:
+            logger.warning(f"eBPF counters unavailable: {self.reason}")
+            return False
+        self.active = True
+        logger.info(f"eBPF counters loaded from {self.object_path}")
+        return True
+
+    async def sync_interfaces(self):
+        """Attach to interfaces that appeared and forget ones that went away"""
+        wanted = self.interfaces()
+        for interface in wanted:
+            if interface in self.attached:
+                continue
+            ok, message = await self.backend.attach(interface)
+            if ok:
+                self.attached.append(interface)
+            else:
+                logger.warning(f"eBPF counters not attached to {interface}: {message}")
+        for interface in [i for i in self.attached if i not in wanted]:
+            await self.backend.detach(interface)
+            self.attached.remove(interface)
+            for table in (self.previous, self.rates, self.series):
+                table.pop(interface, None)
+
+    def sample(self, counters: Dict[str, Dict[str, Dict[str, Tuple[int, int]]]], now: float):
+        """Turn the growth since the last read into per-second rates"""
+        elapsed = now - self.sampled_at if self.sampled_at is not None else None
+        if elapsed:
+            for interface, directions in counters.items():
+                before = self.previous.get(interface, {})
+                rates = {}
+                for direction in DIRECTIONS:
+                    protocols = {}
+                    for protocol, (packets, nbytes) in directions.get(direction, {}).items():
+                        old_packets, old_bytes = before.get(direction, {}).get(protocol, (0, 0))
+                        protocols[protocol] = {
+                            "bytes_per_s": round(max(0, nbytes - old_bytes) / elapsed, 1),
+                            "packets_per_s": round(max(0, packets - old_packets) / elapsed, 1)
+                        }
+                    rates[direction] = {
+                        "bytes_per_s": round(sum(p["bytes_per_s"] for p in protocols.values()), 1),
+                        "packets_per_s": round(sum(p["packets_per_s"] for p in protocols.values()), 1),
+                        "protocols": protocols
+                    }
+                self.rates[interface] = rates
+                series = self.series.setdefault(interface, deque(maxlen=int(HISTORY_SECONDS / self.interval)))
+                series.append((round(time.time(), 3), rates["rx"]["bytes_per_s"], rates["tx"]["bytes_per_s"]))
+        self.previous = counters
+        self.sampled_at = now
+
+    def snapshot(self, interface: Optional[str] = None) -> Dict:
+        """Latest rates per interface with the window's peaks; one interface also gets its series"""
+        names = [interface] if interface else sorted(self.rates)
+        interfaces = {}
+        for name in names:
+            series = list(self.series.get(name, ()))
+            interfaces[name] = dict(
+                self.rates.get(name, {}),
+                peak={"rx": max((s[1] for s in series), default=0), "tx": max((s[2] for s in series), default=0)}
+            )
+            if interface:
+                interfaces[name]["series"] = [list(s) for s in series]
+        return {
+            "enabled": True,
+            "active": self.active,
+            "method": self.backend.backend_name,
+            "reason": self.reason or self.last_error,
+            "interval_ms": int(self.interval * 1000),
+            "window": HISTORY_SECONDS,
+            "interfaces": interfaces
+        }
+
+    async def run(self):
+        if not await self.start():
+            return
+        next_sync = 0.0
+        while True:
+            now = time.monotonic()
+            if now >= next_sync:
+                await self.sync_interfaces()
+                next_sync = now + SYNC_INTERVAL
+            try:
+                self.sample(self.backend.read(), now)
+                self.last_error = None
+            except OSError as e:
+                if str(e) != self.last_error:
+                    logger.warning(f"eBPF counters unreadable: {e}")
+                self.last_error = str(e)
+            await asyncio.sleep(max(0.0, self.interval - (time.monotonic() - now)))
+
+    async def stop(self):
+        """Take our filters off the interfaces and unpin the programs"""
+        if self.active:
+            await self.backend.stop()
+            self.attached = []
+            self.active = False
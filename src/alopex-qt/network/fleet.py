@@ -0,0 +1,105 @@
+"""
+Fleet - The daemons one terminal manages
+The local daemon always comes first; fleet.json adds daemons on other machines
+reached over mutual TLS, each by the name the GUI's host selector and
+alopex-cli --host use. Certificate files given at the top apply to every host
+that doesn't name its own. A host with "via" instead of an address is reached
+through that host's daemon, a relay, which passes on requests that read state
+"""
+
+import os
+import json
+import socket
+import logging
+from dataclasses import dataclass
+from pathlib import Path
+from typing import Dict, List, Optional
+
+from .ipc import SOCKET_PATH, IpcClient, RemoteEndpoint, parse_remote
+
+# Configure logging
+logger = logging.getLogger(__name__)
+
+FLEET_FILE = Path(os.getenv("ALOPEX_FLEET", Path.home() / ".config" / "alopex" / "fleet.json"))
+CERT_FILES = ("ca_file", "cert_file", "key_file")
+# Always means this machine, whatever it is called
+LOCAL = "local"
+
+@dataclass
+class FleetHost:
+    """One daemon: the local one, one reached over TLS, or one behind a relay (via)"""
+    name: str
+    remote: Optional[RemoteEndpoint] = None
+    via: Optional["FleetHost"] = None
+
+    @property
+    def local(self) -> bool:
+        return self.remote is None and self.via is None
+
+    @property
+    def address(self) -> str:
+        if self.via:
+            return f"{self.name} via {self.via.address}"
+        return self.remote.address if self.remote else str(SOCKET_PATH)
+
+    def client(self, socket_path: Path = SOCKET_PATH, timeout: float = 5.0) -> IpcClient:
+        if self.via:
+            return IpcClient(socket_path, timeout, self.via.remote, relayed=self.name)
+        return IpcClient(socket_path, timeout, self.remote)
+
+def parse_fleet(config: Dict) -> List[FleetHost]:
+    """This machine, then the configured hosts in order; raises ValueError naming the bad entry"""
+    defaults = {key: config.get(key) for key in CERT_FILES}
+    hosts = [FleetHost(socket.gethostname())]
+    for index, spec in enumerate(config.get("hosts") or []):
+        if isinstance(spec, dict) and spec.get("via"):
+            hosts.append(relayed_host(hosts, spec))
+            continue
+        if not isinstance(spec, dict) or not spec.get("address"):
+            raise ValueError(f"fleet host {index + 1} needs an address (tls://host:port) or a relay to go via")
+        name = spec.get("name") or spec["address"]
+        files = {key: spec.get(key, defaults[key]) for key in CERT_FILES}
+        files = {key: os.path.expanduser(path) if path else None for key, path in files.items()}
+        try:
+            remote = parse_remote(spec["address"], **files)
+        except ValueError as e:
+            raise ValueError(f"fleet host {name}: {e}")
+        if not remote.cert_file:
+            raise ValueError(f"fleet host {name}: needs cert_file, the client certificate its daemon expects")
+        if name == LOCAL or any(host.name == name for host in hosts):
+            raise ValueError(f"fleet host name {name} is taken")
+        hosts.append(FleetHost(name, remote))
+    return hosts
+
+def relayed_host(hosts: List[FleetHost], spec: Dict) -> FleetHost:
+    """A host reached through a relay listed before it ("local" for this machine's daemon)"""
+    name = spec.get("name")
+    if not name:
+        raise ValueError(f"fleet host via {spec['via']} needs the name its relay knows it by")
+    relay = find_host(hosts, spec["via"])
+    if not relay:
+        raise ValueError(f"fleet host {name}: no relay {spec['via']} listed before it")
+    if relay.via:
+        raise ValueError(f"fleet host {name}: {relay.name} is itself behind a relay")
+    if name == LOCAL or any(host.name == name for host in hosts):
+        raise ValueError(f"fleet host name {name} is taken")
+    return FleetHost(name, via=relay)
+
+def load_fleet(path: Path = FLEET_FILE) -> List[FleetHost]:
+    """The fleet from fleet.json; just this machine when there is none. Raises ValueError for a bad file"""
+    if not path.exists():
+        return parse_fleet({})
+    try:
+        with open(path) as f:
+            config = json.load(f)
+    except (OSError, ValueError) as e:
+        raise ValueError(f"Cannot read {path}: {e}")
+    if not isinstance(config, dict):
+        raise ValueError(f"{path}: expected an object with a hosts list")
+    return parse_fleet(config)
+
+def find_host(hosts: List[FleetHost], name: str) -> Optional[FleetHost]:
+    """The host called name; "local" is always this machine"""
+    if name == LOCAL:
+        return hosts[0]
+    return next((host for host in hosts if host.name == name), None)
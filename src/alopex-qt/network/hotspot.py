@@ -0,0 +1,355 @@
+"""
+Hotspot - Sharing a connection over a WiFi access point
+hostapd runs the access point and masquerading shares the uplink; addresses
+come from the built-in DHCP server, stations from `iw station dump`
+"""
+
+import os
+import re
+import asyncio
+import logging
+import ipaddress
+import subprocess
+from pathlib import Path
+from typing import Callable, Dict, List, Optional, Tuple
+
+from .system_integration import communicate_or_terminate
+
+# Configure logging
+logger = logging.getLogger(__name__)
+
+RUN_DIR = Path("/run/alopex/hotspot")
+HOSTAPD_CTRL = "/run/hostapd"
+
+DEFAULT_ADDRESS = "10.42.0.1"
+DEFAULT_NETMASK = "255.255.255.0"
+DEFAULT_CHANNEL = 6
+POLL_INTERVAL = 2.0
+# kbit/s a per-client limit may be set to
+MAX_RATE = 10_000_000
+# HTB class for traffic to clients without a limit; limited ones start at LIMIT_CLASS
+UNLIMITED_CLASS = 1
+LIMIT_CLASS = 10
+
+MAC = re.compile(r"^[0-9a-f]{2}(:[0-9a-f]{2}){5}$")
+
+def normalize_mac(mac: str) -> Optional[str]:
+    """Lower-case colon form, or None if mac isn't one"""
+    mac = mac.strip().lower().replace("-", ":")
+    return mac if MAC.match(mac) else None
+
+def hotspot_network(address: Optional[str], netmask: Optional[str]) -> ipaddress.IPv4Interface:
+    """The hotspot's own address with its subnet, 10.42.0.1/24 unless the profile says otherwise"""
+    return ipaddress.IPv4Interface(f"{address or DEFAULT_ADDRESS}/{netmask or DEFAULT_NETMASK}")
+
+def admitted(mac: str, allowed: Optional[List[str]], blocked: List[str]) -> bool:
+    """Whether the access lists let a station join: never when blocked, and with an allow list only if on it"""
+    return mac not in blocked and (allowed is None or mac in allowed)
+
+def hostapd_config(interface: str, ssid: str, password: Optional[str], channel: int, deny_file: Path,
+                   accept_file: Optional[Path] = None, isolate: bool = False) -> str:
+    """hostapd.conf for a WPA2 (or, without a password, open) access point; with accept_file only the
+    MACs in it may join, with isolate stations can't reach each other"""
+    lines = [
+        f"interface={interface}",
+        "driver=nl80211",
+        f"ctrl_interface={HOSTAPD_CTRL}",
+        f"ssid={ssid}",
+        f"hw_mode={'a' if channel > 14 else 'g'}",
+        f"channel={channel}",
+        "ieee80211n=1",
+        "wmm_enabled=1",
+        f"ap_isolate={1 if isolate else 0}",
+        # hostapd checks deny_file first either way
+        f"macaddr_acl={1 if accept_file else 0}",
+        f"deny_mac_file={deny_file}"
+    ]
+    if accept_file:
+        lines.append(f"accept_mac_file={accept_file}")
+    if password:
+        lines += ["wpa=2", "wpa_key_mgmt=WPA-PSK", "rsn_pairwise=CCMP", f"wpa_passphrase={password}"]
+    return "\n".join(lines) + "\n"
+
+def isolation_rule(interface: str) -> List[str]:
+    """iptables FORWARD rule (after -A/-I/-D) that stops the host routing between two stations, which
+    ap_isolate alone doesn't prevent"""
+    return ["FORWARD", "-i", interface, "-o", interface, "-j", "DROP"]
+
+def limit_commands(interface: str, limits: Dict[str, Dict]) -> List[List[str]]:
+    """tc commands shaping each client by MAC: downloads in an HTB class on egress, uploads policed on
+    ingress. limits maps MAC to {"down", "up"} in kbit/s (None for no limit); run after clear_commands"""
+    commands = []
+    downloads = [(mac, limit["down"]) for mac, limit in sorted(limits.items()) if limit.get("down")]
+    uploads = [(mac, limit["up"]) for mac, limit in sorted(limits.items()) if limit.get("up")]
+    if downloads:
+        commands += [
+            ['tc', 'qdisc', 'add', 'dev', interface, 'root', 'handle', '1:', 'htb', 'default', str(UNLIMITED_CLASS)],
+            ['tc', 'class', 'add', 'dev', interface, 'parent', '1:', 'classid', f'1:{UNLIMITED_CLASS}',
+             'htb', 'rate', f'{MAX_RATE}kbit']
+        ]
+        for index, (mac, rate) in enumerate(downloads, LIMIT_CLASS):
+            commands += [
+                ['tc', 'class', 'add', 'dev', interface, 'parent', '1:', 'classid', f'1:{index}',
+                 'htb', 'rate', f'{rate}kbit', 'ceil', f'{rate}kbit'],
+                ['tc', 'filter', 'add', 'dev', interface, 'parent', '1:', 'protocol', 'all', 'prio', '1',
+                 'flower', 'dst_mac', mac, 'classid', f'1:{index}']
+            ]
+    if uploads:
+        commands.append(['tc', 'qdisc', 'add', 'dev', interface, 'handle', 'ffff:', 'ingress'])
+        for mac, rate in uploads:
+            # A tenth of a second at the rate, and never less than a few full-size frames
+            burst = max(rate * 125 // 10, 16 * 1024)
+            commands.append(['tc', 'filter', 'add', 'dev', interface, 'parent', 'ffff:', 'protocol', 'all',
+                             'prio', '1', 'flower', 'src_mac', mac, 'action', 'police', 'rate', f'{rate}kbit',
+                             'burst', str(burst), 'drop'])
+    return commands
+
+def clear_commands(interface: str) -> List[List[str]]:
+    """Remove whatever limit_commands set up; these fail harmlessly when there is nothing to remove"""
+    return [['tc', 'qdisc', 'del', 'dev', interface, 'root'],
+            ['tc', 'qdisc', 'del', 'dev', interface, 'ingress']]
+
+def parse_limit(text: str) -> Tuple[Optional[int], Optional[int]]:
+    """(down, up) kbit/s from "DOWN/UP", "DOWN" or "/UP"; empty or "-" means no limit. Raises ValueError"""
+    down, _, up = text.strip().partition("/")
+    try:
+        rates = tuple(int(part) if part.strip() not in ("", "-") else None for part in (down, up))
+    except ValueError:
+        raise ValueError(f"Not a rate: {text.strip()}")
+    if any(rate is not None and not 1 <= rate <= MAX_RATE for rate in rates):
+        raise ValueError(f"Rates are 1 to {MAX_RATE} kbit/s")
+    return rates
+
+def format_limit(limit: Optional[Dict]) -> Optional[str]:
+    """ "2000/500" in kbit/s, "-" for the unlimited direction; None without a limit"""
+    if not limit or not (limit.get("down") or limit.get("up")):
+        return None
+    return f"{limit.get('down') or '-'}/{limit.get('up') or '-'}"
+
+def parse_station_dump(text: str) -> List[Dict]:
+    """Stations from `iw dev X station dump`"""
+    stations = []
+    for line in text.splitlines():
+        header = re.match(r"^Station\s+([0-9a-fA-F:]{17})", line)
+        if header:
+            stations.append({"mac": header.group(1).lower(), "rx_bytes": 0, "tx_bytes": 0, "signal": None,
+                             "connected_time": None, "inactive_ms": None})
+            continue
+        if not stations or ":" not in line:
+            continue
+        key, _, value = line.strip().partition(":")
+        number = re.match(r"\s*(-?\d+)", value)
+        if not number:
+            continue
+        field = {"rx bytes": "rx_bytes", "tx bytes": "tx_bytes", "signal": "signal",
+                 "connected time": "connected_time", "inactive time": "inactive_ms"}.get(key.strip())
+        if field:
+            stations[-1][field] = int(number.group(1))
+    return stations
+
+async def _run(*args: str) -> Tuple[bool, str]:
+    """Run a command under sudo; (succeeded, its error output)"""
+    process = await asyncio.create_subprocess_exec(
+        'sudo', *args, stdout=asyncio.subprocess.PIPE, stderr=asyncio.subprocess.PIPE
+    )
+    stdout, stderr = await communicate_or_terminate(process)
+    return process.returncode == 0, (stderr or stdout).decode(errors="replace").strip()
+
+class HostapdHotspot:
+    """Access point plumbing for one interface at a time; state lives in RUN_DIR"""
+
+    @staticmethod
+    def _files(interface: str) -> Dict[str, Path]:
+        return {kind: RUN_DIR / f"{interface}.{kind}"
+                for kind in ("conf", "deny", "accept", "hostapd.pid")}
+
+    @staticmethod
+    async def start(interface: str, ssid: str, password: Optional[str], channel: int,
+                    network: ipaddress.IPv4Interface, blocked: List[str],
+                    allowed: Optional[List[str]] = None, isolate: bool = False) -> Tuple[bool, str]:
+        files = HostapdHotspot._files(interface)
+        RUN_DIR.mkdir(parents=True, exist_ok=True)
+        files["deny"].write_text("".join(f"{mac}\n" for mac in blocked))
+        files["accept"].write_text("".join(f"{mac}\n" for mac in allowed or []))
+        # Holds the passphrase, so it is root-only before anything is written to it
+        fd = os.open(files["conf"], os.O_WRONLY | os.O_CREAT | os.O_TRUNC, 0o600)
+        os.fchmod(fd, 0o600)
+        with os.fdopen(fd, 'w') as f:
+            f.write(hostapd_config(interface, ssid, password, channel, files["deny"],
+                                   files["accept"] if allowed is not None else None, isolate))
+
+        steps = [
+            ("ip", "addr", "flush", "dev", interface),
+            ("ip", "addr", "add", str(network), "dev", interface),
+            ("ip", "link", "set", interface, "up"),
+            ("hostapd", "-B", "-P", str(files["hostapd.pid"]), str(files["conf"])),
+            ("sysctl", "-q", "-w", "net.ipv4.ip_forward=1"),
+            ("iptables", "-t", "nat", "-A", "POSTROUTING", "-s", str(network.network),
+             "!", "-o", interface, "-j", "MASQUERADE")
+        ]
+        if isolate:
+            steps.append(("iptables", "-I", *isolation_rule(interface)))
+        for step in steps:
+            ok, output = await _run(*step)
+            if not ok:
+                logger.error(f"Hotspot on {interface}: {' '.join(step[:2])} failed: {output}")
+                await HostapdHotspot.stop(interface, network)
+                return False, f"{step[0]} failed: {output or 'no output'}"
+        return True, f"Sharing as {ssid} on channel {channel}"
+
+    @staticmethod
+    async def stop(interface: str, network: Optional[ipaddress.IPv4Interface] = None) -> bool:
+        """Undo start(); every step runs even if an earlier one fails"""
+        files = HostapdHotspot._files(interface)
+        pid = files["hostapd.pid"].read_text().strip() if files["hostapd.pid"].exists() else ""
+        if pid.isdigit():
+            await _run("kill", pid)
+        files["hostapd.pid"].unlink(missing_ok=True)
+        if network:
+            await _run("iptables", "-t", "nat", "-D", "POSTROUTING", "-s", str(network.network),
+                       "!", "-o", interface, "-j", "MASQUERADE")
+        # Fails harmlessly when the hotspot wasn't isolated
+        await _run("iptables", "-D", *isolation_rule(interface))
+        for command in clear_commands(interface):
+            await _run(*command)
+        ok, _ = await _run("ip", "addr", "flush", "dev", interface)
+        return ok
+
+    @staticmethod
+    async def set_limits(interface: str, limits: Dict[str, Dict]) -> bool:
+        """Replace the per-client rate limits on a running hotspot"""
+        for command in clear_commands(interface):
+            await _run(*command)
+        for command in limit_commands(interface, limits):
+            ok, output = await _run(*command)
+            if not ok:
+                logger.error(f"Hotspot on {interface}: {' '.join(command[:3])} failed: {output}")
+                for clear in clear_commands(interface):
+                    await _run(*clear)
+                return False
+        return True
+
+    @staticmethod
+    def stations(interface: str) -> List[Dict]:
+        """Associated stations; the DHCP server knows their addresses and hostnames"""
+        try:
+            result = subprocess.run(['iw', 'dev', interface, 'station', 'dump'],
+                                    capture_output=True, text=True, timeout=5)
+        except Exception as e:
+            logger.debug(f"Cannot list stations on {interface}: {e}")
+            return []
+        return parse_station_dump(result.stdout)
+
+    @staticmethod
+    def _hostapd_cli(interface: str, *args: str) -> bool:
+        try:
+            result = subprocess.run(['sudo', 'hostapd_cli', '-p', HOSTAPD_CTRL, '-i', interface, *args],
+                                    capture_output=True, text=True, timeout=5)
+            return result.returncode == 0 and "FAIL" not in result.stdout
+        except Exception as e:
+            logger.error(f"hostapd_cli {' '.join(args)} on {interface} failed: {e}")
+            return False
+
+    @staticmethod
+    def kick(interface: str, mac: str) -> bool:
+        """Deauthenticate a station; it may associate again straight away"""
+        return HostapdHotspot._hostapd_cli(interface, "deauthenticate", mac)
+
+    @staticmethod
+    def set_blocked(interface: str, mac: str, blocked: bool, all_blocked: List[str]) -> bool:
+        """Add a station to (or drop it from) the deny list, kicking it when blocked"""
+        deny_file = HostapdHotspot._files(interface)["deny"]
+        try:
+            deny_file.write_text("".join(f"{entry}\n" for entry in all_blocked))
+        except OSError as e:
+            logger.error(f"Cannot write {deny_file}: {e}")
+            return False
+        ok = HostapdHotspot._hostapd_cli(interface, "deny_acl", "ADD_MAC" if blocked else "DEL_MAC", mac)
+        if ok and blocked:
+            HostapdHotspot.kick(interface, mac)
+        return ok
+
+    @staticmethod
+    def set_filter(interface: str, allowed: Optional[List[str]], blocked: List[str]) -> bool:
+        """Switch between the allow list (allowed, a list) and letting everyone in (None), kicking stations
+        the lists now keep out"""
+        accept_file = HostapdHotspot._files(interface)["accept"]
+        try:
+            accept_file.write_text("".join(f"{entry}\n" for entry in allowed or []))
+        except OSError as e:
+            logger.error(f"Cannot write {accept_file}: {e}")
+            return False
+        commands = [("set", "macaddr_acl", "1" if allowed is not None else "0"), ("accept_acl", "CLEAR")]
+        commands += [("accept_acl", "ADD_MAC", mac) for mac in allowed or []]
+        if not all(HostapdHotspot._hostapd_cli(interface, *command) for command in commands):
+            return False
+        for station in HostapdHotspot.stations(interface):
+            if not admitted(station["mac"], allowed, blocked):
+                HostapdHotspot.kick(interface, station["mac"])
+        return True
+
+    @staticmethod
+    async def set_isolation(interface: str, isolate: bool) -> bool:
+        """Turn client isolation on or off on a running hotspot; hostapd reloads, so stations reassociate"""
+        conf = HostapdHotspot._files(interface)["conf"]
+        try:
+            text = conf.read_text()
+            conf.write_text(re.sub(r"^ap_isolate=\d$", f"ap_isolate={1 if isolate else 0}", text, flags=re.M))
+        except OSError as e:
+            logger.error(f"Cannot update {conf}: {e}")
+            return False
+        # -C first, so the rule is never added twice or removed when missing
+        present, _ = await _run("iptables", "-C", *isolation_rule(interface))
+        if isolate != present:
+            ok, output = await _run("iptables", "-I" if isolate else "-D", *isolation_rule(interface))
+            if not ok:
+                logger.error(f"Hotspot on {interface}: iptables failed: {output}")
+                return False
+        return HostapdHotspot._hostapd_cli(interface, "reload")
+
+class HotspotMonitor:
+    """Polls the stations of every running hotspot, publishing joins and departures"""
+
+    def __init__(self, backend, hotspots: Callable[[], List[str]], leases: Callable[[str], List[Dict]],
+                 events=None, interval: float = POLL_INTERVAL):
+        self.backend = backend
+        self.hotspots = hotspots
+        self.leases = leases
+        self.events = events
+        self.interval = interval
+        self.known: Dict[str, Dict[str, Dict]] = {}
+
+    def poll(self) -> Dict[str, List[Dict]]:
+        """Stations per hotspot interface, publishing changes since the last poll"""
+        current = {}
+        for interface in self.hotspots():
+            stations = self.backend.stations(interface)
+            leases = {lease["mac"]: lease for lease in self.leases(interface)}
+            current[interface] = {
+                s["mac"]: dict(s, ip=leases.get(s["mac"], {}).get("ip"),
+                               hostname=leases.get(s["mac"], {}).get("hostname"))
+                for s in stations
+            }
+        for interface, stations in current.items():
+            before = self.known.get(interface, {})
+            for mac in stations.keys() - before.keys():
+                self._publish("HotspotClientJoined", interface, stations[mac])
+            for mac in before.keys() - stations.keys():
+                self._publish("HotspotClientLeft", interface, before[mac])
+        self.known = current
+        return {interface: list(stations.values()) for interface, stations in current.items()}
+
+    def _publish(self, kind: str, interface: str, station: Dict):
+        logger.info(f"{station['mac']} ({station.get('hostname') or 'unknown'}) "
+                    f"{'joined' if kind == 'HotspotClientJoined' else 'left'} the hotspot on {interface}")
+        if self.events:
+            self.events.publish(kind, interface=interface, mac=station["mac"], hostname=station.get("hostname"),
+                                ip=station.get("ip"))
+
+    async def run(self):
+        while True:
+            try:
+                self.poll()
+            except Exception as e:
+                logger.error(f"Hotspot station poll failed: {e}")
+            await asyncio.sleep(self.interval)
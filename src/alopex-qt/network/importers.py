@@ -0,0 +1,460 @@
+"""
+Profile Importers - Carry connections over from other network stacks
+Each importer converts what it can into alopex profiles and reports the rest
+"""
+
+import logging
+import ipaddress
+import configparser
+from pathlib import Path
+from dataclasses import dataclass, field, asdict
+from typing import Callable, Dict, List, Optional
+
+from .connection_manager import ConnectionProfile
+
+# Configure logging
+logger = logging.getLogger(__name__)
+
+NM_CONNECTIONS_DIR = Path("/etc/NetworkManager/system-connections")
+NETWORKD_DIR = Path("/etc/systemd/network")
+NETPLAN_DIR = Path("/etc/netplan")
+SYS_CLASS_NET = Path("/sys/class/net")
+
+@dataclass
+class ImportReport:
+    """Profiles converted from one source, plus what could not be carried over"""
+    source: str
+    profiles: List[ConnectionProfile] = field(default_factory=list)
+    skipped: List[Dict] = field(default_factory=list)   # {"file", "name", "reason"}
+    warnings: List[Dict] = field(default_factory=list)  # {"file", "name", "message"}
+
+    def skip(self, path: Path, name: str, reason: str):
+        self.skipped.append({"file": str(path), "name": name, "reason": reason})
+        logger.info(f"Import {self.source}: skipped {name} ({reason})")
+
+    def warn(self, path: Path, name: str, message: str):
+        self.warnings.append({"file": str(path), "name": name, "message": message})
+
+    def to_dict(self) -> Dict:
+        profiles = []
+        for profile in self.profiles:
+            entry = asdict(profile)
+            entry.pop("password", None)
+            profiles.append(entry)
+        return {"source": self.source, "profiles": profiles, "skipped": self.skipped, "warnings": self.warnings}
+
+def netmask_for_prefix(prefix: int) -> str:
+    """Dotted-quad netmask for an IPv4 prefix length"""
+    return str(ipaddress.IPv4Network(f"0.0.0.0/{prefix}").netmask)
+
+def interface_for_mac(mac: str, sys_class_net: Path = SYS_CLASS_NET) -> Optional[str]:
+    """Find the local interface with a hardware address"""
+    mac = mac.lower()
+    for address_file in sys_class_net.glob("*/address"):
+        try:
+            if address_file.read_text().strip().lower() == mac:
+                return address_file.parent.name
+        except OSError:
+            continue
+    return None
+
+def _ipv4_fields(path: Path, name: str, report: ImportReport, dhcp: bool, addresses: List[str],
+                 gateway: Optional[str], dns: List[str]) -> Optional[Dict]:
+    """Profile method/address fields from generic DHCP/static settings, or None without IPv4 config"""
+    fields = {"dns_servers": dns}
+    ipv4 = []
+    for address in addresses:
+        try:
+            ipv4.append(ipaddress.IPv4Interface(address))
+        except ValueError:
+            report.warn(path, name, f"address {address} was not imported (only IPv4 is supported)")
+
+    if ipv4:
+        fields.update(method="static", ip_address=str(ipv4[0].ip),
+                      netmask=netmask_for_prefix(ipv4[0].network.prefixlen), gateway=gateway)
+        if len(ipv4) > 1:
+            report.warn(path, name, f"only the first of {len(ipv4)} IPv4 addresses was imported")
+        if dhcp:
+            report.warn(path, name, "DHCP alongside static addresses was dropped")
+    elif dhcp:
+        fields["method"] = "dhcp"
+    else:
+        return None
+    return fields
+
+# NetworkManager keyfiles
+
+NM_TYPES = {
+    "ethernet": "ethernet",
+    "802-3-ethernet": "ethernet",
+    "wifi": "wifi",
+    "802-11-wireless": "wifi",
+    "vpn": "vpn",
+    "wireguard": "vpn"
+}
+
+NM_SECURITY = {
+    "none": "Open",
+    "wpa-psk": "WPA2",
+    "sae": "WPA3"
+}
+
+def _read_keyfile(path: Path) -> configparser.ConfigParser:
+    parser = configparser.ConfigParser(interpolation=None, strict=False)
+    parser.optionxform = str  # keyfile keys are case-sensitive
+    parser.read(path)
+    return parser
+
+def _section(keyfile: configparser.ConfigParser, *names: str):
+    """First present section among NetworkManager's old and new names, or {}"""
+    return next((keyfile[name] for name in names if keyfile.has_section(name)), {})
+
+def _nm_ipv4(path: Path, name: str, keyfile: configparser.ConfigParser, report: ImportReport) -> Optional[Dict]:
+    """Translate [ipv4] into method/address fields, or None if it cannot be"""
+    ipv4 = _section(keyfile, "ipv4")
+    method = ipv4.get("method", "auto")
+    fields = {"dns_servers": [dns for dns in ipv4.get("dns", "").split(";") if dns]}
+
+    if method == "auto":
+        fields["method"] = "dhcp"
+    elif method == "manual":
+        addresses = sorted(key for key in ipv4 if key.startswith("address"))
+        if not addresses:
+            report.skip(path, name, "ipv4.method=manual without an address")
+            return None
+        # addressN=192.168.1.10/24[,gateway]
+        address, _, gateway = ipv4[addresses[0]].partition(",")
+        interface = ipaddress.IPv4Interface(address)
+        fields.update(method="static", ip_address=str(interface.ip),
+                      netmask=netmask_for_prefix(interface.network.prefixlen),
+                      gateway=gateway or ipv4.get("gateway"))
+        if len(addresses) > 1:
+            report.warn(path, name, f"only the first of {len(addresses)} IPv4 addresses was imported")
+    elif method == "disabled":
+        fields["method"] = "manual"
+    else:
+        report.skip(path, name, f"ipv4.method={method} is not supported")
+        return None
+
+    if _section(keyfile, "ipv6").get("method", "auto") == "manual":
+        report.warn(path, name, "static IPv6 addressing was not imported")
+    if ipv4.get("route1"):
+        report.warn(path, name, "static routes were not imported")
+    return fields
+
+def _nm_interface(path: Path, name: str, keyfile: configparser.ConfigParser,
+                  device: Dict, report: ImportReport) -> Optional[str]:
+    """interface-name, or the interface owning the profile's MAC address"""
+    interface = keyfile["connection"].get("interface-name")
+    if interface:
+        return interface
+
+    mac = device.get("mac-address")
+    interface = interface_for_mac(mac) if mac else None
+    if interface:
+        report.warn(path, name, f"bound by MAC address {mac}; imported for {interface}")
+    return interface
+
+def _convert_nm_keyfile(path: Path, report: ImportReport, wifi_interface: Optional[str]):
+    keyfile = _read_keyfile(path)
+    if not keyfile.has_section("connection"):
+        report.skip(path, path.stem, "no [connection] section")
+        return
+
+    connection = keyfile["connection"]
+    name = connection.get("id", path.stem)
+    nm_type = connection.get("type", "")
+    connection_type = NM_TYPES.get(nm_type)
+    if not connection_type:
+        report.skip(path, name, f"connection type '{nm_type}' is not supported")
+        return
+
+    fields = {
+        "auto_connect": connection.get("autoconnect", "true").lower() != "false",
+        "priority": int(connection.get("autoconnect-priority", "0"))
+    }
+
+    if connection_type == "vpn":
+        # alopex VPN profiles refer to a WireGuard/OpenVPN config by name
+        interface = connection.get("interface-name") or name
+        service = _section(keyfile, "vpn").get("service-type", nm_type)
+        report.warn(path, name, f"{service.rsplit('.', 1)[-1]} settings stay in NetworkManager's format; "
+                                f"provide a config named '{interface}' for the VPN backend")
+        report.profiles.append(ConnectionProfile(name=name, interface=interface, connection_type="vpn",
+                                                 method="manual", **fields))
+        return
+
+    if connection_type == "wifi":
+        device = wifi = _section(keyfile, "wifi", "802-11-wireless")
+        if wifi.get("mode", "infrastructure") != "infrastructure":
+            report.skip(path, name, f"WiFi mode '{wifi.get('mode')}' is not supported")
+            return
+        if not wifi.get("ssid"):
+            report.skip(path, name, "no SSID")
+            return
+
+        security = _section(keyfile, "wifi-security", "802-11-wireless-security")
+        key_mgmt = security.get("key-mgmt", "none")
+        if key_mgmt not in NM_SECURITY:
+            report.skip(path, name, f"WiFi key management '{key_mgmt}' (802.1X/WEP) is not supported")
+            return
+        fields.update(ssid=wifi["ssid"], security=NM_SECURITY[key_mgmt], password=security.get("psk"))
+        if key_mgmt != "none" and not fields["password"]:
+            report.warn(path, name, "the PSK is kept by a secret agent, not the keyfile; set it before connecting")
+    else:
+        device = _section(keyfile, "ethernet", "802-3-ethernet")
+
+    interface = _nm_interface(path, name, keyfile, device, report)
+    if not interface and connection_type == "wifi":
+        # NetworkManager lets WiFi profiles roam across adapters; alopex binds them
+        interface = wifi_interface
+    if not interface:
+        report.skip(path, name, "not bound to an interface name or a present MAC address")
+        return
+
+    ipv4 = _nm_ipv4(path, name, keyfile, report)
+    if ipv4 is None:
+        return
+    method = ipv4.pop("method")
+    fields.update(ipv4)
+
+    report.profiles.append(ConnectionProfile(name=name, interface=interface, connection_type=connection_type,
+                                             method=method, **fields))
+
+def import_networkmanager(directory: Optional[Path] = None, wifi_interface: Optional[str] = None) -> ImportReport:
+    """Convert NetworkManager keyfiles (*.nmconnection); unbound WiFi profiles go to wifi_interface"""
+    directory = Path(directory or NM_CONNECTIONS_DIR)
+    report = ImportReport(source="networkmanager")
+
+    for path in sorted(directory.glob("*.nmconnection")):
+        try:
+            _convert_nm_keyfile(path, report, wifi_interface)
+        except (configparser.Error, ValueError, KeyError) as e:
+            report.skip(path, path.stem, f"could not parse: {e}")
+
+    return report
+
+# systemd-networkd
+
+NETWORKD_KINDS = ("vlan", "bridge", "bond")
+
+def _read_unit(path: Path) -> Dict[str, Dict[str, List[str]]]:
+    """Parse a systemd unit-style file; keys may repeat, so values are lists"""
+    sections: Dict[str, Dict[str, List[str]]] = {}
+    current = None
+    for line in path.read_text().splitlines():
+        line = line.strip()
+        if not line or line[0] in "#;":
+            continue
+        if line.startswith("[") and line.endswith("]"):
+            current = sections.setdefault(line[1:-1], {})
+        elif "=" in line and current is not None:
+            key, value = line.split("=", 1)
+            current.setdefault(key.strip(), []).append(value.strip())
+    return sections
+
+def _words(values: List[str]) -> List[str]:
+    """Space-separated values across repeated keys (DNS=a b, DNS=c)"""
+    return [word for value in values for word in value.split()]
+
+def import_networkd(directory: Optional[Path] = None, wifi_interface: Optional[str] = None) -> ImportReport:
+    """Convert systemd-networkd .network/.netdev files, including VLANs, bridges and bonds"""
+    directory = Path(directory or NETWORKD_DIR)
+    report = ImportReport(source="networkd")
+
+    # Virtual devices: name -> (file, netdev spec)
+    netdevs: Dict[str, tuple] = {}
+    for path in sorted(directory.glob("*.netdev")):
+        unit = _read_unit(path)
+        netdev = unit.get("NetDev", {})
+        name, kind = (netdev.get("Name") or [path.stem])[0], (netdev.get("Kind") or [""])[0]
+        if kind not in NETWORKD_KINDS:
+            report.skip(path, name, f"netdev kind '{kind}' is not supported")
+            continue
+        spec = {"kind": kind}
+        if kind == "vlan":
+            spec["id"] = int((unit.get("VLAN", {}).get("Id") or ["0"])[0])
+        elif kind == "bond":
+            spec["mode"] = (unit.get("Bond", {}).get("Mode") or ["balance-rr"])[0]
+        if kind != "vlan":
+            spec["ports"] = []
+        netdevs[name] = (path, spec)
+
+    networks = []
+    for path in sorted(directory.glob("*.network")):
+        unit = _read_unit(path)
+        names = _words(unit.get("Match", {}).get("Name", []))
+        if len(names) != 1 or any(c in names[0] for c in "*?["):
+            macs = unit.get("Match", {}).get("MACAddress", [])
+            interface = interface_for_mac(macs[0]) if len(macs) == 1 and not names else None
+            if not interface:
+                report.skip(path, path.stem, "matches several interfaces; alopex profiles bind exactly one")
+                continue
+            report.warn(path, path.stem, f"matched by MAC address {macs[0]}; imported for {interface}")
+        else:
+            interface = names[0]
+        networks.append((path, interface, unit))
+
+    # Port and VLAN membership is declared on the lower link's .network
+    for path, interface, unit in networks:
+        network = unit.get("Network", {})
+        for master in network.get("Bridge", []) + network.get("Bond", []):
+            if master in netdevs and "ports" in netdevs[master][1]:
+                netdevs[master][1]["ports"].append(interface)
+        for vlan in network.get("VLAN", []):
+            if vlan in netdevs:
+                netdevs[vlan][1]["parent"] = interface
+
+    configured = set()
+    for path, interface, unit in networks:
+        network = unit.get("Network", {})
+        name = path.stem
+        if interface == wifi_interface:
+            report.skip(path, name, "WiFi association is done by wpa_supplicant here; import that instead")
+            continue
+
+        dhcp = (network.get("DHCP") or ["no"])[0].lower() in ("yes", "true", "ipv4", "both")
+        addresses = network.get("Address", []) + unit.get("Address", {}).get("Address", [])
+        gateways = network.get("Gateway", []) + unit.get("Route", {}).get("Gateway", [])
+        fields = _ipv4_fields(path, name, report, dhcp, addresses, gateways[0] if gateways else None,
+                              _words(network.get("DNS", [])))
+        if fields is None:
+            if network.get("Bridge") or network.get("Bond") or network.get("VLAN"):
+                continue  # only a lower link; its master carries the profile
+            if interface not in netdevs:
+                report.skip(path, name, "no IPv4 addressing configured")
+                continue
+            fields = {"method": "manual"}
+        if unit.get("Route", {}).get("Destination"):
+            report.warn(path, name, "static routes were not imported")
+
+        if interface in netdevs:
+            netdev = netdevs[interface][1]
+            if netdev["kind"] == "vlan" and "parent" not in netdev:
+                report.skip(path, name, f"no .network file lists {interface} under VLAN=")
+                continue
+            fields["netdev"] = netdev
+            configured.add(interface)
+
+        method = fields.pop("method")
+        report.profiles.append(ConnectionProfile(name=name, interface=interface, connection_type="ethernet",
+                                                 method=method, **fields))
+
+    for name, (path, spec) in netdevs.items():
+        if name not in configured:
+            report.skip(path, name, f"{spec['kind']} is not configured by any .network file")
+
+    return report
+
+# netplan
+
+def _netplan_interface(path: Path, device_id: str, config: Dict, report: ImportReport) -> Optional[str]:
+    """Kernel name of a netplan device: set-name, match by name/MAC, or the ID itself"""
+    if config.get("set-name"):
+        return config["set-name"]
+    match = config.get("match")
+    if not match:
+        return device_id
+    if match.get("name") and not any(c in match["name"] for c in "*?["):
+        return match["name"]
+    if match.get("macaddress"):
+        interface = interface_for_mac(match["macaddress"])
+        if interface:
+            report.warn(path, device_id, f"matched by MAC address {match['macaddress']}; imported for {interface}")
+        return interface
+    return None
+
+def _netplan_ipv4(path: Path, name: str, config: Dict, report: ImportReport) -> Optional[Dict]:
+    addresses = [a if isinstance(a, str) else next(iter(a)) for a in config.get("addresses", [])]
+    gateway = config.get("gateway4") or next(
+        (r.get("via") for r in config.get("routes", []) if r.get("to") in ("default", "0.0.0.0/0")), None)
+    if config.get("routes") and any(r.get("to") not in ("default", "0.0.0.0/0", "::/0") for r in config["routes"]):
+        report.warn(path, name, "static routes were not imported")
+    dns = config.get("nameservers", {}).get("addresses", [])
+    return _ipv4_fields(path, name, report, bool(config.get("dhcp4")), addresses, gateway, dns)
+
+def import_netplan(directory: Optional[Path] = None, wifi_interface: Optional[str] = None) -> ImportReport:
+    """Convert netplan YAML (ethernets, wifis, bridges, bonds, vlans)"""
+    directory = Path(directory or NETPLAN_DIR)
+    report = ImportReport(source="netplan")
+    try:
+        import yaml
+    except ImportError:
+        report.skip(directory, "netplan", "PyYAML is not installed")
+        return report
+
+    # Later files override earlier ones per device, as netplan merges them
+    devices: Dict[str, Dict[str, tuple]] = {}
+    for path in sorted(directory.glob("*.yaml")):
+        try:
+            network = (yaml.safe_load(path.read_text()) or {}).get("network", {})
+        except yaml.YAMLError as e:
+            report.skip(path, path.stem, f"could not parse: {e}")
+            continue
+        for section in ("ethernets", "wifis", "bridges", "bonds", "vlans"):
+            for device_id, config in (network.get(section) or {}).items():
+                previous = devices.setdefault(section, {}).get(device_id, (path, {}))[1]
+                devices[section][device_id] = (path, dict(previous, **(config or {})))
+        for section in set(network) - {"version", "renderer", "ethernets", "wifis", "bridges", "bonds", "vlans"}:
+            report.skip(path, section, f"netplan '{section}' devices are not supported")
+
+    ports = {port for section in ("bridges", "bonds")
+             for _, config in devices.get(section, {}).values() for port in config.get("interfaces", [])}
+
+    for device_id, (path, config) in devices.get("ethernets", {}).items():
+        interface = _netplan_interface(path, device_id, config, report)
+        if not interface:
+            report.skip(path, device_id, "matches several interfaces; alopex profiles bind exactly one")
+            continue
+        fields = _netplan_ipv4(path, device_id, config, report)
+        if fields is None:
+            if device_id not in ports:
+                report.skip(path, device_id, "no IPv4 addressing configured")
+            continue
+        method = fields.pop("method")
+        report.profiles.append(ConnectionProfile(name=device_id, interface=interface, connection_type="ethernet",
+                                                 method=method, **fields))
+
+    for device_id, (path, config) in devices.get("wifis", {}).items():
+        interface = _netplan_interface(path, device_id, config, report) or wifi_interface
+        fields = _netplan_ipv4(path, device_id, config, report) or {"method": "dhcp", "dns_servers": []}
+        for ssid, access_point in (config.get("access-points") or {}).items():
+            access_point = access_point or {}
+            if access_point.get("auth", {}).get("key-management", "psk") not in ("psk", "none"):
+                report.skip(path, ssid, "802.1X authentication is not supported")
+                continue
+            if access_point.get("mode", "infrastructure") != "infrastructure":
+                report.skip(path, ssid, f"WiFi mode '{access_point['mode']}' is not supported")
+                continue
+            password = access_point.get("password") or access_point.get("auth", {}).get("password")
+            profile_fields = dict(fields, ssid=ssid, password=password, security="WPA2" if password else "Open")
+            method = profile_fields.pop("method")
+            report.profiles.append(ConnectionProfile(name=ssid, interface=interface, connection_type="wifi",
+                                                     method=method, **profile_fields))
+
+    for section, kind in (("bridges", "bridge"), ("bonds", "bond"), ("vlans", "vlan")):
+        for device_id, (path, config) in devices.get(section, {}).items():
+            netdev = {"kind": kind}
+            if kind == "vlan":
+                if not config.get("link") or config.get("id") is None:
+                    report.skip(path, device_id, "VLAN without link or id")
+                    continue
+                netdev.update(parent=config["link"], id=int(config["id"]))
+            else:
+                netdev["ports"] = list(config.get("interfaces", []))
+                if kind == "bond":
+                    netdev["mode"] = config.get("parameters", {}).get("mode", "balance-rr")
+
+            fields = _netplan_ipv4(path, device_id, config, report) or {"method": "manual", "dns_servers": []}
+            method = fields.pop("method")
+            report.profiles.append(ConnectionProfile(name=device_id, interface=device_id, connection_type="ethernet",
+                                                     method=method, netdev=netdev, **fields))
+
+    return report
+
+# source name -> importer(path, wifi_interface)
+IMPORTERS: Dict[str, Callable[[Optional[Path], Optional[str]], ImportReport]] = {
+    "networkmanager": import_networkmanager,
+    "networkd": import_networkd,
+    "netplan": import_netplan
+}
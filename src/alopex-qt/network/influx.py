@@ -0,0 +1,179 @@
+"""
+InfluxDB Sink - Per-interface counters and rates pushed as line protocol
+Posts to an InfluxDB (v1 /write or v2 /api/v2/write) or Telegraf http_listener
+URL, or sends to a Telegraf socket_listener at udp://host:port, every interval
+"""
+
+import time
+import base64
+import socket
+import asyncio
+import logging
+import urllib.error
+import urllib.request
+from typing import Callable, Dict, Iterable, List, Optional, Tuple
+from urllib.parse import urlparse
+
+from .discovery import InterfaceType, NetworkInterface
+from .secret import Secret, reveal
+
+# Configure logging
+logger = logging.getLogger(__name__)
+
+DEFAULT_INTERVAL = 10.0
+MIN_INTERVAL = 1.0
+HTTP_TIMEOUT = 5.0
+MEASUREMENT = "alopex_interface"
+# Lines per UDP datagram stay under a typical MTU
+UDP_PAYLOAD = 1400
+
+COUNTERS = (
+    ("rx_bytes", "bytes_rx"), ("tx_bytes", "bytes_tx"), ("rx_packets", "packets_rx"), ("tx_packets", "packets_tx"),
+    ("rx_errors", "errors_rx"), ("tx_errors", "errors_tx"), ("rx_dropped", "dropped_rx"), ("tx_dropped", "dropped_tx")
+)
+
+def escape_key(value: str) -> str:
+    """Measurement, tag key/value or field key with commas, spaces and equals signs escaped"""
+    return str(value).replace("\\", "\\\\").replace(",", "\\,").replace(" ", "\\ ").replace("=", "\\=")
+
+def _field_value(value) -> str:
+    if isinstance(value, bool):
+        return "true" if value else "false"
+    if isinstance(value, int):
+        return f"{value}i"
+    if isinstance(value, float):
+        return repr(value)
+    return '"' + str(value).replace("\\", "\\\\").replace('"', '\\"') + '"'
+
+def format_line(measurement: str, tags: Dict[str, str], fields: Dict, timestamp_ns: int) -> str:
+    """One line of line protocol; empty tags are left out, as the format requires"""
+    tag_text = "".join(f",{escape_key(k)}={escape_key(v)}" for k, v in sorted(tags.items()) if v not in (None, ""))
+    field_text = ",".join(f"{escape_key(k)}={_field_value(v)}" for k, v in fields.items() if v is not None)
+    return f"{escape_key(measurement)}{tag_text} {field_text} {timestamp_ns}"
+
+def batch_lines(lines: List[str], size: int = UDP_PAYLOAD) -> List[bytes]:
+    """Newline-joined payloads of at most size bytes (a longer single line goes alone)"""
+    batches, current = [], b""
+    for line in lines:
+        encoded = line.encode() + b"\n"
+        if current and len(current) + len(encoded) > size:
+            batches.append(current)
+            current = b""
+        current += encoded
+    if current:
+        batches.append(current)
+    return batches
+
+class InfluxSink:
+    """Samples interfaces on an interval and pushes one line per interface"""
+
+    def __init__(self, url: str, interfaces: Callable[[], Iterable[NetworkInterface]],
+                 interval: float = DEFAULT_INTERVAL, token: Optional[str] = None,
+                 measurement: str = MEASUREMENT, tags: Optional[Dict[str, str]] = None):
+        parsed = urlparse(url)
+        if parsed.scheme not in ("http", "https", "udp") or not parsed.hostname:
+            raise ValueError(f"Expected an http(s):// write URL or udp://host:port, got {url!r}")
+        if parsed.scheme == "udp" and not parsed.port:
+            raise ValueError(f"UDP needs a port: {url!r}")
+        # urllib cannot take user:password@ in the URL; InfluxDB v1 accepts it as basic auth instead
+        netloc = parsed.hostname if ":" not in parsed.hostname else f"[{parsed.hostname}]"
+        netloc += f":{parsed.port}" if parsed.port else ""
+        self.parsed = parsed._replace(netloc=netloc)
+        self.url = self.parsed.geturl()
+        self.basic_auth = None
+        if parsed.username:
+            credentials = f"{parsed.username}:{parsed.password or ''}".encode()
+            self.basic_auth = Secret(base64.b64encode(credentials))
+        self.interfaces = interfaces
+        self.interval = max(MIN_INTERVAL, float(interval))
+        self.token = Secret.wrap(token)
+        self.measurement = measurement
+        self.tags = dict({"host": socket.gethostname()}, **(tags or {}))
+        self.previous: Dict[str, Tuple[float, int, int]] = {}
+        self.sent = 0
+        self.failures = 0
+        self.last_sent: Optional[float] = None
+        self.last_error: Optional[str] = None
+
+    def lines(self, interfaces: Iterable[NetworkInterface], now: Optional[float] = None) -> List[str]:
+        """Line protocol for every interface but loopback; rates need the previous sample"""
+        now = time.time() if now is None else now
+        lines = []
+        for interface in interfaces:
+            if interface.interface_type == InterfaceType.LOOPBACK:
+                continue
+            metrics = interface.metrics
+            fields = {name: int(getattr(metrics, attribute)) for name, attribute in COUNTERS}
+            previous = self.previous.get(interface.name)
+            self.previous[interface.name] = (now, metrics.bytes_rx, metrics.bytes_tx)
+            if previous and now > previous[0] and metrics.bytes_rx >= previous[1] and metrics.bytes_tx >= previous[2]:
+                fields["rx_rate"] = (metrics.bytes_rx - previous[1]) / (now - previous[0])
+                fields["tx_rate"] = (metrics.bytes_tx - previous[2]) / (now - previous[0])
+            fields["up"] = interface.status == "Connected"
+            tags = dict(self.tags, interface=interface.name, type=str(interface.interface_type))
+            lines.append(format_line(self.measurement, tags, fields, int(now * 1e9)))
+        return lines
+
+    def _send(self, lines: List[str]):
+        """Deliver one batch; raises OSError when the endpoint refuses or is unreachable"""
+        if self.parsed.scheme == "udp":
+            family = socket.AF_INET6 if ":" in self.parsed.hostname else socket.AF_INET
+            with socket.socket(family, socket.SOCK_DGRAM) as sock:
+                for payload in batch_lines(lines):
+                    sock.sendto(payload, (self.parsed.hostname, self.parsed.port))
+            return
+        request = urllib.request.Request(self.url, data="\n".join(lines).encode(), method="POST",
+                                         headers={"Content-Type": "text/plain; charset=utf-8"})
+        if self.token:
+            request.add_header("Authorization", f"Token {reveal(self.token)}")
+        elif self.basic_auth:
+            request.add_header("Authorization", f"Basic {reveal(self.basic_auth)}")
+        try:
+            with urllib.request.urlopen(request, timeout=HTTP_TIMEOUT) as response:
+                response.read()
+        except urllib.error.HTTPError as e:
+            detail = e.read().decode("utf-8", "replace").strip()[:200]
+            raise OSError(f"HTTP {e.code}{': ' + detail if detail else ''}")
+        except urllib.error.URLError as e:
+            raise OSError(str(e.reason))
+
+    async def push(self) -> bool:
+        """Sample and send once; failures are counted and logged when they start and stop"""
+        lines = self.lines(self.interfaces())
+        if not lines:
+            return True
+        try:
+            await asyncio.get_running_loop().run_in_executor(None, self._send, lines)
+        except OSError as e:
+            if self.last_error is None:
+                logger.warning(f"Cannot push metrics to {self.parsed.hostname}: {e}")
+            self.failures += 1
+            self.last_error = str(e)
+            return False
+        if self.last_error is not None:
+            logger.info(f"Pushing metrics to {self.parsed.hostname} again")
+        self.sent += len(lines)
+        self.last_sent = time.time()
+        self.last_error = None
+        return True
+
+    def stats(self) -> Dict:
+        """Where metrics go and how that is going; the URL without credentials"""
+        return {
+            "enabled": True,
+            "url": self.parsed._replace(netloc=urlparse(self.url).netloc, query="").geturl(),
+            "interval": self.interval,
+            "measurement": self.measurement,
+            "lines_sent": self.sent,
+            "failures": self.failures,
+            "last_sent": self.last_sent,
+            "last_error": self.last_error
+        }
+
+    async def run(self):
+        while True:
+            try:
+                await self.push()
+            except Exception as e:
+                logger.error(f"Metrics push failed: {e}")
+            await asyncio.sleep(self.interval)
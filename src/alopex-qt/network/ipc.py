@@ -0,0 +1,522 @@
+"""
+Daemon IPC - Control socket shared by alopexd, the GUI and the CLI tools
+Newline-delimited JSON requests over a Unix domain socket, or over TCP with
+mutual TLS for daemons on other machines
+"""
+
+import os
+import ssl
+import json
+import time
+import socket
+import struct
+import asyncio
+import inspect
+import logging
+import itertools
+from enum import Enum
+from pathlib import Path
+from dataclasses import dataclass, field
+from typing import Any, Callable, Dict, Iterable, Iterator, List, Optional, Tuple
+from urllib.parse import urlparse
+
+from .logs import log_context
+from .events import EventBus
+from .secret import json_default
+
+# Configure logging
+logger = logging.getLogger(__name__)
+
+SOCKET_PATH = Path(os.getenv("ALOPEX_SOCKET", "/run/alopex/alopexd.sock"))
+MAX_MESSAGE_SIZE = 1024 * 1024
+REMOTE_PORT = 7443
+
+# Requests that only read state; everything else is offered to the audit hook
+READ_ONLY_PREFIXES = ("Get", "List", "Wait")
+
+class ErrorCode(str, Enum):
+    """Machine-readable error categories carried in Error replies"""
+    NOT_FOUND = "NotFound"                        # No such interface, profile, ...
+    PERMISSION_DENIED = "PermissionDenied"        # Refused by policy or the kernel
+    BUSY = "Busy"                                 # Conflicting operation in progress
+    BACKEND_UNAVAILABLE = "BackendUnavailable"    # Backend tooling missing or failed
+    INVALID_CONFIG = "InvalidConfig"              # Bad parameter value or profile
+    INVALID_REQUEST = "InvalidRequest"            # Malformed or unknown request
+    INTERNAL = "Internal"                         # Unexpected daemon-side failure
+    CANCELLED = "Cancelled"                       # Job cancelled by a client
+    TIMED_OUT = "TimedOut"                        # Job exceeded its daemon-side timeout
+    DAEMON_UNAVAILABLE = "DaemonUnavailable"      # Client could not reach alopexd
+
+class IpcError(Exception):
+    """Error reported by the daemon or raised while talking to it"""
+
+    def __init__(self, message: str, code: ErrorCode = ErrorCode.INTERNAL,
+                 details: Optional[Dict] = None):
+        super().__init__(message)
+        self.message = message
+        self.code = ErrorCode(code)
+        self.details = details
+
+    def to_reply(self) -> Dict:
+        """Encode as an Error reply"""
+        reply = {"type": "Error", "code": self.code.value, "message": self.message}
+        if self.details:
+            reply["details"] = self.details
+        return reply
+
+    @classmethod
+    def from_reply(cls, reply: Dict) -> "IpcError":
+        """Decode an Error reply, tolerating daemons that predate error codes"""
+        try:
+            code = ErrorCode(reply.get("code", ErrorCode.INTERNAL))
+        except ValueError:
+            code = ErrorCode.INTERNAL
+        return cls(reply.get("message", "Unknown error"), code, reply.get("details"))
+
+@dataclass
+class RemoteEndpoint:
+    """A daemon on another machine, reached over TCP with mutual TLS: it checks our certificate against its CA,
+    we check its certificate against ca_file (the system store when unset)"""
+    host: str
+    port: int = REMOTE_PORT
+    ca_file: Optional[str] = None
+    cert_file: Optional[str] = None
+    key_file: Optional[str] = None
+    _context: Optional[ssl.SSLContext] = field(default=None, repr=False, compare=False)
+
+    @property
+    def address(self) -> str:
+        host = f"[{self.host}]" if ":" in self.host else self.host
+        return f"tls://{host}:{self.port}"
+
+    def context(self) -> ssl.SSLContext:
+        """Built once; raises OSError (ssl.SSLError, FileNotFoundError) for unusable certificate files"""
+        if self._context is None:
+            context = ssl.create_default_context(ssl.Purpose.SERVER_AUTH, cafile=self.ca_file)
+            if self.cert_file:
+                context.load_cert_chain(self.cert_file, self.key_file)
+            self._context = context
+        return self._context
+
+def parse_remote(address: str, **files) -> RemoteEndpoint:
+    """A RemoteEndpoint from tls://host[:port] (or plain host[:port]); raises ValueError"""
+    text = str(address or "").strip()
+    parsed = urlparse(text if "://" in text else f"tls://{text}")
+    try:
+        port = parsed.port or REMOTE_PORT
+    except ValueError:
+        raise ValueError(f"Bad port in {address!r}")
+    if parsed.scheme != "tls" or not parsed.hostname or parsed.path not in ("", "/"):
+        raise ValueError(f"Expected tls://host[:port], got {address!r}")
+    return RemoteEndpoint(parsed.hostname, port, **files)
+
+def server_tls_context(ca_file: str, cert_file: str, key_file: Optional[str] = None) -> ssl.SSLContext:
+    """TLS for the daemon's remote listener: our certificate, and clients must present one signed by ca_file.
+    Raises OSError when a file is missing or unusable"""
+    context = ssl.SSLContext(ssl.PROTOCOL_TLS_SERVER)
+    context.minimum_version = ssl.TLSVersion.TLSv1_2
+    context.verify_mode = ssl.CERT_REQUIRED
+    context.load_verify_locations(ca_file)
+    context.load_cert_chain(cert_file, key_file)
+    return context
+
+def certificate_name(peercert: Optional[Dict]) -> Optional[str]:
+    """The common name in a peer certificate as ssl decodes it"""
+    for rdn in (peercert or {}).get("subject", ()):
+        for key, value in rdn:
+            if key == "commonName":
+                return value
+    return None
+
+class IpcServer:
+    """Daemon side of the control socket
+
+    Each request is a JSON object with a "request" field naming the
+    operation; the remaining fields are passed to the registered handler.
+    Replies are {"type": "Ok", "data": ...} or
+    {"type": "Error", "code": ErrorCode, "message": ..., "details": {...}}.
+
+    A "Subscribe" request (optional "events" list) turns the connection into
+    an event stream of {"type": "Event", "event": {...}} lines.
+
+    start_remote() also serves the same requests over TCP with mutual TLS;
+    such clients have no uid and are known by their certificate's common
+    name (client_cert), optionally limited to an allow-list.
+
+    An "interface" parameter may name an interface by its label; the
+    resolve_interface callable turns it into the kernel name first.
+
+    With an access policy (see access.AccessPolicy), each connection gets
+    the caller's role and requests that need a higher one are refused. A
+    "Hello" request, the handshake, returns the role with capability flags
+    and the request types refused, so clients can hide those actions.
+
+    Every request that may change state, refused or not, is passed to the
+    audit callable as {timestamp, request, params, client_uid, client_cert,
+    request_id, outcome ("ok" or an ErrorCode), message}.
+
+    A secret.Secret anywhere in a reply or event goes out as "<redacted>".
+    """
+
+    def __init__(self, socket_path: Path = SOCKET_PATH, events: Optional[EventBus] = None,
+                 audit: Optional[Callable[[Dict], None]] = None,
+                 resolve_interface: Optional[Callable[[str], str]] = None, access=None):
+        self.socket_path = Path(socket_path)
+        self.handlers: Dict[str, Callable] = {}
+        self.server = None
+        self.remote_server = None
+        self.remote_clients: Optional[set] = None
+        self.request_ids = itertools.count(1)
+        self.events = events or EventBus()
+        self.audit = audit
+        # Maps an "interface" parameter (a label, say) to the kernel name handlers expect
+        self.resolve_interface = resolve_interface
+        self.access = access
+
+    def register(self, request_type: str, handler: Callable):
+        """Register handler (sync or async) for a request type"""
+        self.handlers[request_type] = handler
+
+    async def start(self):
+        """Start listening on the control socket"""
+        self.socket_path.parent.mkdir(parents=True, exist_ok=True)
+        if self.socket_path.exists():
+            self.socket_path.unlink()
+
+        self.server = await asyncio.start_unix_server(
+            self._handle_client, path=str(self.socket_path), limit=MAX_MESSAGE_SIZE
+        )
+        os.chmod(self.socket_path, 0o660)
+        logger.info(f"IPC listening on {self.socket_path}")
+
+    async def start_remote(self, host: str, port: int, context: ssl.SSLContext,
+                           clients: Optional[Iterable[str]] = None):
+        """Also serve clients on other machines over mutual TLS; clients limits them to these certificate
+        common names. Raises OSError when the address can't be bound"""
+        self.remote_clients = set(clients) if clients else None
+        self.remote_server = await asyncio.start_server(
+            self._handle_remote, host, port, ssl=context, limit=MAX_MESSAGE_SIZE
+        )
+        logger.info(f"IPC listening on tls://{host}:{port}")
+
+    async def stop(self):
+        """Stop listening and remove the socket"""
+        for server in (self.server, self.remote_server):
+            if server:
+                server.close()
+                await server.wait_closed()
+        self.server = self.remote_server = None
+        try:
+            self.socket_path.unlink()
+        except FileNotFoundError:
+            pass
+
+    async def _handle_client(self, reader: asyncio.StreamReader, writer: asyncio.StreamWriter):
+        """A client on the control socket, known by its uid"""
+        await self._serve(reader, writer, self._peer_uid(writer))
+
+    async def _handle_remote(self, reader: asyncio.StreamReader, writer: asyncio.StreamWriter):
+        """A client that got through the TLS handshake, so its certificate is signed by our CA"""
+        name = certificate_name(writer.get_extra_info("peercert"))
+        if self.remote_clients is not None and name not in self.remote_clients:
+            peer = writer.get_extra_info("peername")
+            logger.warning(f"Refused remote client {name or 'without a common name'} from {peer[0] if peer else '?'}")
+            error = IpcError(f"Certificate {name} is not allowed here", ErrorCode.PERMISSION_DENIED)
+            try:
+                writer.write(json.dumps(error.to_reply()).encode() + b"\n")
+                await writer.drain()
+            except (ConnectionResetError, BrokenPipeError):
+                pass
+            writer.close()
+            return
+        await self._serve(reader, writer, None, name)
+
+    async def _serve(self, reader: asyncio.StreamReader, writer: asyncio.StreamWriter, client_uid: Optional[int],
+                     client_cert: Optional[str] = None):
+        """Serve requests from one client until it disconnects"""
+        role = self.access.role(client_uid, client_cert) if self.access else None
+        try:
+            while True:
+                line = await reader.readline()
+                if not line:
+                    break
+
+                log_context.set({"request_id": next(self.request_ids), "client_uid": client_uid,
+                                 "client_cert": client_cert})
+                try:
+                    request_type, params = self._decode(line)
+                except IpcError as e:
+                    response = e.to_reply()
+                else:
+                    refusal = self._refusal(request_type, params, role, client_uid, client_cert)
+                    if request_type == "Hello":
+                        # Even callers with no role may ask what they can do
+                        response = {"type": "Ok", "data": self._hello(role, client_uid, client_cert)}
+                    elif refusal:
+                        response = refusal.to_reply()
+                    elif request_type == "Subscribe":
+                        await self._stream_events(reader, writer, params.get("events"))
+                        break
+                    else:
+                        response = await self._dispatch(request_type, params)
+                    if self.audit and request_type != "Hello" and not request_type.startswith(READ_ONLY_PREFIXES):
+                        self._audit(request_type, params, response, client_uid, client_cert)
+
+                writer.write(json.dumps(response, default=json_default).encode() + b"\n")
+                await writer.drain()
+        except (ConnectionResetError, BrokenPipeError):
+            pass
+        except Exception as e:
+            logger.error(f"IPC client error: {e}")
+        finally:
+            writer.close()
+
+    def _refusal(self, request_type: str, params: Dict, role: Optional[str], client_uid: Optional[int],
+                 client_cert: Optional[str]) -> Optional[IpcError]:
+        """The error for a request the caller's role doesn't cover, or None. A RelayRequest also needs the
+        role of the request it carries, so permissions that tighten a read request hold through the relay"""
+        if not self.access or request_type == "Hello":
+            return None
+        checked = [request_type]
+        if request_type == "RelayRequest" and isinstance(params.get("relayed"), str):
+            checked.append(params["relayed"])
+        denied = next((checked_type for checked_type in checked if not self.access.allows(role, checked_type)), None)
+        if denied is None:
+            return None
+        required = self.access.required(denied)
+        caller = f"certificate {client_cert}" if client_uid is None else f"uid {client_uid}"
+        logger.info(f"Refused {' of '.join(reversed(checked))} from {caller} (role {role or 'none'}, needs {required})")
+        return IpcError(f"{denied} needs the {required} role; yours is {role or 'none'}",
+                        ErrorCode.PERMISSION_DENIED, {"request": denied, "role": role, "required": required})
+
+    def _hello(self, role: Optional[str], client_uid: Optional[int], client_cert: Optional[str]) -> Dict:
+        """The handshake reply; without an access policy everything is allowed (capabilities None)"""
+        capabilities = self.access.capabilities(role, self.handlers) if self.access else None
+        return {"uid": client_uid, "certificate": client_cert, "role": role, "capabilities": capabilities}
+
+    def _audit(self, request_type: str, params: Dict, response: Dict, client_uid: Optional[int],
+               client_cert: Optional[str] = None):
+        """Hand one audit record to the hook; its failures never reach the client"""
+        record = {
+            "timestamp": time.time(),
+            "request": request_type,
+            "params": params,
+            "client_uid": client_uid,
+            "client_cert": client_cert,
+            "request_id": log_context.get().get("request_id"),
+            "outcome": "ok" if response["type"] == "Ok" else response["code"],
+            "message": response.get("message")
+        }
+        try:
+            self.audit(record)
+        except Exception as e:
+            logger.error(f"Audit hook failed for {request_type}: {e}")
+
+    @staticmethod
+    def _peer_uid(writer: asyncio.StreamWriter) -> Optional[int]:
+        """Read the connecting process's uid from SO_PEERCRED"""
+        try:
+            sock = writer.get_extra_info("socket")
+            creds = sock.getsockopt(socket.SOL_SOCKET, socket.SO_PEERCRED, struct.calcsize("3i"))
+            _pid, uid, _gid = struct.unpack("3i", creds)
+            return uid
+        except (OSError, AttributeError, struct.error):
+            return None
+
+    async def _stream_events(self, reader: asyncio.StreamReader, writer: asyncio.StreamWriter,
+                             kinds: Optional[List[str]] = None):
+        """Forward published events to a subscribed client until it hangs up"""
+        subscription = self.events.subscribe(kinds)
+        writer.write(json.dumps({"type": "Ok", "data": {"events": kinds}}).encode() + b"\n")
+        await writer.drain()
+
+        hangup = asyncio.create_task(reader.read())
+        try:
+            while True:
+                next_event = asyncio.create_task(subscription.get())
+                done, _ = await asyncio.wait({next_event, hangup}, return_when=asyncio.FIRST_COMPLETED)
+                if hangup in done:
+                    next_event.cancel()
+                    break
+
+                message = {"type": "Event", "event": next_event.result()}
+                writer.write(json.dumps(message, default=json_default).encode() + b"\n")
+                await writer.drain()
+        finally:
+            hangup.cancel()
+            self.events.unsubscribe(subscription)
+
+    @staticmethod
+    def _decode(line: bytes) -> Tuple[str, Dict]:
+        """Split a request line into its type and parameters"""
+        try:
+            message = json.loads(line)
+            request_type = message.pop("request")
+        except (ValueError, KeyError, AttributeError, TypeError):
+            raise IpcError("Malformed request", ErrorCode.INVALID_REQUEST)
+        return request_type, message
+
+    async def _dispatch(self, request_type: str, message: Dict) -> Dict:
+        """Run the handler for one request"""
+        handler = self.handlers.get(request_type)
+        if not handler:
+            return IpcError(f"Unknown request: {request_type}", ErrorCode.INVALID_REQUEST,
+                            {"request": request_type}).to_reply()
+
+        logger.debug(f"IPC request {request_type}")
+        if self.resolve_interface and isinstance(message.get("interface"), str):
+            message = dict(message, interface=self.resolve_interface(message["interface"]))
+
+        try:
+            result = handler(**message)
+            if inspect.isawaitable(result):
+                result = await result
+            return {"type": "Ok", "data": result}
+        except IpcError as e:
+            return e.to_reply()
+        except TypeError as e:
+            return IpcError(f"Invalid parameters for {request_type}: {e}", ErrorCode.INVALID_REQUEST,
+                            {"request": request_type}).to_reply()
+        except PermissionError as e:
+            return IpcError(str(e), ErrorCode.PERMISSION_DENIED).to_reply()
+        except Exception as e:
+            logger.exception(f"IPC handler {request_type} failed")
+            return IpcError(str(e), ErrorCode.INTERNAL).to_reply()
+
+class IpcClient:
+    """Blocking client for the daemon control socket, or for a remote daemon's TLS listener"""
+
+    def __init__(self, socket_path: Path = SOCKET_PATH, timeout: float = 5.0,
+                 remote: Optional[RemoteEndpoint] = None, relayed: Optional[str] = None):
+        self.socket_path = Path(socket_path)
+        self.timeout = timeout
+        self.remote = remote
+        # Downstream host the daemon we connect to, a relay, should pass requests on to
+        self.relayed = relayed
+        # Request types the daemon refuses us, from hello(); None until then
+        self.denied: Optional[set] = None
+
+    @property
+    def address(self) -> str:
+        address = self.remote.address if self.remote else str(self.socket_path)
+        return f"{self.relayed} via {address}" if self.relayed else address
+
+    def with_timeout(self, timeout: float) -> "IpcClient":
+        """A client for the same daemon that waits longer (or shorter) for replies"""
+        return IpcClient(self.socket_path, timeout, self.remote, self.relayed)
+
+    def switch(self, other: "IpcClient"):
+        """Talk to the daemon other talks to from now on; everything holding this client follows"""
+        self.socket_path, self.remote, self.relayed = other.socket_path, other.remote, other.relayed
+        self.denied = None
+
+    def hello(self) -> Dict:
+        """The handshake: our role and capabilities, remembering what we may not do for can()"""
+        reply = self.request("Hello")
+        self.denied = set((reply.get("capabilities") or {}).get("denied", []))
+        return reply
+
+    def can(self, request_type: str) -> bool:
+        """Whether the daemon accepts this request type from us, as far as hello() told; True before it"""
+        return not self.denied or request_type not in self.denied
+
+    def is_available(self) -> bool:
+        """Check whether the daemon socket exists (a remote daemon is assumed to)"""
+        return self.remote is not None or self.socket_path.exists()
+
+    def _connect(self) -> socket.socket:
+        """A connected socket; raises OSError (including TLS handshake failures)"""
+        if self.remote:
+            sock = socket.create_connection((self.remote.host, self.remote.port), timeout=self.timeout)
+            try:
+                return self.remote.context().wrap_socket(sock, server_hostname=self.remote.host)
+            except OSError:
+                sock.close()
+                raise
+        sock = socket.socket(socket.AF_UNIX, socket.SOCK_STREAM)
+        try:
+            sock.settimeout(self.timeout)
+            sock.connect(str(self.socket_path))
+        except OSError:
+            sock.close()
+            raise
+        return sock
+
+    def request(self, request_type: str, **params) -> Any:
+        """Send a request and return the reply data, raising IpcError on failure"""
+        message = dict(params, request=request_type)
+        if self.relayed:
+            message = {"request": "RelayRequest", "host": self.relayed, "relayed": request_type, "params": params}
+
+        try:
+            with self._connect() as sock:
+                sock.sendall(json.dumps(message).encode() + b"\n")
+
+                reply = b""
+                while not reply.endswith(b"\n"):
+                    chunk = sock.recv(65536)
+                    if not chunk:
+                        break
+                    reply += chunk
+                    if len(reply) > MAX_MESSAGE_SIZE:
+                        raise IpcError("Reply too large")
+        except OSError as e:
+            raise IpcError(f"Cannot reach alopexd at {self.address}: {e}", ErrorCode.DAEMON_UNAVAILABLE)
+        if not reply:
+            # With TLS 1.3 a daemon that rejects our certificate says so only by hanging up
+            hint = " (does its CA accept our certificate?)" if self.remote else ""
+            raise IpcError(f"alopexd at {self.address} closed the connection{hint}", ErrorCode.DAEMON_UNAVAILABLE)
+
+        try:
+            response = json.loads(reply)
+        except ValueError:
+            raise IpcError("Malformed reply from alopexd")
+
+        if response.get("type") != "Ok":
+            raise IpcError.from_reply(response)
+        return response.get("data")
+
+    def run_job(self, request_type: str, timeout: float = 60.0, **params) -> Any:
+        """Start a job-returning request, wait for it and return its result"""
+        job = self.request(request_type, **params)
+        waiter = self.with_timeout(self.timeout + timeout)
+        job = waiter.request("WaitJob", id=job["id"], timeout=timeout)
+
+        if job["state"] in ("failed", "cancelled"):
+            raise IpcError.from_reply(job["error"])
+        if job["state"] != "completed":
+            raise IpcError(f"Job {job['id']} still running after {timeout}s", ErrorCode.BUSY, {"job": job})
+        return job["result"]
+
+    def subscribe(self, events: Optional[List[str]] = None) -> Iterator[Dict]:
+        """Yield daemon events as they happen (blocks; run it in a thread)"""
+        if self.relayed:
+            raise IpcError(f"Events of {self.relayed} can't be followed through a relay", ErrorCode.INVALID_REQUEST)
+        try:
+            sock = self._connect()
+        except OSError as e:
+            raise IpcError(f"Cannot reach alopexd at {self.address}: {e}", ErrorCode.DAEMON_UNAVAILABLE)
+        try:
+            sock.sendall(json.dumps({"request": "Subscribe", "events": events}).encode() + b"\n")
+        except OSError as e:
+            sock.close()
+            raise IpcError(f"Cannot reach alopexd at {self.address}: {e}", ErrorCode.DAEMON_UNAVAILABLE)
+
+        with sock, sock.makefile("rb") as stream:
+            acknowledgement = json.loads(stream.readline() or b"{}")
+            if acknowledgement.get("type") != "Ok":
+                raise IpcError.from_reply(acknowledgement)
+
+            sock.settimeout(None)
+            for line in stream:
+                message = json.loads(line)
+                if message.get("type") == "Event":
+                    yield message["event"]
+
+    def try_request(self, request_type: str, default: Optional[Any] = None, **params) -> Any:
+        """Send a request, returning default if the daemon is unreachable or refuses"""
+        try:
+            return self.request(request_type, **params)
+        except IpcError as e:
+            logger.debug(f"IPC {request_type} failed: {e}")
+            return default
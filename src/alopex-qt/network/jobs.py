@@ -0,0 +1,159 @@
+"""
+Daemon Jobs - Long-running operations tracked by ID
+Connects, scans and VPN handshakes return a job immediately and report
+JobProgress / JobCompleted / JobFailed / JobCancelled events while they run
+"""
+
+import time
+import asyncio
+import logging
+import itertools
+from collections import OrderedDict
+from dataclasses import dataclass, field
+from typing import Any, Awaitable, Callable, Dict, List, Optional
+
+from .events import EventBus
+from .ipc import IpcError, ErrorCode
+
+# Configure logging
+logger = logging.getLogger(__name__)
+
+# Finished jobs kept around for GetJob/WaitJob after they complete
+MAX_FINISHED_JOBS = 100
+
+# Called by an operation to report how far along it is
+ProgressCallback = Callable[[Optional[int], str], None]
+
+@dataclass
+class Job:
+    """One tracked operation"""
+    id: int
+    kind: str
+    description: str
+    state: str = "running"  # running, completed, failed, cancelled
+    progress: Optional[int] = None  # percent, None if unknown
+    message: str = ""
+    result: Any = None
+    error: Optional[Dict] = None
+    timeout: Optional[float] = None  # seconds
+    created_at: float = field(default_factory=time.time)
+    finished_at: Optional[float] = None
+    task: Optional[asyncio.Task] = field(default=None, repr=False)
+
+    @property
+    def finished(self) -> bool:
+        return self.state != "running"
+
+    def to_dict(self) -> Dict:
+        return {
+            "id": self.id,
+            "kind": self.kind,
+            "description": self.description,
+            "state": self.state,
+            "progress": self.progress,
+            "message": self.message,
+            "result": self.result,
+            "error": self.error,
+            "timeout": self.timeout,
+            "created_at": self.created_at,
+            "finished_at": self.finished_at
+        }
+
+class JobManager:
+    """Run operations as background tasks and publish their progress"""
+
+    def __init__(self, events: EventBus):
+        self.events = events
+        self.jobs: "OrderedDict[int, Job]" = OrderedDict()
+        self.ids = itertools.count(1)
+
+    def start(self, kind: str, description: str,
+              operation: Callable[[ProgressCallback], Awaitable[Any]],
+              timeout: Optional[float] = None) -> Job:
+        """Start operation(progress) in the background and return its job
+
+        The operation is cancelled if it runs longer than timeout seconds.
+        """
+        job = Job(id=next(self.ids), kind=kind, description=description, timeout=timeout)
+        self.jobs[job.id] = job
+        job.task = asyncio.create_task(self._run(job, operation))
+
+        logger.info(f"Job {job.id} started: {description}")
+        self.events.publish("JobStarted", job=job.to_dict())
+        return job
+
+    async def _run(self, job: Job, operation: Callable[[ProgressCallback], Awaitable[Any]]):
+        def report(progress: Optional[int], message: str):
+            job.progress = progress
+            job.message = message
+            self.events.publish("JobProgress", job=job.to_dict())
+
+        try:
+            job.result = await asyncio.wait_for(operation(report), job.timeout)
+            job.state = "completed"
+            job.progress = 100
+            job.message = "Done"
+        except asyncio.CancelledError:
+            job.state = "cancelled"
+            job.error = IpcError(f"{job.description} cancelled", ErrorCode.CANCELLED).to_reply()
+            job.message = "Cancelled"
+        except asyncio.TimeoutError:
+            job.state = "failed"
+            job.error = IpcError(f"{job.description} timed out after {job.timeout:g}s", ErrorCode.TIMED_OUT,
+                                 {"timeout": job.timeout}).to_reply()
+            job.message = job.error["message"]
+        except IpcError as e:
+            job.state = "failed"
+            job.error = e.to_reply()
+            job.message = e.message
+        except Exception as e:
+            logger.exception(f"Job {job.id} ({job.kind}) crashed")
+            job.state = "failed"
+            job.error = IpcError(str(e), ErrorCode.INTERNAL).to_reply()
+            job.message = str(e)
+
+        job.finished_at = time.time()
+        if job.state == "completed":
+            logger.info(f"Job {job.id} completed: {job.description}")
+            self.events.publish("JobCompleted", job=job.to_dict())
+        elif job.state == "cancelled":
+            logger.info(f"Job {job.id} cancelled: {job.description}")
+            self.events.publish("JobCancelled", job=job.to_dict())
+        else:
+            logger.warning(f"Job {job.id} failed: {job.message}")
+            self.events.publish("JobFailed", job=job.to_dict())
+        self._prune()
+
+    def _prune(self):
+        """Forget the oldest finished jobs beyond MAX_FINISHED_JOBS"""
+        finished = [job_id for job_id, job in self.jobs.items() if job.finished]
+        for job_id in finished[:max(0, len(finished) - MAX_FINISHED_JOBS)]:
+            del self.jobs[job_id]
+
+    def get(self, job_id: int) -> Job:
+        """Look up a job, raising NotFound for unknown or pruned IDs"""
+        job = self.jobs.get(job_id)
+        if not job:
+            raise IpcError(f"No such job: {job_id}", ErrorCode.NOT_FOUND, {"job": job_id})
+        return job
+
+    async def cancel(self, job_id: int) -> Dict:
+        """Cancel a running job and wait for its cleanup; finished jobs are left as they are"""
+        job = self.get(job_id)
+        if not job.finished:
+            job.task.cancel()
+            await asyncio.wait({job.task})
+        return job.to_dict()
+
+    def list(self, include_finished: bool = True) -> List[Dict]:
+        return [job.to_dict() for job in self.jobs.values() if include_finished or not job.finished]
+
+    async def wait(self, job_id: int, timeout: Optional[float] = None) -> Dict:
+        """Wait for a job to finish; returns its state even if the wait timed out"""
+        job = self.get(job_id)
+        if not job.finished:
+            try:
+                await asyncio.wait_for(asyncio.shield(job.task), timeout)
+            except asyncio.TimeoutError:
+                pass
+        return job.to_dict()
@@ -0,0 +1,124 @@
+"""
+Interface Labels - Friendly names and groups for interfaces
+Labels like "WAN" or "Mgmt" are shown beside kernel names and accepted wherever
+a request takes an interface; groups ("LAB") select several at once
+"""
+
+import os
+import re
+import json
+import logging
+from pathlib import Path
+from typing import Dict, List, Optional
+
+# Configure logging
+logger = logging.getLogger(__name__)
+
+# Short enough for the interface list, and never mistaken for a path or an option
+NAME_PATTERN = re.compile(r"^[A-Za-z0-9][A-Za-z0-9 ._-]{0,31}$")
+
+class LabelError(ValueError):
+    """A label or group name that cannot be used"""
+
+class InterfaceLabels:
+    """Labels and groups per kernel interface name, kept in labels.json"""
+
+    def __init__(self, config_path: Path):
+        self.labels_file = config_path / "labels.json"
+        self.interfaces: Dict[str, Dict] = {}
+        self._load()
+
+    def _load(self):
+        if not self.labels_file.exists():
+            return
+        try:
+            with open(self.labels_file) as f:
+                data = json.load(f)
+            self.interfaces = {
+                name: {"label": entry.get("label"), "groups": list(entry.get("groups", []))}
+                for name, entry in data.get("interfaces", {}).items()
+            }
+            logger.info(f"Loaded labels for {len(self.interfaces)} interfaces")
+        except Exception as e:
+            logger.error(f"Failed to load interface labels: {e}")
+
+    def _save(self):
+        """Save labels via a temp file"""
+        self.labels_file.parent.mkdir(parents=True, exist_ok=True)
+        temp_file = self.labels_file.with_suffix(".tmp")
+        with open(temp_file, "w") as f:
+            json.dump({"interfaces": self.interfaces}, f, indent=2)
+        os.replace(temp_file, self.labels_file)
+
+    def label(self, interface: str) -> Optional[str]:
+        return self.interfaces.get(interface, {}).get("label")
+
+    def groups(self, interface: str) -> List[str]:
+        return list(self.interfaces.get(interface, {}).get("groups", []))
+
+    def set(self, interface: str, label: Optional[str] = None, groups: Optional[List[str]] = None,
+            known: Optional[List[str]] = None) -> Dict:
+        """Give an interface a label and groups; an empty label and no groups forget it.
+        known lists current kernel names, which no label may shadow"""
+        label = label.strip() if label else None
+        groups = [group.strip() for group in (groups or [])]
+        for name in ([label] if label else []) + groups:
+            if not NAME_PATTERN.match(name):
+                raise LabelError(f"Invalid name: {name!r} (letters, digits, space, '.', '_' or '-', up to 32)")
+        if label:
+            if label != interface and label in (known or []):
+                raise LabelError(f"{label} is already an interface name")
+            owner = next((name for name, entry in self.interfaces.items()
+                          if name != interface and (entry.get("label") or "").lower() == label.lower()), None)
+            if owner:
+                raise LabelError(f"{owner} is already labelled {self.interfaces[owner]['label']}")
+
+        # Case differences alone don't make two groups
+        spelled = {}
+        for group in groups:
+            spelled.setdefault(group.lower(), self._group_spelling(group))
+        groups = list(spelled.values())
+        if label or groups:
+            self.interfaces[interface] = {"label": label, "groups": groups}
+        else:
+            self.interfaces.pop(interface, None)
+        self._save()
+        return self.entry(interface)
+
+    def _group_spelling(self, group: str) -> str:
+        """The spelling already in use for a group, so 'lab' joins an existing 'LAB'"""
+        for entry in self.interfaces.values():
+            for existing in entry.get("groups", []):
+                if existing.lower() == group.lower():
+                    return existing
+        return group
+
+    def rename(self, old: str, new: str):
+        """Keep an interface's label and groups when its kernel name changes"""
+        if old in self.interfaces:
+            self.interfaces[new] = self.interfaces.pop(old)
+            self._save()
+
+    def entry(self, interface: str) -> Dict:
+        return {"interface": interface, "label": self.label(interface), "groups": self.groups(interface)}
+
+    def list(self) -> List[Dict]:
+        return [self.entry(name) for name in sorted(self.interfaces)]
+
+    def members(self, group: str) -> List[str]:
+        """Interfaces in a group, matched without regard to case"""
+        return sorted(name for name, entry in self.interfaces.items()
+                      if any(g.lower() == group.lower() for g in entry.get("groups", [])))
+
+    def resolve(self, selector: str) -> str:
+        """The kernel name a label stands for; anything else is passed through unchanged"""
+        for name, entry in self.interfaces.items():
+            if (entry.get("label") or "").lower() == selector.lower() and selector != name:
+                return name
+        return selector
+
+    def annotate(self, interfaces):
+        """Fill in label and groups on discovered interfaces"""
+        for interface in interfaces:
+            interface.label = self.label(interface.name)
+            interface.groups = self.groups(interface.name)
@@ -0,0 +1,207 @@
+"""
+LAN Scan - Devices on the subnet of a connected interface
+A UDP nudge to every address fills the kernel's ARP table, which then gives IP
+and MAC; names come from unicast mDNS and NetBIOS queries, vendors from the OUI list
+"""
+
+import json
+import time
+import random
+import select
+import socket
+import struct
+import logging
+import ipaddress
+import subprocess
+from typing import Callable, Dict, List, Optional, Tuple
+
+from . import oui
+
+# Configure logging
+logger = logging.getLogger(__name__)
+
+# Wider subnets are narrowed to the /24 around our own address
+SWEEP_MIN_PREFIX = 23
+# How long the kernel gets to collect ARP replies after the sweep
+ARP_WAIT = 2.0
+NAME_TIMEOUT = 1.5
+DISCARD_PORT = 9
+MDNS_PORT = 5353
+NETBIOS_PORT = 137
+
+# ip neigh states that mean the address answered
+ANSWERED_STATES = {"REACHABLE", "STALE", "DELAY", "PROBE", "PERMANENT"}
+
+RTYPE_PTR = 12
+RTYPE_NBSTAT = 0x21
+# Top bit of the class asks an mDNS responder for a unicast reply
+CLASS_IN_UNICAST = 0x8001
+
+def sweep_targets(address: ipaddress.IPv4Interface) -> List[str]:
+    """Every host address worth nudging on address's subnet, except address itself"""
+    network = address.network
+    if network.prefixlen < SWEEP_MIN_PREFIX:
+        network = ipaddress.ip_network(f"{address.ip}/24", strict=False)
+    return [str(host) for host in network.hosts() if host != address.ip]
+
+def arp_sweep(interface: str, targets: List[str], wait: float = ARP_WAIT) -> Dict[str, str]:
+    """{ip: mac} of every target that answered ARP on interface"""
+    with socket.socket(socket.AF_INET, socket.SOCK_DGRAM) as sock:
+        try:
+            sock.setsockopt(socket.SOL_SOCKET, socket.SO_BINDTODEVICE, interface.encode())
+        except OSError:
+            # Unprivileged; the subnet route sends them out of interface anyway
+            pass
+        for target in targets:
+            try:
+                sock.sendto(b"", (target, DISCARD_PORT))
+            except OSError:
+                pass
+    time.sleep(wait)
+    wanted = set(targets)
+    return {ip: mac for ip, mac in read_neighbours(interface).items() if ip in wanted}
+
+def read_neighbours(interface: str) -> Dict[str, str]:
+    """{ip: mac} of the IPv4 neighbours on interface that have answered"""
+    try:
+        result = subprocess.run(["ip", "-j", "-4", "neigh", "show", "dev", interface],
+                                capture_output=True, text=True, timeout=5)
+        entries = json.loads(result.stdout or "[]")
+    except Exception as e:
+        logger.warning(f"Could not read the neighbour table of {interface}: {e}")
+        return {}
+    return {entry["dst"]: entry["lladdr"].lower() for entry in entries
+            if entry.get("lladdr") and ANSWERED_STATES & set(entry.get("state", []))}
+
+def _encode_name(name: str) -> bytes:
+    return b"".join(bytes([len(part)]) + part.encode() for part in name.rstrip(".").split(".")) + b"\x00"
+
+def _read_name(message: bytes, offset: int) -> Tuple[str, int]:
+    """A (possibly compressed) name and the offset just past it"""
+    labels, end, jumps = [], None, 0
+    while True:
+        length = message[offset]
+        if length & 0xC0 == 0xC0:
+            if end is None:
+                end = offset + 2
+            jumps += 1
+            if jumps > 16:
+                raise ValueError("name compression loop")
+            offset = struct.unpack("!H", message[offset:offset + 2])[0] & 0x3FFF
+            continue
+        if length == 0:
+            return ".".join(labels), end if end is not None else offset + 1
+        labels.append(message[offset + 1:offset + 1 + length].decode("utf-8", "replace"))
+        offset += 1 + length
+
+def mdns_query(ip: str, query_id: int) -> bytes:
+    """Reverse lookup of ip, asked of the device itself"""
+    reverse = ipaddress.ip_address(ip).reverse_pointer
+    header = struct.pack("!HHHHHH", query_id, 0, 1, 0, 0, 0)
+    return header + _encode_name(reverse) + struct.pack("!HH", RTYPE_PTR, CLASS_IN_UNICAST)
+
+def parse_mdns_reply(message: bytes) -> Optional[str]:
+    """Host name (without .local) from the first PTR answer"""
+    try:
+        questions, answers = struct.unpack("!HH", message[4:8])
+        offset = 12
+        for _ in range(questions):
+            offset = _read_name(message, offset)[1] + 4
+        for _ in range(answers):
+            offset = _read_name(message, offset)[1]
+            rtype, _, _, length = struct.unpack("!HHIH", message[offset:offset + 10])
+            offset += 10
+            if rtype == RTYPE_PTR:
+                name = _read_name(message, offset)[0]
+                return name[:-len(".local")] if name.endswith(".local") else name or None
+            offset += length
+    except (IndexError, ValueError, struct.error):
+        pass
+    return None
+
+def netbios_query(ip: str, query_id: int) -> bytes:
+    """Node status request for the wildcard name"""
+    encoded = b"".join(bytes([0x41 + (byte >> 4), 0x41 + (byte & 0x0F)]) for byte in b"*" + b"\x00" * 15)
+    header = struct.pack("!HHHHHH", query_id, 0, 1, 0, 0, 0)
+    return header + b"\x20" + encoded + b"\x00" + struct.pack("!HH", RTYPE_NBSTAT, 1)
+
+def parse_netbios_reply(message: bytes) -> Optional[str]:
+    """The workstation name (unique, suffix 00) from a node status response"""
+    try:
+        offset = _read_name(message, 12)[1]
+        rtype = struct.unpack("!H", message[offset:offset + 2])[0]
+        if rtype != RTYPE_NBSTAT:
+            return None
+        offset += 10
+        count = message[offset]
+        offset += 1
+        for index in range(count):
+            entry = message[offset + index * 18:offset + (index + 1) * 18]
+            if len(entry) < 18:
+                break
+            flags = struct.unpack("!H", entry[16:18])[0]
+            if entry[15] == 0x00 and not flags & 0x8000:
+                return entry[:15].decode("ascii", "replace").strip() or None
+    except (IndexError, ValueError, struct.error):
+        pass
+    return None
+
+def _query_all(ips: List[str], port: int, build: Callable[[str, int], bytes],
+               parse: Callable[[bytes], Optional[str]], timeout: float) -> Dict[str, str]:
+    """Send build(ip) to every ip from one socket and collect parsed replies until timeout"""
+    names: Dict[str, str] = {}
+    pending = set(ips)
+    with socket.socket(socket.AF_INET, socket.SOCK_DGRAM) as sock:
+        sock.setblocking(False)
+        for ip in ips:
+            try:
+                sock.sendto(build(ip, random.randrange(1 << 16)), (ip, port))
+            except OSError:
+                pending.discard(ip)
+        deadline = time.monotonic() + timeout
+        while pending:
+            remaining = deadline - time.monotonic()
+            if remaining <= 0 or not select.select([sock], [], [], remaining)[0]:
+                break
+            try:
+                message, (source, _) = sock.recvfrom(4096)
+            except OSError:
+                continue
+            if source in pending:
+                name = parse(message)
+                if name:
+                    names[source] = name
+                    pending.discard(source)
+    return names
+
+def name_hints(ips: List[str], timeout: float = NAME_TIMEOUT) -> Dict[str, Tuple[str, str]]:
+    """{ip: (hostname, "mdns" or "netbios")} for the devices that say who they are"""
+    hints = {ip: (name, "mdns") for ip, name in
+             _query_all(ips, MDNS_PORT, mdns_query, parse_mdns_reply, timeout).items()}
+    unnamed = [ip for ip in ips if ip not in hints]
+    if unnamed:
+        for ip, name in _query_all(unnamed, NETBIOS_PORT, netbios_query, parse_netbios_reply, timeout).items():
+            hints[ip] = (name, "netbios")
+    return hints
+
+def scan(interface: str, address: ipaddress.IPv4Interface, wait: float = ARP_WAIT,
+         timeout: float = NAME_TIMEOUT) -> List[Dict]:
+    """Sweep address's subnet on interface; one {ip, mac, hostname, name_source} per device that answered"""
+    targets = sweep_targets(address)
+    logger.info(f"Sweeping {len(targets)} addresses on {interface}")
+    found = arp_sweep(interface, targets, wait)
+    hints = name_hints(list(found), timeout) if found else {}
+    return [{"ip": ip, "mac": mac, "hostname": hints.get(ip, (None, None))[0],
+             "name_source": hints.get(ip, (None, None))[1]} for ip, mac in found.items()]
+
+def annotate(devices: List[Dict], address: ipaddress.IPv4Interface, mac: Optional[str],
+             gateway: Optional[str]) -> List[Dict]:
+    """Add ourselves, vendors and roles; sorted by address"""
+    devices = [dict(device) for device in devices if device["ip"] != str(address.ip)]
+    devices.append({"ip": str(address.ip), "mac": mac, "hostname": socket.gethostname(), "name_source": "self"})
+    for device in devices:
+        device["vendor"] = oui.lookup_vendor(device["mac"])
+        device["randomized_mac"] = bool(device["mac"]) and oui.is_locally_administered(device["mac"])
+        device["role"] = ("self" if device["ip"] == str(address.ip) else
+                          "gateway" if device["ip"] == gateway else None)
+    return sorted(devices, key=lambda device: ipaddress.ip_address(device["ip"]))
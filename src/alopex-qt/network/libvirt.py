@@ -0,0 +1,149 @@
+"""
+Libvirt - Virtual networks behind virbr bridges and the guests on them
+Reads each network's bridge and subnet from virsh, and pairs the guests'
+interfaces with the DHCP leases libvirt's dnsmasq handed out
+"""
+
+import re
+import asyncio
+import logging
+import ipaddress
+import xml.etree.ElementTree as ElementTree
+from typing import Callable, Dict, List, Optional
+
+# Configure logging
+logger = logging.getLogger(__name__)
+
+DEFAULT_URI = "qemu:///system"
+DEFAULT_INTERVAL = 30.0
+# libvirt's own naming for the bridges it creates
+BRIDGE_PATTERN = re.compile(r"^virbr\d+$")
+MAC_PATTERN = re.compile(r"^[0-9a-f]{2}(:[0-9a-f]{2}){5}$")
+
+def parse_network_xml(text: str) -> Dict:
+    """Bridge, forward mode and subnets from virsh net-dumpxml"""
+    root = ElementTree.fromstring(text)
+    bridge = root.find("bridge")
+    forward = root.find("forward")
+    subnets = []
+    for ip in root.findall("ip"):
+        address = ip.get("address")
+        prefix = ip.get("prefix") or ip.get("netmask")
+        if address and prefix:
+            try:
+                subnets.append(str(ipaddress.ip_interface(f"{address}/{prefix}").network))
+            except ValueError:
+                continue
+    return {
+        "name": root.findtext("name"),
+        "bridge": bridge.get("name") if bridge is not None else None,
+        # No <forward> is an isolated network
+        "forward": forward.get("mode", "nat") if forward is not None else "isolated",
+        "subnets": subnets
+    }
+
+def _table_rows(text: str) -> List[List[str]]:
+    """Rows of a virsh table, without its header and rule"""
+    lines = text.splitlines()
+    start = next((i + 1 for i, line in enumerate(lines) if line.strip().startswith("---")), len(lines))
+    return [line.split() for line in lines[start:] if line.strip()]
+
+def parse_leases(text: str) -> List[Dict]:
+    """virsh net-dhcp-leases: expiry date and time, MAC, protocol, address, hostname, client ID"""
+    leases = []
+    for fields in _table_rows(text):
+        if len(fields) < 5 or not MAC_PATTERN.match(fields[2].lower()):
+            continue
+        hostname = fields[5] if len(fields) > 5 and fields[5] != "-" else None
+        leases.append({"mac": fields[2].lower(), "ip": fields[4].split("/")[0], "hostname": hostname})
+    return leases
+
+def parse_domiflist(text: str) -> List[Dict]:
+    """virsh domiflist: interface, type, source, model, MAC"""
+    interfaces = []
+    for fields in _table_rows(text):
+        if len(fields) < 5 or not MAC_PATTERN.match(fields[4].lower()):
+            continue
+        interfaces.append({
+            "interface": fields[0] if fields[0] != "-" else None,
+            "type": fields[1],
+            "source": fields[2],
+            "mac": fields[4].lower()
+        })
+    return interfaces
+
+def attach_guests(network: Dict, leases: List[Dict], domains: Dict[str, List[Dict]]) -> Dict:
+    """The network with its guests: running domains plugged into it, and any lease no domain explains"""
+    by_mac = {lease["mac"]: lease for lease in leases}
+    guests = []
+    for domain, interfaces in sorted(domains.items()):
+        for nic in interfaces:
+            if nic["source"] not in (network["name"], network["bridge"]):
+                continue
+            lease = by_mac.pop(nic["mac"], {})
+            guests.append({"domain": domain, "mac": nic["mac"], "ip": lease.get("ip"),
+                           "hostname": lease.get("hostname"), "interface": nic["interface"]})
+    for lease in by_mac.values():
+        guests.append({"domain": None, "mac": lease["mac"], "ip": lease["ip"], "hostname": lease["hostname"],
+                       "interface": None})
+    return dict(network, guests=guests)
+
+def guess_network(bridge: str) -> Optional[Dict]:
+    """A virbr bridge libvirt didn't tell us about (libvirtd not reachable)"""
+    if not BRIDGE_PATTERN.match(bridge):
+        return None
+    return {"name": None, "bridge": bridge, "forward": None, "subnets": [], "active": True, "guests": []}
+
+class VirtualNetworks:
+    """libvirt networks and their guests, refreshed every so often and matched to bridges"""
+
+    def __init__(self, backend, interfaces: Callable[[], List[str]], uri: str = DEFAULT_URI, events=None,
+                 interval: float = DEFAULT_INTERVAL):
+        self.backend = backend
+        self.interfaces = interfaces
+        self.uri = uri
+        self.events = events
+        self.interval = interval
+        self.networks: List[Dict] = []
+        self.error: Optional[str] = None
+
+    async def refresh(self):
+        try:
+            reported = await self.backend.networks(self.uri)
+            error = None
+        except OSError as e:
+            reported = []
+            error = str(e) or e.__class__.__name__
+        if error and error != self.error:
+            logger.warning(f"Could not list libvirt networks: {error}")
+        self.error = error
+
+        present = self.interfaces()
+        networks = [network for network in reported if network["bridge"] in present or not network["active"]]
+        known = {network["bridge"] for network in networks}
+        networks += [guess for guess in map(guess_network, sorted(set(present) - known)) if guess]
+        guests = {network["bridge"]: len(network["guests"]) for network in networks}
+        if self.events and guests != {network["bridge"]: len(network["guests"]) for network in self.networks}:
+            self.events.publish("VirtualNetworksChanged", guests=guests)
+        self.networks = networks
+
+    def network(self, bridge: str) -> Optional[Dict]:
+        return next((network for network in self.networks if network["bridge"] == bridge), None)
+
+    def annotate(self, interfaces):
+        """Name the libvirt network behind each virbr bridge"""
+        for interface in interfaces:
+            network = self.network(interface.name)
+            interface.virtual_network = (network["name"] or "libvirt") if network else None
+
+    def snapshot(self) -> Dict:
+        return {"uri": self.uri, "error": self.error, "networks": self.networks}
+
+    async def run(self):
+        # virsh can take a while, so the first look happens here rather than before startup finishes
+        while True:
+            try:
+                await self.refresh()
+            except Exception as e:
+                logger.error(f"libvirt network refresh failed: {e}")
+            await asyncio.sleep(self.interval)
@@ -0,0 +1,90 @@
+"""
+Link Names - Renaming interfaces over rtnetlink, and keeping the name
+RTM_NEWLINK with IFLA_IFNAME renames in place (downing the link when the kernel
+insists); a systemd .link file matched on the MAC makes udev reapply it at boot
+"""
+
+import os
+import re
+import socket
+import struct
+import logging
+from pathlib import Path
+from typing import List, Optional, Tuple
+
+from .netlink import NLM_F_ACK, NLM_F_REQUEST, attr, message, parse_ack, rtnetlink_request
+
+# Configure logging
+logger = logging.getLogger(__name__)
+
+LINK_DIR = Path("/etc/systemd/network")
+LINK_PREFIX = "10-alopex-"
+RTM_NEWLINK = 16
+IFLA_IFNAME = 3
+IFF_UP = 0x1
+EBUSY = 16
+# IFNAMSIZ less the terminating NUL; no '/', ':' or whitespace, and never "." or ".."
+NAME_PATTERN = re.compile(r"^[^/:\s]{1,15}$")
+
+def valid_name(name: str) -> bool:
+    return bool(NAME_PATTERN.match(name)) and name not in (".", "..")
+
+def build_setlink(index: int, name: Optional[str] = None, flags: int = 0, change: int = 0, seq: int = 1) -> bytes:
+    """RTM_NEWLINK for an existing link: a new name, and/or flags under the change mask"""
+    body = struct.pack("=BxHiII", socket.AF_UNSPEC, 0, index, flags, change)
+    if name is not None:
+        body += attr(IFLA_IFNAME, name.encode() + b"\0")
+    return message(RTM_NEWLINK, NLM_F_REQUEST | NLM_F_ACK, body, seq)
+
+def _setlink(index: int, **changes):
+    rtnetlink_request(build_setlink(index, **changes))
+
+def link_is_up(interface: str) -> bool:
+    try:
+        return bool(int(Path(f"/sys/class/net/{interface}/flags").read_text(), 16) & IFF_UP)
+    except (OSError, ValueError):
+        return False
+
+def rename_link(old: str, new: str) -> bool:
+    """Rename a link, downing it for the rename if the kernel refuses while it is up.
+    Returns whether it had to be downed; raises OSError (CAP_NET_ADMIN is needed)"""
+    index = socket.if_nametoindex(old)
+    try:
+        _setlink(index, name=new)
+        return False
+    except OSError as e:
+        if e.errno != EBUSY:
+            raise
+    was_up = link_is_up(old)
+    _setlink(index, flags=0, change=IFF_UP)
+    try:
+        _setlink(index, name=new)
+    finally:
+        if was_up:
+            _setlink(index, flags=IFF_UP, change=IFF_UP)
+    return True
+
+def link_file(name: str, mac: str, permanent: bool) -> str:
+    """A .link file naming whichever device has this MAC"""
+    key = "PermanentMACAddress" if permanent else "MACAddress"
+    return (f"# Written by alopexd (RenameInterface); delete to go back to the kernel's name\n"
+            f"[Match]\n{key}={mac.lower()}\n\n[Link]\nName={name}\n")
+
+def _matches(path: Path, mac: str) -> bool:
+    try:
+        return any(line.split("=", 1)[-1].strip().lower() == mac.lower()
+                   for line in path.read_text().splitlines() if "MACAddress=" in line)
+    except OSError:
+        return False
+
+def write_link_file(link_dir: Path, name: str, mac: str, permanent: bool) -> Tuple[Path, List[Path]]:
+    """Persist the name, replacing earlier files of ours for the same device; returns the file and those removed"""
+    link_dir.mkdir(parents=True, exist_ok=True)
+    removed = [path for path in sorted(link_dir.glob(f"{LINK_PREFIX}*.link")) if _matches(path, mac)]
+    for path in removed:
+        path.unlink()
+    path = link_dir / f"{LINK_PREFIX}{name}.link"
+    temp_file = path.with_suffix(".tmp")
+    temp_file.write_text(link_file(name, mac, permanent))
+    os.replace(temp_file, path)
+    return path, removed
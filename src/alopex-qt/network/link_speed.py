@@ -0,0 +1,125 @@
+"""
+Link Speed - Wired links that negotiated below what they can do
+Compares each Ethernet link's negotiated speed with the fastest mode the NIC
+supports (from ethtool); a gigabit port that came up at 100 Mb/s usually has
+a damaged cable, since 100BASE-TX needs only two of the four pairs
+"""
+
+import re
+import asyncio
+import logging
+import subprocess
+from typing import Dict, Iterable, List, Optional
+
+from .discovery import InterfaceType, NetworkInterface
+
+# Configure logging
+logger = logging.getLogger(__name__)
+
+CHECK_INTERVAL = 30.0
+MODE_SPEED = re.compile(r"\b(\d+)base")
+
+def parse_ethtool(text: str) -> Dict:
+    """{"max_speed", "partner_max_speed"} in Mb/s from `ethtool IFACE`; None where it doesn't say"""
+    modes: Dict[str, List[str]] = {}
+    key = None
+    for line in text.splitlines():
+        if not line.strip():
+            continue
+        name, colon, value = line.strip().partition(":")
+        # Mode lists wrap onto lines of their own, without a key
+        if colon and not MODE_SPEED.match(line.strip()):
+            key = name.strip()
+            modes[key] = [value]
+        elif key:
+            modes[key].append(line)
+
+    def fastest(key: str) -> Optional[int]:
+        speeds = [int(speed) for speed in MODE_SPEED.findall(" ".join(modes.get(key, [])))]
+        return max(speeds) if speeds else None
+
+    return {"max_speed": fastest("Supported link modes"),
+            "partner_max_speed": fastest("Link partner advertised link modes")}
+
+def assess(interface: str, speed: Optional[int], duplex: Optional[str], max_speed: Optional[int],
+           partner_max_speed: Optional[int]) -> Optional[Dict]:
+    """A warning for a link below its NIC's best (unless the other end is what holds it back) or at half duplex"""
+    if not speed or speed <= 0:
+        return None
+    warning = {"interface": interface, "speed": speed, "max_speed": max_speed,
+               "partner_max_speed": partner_max_speed, "duplex": duplex}
+    if max_speed and speed < max_speed and not (partner_max_speed and partner_max_speed <= speed):
+        if partner_max_speed:
+            cause = (f"the other end offers {partner_max_speed} Mb/s too, so the cable is the likely culprit; "
+                     f"replace it")
+        else:
+            cause = "check the cable, then the port at the other end"
+        return dict(warning, message=f"{interface} negotiated {speed} Mb/s but the NIC does {max_speed} Mb/s: {cause}")
+    if duplex == "half":
+        return dict(warning, message=f"{interface} runs at half duplex, usually because autonegotiation is off "
+                                     f"on one end; expect collisions and slow transfers")
+    return None
+
+def read_link_modes(interface: str) -> Optional[Dict]:
+    """parse_ethtool() of an interface, or None without ethtool or an answer"""
+    try:
+        result = subprocess.run(['ethtool', interface], capture_output=True, text=True, timeout=5)
+    except Exception as e:
+        logger.debug(f"Cannot run ethtool on {interface}: {e}")
+        return None
+    return parse_ethtool(result.stdout) if result.returncode == 0 else None
+
+class LinkSpeedMonitor:
+    """Assesses the connected Ethernet links; publishes LinkSpeedDegraded when one falls short and
+    LinkSpeedRestored once it no longer does"""
+
+    def __init__(self, discovery, host, events=None, interval: float = CHECK_INTERVAL):
+        self.discovery = discovery
+        self.host = host
+        self.events = events
+        self.interval = interval
+        self.modes: Dict[str, Dict] = {}
+        self.warnings: Dict[str, Dict] = {}
+
+    async def check(self) -> Dict[str, Dict]:
+        """Warnings by interface, after publishing what changed"""
+        current, modes = {}, {}
+        for interface in self.discovery.discover_interfaces():
+            if interface.interface_type != InterfaceType.ETHERNET or interface.status != "Connected":
+                continue
+            link_modes = await self.host.link_modes(interface.name)
+            if not link_modes:
+                continue
+            modes[interface.name] = dict(link_modes, speed=interface.metrics.link_speed)
+            warning = assess(interface.name, interface.metrics.link_speed, interface.metrics.duplex,
+                             link_modes["max_speed"], link_modes["partner_max_speed"])
+            if warning:
+                current[interface.name] = warning
+
+        for name, warning in current.items():
+            if self.warnings.get(name, {}).get("message") != warning["message"]:
+                logger.warning(warning["message"])
+                if self.events:
+                    self.events.publish("LinkSpeedDegraded", **warning)
+        for name in self.warnings.keys() - current.keys():
+            # Unplugged links drop their warning quietly; the next link up is assessed afresh
+            if name in modes:
+                logger.info(f"{name} now runs at its full speed")
+                if self.events:
+                    self.events.publish("LinkSpeedRestored", interface=name, speed=modes[name]["speed"])
+        self.modes, self.warnings = modes, current
+        return current
+
+    def annotate(self, interfaces: Iterable[NetworkInterface]):
+        """Fill in the NIC's top speed and any warning on the last checked links"""
+        for interface in interfaces:
+            interface.metrics.max_link_speed = self.modes.get(interface.name, {}).get("max_speed")
+            interface.metrics.link_warning = self.warnings.get(interface.name, {}).get("message")
+
+    async def run(self):
+        while True:
+            try:
+                await self.check()
+            except Exception as e:
+                logger.error(f"Link speed check failed: {e}")
+            await asyncio.sleep(self.interval)
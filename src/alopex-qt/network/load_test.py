@@ -0,0 +1,140 @@
+"""
+Load Test - Latency while the link is saturated
+Idle round trips say little about a link with deep buffers: the queue only
+fills under load, which is when a call stalls behind a download. This pings a
+target at rest, then again while parallel transfers fill the link, and reports
+both along with the throughput the transfers reached
+"""
+
+import time
+import asyncio
+import logging
+from typing import Callable, Dict, List, Optional
+
+from . import shaping
+from .sla import round_stats
+from .system_integration import communicate_or_terminate
+
+# Configure logging
+logger = logging.getLogger(__name__)
+
+DEFAULT_TARGET = "1.1.1.1"
+DEFAULT_SECONDS = 10
+MIN_SECONDS = 2
+MAX_SECONDS = 60
+STREAMS = 4
+DOWNLOAD_URL = "https://speed.cloudflare.com/__down?bytes=25000000000"
+UPLOAD_URL = "https://speed.cloudflare.com/__up"
+DIRECTIONS = ("download", "upload")
+PING_INTERVAL = 0.2
+PING_TIMEOUT = 2.0
+# TCP needs a moment to fill the queue before loaded round trips mean anything
+WARMUP_FRACTION = 0.25
+# Grades by the latency load adds, in ms (the usual bufferbloat scale): under 5 is A+, 400 and over F
+GRADES = ((5, "A+"), (30, "A"), (60, "B"), (200, "C"), (400, "D"))
+# Shaping a little under the measured rate keeps the queue in the host, where cake can manage it
+SHAPING_FRACTION = 0.9
+
+def curl_command(interface: str, url: str, seconds: float, direction: str) -> List[str]:
+    """One transfer stream bound to the interface, printing the bytes it moved when time is up"""
+    command = ['curl', '-s', '-o', '/dev/null', '--interface', interface, '--max-time', f"{seconds:g}"]
+    if direction == "upload":
+        return command + ['-X', 'POST', '-T', '/dev/zero', '-w', '%{size_upload}', url]
+    return command + ['-w', '%{size_download}', url]
+
+def parse_size(text: str) -> int:
+    """Bytes from curl's -w output; older versions print a float"""
+    try:
+        return int(float(text.strip() or 0))
+    except ValueError:
+        return 0
+
+def mbps(size: int, seconds: float) -> Optional[float]:
+    return round(size * 8 / seconds / 1_000_000, 1) if seconds > 0 else None
+
+async def saturate(interface: str, direction: str, seconds: float, url: Optional[str] = None) -> Dict:
+    """Run STREAMS transfers for seconds: {"bytes", "seconds", "mbps"}; raises OSError if none moved data"""
+    url = url or (UPLOAD_URL if direction == "upload" else DOWNLOAD_URL)
+    started = time.monotonic()
+    processes = [await asyncio.create_subprocess_exec(
+        *curl_command(interface, url, seconds, direction),
+        stdout=asyncio.subprocess.PIPE, stderr=asyncio.subprocess.DEVNULL
+    ) for _ in range(STREAMS)]
+    outputs = await asyncio.gather(*(communicate_or_terminate(process) for process in processes))
+    elapsed = time.monotonic() - started
+    size = sum(parse_size(stdout.decode(errors="replace")) for stdout, _ in outputs)
+    if not size:
+        codes = {process.returncode for process in processes}
+        raise OSError(f"no data moved over {interface} (curl exited with {', '.join(map(str, sorted(codes)))})")
+    return {"bytes": size, "seconds": round(elapsed, 1), "mbps": mbps(size, elapsed)}
+
+def ping_count(seconds: float) -> int:
+    """Echo requests that fit in the loaded part of a phase"""
+    return max(3, int(seconds * (1 - WARMUP_FRACTION) / PING_INTERVAL) - 1)
+
+async def measure(host, interface: str, target: str = DEFAULT_TARGET, seconds: float = DEFAULT_SECONDS,
+                  direction: str = "download", url: Optional[str] = None,
+                  progress: Optional[Callable[[Optional[int], str], None]] = None) -> Dict:
+    """Idle round trips, then round trips under load, through host (a HostBackend). Raises OSError when the
+    link could not be loaded"""
+    report = progress or (lambda percent, message: None)
+    count = ping_count(seconds)
+    report(None, f"Measuring idle latency to {target}")
+    idle = round_stats(await host.ping(target, count, PING_INTERVAL, PING_TIMEOUT, interface))
+
+    async def loaded_pings():
+        await asyncio.sleep(seconds * WARMUP_FRACTION)
+        return await host.ping(target, count, PING_INTERVAL, PING_TIMEOUT, interface)
+
+    report(None, f"Saturating {interface} ({direction}) for {seconds:g} s")
+    load, rtts = await asyncio.gather(host.load_link(interface, direction, seconds, url), loaded_pings())
+    loaded = round_stats(rtts)
+    increase = (round(loaded["rtt"] - idle["rtt"], 2)
+                if loaded["rtt"] is not None and idle["rtt"] is not None else None)
+    return {"interface": interface, "target": target, "direction": direction, "idle": idle, "loaded": loaded,
+            "increase_ms": increase, "throughput": load, "timestamp": time.time()}
+
+def grade(increase_ms: Optional[float]) -> Optional[str]:
+    """A+ to F for the latency load added; None when either phase got no replies"""
+    if increase_ms is None:
+        return None
+    return next((letter for limit, letter in GRADES if increase_ms < limit), "F")
+
+def worst(grades: List[Optional[str]]) -> Optional[str]:
+    order = [letter for _, letter in GRADES] + ["F"]
+    known = [letter for letter in grades if letter is not None]
+    return max(known, key=order.index) if known else None
+
+def shaping_rate(mbps: Optional[float]) -> Optional[str]:
+    """cake's bandwidth for a link measured at mbps, as tc takes it"""
+    if not mbps:
+        return None
+    return shaping.format_bandwidth(max(1000, int(mbps * SHAPING_FRACTION * 1_000_000) // 1000 * 1000))
+
+def assess(download: Dict, upload: Dict) -> Dict:
+    """Grade both directions and, when the upstream queues, suggest cake at the rate it reached. Egress is
+    all a root qdisc shapes; a bloated downstream needs shaping on the router (or an ingress qdisc)"""
+    download["grade"], upload["grade"] = grade(download["increase_ms"]), grade(upload["increase_ms"])
+    overall = worst([download["grade"], upload["grade"]])
+    suggestion = None
+    if upload["grade"] not in (None, "A+", "A"):
+        suggestion = {"qdisc": "cake", "bandwidth": shaping_rate(upload["throughput"]["mbps"])}
+    interface = download["interface"]
+    parts = [f"{direction} +{result['increase_ms']} ms ({result['grade']})"
+             for direction, result in (("download", download), ("upload", upload)) if result["grade"]]
+    summary = f"{interface}: grade {overall or 'unknown'}" + (f"; {', '.join(parts)}" if parts else "")
+    if suggestion:
+        summary += f". Try cake at {suggestion['bandwidth']} on {interface}"
+    if download["grade"] not in (None, "A+", "A"):
+        summary += ". The downstream queues as well, which only shaping on the router fixes"
+    return {"interface": interface, "grade": overall, "download": download, "upload": upload,
+            "suggestion": suggestion, "summary": summary}
+
+def compare(a: Dict, b: Dict) -> Dict:
+    """Which of two measurements added less latency under load; within 10% (or 2 ms) counts as the same"""
+    if a["increase_ms"] is None or b["increase_ms"] is None:
+        return {"better": None, "difference_ms": None}
+    difference = round(a["increase_ms"] - b["increase_ms"], 2)
+    margin = max(2.0, 0.1 * max(abs(a["increase_ms"]), abs(b["increase_ms"])))
+    better = "same" if abs(difference) <= margin else ("b" if difference > 0 else "a")
+    return {"better": better, "difference_ms": difference}
@@ -0,0 +1,269 @@
+"""
+Locations - Named sets of preferred profiles and host settings
+Switching location (by hand, or automatically from the associated SSID or the
+gateway's MAC address) connects its profiles and applies its DNS, proxy and
+firewall zone in one go
+"""
+
+import os
+import json
+import asyncio
+import logging
+from dataclasses import dataclass, field, asdict, fields
+from pathlib import Path
+from typing import Dict, List, Optional
+
+from .connection_manager import ConnectionManager, Progress, _report
+from .state_machine import LinkState
+
+# Configure logging
+logger = logging.getLogger(__name__)
+
+@dataclass
+class Location:
+    """A place the machine is used, and what to set up there"""
+    name: str
+    profiles: List[str] = field(default_factory=list)  # preferred, in order; first per interface wins
+    dns_servers: List[str] = field(default_factory=list)
+    proxy: Optional[str] = None  # e.g. http://proxy.corp:3128; None clears it
+    firewall_zone: Optional[str] = None
+
+    # Automatic detection; the highest priority match wins
+    match_ssids: List[str] = field(default_factory=list)
+    match_gateway_macs: List[str] = field(default_factory=list)
+    priority: int = 0
+
+    def __post_init__(self):
+        self.match_gateway_macs = [mac.lower() for mac in self.match_gateway_macs]
+
+    @classmethod
+    def from_dict(cls, data: Dict) -> "Location":
+        """Build from stored or requested fields, ignoring unknown keys"""
+        known = {f.name for f in fields(cls)}
+        return cls(**{key: value for key, value in data.items() if key in known})
+
+class LocationManager:
+    """Stores locations and applies the active one"""
+
+    def __init__(self, connections: ConnectionManager, config_path: Path):
+        self.connections = connections
+        self.backends = connections.backends
+        self.events = connections.events
+        self.locations_file = config_path / "locations.json"
+
+        self.locations: Dict[str, Location] = {}
+        self.active: Optional[str] = None
+        self.auto_switch = True
+
+        # One switch at a time; auto-detection must not interleave with a manual switch
+        self._lock = asyncio.Lock()
+
+        self._load()
+
+    def _load(self):
+        """Load locations and the active one from persistent storage"""
+        if not self.locations_file.exists():
+            return
+        try:
+            with open(self.locations_file) as f:
+                data = json.load(f)
+
+            self.locations = {name: Location.from_dict(spec) for name, spec in data.get("locations", {}).items()}
+            self.active = data.get("active") if data.get("active") in self.locations else None
+            self.auto_switch = data.get("auto_switch", True)
+            logger.info(f"Loaded {len(self.locations)} locations")
+        except Exception as e:
+            logger.error(f"Failed to load locations: {e}")
+
+    def _save(self):
+        """Save locations via a temp file"""
+        self.locations_file.parent.mkdir(parents=True, exist_ok=True)
+        data = {
+            "active": self.active,
+            "auto_switch": self.auto_switch,
+            "locations": {name: asdict(location) for name, location in self.locations.items()}
+        }
+        try:
+            temp_path = self.locations_file.with_suffix(".json.tmp")
+            with open(temp_path, 'w') as f:
+                json.dump(data, f, indent=2)
+            os.replace(temp_path, self.locations_file)
+        except Exception as e:
+            logger.error(f"Failed to save locations: {e}")
+
+    def to_dict(self) -> Dict:
+        """All locations plus which one is active"""
+        return {
+            "active": self.active,
+            "auto_switch": self.auto_switch,
+            "locations": [asdict(location) for location in self.list()]
+        }
+
+    def list(self) -> List[Location]:
+        """Locations by priority, then name"""
+        return sorted(self.locations.values(), key=lambda location: (-location.priority, location.name))
+
+    def get(self, name: str) -> Optional[Location]:
+        return self.locations.get(name)
+
+    def set(self, location: Location) -> Location:
+        """Create or replace a location"""
+        self.locations[location.name] = location
+        self._save()
+        return location
+
+    def delete(self, name: str) -> bool:
+        """Remove a location; deleting the active one leaves no location active"""
+        if self.locations.pop(name, None) is None:
+            return False
+        if self.active == name:
+            self.active = None
+        self._save()
+        return True
+
+    def set_auto_switch(self, enabled: bool):
+        self.auto_switch = enabled
+        self._save()
+
+    def observed(self) -> Dict[str, List[str]]:
+        """SSIDs and gateway MACs of the links that are currently up"""
+        ssids, gateway_macs = [], []
+        for interface, state in self.connections.interface_states.items():
+            if state.status != LinkState.CONFIGURED:
+                continue
+
+            profile = self.connections.get_profile(state.profile_name) if state.profile_name else None
+            if profile and profile.connection_type == "wifi":
+                ssid = self.backends.wifi.current_ssid(interface) or profile.ssid
+                if ssid:
+                    ssids.append(ssid)
+
+            if state.gateway:
+                mac = self.backends.host.gateway_mac(state.gateway)
+                if mac:
+                    gateway_macs.append(mac.lower())
+
+        return {"ssids": ssids, "gateway_macs": gateway_macs}
+
+    def detect(self) -> Optional[Location]:
+        """The best location matching what the machine is connected to"""
+        seen = self.observed()
+        for location in self.list():
+            if (set(location.match_ssids) & set(seen["ssids"]) or
+                    set(location.match_gateway_macs) & set(seen["gateway_macs"])):
+                return location
+        return None
+
+    async def switch(self, name: str, reason: str = "requested", progress: Progress = None) -> Dict:
+        """Make a location active and apply everything it sets; returns what was done"""
+        location = self.locations.get(name)
+        if location is None:
+            raise KeyError(name)
+
+        async with self._lock:
+            previous = self.active
+            result = {"location": name, "previous": previous, "connected": [], "failed": [],
+                      "dns": None, "proxy": None, "firewall": {}, "previous_zones": {}}
+
+            await self._connect_preferred(location, result, progress)
+
+            _report(progress, 80, "Applying host settings")
+            if location.dns_servers:
+                result["dns"] = await self.backends.host.set_dns(location.dns_servers)
+            result["proxy"] = await self.backends.host.set_proxy(location.proxy)
+            if location.firewall_zone:
+                for interface in self._configured_interfaces():
+                    result["previous_zones"][interface] = await self.backends.host.firewall_zone(interface)
+                    result["firewall"][interface] = await self.backends.host.set_firewall_zone(
+                        interface, location.firewall_zone
+                    )
+
+            self.active = name
+            self._save()
+
+        logger.info(f"Location {name} active ({reason})")
+        self.events.publish("LocationChanged", location=name, previous=previous, reason=reason)
+        _report(progress, 100, f"Location {name} active")
+        return result
+
+    async def restore(self, previous: Optional[str], zones: Dict[str, Optional[str]]) -> Dict:
+        """Undo a switch: interfaces back in their earlier firewall zones, the earlier location active again.
+        Profiles the switch connected stay up"""
+        async with self._lock:
+            restored = {}
+            for interface, zone in zones.items():
+                if zone:
+                    restored[interface] = await self.backends.host.set_firewall_zone(interface, zone)
+            current = self.active
+            self.active = previous if previous in self.locations else None
+            self._save()
+        self.events.publish("LocationChanged", location=self.active, previous=current, reason="undo")
+        return {"location": self.active, "firewall": restored}
+
+    async def _connect_preferred(self, location: Location, result: Dict, progress: Progress):
+        """Connect the first preferred profile of each interface unless it is already up"""
+        claimed = set()
+        for index, profile_name in enumerate(location.profiles):
+            profile = self.connections.get_profile(profile_name)
+            if profile is None:
+                logger.warning(f"Location {location.name} refers to missing profile {profile_name}")
+                result["failed"].append(profile_name)
+                continue
+            if profile.interface in claimed or self.connections.is_unmanaged(profile.interface):
+                continue
+            claimed.add(profile.interface)
+
+            state = self.connections.get_interface_state(profile.interface)
+            if state and state.status == LinkState.CONFIGURED and state.profile_name == profile_name:
+                continue
+
+            _report(progress, 10 + 60 * index // len(location.profiles), f"Connecting {profile_name}")
+            if await self.connections.connect_profile(profile_name):
+                result["connected"].append(profile_name)
+            else:
+                result["failed"].append(profile_name)
+
+    def _configured_interfaces(self) -> List[str]:
+        return [
+            interface for interface, state in self.connections.interface_states.items()
+            if state.status == LinkState.CONFIGURED and not self.connections.is_unmanaged(interface)
+        ]
+
+    async def run(self):
+        """Follow interface changes: auto-switch location and put new links in its firewall zone"""
+        subscription = self.events.subscribe(["InterfaceStateChanged"])
+        try:
+            # Links brought up before we started listening
+            try:
+                await self._auto_switch("startup")
+            except Exception as e:
+                logger.error(f"Location detection failed at startup: {e}")
+
+            while True:
+                event = await subscription.get()
+                if event["state"] != LinkState.CONFIGURED.value:
+                    continue
+                try:
+                    await self._on_configured(event["interface"])
+                except Exception as e:
+                    logger.error(f"Location handling failed for {event['interface']}: {e}")
+        finally:
+            self.events.unsubscribe(subscription)
+
+    async def _auto_switch(self, where: str) -> bool:
+        """Switch to the detected location if it is not already active"""
+        if not self.auto_switch or self._lock.locked():
+            return False
+        detected = self.detect()
+        if not detected or detected.name == self.active:
+            return False
+        await self.switch(detected.name, reason=f"detected on {where}")
+        return True
+
+    async def _on_configured(self, interface: str):
+        if await self._auto_switch(interface):
+            return
+
+        location = self.locations.get(self.active) if self.active else None
+        if location and location.firewall_zone and not self.connections.is_unmanaged(interface):
+            await self.backends.host.set_firewall_zone(interface, location.firewall_zone)
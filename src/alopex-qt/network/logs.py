@@ -0,0 +1,198 @@
+"""
+Daemon Logging - journald output, per-module levels and request context
+Structured fields (interface, request_id, client_uid, client_cert) travel with each record
+"""
+
+import os
+import socket
+import struct
+import logging
+import contextvars
+from collections import deque
+from pathlib import Path
+from typing import Dict, List, Optional
+
+LOG_FORMAT = "%(asctime)s [%(levelname)s] %(name)s: %(message)s"
+JOURNAL_SOCKET = "/run/systemd/journal/socket"
+
+# Record attributes exported as structured journald fields
+CONTEXT_FIELDS = ("interface", "request_id", "client_uid", "client_cert")
+
+# Fields attached to every record logged while serving a request
+log_context: contextvars.ContextVar = contextvars.ContextVar("alopex_log_context", default={})
+
+# Per-module level overrides currently in effect
+_module_levels: Dict[str, str] = {}
+
+DEFAULT_BUFFER_SIZE = 1000
+
+class ContextFilter(logging.Filter):
+    """Copy the current request context onto log records"""
+
+    def filter(self, record: logging.LogRecord) -> bool:
+        for key, value in log_context.get().items():
+            if not hasattr(record, key):
+                setattr(record, key, value)
+        return True
+
+class JournaldHandler(logging.Handler):
+    """Send records to journald using its native datagram protocol"""
+
+    PRIORITIES = {
+        logging.CRITICAL: 2,
+        logging.ERROR: 3,
+        logging.WARNING: 4,
+        logging.INFO: 6,
+        logging.DEBUG: 7
+    }
+
+    def __init__(self, identifier: str = "alopexd"):
+        super().__init__()
+        self.identifier = identifier
+        self.socket = socket.socket(socket.AF_UNIX, socket.SOCK_DGRAM)
+
+    @staticmethod
+    def is_available() -> bool:
+        """Check whether journald is listening"""
+        return os.path.exists(JOURNAL_SOCKET)
+
+    def emit(self, record: logging.LogRecord):
+        try:
+            fields = {
+                "MESSAGE": self.format(record),
+                "PRIORITY": self.PRIORITIES.get(record.levelno, 6),
+                "SYSLOG_IDENTIFIER": self.identifier,
+                "LOGGER": record.name,
+                "CODE_FILE": record.pathname,
+                "CODE_LINE": record.lineno,
+                "CODE_FUNC": record.funcName
+            }
+            for key in CONTEXT_FIELDS:
+                value = getattr(record, key, None)
+                if value is not None:
+                    fields[key.upper()] = value
+
+            payload = b"".join(self._encode_field(k, v) for k, v in fields.items())
+            self.socket.sendto(payload, JOURNAL_SOCKET)
+        except Exception:
+            self.handleError(record)
+
+    @staticmethod
+    def _encode_field(key: str, value) -> bytes:
+        """Encode one field, using the length-prefixed form for multi-line values"""
+        data = str(value).encode("utf-8", "replace")
+        if b"\n" in data:
+            return key.encode() + b"\n" + struct.pack("<Q", len(data)) + data + b"\n"
+        return key.encode() + b"=" + data + b"\n"
+
+    def close(self):
+        self.socket.close()
+        super().close()
+
+class RingBufferHandler(logging.Handler):
+    """Keep the most recent records in memory for GetLogs"""
+
+    def __init__(self, capacity: int = DEFAULT_BUFFER_SIZE):
+        super().__init__()
+        self.records = deque(maxlen=capacity)
+
+    def emit(self, record: logging.LogRecord):
+        try:
+            entry = {
+                "timestamp": record.created,
+                "level": record.levelname,
+                "target": record.name,
+                "message": record.getMessage()
+            }
+            for key in CONTEXT_FIELDS:
+                value = getattr(record, key, None)
+                if value is not None:
+                    entry[key] = value
+            self.records.append(entry)
+        except Exception:
+            self.handleError(record)
+
+    def get_records(self, since: Optional[float] = None, level: Optional[str] = None,
+                    limit: Optional[int] = None) -> List[Dict]:
+        """Return buffered records newer than since and at or above level"""
+        min_level = _parse_level(level) if level else logging.NOTSET
+        records = [
+            r for r in list(self.records)
+            if (since is None or r["timestamp"] > since)
+            and logging.getLevelName(r["level"]) >= min_level
+        ]
+        if limit:
+            records = records[-limit:]
+        return records
+
+# Shared buffer installed by configure_logging
+log_buffer = RingBufferHandler()
+
+def get_logs(since: Optional[float] = None, level: Optional[str] = None,
+             limit: Optional[int] = None) -> List[Dict]:
+    """GetLogs request handler"""
+    return log_buffer.get_records(since, level, limit)
+
+def _parse_level(level: str) -> int:
+    """Convert a level name to its numeric value"""
+    value = logging.getLevelName(str(level).upper())
+    if not isinstance(value, int):
+        raise ValueError(f"Unknown log level: {level}")
+    return value
+
+def configure_logging(config: Dict, log_file: Optional[Path] = None, identifier: str = "alopexd"):
+    """Configure root logging from the daemon's "logging" config section
+
+    Recognised keys: level, journald, modules ({logger name: level}),
+    buffer_size (records kept in memory for GetLogs).
+    """
+    root = logging.getLogger()
+    for handler in list(root.handlers):
+        root.removeHandler(handler)
+        if handler is not log_buffer:
+            handler.close()
+
+    log_buffer.records = deque(log_buffer.records, maxlen=config.get("buffer_size", DEFAULT_BUFFER_SIZE))
+    handlers = [log_buffer]
+    if config.get("journald", False) and JournaldHandler.is_available():
+        journal_handler = JournaldHandler(identifier)
+        journal_handler.setFormatter(logging.Formatter("%(message)s"))
+        handlers.append(journal_handler)
+    else:
+        console_handler = logging.StreamHandler()
+        console_handler.setFormatter(logging.Formatter(LOG_FORMAT))
+        handlers.append(console_handler)
+
+    if log_file:
+        try:
+            log_file.parent.mkdir(parents=True, exist_ok=True)
+            file_handler = logging.FileHandler(log_file)
+            file_handler.setFormatter(logging.Formatter(LOG_FORMAT))
+            handlers.append(file_handler)
+        except OSError as e:
+            print(f"Cannot open log file {log_file}: {e}")
+
+    for handler in handlers:
+        if not any(isinstance(f, ContextFilter) for f in handler.filters):
+            handler.addFilter(ContextFilter())
+        root.addHandler(handler)
+
+    root.setLevel(_parse_level(config.get("level", "INFO")))
+
+    for module, level in config.get("modules", {}).items():
+        set_log_level(level, module)
+
+def set_log_level(level: str, module: Optional[str] = None):
+    """Change the level of the root logger or of one module at runtime"""
+    value = _parse_level(level)
+    logging.getLogger(module or "").setLevel(value)
+
+    if module:
+        _module_levels[module] = logging.getLevelName(value)
+
+def get_log_levels() -> Dict:
+    """Report the root level and all per-module overrides"""
+    return {
+        "level": logging.getLevelName(logging.getLogger().level),
+        "modules": dict(_module_levels)
+    }
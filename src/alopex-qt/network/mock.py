@@ -0,0 +1,1332 @@
+"""
+Mock Network - In-memory fake network for alopexd --mock
+Scripted link flaps, fake WiFi networks and deterministic counters so the
+GUI, CLI tools and integration tests run without root or real hardware
+"""
+
+import os
+import json
+import time
+import zlib
+import socket
+import asyncio
+import logging
+import ipaddress
+from copy import deepcopy
+from dataclasses import dataclass, field
+from pathlib import Path
+from typing import Callable, Dict, List, Optional, Tuple
+
+from .discovery import InterfaceType, NetworkDiscovery, NetworkInterface, NetworkMetrics, parse_interface_type
+from .wifi import WiFiNetwork, WifiSecurity
+from .vpn import VpnConfig
+from .spectrum import CHANNELS
+from .rfkill import RfkillDevice
+from .hotspot import admitted
+from . import bluetooth, cable_test, connectivity, containers, lan_scan, obex, oui, shaping
+from .dhcp_server import (
+    ACK, BOOTREQUEST, DISCOVER, OPT_HOSTNAME, OPT_MESSAGE_TYPE, OPT_REQUESTED_IP, OPT_SERVER_ID, REQUEST,
+    DhcpPacket, DhcpServer
+)
+from .backends import (
+    BackendSet, EthernetBackend, WifiBackend, VpnBackend, BluetoothBackend, HostBackend, ServiceBackend,
+    RfkillBackend, HotspotBackend, BuiltinDhcpServerBackend, AccountingBackend, CountersBackend,
+    ContainersBackend, LibvirtBackend, register_backend
+)
+
+# Configure logging
+logger = logging.getLogger(__name__)
+
+DEFAULT_SCENARIO = {
+    "links": [
+        {"name": "eth0", "type": "Ethernet", "up": True, "address": "192.168.1.50",
+         "gateway": "192.168.1.1", "gateway_mac": "52:54:00:00:01:01", "link_speed": 1000,
+         "rx_rate": 250000, "tx_rate": 40000, "mac": "00:1b:21:3a:4c:50",
+         "throughput": {"download": 94.0, "upload": 18.5}, "bloat": {"fq_codel": 180, "fq": 120, "cake": 4}},
+        {"name": "wlan0", "type": "WiFi", "up": False, "address": "10.20.0.42",
+         "gateway": "10.20.0.1", "gateway_mac": "52:54:00:00:14:01", "link_speed": 866,
+         "rx_rate": 120000, "tx_rate": 15000, "mac": "da:a1:19:5e:0c:42", "permanent_mac": "a4:34:d9:11:20:42"},
+        {"name": "docker0", "type": "Bridge", "up": True, "address": "172.17.0.1", "mac": "02:42:ac:11:00:01",
+         "permanent_mac": None},
+        {"name": "veth3f1c2ab", "type": "Other", "detail": "veth", "up": True, "master": "docker0",
+         "mac": "9a:0e:3c:51:7d:20", "permanent_mac": None},
+        {"name": "virbr0", "type": "Bridge", "up": True, "address": "192.168.122.1", "mac": "52:54:00:8e:21:5a",
+         "permanent_mac": None}
+    ],
+    "libvirt_networks": [
+        {"name": "default", "bridge": "virbr0", "subnets": ["192.168.122.0/24"],
+         "guests": [{"domain": "fedora-dev", "mac": "52:54:00:3b:7c:11", "ip": "192.168.122.41",
+                     "hostname": "fedora-dev", "interface": "vnet0"}]}
+    ],
+    # Network inspect replies from the Docker socket; leave an engine out and it isn't running
+    "container_engines": {
+        "docker": [
+            {"Name": "bridge", "Id": "5e7d1f0a9c3b2d4e", "Driver": "bridge",
+             "Options": {"com.docker.network.bridge.name": "docker0"},
+             "IPAM": {"Config": [{"Subnet": "172.17.0.0/16", "Gateway": "172.17.0.1"}]},
+             "Containers": {"c0ffee": {"Name": "web", "MacAddress": "02:42:ac:11:00:02",
+                                       "IPv4Address": "172.17.0.2/16"}}}
+        ]
+    },
+    "wifi_networks": [
+        {"ssid": "AlopexHQ", "signal": -45, "security": "WPA2", "password": "arctic-fox", "channel": 36},
+        {"ssid": "Guest", "signal": -67, "security": "Open", "channel": 6},
+        {"ssid": "Neighbour", "signal": -82, "security": "WPA3", "password": "not-yours", "channel": 11}
+    ],
+    "vpn": [
+        {"name": "office", "address": "10.99.0.7"}
+    ],
+    # type classic needs pairing before connecting, le doesn't; battery reads once connected; push False
+    # declines OBEX transfers
+    "bluetooth": [
+        {"mac": "AA:BB:CC:DD:EE:01", "name": "Headset", "type": "classic", "rssi": -58},
+        {"mac": "F1:22:33:44:55:66", "name": "Heart Rate", "type": "le", "rssi": -71, "battery": 64,
+         "services": ["Heart Rate", "Battery Service"]}
+    ],
+    # systemd units profiles may start; any other unit name fails like a missing unit
+    "units": ["syncthing.service", "nfs-client.target"],
+    "profiles": [
+        {"name": "Wired", "interface": "eth0", "connection_type": "ethernet", "method": "dhcp"},
+        {"name": "HQ", "interface": "wlan0", "connection_type": "wifi", "method": "dhcp",
+         "ssid": "AlopexHQ", "password": "arctic-fox", "auto_connect": False},
+        {"name": "Office VPN", "interface": "office", "connection_type": "vpn", "method": "manual",
+         "auto_connect": False}
+    ],
+    "script": [
+        {"at": 60, "interface": "eth0", "up": False},
+        {"at": 65, "interface": "eth0", "up": True}
+    ],
+    "repeat_script": False,
+    # Devices a LAN scan finds, on whichever connected link's /24 they fall in
+    "lan_devices": [
+        {"ip": "192.168.1.1", "mac": "52:54:00:00:01:01", "hostname": "gateway", "name_source": "mdns"},
+        {"ip": "192.168.1.20", "mac": "00:11:32:0a:0b:0c", "hostname": "NAS", "name_source": "netbios"},
+        {"ip": "192.168.1.33", "mac": "3e:22:fb:12:34:56"},
+        {"ip": "10.20.0.1", "mac": "52:54:00:00:14:01"}
+    ],
+    # Tracked connections; fields left out default as in conntrack.make_flow
+    "flows": [
+        {"src": "192.168.1.50", "dst": "140.82.112.3", "dport": 443, "state": "ESTABLISHED", "bytes": 184220},
+        {"src": "192.168.1.50", "dst": "140.82.112.3", "dport": 22, "state": "ESTABLISHED", "bytes": 9120},
+        {"src": "192.168.1.50", "dst": "192.0.2.53", "protocol": "udp", "dport": 53, "bytes": 312},
+        {"src": "192.168.1.50", "dst": "192.168.1.20", "dport": 445, "state": "TIME_WAIT", "bytes": 40960}
+    ],
+    # Bytes each unit cgroup receives and sends per accounting sample
+    "unit_traffic": {
+        "system.slice/syncthing.service": {"rx": 1200000, "tx": 4500000},
+        "system.slice/sshd.service": {"rx": 8000, "tx": 24000},
+        "user.slice/user-1000.slice": {"rx": 640000, "tx": 52000}
+    },
+    # Seconds each slow operation takes, so progress and spinners are visible
+    "delays": {"dhcp": 0.5, "associate": 1.0, "vpn": 1.5, "scan": 0.8}
+}
+
+MOCK_FLOW = {"family": 4, "protocol": "tcp", "sport": 40000, "dport": None, "state": None, "snat": None,
+             "dnat": None, "packets": None, "bytes": None}
+
+MOCK_SYSCTLS = {"net.ipv4.tcp_congestion_control": "cubic", "net.ipv4.tcp_mtu_probing": "0", "net.ipv4.tcp_ecn": "2",
+                "net.ipv4.tcp_available_congestion_control": "reno cubic bbr"}
+MOCK_INTERFACE_SYSCTLS = {"rp_filter": "2", "accept_ra": "1"}
+
+@dataclass
+class FakeLink:
+    """One fake network interface"""
+    name: str
+    interface_type: InterfaceType
+    type_detail: Optional[str] = None
+    mac: Optional[str] = None
+    permanent_mac: Optional[str] = None
+    up: bool = False
+    carrier: bool = True
+    ip: Optional[str] = None
+    gateway: Optional[str] = None
+    gateway_mac: Optional[str] = None
+    dns: List[str] = field(default_factory=lambda: ["192.0.2.53"])
+    dhcp_address: Optional[str] = None
+    link_speed: Optional[int] = None
+    max_speed: Optional[int] = None  # what ethtool would list as supported; link_speed when left out
+    partner_max_speed: Optional[int] = None
+    cable: Optional[Dict] = None  # pair -> {"status", "fault_length_m"} the cable test finds; None can't test
+    module: Optional[Dict] = None  # the plugged transceiver, as transceiver.parse_module_eeprom gives it
+    queues: List[Dict] = field(default_factory=list)  # {"queue", "rx_packets", "tx_packets"}
+    irqs: List[Dict] = field(default_factory=list)  # {"irq", "name", "interrupts", "cpus"}
+    mtu: int = 1500
+    master: Optional[str] = None  # bridge or bond it is plugged into
+    addresses: List[str] = field(default_factory=list)  # static "address/prefix" besides the DHCP one
+    routes: List[Dict] = field(default_factory=list)  # static {"to", "via", "metric"}
+    default_metric: Optional[int] = None  # of the DHCP default route
+    rx_rate: int = 0  # bytes per discovery tick
+    tx_rate: int = 0
+    ssid: Optional[str] = None
+    bssid: Optional[str] = None  # access point associated with, and its signal in dBm
+    signal: Optional[int] = None
+    power_save: bool = True
+    lease: Optional[Dict] = None
+    hotspot: Optional[Dict] = None  # {"ssid", "channel", "blocked", "allowed", "kicked", "isolated"} while sharing
+    credentials: Optional[Dict] = None  # 802.1X settings or VPN certificate paths it came up with
+    metrics: NetworkMetrics = field(default_factory=NetworkMetrics)
+    qdisc: Dict = field(default_factory=lambda: {"kind": "fq_codel", "bandwidth": None})  # root qdisc
+    congestion_control: Optional[str] = None  # congctl on its default route
+    throughput: Dict = field(default_factory=dict)  # Mbit/s a load test reaches: "download", "upload"
+    bloat: Dict = field(default_factory=dict)  # ms a full queue adds to round trips, by root qdisc kind
+
+    @property
+    def connected(self) -> bool:
+        return self.up and self.carrier
+
+@dataclass
+class LinkFlap:
+    """Scripted carrier change, relative to the start of the script"""
+    at: float
+    interface: str
+    up: bool
+
+class FakeNetwork:
+    """Shared in-memory state behind the mock backends and discovery"""
+
+    def __init__(self, scenario: Optional[Dict] = None):
+        self.scenario = deepcopy(scenario or DEFAULT_SCENARIO)
+        self.links: Dict[str, FakeLink] = {}
+        self.wifi_networks: List[Dict] = self.scenario.get("wifi_networks", [])
+        # Deauth/disassoc events not yet delivered: {interface, kind, bssid, reason}
+        self.wifi_frames: List[Dict] = self.scenario.get("wifi_frames", [])
+        self.vpn_configs: Dict[str, Dict] = {v["name"]: v for v in self.scenario.get("vpn", [])}
+        self.bluetooth_devices: List[Dict] = self.scenario.get("bluetooth", [])
+        self.paired: Dict[str, bool] = {}  # address -> connected
+        self.le_connected: set = set()  # LE peripherals connected without pairing
+        # What the host backend last applied: {"dns": [...], "proxy": ..., "zones": {iface: zone}},
+        # plus the addresses it announced ({"interface", "mac", "addresses"}) and where proxy ARP is on
+        self.host: Dict = {"dns": [], "proxy": None, "zones": {}, "announced": [], "proxy_arp": {}}
+        # Sysctls by dotted key; unset per-interface ones of an existing link read as MOCK_INTERFACE_SYSCTLS says
+        self.sysctls: Dict[str, str] = dict(MOCK_SYSCTLS, **self.scenario.get("sysctls", {}))
+        # The MPTCP path manager; "mptcp": false in the scenario is a kernel without it
+        self.mptcp: Optional[Dict] = {"endpoints": [], "limits": {"subflows": 2, "add_addr_accepted": 0},
+                                      "next_id": 1} if self.scenario.get("mptcp", True) else None
+        self.units: Dict[str, bool] = {unit: False for unit in self.scenario.get("units", [])}
+        self.script = [LinkFlap(**step) for step in self.scenario.get("script", [])]
+        self.repeat_script = self.scenario.get("repeat_script", False)
+        self.delays: Dict[str, float] = self.scenario.get("delays", {})
+        self.cpus: List[int] = self.scenario.get("cpus", [0, 1, 2, 3])
+        # Regulatory domain; 00 is the world domain, where 5 GHz is listen-only
+        self.country: str = self.scenario.get("country", "00")
+        # Resolver answer times in ms; null never answers, unlisted servers take 20 ms
+        self.dns_latency: Dict[str, Optional[float]] = self.scenario.get("dns_latency", {})
+        # Stations that join any hotspot: {"mac", "hostname", "signal", "rx_rate", "tx_rate"}
+        self.hotspot_clients: List[Dict] = self.scenario.get("hotspot_clients", [])
+        self.lan_devices: List[Dict] = self.scenario.get("lan_devices", [])
+        # "host:port" answer times in ms for port probes; null never answers, unlisted ports refuse
+        self.ports: Dict[str, Optional[float]] = self.scenario.get("ports", {})
+        # "host:port" -> {"ipv4", "ipv6"} for connectivity checks: answer time in ms, null never answers,
+        # "unreachable" has no route; a family left out has no address
+        self.dual_stack: Dict[str, Dict] = self.scenario.get("dual_stack", {})
+        # Echo reply times in ms by host: one value, or a list cycled through packet by packet;
+        # null is a lost packet, unlisted hosts answer in 10 ms
+        self.ping: Dict[str, object] = self.scenario.get("ping", {})
+        self.pings_sent: Dict[str, int] = {}
+        # Interfaces a load test is filling right now -> direction
+        self.loaded: Dict[str, str] = {}
+        self.flows: List[Dict] = [dict(MOCK_FLOW, **flow) for flow in self.scenario.get("flows", [])]
+        self.conntrack_max: Optional[int] = self.scenario.get("conntrack_max", 65536)
+        self.unit_traffic: Dict[str, Dict[str, int]] = self.scenario.get("unit_traffic", {})
+        # Why the eBPF counters would not load here; null lets them load
+        self.ebpf_unavailable: Optional[str] = self.scenario.get("ebpf_unavailable")
+        # Network inspect replies per running engine ("docker", "podman"); engines not listed aren't running
+        self.container_engines: Dict[str, List[Dict]] = self.scenario.get("container_engines", {})
+        # libvirt networks {"name", "bridge", "forward", "subnets", "active", "guests"}; null means libvirtd is down
+        self.libvirt_networks: Optional[List[Dict]] = self.scenario.get("libvirt_networks")
+        # DHCP servers by interface; they answer without sockets, so joining stations ask them directly
+        self.dhcp_servers: Dict[str, DhcpServer] = {}
+        self.ticks = 0
+
+        for spec in self.scenario.get("links", []):
+            link = FakeLink(
+                name=spec["name"],
+                interface_type=parse_interface_type(spec.get("type", "Ethernet")),
+                type_detail=spec.get("detail"),
+                mac=spec.get("mac"),
+                permanent_mac=spec.get("permanent_mac", spec.get("mac")),
+                up=spec.get("up", False),
+                dhcp_address=spec.get("address"),
+                gateway=spec.get("gateway"),
+                gateway_mac=spec.get("gateway_mac"),
+                link_speed=spec.get("link_speed"),
+                max_speed=spec.get("max_speed", spec.get("link_speed")),
+                partner_max_speed=spec.get("partner_max_speed"),
+                cable=spec.get("cable", {}),
+                module=spec.get("module"),
+                queues=deepcopy(spec.get("queues", [])),
+                irqs=deepcopy(spec.get("irqs", [])),
+                mtu=spec.get("mtu", 1500),
+                master=spec.get("master"),
+                addresses=list(spec.get("addresses", [])),
+                routes=deepcopy(spec.get("routes", [])),
+                rx_rate=spec.get("rx_rate", 0),
+                tx_rate=spec.get("tx_rate", 0),
+                throughput=dict(spec.get("throughput", {})),
+                bloat=dict(spec.get("bloat", {}))
+            )
+            if link.up:
+                link.ip = link.dhcp_address
+            self.links[link.name] = link
+
+        # Kill switches; by default one per WiFi link plus a Bluetooth adapter, none blocked
+        specs = self.scenario.get("rfkill")
+        if specs is None:
+            specs = [{"type": "wlan", "name": f"phy{index}", "interface": link.name}
+                     for index, link in enumerate(l for l in self.links.values()
+                                                  if l.interface_type == InterfaceType.WIFI)]
+            specs.append({"type": "bluetooth", "name": "hci0"})
+        self.rfkill: List[RfkillDevice] = [RfkillDevice(index=index, **spec) for index, spec in enumerate(specs)]
+
+    @classmethod
+    def from_file(cls, path: Path) -> "FakeNetwork":
+        """Load a scenario from a JSON file, falling back to defaults per key"""
+        with open(path) as f:
+            scenario = dict(DEFAULT_SCENARIO, **json.load(f))
+        return cls(scenario)
+
+    @property
+    def profiles(self) -> List[Dict]:
+        return self.scenario.get("profiles", [])
+
+    def tick(self):
+        """Advance traffic counters by one step"""
+        self.ticks += 1
+        for link in self.links.values():
+            if not link.connected:
+                continue
+            metrics = link.metrics
+            metrics.bytes_rx += link.rx_rate
+            metrics.bytes_tx += link.tx_rate
+            metrics.packets_rx += link.rx_rate // 1000
+            metrics.packets_tx += link.tx_rate // 1000
+
+    def set_carrier(self, interface: str, up: bool) -> bool:
+        """Simulate cable plug/unplug or AP loss"""
+        link = self.links.get(interface)
+        if not link:
+            return False
+
+        link.carrier = up
+        if not up and link.interface_type == InterfaceType.WIFI:
+            # Losing the AP drops the association; it has to be re-established
+            if link.bssid:
+                self.wifi_frames.append({"interface": interface, "kind": "disconnected", "by_ap": True,
+                                         "reason": 4})
+            link.ssid = None
+            link.bssid = None
+            link.signal = None
+            link.hotspot = None
+            link.up = False
+            link.ip = None
+        logger.info(f"Mock carrier {'up' if up else 'down'} on {interface}")
+        return True
+
+    def set_link_speed(self, interface: str, speed: int, partner_max_speed: Optional[int] = None) -> bool:
+        """Simulate a link renegotiating, e.g. after swapping the cable or the switch"""
+        link = self.links.get(interface)
+        if not link:
+            return False
+        link.link_speed = speed
+        link.partner_max_speed = partner_max_speed
+        logger.info(f"Mock link speed {speed} Mb/s on {interface}")
+        return True
+
+    def roam(self, interface: str, bssid: str, signal: Optional[int] = None) -> bool:
+        """Simulate the supplicant moving an associated interface to another access point"""
+        link = self.links.get(interface)
+        if not link or not link.bssid:
+            return False
+        link.bssid = bssid.lower()
+        link.signal = signal if signal is not None else link.signal
+        self.wifi_frames.append({"interface": interface, "kind": "roamed", "bssid": link.bssid})
+        logger.info(f"Mock roam of {interface} to {link.bssid}")
+        return True
+
+    async def run_script(self):
+        """Replay the scenario's scripted link flaps"""
+        if not self.script:
+            return
+
+        while True:
+            started = time.monotonic()
+            for step in sorted(self.script, key=lambda s: s.at):
+                await asyncio.sleep(max(0.0, step.at - (time.monotonic() - started)))
+                self.set_carrier(step.interface, step.up)
+
+            if not self.repeat_script:
+                return
+
+    def radio_block(self, interface: str) -> Optional[str]:
+        return next((d.block for d in self.rfkill if d.interface == interface), None)
+
+    def to_interface(self, link: FakeLink) -> NetworkInterface:
+        """Render a fake link the way NetworkDiscovery reports real ones"""
+        metrics = deepcopy(link.metrics)
+        metrics.link_speed = link.link_speed if link.connected else None
+        metrics.duplex = "full" if link.connected and link.link_speed else None
+        metrics.mtu = link.mtu
+
+        return NetworkInterface(
+            name=link.name,
+            interface_type=link.interface_type,
+            type_detail=link.type_detail,
+            mac=link.mac,
+            permanent_mac=link.permanent_mac,
+            status="Connected" if link.connected else "Disconnected",
+            ip=link.ip if link.connected else None,
+            gateway=link.gateway,
+            dns=list(link.dns),
+            metrics=metrics,
+            rfkill=self.radio_block(link.name)
+        )
+
+class MockDiscovery(NetworkDiscovery):
+    """NetworkDiscovery over a FakeNetwork"""
+
+    def __init__(self, network: FakeNetwork):
+        super().__init__()
+        self.network = network
+
+    def discover_interfaces(self) -> List[NetworkInterface]:
+        self.network.tick()
+        interfaces = [self.network.to_interface(link) for link in self.network.links.values()]
+        interfaces.sort(key=lambda x: (self._type_priority(x.interface_type), x.name))
+        return interfaces
+
+    def get_routes(self, interface: Optional[str] = None) -> List[Dict]:
+        """Connected subnets and default routes of the fake links that are up"""
+        routes = []
+        for link in self.network.links.values():
+            if not link.connected or not link.ip or interface not in (None, link.name):
+                continue
+            if link.gateway:
+                routes.append({"dst": "default", "gateway": link.gateway, "dev": link.name, "flags": []})
+            subnet = ".".join(link.ip.split(".")[:3]) + ".0/24"
+            routes.append({"dst": subnet, "dev": link.name, "protocol": "kernel", "scope": "link",
+                           "prefsrc": link.ip, "flags": []})
+        return routes
+
+    def _get_dns_servers(self) -> List[str]:
+        """What the host backend last applied, else the fake links' servers"""
+        if self.network.host["dns"]:
+            return list(self.network.host["dns"])
+        servers = []
+        for link in self.network.links.values():
+            servers.extend(server for server in link.dns if link.connected and server not in servers)
+        return servers
+
+def _certificates_readable(eap: Optional[Dict], method: str) -> bool:
+    """Whether eap is the method a fake 802.1X network wants, with every certificate it needs on disk"""
+    if not eap or (eap.get("method") or "peap") != method:
+        return False
+    needed = ("ca_cert", "client_cert", "private_key") if method == "tls" else ("ca_cert",)
+    return all(eap.get(key) and Path(eap[key]).is_file() for key in needed)
+
+class MockBackend:
+    """Mixin holding the shared fake network"""
+
+    def __init__(self, network: Optional[FakeNetwork] = None):
+        self.network = network or FakeNetwork()
+
+    def _link(self, interface: str) -> Optional[FakeLink]:
+        return self.network.links.get(interface)
+
+@register_backend("ethernet", "mock")
+class MockEthernetBackend(MockBackend, EthernetBackend):
+    """Addressing on fake links"""
+
+    async def configure_dhcp(self, interface: str) -> bool:
+        link = self._link(interface)
+        if not link or not link.carrier or not link.dhcp_address:
+            return False
+
+        await asyncio.sleep(self.network.delays.get("dhcp", 0))
+
+        link.up = True
+        link.ip = link.dhcp_address
+        link.default_metric = None
+        link.lease = {
+            "ip_address": link.ip,
+            "server": link.gateway,
+            "routers": [link.gateway] if link.gateway else [],
+            "dns_servers": list(link.dns),
+            "lease_time": 3600,
+            "renewal_time": 1800,
+            "rebinding_time": 3150,
+            "acquired_at": time.time(),
+            "options": {
+                "subnet_mask": "255.255.255.0",
+                "routers": link.gateway or "",
+                "domain_name_servers": " ".join(link.dns),
+                "dhcp_server_identifier": link.gateway or ""
+            }
+        }
+        return True
+
+    async def configure_static(self, interface: str, ip: str, gateway: Optional[str], dns: List[str]) -> bool:
+        link = self._link(interface)
+        if not link or not ip:
+            return False
+
+        link.up = True
+        link.ip = ip.split("/")[0]
+        link.gateway = gateway
+        link.default_metric = None
+        link.dns = list(dns or link.dns)
+        link.lease = None
+        return True
+
+    async def bring_up(self, interface: str) -> bool:
+        link = self._link(interface)
+        if not link:
+            return False
+        link.up = True
+        return True
+
+    async def bring_down(self, interface: str) -> bool:
+        link = self._link(interface)
+        if not link:
+            return False
+        link.up = False
+        link.ip = None
+        link.lease = None
+        return True
+
+    async def create_netdev(self, interface: str, netdev: Dict) -> bool:
+        if netdev.get("kind") not in ("vlan", "bridge", "bond", "gre", "gretap", "vxlan"):
+            return False
+        if netdev["kind"] in ("vlan", "vxlan"):
+            lower = [netdev["parent"]] if netdev.get("parent") else []
+        else:
+            lower = netdev.get("ports", [])
+        if any(name not in self.network.links for name in lower):
+            return False
+
+        link = self._link(interface)
+        if not link:
+            # Virtual devices get their address from the network behind their first lower link;
+            # a tunnel's network is whatever is at the far end, so it has no DHCP here
+            first = self._link(lower[0]) if lower and netdev["kind"] in ("vlan", "bridge", "bond") else None
+            link = FakeLink(name=interface, interface_type=InterfaceType.ETHERNET,
+                            dhcp_address=first.dhcp_address if first else None,
+                            gateway=first.gateway if first else None)
+            self.network.links[interface] = link
+        for port in netdev.get("ports", []):
+            self.network.links[port].master = interface
+        link.up = True
+        return True
+
+    async def ensure_dhcp(self, interface: str) -> bool:
+        link = self._link(interface)
+        return bool(link and link.connected)
+
+    def get_dhcp_lease(self, interface: str) -> Optional[Dict]:
+        link = self._link(interface)
+        return link.lease if link else None
+
+    async def renew_dhcp(self, interface: str) -> bool:
+        link = self._link(interface)
+        if not link or not link.lease or not link.carrier:
+            return False
+        await asyncio.sleep(self.network.delays.get("dhcp", 0))
+        link.lease = dict(link.lease, acquired_at=time.time())
+        return True
+
+    async def release_dhcp(self, interface: str) -> bool:
+        link = self._link(interface)
+        if not link or not link.lease:
+            return False
+        link.ip = None
+        link.lease = None
+        return True
+
+    async def rename(self, interface: str, new_name: str) -> Tuple[bool, str]:
+        link = self._link(interface)
+        if not link:
+            return False, "No such device"
+        if new_name in self.network.links:
+            return False, "File exists"
+        link.name = new_name
+        self.network.links = {(new_name if name == interface else name): value
+                              for name, value in self.network.links.items()}
+        # Like the kernel, which only renames links that are down
+        return True, "Renamed (link was down for the rename)" if link.up else "Renamed"
+
+    def get_address(self, interface: str) -> Tuple[Optional[str], Optional[str]]:
+        link = self._link(interface)
+        if not link or not link.connected:
+            return None, None
+        return link.ip, link.gateway
+
+    async def address_state(self, interface: str) -> Optional[Dict]:
+        """The DHCP address and default route count as dynamic, the link's static ones as set by hand"""
+        link = self._link(interface)
+        if not link:
+            return None
+        dynamic = link.ip and link.connected
+        addresses = [{"address": f"{link.ip}/24", "dynamic": True}] if dynamic else []
+        addresses += [{"address": address, "dynamic": False} for address in link.addresses]
+        routes = []
+        if dynamic and link.gateway:
+            routes.append({"to": "default", "via": link.gateway, "metric": link.default_metric, "protocol": "dhcp"})
+        routes += [dict(route, protocol="boot") for route in link.routes]
+        return {"addresses": addresses, "routes": routes}
+
+    async def apply_change(self, change: Dict) -> Optional[str]:
+        link = self._link(change["interface"])
+        if not link:
+            return "Cannot find device"
+        if change["kind"] == "route" and change["route"].get("protocol") == "dhcp":
+            # The lease's default route; moving it to another metric is all that can happen to it
+            if change["action"] == "add":
+                link.default_metric = change["route"]["metric"]
+            return None
+        entries, entry = (link.addresses, change["address"]) if change["kind"] == "address" else (
+            link.routes, {key: change["route"].get(key) for key in ("to", "via", "metric")})
+        if change["action"] == "add":
+            if entry in entries:
+                return "File exists"
+            entries.append(entry)
+        elif entry in entries:
+            entries.remove(entry)
+        else:
+            return "No such process"
+        return None
+
+@register_backend("wifi", "mock")
+class MockWifiBackend(MockBackend, WifiBackend):
+    """Fake access points from the scenario"""
+
+    def list_interfaces(self) -> List[str]:
+        return [link.name for link in self.network.links.values() if link.interface_type == InterfaceType.WIFI]
+
+    def scan(self, interface: str) -> List[WiFiNetwork]:
+        link = self._link(interface)
+        if not link or link.interface_type != InterfaceType.WIFI:
+            return []
+
+        # Called from a worker thread, like the blocking iw scan
+        time.sleep(self.network.delays.get("scan", 0))
+        return self.cached_scan(interface)
+
+    def cached_scan(self, interface: str) -> List[WiFiNetwork]:
+        link = self._link(interface)
+        if not link or link.interface_type != InterfaceType.WIFI:
+            return []
+        networks = [
+            WiFiNetwork(
+                ssid=spec["ssid"],
+                signal_strength=spec.get("signal", -60),
+                security=WifiSecurity(spec.get("security", "WPA2")),
+                frequency="5 GHz" if spec.get("channel", 1) > 14 else "2.4 GHz",
+                bssid=spec.get("bssid", f"02:00:00:00:00:{index:02x}"),
+                connected=spec["ssid"] == link.ssid,
+                channel=spec.get("channel")
+            )
+            for index, spec in enumerate(self.network.wifi_networks, 1)
+        ]
+        networks.sort(key=lambda x: x.signal_strength, reverse=True)
+        return networks
+
+    async def watch_frames(self, handler):
+        # Tests append to wifi_frames while the daemon runs
+        while True:
+            while self.network.wifi_frames:
+                handler(dict(self.network.wifi_frames.pop(0)))
+            await asyncio.sleep(0.05)
+
+    async def connect(self, interface: str, ssid: str, password: Optional[str] = None,
+                      username: Optional[str] = None, security: Optional[WifiSecurity] = None,
+                      eap: Optional[Dict] = None) -> bool:
+        link = self._link(interface)
+        network = next((n for n in self.network.wifi_networks if n["ssid"] == ssid), None)
+        if not link or not network or self.network.radio_block(interface):
+            return False
+
+        await asyncio.sleep(self.network.delays.get("associate", 0))
+        if network.get("hang"):
+            # AP that never answers; only a cancel or job timeout gets out of this
+            await asyncio.get_running_loop().create_future()
+        if network.get("password") and network["password"] != password:
+            logger.info(f"Mock authentication to {ssid} rejected")
+            return False
+        if network.get("eap") and not _certificates_readable(eap, network["eap"]):
+            logger.info(f"Mock 802.1X ({network['eap']}) to {ssid} rejected")
+            return False
+
+        link.carrier = True
+        link.ssid = ssid
+        index = self.network.wifi_networks.index(network) + 1
+        link.bssid = network.get("bssid", f"02:00:00:00:00:{index:02x}")
+        link.signal = network.get("signal", -60)
+        self.network.wifi_frames.append({"interface": interface, "kind": "connected", "bssid": link.bssid})
+        link.credentials = eap
+        return await MockEthernetBackend(self.network).configure_dhcp(interface)
+
+    def disconnect(self, interface: str) -> bool:
+        link = self._link(interface)
+        if not link:
+            return False
+        if link.bssid:
+            self.network.wifi_frames.append({"interface": interface, "kind": "disconnected", "by_ap": False,
+                                             "reason": 3})
+        link.ssid = None
+        link.bssid = None
+        link.signal = None
+        link.up = False
+        link.ip = None
+        link.lease = None
+        return True
+
+    def current_ssid(self, interface: str) -> Optional[str]:
+        link = self._link(interface)
+        return link.ssid if link else None
+
+    def link_info(self, interface: str) -> Optional[Dict]:
+        link = self._link(interface)
+        if not link or not link.bssid:
+            return None
+        return {"bssid": link.bssid, "ssid": link.ssid, "frequency": None, "signal": link.signal}
+
+    def regulatory_domain(self) -> Optional[Dict]:
+        return {"country": self.network.country, "dfs_region": None if self.network.country == "00" else "DFS-ETSI"}
+
+    def set_regulatory_domain(self, country: str) -> bool:
+        self.network.country = country
+        return True
+
+    def power_save(self, interface: str) -> Optional[bool]:
+        link = self._link(interface)
+        return link.power_save if link else None
+
+    def set_power_save(self, interface: str, enabled: bool) -> bool:
+        link = self._link(interface)
+        if not link or link.interface_type != InterfaceType.WIFI:
+            return False
+        link.power_save = enabled
+        return True
+
+    def channels(self, interface: str) -> List[Dict]:
+        if interface not in self.list_interfaces():
+            return []
+        world = self.network.country == "00"
+        channels = []
+        for band, numbers in CHANNELS.items():
+            for number in numbers:
+                low = band == "2.4 GHz"
+                channels.append({
+                    "channel": number,
+                    "frequency": 2407 + 5 * number if low else 5000 + 5 * number,
+                    "band": band,
+                    "max_power_dbm": 20.0,
+                    "disabled": False,
+                    "no_ir": world and (not low or number > 11),
+                    "radar": not low and 52 <= number <= 144
+                })
+        return channels
+
+@register_backend("vpn", "mock")
+class MockVpnBackend(MockBackend, VpnBackend):
+    """Fake tunnels that appear as links while active"""
+
+    def discover(self) -> List[VpnConfig]:
+        return [
+            VpnConfig(name=name, path=Path(f"/mock/vpn/{name}.conf"), interface_name=name)
+            for name in self.network.vpn_configs
+        ]
+
+    async def connect(self, config: VpnConfig, certs: Optional[Dict[str, str]] = None) -> Tuple[bool, str]:
+        spec = self.network.vpn_configs.get(config.name)
+        if not spec:
+            return False, f"Unknown VPN {config.name}"
+        if spec.get("certificates") and not all(certs and certs.get(k) and Path(certs[k]).is_file()
+                                                for k in ("ca", "cert", "key")):
+            return False, "TLS handshake failed: certificate missing"
+
+        await asyncio.sleep(self.network.delays.get("vpn", 0))
+
+        self.network.links[config.name] = FakeLink(
+            name=config.name, interface_type=InterfaceType.VPN, up=True,
+            ip=spec.get("address"), dhcp_address=spec.get("address"), mtu=1420, credentials=certs
+        )
+        return True, f"Connected to {config.name}"
+
+    async def disconnect(self, config: VpnConfig) -> bool:
+        return self.network.links.pop(config.name, None) is not None
+
+    def is_active(self, config: VpnConfig) -> bool:
+        return config.name in self.network.links
+
+@register_backend("bluetooth", "mock")
+class MockBluetoothBackend(MockBackend, BluetoothBackend):
+    """Fake nearby Bluetooth devices"""
+
+    # Service names to the UUIDs BlueZ would list them under
+    SERVICE_UUIDS = {"Generic Access Profile": 0x1800, "Generic Attribute Profile": 0x1801, "Heart Rate": 0x180D,
+                     "Battery Service": 0x180F, "Audio Sink": 0x110B, "Handsfree": 0x111E}
+
+    def _record(self, spec: Dict) -> Dict:
+        mac = spec["mac"]
+        connected = self.network.paired.get(mac, False) or mac in self.network.le_connected
+        le = spec.get("type") == "le"
+        services = (["Generic Access Profile", "Generic Attribute Profile"] if le else []) + spec.get("services", [])
+        return dict(
+            bluetooth.blank(mac, spec.get("name")), alias=spec.get("alias", spec.get("name")), type=spec.get("type"),
+            address_type="random" if le else "public", paired=mac in self.network.paired, connected=connected,
+            rssi=spec.get("rssi"), battery=spec.get("battery") if connected else None,
+            battery_service="Battery Service" in services,
+            services=[{"uuid": f"0000{self.SERVICE_UUIDS.get(name, 0):04x}-0000-1000-8000-00805f9b34fb", "name": name}
+                      for name in services],
+            vendor=None if le else oui.lookup_vendor(mac)
+        )
+
+    async def scan(self) -> List[Dict]:
+        await asyncio.sleep(self.network.delays.get("bluetooth_scan", 0))
+        return [self._record(d) for d in self.network.bluetooth_devices]
+
+    async def devices(self) -> List[Dict]:
+        return [self._record(d) for d in self.network.bluetooth_devices]
+
+    async def device_info(self, mac_address: str) -> Optional[Dict]:
+        spec = next((d for d in self.network.bluetooth_devices if d["mac"] == mac_address), None)
+        return self._record(spec) if spec else None
+
+    async def pair(self, mac_address: str) -> bool:
+        if not any(d["mac"] == mac_address for d in self.network.bluetooth_devices):
+            return False
+        self.network.paired[mac_address] = False
+        return True
+
+    async def connect(self, mac_address: str) -> bool:
+        spec = next((d for d in self.network.bluetooth_devices if d["mac"] == mac_address), None)
+        if spec and spec.get("type") == "le":
+            self.network.le_connected.add(mac_address)
+            return True
+        if mac_address not in self.network.paired:
+            return False
+        self.network.paired[mac_address] = True
+        return True
+
+    async def send_file(self, mac_address: str, path: str, progress: Callable[[int, Optional[int]], None],
+                        uid: Optional[int] = None) -> Dict:
+        """Four chunks, delays["obex"] apart"""
+        try:
+            size = os.path.getsize(path)
+        except OSError as e:
+            raise obex.TransferError(f"Cannot read {path}: {e.strerror}")
+        spec = next((d for d in self.network.bluetooth_devices if d["mac"] == mac_address), {})
+        progress(0, size)
+        if not spec.get("push", True):
+            raise obex.TransferError(f"{mac_address} declined or dropped the transfer")
+        for chunk in range(1, 5):
+            await asyncio.sleep(self.network.delays.get("obex", 0))
+            progress(size * chunk // 4, size)
+        return {"file": os.path.basename(path), "size": size}
+
+    def paired_devices(self) -> List[Dict]:
+        names = {d["mac"]: d.get("name", d["mac"]) for d in self.network.bluetooth_devices}
+        return [
+            {"mac_address": mac, "name": names.get(mac, mac), "connected": connected, "vendor": oui.lookup_vendor(mac)}
+            for mac, connected in self.network.paired.items()
+        ]
+
+@register_backend("host", "mock")
+class MockHostBackend(MockBackend, HostBackend):
+    """Records host settings instead of applying them"""
+
+    async def set_dns(self, servers: List[str]) -> bool:
+        self.network.host["dns"] = list(servers)
+        return True
+
+    async def set_proxy(self, proxy: Optional[str]) -> bool:
+        self.network.host["proxy"] = proxy
+        return True
+
+    async def set_firewall_zone(self, interface: str, zone: str) -> bool:
+        if not self._link(interface):
+            return False
+        self.network.host["zones"][interface] = zone
+        return True
+
+    async def firewall_zone(self, interface: str) -> Optional[str]:
+        return self.network.host["zones"].get(interface)
+
+    def gateway_mac(self, gateway: str) -> Optional[str]:
+        for link in self.network.links.values():
+            if link.connected and link.gateway == gateway:
+                return link.gateway_mac
+        return None
+
+    async def query_dns(self, server: str, name: str, timeout: float) -> float:
+        latency = self.network.dns_latency.get(server, 20.0)
+        if latency is None or latency / 1000 > timeout:
+            raise OSError("timed out")
+        return float(latency)
+
+    def interface_address(self, interface: str) -> Optional[ipaddress.IPv4Interface]:
+        link = self._link(interface)
+        if not link or not link.connected or not link.ip:
+            return None
+        return ipaddress.IPv4Interface(f"{link.ip}/24")
+
+    async def scan_lan(self, interface: str, address: ipaddress.IPv4Interface) -> List[Dict]:
+        await asyncio.sleep(self.network.delays.get("scan", 0))
+        targets = set(lan_scan.sweep_targets(address))
+        return [{"ip": device["ip"], "mac": device["mac"], "hostname": device.get("hostname"),
+                 "name_source": device.get("name_source")}
+                for device in self.network.lan_devices if device["ip"] in targets]
+
+    async def probe_port(self, host: str, port: int, source: Optional[str], timeout: float) -> Dict:
+        if source and not any(link.connected and link.ip == source for link in self.network.links.values()):
+            return {"state": "unreachable", "address": host, "latency_ms": None,
+                    "error": "Cannot assign requested address"}
+        if f"{host}:{port}" not in self.network.ports:
+            return {"state": "closed", "address": host, "latency_ms": 1.0, "error": "Connection refused"}
+        latency = self.network.ports[f"{host}:{port}"]
+        if latency is None or latency / 1000 > timeout:
+            return {"state": "filtered", "address": host, "latency_ms": None, "error": f"no answer in {timeout:g}s"}
+        return {"state": "open", "address": host, "latency_ms": float(latency), "error": None}
+
+    async def check_families(self, host: str, port: int, timeout: float) -> Dict:
+        families = self.network.dual_stack.get(f"{host}:{port}", {})
+        probes = {}
+        for family, address in (("ipv4", "192.0.2.80"), ("ipv6", "2001:db8::80")):
+            if family not in families:
+                probes[family] = {"state": "unresolved", "address": None, "latency_ms": None,
+                                  "error": "Name or service not known"}
+            elif families[family] == "unreachable":
+                probes[family] = {"state": "unreachable", "address": address, "latency_ms": None,
+                                  "error": "Network is unreachable"}
+            elif families[family] is None or families[family] / 1000 > timeout:
+                probes[family] = {"state": "filtered", "address": address, "latency_ms": None,
+                                  "error": f"no answer in {timeout:g}s"}
+            else:
+                probes[family] = {"state": "open", "address": address, "latency_ms": float(families[family]),
+                                  "error": None}
+        return dict(probes, used=connectivity.race_outcome(probes["ipv4"], probes["ipv6"]))
+
+    async def ping(self, host: str, count: int, interval: float, timeout: float,
+                   interface: Optional[str] = None) -> List[Optional[float]]:
+        pattern = self.network.ping.get(host, 10.0)
+        pattern = pattern if isinstance(pattern, list) else [pattern]
+        sent = self.network.pings_sent.get(host, 0)
+        self.network.pings_sent[host] = sent + count
+        rtts = [pattern[(sent + index) % len(pattern)] for index in range(count)]
+        link = self._link(interface) if interface in self.network.loaded else None
+        if link:
+            # A full queue in front of the link delays every echo request behind it
+            extra = link.bloat.get(link.qdisc["kind"], 0)
+            rtts = [rtt + extra if rtt is not None else None for rtt in rtts]
+        return [float(rtt) if rtt is not None and rtt / 1000 <= timeout else None for rtt in rtts]
+
+    async def link_modes(self, interface: str) -> Optional[Dict]:
+        link = self._link(interface)
+        if not link or not link.max_speed:
+            return None
+        return {"max_speed": link.max_speed, "partner_max_speed": link.partner_max_speed if link.connected else None}
+
+    async def cable_test(self, interface: str) -> List[Dict]:
+        link = self._link(interface)
+        if not link or link.interface_type != InterfaceType.ETHERNET or link.cable is None:
+            raise OSError(f"{interface} does not support cable testing")
+        await asyncio.sleep(self.network.delays.get("cable_test", 0))
+        return [{"pair": pair, "status": link.cable.get(pair, {}).get("status", "ok"),
+                 "fault_length_m": link.cable.get(pair, {}).get("fault_length_m")} for pair in cable_test.PAIRS]
+
+    async def module_info(self, interface: str) -> Optional[Dict]:
+        link = self._link(interface)
+        return dict(link.module) if link and link.module else None
+
+    async def nic_queues(self, interface: str) -> Dict:
+        link = self._link(interface)
+        return {"queues": deepcopy(link.queues) if link else [], "irqs": deepcopy(link.irqs) if link else [],
+                "cpus": list(self.network.cpus)}
+
+    def set_irq_affinity(self, irq: int, cpus: List[int]) -> bool:
+        entry = next((entry for link in self.network.links.values() for entry in link.irqs if entry["irq"] == irq),
+                     None)
+        if not entry or not set(cpus) <= set(self.network.cpus):
+            return False
+        entry["cpus"] = sorted(cpus)
+        return True
+
+    async def flows(self) -> Tuple[List[Dict], Optional[int]]:
+        return deepcopy(self.network.flows), self.network.conntrack_max
+
+    async def announce_addresses(self, interface: str, mac: str, addresses: List[str]) -> Dict:
+        result = {"interface": interface, "sent": [], "errors": {}, "timestamp": time.time()}
+        link = self._link(interface)
+        for address in addresses:
+            if link and link.connected:
+                result["sent"].append(address)
+            else:
+                result["errors"][address] = "Network is down"
+        if result["sent"]:
+            self.network.host["announced"].append({"interface": interface, "mac": mac,
+                                                   "addresses": list(result["sent"])})
+        return result
+
+    def set_proxy_arp(self, interface: str, enabled: bool) -> bool:
+        if not self._link(interface):
+            return False
+        self.network.host["proxy_arp"][interface] = enabled
+        return True
+
+    def read_sysctl(self, key: str) -> Optional[str]:
+        if key in self.network.sysctls:
+            return self.network.sysctls[key]
+        parts = key.split(".")
+        if len(parts) == 5 and parts[2] == "conf" and self._link(parts[3].replace("/", ".")):
+            return MOCK_INTERFACE_SYSCTLS.get(parts[4], "0")
+        return None
+
+    def write_sysctl(self, key: str, value: str) -> Optional[str]:
+        if self.read_sysctl(key) is None:
+            return "No such file or directory"
+        if key == "net.ipv4.tcp_congestion_control" and \
+                value not in self.network.sysctls["net.ipv4.tcp_available_congestion_control"].split():
+            return "No such file or directory"
+        self.network.sysctls[key] = value
+        return None
+
+    def _mptcp(self) -> Dict:
+        if self.network.mptcp is None:
+            raise OSError(2, "The kernel has no mptcp_pm netlink family (MPTCP not built in)")
+        return self.network.mptcp
+
+    async def mptcp_state(self) -> Dict:
+        state = self._mptcp()
+        return {"enabled": True, "endpoints": deepcopy(state["endpoints"]), "limits": dict(state["limits"])}
+
+    async def add_mptcp_endpoint(self, address: str, interface: str, flags: List[str]) -> Dict:
+        state = self._mptcp()
+        if not self._link(interface):
+            raise OSError(19, "No such device")
+        if any(endpoint["address"] == address for endpoint in state["endpoints"]):
+            raise OSError(22, "mptcp_pm: Invalid argument")
+        endpoint = {"id": state["next_id"], "address": address, "port": None, "interface": interface,
+                    "flags": list(flags)}
+        state["next_id"] += 1
+        state["endpoints"].append(endpoint)
+        return dict(endpoint)
+
+    async def remove_mptcp_endpoint(self, endpoint_id: int):
+        state = self._mptcp()
+        if not any(endpoint["id"] == endpoint_id for endpoint in state["endpoints"]):
+            raise OSError(22, "mptcp_pm: Invalid argument")
+        state["endpoints"] = [endpoint for endpoint in state["endpoints"] if endpoint["id"] != endpoint_id]
+
+    async def set_mptcp_limits(self, subflows: int, add_addr_accepted: int):
+        self._mptcp()["limits"] = {"subflows": subflows, "add_addr_accepted": add_addr_accepted}
+
+    async def shaping_state(self, interface: str) -> Optional[Dict]:
+        link = self._link(interface)
+        if not link:
+            return None
+        return {"qdisc": dict(link.qdisc), "congestion_control": link.congestion_control}
+
+    async def set_qdisc(self, interface: str, qdisc: Optional[str], bandwidth: Optional[str] = None) -> Optional[str]:
+        link = self._link(interface)
+        if not link:
+            return "Cannot find device"
+        link.qdisc = {"kind": qdisc or "fq_codel", "bandwidth": bandwidth if qdisc else None}
+        return None
+
+    async def set_congestion_control(self, interface: str, algorithm: Optional[str]) -> Optional[str]:
+        link = self._link(interface)
+        if not link:
+            return "Cannot find device"
+        if algorithm and algorithm not in self.network.sysctls["net.ipv4.tcp_available_congestion_control"].split():
+            return "No such file or directory"
+        link.congestion_control = algorithm
+        return None
+
+    async def load_link(self, interface: str, direction: str, seconds: float, url: Optional[str] = None) -> Dict:
+        link = self._link(interface)
+        if not link or not link.connected:
+            raise OSError(f"no data moved over {interface} (curl exited with 7)")
+        rate = link.throughput.get(direction) or link.link_speed or 100
+        if link.qdisc["bandwidth"]:
+            rate = min(rate, shaping.bandwidth_bits(link.qdisc["bandwidth"]) / 1_000_000)
+        self.network.loaded[interface] = direction
+        try:
+            await asyncio.sleep(seconds)
+        finally:
+            self.network.loaded.pop(interface, None)
+        return {"bytes": int(rate * 1_000_000 / 8 * seconds), "seconds": seconds, "mbps": round(rate, 1)}
+
+@register_backend("services", "mock")
+class MockServiceBackend(MockBackend, ServiceBackend):
+    """Fake systemd units that only flip an active flag"""
+
+    async def start_unit(self, unit: str) -> Tuple[bool, str]:
+        return self._set(unit, True)
+
+    async def stop_unit(self, unit: str) -> Tuple[bool, str]:
+        return self._set(unit, False)
+
+    def _set(self, unit: str, active: bool) -> Tuple[bool, str]:
+        if unit not in self.network.units:
+            return False, f"Unit {unit} not found."
+        self.network.units[unit] = active
+        return True, f"{unit} {'started' if active else 'stopped'}"
+
+@register_backend("rfkill", "mock")
+class MockRfkillBackend(MockBackend, RfkillBackend):
+    """The scenario's kill switches; soft-blocking a WiFi radio drops its association"""
+
+    def devices(self) -> List[RfkillDevice]:
+        return deepcopy(self.network.rfkill)
+
+    def set_soft_block(self, index: int, blocked: bool) -> bool:
+        device = next((d for d in self.network.rfkill if d.index == index), None)
+        if not device:
+            return False
+        device.soft = blocked
+        if device.interface and device.block:
+            self.network.set_carrier(device.interface, False)
+        elif device.interface:
+            self.network.links[device.interface].carrier = True
+        return True
+
+@register_backend("hotspot", "mock")
+class MockHotspotBackend(MockBackend, HotspotBackend):
+    """Every scenario station joins a running hotspot (and asks for an address, if a DHCP server
+    runs there); kicked ones stay away until it restarts"""
+
+    async def start(self, interface: str, ssid: str, password: Optional[str], channel: int,
+                    network: ipaddress.IPv4Interface, blocked: List[str],
+                    allowed: Optional[List[str]] = None, isolate: bool = False) -> Tuple[bool, str]:
+        link = self._link(interface)
+        if not link or link.interface_type != InterfaceType.WIFI:
+            return False, f"{interface} is not a WiFi interface"
+        if self.network.radio_block(interface):
+            return False, "radio is blocked"
+
+        await asyncio.sleep(self.network.delays.get("associate", 0))
+        link.ssid = None
+        link.up = link.carrier = True
+        link.ip = str(network.ip)
+        link.hotspot = {"ssid": ssid, "channel": channel, "blocked": set(blocked),
+                        "allowed": set(allowed) if allowed is not None else None, "kicked": set(),
+                        "limits": {}, "isolated": isolate, "started": self.network.ticks}
+        return True, f"Sharing as {ssid} on channel {channel}"
+
+    async def stop(self, interface: str, network: Optional[ipaddress.IPv4Interface] = None) -> bool:
+        link = self._link(interface)
+        if not link:
+            return False
+        link.hotspot = None
+        link.up = False
+        link.ip = None
+        return True
+
+    def stations(self, interface: str) -> List[Dict]:
+        link = self._link(interface)
+        if not link or not link.hotspot:
+            return []
+        ticks = self.network.ticks - link.hotspot["started"]
+        present = [spec for spec in self.network.hotspot_clients if spec["mac"] not in link.hotspot["kicked"]
+                   and admitted(spec["mac"], link.hotspot["allowed"], link.hotspot["blocked"])]
+        server = self.network.dhcp_servers.get(interface)
+        for spec in present:
+            if server and spec["mac"] not in {lease.mac for lease in server.pool.active()}:
+                dhcp_handshake(server, spec["mac"], spec.get("hostname"))
+        return [
+            {"mac": spec["mac"], "signal": spec.get("signal", -55),
+             "rx_bytes": self._limited(spec.get("rx_rate", 0), link.hotspot["limits"], spec["mac"], "up") * ticks,
+             "tx_bytes": self._limited(spec.get("tx_rate", 0), link.hotspot["limits"], spec["mac"], "down") * ticks,
+             "connected_time": ticks, "inactive_ms": 0}
+            for spec in present
+        ]
+
+    @staticmethod
+    def _limited(rate: int, limits: Dict[str, Dict], mac: str, direction: str) -> int:
+        """Bytes per tick once a kbit/s limit has its say"""
+        limit = limits.get(mac, {}).get(direction)
+        return min(rate, limit * 125) if limit else rate
+
+    def kick(self, interface: str, mac: str) -> bool:
+        link = self._link(interface)
+        if not link or not link.hotspot or mac not in {s["mac"] for s in self.stations(interface)}:
+            return False
+        link.hotspot["kicked"].add(mac)
+        return True
+
+    def set_blocked(self, interface: str, mac: str, blocked: bool, all_blocked: List[str]) -> bool:
+        link = self._link(interface)
+        if not link or not link.hotspot:
+            return False
+        link.hotspot["blocked"] = set(all_blocked)
+        return True
+
+    def set_filter(self, interface: str, allowed: Optional[List[str]], blocked: List[str]) -> bool:
+        link = self._link(interface)
+        if not link or not link.hotspot:
+            return False
+        link.hotspot["allowed"] = set(allowed) if allowed is not None else None
+        link.hotspot["blocked"] = set(blocked)
+        return True
+
+    async def set_isolation(self, interface: str, isolate: bool) -> bool:
+        link = self._link(interface)
+        if not link or not link.hotspot:
+            return False
+        link.hotspot["isolated"] = isolate
+        return True
+
+    async def set_limits(self, interface: str, limits: Dict[str, Dict]) -> bool:
+        link = self._link(interface)
+        if not link or not link.hotspot:
+            return False
+        link.hotspot["limits"] = {mac: dict(limit) for mac, limit in limits.items()}
+        return True
+
+def dhcp_handshake(server: DhcpServer, mac: str, hostname: Optional[str] = None) -> Optional[str]:
+    """DISCOVER then REQUEST the way a joining client does, over the wire format; the address it gets"""
+    xid = zlib.crc32(mac.encode())
+    options = {OPT_HOSTNAME: hostname.encode()} if hostname else {}
+
+    def exchange(kind: int, extra: Optional[Dict[int, bytes]] = None) -> Optional[DhcpPacket]:
+        request = DhcpPacket(op=BOOTREQUEST, xid=xid, mac=mac,
+                             options={**options, OPT_MESSAGE_TYPE: bytes([kind]), **(extra or {})})
+        reply = server.handle(DhcpPacket.decode(request.encode()))
+        return DhcpPacket.decode(reply.encode()) if reply else None
+
+    offer = exchange(DISCOVER)
+    if not offer:
+        return None
+    ack = exchange(REQUEST, {OPT_REQUESTED_IP: socket.inet_aton(offer.yiaddr),
+                             OPT_SERVER_ID: offer.options[OPT_SERVER_ID]})
+    return ack.yiaddr if ack and ack.message_type == ACK else None
+
+@register_backend("dhcp_server", "mock")
+class MockDhcpServerBackend(MockBackend, BuiltinDhcpServerBackend):
+    """The built-in server's lease logic with no sockets and no lease files"""
+
+    lease_dir = None
+
+    def __init__(self, network: Optional[FakeNetwork] = None):
+        MockBackend.__init__(self, network)
+        self.servers = self.network.dhcp_servers
+
+    async def _listen(self, server: DhcpServer):
+        pass
+
+@register_backend("accounting", "mock")
+class MockAccountingBackend(MockBackend, AccountingBackend):
+    """Counters that grow by the scenario's amounts each time they are read"""
+
+    def __init__(self, network: Optional[FakeNetwork] = None):
+        MockBackend.__init__(self, network)
+        self.counted: Dict[str, Dict[str, int]] = {}
+
+    def cgroups(self) -> Dict[str, int]:
+        return {cgroup: index + 1 for index, cgroup in enumerate(self.network.unit_traffic)}
+
+    async def sync(self, cgroups: List[str]) -> Tuple[bool, str]:
+        self.counted = {cgroup: {"rx": 0, "tx": 0} for cgroup in cgroups}
+        return True, f"Counting {len(cgroups)} units"
+
+    async def counters(self) -> Dict[str, Dict[str, int]]:
+        for cgroup, counts in self.counted.items():
+            step = self.network.unit_traffic.get(cgroup, {})
+            for direction in ("rx", "tx"):
+                counts[direction] += step.get(direction, 0)
+        return deepcopy(self.counted)
+
+    async def clear(self) -> bool:
+        self.counted = {}
+        return True
+
+# How the mock splits a link's traffic between protocols
+MOCK_PROTOCOL_SHARE = {"tcp": 0.8, "udp": 0.15, "icmp": 0.01, "other": 0.04}
+MOCK_PACKET_SIZE = 1000
+
+@register_backend("counters", "mock")
+class MockCountersBackend(MockBackend, CountersBackend):
+    """Counters that grow at each fake link's rate, in real time"""
+
+    def __init__(self, network: Optional[FakeNetwork] = None):
+        MockBackend.__init__(self, network)
+        self.counted: Dict[str, Dict[str, float]] = {}
+        self.read_at: Optional[float] = None
+
+    def unavailable(self, object_path: str) -> Optional[str]:
+        return self.network.ebpf_unavailable
+
+    async def start(self, object_path: str) -> Tuple[bool, str]:
+        return True, "Loaded"
+
+    async def attach(self, interface: str) -> Tuple[bool, str]:
+        if not self._link(interface):
+            return False, "No such interface"
+        self.counted[interface] = {"rx": 0.0, "tx": 0.0}
+        return True, "Attached"
+
+    async def detach(self, interface: str) -> bool:
+        self.counted.pop(interface, None)
+        return True
+
+    def read(self) -> Dict[str, Dict[str, Dict[str, Tuple[int, int]]]]:
+        now = time.monotonic()
+        elapsed = now - self.read_at if self.read_at is not None else 0.0
+        self.read_at = now
+        counters = {}
+        for interface, counts in self.counted.items():
+            link = self._link(interface)
+            if link and link.connected:
+                counts["rx"] += link.rx_rate * elapsed
+                counts["tx"] += link.tx_rate * elapsed
+            counters[interface] = {
+                direction: {protocol: (int(total * share / MOCK_PACKET_SIZE), int(total * share))
+                            for protocol, share in MOCK_PROTOCOL_SHARE.items()}
+                for direction, total in counts.items()
+            }
+        return counters
+
+    async def stop(self):
+        self.counted = {}
+
+@register_backend("containers", "mock")
+class MockContainersBackend(MockBackend, ContainersBackend):
+    """Engines from the scenario's container_engines, bridge ports from the fake links"""
+
+    async def networks(self, socket_path: str) -> List[Dict]:
+        engine = containers.engine_name(socket_path)
+        if engine not in self.network.container_engines:
+            raise FileNotFoundError(socket_path)
+        parsed = [containers.parse_network(engine, raw) for raw in self.network.container_engines[engine]]
+        return [network for network in parsed if network]
+
+    def masters(self) -> Dict[str, str]:
+        return {link.name: link.master for link in self.network.links.values() if link.master}
+
+@register_backend("libvirt", "mock")
+class MockLibvirtBackend(MockBackend, LibvirtBackend):
+    """The scenario's libvirt_networks, whatever the URI"""
+
+    async def networks(self, uri: str) -> List[Dict]:
+        if self.network.libvirt_networks is None:
+            raise OSError(f"failed to connect to the hypervisor {uri}")
+        return [dict({"forward": "nat", "subnets": [], "active": True, "guests": []}, **deepcopy(network))
+                for network in self.network.libvirt_networks]
+
+def mock_backends(network: FakeNetwork) -> BackendSet:
+    """Backends that all act on the same fake network"""
+    return BackendSet(
+        ethernet=MockEthernetBackend(network),
+        wifi=MockWifiBackend(network),
+        vpn=MockVpnBackend(network),
+        bluetooth=MockBluetoothBackend(network),
+        host=MockHostBackend(network),
+        services=MockServiceBackend(network),
+        rfkill=MockRfkillBackend(network),
+        hotspot=MockHotspotBackend(network),
+        dhcp_server=MockDhcpServerBackend(network),
+        accounting=MockAccountingBackend(network),
+        counters=MockCountersBackend(network),
+        containers=MockContainersBackend(network),
+        libvirt=MockLibvirtBackend(network)
+    )
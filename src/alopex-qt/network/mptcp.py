@@ -0,0 +1,193 @@
+"""
+MPTCP - Multipath TCP endpoints over the mptcp_pm generic netlink family
+An endpoint tells the kernel's path manager it may use an address: "subflow"
+opens extra subflows from it, "signal" announces it to the peer, "backup" only
+carries traffic when the others fail and "fullmesh" opens a subflow to every
+address the peer has. With WiFi and LTE both endpoints, an MPTCP-capable
+application keeps its connections across either link going away. The same
+calls as ip mptcp endpoint and ip mptcp limits
+"""
+
+import socket
+import struct
+import logging
+import ipaddress
+from pathlib import Path
+from typing import Dict, List, Optional, Tuple
+
+from .netlink import (
+    ENOENT, NLA_F_NESTED, NLM_F_ACK, NLM_F_DUMP, GenericNetlink, attr, attributes, family_request, genl_message,
+    genl_replies
+)
+
+# Configure logging
+logger = logging.getLogger(__name__)
+
+ENABLED_FILE = Path("/proc/sys/net/mptcp/enabled")
+FAMILY_NAME = "mptcp_pm"
+FAMILY_VERSION = 1
+
+# Commands and attributes from linux/mptcp.h
+MPTCP_PM_CMD_ADD_ADDR = 1
+MPTCP_PM_CMD_DEL_ADDR = 2
+MPTCP_PM_CMD_GET_ADDR = 3
+MPTCP_PM_CMD_SET_LIMITS = 5
+MPTCP_PM_CMD_GET_LIMITS = 6
+MPTCP_PM_ATTR_ADDR = 1
+MPTCP_PM_ATTR_RCV_ADD_ADDRS = 2
+MPTCP_PM_ATTR_SUBFLOWS = 3
+ADDR_ATTR_FAMILY = 1
+ADDR_ATTR_ID = 2
+ADDR_ATTR_ADDR4 = 3
+ADDR_ATTR_ADDR6 = 4
+ADDR_ATTR_PORT = 5
+ADDR_ATTR_FLAGS = 6
+ADDR_ATTR_IF_IDX = 7
+
+FLAGS = {"signal": 1 << 0, "subflow": 1 << 1, "backup": 1 << 2, "fullmesh": 1 << 3}
+# Set by the kernel on endpoints it made for addresses the peer announced; never asked for
+FLAG_IMPLICIT = 1 << 4
+# MPTCP_PM_ADDR_MAX: the most subflows and accepted ADD_ADDRs the kernel allows
+MAX_LIMIT = 8
+
+def check_flags(flags: Optional[List[str]]) -> List[str]:
+    """A profile's endpoint flags in canonical order; raises ValueError for ones the kernel would refuse"""
+    flags = list(flags or [])
+    unknown = [flag for flag in flags if flag not in FLAGS]
+    if unknown:
+        raise ValueError(f"Unknown MPTCP flag {unknown[0]!r}; use {', '.join(FLAGS)}")
+    if "signal" in flags and "fullmesh" in flags:
+        raise ValueError("An MPTCP endpoint cannot be both signal and fullmesh")
+    return [flag for flag in FLAGS if flag in flags]
+
+def flag_bits(flags: List[str]) -> int:
+    bits = 0
+    for flag in flags:
+        bits |= FLAGS[flag]
+    return bits
+
+def flag_names(bits: int) -> List[str]:
+    names = [flag for flag, bit in FLAGS.items() if bits & bit]
+    return names + (["implicit"] if bits & FLAG_IMPLICIT else [])
+
+def endpoint_addresses(addresses: List[Dict]) -> List[str]:
+    """The addresses (reconcile.parse_addresses entries) that can be endpoints: not loopback or link-local"""
+    targets = []
+    for entry in addresses:
+        try:
+            address = ipaddress.ip_interface(entry["address"]).ip
+        except ValueError:
+            continue
+        if not address.is_loopback and not address.is_link_local and str(address) not in targets:
+            targets.append(str(address))
+    return targets
+
+def build_message(family: int, command: int, attrs: bytes = b"", flags: int = NLM_F_ACK, seq: int = 1) -> bytes:
+    return genl_message(family, command, FAMILY_VERSION, attrs, flags, seq)
+
+def build_family_request(seq: int = 1) -> bytes:
+    return family_request(FAMILY_NAME, seq)
+
+def build_add(family: int, address: str, index: int, flags: List[str], seq: int = 1) -> bytes:
+    """MPTCP_PM_CMD_ADD_ADDR for address on the interface with this index; the kernel picks the ID"""
+    ip = ipaddress.ip_address(address)
+    nested = (attr(ADDR_ATTR_FAMILY, struct.pack("=H", socket.AF_INET if ip.version == 4 else socket.AF_INET6))
+              + attr(ADDR_ATTR_ADDR4 if ip.version == 4 else ADDR_ATTR_ADDR6, ip.packed)
+              + attr(ADDR_ATTR_FLAGS, struct.pack("=I", flag_bits(flags)))
+              + attr(ADDR_ATTR_IF_IDX, struct.pack("=i", index)))
+    return build_message(family, MPTCP_PM_CMD_ADD_ADDR, attr(MPTCP_PM_ATTR_ADDR | NLA_F_NESTED, nested), seq=seq)
+
+def build_delete(family: int, endpoint_id: int, seq: int = 1) -> bytes:
+    nested = attr(ADDR_ATTR_ID, struct.pack("=B", endpoint_id))
+    return build_message(family, MPTCP_PM_CMD_DEL_ADDR, attr(MPTCP_PM_ATTR_ADDR | NLA_F_NESTED, nested), seq=seq)
+
+def build_set_limits(family: int, subflows: int, add_addr_accepted: int, seq: int = 1) -> bytes:
+    attrs = (attr(MPTCP_PM_ATTR_RCV_ADD_ADDRS, struct.pack("=I", add_addr_accepted))
+             + attr(MPTCP_PM_ATTR_SUBFLOWS, struct.pack("=I", subflows)))
+    return build_message(family, MPTCP_PM_CMD_SET_LIMITS, attrs, seq=seq)
+
+def parse_replies(data: bytes) -> Tuple[List[Dict[int, bytes]], bool]:
+    """Attributes of each mptcp_pm reply in a buffer, and whether the exchange is over"""
+    return genl_replies(data, FAMILY_NAME)
+
+def parse_endpoint(attrs: Dict[int, bytes]) -> Dict:
+    """One endpoint from the attributes nested in MPTCP_PM_ATTR_ADDR"""
+    address = None
+    if ADDR_ATTR_ADDR4 in attrs:
+        address = str(ipaddress.IPv4Address(attrs[ADDR_ATTR_ADDR4][:4]))
+    elif ADDR_ATTR_ADDR6 in attrs:
+        address = str(ipaddress.IPv6Address(attrs[ADDR_ATTR_ADDR6][:16]))
+    index = struct.unpack("=i", attrs[ADDR_ATTR_IF_IDX][:4])[0] if ADDR_ATTR_IF_IDX in attrs else 0
+    try:
+        interface = socket.if_indextoname(index) if index else None
+    except OSError:
+        interface = None
+    return {
+        "id": attrs[ADDR_ATTR_ID][0] if ADDR_ATTR_ID in attrs else None,
+        "address": address,
+        "port": struct.unpack("=H", attrs[ADDR_ATTR_PORT][:2])[0] if ADDR_ATTR_PORT in attrs else None,
+        "interface": interface,
+        "flags": flag_names(struct.unpack("=I", attrs[ADDR_ATTR_FLAGS][:4])[0] if ADDR_ATTR_FLAGS in attrs else 0)
+    }
+
+def available() -> bool:
+    """Whether the kernel has MPTCP and it is switched on (net.mptcp.enabled)"""
+    try:
+        return ENABLED_FILE.read_text().strip() == "1"
+    except OSError:
+        return False
+
+class PathManager(GenericNetlink):
+    """One generic netlink socket to the in-kernel path manager; needs CAP_NET_ADMIN to change anything"""
+
+    def __init__(self):
+        try:
+            super().__init__(FAMILY_NAME, FAMILY_VERSION)
+        except OSError as e:
+            if e.errno == ENOENT:
+                raise OSError(ENOENT, "The kernel has no mptcp_pm netlink family (MPTCP not built in)")
+            raise
+
+    def endpoints(self) -> List[Dict]:
+        replies = self._exchange(build_message(self.family, MPTCP_PM_CMD_GET_ADDR, flags=NLM_F_DUMP,
+                                               seq=self._next()), dump=True)
+        return [parse_endpoint(attributes(reply[MPTCP_PM_ATTR_ADDR])) for reply in replies
+                if MPTCP_PM_ATTR_ADDR in reply]
+
+    def add(self, address: str, interface: str, flags: List[str]) -> Dict:
+        """Add the endpoint and return it as the kernel now lists it"""
+        self._exchange(build_add(self.family, address, socket.if_nametoindex(interface), flags, self._next()))
+        endpoint = next((e for e in self.endpoints() if e["address"] == address), None)
+        if not endpoint:
+            raise OSError(f"The kernel accepted endpoint {address} but does not list it")
+        return endpoint
+
+    def remove(self, endpoint_id: int):
+        self._exchange(build_delete(self.family, endpoint_id, self._next()))
+
+    def limits(self) -> Dict:
+        replies = self._exchange(build_message(self.family, MPTCP_PM_CMD_GET_LIMITS, flags=0, seq=self._next()))
+        attrs = replies[0] if replies else {}
+        return {name: struct.unpack("=I", attrs[kind][:4])[0] if kind in attrs else None
+                for name, kind in (("subflows", MPTCP_PM_ATTR_SUBFLOWS),
+                                   ("add_addr_accepted", MPTCP_PM_ATTR_RCV_ADD_ADDRS))}
+
+    def set_limits(self, subflows: int, add_addr_accepted: int):
+        self._exchange(build_set_limits(self.family, subflows, add_addr_accepted, self._next()))
+
+def read_state() -> Dict:
+    """{"enabled", "endpoints", "limits"}; raises OSError without MPTCP"""
+    with PathManager() as manager:
+        return {"enabled": available(), "endpoints": manager.endpoints(), "limits": manager.limits()}
+
+def add_endpoint(address: str, interface: str, flags: List[str]) -> Dict:
+    with PathManager() as manager:
+        return manager.add(address, interface, flags)
+
+def remove_endpoint(endpoint_id: int):
+    with PathManager() as manager:
+        manager.remove(endpoint_id)
+
+def set_limits(subflows: int, add_addr_accepted: int):
+    with PathManager() as manager:
+        manager.set_limits(subflows, add_addr_accepted)
@@ -0,0 +1,108 @@
+"""
+Neighbor Announce - Gratuitous ARP and unsolicited neighbour advertisements
+When an address comes up on an interface, or traffic moves to another link on
+failover, neighbours keep sending to the MAC they cached until it times out.
+Announcing the address (a gratuitous ARP request for IPv4, an unsolicited NA
+with the override flag for IPv6) makes them update straight away. Proxy ARP
+and proxy NDP let an interface answer for addresses behind it, for bridged or
+shared setups
+"""
+
+import time
+import socket
+import struct
+import logging
+import ipaddress
+from pathlib import Path
+from typing import Dict, List
+
+# Configure logging
+logger = logging.getLogger(__name__)
+
+PROC_CONF = Path("/proc/sys/net")
+# Announced this many times, a second apart, in case the first is lost
+ANNOUNCE_ROUNDS = 3
+ANNOUNCE_INTERVAL = 1.0
+
+ETH_P_ARP = 0x0806
+ETH_BROADCAST = b"\xff" * 6
+ARP_REQUEST = 1
+ICMPV6_NEIGHBOR_ADVERT = 136
+NA_FLAG_OVERRIDE = 0x20000000
+OPTION_TARGET_LINK_ADDRESS = 2
+ALL_NODES = "ff02::1"
+# RFC 4861: neighbours drop NAs that crossed a router
+NDP_HOP_LIMIT = 255
+
+def _mac_bytes(mac: str) -> bytes:
+    return bytes.fromhex(mac.replace(":", "").replace("-", ""))
+
+def announce_targets(addresses: List[Dict]) -> List[str]:
+    """The addresses (reconcile.parse_addresses entries) worth announcing: everything but loopback"""
+    targets = []
+    for entry in addresses:
+        try:
+            address = ipaddress.ip_interface(entry["address"]).ip
+        except ValueError:
+            continue
+        if not address.is_loopback and str(address) not in targets:
+            targets.append(str(address))
+    return targets
+
+def gratuitous_arp(mac: str, ip: str) -> bytes:
+    """Ethernet frame of a gratuitous ARP request: sender and target are both ip"""
+    source = _mac_bytes(mac)
+    address = ipaddress.IPv4Address(ip).packed
+    arp = struct.pack("!HHBBH6s4s6s4s", 1, 0x0800, 6, 4, ARP_REQUEST, source, address, b"\x00" * 6, address)
+    return ETH_BROADCAST + source + struct.pack("!H", ETH_P_ARP) + arp
+
+def unsolicited_na(mac: str, ip: str) -> bytes:
+    """ICMPv6 body of an unsolicited neighbour advertisement for ip; the kernel fills in the checksum"""
+    target = ipaddress.IPv6Address(ip).packed
+    return (struct.pack("!BBHI16s", ICMPV6_NEIGHBOR_ADVERT, 0, 0, NA_FLAG_OVERRIDE, target)
+            + struct.pack("!BB6s", OPTION_TARGET_LINK_ADDRESS, 1, _mac_bytes(mac)))
+
+def _send_arp(interface: str, mac: str, ip: str):
+    with socket.socket(socket.AF_PACKET, socket.SOCK_RAW, socket.htons(ETH_P_ARP)) as sock:
+        sock.bind((interface, ETH_P_ARP))
+        sock.send(gratuitous_arp(mac, ip))
+
+def _send_na(interface: str, mac: str, ip: str):
+    index = socket.if_nametoindex(interface)
+    with socket.socket(socket.AF_INET6, socket.SOCK_RAW, socket.IPPROTO_ICMPV6) as sock:
+        sock.setsockopt(socket.IPPROTO_IPV6, socket.IPV6_MULTICAST_HOPS, NDP_HOP_LIMIT)
+        sock.setsockopt(socket.IPPROTO_IPV6, socket.IPV6_MULTICAST_IF, index)
+        sock.bind((ip, 0, 0, index))
+        sock.sendto(unsolicited_na(mac, ip), (ALL_NODES, 0, 0, index))
+
+def announce(interface: str, mac: str, addresses: List[str]) -> Dict:
+    """Announce each address once on interface (needs CAP_NET_RAW): {"sent": [...], "errors": {address: why}}"""
+    result = {"interface": interface, "sent": [], "errors": {}, "timestamp": time.time()}
+    for address in addresses:
+        try:
+            if ipaddress.ip_address(address).version == 4:
+                _send_arp(interface, mac, address)
+            else:
+                _send_na(interface, mac, address)
+            result["sent"].append(address)
+        except (OSError, ValueError) as e:
+            result["errors"][address] = str(e)
+    if result["errors"]:
+        logger.warning(f"Could not announce {', '.join(result['errors'])} on {interface}: "
+                       f"{next(iter(result['errors'].values()))}")
+    return result
+
+def set_proxy_arp(interface: str, enabled: bool, proc_conf: Path = PROC_CONF) -> bool:
+    """Turn proxy ARP and proxy NDP on or off for interface; False if the kernel refuses"""
+    value = "1" if enabled else "0"
+    try:
+        (proc_conf / "ipv4" / "conf" / interface / "proxy_arp").write_text(value)
+    except OSError as e:
+        logger.warning(f"Cannot set proxy ARP on {interface}: {e}")
+        return False
+    try:
+        (proc_conf / "ipv6" / "conf" / interface / "proxy_ndp").write_text(value)
+    except OSError as e:
+        # IPv6 disabled on the interface; proxy ARP alone still helps
+        logger.debug(f"Cannot set proxy NDP on {interface}: {e}")
+    return True
@@ -0,0 +1,155 @@
+"""
+Netlink - Message framing shared by the rtnetlink, ctnetlink and generic netlink users
+Headers, type-length-value attributes and error acknowledgements; each caller
+adds the family header and attribute types of its own subsystem. rtnetlink
+requests that only need the kernel's ack go through one helper; generic
+netlink families (mptcp_pm, nl80211) also share the socket that looks up the
+family's ID and trades requests for replies
+"""
+
+import os
+import socket
+import struct
+import logging
+from typing import Dict, Iterator, List, Tuple
+
+# Configure logging
+logger = logging.getLogger(__name__)
+
+NLM_F_REQUEST = 0x1
+NLM_F_ACK = 0x4
+NLM_F_DUMP = 0x300
+NLMSG_ERROR = 2
+NLMSG_DONE = 3
+NLA_F_NESTED = 0x8000
+NLA_TYPE_MASK = 0x3fff
+HEADER_SIZE = 16
+NETLINK_GENERIC = 16
+GENL_ID_CTRL = 0x10
+CTRL_CMD_GETFAMILY = 3
+CTRL_ATTR_FAMILY_ID = 1
+CTRL_ATTR_FAMILY_NAME = 2
+ENOENT = 2
+RECV_SIZE = 65536
+RECV_TIMEOUT = 5.0
+
+def message(kind: int, flags: int, body: bytes, seq: int = 1) -> bytes:
+    """One request: nlmsghdr (the sender's port left to the kernel) and body"""
+    return struct.pack("=IHHII", HEADER_SIZE + len(body), kind, flags, seq, 0) + body
+
+def attr(kind: int, value: bytes) -> bytes:
+    """One attribute, padded to four bytes"""
+    encoded = struct.pack("=HH", 4 + len(value), kind) + value
+    return encoded + b"\0" * (-len(encoded) % 4)
+
+def attributes(data: bytes) -> Dict[int, bytes]:
+    """Netlink attributes by type (flags stripped); later duplicates win"""
+    attrs = {}
+    offset = 0
+    while offset + 4 <= len(data):
+        length, kind = struct.unpack_from("=HH", data, offset)
+        if length < 4:
+            break
+        attrs[kind & NLA_TYPE_MASK] = data[offset + 4:offset + length]
+        offset += (length + 3) & ~3
+    return attrs
+
+def messages(data: bytes) -> Iterator[Tuple[int, bytes]]:
+    """Type and body of each message in a buffer of replies"""
+    offset = 0
+    while offset + HEADER_SIZE <= len(data):
+        length, kind, _, _, _ = struct.unpack_from("=IHHII", data, offset)
+        if length < HEADER_SIZE:
+            break
+        yield kind, data[offset + HEADER_SIZE:offset + length]
+        offset += (length + 3) & ~3
+
+def check_error(body: bytes, what: str):
+    """Raise OSError, naming what answered, when an NLMSG_ERROR body carries an error; zero is an ack"""
+    error = struct.unpack_from("=i", body)[0]
+    if error:
+        raise OSError(-error, f"{what}: {os.strerror(-error)}")
+
+def parse_ack(data: bytes, what: str = "rtnetlink"):
+    """Raise OSError for a negative acknowledgement"""
+    if len(data) < HEADER_SIZE + 4:
+        raise OSError(f"Short {what} reply")
+    for kind, body in messages(data):
+        if kind == NLMSG_ERROR:
+            check_error(body, what)
+
+def rtnetlink_request(request: bytes):
+    """Send one rtnetlink request and wait for its ack; raises OSError (most changes need CAP_NET_ADMIN)"""
+    with socket.socket(socket.AF_NETLINK, socket.SOCK_RAW, socket.NETLINK_ROUTE) as sock:
+        sock.settimeout(RECV_TIMEOUT)
+        sock.bind((0, 0))
+        sock.send(request)
+        parse_ack(sock.recv(4096))
+
+def genl_message(family: int, command: int, version: int, attrs: bytes = b"", flags: int = NLM_F_ACK,
+                 seq: int = 1) -> bytes:
+    """One generic netlink request: nlmsghdr, genlmsghdr and attributes"""
+    return message(family, NLM_F_REQUEST | flags, struct.pack("=BBH", command, version, 0) + attrs, seq)
+
+def family_request(name: str, seq: int = 1) -> bytes:
+    """CTRL_CMD_GETFAMILY for a generic netlink family by name"""
+    return genl_message(GENL_ID_CTRL, CTRL_CMD_GETFAMILY, 1, attr(CTRL_ATTR_FAMILY_NAME, name.encode() + b"\0"),
+                        flags=0, seq=seq)
+
+def genl_replies(data: bytes, what: str) -> Tuple[List[Dict[int, bytes]], bool]:
+    """Attributes of each generic netlink reply in a buffer, and whether the exchange is over (DONE or the
+    acknowledgement); raises OSError for an error reply"""
+    replies = []
+    for kind, body in messages(data):
+        if kind == NLMSG_DONE:
+            return replies, True
+        if kind == NLMSG_ERROR:
+            check_error(body, what)
+            return replies, True
+        # Skip the 4-byte genlmsghdr
+        replies.append(attributes(body[4:]))
+    return replies, False
+
+class GenericNetlink:
+    """One generic netlink socket to a named family; raises OSError(ENOENT) when the kernel lacks it"""
+
+    def __init__(self, name: str, version: int):
+        self.name = name
+        self.version = version
+        self.sock = socket.socket(socket.AF_NETLINK, socket.SOCK_RAW, NETLINK_GENERIC)
+        self.sock.settimeout(RECV_TIMEOUT)
+        self.sock.bind((0, 0))
+        self.seq = 0
+        try:
+            replies = self._exchange(family_request(name, self._next()))
+        except OSError:
+            self.close()
+            raise
+        self.family = struct.unpack("=H", replies[0][CTRL_ATTR_FAMILY_ID][:2])[0]
+
+    def _next(self) -> int:
+        self.seq += 1
+        return self.seq
+
+    def _exchange(self, message: bytes, dump: bool = False) -> List[Dict[int, bytes]]:
+        """Send a request and collect the replies: up to DONE for a dump, else the one reply or the ack"""
+        self.sock.send(message)
+        replies = []
+        while True:
+            batch, done = genl_replies(self.sock.recv(RECV_SIZE), self.name)
+            replies.extend(batch)
+            if done or (replies and not dump):
+                return replies
+
+    def request(self, command: int, attrs: bytes = b"", flags: int = NLM_F_ACK,
+                dump: bool = False) -> List[Dict[int, bytes]]:
+        return self._exchange(genl_message(self.family, command, self.version, attrs, flags, self._next()), dump)
+
+    def close(self):
+        self.sock.close()
+
+    def __enter__(self):
+        return self
+
+    def __exit__(self, *exc):
+        self.close()
@@ -0,0 +1,131 @@
+"""
+NIC Queues - How a multiqueue NIC's traffic and interrupts spread over the CPUs
+Per-queue packet counts come from `ethtool -S`, the NIC's IRQs from its MSI
+vectors and /proc/interrupts, and their CPU affinity from /proc/irq. A 10GbE
+link whose queues all interrupt the same core tops out at what that core can do
+"""
+
+import re
+import logging
+import subprocess
+from pathlib import Path
+from typing import Dict, List, Optional
+
+# Configure logging
+logger = logging.getLogger(__name__)
+
+PROC_INTERRUPTS = Path("/proc/interrupts")
+PROC_IRQ = Path("/proc/irq")
+SYS_CLASS_NET = Path("/sys/class/net")
+CPU_ONLINE = Path("/sys/devices/system/cpu/online")
+# rx_queue_0_packets (ixgbe, virtio), rx-0.packets (i40e, ice), rx0_packets (mlx5)
+QUEUE_STAT = re.compile(r"^(rx|tx)(?:_queue_|-|)(\d+)[._]packets$")
+# A queue carrying more than this share of the traffic while others idle suggests poor RSS hashing
+SKEW_SHARE = 0.75
+
+def parse_queue_stats(text: str) -> List[Dict]:
+    """[{"queue", "rx_packets", "tx_packets"}] from `ethtool -S IFACE`, by queue number"""
+    queues: Dict[int, Dict] = {}
+    for line in text.splitlines():
+        name, _, value = line.strip().partition(":")
+        match = QUEUE_STAT.match(name.strip())
+        if match and value.strip().isdigit():
+            queue = queues.setdefault(int(match.group(2)), {"queue": int(match.group(2)), "rx_packets": 0,
+                                                             "tx_packets": 0})
+            queue[f"{match.group(1)}_packets"] = int(value)
+    return [queues[number] for number in sorted(queues)]
+
+def parse_cpu_list(text: str) -> List[int]:
+    """"0-3,8" -> [0, 1, 2, 3, 8]"""
+    cpus = []
+    for part in text.strip().split(","):
+        if part:
+            first, _, last = part.partition("-")
+            cpus.extend(range(int(first), int(last or first) + 1))
+    return cpus
+
+def format_cpu_list(cpus: List[int]) -> str:
+    """[0, 1, 2, 3, 8] -> "0-3,8", as smp_affinity_list writes it"""
+    ranges: List[List[int]] = []
+    for cpu in sorted(set(cpus)):
+        if ranges and cpu == ranges[-1][1] + 1:
+            ranges[-1][1] = cpu
+        else:
+            ranges.append([cpu, cpu])
+    return ",".join(f"{first}-{last}" if last > first else str(first) for first, last in ranges)
+
+def parse_interrupts(text: str, irqs: Optional[List[int]] = None, interface: Optional[str] = None) -> List[Dict]:
+    """[{"irq", "name", "interrupts"}] for the given IRQ numbers, or those whose name mentions the interface"""
+    lines = text.splitlines()
+    cpus = len(lines[0].split()) if lines else 0
+    found = []
+    for line in lines[1:]:
+        number, colon, rest = line.partition(":")
+        if not colon or not number.strip().isdigit():
+            continue
+        fields = rest.split()
+        counts = [int(field) for field in fields[:cpus] if field.isdigit()]
+        name = fields[-1] if len(fields) > cpus else ""
+        irq = int(number)
+        if (irqs is not None and irq in irqs) or (irqs is None and interface and interface in name):
+            found.append({"irq": irq, "name": name, "interrupts": sum(counts)})
+    return found
+
+def read_queues(interface: str, net_path: Path = SYS_CLASS_NET, proc_irq: Path = PROC_IRQ,
+                interrupts: Path = PROC_INTERRUPTS) -> Dict:
+    """{"queues", "irqs", "cpus"}: per-queue packets, the NIC's IRQs with their CPUs, and the online CPUs"""
+    try:
+        result = subprocess.run(['ethtool', '-S', interface], capture_output=True, text=True, timeout=5)
+        queues = parse_queue_stats(result.stdout) if result.returncode == 0 else []
+    except Exception as e:
+        logger.debug(f"Cannot read queue statistics of {interface}: {e}")
+        queues = []
+
+    msi = net_path / interface / "device" / "msi_irqs"
+    numbers = sorted(int(entry.name) for entry in msi.iterdir() if entry.name.isdigit()) if msi.is_dir() else None
+    try:
+        irqs = parse_interrupts(interrupts.read_text(), numbers, interface)
+    except OSError:
+        irqs = []
+    for irq in irqs:
+        try:
+            irq["cpus"] = parse_cpu_list((proc_irq / str(irq["irq"]) / "smp_affinity_list").read_text())
+        except (OSError, ValueError):
+            irq["cpus"] = []
+    try:
+        cpus = parse_cpu_list(CPU_ONLINE.read_text())
+    except (OSError, ValueError):
+        cpus = [0]
+    return {"queues": queues, "irqs": irqs, "cpus": cpus}
+
+def summarize(interface: str, raw: Dict) -> Dict:
+    """read_queues() with each queue's share of the packets, the CPUs the IRQs land on, and what looks wrong"""
+    total = sum(queue["rx_packets"] + queue["tx_packets"] for queue in raw["queues"])
+    queues = [dict(queue, share=round((queue["rx_packets"] + queue["tx_packets"]) / total, 3) if total else None)
+              for queue in raw["queues"]]
+    irq_cpus = sorted({cpu for irq in raw["irqs"] for cpu in irq["cpus"]})
+
+    warnings = []
+    if len(raw["irqs"]) > 1 and len(raw["cpus"]) > 1 and len(irq_cpus) == 1:
+        warnings.append(f"All {len(raw['irqs'])} IRQs of {interface} go to CPU {irq_cpus[0]}; spread them "
+                        f"so one core doesn't cap the link")
+    busiest = max(queues, key=lambda queue: queue["share"] or 0, default=None)
+    if len(queues) > 1 and busiest and (busiest["share"] or 0) > SKEW_SHARE:
+        warnings.append(f"Queue {busiest['queue']} carries {busiest['share']:.0%} of the packets; few flows, "
+                        f"or RSS hashing that doesn't spread them")
+    return {"interface": interface, "queues": queues, "irqs": raw["irqs"], "cpus": raw["cpus"],
+            "irq_cpus": irq_cpus, "warnings": warnings}
+
+def spread_plan(irqs: List[Dict], cpus: List[int]) -> Dict[int, List[int]]:
+    """One CPU per IRQ, round robin over the online CPUs"""
+    return {irq["irq"]: [cpus[index % len(cpus)]]
+            for index, irq in enumerate(sorted(irqs, key=lambda irq: irq["irq"]))}
+
+def write_affinity(irq: int, cpus: List[int], proc_irq: Path = PROC_IRQ) -> bool:
+    """Pin an IRQ to CPUs (the daemon runs as root); False if the kernel refuses, e.g. for a managed IRQ"""
+    try:
+        (proc_irq / str(irq) / "smp_affinity_list").write_text(format_cpu_list(cpus))
+        return True
+    except OSError as e:
+        logger.warning(f"Cannot set the affinity of IRQ {irq}: {e}")
+        return False
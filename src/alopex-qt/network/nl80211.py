@@ -0,0 +1,65 @@
+"""
+nl80211 - The wireless regulatory domain over the nl80211 generic netlink family
+The kernel keeps one global domain (the country whose channel and power rules
+apply) and the DFS region its radar rules follow. The same calls as iw reg get
+and iw reg set; changing the domain needs CAP_NET_ADMIN
+"""
+
+import logging
+from typing import Dict, Optional
+
+from .netlink import ENOENT, GenericNetlink, attr
+
+# Configure logging
+logger = logging.getLogger(__name__)
+
+FAMILY_NAME = "nl80211"
+FAMILY_VERSION = 0
+
+# Commands and attributes from linux/nl80211.h
+NL80211_CMD_REQ_SET_REG = 27
+NL80211_CMD_GET_REG = 31
+NL80211_ATTR_REG_ALPHA2 = 33
+NL80211_ATTR_DFS_REGION = 146
+
+# enum nl80211_dfs_regions, named the way iw prints them
+DFS_REGIONS = {0: "DFS-UNSET", 1: "DFS-FCC", 2: "DFS-ETSI", 3: "DFS-JP"}
+
+def alpha2_attr(country: str) -> bytes:
+    """NL80211_ATTR_REG_ALPHA2: the two-letter code, NUL-terminated"""
+    return attr(NL80211_ATTR_REG_ALPHA2, country.encode("ascii") + b"\0")
+
+def parse_regulatory_domain(attrs: Dict[int, bytes]) -> Optional[Dict]:
+    """{"country", "dfs_region"} from an NL80211_CMD_GET_REG reply; 00 is the restrictive world domain"""
+    if NL80211_ATTR_REG_ALPHA2 not in attrs:
+        return None
+    country = attrs[NL80211_ATTR_REG_ALPHA2].split(b"\0", 1)[0].decode("ascii", "replace")
+    region = attrs[NL80211_ATTR_DFS_REGION][0] if attrs.get(NL80211_ATTR_DFS_REGION) else None
+    return {"country": country, "dfs_region": DFS_REGIONS.get(region) if region is not None else None}
+
+class Regulatory(GenericNetlink):
+    """One generic netlink socket to cfg80211"""
+
+    def __init__(self):
+        try:
+            super().__init__(FAMILY_NAME, FAMILY_VERSION)
+        except OSError as e:
+            if e.errno == ENOENT:
+                raise OSError(ENOENT, "The kernel has no nl80211 netlink family (cfg80211 not loaded)")
+            raise
+
+    def domain(self) -> Optional[Dict]:
+        replies = self.request(NL80211_CMD_GET_REG, flags=0)
+        return parse_regulatory_domain(replies[0]) if replies else None
+
+    def set_domain(self, country: str):
+        """Ask for a country's rules; cfg80211 applies them once the regulatory database answers"""
+        self.request(NL80211_CMD_REQ_SET_REG, alpha2_attr(country))
+
+def read_domain() -> Optional[Dict]:
+    with Regulatory() as regulatory:
+        return regulatory.domain()
+
+def request_domain(country: str):
+    with Regulatory() as regulatory:
+        regulatory.set_domain(country)
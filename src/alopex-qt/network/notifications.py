@@ -0,0 +1,502 @@
+"""
+Notifications - Selected daemon events sent to webhooks, ntfy and mail
+Each sink names what it wants: alerts alopexd derives from events (a WAN or
+VPN connection going down on its own, a data quota running out) or event name
+patterns. A webhook's JSON body is a template whose "{field}" placeholders are
+filled from the notification, so one event can be shaped for Slack or Matrix;
+ntfy topics and SMTP relays are built in. Every sink has a rate limit and may
+have quiet hours
+"""
+
+import re
+import ssl
+import json
+import time
+import socket
+import string
+import asyncio
+import fnmatch
+import logging
+import smtplib
+import email.utils
+import urllib.error
+import urllib.request
+from collections import deque
+from datetime import datetime
+from email.message import EmailMessage
+from typing import Any, Callable, Deque, Dict, Iterable, List, Optional, Tuple
+from urllib.parse import urlparse
+
+from .diagnostics import redact
+from .events import EventBus
+from .secret import Secret, reveal
+
+# Configure logging
+logger = logging.getLogger(__name__)
+
+HTTP_TIMEOUT = 5.0
+RETRIES = 2
+RETRY_DELAY = 5.0
+METHODS = ("POST", "PUT")
+SEVERITIES = ("critical", "warning", "info")
+# A flapping uplink shouldn't page anyone forty times an hour
+DEFAULT_RATE_LIMIT = "20/hour"
+RATE_LIMIT = re.compile(r"^(\d+)\s*/\s*(minute|hour|day)$")
+RATE_PERIODS = {"minute": 60.0, "hour": 3600.0, "day": 86400.0}
+RATE_NAMES = {seconds: name for name, seconds in RATE_PERIODS.items()}
+NTFY_SERVER = "https://ntfy.sh"
+NTFY_PRIORITIES = {"critical": 5, "warning": 4, "info": 3}
+NTFY_TAGS = {"critical": "rotating_light", "warning": "warning", "info": "information_source"}
+SMTP_PORT = 587
+SMTP_TIMEOUT = 15.0
+SMTP_TLS = ("starttls", "ssl", "none")
+ALERTS = ("wan_down", "vpn_down", "quota_exceeded")
+# States a connection leaves on its own; a requested disconnect passes through "deactivating" instead
+DOWN_FROM = ("configured", "acquiring")
+# Too chatty to notify for, even when a pattern would match them
+QUIET_EVENTS = {"JobProgress", "BluetoothFileProgress"}
+DEFAULT_BODY = {
+    "title": "{title}",
+    "message": "{message}",
+    "severity": "{severity}",
+    "alert": "{alert}",
+    "host": "{host}",
+    "time": "{time}"
+}
+
+class _Fields(dict):
+    """Placeholders the notification doesn't have come out empty rather than failing the delivery"""
+
+    def __missing__(self, key):
+        return ""
+
+def render(template: Any, fields: Dict) -> Any:
+    """The template with every string formatted from fields. A string that is one placeholder and nothing else
+    ("{used}") takes the field's value as is, so numbers, booleans and objects stay JSON ones"""
+    if isinstance(template, dict):
+        return {render(key, fields): render(value, fields) for key, value in template.items()}
+    if isinstance(template, list):
+        return [render(value, fields) for value in template]
+    if not isinstance(template, str):
+        return template
+    parsed = list(string.Formatter().parse(template))
+    if len(parsed) == 1 and parsed[0][0] == "" and (parsed[0][1] or "").isidentifier() \
+            and not parsed[0][2] and not parsed[0][3]:
+        return fields.get(parsed[0][1], "")
+    try:
+        return string.Formatter().vformat(template, (), _Fields({k: "" if v is None else v
+                                                                 for k, v in fields.items()}))
+    except (LookupError, AttributeError, TypeError, ValueError):
+        # "{fields[missing]}" and the like: better the template as written than no notification
+        return template
+
+def check_template(template: Any):
+    """Raises ValueError when a string in the template is not a valid format string"""
+    if isinstance(template, dict):
+        for key, value in template.items():
+            check_template(key)
+            check_template(value)
+    elif isinstance(template, list):
+        for value in template:
+            check_template(value)
+    elif isinstance(template, str):
+        try:
+            list(string.Formatter().parse(template))
+        except ValueError as e:
+            raise ValueError(f"bad body template {template!r}: {e}")
+
+def _size(count) -> str:
+    value = float(count or 0)
+    for unit in ("B", "kB", "MB", "GB"):
+        if value < 1000:
+            return f"{value:.1f} {unit}" if unit != "B" else f"{int(value)} B"
+        value /= 1000
+    return f"{value:.1f} TB"
+
+def derive_alerts(event: Dict, connection_type: Callable[[Optional[str]], Optional[str]]) -> List[str]:
+    """The alerts an event amounts to; connection_type maps a profile name to its connection type"""
+    if event["event"] == "QuotaExceeded":
+        return ["quota_exceeded"]
+    if event["event"] != "InterfaceStateChanged":
+        return []
+    if event.get("state") not in ("failed", "disconnected") or event.get("previous") not in DOWN_FROM:
+        return []
+    if "cancelled" in (event.get("reason") or ""):
+        return []
+    kind = connection_type(event.get("profile"))
+    if kind == "vpn":
+        return ["vpn_down"]
+    # A shared hotspot isn't an uplink
+    return [] if kind == "hotspot" else ["wan_down"]
+
+def describe(event: Dict, alert: Optional[str]) -> Dict:
+    """{"title", "message", "severity"} in words, for templates that don't build their own"""
+    interface = event.get("interface") or ""
+    subject = event.get("profile") or interface
+    reason = event.get("reason") or ""
+    if alert in ("wan_down", "vpn_down"):
+        what = "VPN" if alert == "vpn_down" else "Connection"
+        verb = "failed to connect" if event.get("previous") == "acquiring" else "went down"
+        return {"title": f"{what} {subject} {verb}", "severity": "critical",
+                "message": f"{interface}: {reason}" if reason else interface}
+    if alert == "quota_exceeded":
+        used, limit = _size(event.get("used")), _size(event.get("limit"))
+        return {"title": f"{interface} is over its data quota", "severity": "warning",
+                "message": f"{used} used of {limit} this {event.get('period')}"}
+    fields = " ".join(f"{key}={value}" for key, value in event.items()
+                      if key not in ("event", "timestamp") and value not in (None, "", [], {}))
+    return {"title": event["event"], "message": fields, "severity": "info"}
+
+def notification(event: Dict, alert: Optional[str], host: Optional[str] = None) -> Dict:
+    """Everything a template can use: the event's own fields (secrets redacted), then event, alert, title,
+    message, severity, host, time (ISO 8601) and timestamp, plus the whole event as fields"""
+    event = redact(event)
+    fields = {key: value for key, value in event.items() if key not in ("event", "timestamp")}
+    return dict(fields, event=event["event"], alert=alert or event["event"], **describe(event, alert),
+                host=host or socket.gethostname(), timestamp=event["timestamp"],
+                time=datetime.fromtimestamp(event["timestamp"]).astimezone().isoformat(timespec="seconds"),
+                fields=fields)
+
+def parse_rate_limit(text: Optional[str]) -> Optional[Tuple[int, float]]:
+    """(count, seconds) from "10/hour" and the like; None for no limit. Raises ValueError"""
+    if text in (None, "", "none"):
+        return None
+    match = RATE_LIMIT.match(str(text).strip().lower())
+    if not match or int(match.group(1)) < 1:
+        raise ValueError(f"rate limit must look like 10/hour (per minute, hour or day), not {text!r}")
+    return int(match.group(1)), RATE_PERIODS[match.group(2)]
+
+def parse_clock(text: str) -> int:
+    """Minutes past midnight in an HH:MM time; raises ValueError"""
+    hours, _, minutes = str(text).partition(":")
+    if not (hours.isdigit() and minutes.isdigit() and int(hours) < 24 and int(minutes) < 60):
+        raise ValueError(f"expected a time such as 22:30, not {text!r}")
+    return int(hours) * 60 + int(minutes)
+
+def parse_quiet_hours(spec: Optional[Dict]) -> Optional[Dict]:
+    """{"start", "end" (minutes past midnight), "allow" (severities still sent)} from the config form"""
+    if not spec:
+        return None
+    allow = spec.get("allow", ["critical"])
+    unknown = set(allow) - set(SEVERITIES)
+    if unknown:
+        raise ValueError(f"quiet hours allow severities {', '.join(SEVERITIES)}, not {', '.join(sorted(unknown))}")
+    return {"start": parse_clock(spec.get("start")), "end": parse_clock(spec.get("end")), "allow": list(allow),
+            "text": f"{spec.get('start')}-{spec.get('end')}"}
+
+def in_quiet_hours(quiet: Dict, now: float) -> bool:
+    moment = datetime.fromtimestamp(now)
+    minute = moment.hour * 60 + moment.minute
+    if quiet["start"] <= quiet["end"]:
+        return quiet["start"] <= minute < quiet["end"]
+    # Spans midnight, e.g. 22:00-07:00
+    return minute >= quiet["start"] or minute < quiet["end"]
+
+class Sink:
+    """Somewhere notifications go; subclasses deliver, this decides what to send and when.
+    A sink holds back what falls in its quiet hours (below the severities it allows) and anything past its
+    rate limit, counting both as suppressed"""
+
+    kind = "sink"
+
+    def __init__(self, name: str, events: Iterable[str], rate_limit: Optional[str] = DEFAULT_RATE_LIMIT,
+                 quiet_hours: Optional[Dict] = None, timeout: float = HTTP_TIMEOUT):
+        self.name = name
+        self.events = list(events or [])
+        if not self.events:
+            raise ValueError(f"{self.kind} {name}: name the alerts or events it is for")
+        try:
+            self.rate_limit = parse_rate_limit(rate_limit)
+            self.quiet_hours = parse_quiet_hours(quiet_hours)
+        except ValueError as e:
+            raise ValueError(f"{self.kind} {name}: {e}")
+        self.timeout = timeout
+        self.recent: Deque[float] = deque()
+        self.sent = 0
+        self.failures = 0
+        self.suppressed = 0
+        self.last_sent: Optional[float] = None
+        self.last_error: Optional[str] = None
+
+    def wants(self, event: str, alerts: List[str]) -> bool:
+        return any(pattern in alerts or fnmatch.fnmatchcase(event, pattern) for pattern in self.events)
+
+    def held_back(self, fields: Dict, now: float) -> Optional[str]:
+        """Why a notification shouldn't go out now, if it shouldn't"""
+        quiet = self.quiet_hours
+        if quiet and fields["severity"] not in quiet["allow"] and in_quiet_hours(quiet, now):
+            return "quiet hours"
+        if self.rate_limit:
+            count, seconds = self.rate_limit
+            while self.recent and self.recent[0] <= now - seconds:
+                self.recent.popleft()
+            if len(self.recent) >= count:
+                return "rate limit"
+        return None
+
+    def deliver(self, fields: Dict):
+        """Send one notification; raises OSError when the destination refuses or is unreachable"""
+        raise NotImplementedError
+
+    async def send(self, fields: Dict, retries: int = RETRIES, force: bool = False) -> bool:
+        """Deliver, retrying a couple of times; failures are counted and logged. force skips quiet hours and
+        the rate limit (test notifications)"""
+        now = time.time()
+        reason = None if force else self.held_back(fields, now)
+        if reason:
+            self.suppressed += 1
+            logger.info(f"{self.kind} {self.name}: held back {fields['alert']} ({reason})")
+            return False
+        if not force:
+            self.recent.append(now)
+        for attempt in range(retries + 1):
+            try:
+                await asyncio.get_running_loop().run_in_executor(None, self.deliver, fields)
+            except OSError as e:
+                self.last_error = str(e)
+                if attempt < retries:
+                    await asyncio.sleep(RETRY_DELAY)
+                    continue
+                self.failures += 1
+                logger.warning(f"{self.kind} {self.name} failed for {fields['alert']}: {e}")
+                return False
+            self.sent += 1
+            self.last_sent = time.time()
+            self.last_error = None
+            return True
+        return False
+
+    def destination(self) -> str:
+        return ""
+
+    def stats(self) -> Dict:
+        return {
+            "name": self.name,
+            "kind": self.kind,
+            "url": self.destination(),
+            "events": self.events,
+            "rate_limit": f"{self.rate_limit[0]}/{RATE_NAMES[self.rate_limit[1]]}" if self.rate_limit else None,
+            "quiet_hours": self.quiet_hours["text"] if self.quiet_hours else None,
+            "sent": self.sent,
+            "failures": self.failures,
+            "suppressed": self.suppressed,
+            "last_sent": self.last_sent,
+            "last_error": self.last_error
+        }
+
+def _public_url(url: str) -> str:
+    """The URL without credentials or query, since tokens often ride in either"""
+    parsed = urlparse(url)
+    return parsed._replace(netloc=parsed.hostname + (f":{parsed.port}" if parsed.port else ""), query="").geturl()
+
+def _check_url(kind: str, name: str, url: str):
+    parsed = urlparse(url or "")
+    if parsed.scheme not in ("http", "https") or not parsed.hostname:
+        raise ValueError(f"{kind} {name}: expected an http:// or https:// URL, got {url!r}")
+
+def http_request(url: str, payload: bytes, method: str, headers: Dict[str, str], timeout: float):
+    """One request whose answer doesn't matter beyond success; raises OSError"""
+    request = urllib.request.Request(url, data=payload, method=method, headers=headers)
+    try:
+        with urllib.request.urlopen(request, timeout=timeout) as response:
+            response.read()
+    except urllib.error.HTTPError as e:
+        detail = e.read().decode("utf-8", "replace").strip()[:200]
+        raise OSError(f"HTTP {e.code}{': ' + detail if detail else ''}")
+    except urllib.error.URLError as e:
+        raise OSError(str(e.reason))
+
+class Webhook(Sink):
+    """An HTTP endpoint that gets a JSON body for the alerts and events it names"""
+
+    kind = "webhook"
+
+    def __init__(self, name: str, url: str, events: Iterable[str], body: Any = None, method: str = "POST",
+                 headers: Optional[Dict[str, str]] = None, timeout: float = HTTP_TIMEOUT, **limits):
+        super().__init__(name, events, timeout=timeout, **limits)
+        _check_url(self.kind, name, url)
+        if str(method).upper() not in METHODS:
+            raise ValueError(f"webhook {name}: method must be one of {', '.join(METHODS)}")
+        self.url = url
+        self.body = DEFAULT_BODY if body is None else body
+        check_template(self.body)
+        self.method = str(method).upper()
+        # Often an API key or a bearer token, so every value is kept as a Secret
+        self.headers = {key: Secret.wrap(str(value)) for key, value in (headers or {}).items()}
+
+    def deliver(self, fields: Dict):
+        payload = json.dumps(render(self.body, fields), default=str).encode()
+        headers = {key: reveal(value) for key, value in self.headers.items()}
+        http_request(self.url, payload, self.method, dict({"Content-Type": "application/json"}, **headers),
+                     self.timeout)
+
+    def destination(self) -> str:
+        return _public_url(self.url)
+
+class NtfySink(Sink):
+    """A topic on ntfy.sh or a self-hosted ntfy server, published as JSON so titles needn't be latin-1"""
+
+    kind = "ntfy"
+
+    def __init__(self, name: str, topic: str, events: Iterable[str], server: str = NTFY_SERVER,
+                 token: Optional[str] = None, timeout: float = HTTP_TIMEOUT, **limits):
+        super().__init__(name, events, timeout=timeout, **limits)
+        _check_url(self.kind, name, server)
+        if not topic or "/" in topic:
+            raise ValueError(f"ntfy {name}: needs a topic name")
+        self.server = server.rstrip("/")
+        self.topic = topic
+        self.token = Secret.wrap(token)
+
+    def payload(self, fields: Dict) -> Dict:
+        return {"topic": self.topic, "title": fields["title"], "message": fields["message"] or fields["title"],
+                "priority": NTFY_PRIORITIES.get(fields["severity"], 3),
+                "tags": [NTFY_TAGS.get(fields["severity"], "information_source"), fields["alert"]]}
+
+    def deliver(self, fields: Dict):
+        headers = {"Content-Type": "application/json"}
+        if self.token:
+            headers["Authorization"] = f"Bearer {reveal(self.token)}"
+        http_request(self.server, json.dumps(self.payload(fields), default=str).encode(), "POST", headers,
+                     self.timeout)
+
+    def destination(self) -> str:
+        return f"{_public_url(self.server)}/{self.topic}"
+
+class SmtpSink(Sink):
+    """Mail through an SMTP relay: STARTTLS on 587, implicit TLS on 465, plain on 25 (a local MTA)"""
+
+    kind = "smtp"
+
+    def __init__(self, name: str, host: str, sender: str, to: Iterable[str], events: Iterable[str],
+                 port: int = SMTP_PORT, username: Optional[str] = None, password: Optional[str] = None,
+                 tls: Optional[str] = None, timeout: float = SMTP_TIMEOUT, **limits):
+        super().__init__(name, events, timeout=timeout, **limits)
+        self.to = [to] if isinstance(to, str) else list(to or [])
+        if not host or not sender or not self.to:
+            raise ValueError(f"smtp {name}: needs host, from and to")
+        self.host = host
+        self.port = int(port)
+        self.sender = sender
+        self.username = username
+        self.password = Secret.wrap(password)
+        # Implied by the port unless set: "starttls", "ssl" or "none"
+        self.tls = tls or {465: "ssl", 25: "none"}.get(self.port, "starttls")
+        if self.tls not in SMTP_TLS:
+            raise ValueError(f"smtp {name}: tls must be one of {', '.join(SMTP_TLS)}")
+
+    def message(self, fields: Dict) -> EmailMessage:
+        message = EmailMessage()
+        message["Subject"] = f"[alopex] {fields['host']}: {fields['title']}"
+        message["From"] = self.sender
+        message["To"] = ", ".join(self.to)
+        message["Date"] = email.utils.formatdate(fields["timestamp"], localtime=True)
+        details = "\n".join(f"{key}: {value}" for key, value in fields["fields"].items()
+                            if value not in (None, "", [], {}))
+        message.set_content(f"{fields['message']}\n\n{details}\n\n{fields['severity']} {fields['alert']} "
+                            f"on {fields['host']} at {fields['time']}\n")
+        return message
+
+    def deliver(self, fields: Dict):
+        try:
+            if self.tls == "ssl":
+                client = smtplib.SMTP_SSL(self.host, self.port, timeout=self.timeout,
+                                          context=ssl.create_default_context())
+            else:
+                client = smtplib.SMTP(self.host, self.port, timeout=self.timeout)
+            with client:
+                if self.tls == "starttls":
+                    client.starttls(context=ssl.create_default_context())
+                if self.username:
+                    client.login(self.username, reveal(self.password) or "")
+                client.send_message(self.message(fields))
+        except smtplib.SMTPException as e:
+            raise OSError(f"SMTP: {e}")
+
+    def destination(self) -> str:
+        return f"smtp://{self.host}:{self.port} to {', '.join(self.to)}"
+
+def load_sinks(config: Dict) -> List[Sink]:
+    """Every sink in the notifications config section; raises ValueError for the first bad one. rate_limit
+    and quiet_hours at the top apply to sinks that don't set their own"""
+    defaults = {"rate_limit": config.get("rate_limit", DEFAULT_RATE_LIMIT), "quiet_hours": config.get("quiet_hours")}
+    sinks: List[Sink] = []
+
+    def options(spec: Dict, *keys) -> Dict:
+        found = {key: spec[key] for key in keys if key in spec}
+        return dict(found, **{key: spec.get(key, value) for key, value in defaults.items()})
+
+    for index, spec in enumerate(config.get("webhooks") or []):
+        sinks.append(Webhook(spec.get("name") or f"webhook{index + 1}", spec.get("url"), spec.get("events"),
+                             **options(spec, "body", "method", "headers", "timeout")))
+    for index, spec in enumerate(config.get("ntfy") or []):
+        sinks.append(NtfySink(spec.get("name") or f"ntfy{index + 1}", spec.get("topic"), spec.get("events"),
+                              **options(spec, "server", "token", "timeout")))
+    for index, spec in enumerate(config.get("smtp") or []):
+        sinks.append(SmtpSink(spec.get("name") or f"smtp{index + 1}", spec.get("host"), spec.get("from"),
+                              spec.get("to"), spec.get("events"),
+                              **options(spec, "port", "username", "password", "tls", "timeout")))
+    names = [sink.name for sink in sinks]
+    duplicate = next((name for name in names if names.count(name) > 1), None)
+    if duplicate:
+        raise ValueError(f"two notification sinks are called {duplicate}")
+    return sinks
+
+def sample_notification(host: Optional[str] = None) -> Dict:
+    """What TestNotification sends: an info notification that names itself"""
+    now = time.time()
+    return notification({"event": "TestNotification", "timestamp": now}, None, host) | {
+        "title": "Test notification from alopexd",
+        "message": "Notifications from this host reach you here."}
+
+class Notifier:
+    """Turns published events into notifications for the sinks that want them"""
+
+    def __init__(self, events: EventBus, sinks: List[Sink],
+                 connection_type: Callable[[Optional[str]], Optional[str]], host: Optional[str] = None):
+        self.events = events
+        self.sinks = sinks
+        self.connection_type = connection_type
+        self.host = host
+        self.deliveries: set = set()
+
+    def dispatch(self, event: Dict) -> List[asyncio.Task]:
+        """Start delivering an event to every sink that wants it; returns the deliveries started"""
+        if event["event"] in QUIET_EVENTS:
+            return []
+        alerts = derive_alerts(event, self.connection_type)
+        tasks = []
+        for sink in self.sinks:
+            if not sink.wants(event["event"], alerts):
+                continue
+            # The most specific thing the sink asked for names the notification
+            alert = next((a for a in alerts if a in sink.events), None)
+            task = asyncio.create_task(sink.send(notification(event, alert, self.host)))
+            self.deliveries.add(task)
+            task.add_done_callback(self.deliveries.discard)
+            tasks.append(task)
+        return tasks
+
+    async def test(self, name: Optional[str] = None) -> List[Dict]:
+        """Send a test notification through every sink, or the one called name, now and once: quiet hours and
+        rate limits don't apply. Raises KeyError for an unknown name"""
+        sinks = [sink for sink in self.sinks if name is None or sink.name == name]
+        if name is not None and not sinks:
+            raise KeyError(name)
+        fields = sample_notification(self.host)
+        results = await asyncio.gather(*(sink.send(fields, retries=0, force=True) for sink in sinks))
+        return [{"name": sink.name, "kind": sink.kind, "sent": sent, "error": None if sent else sink.last_error}
+                for sink, sent in zip(sinks, results)]
+
+    def stats(self) -> Dict:
+        return {"enabled": True, "sinks": [sink.stats() for sink in self.sinks]}
+
+    async def run(self):
+        subscription = self.events.subscribe()
+        try:
+            while True:
+                self.dispatch(await subscription.get())
+        finally:
+            self.events.unsubscribe(subscription)
+            for task in list(self.deliveries):
+                task.cancel()
@@ -0,0 +1,116 @@
+"""
+OBEX - Send files to paired phones through BlueZ's obexd (Object Push)
+obexd lives on each user's session bus, so gdbus runs as the user who asked:
+the file is read with their permissions, and transfer progress comes from
+watching obexd's Transfer1 property changes with `gdbus monitor`
+"""
+
+import os
+import re
+import asyncio
+import logging
+from typing import Callable, Dict, List, Optional
+
+from .system_integration import communicate_or_terminate
+
+# Configure logging
+logger = logging.getLogger(__name__)
+
+OBEX_SERVICE = "org.bluez.obex"
+OBEX_PATH = "/org/bluez/obex"
+# How long gdbus monitor gets to subscribe before the transfer starts
+MONITOR_START_SECONDS = 5
+OBJECT_PATH = re.compile(r"objectpath '([^']+)'")
+STATUS = re.compile(r"'Status': <'(\w+)'>")
+SIZE = re.compile(r"'Size': <(?:uint64 )?(\d+)>")
+TRANSFERRED = re.compile(r"'Transferred': <(?:uint64 )?(\d+)>")
+GDBUS_ERROR = re.compile(r"GDBus\.Error:[\w.]+:\s*(.*)")
+
+class TransferError(Exception):
+    """obexd refused the transfer, or the phone declined or dropped it"""
+
+def parse_object_path(text: str) -> Optional[str]:
+    """"(objectpath '/org/bluez/obex/client/session0',)" -> the path"""
+    match = OBJECT_PATH.search(text)
+    return match.group(1) if match else None
+
+def parse_transfer(text: str) -> Dict:
+    """Status, Size and Transferred from Transfer1 properties as gdbus prints them; missing ones are None"""
+    status, size, transferred = STATUS.search(text), SIZE.search(text), TRANSFERRED.search(text)
+    return {"status": status.group(1) if status else None, "size": int(size.group(1)) if size else None,
+            "transferred": int(transferred.group(1)) if transferred else None}
+
+def gdbus_error(text: str) -> str:
+    """The message of a "GDBus.Error:org.bluez.obex.Error.Failed: ..." reply"""
+    match = GDBUS_ERROR.search(text)
+    return match.group(1).strip() if match else (text.strip().splitlines() or ["gdbus failed"])[-1]
+
+def _session(uid: Optional[int]) -> Dict:
+    """subprocess arguments that put gdbus on a user's session bus, as that user"""
+    if uid is None or uid == os.getuid():
+        bus = os.environ.get("DBUS_SESSION_BUS_ADDRESS") or f"unix:path=/run/user/{os.getuid()}/bus"
+        return {"env": dict(os.environ, DBUS_SESSION_BUS_ADDRESS=bus)}
+    return {"user": uid, "env": {"PATH": os.environ.get("PATH", "/usr/bin:/bin"),
+                                 "DBUS_SESSION_BUS_ADDRESS": f"unix:path=/run/user/{uid}/bus"}}
+
+async def _call(uid: Optional[int], path: str, method: str, *args: str) -> str:
+    """One obexd method call; raises TransferError with obexd's message, OSError without gdbus"""
+    process = await asyncio.create_subprocess_exec(
+        'gdbus', 'call', '--session', '--dest', OBEX_SERVICE, '--object-path', path, '--method', method, *args,
+        stdout=asyncio.subprocess.PIPE, stderr=asyncio.subprocess.PIPE, **_session(uid)
+    )
+    stdout, stderr = await communicate_or_terminate(process)
+    if process.returncode != 0:
+        raise TransferError(gdbus_error(stderr.decode(errors="replace")))
+    return stdout.decode(errors="replace")
+
+async def send_file(address: str, path: str, progress: Callable[[int, Optional[int]], None],
+                    uid: Optional[int] = None) -> Dict:
+    """Push a file to a paired device, calling progress(transferred, size) as it goes; {"file", "size"}"""
+    session = parse_object_path(await _call(uid, OBEX_PATH, "org.bluez.obex.Client1.CreateSession",
+                                            address, "{'Target': <'opp'>}"))
+    if not session:
+        raise TransferError(f"obexd opened no session to {address}")
+    monitor = await asyncio.create_subprocess_exec(
+        'gdbus', 'monitor', '--session', '--dest', OBEX_SERVICE,
+        stdout=asyncio.subprocess.PIPE, stderr=asyncio.subprocess.DEVNULL, **_session(uid)
+    )
+    transfer = None
+    try:
+        # gdbus announces the service's owner once its match rule is in place
+        await asyncio.wait_for(monitor.stdout.readline(), MONITOR_START_SECONDS)
+        reply = await _call(uid, session, "org.bluez.obex.ObjectPush1.SendFile", path)
+        transfer = parse_object_path(reply)
+        size = parse_transfer(reply)["size"]
+        progress(0, size)
+        while True:
+            line = (await monitor.stdout.readline()).decode(errors="replace")
+            if not line:
+                raise TransferError(f"Lost track of the transfer to {address}")
+            if not line.startswith(f"{transfer}:"):
+                continue
+            state = parse_transfer(line)
+            if state["transferred"] is not None:
+                progress(state["transferred"], size)
+            if state["status"] == "complete":
+                logger.info(f"Sent {path} to {address}")
+                transfer = None
+                return {"file": os.path.basename(path), "size": size}
+            if state["status"] == "error":
+                transfer = None
+                raise TransferError(f"{address} declined or dropped the transfer")
+    finally:
+        if monitor.returncode is None:
+            monitor.terminate()
+            await monitor.wait()
+        await _cleanup(uid, session, transfer)
+
+async def _cleanup(uid: Optional[int], session: str, transfer: Optional[str]):
+    """Cancel an unfinished transfer and close the session, whatever state they are in"""
+    calls: List[tuple] = ([(transfer, "org.bluez.obex.Transfer1.Cancel")] if transfer else []) + [
+        (OBEX_PATH, "org.bluez.obex.Client1.RemoveSession", session)]
+    for path, method, *args in calls:
+        try:
+            await _call(uid, path, method, *args)
+        except (TransferError, OSError) as e:
+            logger.debug(f"{method} on {path}: {e}")
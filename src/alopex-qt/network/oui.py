@@ -0,0 +1,123 @@
+"""
+OUI Vendor Lookup - Offline manufacturer names for MAC addresses
+Reads whichever IEEE OUI list the system ships (hwdata, ieee-data, Wireshark
+or nmap), else the compressed table bundled with ALOPEX; without either,
+lookups simply return None. alopex-daemon/build-oui-table.py generates the table
+"""
+
+import os
+import re
+import csv
+import gzip
+import logging
+from pathlib import Path
+from typing import Dict, List, Optional
+
+# Configure logging
+logger = logging.getLogger(__name__)
+
+# First one that exists wins; ALOPEX_OUI_FILE takes precedence
+OUI_FILES = [
+    Path("/usr/share/hwdata/oui.txt"),
+    Path("/usr/share/ieee-data/oui.txt"),
+    Path("/usr/share/misc/oui.txt"),
+    Path("/usr/share/wireshark/manuf"),
+    Path("/usr/share/nmap/nmap-mac-prefixes"),
+]
+# Generated from IEEE's MA-L, MA-M and MA-S registries by `make oui-table`
+BUNDLED_TABLE = Path(__file__).parent / "data" / "oui.tsv.gz"
+
+# "00-00-0C   (hex)\t\tCisco Systems, Inc", "00:00:0C\tCisco\tCisco Systems, Inc" or "00000C Cisco Systems"
+IEEE_LINE = re.compile(r"^([0-9A-Fa-f]{2})-([0-9A-Fa-f]{2})-([0-9A-Fa-f]{2})\s+\(hex\)\s+(.+)$")
+MANUF_LINE = re.compile(r"^([0-9A-Fa-f]{2}):([0-9A-Fa-f]{2}):([0-9A-Fa-f]{2})\s+\S+\s*(.*)$")
+NMAP_LINE = re.compile(r"^([0-9A-Fa-f]{6})\s+([^\s(].*)$")
+# The bundled table: "001B21\tIntel Corporate", with 7 and 9 digit prefixes for MA-M and MA-S blocks
+TABLE_LINE = re.compile(r"^([0-9A-F]{6}|[0-9A-F]{7}|[0-9A-F]{9})\t(.+)$")
+
+_vendors: Optional[Dict[str, str]] = None
+
+def oui_file() -> Optional[Path]:
+    """The OUI list in use, if any"""
+    override = os.environ.get("ALOPEX_OUI_FILE")
+    candidates = [Path(override)] if override else OUI_FILES + [BUNDLED_TABLE]
+    return next((path for path in candidates if path.is_file()), None)
+
+def _parse_line(line: str) -> Optional[tuple]:
+    match = TABLE_LINE.match(line)
+    if match:
+        return match.group(1), match.group(2)
+    match = IEEE_LINE.match(line)
+    if match:
+        return "".join(match.group(1, 2, 3)).upper(), match.group(4).strip()
+    match = MANUF_LINE.match(line)
+    if match:
+        prefix = "".join(match.group(1, 2, 3)).upper()
+        # Wireshark: short name, then the long one when there is one
+        return prefix, match.group(4).strip() or line.split()[1]
+    match = NMAP_LINE.match(line)
+    if match:
+        return match.group(1).upper(), match.group(2).strip()
+    return None
+
+def _load() -> Dict[str, str]:
+    vendors: Dict[str, str] = {}
+    path = oui_file()
+    if path is None:
+        return vendors
+    try:
+        opener = gzip.open if path.suffix == ".gz" else open
+        with opener(path, "rt", encoding="utf-8", errors="replace") as f:
+            for line in f:
+                parsed = _parse_line(line.rstrip("\n"))
+                if parsed:
+                    vendors.setdefault(*parsed)
+        logger.debug(f"Loaded {len(vendors)} OUI prefixes from {path}")
+    except OSError as e:
+        logger.warning(f"Cannot read OUI list {path}: {e}")
+    return vendors
+
+def is_locally_administered(mac: str) -> bool:
+    """Set by software (randomized WiFi, VMs, containers) rather than assigned by IEEE"""
+    try:
+        return bool(int(mac.split(":")[0], 16) & 0x02)
+    except ValueError:
+        return False
+
+def lookup_vendor(mac: Optional[str]) -> Optional[str]:
+    """Manufacturer for a MAC address, None if unknown or locally administered"""
+    global _vendors
+    if not mac or is_locally_administered(mac):
+        return None
+    if _vendors is None:
+        _vendors = _load()
+    digits = mac.replace(":", "").replace("-", "").upper()
+    # MA-S and MA-M blocks are carved out of an IEEE-owned MA-L, so the longest prefix wins
+    return next((_vendors[digits[:length]] for length in (9, 7, 6) if digits[:length] in _vendors), None)
+
+def read_source(path: Path) -> Dict[str, str]:
+    """Prefixes and vendors from an IEEE registry CSV (oui.csv, mam.csv, oui36.csv) or any list _load reads"""
+    vendors: Dict[str, str] = {}
+    with open(path, encoding="utf-8", errors="replace", newline="") as f:
+        if path.suffix == ".csv":
+            # Registry,Assignment,Organization Name,Organization Address
+            for row in csv.reader(f):
+                if len(row) >= 3 and re.fullmatch(r"[0-9A-Fa-f]{6,9}", row[1]) and row[2].strip():
+                    vendors.setdefault(row[1].upper(), " ".join(row[2].split()))
+        else:
+            for line in f:
+                parsed = _parse_line(line.rstrip("\n"))
+                if parsed:
+                    vendors.setdefault(*parsed)
+    return vendors
+
+def build_table(sources: List[Path], output: Path) -> int:
+    """Write the bundled table from registry files, earlier sources winning; returns the prefix count"""
+    vendors: Dict[str, str] = {}
+    for source in sources:
+        for prefix, vendor in read_source(source).items():
+            vendors.setdefault(prefix, vendor)
+    output.parent.mkdir(parents=True, exist_ok=True)
+    # mtime 0 keeps the output byte-identical for identical input
+    with open(output, "wb") as raw, gzip.GzipFile(fileobj=raw, mode="wb", mtime=0) as f:
+        f.write("".join(f"{prefix}\t{vendor}\n" for prefix, vendor in sorted(vendors.items())).encode())
+    return len(vendors)
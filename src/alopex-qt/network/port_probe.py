@@ -0,0 +1,82 @@
+"""
+Port Probe - Whether a TCP port answers, optionally from one interface's address
+Binding the source address makes the connection leave the way traffic from that
+interface would, which is what split tunnels and per-link firewalls care about
+"""
+
+import time
+import socket
+import asyncio
+import logging
+from typing import Dict, Optional, Tuple
+
+# Configure logging
+logger = logging.getLogger(__name__)
+
+DEFAULT_TIMEOUT = 3.0
+MAX_TIMEOUT = 30.0
+
+# open: handshake completed; closed: refused; filtered: no answer before the timeout;
+# unreachable: the network said no; unresolved: the name did not resolve
+STATES = ("open", "closed", "filtered", "unreachable", "unresolved")
+
+def parse_target(text: str) -> Tuple[str, int]:
+    """(host, port) from "host:port", "[v6 address]:port" or "host port"; raises ValueError"""
+    text = text.strip()
+    if text.startswith("["):
+        host, _, port = text[1:].partition("]")
+        port = port.lstrip(":")
+    elif " " in text:
+        host, port = text.rsplit(None, 1)
+    else:
+        host, _, port = text.rpartition(":")
+    if not host or not port.isdigit() or not 0 < int(port) < 65536:
+        raise ValueError(f"Expected host:port, got {text!r}")
+    return host, int(port)
+
+def summary(result: Dict) -> str:
+    """One line for a probe result, e.g. example.com:443 (93.184.215.14) open in 23.1 ms"""
+    target = f"{result['host']}:{result['port']}"
+    if result.get("address") and result["address"] != result["host"]:
+        target += f" ({result['address']})"
+    text = f"{target} {result['state']}"
+    if result.get("latency_ms") is not None:
+        text += f" in {result['latency_ms']:.1f} ms"
+    if result.get("interface"):
+        text += f" from {result['interface']} ({result['source']})"
+    if result.get("error") and result["state"] != "filtered":
+        text += f": {result['error']}"
+    return text
+
+async def probe(host: str, port: int, source: Optional[str] = None, timeout: float = DEFAULT_TIMEOUT,
+                family: int = socket.AF_UNSPEC) -> Dict:
+    """{"state", "address", "latency_ms", "error"} of one TCP connection attempt"""
+    loop = asyncio.get_running_loop()
+    try:
+        infos = await asyncio.wait_for(loop.getaddrinfo(
+            host, port, family=socket.AF_INET if source else family, type=socket.SOCK_STREAM
+        ), timeout)
+    except (socket.gaierror, asyncio.TimeoutError) as e:
+        return {"state": "unresolved", "address": None, "latency_ms": None, "error": str(e) or "timed out"}
+    family, _, _, _, sockaddr = infos[0]
+    address = sockaddr[0]
+
+    started = time.monotonic()
+    writer = None
+    try:
+        _, writer = await asyncio.wait_for(asyncio.open_connection(
+            address, port, family=family, local_addr=(source, 0) if source else None
+        ), timeout)
+        state, error = "open", None
+    except asyncio.TimeoutError:
+        state, error = "filtered", f"no answer in {timeout:g}s"
+    except ConnectionRefusedError as e:
+        state, error = "closed", e.strerror
+    except OSError as e:
+        state, error = "unreachable", e.strerror or str(e)
+    finally:
+        if writer:
+            writer.close()
+    latency = (time.monotonic() - started) * 1000 if state in ("open", "closed") else None
+    logger.debug(f"Probe of {address}:{port} from {source or 'any'}: {state}")
+    return {"state": state, "address": address, "latency_ms": latency, "error": error}
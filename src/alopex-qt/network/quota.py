@@ -0,0 +1,122 @@
+"""
+Quota - Data caps per interface, counted from the bandwidth history
+Each quota sums what an interface moved (both directions) since the start of
+the day, week or month and publishes QuotaExceeded once per period when it
+goes over; the periods already reported are kept across restarts
+"""
+
+import os
+import re
+import json
+import time
+import asyncio
+import logging
+from datetime import datetime, timedelta
+from pathlib import Path
+from typing import Dict, List, Optional
+
+# Configure logging
+logger = logging.getLogger(__name__)
+
+DEFAULT_INTERVAL = 60.0
+PERIODS = ("day", "week", "month")
+# "20GB", "1.5TiB", "500MB"; decimal units are what carriers bill in
+SIZE = re.compile(r"^(\d+(?:\.\d+)?)\s*([kmgt]i?b)?$")
+SIZE_UNITS = {
+    "kb": 10 ** 3, "mb": 10 ** 6, "gb": 10 ** 9, "tb": 10 ** 12,
+    "kib": 2 ** 10, "mib": 2 ** 20, "gib": 2 ** 30, "tib": 2 ** 40
+}
+
+def parse_size(text) -> int:
+    """Bytes in a size such as 20GB or 1.5TiB (a bare number is bytes); raises ValueError"""
+    if isinstance(text, int) and not isinstance(text, bool):
+        size = text
+    else:
+        match = SIZE.match(str(text).strip().lower())
+        if not match:
+            raise ValueError(f"Not a size: {text!r} (e.g. 20GB, 500MB, 1TiB)")
+        size = int(float(match.group(1)) * SIZE_UNITS.get(match.group(2), 1))
+    if size <= 0:
+        raise ValueError("a quota must be above zero")
+    return size
+
+def period_start(period: str, now: Optional[float] = None) -> float:
+    """Local midnight starting the day, the week (Monday) or the month that now falls in"""
+    moment = datetime.fromtimestamp(time.time() if now is None else now)
+    start = moment.replace(hour=0, minute=0, second=0, microsecond=0)
+    if period == "week":
+        start -= timedelta(days=start.weekday())
+    elif period == "month":
+        start = start.replace(day=1)
+    return start.timestamp()
+
+def check_quota(spec: Dict) -> Dict:
+    """{"interface", "limit" (bytes), "period"} from a config entry; raises ValueError naming what's wrong"""
+    if not isinstance(spec, dict) or not spec.get("interface"):
+        raise ValueError("each quota needs an interface")
+    period = spec.get("period", "month")
+    if period not in PERIODS:
+        raise ValueError(f"quota period must be one of {', '.join(PERIODS)}, not {period!r}")
+    return {"interface": spec["interface"], "limit": parse_size(spec.get("limit")), "period": period}
+
+class QuotaMonitor:
+    """Compares each quota with the interface's traffic this period"""
+
+    def __init__(self, history, quotas: List[Dict], state_path: Path, events=None,
+                 interval: float = DEFAULT_INTERVAL):
+        self.history = history
+        self.quotas = [check_quota(spec) for spec in quotas]
+        self.state_file = state_path / "quota.json"
+        self.events = events
+        self.interval = interval
+        # interface -> start of the period QuotaExceeded went out for
+        self.reported: Dict[str, float] = {}
+        self._load()
+
+    def _load(self):
+        if not self.state_file.exists():
+            return
+        try:
+            with open(self.state_file) as f:
+                self.reported = json.load(f).get("reported", {})
+        except Exception as e:
+            logger.error(f"Failed to load quota state: {e}")
+
+    def _save(self):
+        try:
+            self.state_file.parent.mkdir(parents=True, exist_ok=True)
+            temp_file = self.state_file.with_suffix(".tmp")
+            with open(temp_file, "w") as f:
+                json.dump({"reported": self.reported}, f)
+            os.replace(temp_file, self.state_file)
+        except OSError as e:
+            logger.error(f"Failed to save quota state: {e}")
+
+    def usage(self, quota: Dict, now: Optional[float] = None) -> Dict:
+        start = period_start(quota["period"], now)
+        used = sum(sample["rx_bytes"] + sample["tx_bytes"] for sample in self.history.query(quota["interface"], start))
+        return dict(quota, used=used, percent=round(100 * used / quota["limit"], 1), period_start=start,
+                    exceeded=used >= quota["limit"])
+
+    def check(self, now: Optional[float] = None) -> List[Dict]:
+        """Usage of every quota; publishes QuotaExceeded for the ones that just went over"""
+        usages = [self.usage(quota, now) for quota in self.quotas]
+        for usage in usages:
+            interface = usage["interface"]
+            if not usage["exceeded"] or self.reported.get(interface) == usage["period_start"]:
+                continue
+            self.reported[interface] = usage["period_start"]
+            self._save()
+            logger.warning(f"{interface} used {usage['used']} of its {usage['limit']} byte {usage['period']} quota")
+            if self.events:
+                self.events.publish("QuotaExceeded", interface=interface, used=usage["used"], limit=usage["limit"],
+                                    period=usage["period"], period_start=usage["period_start"])
+        return usages
+
+    async def run(self):
+        while True:
+            try:
+                self.check()
+            except Exception as e:
+                logger.error(f"Quota check failed: {e}")
+            await asyncio.sleep(self.interval)
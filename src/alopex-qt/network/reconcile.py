@@ -0,0 +1,157 @@
+"""
+Reconcile - Bring interface addresses and routes in line with enterprise.json
+The "interfaces" section lists the static addresses and routes each interface
+should have. Comparing it with what the kernel has gives the changes to make;
+addresses and routes from DHCP, SLAAC or the kernel itself are left alone
+"""
+
+import json
+import asyncio
+import logging
+import ipaddress
+from typing import Dict, List, Optional
+
+from .system_integration import communicate_or_terminate
+
+# Configure logging
+logger = logging.getLogger(__name__)
+
+# Route protocols someone added by hand (ip route add) or through a config; anything else isn't ours to remove
+STATIC_PROTOCOLS = ("boot", "static")
+
+def parse_interfaces(section: Dict) -> Dict[str, Dict]:
+    """Validate an "interfaces" config section; addresses and routes normalised, ValueError on the first bad entry"""
+    if not isinstance(section, dict):
+        raise ValueError("interfaces must map interface names to their addresses and routes")
+    desired = {}
+    for interface, config in section.items():
+        if not isinstance(config, dict):
+            raise ValueError(f"{interface}: expected {{\"addresses\": [...], \"routes\": [...]}}")
+        addresses = []
+        for address in config.get("addresses") or []:
+            try:
+                # ipaddress would take a bare address as a /32, which is rarely what was meant
+                if "/" not in str(address):
+                    raise ValueError
+                addresses.append(str(ipaddress.ip_interface(address)))
+            except ValueError:
+                raise ValueError(f"{interface}: {address!r} is not an address with a prefix length")
+        routes = [_route(interface, route) for route in config.get("routes") or []]
+        desired[interface] = {"addresses": addresses, "routes": routes}
+    return desired
+
+def _route(interface: str, route: Dict) -> Dict:
+    """{"to", "via", "metric"} with "to" a network or "default" and the others optional"""
+    if not isinstance(route, dict) or not route.get("to"):
+        raise ValueError(f"{interface}: every route needs a \"to\" (a network or \"default\")")
+    to, via, metric = route["to"], route.get("via"), route.get("metric")
+    try:
+        if to != "default":
+            to = str(ipaddress.ip_network(to, strict=False))
+        if via is not None:
+            via = str(ipaddress.ip_address(via))
+    except ValueError as e:
+        raise ValueError(f"{interface}: {e}")
+    if metric is not None and (not isinstance(metric, int) or isinstance(metric, bool) or metric < 0):
+        raise ValueError(f"{interface}: metric must be a non-negative integer")
+    return {"to": to, "via": via, "metric": metric}
+
+def parse_addresses(text: str) -> List[Dict]:
+    """Global addresses from `ip -j addr show dev X`: {"address", "dynamic"}"""
+    addresses = []
+    for link in json.loads(text or "[]"):
+        for info in link.get("addr_info", []):
+            if info.get("scope") != "global" or "local" not in info:
+                continue
+            addresses.append({"address": str(ipaddress.ip_interface(f"{info['local']}/{info['prefixlen']}")),
+                              "dynamic": bool(info.get("dynamic"))})
+    return addresses
+
+def parse_routes(text: str) -> List[Dict]:
+    """Routes from `ip -j route show dev X` (either family): {"to", "via", "metric", "protocol"}"""
+    routes = []
+    for route in json.loads(text or "[]"):
+        to = route.get("dst", "default")
+        routes.append({"to": to if to == "default" else str(ipaddress.ip_network(to, strict=False)),
+                       "via": route.get("gateway"), "metric": route.get("metric"),
+                       "protocol": route.get("protocol", "boot")})
+    return routes
+
+def _matches(wanted: Dict, route: Dict) -> bool:
+    # Without a metric in the config, whatever metric the kernel picked is fine
+    return (wanted["to"] == route["to"] and wanted["via"] == route["via"]
+            and wanted["metric"] in (None, route["metric"]))
+
+def plan(interface: str, desired: Dict, actual: Dict) -> List[Dict]:
+    """Changes that turn actual ({"addresses", "routes"} as parsed above) into desired, in the order to make them.
+    Stale routes go first and new routes last, so gateways are reachable by the time a route needs them"""
+    static = [entry["address"] for entry in actual["addresses"] if not entry["dynamic"]]
+    present = [entry["address"] for entry in actual["addresses"]]
+    changes = [
+        {"interface": interface, "action": "remove", "kind": "route",
+         "route": {key: route[key] for key in ("to", "via", "metric")}}
+        for route in actual["routes"]
+        if route["protocol"] in STATIC_PROTOCOLS and not any(_matches(wanted, route) for wanted in desired["routes"])
+    ]
+    changes += [{"interface": interface, "action": "remove", "kind": "address", "address": address}
+                for address in static if address not in desired["addresses"]]
+    changes += [{"interface": interface, "action": "add", "kind": "address", "address": address}
+                for address in desired["addresses"] if address not in present]
+    changes += [{"interface": interface, "action": "add", "kind": "route", "route": wanted}
+                for wanted in desired["routes"] if not any(_matches(wanted, route) for route in actual["routes"])]
+    return changes
+
+def format_route(route: Dict) -> str:
+    via = f" via {route['via']}" if route.get("via") else ""
+    metric = f" metric {route['metric']}" if route.get("metric") is not None else ""
+    return f"{route['to']}{via}{metric}"
+
+def format_change(change: Dict) -> str:
+    """"+ eth0 address 192.0.2.10/24", "- eth0 route 10.0.0.0/8 via 192.0.2.254" """
+    sign = "+" if change["action"] == "add" else "-"
+    target = change["address"] if change["kind"] == "address" else format_route(change["route"])
+    return f"{sign} {change['interface']} {change['kind']} {target}"
+
+def ip_arguments(change: Dict) -> List[str]:
+    """The ip(8) command line (after "ip") that makes a change"""
+    verb = "add" if change["action"] == "add" else "del"
+    if change["kind"] == "address":
+        return ["addr", verb, change["address"], "dev", change["interface"]]
+    route = change["route"]
+    family = ["-6"] if ":" in route["to"] + (route.get("via") or "") else []
+    arguments = family + ["route", verb, route["to"]]
+    if route.get("via"):
+        arguments += ["via", route["via"]]
+    arguments += ["dev", change["interface"]]
+    if route.get("metric") is not None:
+        arguments += ["metric", str(route["metric"])]
+    if route.get("protocol"):
+        # Kept when a route is moved to another metric, so a DHCP route still reads as one
+        arguments += ["proto", route["protocol"]]
+    return arguments
+
+async def _ip(*arguments: str) -> tuple:
+    process = await asyncio.create_subprocess_exec(
+        'ip', *arguments, stdout=asyncio.subprocess.PIPE, stderr=asyncio.subprocess.PIPE
+    )
+    stdout, stderr = await communicate_or_terminate(process)
+    return process.returncode, stdout.decode(errors="replace"), stderr.decode(errors="replace").strip()
+
+async def read_state(interface: str) -> Optional[Dict]:
+    """The interface's addresses and routes as the kernel has them; None if there is no such interface"""
+    code, addresses, _ = await _ip('-j', 'addr', 'show', 'dev', interface)
+    if code != 0:
+        return None
+    routes = []
+    for family in ('-4', '-6'):
+        code, text, error = await _ip('-j', family, 'route', 'show', 'dev', interface)
+        if code == 0:
+            routes += parse_routes(text)
+        else:
+            logger.debug(f"ip {family} route on {interface}: {error}")
+    return {"addresses": parse_addresses(addresses), "routes": routes}
+
+async def apply_change(change: Dict) -> Optional[str]:
+    """Make one change; None when it worked, else ip's complaint"""
+    code, _, error = await _ip(*ip_arguments(change))
+    return None if code == 0 else error or f"ip exited with {code}"
@@ -0,0 +1,136 @@
+"""
+Relay - One daemon speaking for several
+A relay daemon polls the daemons downstream of it over mutual TLS for their
+status and interfaces, so a fleet view can list every machine's interfaces,
+tagged by host, in one request. Requests that only read state can also be
+passed on to one downstream daemon, for clients that reach the relay but not
+the machines behind it
+"""
+
+import time
+import asyncio
+import logging
+from dataclasses import dataclass, field
+from typing import Any, Callable, Dict, List, Optional
+
+from .fleet import FleetHost, parse_fleet
+from .ipc import READ_ONLY_PREFIXES, IpcError
+
+# Configure logging
+logger = logging.getLogger(__name__)
+
+DEFAULT_INTERVAL = 15.0
+DEFAULT_TIMEOUT = 5.0
+
+@dataclass
+class Downstream:
+    """A daemon behind the relay and what it last reported"""
+    host: FleetHost
+    status: Optional[Dict] = None
+    interfaces: List[Dict] = field(default_factory=list)
+    error: Optional[str] = None
+    # Last poll that got an answer, and last poll at all
+    updated: Optional[float] = None
+    checked: Optional[float] = None
+
+    @property
+    def reachable(self) -> bool:
+        return self.checked is not None and self.error is None
+
+class Relay:
+    """Polls the downstream daemons and serves their combined view
+
+    config has the fleet.json shape: certificate files at the top and a
+    hosts list of {name, address, ca_file, cert_file, key_file}. Interfaces
+    of a host that stopped answering stay listed, as last reported, while
+    its entry carries the error. RelayHostChanged is published when a host
+    stops or starts answering.
+    """
+
+    def __init__(self, config: Dict, local_status: Callable[[], Dict], local_interfaces: Callable[[], List[Dict]],
+                 events=None):
+        hosts = parse_fleet(config)
+        if len(hosts) < 2:
+            raise ValueError("a relay needs at least one downstream host")
+        if any(not host.remote for host in hosts[1:]):
+            raise ValueError("a relay reaches its downstream hosts directly, not via another relay")
+        self.name = hosts[0].name
+        self.downstream = {host.name: Downstream(host) for host in hosts[1:]}
+        # This daemon's own GetStatus and GetInterfaces
+        self.local_status = local_status
+        self.local_interfaces = local_interfaces
+        self.events = events
+        self.interval = config.get("interval", DEFAULT_INTERVAL)
+        self.timeout = config.get("timeout", DEFAULT_TIMEOUT)
+
+    async def run(self):
+        while True:
+            await self.poll()
+            await asyncio.sleep(self.interval)
+
+    async def poll(self):
+        """Ask every downstream daemon for its status and interfaces at once"""
+        await asyncio.gather(*(self._poll(downstream) for downstream in self.downstream.values()))
+
+    async def _poll(self, downstream: Downstream):
+        client = downstream.host.client(timeout=self.timeout)
+        loop = asyncio.get_running_loop()
+        # Hosts start out presumed reachable, so one that never answers is reported too
+        was_reachable = downstream.error is None
+        try:
+            status = await loop.run_in_executor(None, client.request, "GetStatus")
+            interfaces = await loop.run_in_executor(None, client.request, "GetInterfaces")
+        except IpcError as e:
+            downstream.error = e.message
+        else:
+            downstream.status, downstream.interfaces, downstream.error = status, interfaces, None
+            downstream.updated = time.time()
+        downstream.checked = time.time()
+        if downstream.reachable != was_reachable:
+            if downstream.error:
+                logger.warning(f"Relay lost {downstream.host.name}: {downstream.error}")
+            else:
+                logger.info(f"Relay reached {downstream.host.name} again")
+            if self.events:
+                self.events.publish("RelayHostChanged", host=downstream.host.name,
+                                    reachable=downstream.reachable, error=downstream.error)
+
+    def hosts(self) -> List[Dict]:
+        """This daemon, then each downstream one with what it last reported"""
+        now = time.time()
+        hosts = [{"name": self.name, "address": "local", "reachable": True, "error": None, "updated": now,
+                  "checked": now, "status": self.local_status(), "interfaces": len(self.local_interfaces())}]
+        for name, downstream in self.downstream.items():
+            hosts.append({
+                "name": name, "address": downstream.host.address, "reachable": downstream.reachable,
+                "error": downstream.error, "updated": downstream.updated, "checked": downstream.checked,
+                "status": downstream.status, "interfaces": len(downstream.interfaces)
+            })
+        return hosts
+
+    def interfaces(self, host: Optional[str] = None) -> List[Dict]:
+        """Every host's interfaces (or one host's), each tagged with its host; raises KeyError for an unknown host"""
+        if host is not None and host != self.name and host not in self.downstream:
+            raise KeyError(host)
+        combined = []
+        if host in (None, self.name):
+            combined.extend(dict(iface, host=self.name) for iface in self.local_interfaces())
+        for name, downstream in self.downstream.items():
+            if host in (None, name):
+                combined.extend(dict(iface, host=name) for iface in downstream.interfaces)
+        return combined
+
+    async def forward(self, host: str, request_type: str, params: Optional[Dict] = None) -> Any:
+        """Pass a request that only reads state on to a downstream daemon and return its reply data
+
+        Raises KeyError for an unknown host, PermissionError for a request
+        that could change something, and the downstream daemon's IpcError
+        """
+        if host not in self.downstream:
+            raise KeyError(host)
+        if not request_type.startswith(READ_ONLY_PREFIXES):
+            raise PermissionError(f"The relay only passes on requests that read state, not {request_type}")
+        client = self.downstream[host].host.client(timeout=self.timeout)
+        return await asyncio.get_running_loop().run_in_executor(
+            None, lambda: client.request(request_type, **(params or {}))
+        )
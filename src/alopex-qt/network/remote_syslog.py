@@ -0,0 +1,219 @@
+"""
+Remote Syslog - Daemon events and audit records sent to a syslog collector
+RFC 5424 messages over udp://, tcp:// or tls:// (octet-counted framing on streams);
+messages wait in a bounded backlog while the collector is unreachable
+"""
+
+import os
+import ssl
+import json
+import socket
+import asyncio
+import logging
+from collections import deque
+from datetime import datetime, timezone
+from typing import Dict, Iterable, Optional
+from urllib.parse import urlparse
+
+from .diagnostics import redact
+from .events import EventBus
+
+# Configure logging
+logger = logging.getLogger(__name__)
+
+DEFAULT_PORTS = {"udp": 514, "tcp": 601, "tls": 6514}
+FACILITIES = {
+    "kern": 0, "user": 1, "daemon": 3, "auth": 4, "syslog": 5, "authpriv": 10, "audit": 13,
+    "local0": 16, "local1": 17, "local2": 18, "local3": 19, "local4": 20, "local5": 21, "local6": 22, "local7": 23
+}
+SEVERITY_WARNING = 4
+SEVERITY_NOTICE = 5
+SEVERITY_INFO = 6
+
+# Structured data ID under the documentation enterprise number (RFC 5612)
+SD_ID = "alopex@32473"
+# Datagrams longer than this may not survive the trip (RFC 5426 asks collectors for 2048)
+UDP_MAX = 2048
+BACKLOG_SIZE = 1000
+CONNECT_TIMEOUT = 5.0
+RETRY_INTERVAL = 10.0
+PARAM_LENGTH = 200
+
+# Too chatty to forward unless asked for by name
+QUIET_EVENTS = {"JobProgress"}
+WARNING_EVENTS = {
+    "JobFailed", "JobCancelled", "CertificateExpired", "HotspotClientBlocked", "SlaBreached", "WifiSecurityAlert",
+    "LinkSpeedDegraded"
+}
+
+def _text(value) -> str:
+    if value is None:
+        return "-"
+    if isinstance(value, bool):
+        return "true" if value else "false"
+    if isinstance(value, (dict, list)):
+        return json.dumps(value, sort_keys=True)
+    return str(value)
+
+def structured_data(params: Dict) -> str:
+    """One SD element with a param per field, values escaped as RFC 5424 requires"""
+    if not params:
+        return "-"
+    elements = []
+    for key, value in params.items():
+        # PARAM-NAME is printable ASCII without '=', ' ', ']' or '"', at most 32 characters
+        name = "".join(c for c in str(key) if 32 < ord(c) < 127 and c not in '= ]"')[:32]
+        text = _text(value).replace("\\", "\\\\").replace('"', '\\"').replace("]", "\\]")
+        elements.append(f'{name}="{text}"')
+    return f"[{SD_ID} {' '.join(elements)}]"
+
+def format_message(facility: int, severity: int, timestamp: float, msgid: str, sd: Dict, text: str,
+                   hostname: Optional[str] = None, app_name: str = "alopexd", procid: Optional[int] = None) -> bytes:
+    """One RFC 5424 message, e.g. <29>1 2026-10-17T08:00:00.000Z host alopexd 812 CarrierChanged [...] ..."""
+    stamp = datetime.fromtimestamp(timestamp, timezone.utc).isoformat(timespec="milliseconds")
+    stamp = stamp.replace("+00:00", "Z")
+    header = (f"<{facility * 8 + severity}>1 {stamp} {(hostname or socket.gethostname())[:255]} {app_name[:48]} "
+              f"{procid if procid is not None else os.getpid()} {msgid[:32] or '-'}")
+    return f"{header} {structured_data(sd)} {text}".encode("utf-8", "replace")
+
+def _summary(fields: Dict) -> str:
+    return " ".join(f"{key}={_text(value)}" for key, value in fields.items())
+
+class SyslogForwarder:
+    """Formats published events and IPC audit records and delivers them to one collector"""
+
+    def __init__(self, url: str, events: EventBus, facility: str = "daemon", kinds: Optional[Iterable[str]] = None,
+                 ca_file: Optional[str] = None, cert_file: Optional[str] = None, key_file: Optional[str] = None):
+        parsed = urlparse(url)
+        if parsed.scheme not in DEFAULT_PORTS or not parsed.hostname:
+            raise ValueError(f"Expected udp://, tcp:// or tls://host[:port], got {url!r}")
+        if facility not in FACILITIES:
+            raise ValueError(f"Unknown syslog facility {facility!r}")
+        self.scheme = parsed.scheme
+        self.host = parsed.hostname
+        self.port = parsed.port or DEFAULT_PORTS[parsed.scheme]
+        self.events = events
+        self.facility = FACILITIES[facility]
+        self.kinds = set(kinds) if kinds else None
+        self.ssl_context = None
+        if self.scheme == "tls":
+            self.ssl_context = ssl.create_default_context(cafile=ca_file)
+            if cert_file:
+                self.ssl_context.load_cert_chain(cert_file, key_file)
+        self.backlog = deque(maxlen=BACKLOG_SIZE)
+        self.pending = asyncio.Event()
+        self.writer: Optional[asyncio.StreamWriter] = None
+        self.sent = 0
+        self.dropped = 0
+        self.last_error: Optional[str] = None
+
+    def _queue(self, message: bytes):
+        if len(self.backlog) == self.backlog.maxlen:
+            self.dropped += 1
+        self.backlog.append(message)
+        self.pending.set()
+
+    def format_event(self, event: Dict) -> bytes:
+        """An EventBus event as a message with its kind as MSGID"""
+        fields = {key: value for key, value in redact(event).items() if key not in ("event", "timestamp")}
+        severity = SEVERITY_WARNING if event["event"] in WARNING_EVENTS else SEVERITY_INFO
+        return format_message(self.facility, severity, event["timestamp"], event["event"], fields,
+                              f"{event['event']} {_summary(fields)}".rstrip())
+
+    def format_audit(self, record: Dict) -> bytes:
+        """An IPC audit record as a message with MSGID audit; secrets and long values cut"""
+        params = {key: _text(value)[:PARAM_LENGTH] for key, value in redact(record["params"]).items()}
+        outcome = record["outcome"]
+        # Remote clients have no uid, only the name on their certificate
+        cert = record.get("client_cert")
+        who = f"certificate {cert}" if cert else f"uid {_text(record['client_uid'])}"
+        text = f"{record['request']} by {who}: {outcome}"
+        if record.get("message"):
+            text += f": {record['message']}"
+        if params:
+            text += f" ({_summary(params)})"
+        identity = {"cert": cert} if cert else {"uid": record["client_uid"]}
+        sd = dict({"request": record["request"], **identity,
+                   "request_id": record.get("request_id"), "outcome": outcome}, **params)
+        severity = SEVERITY_NOTICE if outcome == "ok" else SEVERITY_WARNING
+        return format_message(self.facility, severity, record["timestamp"], "audit", sd, text)
+
+    def audit(self, record: Dict):
+        """IpcServer audit hook"""
+        self._queue(self.format_audit(record))
+
+    def _send_datagram(self, message: bytes):
+        family = socket.AF_INET6 if ":" in self.host else socket.AF_INET
+        with socket.socket(family, socket.SOCK_DGRAM) as sock:
+            sock.sendto(message[:UDP_MAX], (self.host, self.port))
+
+    async def _send(self, message: bytes):
+        """Deliver one message; raises OSError (or ssl.SSLError, a subclass) on failure"""
+        if self.scheme == "udp":
+            await asyncio.get_running_loop().run_in_executor(None, self._send_datagram, message)
+            return
+        try:
+            if self.writer is None or self.writer.is_closing():
+                _, self.writer = await asyncio.wait_for(asyncio.open_connection(
+                    self.host, self.port, ssl=self.ssl_context,
+                    server_hostname=self.host if self.ssl_context else None
+                ), CONNECT_TIMEOUT)
+            self.writer.write(f"{len(message)} ".encode() + message)
+            await asyncio.wait_for(self.writer.drain(), CONNECT_TIMEOUT)
+        except asyncio.TimeoutError:
+            self._close()
+            raise OSError(f"no answer from {self.host}:{self.port} in {CONNECT_TIMEOUT:g}s")
+        except OSError:
+            self._close()
+            raise
+
+    def _close(self):
+        if self.writer:
+            self.writer.close()
+            self.writer = None
+
+    async def _deliver(self):
+        """Send the backlog oldest first, retrying while the collector is away"""
+        while True:
+            await self.pending.wait()
+            self.pending.clear()
+            while self.backlog:
+                try:
+                    await self._send(self.backlog[0])
+                except OSError as e:
+                    if self.last_error is None:
+                        logger.warning(f"Cannot reach syslog collector {self.host}:{self.port}: {e}")
+                    self.last_error = str(e)
+                    await asyncio.sleep(RETRY_INTERVAL)
+                    continue
+                if self.last_error is not None:
+                    logger.info(f"Forwarding to syslog collector {self.host}:{self.port} again")
+                    self.last_error = None
+                self.backlog.popleft()
+                self.sent += 1
+
+    def stats(self) -> Dict:
+        """Where messages go and how that is going"""
+        return {
+            "enabled": True,
+            "url": f"{self.scheme}://{self.host}:{self.port}",
+            "events": sorted(self.kinds) if self.kinds else None,
+            "sent": self.sent,
+            "queued": len(self.backlog),
+            "dropped": self.dropped,
+            "last_error": self.last_error
+        }
+
+    async def run(self):
+        subscription = self.events.subscribe(self.kinds)
+        delivery = asyncio.create_task(self._deliver())
+        try:
+            while True:
+                event = await subscription.get()
+                if self.kinds is None and event["event"] in QUIET_EVENTS:
+                    continue
+                self._queue(self.format_event(event))
+        finally:
+            delivery.cancel()
+            self.events.unsubscribe(subscription)
+            self._close()
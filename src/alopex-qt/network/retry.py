@@ -0,0 +1,69 @@
+"""
+Retry - Exponential backoff for profiles auto-connect keeps failing on
+Each failure (a wrong PSK, a DHCP timeout) doubles the wait before the next
+attempt, up to a cap. After max_attempts failures auto-connect gives up on the
+profile until it connects some other way, is edited, or is reset by hand
+"""
+
+import time
+import logging
+from typing import Dict, List, Optional
+
+# Configure logging
+logger = logging.getLogger(__name__)
+
+RETRY_INITIAL = 1.0
+RETRY_MAX = 30.0
+
+def backoff(attempt: int, initial: float = RETRY_INITIAL, maximum: float = RETRY_MAX) -> float:
+    """Seconds to wait after the attempt-th failure: initial, doubling, capped at maximum"""
+    return min(maximum, initial * 2 ** max(0, attempt - 1))
+
+class RetryTracker:
+    """Failures per profile and when each may be tried again; max_attempts 0 never gives up"""
+
+    def __init__(self, initial: float = 5.0, maximum: float = 300.0, max_attempts: int = 10):
+        self.initial = initial
+        self.maximum = maximum
+        self.max_attempts = max_attempts
+        self.entries: Dict[str, Dict] = {}
+
+    @classmethod
+    def from_config(cls, section: Dict) -> "RetryTracker":
+        """From the "auto_connect_retry" config section: initial, max_delay, max_attempts"""
+        section = section or {}
+        return cls(section.get("initial", 5.0), section.get("max_delay", 300.0), section.get("max_attempts", 10))
+
+    def due(self, name: str, now: Optional[float] = None) -> bool:
+        """Whether auto-connect may try the profile now"""
+        entry = self.entries.get(name)
+        if not entry:
+            return True
+        return not entry["gave_up"] and (time.time() if now is None else now) >= entry["next_attempt"]
+
+    def failed(self, name: str, interface: str, error: Optional[str], now: Optional[float] = None) -> Dict:
+        """Count a failed attempt; the entry says when to try next, or that it gave up"""
+        now = time.time() if now is None else now
+        entry = self.entries.setdefault(name, {"profile": name, "interface": interface, "failures": 0,
+                                               "next_attempt": None, "gave_up": False, "last_error": None})
+        entry["failures"] += 1
+        entry["last_error"] = error
+        if self.max_attempts and entry["failures"] >= self.max_attempts:
+            entry.update(gave_up=True, next_attempt=None)
+        else:
+            entry["next_attempt"] = now + backoff(entry["failures"], self.initial, self.maximum)
+        return dict(entry)
+
+    def reset(self, name: str) -> bool:
+        """Forget a profile's failures (it connected, or was edited); False if there were none"""
+        return self.entries.pop(name, None) is not None
+
+    def next_attempt(self, names: List[str]) -> Optional[float]:
+        """The earliest retry among these profiles, None when none is waiting on one"""
+        waiting = [self.entries[name]["next_attempt"] for name in names
+                   if name in self.entries and not self.entries[name]["gave_up"]]
+        return min(waiting) if waiting else None
+
+    def status(self) -> List[Dict]:
+        """Profiles with failures, in the order they first failed"""
+        return [dict(entry) for entry in self.entries.values()]
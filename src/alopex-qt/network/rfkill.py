@@ -0,0 +1,194 @@
+"""
+Rfkill - Radio kill switches for WiFi, Bluetooth and WWAN
+Reads /sys/class/rfkill and tells a soft block (software, can be lifted) apart
+from a hard block (a physical switch, key or BIOS setting nothing here can lift);
+airplane mode soft-blocks every radio and puts each back the way it was
+"""
+
+import os
+import json
+import asyncio
+import logging
+from dataclasses import dataclass, asdict
+from pathlib import Path
+from typing import Dict, List, Optional
+
+# Configure logging
+logger = logging.getLogger(__name__)
+
+SYS_CLASS_RFKILL = Path("/sys/class/rfkill")
+POLL_INTERVAL = 1.0
+
+# Radio types ALOPEX switches; the kernel also knows uwb, wimax, gps, fm and nfc
+RADIO_TYPES = ("wlan", "bluetooth", "wwan")
+# What users type for them
+TYPE_ALIASES = {"wifi": "wlan", "wlan": "wlan", "bluetooth": "bluetooth", "bt": "bluetooth", "wwan": "wwan"}
+
+# How each block state reads in listings
+BLOCK_LABELS = {None: "unblocked", "soft": "soft blocked", "hard": "hard blocked (hardware switch)"}
+
+# What an interface whose radio is blocked shows instead of Disconnected
+BLOCK_STATUS = {"hard": "Blocked by hardware switch", "soft": "Radio off"}
+
+# Why a blocked radio cannot connect, as a connect failure reason
+BLOCK_REASONS = {
+    "hard": "radio is blocked by a hardware switch or key",
+    "soft": "radio is switched off (rfkill soft block)"
+}
+
+@dataclass
+class RfkillDevice:
+    """One kill switch, e.g. phy0 (wlan) or hci0 (bluetooth)"""
+    index: int
+    type: str
+    name: str
+    soft: bool = False
+    hard: bool = False
+    interface: Optional[str] = None  # network interface of a wlan radio
+
+    @property
+    def block(self) -> Optional[str]:
+        """hard, soft or None; hard wins, since lifting the soft block alone would not help"""
+        return "hard" if self.hard else "soft" if self.soft else None
+
+def _read(path: Path) -> Optional[str]:
+    try:
+        return path.read_text().strip()
+    except OSError:
+        return None
+
+def read_devices(rfkill_path: Path = SYS_CLASS_RFKILL) -> List[RfkillDevice]:
+    """Every kill switch the kernel knows about, in index order"""
+    devices = []
+    if not rfkill_path.is_dir():
+        return devices
+    for entry in rfkill_path.glob("rfkill*"):
+        index = entry.name[len("rfkill"):]
+        if not index.isdigit():
+            continue
+        # phyN -> its parent device -> net/wlan0
+        net = sorted((entry / "device" / "device" / "net").glob("*"))
+        devices.append(RfkillDevice(
+            index=int(index),
+            type=_read(entry / "type") or "unknown",
+            name=_read(entry / "name") or entry.name,
+            soft=_read(entry / "soft") == "1",
+            hard=_read(entry / "hard") == "1",
+            interface=net[0].name if net else None
+        ))
+    return sorted(devices, key=lambda d: d.index)
+
+def write_soft_block(index: int, blocked: bool, rfkill_path: Path = SYS_CLASS_RFKILL) -> bool:
+    """Set one switch's soft block (needs root)"""
+    try:
+        (rfkill_path / f"rfkill{index}" / "soft").write_text("1" if blocked else "0")
+        return True
+    except OSError as e:
+        logger.error(f"Cannot {'block' if blocked else 'unblock'} rfkill{index}: {e}")
+        return False
+
+def interface_block(interface: str, net_path: Path = Path("/sys/class/net")) -> Optional[str]:
+    """How a WiFi interface's radio is blocked, if it is"""
+    for entry in (net_path / interface / "phy80211").glob("rfkill*"):
+        if _read(entry / "hard") == "1":
+            return "hard"
+        if _read(entry / "soft") == "1":
+            return "soft"
+    return None
+
+class RfkillMonitor:
+    """Polls the rfkill backend and publishes RfkillChanged when a switch flips"""
+
+    def __init__(self, backend, events=None, interval: float = POLL_INTERVAL):
+        self.backend = backend
+        self.events = events
+        self.interval = interval
+        self.known: Dict[int, RfkillDevice] = {}
+
+    def poll(self) -> List[RfkillDevice]:
+        """Read the switches, publishing changes since the last poll"""
+        devices = self.backend.devices()
+        for device in devices:
+            previous = self.known.get(device.index)
+            if previous and previous.block != device.block:
+                logger.info(f"{device.type} {device.name} is {device.block or 'unblocked'}")
+                if self.events:
+                    self.events.publish("RfkillChanged", **asdict(device), block=device.block,
+                                        previous=previous.block)
+        self.known = {device.index: device for device in devices}
+        return devices
+
+    async def run(self):
+        while True:
+            try:
+                self.poll()
+            except Exception as e:
+                logger.error(f"rfkill poll failed: {e}")
+            await asyncio.sleep(self.interval)
+
+class AirplaneMode:
+    """Soft-blocks all radios, remembering each one's soft block to restore on the way out"""
+
+    def __init__(self, backend, state_path: Path, events=None):
+        self.backend = backend
+        self.events = events
+        self.state_file = state_path / "airplane.json"
+        # "type/name" -> soft block before airplane mode; None while it is off
+        self.saved: Optional[Dict[str, bool]] = None
+        self._load()
+
+    @property
+    def active(self) -> bool:
+        return self.saved is not None
+
+    @staticmethod
+    def _key(device: RfkillDevice) -> str:
+        # Indexes are handed out at probe time, so they can differ after a reboot; names don't
+        return f"{device.type}/{device.name}"
+
+    def _load(self):
+        if not self.state_file.exists():
+            return
+        try:
+            with open(self.state_file) as f:
+                self.saved = json.load(f).get("saved")
+            if self.saved is not None:
+                logger.info("Airplane mode is on")
+        except Exception as e:
+            logger.error(f"Failed to load airplane mode state: {e}")
+
+    def _save(self):
+        """Write the saved states via a temp file, or remove the file once airplane mode is off"""
+        try:
+            if self.saved is None:
+                self.state_file.unlink(missing_ok=True)
+                return
+            self.state_file.parent.mkdir(parents=True, exist_ok=True)
+            temp_path = self.state_file.with_suffix(".json.tmp")
+            with open(temp_path, 'w') as f:
+                json.dump({"saved": self.saved}, f, indent=2)
+            os.replace(temp_path, self.state_file)
+        except Exception as e:
+            logger.error(f"Failed to save airplane mode state: {e}")
+
+    def set(self, enabled: bool) -> List[str]:
+        """Turn airplane mode on or off; returns the radios that could not be switched"""
+        if enabled == self.active:
+            return []
+        radios = [d for d in self.backend.devices() if d.type in RADIO_TYPES]
+        failed = []
+        if enabled:
+            self.saved = {self._key(d): d.soft for d in radios}
+            self._save()
+            failed = [d.name for d in radios if not d.soft and not self.backend.set_soft_block(d.index, True)]
+        else:
+            # Radios that turned up mid-flight had no state before; they come back on
+            failed = [d.name for d in radios if d.soft != self.saved.get(self._key(d), False)
+                      and not self.backend.set_soft_block(d.index, self.saved.get(self._key(d), False))]
+            self.saved = None
+            self._save()
+
+        logger.info(f"Airplane mode {'on' if enabled else 'off'}")
+        if self.events:
+            self.events.publish("AirplaneModeChanged", enabled=enabled, failed=failed)
+        return failed
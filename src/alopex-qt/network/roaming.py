@@ -0,0 +1,196 @@
+"""
+Roaming - Timeline of the access points each WiFi interface used
+Records every association, reassociation, roam and disconnect with the BSSID,
+the signal on the access point being left and how long the interface stayed
+on it, so a flaky handover shows up as a pattern rather than a hunch. nl80211
+events drive it; sampling `iw dev X link` keeps the signal current and catches
+roams the events missed
+"""
+
+import os
+import json
+import time
+import asyncio
+import logging
+from collections import deque
+from pathlib import Path
+from typing import Callable, Deque, Dict, List, Optional, Set
+
+# Configure logging
+logger = logging.getLogger(__name__)
+
+DEFAULT_INTERVAL = 5.0
+MAX_ENTRIES = 500
+
+class RoamingTimeline:
+    """Association history per WiFi interface, kept across restarts"""
+
+    def __init__(self, backend, interfaces: Callable[[], List[str]], state_path: Path, events=None,
+                 interval: float = DEFAULT_INTERVAL, limit: int = MAX_ENTRIES):
+        self.backend = backend
+        self.interfaces = interfaces
+        self.timeline_file = state_path / "roaming.json"
+        self.events = events
+        self.interval = interval
+        self.entries: Deque[Dict] = deque(maxlen=limit)
+        # interface -> {"bssid", "ssid", "since", "signal", "entry"}: the access point in use now
+        self.stints: Dict[str, Dict] = {}
+        self.sampled: Set[str] = set()
+        self._wake: Optional[asyncio.Event] = None
+        self._load()
+
+    def _load(self):
+        if not self.timeline_file.exists():
+            return
+        try:
+            with open(self.timeline_file) as f:
+                self.entries.extend(json.load(f).get("entries", []))
+            logger.info(f"Loaded {len(self.entries)} roaming timeline entries")
+        except Exception as e:
+            logger.error(f"Failed to load roaming timeline: {e}")
+
+    def _save(self):
+        """Save the timeline via a temp file"""
+        try:
+            self.timeline_file.parent.mkdir(parents=True, exist_ok=True)
+            temp_file = self.timeline_file.with_suffix(".tmp")
+            with open(temp_file, "w") as f:
+                json.dump({"entries": list(self.entries)}, f)
+            os.replace(temp_file, self.timeline_file)
+        except OSError as e:
+            logger.error(f"Failed to save roaming timeline: {e}")
+
+    def _record(self, entry: Dict) -> Dict:
+        self.entries.append(entry)
+        self._save()
+        logger.info(f"{entry['interface']}: {entry['kind']} {entry.get('bssid') or ''}".rstrip())
+        if self.events:
+            # "kind" names the event itself on the bus
+            fields = {k: v for k, v in entry.items() if k not in ("kind", "timestamp")}
+            self.events.publish("WifiAssociation", change=entry["kind"], **fields)
+        return entry
+
+    def _join(self, interface: str, bssid: str, now: float, ssid: Optional[str] = None,
+              signal: Optional[int] = None) -> Dict:
+        stint = self.stints.get(interface)
+        if not stint:
+            kind = "associated"
+        else:
+            kind = "reassociated" if stint["bssid"] == bssid else "roamed"
+        entry = {
+            "interface": interface,
+            "kind": kind,
+            "bssid": bssid,
+            "ssid": ssid or (stint["ssid"] if stint else None),
+            "previous_bssid": stint["bssid"] if stint else None,
+            # What the interface had on the access point it left, i.e. what it roamed at
+            "signal": stint["signal"] if stint else None,
+            "duration": round(now - stint["since"], 1) if stint else None,
+            "new_signal": signal,
+            "timestamp": now
+        }
+        # Reassociating to the same access point doesn't end the time spent on it
+        since = stint["since"] if kind == "reassociated" else now
+        self.stints[interface] = {"bssid": bssid, "ssid": entry["ssid"], "since": since, "signal": signal,
+                                  "entry": entry}
+        return self._record(entry)
+
+    def _leave(self, interface: str, now: float, reason: Optional[int] = None,
+               by_ap: Optional[bool] = None) -> Optional[Dict]:
+        stint = self.stints.pop(interface, None)
+        if not stint:
+            return None
+        return self._record({
+            "interface": interface,
+            "kind": "disconnected",
+            "bssid": stint["bssid"],
+            "ssid": stint["ssid"],
+            "signal": stint["signal"],
+            "duration": round(now - stint["since"], 1),
+            "reason": reason,
+            "by_ap": by_ap,
+            "timestamp": now
+        })
+
+    def event(self, event: Dict) -> Optional[Dict]:
+        """Record a connected/roamed/disconnected event from nl80211; returns the entry, if any"""
+        now = time.time()
+        interface = event["interface"]
+        if event["kind"] in ("connected", "roamed"):
+            entry = self._join(interface, event["bssid"], now)
+        elif event["kind"] == "disconnected":
+            entry = self._leave(interface, now, event.get("reason"), event.get("by_ap"))
+        else:
+            return None
+        if self._wake:
+            # Fill in the SSID and signal of the new access point without waiting for the next sample
+            self._wake.set()
+        return entry
+
+    def sample(self, interface: str, link: Optional[Dict]) -> Optional[Dict]:
+        """Update from `iw dev X link` (link_info); returns an entry when it shows a change the events missed"""
+        now = time.time()
+        first = interface not in self.sampled
+        self.sampled.add(interface)
+        stint = self.stints.get(interface)
+        if not link or not link.get("bssid"):
+            return self._leave(interface, now) if stint else None
+        if not stint and first:
+            # Already associated when alopex started: a stint of unknown start, not an association
+            self.stints[interface] = {"bssid": link["bssid"], "ssid": link.get("ssid"), "since": now,
+                                      "signal": link.get("signal"), "entry": None}
+            return None
+        if not stint or stint["bssid"] != link["bssid"]:
+            return self._join(interface, link["bssid"], now, link.get("ssid"), link.get("signal"))
+        stint["signal"] = link.get("signal")
+        stint["ssid"] = stint["ssid"] or link.get("ssid")
+        entry = stint["entry"]
+        if entry and (entry["ssid"] is None or entry["new_signal"] is None):
+            entry["ssid"] = entry["ssid"] or link.get("ssid")
+            entry["new_signal"] = entry["new_signal"] if entry["new_signal"] is not None else link.get("signal")
+            self._save()
+        return None
+
+    def snapshot(self, interface: Optional[str] = None, since: Optional[float] = None,
+                 limit: int = MAX_ENTRIES) -> Dict:
+        """Entries oldest first, plus the access point each interface is on now"""
+        entries = [entry for entry in self.entries
+                   if (interface is None or entry["interface"] == interface)
+                   and (since is None or entry["timestamp"] > since)]
+        now = time.time()
+        current = [
+            {"interface": name, "bssid": stint["bssid"], "ssid": stint["ssid"], "signal": stint["signal"],
+             "since": stint["since"], "duration": round(now - stint["since"], 1)}
+            for name, stint in sorted(self.stints.items()) if interface is None or name == interface
+        ]
+        return {"entries": entries[-limit:] if limit else [], "current": current}
+
+    async def sample_once(self):
+        for interface in self.interfaces():
+            self.sample(interface, await asyncio.to_thread(self.backend.link_info, interface))
+
+    async def _sample_loop(self):
+        self._wake = asyncio.Event()
+        while True:
+            try:
+                await self.sample_once()
+            except Exception as e:
+                logger.error(f"Roaming sample failed: {e}")
+            try:
+                await asyncio.wait_for(self._wake.wait(), self.interval)
+            except asyncio.TimeoutError:
+                pass
+            self._wake.clear()
+
+    async def run(self, watch: bool = True):
+        """Sample links; with watch, also read nl80211 events here (otherwise WifiWatch passes them on)"""
+        sampling = asyncio.create_task(self._sample_loop())
+        try:
+            if watch:
+                try:
+                    await self.backend.watch_frames(self.event)
+                except OSError as e:
+                    logger.warning(f"No association events ({e}); sampling links only")
+            await sampling
+        finally:
+            sampling.cancel()
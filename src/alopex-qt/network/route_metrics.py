@@ -0,0 +1,52 @@
+"""
+Route Metrics - Default-route metrics for connections that are up side by side
+Each link type gets a band (Ethernet below WiFi below cellular), and a profile's
+priority moves it within its band, so with several connections up traffic
+prefers the wired one without anyone touching routes. A profile's route_metric
+overrides the lot
+"""
+
+import logging
+from typing import Dict, List, Optional
+
+from .discovery import InterfaceType
+
+# Configure logging
+logger = logging.getLogger(__name__)
+
+TYPE_METRICS = {
+    InterfaceType.ETHERNET: 100, InterfaceType.BOND: 100, InterfaceType.BRIDGE: 100, InterfaceType.VLAN: 100,
+    InterfaceType.OTHER: 100, InterfaceType.WIFI: 600, InterfaceType.CELLULAR: 700
+}
+# How far priority moves a metric either way; keeps each type inside its band
+PRIORITY_SPAN = 49
+MAX_METRIC = 2 ** 32 - 1
+# Routes a DHCP client or router advertisements put there, whatever their metric
+DYNAMIC_PROTOCOLS = ("dhcp", "ra")
+
+def route_metric(interface_type: InterfaceType, priority: int = 0, override: Optional[int] = None) -> Optional[int]:
+    """The metric a connection's default route should have; None for types left alone (VPNs, tunnels)"""
+    if override is not None:
+        return override
+    base = TYPE_METRICS.get(interface_type)
+    if base is None:
+        return None
+    return base - max(-PRIORITY_SPAN, min(PRIORITY_SPAN, priority))
+
+def metric_changes(interface: str, metric: int, routes: List[Dict]) -> List[Dict]:
+    """Reconcile-style changes that put the interface's default routes on metric: each is added again with it,
+    then the old one removed. Only DHCP and RA routes and ones added without a metric are touched; a metric
+    someone chose on purpose (failover's, say) stays"""
+    changes = []
+    for route in routes:
+        if route["to"] != "default" or route["metric"] == metric:
+            continue
+        if route.get("protocol") not in DYNAMIC_PROTOCOLS and route["metric"] is not None:
+            continue
+        wanted = {"to": "default", "via": route["via"], "metric": metric, "protocol": route.get("protocol")}
+        if not any(other["to"] == "default" and other["via"] == route["via"] and other["metric"] == metric
+                   for other in routes):
+            changes.append({"interface": interface, "action": "add", "kind": "route", "route": wanted})
+        changes.append({"interface": interface, "action": "remove", "kind": "route",
+                        "route": {key: route.get(key) for key in ("to", "via", "metric", "protocol")}})
+    return changes
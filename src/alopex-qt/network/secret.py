@@ -0,0 +1,105 @@
+"""
+Secret - Passwords, PSKs and key material that must not leak
+A Secret holds its value in a buffer it overwrites with zeros when wiped or
+dropped, prints as <redacted> in reprs, f-strings and logs, and becomes
+<redacted> in IPC replies. Code that needs the value (a backend handing a PSK
+to wpa_supplicant, profiles written to their 0600 file) calls reveal() right
+at that boundary
+"""
+
+import hmac
+import logging
+from typing import Any, Optional, Union
+
+# Configure logging
+logger = logging.getLogger(__name__)
+
+REDACTED = "<redacted>"
+
+class Secret:
+    """A string that never shows itself by accident"""
+
+    __slots__ = ("_value",)
+
+    def __init__(self, value: Union[str, bytes] = ""):
+        self._value = bytearray(value.encode() if isinstance(value, str) else value)
+
+    @classmethod
+    def wrap(cls, value: Any) -> Optional["Secret"]:
+        """value as a Secret; None and Secrets pass through"""
+        if value is None or isinstance(value, Secret):
+            return value
+        return cls(value)
+
+    def reveal(self) -> str:
+        return self._value.decode()
+
+    def reveal_bytes(self) -> bytes:
+        return bytes(self._value)
+
+    def wipe(self):
+        """Overwrite the value with zeros and forget it"""
+        for index in range(len(self._value)):
+            self._value[index] = 0
+        self._value = bytearray()
+
+    def __del__(self):
+        # The interpreter may already be tearing down the buffer at exit
+        try:
+            self.wipe()
+        except Exception:
+            pass
+
+    def __repr__(self) -> str:
+        return f"Secret({REDACTED})"
+
+    def __str__(self) -> str:
+        return REDACTED
+
+    def __format__(self, spec: str) -> str:
+        return format(REDACTED, spec)
+
+    def __len__(self) -> int:
+        return len(self._value)
+
+    def __bool__(self) -> bool:
+        return bool(self._value)
+
+    def __eq__(self, other) -> bool:
+        if isinstance(other, Secret):
+            other = other._value
+        elif isinstance(other, str):
+            other = other.encode()
+        elif not isinstance(other, (bytes, bytearray)):
+            return NotImplemented
+        return hmac.compare_digest(bytes(self._value), bytes(other))
+
+    __hash__ = None
+
+    def __copy__(self) -> "Secret":
+        # Each copy owns its buffer, so wiping one (or dropping it) leaves the others intact
+        return Secret(self._value)
+
+    def __deepcopy__(self, memo) -> "Secret":
+        return Secret(self._value)
+
+    def __reduce__(self):
+        raise TypeError("Secrets are not pickled; reveal() them where storing is intended")
+
+def reveal(value: Any) -> Any:
+    """The plain value of a Secret; anything else (None, a str) as it is"""
+    return value.reveal() if isinstance(value, Secret) else value
+
+def revealed(data: Any) -> Any:
+    """Copy of data with every Secret in it revealed, for the files and exports meant to hold them"""
+    if isinstance(data, dict):
+        return {key: revealed(value) for key, value in data.items()}
+    if isinstance(data, (list, tuple)):
+        return [revealed(item) for item in data]
+    return reveal(data)
+
+def json_default(value: Any) -> Any:
+    """json.dumps default= hook: Secrets become <redacted>, anything else unserialisable is still an error"""
+    if isinstance(value, Secret):
+        return REDACTED
+    raise TypeError(f"Object of type {type(value).__name__} is not JSON serializable")
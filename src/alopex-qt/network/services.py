@@ -0,0 +1,84 @@
+"""
+Profile Services - systemd units that run only while a profile is active
+Units listed on a profile are started when it becomes configured and stopped
+when its interface goes down or switches to another profile
+"""
+
+import logging
+from typing import Dict, List, Tuple
+
+from .connection_manager import ConnectionManager
+from .state_machine import LinkState
+
+# Configure logging
+logger = logging.getLogger(__name__)
+
+# Moving to one of these stops the units started for the interface's profile
+STOPPING_STATES = {LinkState.DISCONNECTED.value, LinkState.DEACTIVATING.value, LinkState.FAILED.value}
+
+class ProfileServices:
+    """Follows interface state changes and starts or stops each profile's units"""
+
+    def __init__(self, connections: ConnectionManager):
+        self.connections = connections
+        self.backend = connections.backends.services
+        self.events = connections.events
+
+        # interface -> (profile, units we started for it); kept so they can be
+        # stopped even if the profile is edited or deleted meanwhile
+        self.running: Dict[str, Tuple[str, List[str]]] = {}
+
+    async def run(self):
+        """Start units of profiles that are already up, then follow state changes"""
+        subscription = self.events.subscribe(["InterfaceStateChanged"])
+        try:
+            for interface, state in list(self.connections.interface_states.items()):
+                if state.status == LinkState.CONFIGURED and state.profile_name:
+                    await self._activated(interface, state.profile_name)
+
+            while True:
+                event = await subscription.get()
+                try:
+                    if event["state"] == LinkState.CONFIGURED.value and event["profile"]:
+                        await self._activated(event["interface"], event["profile"])
+                    elif event["state"] in STOPPING_STATES:
+                        await self._deactivated(event["interface"])
+                except Exception as e:
+                    logger.error(f"Profile services failed for {event['interface']}: {e}")
+        finally:
+            self.events.unsubscribe(subscription)
+
+    async def _activated(self, interface: str, profile_name: str):
+        previous = self.running.get(interface)
+        if previous and previous[0] == profile_name:
+            return
+        if previous:
+            await self._deactivated(interface)
+
+        profile = self.connections.get_profile(profile_name)
+        if profile and profile.units:
+            self.running[interface] = (profile_name, list(profile.units))
+            for unit in profile.units:
+                await self._apply("start", unit, profile_name, interface)
+
+    async def _deactivated(self, interface: str):
+        if interface not in self.running:
+            return
+        profile_name, units = self.running.pop(interface)
+        # Reverse order, so units that depend on earlier ones stop first
+        for unit in reversed(units):
+            await self._apply("stop", unit, profile_name, interface)
+
+    async def _apply(self, action: str, unit: str, profile: str, interface: str):
+        if action == "start":
+            ok, message = await self.backend.start_unit(unit)
+        else:
+            ok, message = await self.backend.stop_unit(unit)
+
+        if ok:
+            logger.info(f"{action} {unit} for {profile}: {message}", extra={"interface": interface})
+        else:
+            logger.error(f"Failed to {action} {unit} for {profile}: {message}", extra={"interface": interface})
+
+        self.events.publish("ProfileServiceChanged", unit=unit, action=action, ok=ok, message=message,
+                            profile=profile, interface=interface)
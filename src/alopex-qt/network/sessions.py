@@ -0,0 +1,107 @@
+"""
+Session Counters - Bytes moved since each connection came up
+Kernel counters run from when the device appeared; a session starts when the
+interface's profile is configured and ends when it goes down, or on request
+"""
+
+import time
+import logging
+from dataclasses import dataclass, asdict
+from typing import Dict, Iterable, List, Optional
+
+from .connection_manager import ConnectionManager
+from .discovery import NetworkInterface
+from .services import STOPPING_STATES
+from .state_machine import LinkState
+
+# Configure logging
+logger = logging.getLogger(__name__)
+
+@dataclass
+class Session:
+    """Counter baseline for one interface's current connection"""
+    interface: str
+    profile: Optional[str]
+    started_at: float
+    baseline_rx: int
+    baseline_tx: int
+    rx: int = 0
+    tx: int = 0
+
+class SessionCounters:
+    """Per-interface connection totals, kept by the daemon so every client agrees"""
+
+    def __init__(self, connections: ConnectionManager):
+        self.connections = connections
+        self.discovery = connections.discovery
+        self.events = connections.events
+        self.sessions: Dict[str, Session] = {}
+
+    def _counters(self, interface: str) -> Optional[NetworkInterface]:
+        return next((i for i in self.discovery.discover_interfaces() if i.name == interface), None)
+
+    def start(self, interface: str, profile: Optional[str]) -> Optional[Session]:
+        """Begin (or restart) a session at the interface's current counters"""
+        current = self._counters(interface)
+        if current is None:
+            self.sessions.pop(interface, None)
+            return None
+        session = Session(interface, profile, time.time(), current.metrics.bytes_rx, current.metrics.bytes_tx)
+        self.sessions[interface] = session
+        return session
+
+    def update(self, interfaces: Iterable[NetworkInterface]):
+        """Fill in each interface's session totals from fresh counters"""
+        for interface in interfaces:
+            session = self.sessions.get(interface.name)
+            if session is None:
+                continue
+            metrics = interface.metrics
+            if metrics.bytes_rx < session.baseline_rx or metrics.bytes_tx < session.baseline_tx:
+                # Counters went backwards (device re-created): carry the total over
+                session.baseline_rx = metrics.bytes_rx - session.rx
+                session.baseline_tx = metrics.bytes_tx - session.tx
+            session.rx = metrics.bytes_rx - session.baseline_rx
+            session.tx = metrics.bytes_tx - session.baseline_tx
+            metrics.total_session_rx = session.rx
+            metrics.total_session_tx = session.tx
+            metrics.session_started = session.started_at
+
+    def list(self) -> List[Dict]:
+        """Current sessions with up-to-date totals"""
+        self.update(self.discovery.discover_interfaces())
+        return [asdict(session) for session in self.sessions.values()]
+
+    def reset(self, interface: Optional[str] = None) -> List[str]:
+        """Restart the session of one interface, or of all; returns the interfaces reset"""
+        names = [interface] if interface else list(self.sessions)
+        reset = []
+        for name in names:
+            session = self.sessions.get(name)
+            if session and self.start(name, session.profile):
+                reset.append(name)
+        if reset:
+            logger.info(f"Session counters reset: {', '.join(reset)}")
+            self.events.publish("SessionCountersReset", interfaces=reset)
+        return reset
+
+    async def run(self):
+        """Start sessions for links already up, then follow state changes"""
+        subscription = self.events.subscribe(["InterfaceStateChanged"])
+        try:
+            for interface, state in list(self.connections.interface_states.items()):
+                if state.status == LinkState.CONFIGURED:
+                    self.start(interface, state.profile_name)
+
+            while True:
+                event = await subscription.get()
+                interface = event["interface"]
+                if event["state"] == LinkState.CONFIGURED.value:
+                    session = self.sessions.get(interface)
+                    # Re-acquiring an address (configured -> acquiring -> configured) keeps the session
+                    if session is None or session.profile != event["profile"]:
+                        self.start(interface, event["profile"])
+                elif event["state"] in STOPPING_STATES:
+                    self.sessions.pop(interface, None)
+        finally:
+            self.events.unsubscribe(subscription)
@@ -0,0 +1,153 @@
+"""
+Shaping - Root qdisc and TCP congestion control per interface
+Bufferbloat comes from deep queues in front of the slowest hop. A fair-queueing
+qdisc (fq, fq_codel) keeps the host's own queue short; cake with a bandwidth just
+under the link's rate moves the bottleneck queue into the host, where it can be
+managed. Congestion control goes on the interface's default routes (congctl), so
+other links keep the host-wide algorithm
+"""
+
+import re
+import json
+import asyncio
+import logging
+from typing import Dict, List, Optional
+
+from .sysctls import CONGESTION_CONTROL
+from .system_integration import communicate_or_terminate
+
+# Configure logging
+logger = logging.getLogger(__name__)
+
+QDISCS = ("fq", "fq_codel", "cake")
+# "95mbit", "1.5gbit", "800kbit"; a bare number is Mbit/s
+BANDWIDTH = re.compile(r"^(\d+(?:\.\d+)?)\s*(kbit|mbit|gbit)?$")
+UNITS = {"kbit": 1_000, "mbit": 1_000_000, "gbit": 1_000_000_000}
+
+def bandwidth_bits(text: str) -> int:
+    """Bits per second of a bandwidth as tc writes it; raises ValueError"""
+    match = BANDWIDTH.match(str(text).strip().lower())
+    if not match:
+        raise ValueError(f"Not a bandwidth: {text!r} (e.g. 95mbit, 1gbit)")
+    bits = int(float(match.group(1)) * UNITS[match.group(2) or "mbit"])
+    if bits <= 0:
+        raise ValueError("bandwidth must be above zero")
+    return bits
+
+def format_bandwidth(bits: int) -> str:
+    """The shortest tc spelling: 95mbit, 1500kbit, 1gbit"""
+    for unit in ("gbit", "mbit"):
+        if bits % UNITS[unit] == 0:
+            return f"{bits // UNITS[unit]}{unit}"
+    return f"{max(1, round(bits / 1000))}kbit"
+
+def check_qdisc(qdisc: Optional[str], bandwidth=None) -> Dict[str, Optional[str]]:
+    """{"qdisc", "bandwidth"} of a profile as tc takes them; raises ValueError naming what's wrong"""
+    if qdisc in (None, "", "none"):
+        if bandwidth not in (None, ""):
+            raise ValueError("bandwidth needs qdisc cake")
+        return {"qdisc": None, "bandwidth": None}
+    if qdisc not in QDISCS:
+        raise ValueError(f"Unknown qdisc {qdisc!r}; use {', '.join(QDISCS)} or none")
+    if bandwidth in (None, ""):
+        return {"qdisc": qdisc, "bandwidth": None}
+    if qdisc != "cake":
+        raise ValueError(f"bandwidth only applies to cake, not {qdisc}")
+    return {"qdisc": qdisc, "bandwidth": format_bandwidth(bandwidth_bits(bandwidth))}
+
+def check_congestion_control(name: Optional[str]) -> Optional[str]:
+    """A congestion control name (or None for the host's); raises ValueError"""
+    if name in (None, "", "none"):
+        return None
+    name = str(name).strip()
+    if not CONGESTION_CONTROL.match(name):
+        raise ValueError(f"congestion_control must be a name such as cubic or bbr, not {name!r}")
+    return name
+
+def qdisc_arguments(interface: str, qdisc: Optional[str], bandwidth: Optional[str] = None) -> List[str]:
+    """tc(8) arguments that put qdisc at the root; None deletes it, leaving the kernel's default"""
+    if qdisc is None:
+        return ["qdisc", "del", "dev", interface, "root"]
+    arguments = ["qdisc", "replace", "dev", interface, "root", qdisc]
+    if bandwidth:
+        arguments += ["bandwidth", bandwidth]
+    return arguments
+
+def parse_qdisc(text: str) -> Optional[Dict]:
+    """The root qdisc from `tc -j qdisc show dev X root`: {"kind", "bandwidth"}; cake reports bytes/s"""
+    for entry in json.loads(text or "[]"):
+        if not entry.get("root"):
+            continue
+        rate = (entry.get("options") or {}).get("bandwidth")
+        bandwidth = format_bandwidth(rate * 8) if isinstance(rate, int) and rate > 0 else None
+        return {"kind": entry.get("kind"), "bandwidth": bandwidth}
+    return None
+
+def parse_default_routes(text: str) -> List[Dict]:
+    """Default routes from `ip -j route show default dev X`: {"via", "metric", "protocol", "congctl"}"""
+    routes = []
+    for route in json.loads(text or "[]"):
+        congctl = None
+        for metric in route.get("metrics") or []:
+            if isinstance(metric, dict) and "congctl" in metric:
+                congctl = str(metric["congctl"]).split()[-1]
+        routes.append({"via": route.get("gateway"), "metric": route.get("metric"),
+                       "protocol": route.get("protocol"), "congctl": congctl})
+    return routes
+
+def route_arguments(interface: str, route: Dict, algorithm: Optional[str], family: str) -> List[str]:
+    """ip(8) arguments that set (or, with None, drop) congctl on one default route, keeping the rest of it"""
+    arguments = [family, "route", "change", "default"]
+    if route.get("via"):
+        arguments += ["via", route["via"]]
+    arguments += ["dev", interface]
+    if route.get("metric") is not None:
+        arguments += ["metric", str(route["metric"])]
+    if route.get("protocol"):
+        arguments += ["proto", route["protocol"]]
+    if algorithm:
+        arguments += ["congctl", algorithm]
+    return arguments
+
+async def _run(*command: str) -> tuple:
+    process = await asyncio.create_subprocess_exec(
+        *command, stdout=asyncio.subprocess.PIPE, stderr=asyncio.subprocess.PIPE
+    )
+    stdout, stderr = await communicate_or_terminate(process)
+    return process.returncode, stdout.decode(errors="replace"), stderr.decode(errors="replace").strip()
+
+async def _default_routes(interface: str) -> Dict[str, List[Dict]]:
+    routes = {}
+    for family in ("-4", "-6"):
+        code, text, _ = await _run("ip", "-j", family, "route", "show", "default", "dev", interface)
+        routes[family] = parse_default_routes(text) if code == 0 else []
+    return routes
+
+async def read_state(interface: str) -> Optional[Dict]:
+    """{"qdisc", "congestion_control"} in force on the interface; None without it"""
+    code, text, _ = await _run("tc", "-j", "qdisc", "show", "dev", interface, "root")
+    if code != 0:
+        return None
+    routes = [route for family in (await _default_routes(interface)).values() for route in family]
+    return {"qdisc": parse_qdisc(text),
+            "congestion_control": next((route["congctl"] for route in routes if route["congctl"]), None)}
+
+async def set_qdisc(interface: str, qdisc: Optional[str], bandwidth: Optional[str] = None) -> Optional[str]:
+    """Change the root qdisc; None when it worked, else tc's complaint"""
+    code, _, error = await _run("tc", *qdisc_arguments(interface, qdisc, bandwidth))
+    if code != 0 and qdisc is None and "No such file" in error:
+        # Already on the default: nothing alopex put there to delete
+        return None
+    return None if code == 0 else error or f"tc exited with {code}"
+
+async def set_congestion_control(interface: str, algorithm: Optional[str]) -> Optional[str]:
+    """Set congctl on each of the interface's default routes; None when all took it, else ip's complaint"""
+    errors = []
+    for family, routes in (await _default_routes(interface)).items():
+        for route in routes:
+            if route["congctl"] == algorithm:
+                continue
+            code, _, error = await _run("ip", *route_arguments(interface, route, algorithm, family))
+            if code != 0:
+                errors.append(error or f"ip exited with {code}")
+    return errors[0] if errors else None
@@ -0,0 +1,247 @@
+"""
+SLA Monitor - Loss, latency and jitter of chosen targets against thresholds
+Each probe sends a burst of echo requests (or TCP connects) every interval and
+keeps an hour of rounds; crossing a threshold publishes events and runs a script
+"""
+
+import os
+import time
+import asyncio
+import logging
+import statistics
+from collections import deque
+from dataclasses import dataclass, field
+from typing import Deque, Dict, List, Optional
+
+# Configure logging
+logger = logging.getLogger(__name__)
+
+DEFAULT_INTERVAL = 30.0
+MIN_INTERVAL = 0.5
+DEFAULT_COUNT = 5
+PACKET_INTERVAL = 0.2
+PACKET_TIMEOUT = 2.0
+HISTORY_SECONDS = 3600
+# Rounds in a row that must agree before the state flips
+DEFAULT_CONSECUTIVE = 2
+SCRIPT_TIMEOUT = 30.0
+
+METRICS = ("loss", "rtt", "jitter")
+METHODS = ("icmp", "tcp")
+
+def round_stats(rtts: List[Optional[float]]) -> Dict:
+    """Loss in percent, median round trip and jitter (mean change between consecutive replies) in ms"""
+    received = [rtt for rtt in rtts if rtt is not None]
+    loss = 100.0 * (len(rtts) - len(received)) / len(rtts) if rtts else 100.0
+    jitter = (statistics.fmean(abs(b - a) for a, b in zip(received, received[1:]))
+              if len(received) > 1 else None)
+    return {
+        "sent": len(rtts),
+        "received": len(received),
+        "loss": round(loss, 1),
+        "rtt": round(statistics.median(received), 2) if received else None,
+        "jitter": round(jitter, 2) if jitter is not None else None
+    }
+
+def summarize(rounds: List[Dict]) -> Dict:
+    """Loss over all packets, mean and worst round trip, mean jitter and breaching rounds"""
+    sent = sum(r["sent"] for r in rounds)
+    received = sum(r["received"] for r in rounds)
+    rtts = [r["rtt"] for r in rounds if r["rtt"] is not None]
+    jitters = [r["jitter"] for r in rounds if r["jitter"] is not None]
+    return {
+        "rounds": len(rounds),
+        "loss": round(100.0 * (sent - received) / sent, 1) if sent else None,
+        "rtt": round(statistics.fmean(rtts), 2) if rtts else None,
+        "rtt_max": max(rtts) if rtts else None,
+        "jitter": round(statistics.fmean(jitters), 2) if jitters else None,
+        "breaching_rounds": sum(1 for r in rounds if r["exceeded"])
+    }
+
+@dataclass
+class SlaProbe:
+    """One target and the limits it is held to"""
+    name: str
+    target: str
+    method: str = "icmp"
+    port: Optional[int] = None
+    interface: Optional[str] = None
+    interval: float = DEFAULT_INTERVAL
+    count: int = DEFAULT_COUNT
+    # Percent for loss, ms for rtt and jitter; None leaves the metric unchecked
+    thresholds: Dict[str, Optional[float]] = field(default_factory=dict)
+    consecutive: int = DEFAULT_CONSECUTIVE
+    script: Optional[str] = None
+
+    @classmethod
+    def from_config(cls, config: Dict) -> "SlaProbe":
+        """Probe from its config entry; raises ValueError for one that cannot run"""
+        try:
+            probe = cls(**config)
+        except TypeError as e:
+            raise ValueError(str(e))
+        if not probe.name or not probe.target:
+            raise ValueError("a probe needs a name and a target")
+        if probe.method not in METHODS:
+            raise ValueError(f"method must be one of {', '.join(METHODS)}")
+        if probe.method == "tcp" and not probe.port:
+            raise ValueError("tcp probes need a port")
+        unknown = set(probe.thresholds) - set(METRICS)
+        if unknown:
+            raise ValueError(f"unknown thresholds {', '.join(sorted(unknown))}")
+        probe.interval = max(MIN_INTERVAL, float(probe.interval))
+        probe.count = max(1, int(probe.count))
+        probe.consecutive = max(1, int(probe.consecutive))
+        return probe
+
+    def exceeded(self, stats: Dict) -> List[str]:
+        """Metrics of a round over their threshold; no replies at all counts against rtt and jitter too"""
+        over = []
+        for metric in METRICS:
+            limit = self.thresholds.get(metric)
+            if limit is None:
+                continue
+            value = stats[metric]
+            if (value is None and stats["received"] == 0) or (value is not None and value > limit):
+                over.append(metric)
+        return over
+
+class ProbeState:
+    """Rounds of the last hour and whether the probe is in breach"""
+
+    def __init__(self, probe: SlaProbe):
+        self.probe = probe
+        self.rounds: Deque[Dict] = deque(maxlen=max(1, int(HISTORY_SECONDS / probe.interval)))
+        self.state = "unknown"  # unknown, ok, breach
+        self.streak = 0
+        self.changed_at: Optional[float] = None
+
+    def to_dict(self, now: Optional[float] = None) -> Dict:
+        now = time.time() if now is None else now
+        hour = [r for r in self.rounds if r["timestamp"] >= now - HISTORY_SECONDS]
+        return {
+            "name": self.probe.name,
+            "target": self.probe.target,
+            "method": self.probe.method,
+            "port": self.probe.port,
+            "interface": self.probe.interface,
+            "interval": self.probe.interval,
+            "thresholds": self.probe.thresholds,
+            "state": self.state,
+            "changed_at": self.changed_at,
+            "last": self.rounds[-1] if self.rounds else None,
+            "hour": summarize(hour)
+        }
+
+class SlaMonitor:
+    """Runs every configured probe on its own interval"""
+
+    def __init__(self, host, probes: List[Dict], events=None):
+        self.host = host
+        self.events = events
+        self.probes: Dict[str, ProbeState] = {}
+        for config in probes:
+            try:
+                probe = SlaProbe.from_config(dict(config))
+            except ValueError as e:
+                logger.error(f"SLA probe {config.get('name')!r} skipped: {e}")
+                continue
+            self.probes[probe.name] = ProbeState(probe)
+
+    async def measure(self, probe: SlaProbe) -> List[Optional[float]]:
+        """Round trips of one burst"""
+        if probe.method == "icmp":
+            return await self.host.ping(probe.target, probe.count, PACKET_INTERVAL, PACKET_TIMEOUT,
+                                        probe.interface)
+        rtts = []
+        for index in range(probe.count):
+            if index:
+                await asyncio.sleep(PACKET_INTERVAL)
+            result = await self.host.probe_port(probe.target, probe.port, None, PACKET_TIMEOUT)
+            # A refusal is still an answer from the target
+            rtts.append(result["latency_ms"] if result["state"] in ("open", "closed") else None)
+        return rtts
+
+    async def run_round(self, name: str) -> Dict:
+        """Measure one probe once and update its state"""
+        state = self.probes[name]
+        probe = state.probe
+        stats = round_stats(await self.measure(probe))
+        stats["timestamp"] = time.time()
+        stats["exceeded"] = probe.exceeded(stats)
+        state.rounds.append(stats)
+
+        breaching = bool(stats["exceeded"])
+        if state.state == "unknown" and not breaching:
+            state.state, state.changed_at, state.streak = "ok", stats["timestamp"], 0
+            return stats
+        wanted = "breach" if breaching else "ok"
+        if state.state == wanted:
+            state.streak = 0
+            return stats
+        state.streak += 1
+        if state.streak >= probe.consecutive:
+            state.streak = 0
+            state.state, state.changed_at = wanted, stats["timestamp"]
+            await self._changed(state, stats)
+        return stats
+
+    async def _changed(self, state: ProbeState, stats: Dict):
+        probe = state.probe
+        metrics = {metric: stats[metric] for metric in METRICS}
+        if state.state == "breach":
+            logger.warning(f"SLA probe {probe.name} ({probe.target}) breached: {', '.join(stats['exceeded'])}")
+            kind = "SlaBreached"
+        else:
+            logger.info(f"SLA probe {probe.name} ({probe.target}) recovered")
+            kind = "SlaRecovered"
+        if self.events:
+            self.events.publish(kind, probe=probe.name, target=probe.target, exceeded=stats["exceeded"], **metrics)
+        if probe.script:
+            await self.run_script(probe, state.state, stats)
+
+    async def run_script(self, probe: SlaProbe, state: str, stats: Dict):
+        """Run the probe's hook with the state as argument and the numbers in the environment"""
+        env = dict(os.environ, ALOPEX_SLA_PROBE=probe.name, ALOPEX_SLA_TARGET=probe.target,
+                   ALOPEX_SLA_STATE=state, ALOPEX_SLA_EXCEEDED=",".join(stats["exceeded"]))
+        for metric in METRICS:
+            env[f"ALOPEX_SLA_{metric.upper()}"] = "" if stats[metric] is None else str(stats[metric])
+        try:
+            process = await asyncio.create_subprocess_exec(
+                probe.script, state, env=env,
+                stdout=asyncio.subprocess.DEVNULL, stderr=asyncio.subprocess.PIPE
+            )
+            _, stderr = await asyncio.wait_for(process.communicate(), SCRIPT_TIMEOUT)
+        except asyncio.TimeoutError:
+            process.kill()
+            logger.error(f"SLA script {probe.script} still running after {SCRIPT_TIMEOUT:g}s, killed")
+            return
+        except OSError as e:
+            logger.error(f"Cannot run SLA script {probe.script}: {e}")
+            return
+        if process.returncode != 0:
+            logger.error(f"SLA script {probe.script} exited with {process.returncode}: "
+                         f"{stderr.decode(errors='replace').strip()[:200]}")
+
+    def list(self) -> List[Dict]:
+        """State, last round and last-hour summary of every probe"""
+        now = time.time()
+        return [state.to_dict(now) for state in self.probes.values()]
+
+    def history(self, name: str) -> Optional[List[Dict]]:
+        """Rounds of the last hour of one probe, oldest first; None for an unknown probe"""
+        state = self.probes.get(name)
+        return list(state.rounds) if state else None
+
+    async def _run_probe(self, name: str):
+        interval = self.probes[name].probe.interval
+        while True:
+            started = time.monotonic()
+            try:
+                await self.run_round(name)
+            except Exception as e:
+                logger.error(f"SLA probe {name} failed: {e}")
+            await asyncio.sleep(max(0.0, interval - (time.monotonic() - started)))
+
+    async def run(self):
+        await asyncio.gather(*(self._run_probe(name) for name in self.probes))
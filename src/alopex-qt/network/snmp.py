@@ -0,0 +1,527 @@
+"""
+SNMP Agent - Read-only SNMPv2c/v3 view of the system group and IF-MIB
+ifTable and ifXTable (64-bit counters) for every interface, served over UDP to
+communities and USM users (MD5/SHA/SHA-256 auth, AES-128 privacy); sets are refused
+"""
+
+import os
+import hmac
+import json
+import time
+import bisect
+import socket
+import asyncio
+import hashlib
+import logging
+import platform
+from pathlib import Path
+from typing import Any, Callable, Dict, Iterable, List, Optional, Tuple
+
+from .discovery import InterfaceType, NetworkInterface
+
+# Configure logging
+logger = logging.getLogger(__name__)
+
+DEFAULT_PORT = 161
+# Counter snapshots are reused this long, so a walk sees one consistent set
+CACHE_SECONDS = 1.0
+MAX_MESSAGE = 65507
+MAX_BULK_VARBINDS = 200
+TIME_WINDOW = 150
+# Real messages nest about six deep; refuse more before recursion gets anywhere near Python's limit
+MAX_DEPTH = 32
+
+# BER tags
+INTEGER, OCTET_STRING, NULL, OBJECT_ID, SEQUENCE = 0x02, 0x04, 0x05, 0x06, 0x30
+IP_ADDRESS, COUNTER32, GAUGE32, TIMETICKS, COUNTER64 = 0x40, 0x41, 0x42, 0x43, 0x46
+NO_SUCH_OBJECT, NO_SUCH_INSTANCE, END_OF_MIB_VIEW = 0x80, 0x81, 0x82
+GET, GET_NEXT, RESPONSE, SET, GET_BULK, REPORT = 0xA0, 0xA1, 0xA2, 0xA3, 0xA5, 0xA8
+UNSIGNED = {COUNTER32, GAUGE32, TIMETICKS, COUNTER64}
+
+VERSION_2C, VERSION_3 = 1, 3
+USM = 3
+FLAG_AUTH, FLAG_PRIV, FLAG_REPORTABLE = 0x01, 0x02, 0x04
+TOO_BIG, NOT_WRITABLE = 1, 17
+
+# Name, HMAC digest and truncated length (RFC 3414, RFC 7860)
+AUTH_PROTOCOLS = {"md5": ("md5", 12), "sha": ("sha1", 12), "sha256": ("sha256", 24)}
+
+SYSTEM = (1, 3, 6, 1, 2, 1, 1)
+IF_NUMBER = (1, 3, 6, 1, 2, 1, 2, 1, 0)
+IF_ENTRY = (1, 3, 6, 1, 2, 1, 2, 2, 1)
+IFX_ENTRY = (1, 3, 6, 1, 2, 1, 31, 1, 1, 1)
+USM_STATS = (1, 3, 6, 1, 6, 3, 15, 1, 1)
+# Under the documentation enterprise number (RFC 5612)
+SYS_OBJECT_ID = (1, 3, 6, 1, 4, 1, 32473, 1)
+ENGINE_PREFIX = bytes([0x80, 0x00, 0x7E, 0xD9, 0x05])
+
+IF_TYPES = {
+    InterfaceType.ETHERNET: 6, InterfaceType.WIFI: 71, InterfaceType.CELLULAR: 244, InterfaceType.VPN: 131,
+    InterfaceType.TUN: 131, InterfaceType.BRIDGE: 209, InterfaceType.BOND: 161, InterfaceType.VLAN: 135,
+    InterfaceType.LOOPBACK: 24
+}
+
+Oid = Tuple[int, ...]
+Value = Tuple[int, Any]
+
+class SnmpError(Exception):
+    """Agent configuration that cannot be served"""
+
+def parse_oid(text: str) -> Oid:
+    return tuple(int(arc) for arc in text.strip(".").split("."))
+
+def encode_length(length: int) -> bytes:
+    if length < 0x80:
+        return bytes([length])
+    data = length.to_bytes((length.bit_length() + 7) // 8, "big")
+    return bytes([0x80 | len(data)]) + data
+
+def tlv(tag: int, content: bytes) -> bytes:
+    return bytes([tag]) + encode_length(len(content)) + content
+
+def encode_int(value: int, tag: int = INTEGER) -> bytes:
+    size = (value if value >= 0 else ~value).bit_length() // 8 + 1
+    return tlv(tag, value.to_bytes(size, "big", signed=True))
+
+def encode_octets(value: bytes) -> bytes:
+    return tlv(OCTET_STRING, value)
+
+def encode_oid(oid: Oid) -> bytes:
+    arcs = [oid[0] * 40 + oid[1]] + list(oid[2:])
+    content = b""
+    for arc in arcs:
+        chunk = [arc & 0x7F]
+        arc >>= 7
+        while arc:
+            chunk.insert(0, 0x80 | (arc & 0x7F))
+            arc >>= 7
+        content += bytes(chunk)
+    return tlv(OBJECT_ID, content)
+
+def encode_value(value: Value) -> bytes:
+    tag, data = value
+    if tag == INTEGER or tag in UNSIGNED:
+        return encode_int(data, tag)
+    if tag == OBJECT_ID:
+        return encode_oid(data)
+    if tag in (OCTET_STRING, IP_ADDRESS):
+        return tlv(tag, data)
+    return tlv(tag, b"")
+
+def encode_pdu(tag: int, request_id: int, error_status: int, error_index: int,
+               varbinds: List[Tuple[Oid, Value]]) -> bytes:
+    bindings = b"".join(tlv(SEQUENCE, encode_oid(oid) + encode_value(value)) for oid, value in varbinds)
+    return tlv(tag, encode_int(request_id) + encode_int(error_status) + encode_int(error_index) +
+               tlv(SEQUENCE, bindings))
+
+def parse(data: bytes, offset: int = 0, depth: int = 0) -> Tuple[int, Any, int, int]:
+    """(tag, value, value offset, end offset) of the TLV at offset; constructed values are lists of these"""
+    if depth > MAX_DEPTH:
+        raise ValueError("nested too deeply")
+    tag = data[offset]
+    length = data[offset + 1]
+    start = offset + 2
+    if length & 0x80:
+        size = length & 0x7F
+        if not 0 < size <= 4:
+            raise ValueError("unsupported length")
+        length = int.from_bytes(data[start:start + size], "big")
+        start += size
+    end = start + length
+    if end > len(data):
+        raise ValueError("truncated")
+    content = data[start:end]
+    if tag & 0x20:
+        children, position = [], start
+        while position < end:
+            child = parse(data, position, depth + 1)
+            children.append(child)
+            position = child[3]
+        return tag, children, start, end
+    if tag == INTEGER:
+        return tag, int.from_bytes(content, "big", signed=True), start, end
+    if tag in UNSIGNED:
+        return tag, int.from_bytes(content, "big"), start, end
+    if tag == OBJECT_ID:
+        arcs, arc = [], 0
+        for byte in content:
+            arc = (arc << 7) | (byte & 0x7F)
+            if not byte & 0x80:
+                arcs.append(arc)
+                arc = 0
+        if not arcs:
+            raise ValueError("empty OID")
+        first = min(arcs[0] // 40, 2)
+        return tag, (first, arcs[0] - first * 40) + tuple(arcs[1:]), start, end
+    if tag in (OCTET_STRING, IP_ADDRESS):
+        return tag, content, start, end
+    return tag, None, start, end
+
+def password_to_key(password: str, engine_id: bytes, auth: str) -> bytes:
+    """Localized key from a password (RFC 3414 A.2): a megabyte of the password hashed, then with the engine ID"""
+    algorithm = AUTH_PROTOCOLS[auth][0]
+    data = password.encode()
+    if len(data) < 8:
+        raise SnmpError("USM passwords need at least 8 characters")
+    key = hashlib.new(algorithm, (data * (1048576 // len(data) + 1))[:1048576]).digest()
+    return hashlib.new(algorithm, key + engine_id + key).digest()
+
+def _aes(key: bytes, iv: bytes, data: bytes, encrypt: bool) -> bytes:
+    """AES-128 in 128-bit CFB mode (RFC 3826)"""
+    from cryptography.hazmat.primitives.ciphers import Cipher, algorithms
+    try:
+        from cryptography.hazmat.decrepit.ciphers.modes import CFB
+    except ImportError:
+        # Older cryptography releases
+        from cryptography.hazmat.primitives.ciphers.modes import CFB
+    cipher = Cipher(algorithms.AES(key[:16]), CFB(iv))
+    context = cipher.encryptor() if encrypt else cipher.decryptor()
+    return context.update(data) + context.finalize()
+
+def load_engine(path: Path) -> Tuple[bytes, int]:
+    """Engine ID and boot count, the count raised and both saved for the next start"""
+    state = {}
+    try:
+        state = json.loads(path.read_text())
+    except (OSError, ValueError):
+        pass
+    engine_id = bytes.fromhex(state.get("engine_id", "")) or ENGINE_PREFIX + os.urandom(8)
+    boots = int(state.get("boots", 0)) + 1
+    try:
+        path.parent.mkdir(parents=True, exist_ok=True)
+        path.write_text(json.dumps({"engine_id": engine_id.hex(), "boots": boots}))
+    except OSError as e:
+        logger.error(f"Failed to save SNMP engine state: {e}")
+    return engine_id, boots
+
+class InterfaceMib:
+    """System group and IF-MIB rows built from discovered interfaces"""
+
+    def __init__(self, interfaces: Callable[[], Iterable[NetworkInterface]], started_at: float,
+                 contact: str = "", location: str = "", kernel_indexes: bool = True):
+        self.interfaces = interfaces
+        self.started_at = started_at
+        self.contact = contact
+        self.location = location
+        self.kernel_indexes = kernel_indexes
+        self.indexes: Dict[str, int] = {}
+        self.oids: List[Oid] = []
+        self.values: List[Value] = []
+        self.taken = 0.0
+
+    def index(self, name: str) -> int:
+        """ifIndex of name: the kernel's where there is one, otherwise a number of our own that sticks"""
+        if name not in self.indexes:
+            index = None
+            if self.kernel_indexes:
+                try:
+                    index = socket.if_nametoindex(name)
+                except OSError:
+                    pass
+            if index is None or index in self.indexes.values():
+                index = max(self.indexes.values(), default=0) + 1
+            self.indexes[name] = index
+        return self.indexes[name]
+
+    def _rows(self) -> Dict[Oid, Value]:
+        ticks = int((time.time() - self.started_at) * 100) % 2 ** 32
+        uname = platform.uname()
+        rows = {
+            SYSTEM + (1, 0): (OCTET_STRING, f"alopexd on {uname.system} {uname.release} {uname.machine}".encode()),
+            SYSTEM + (2, 0): (OBJECT_ID, SYS_OBJECT_ID),
+            SYSTEM + (3, 0): (TIMETICKS, ticks),
+            SYSTEM + (4, 0): (OCTET_STRING, self.contact.encode()),
+            SYSTEM + (5, 0): (OCTET_STRING, socket.gethostname().encode()),
+            SYSTEM + (6, 0): (OCTET_STRING, self.location.encode()),
+            # Internet and end-to-end layers
+            SYSTEM + (7, 0): (INTEGER, 72)
+        }
+        interfaces = list(self.interfaces())
+        rows[IF_NUMBER] = (INTEGER, len(interfaces))
+        for interface in interfaces:
+            index = self.index(interface.name)
+            metrics = interface.metrics
+            speed = (metrics.link_speed or 0) * 1_000_000
+            up = interface.status == "Connected"
+            mac = bytes.fromhex(interface.mac.replace(":", "")) if interface.mac else b""
+            columns = {
+                IF_ENTRY + (1,): (INTEGER, index),
+                IF_ENTRY + (2,): (OCTET_STRING, interface.name.encode()),
+                IF_ENTRY + (3,): (INTEGER, IF_TYPES.get(interface.interface_type, 1)),
+                IF_ENTRY + (4,): (INTEGER, metrics.mtu or 0),
+                IF_ENTRY + (5,): (GAUGE32, min(speed, 2 ** 32 - 1)),
+                IF_ENTRY + (6,): (OCTET_STRING, mac),
+                # Administratively down only when a kill switch holds the radio
+                IF_ENTRY + (7,): (INTEGER, 2 if interface.rfkill else 1),
+                IF_ENTRY + (8,): (INTEGER, 1 if up else 2),
+                IF_ENTRY + (10,): (COUNTER32, metrics.bytes_rx % 2 ** 32),
+                IF_ENTRY + (11,): (COUNTER32, metrics.packets_rx % 2 ** 32),
+                IF_ENTRY + (13,): (COUNTER32, metrics.dropped_rx % 2 ** 32),
+                IF_ENTRY + (14,): (COUNTER32, metrics.errors_rx % 2 ** 32),
+                IF_ENTRY + (16,): (COUNTER32, metrics.bytes_tx % 2 ** 32),
+                IF_ENTRY + (17,): (COUNTER32, metrics.packets_tx % 2 ** 32),
+                IF_ENTRY + (19,): (COUNTER32, metrics.dropped_tx % 2 ** 32),
+                IF_ENTRY + (20,): (COUNTER32, metrics.errors_tx % 2 ** 32),
+                IFX_ENTRY + (1,): (OCTET_STRING, interface.name.encode()),
+                IFX_ENTRY + (6,): (COUNTER64, metrics.bytes_rx),
+                IFX_ENTRY + (7,): (COUNTER64, metrics.packets_rx),
+                IFX_ENTRY + (10,): (COUNTER64, metrics.bytes_tx),
+                IFX_ENTRY + (11,): (COUNTER64, metrics.packets_tx),
+                IFX_ENTRY + (15,): (GAUGE32, metrics.link_speed or 0)
+            }
+            for column, value in columns.items():
+                rows[column + (index,)] = value
+        return rows
+
+    def refresh(self):
+        if time.monotonic() - self.taken < CACHE_SECONDS:
+            return
+        rows = self._rows()
+        self.oids = sorted(rows)
+        self.values = [rows[oid] for oid in self.oids]
+        self.taken = time.monotonic()
+
+    def get(self, oid: Oid) -> Value:
+        self.refresh()
+        position = bisect.bisect_left(self.oids, oid)
+        if position < len(self.oids) and self.oids[position] == oid:
+            return self.values[position]
+        # The object exists when some instance of it does
+        known = any(candidate[:len(oid) - 1] == oid[:-1] for candidate in self.oids[position - 1:position + 1])
+        return (NO_SUCH_INSTANCE if known else NO_SUCH_OBJECT), None
+
+    def get_next(self, oid: Oid) -> Tuple[Oid, Value]:
+        self.refresh()
+        position = bisect.bisect_right(self.oids, oid)
+        if position < len(self.oids):
+            return self.oids[position], self.values[position]
+        return oid, (END_OF_MIB_VIEW, None)
+
+class _UdpListener(asyncio.DatagramProtocol):
+    def __init__(self, agent: "SnmpAgent"):
+        self.agent = agent
+        self.transport = None
+
+    def connection_made(self, transport):
+        self.transport = transport
+
+    def datagram_received(self, data: bytes, addr):
+        response = self.agent.handle(data)
+        if response and self.transport and not self.transport.is_closing():
+            self.transport.sendto(response, addr)
+
+class SnmpAgent:
+    """Answers Get, GetNext and GetBulk from the MIB; Set always fails with notWritable"""
+
+    def __init__(self, mib: InterfaceMib, communities: Iterable[str] = (), users: Iterable[Dict] = (),
+                 engine_id: bytes = b"", boots: int = 1):
+        self.mib = mib
+        self.communities = {community.encode() for community in communities}
+        self.engine_id = engine_id or ENGINE_PREFIX + os.urandom(8)
+        self.boots = boots
+        self.started = time.monotonic()
+        self.salt = int.from_bytes(os.urandom(8), "big")
+        self.users: Dict[bytes, Dict] = {}
+        for user in users:
+            try:
+                self.add_user(**user)
+            except (SnmpError, KeyError, TypeError) as e:
+                logger.error(f"SNMP user {user.get('name')!r} skipped: {e}")
+        self.transport = None
+        self.counters = {"requests": 0, "bad_community": 0, "bad_version": 0, "parse_errors": 0}
+        self.usm_stats = {1: 0, 2: 0, 3: 0, 4: 0, 5: 0, 6: 0}
+
+    def add_user(self, name: str, auth: Optional[str] = None, auth_password: Optional[str] = None,
+                 priv: Optional[str] = None, priv_password: Optional[str] = None):
+        """A USM user; auth is md5, sha or sha256, priv is aes (needs auth and the cryptography package)"""
+        user = {"auth": None, "auth_key": None, "priv_key": None}
+        if auth:
+            if auth not in AUTH_PROTOCOLS:
+                raise SnmpError(f"unknown auth protocol {auth!r}")
+            user["auth"] = auth
+            user["auth_key"] = password_to_key(auth_password or "", self.engine_id, auth)
+        if priv:
+            if priv != "aes" or not auth:
+                raise SnmpError("privacy is AES and needs auth")
+            try:
+                import cryptography  # noqa: F401
+            except ImportError:
+                raise SnmpError("AES privacy needs the Python 'cryptography' package")
+            user["priv_key"] = password_to_key(priv_password or "", self.engine_id, auth)
+        self.users[name.encode()] = user
+
+    def engine_time(self) -> int:
+        return int(time.monotonic() - self.started)
+
+    async def start(self, address: str, port: int = DEFAULT_PORT) -> bool:
+        """Listen on address; False (and logged) when the port cannot be bound"""
+        try:
+            self.transport, _ = await asyncio.get_running_loop().create_datagram_endpoint(
+                lambda: _UdpListener(self), local_addr=(address, port)
+            )
+        except OSError as e:
+            logger.error(f"SNMP agent cannot listen on {address}:{port}: {e}")
+            return False
+        logger.info(f"SNMP agent listening on {address}:{port}")
+        return True
+
+    def stop(self):
+        if self.transport:
+            self.transport.close()
+            self.transport = None
+
+    def stats(self) -> Dict:
+        """Listening address, access and request counters"""
+        address = self.transport.get_extra_info("sockname") if self.transport else None
+        return dict(self.counters, enabled=True, listening=f"{address[0]}:{address[1]}" if address else None,
+                    engine_id=self.engine_id.hex(), engine_boots=self.boots, communities=len(self.communities),
+                    users=sorted(name.decode() for name in self.users),
+                    usm_errors=sum(self.usm_stats.values()))
+
+    def handle(self, data: bytes) -> Optional[bytes]:
+        """The response to one request datagram, or None when it gets none"""
+        self.counters["requests"] += 1
+        try:
+            tag, message, _, _ = parse(data)
+            if tag != SEQUENCE:
+                raise ValueError("not a message")
+            version = message[0][1]
+            if version == VERSION_2C:
+                return self._handle_v2c(message)
+            if version == VERSION_3:
+                return self._handle_v3(data, message)
+            self.counters["bad_version"] += 1
+        except (ValueError, IndexError, TypeError) as e:
+            logger.debug(f"Unparseable SNMP message: {e}")
+            self.counters["parse_errors"] += 1
+        return None
+
+    def _handle_v2c(self, message: List) -> Optional[bytes]:
+        community = message[1][1]
+        if community not in self.communities:
+            # Wrong communities get silence, as on any agent
+            self.counters["bad_community"] += 1
+            return None
+        pdu = self._respond(message[2], MAX_MESSAGE)
+        if pdu is None:
+            return None
+        return tlv(SEQUENCE, encode_int(VERSION_2C) + encode_octets(community) + pdu)
+
+    def _respond(self, pdu: Tuple, max_size: int) -> Optional[bytes]:
+        """Response PDU for a request PDU"""
+        tag, fields, _, _ = pdu
+        request_id = fields[0][1]
+        requested = [binding[1][0][1] for binding in fields[3][1]]
+        if tag == GET:
+            varbinds = [(oid, self.mib.get(oid)) for oid in requested]
+        elif tag == GET_NEXT:
+            varbinds = [self.mib.get_next(oid) for oid in requested]
+        elif tag == GET_BULK:
+            varbinds = self._bulk(requested, max(0, fields[1][1]), max(0, fields[2][1]), max_size)
+        elif tag == SET:
+            varbinds = [(binding[1][0][1], (NULL, None)) for binding in fields[3][1]]
+            return encode_pdu(RESPONSE, request_id, NOT_WRITABLE, 1, varbinds)
+        else:
+            return None
+        response = encode_pdu(RESPONSE, request_id, 0, 0, varbinds)
+        if len(response) > max_size:
+            return encode_pdu(RESPONSE, request_id, TOO_BIG, 0, [])
+        return response
+
+    def _bulk(self, requested: List[Oid], non_repeaters: int, repetitions: int, max_size: int) -> List:
+        varbinds = [self.mib.get_next(oid) for oid in requested[:non_repeaters]]
+        repeating = requested[non_repeaters:]
+        size = sum(len(encode_oid(oid)) + len(encode_value(value)) + 4 for oid, value in varbinds)
+        for _ in range(repetitions):
+            row = [self.mib.get_next(oid) for oid in repeating]
+            size += sum(len(encode_oid(oid)) + len(encode_value(value)) + 4 for oid, value in row)
+            # Leave room for the message around the bindings
+            if not row or size > max_size - 200 or len(varbinds) + len(row) > MAX_BULK_VARBINDS:
+                break
+            varbinds.extend(row)
+            repeating = [oid for oid, _ in row]
+            if all(value[0] == END_OF_MIB_VIEW for _, value in row):
+                break
+        return varbinds
+
+    def _handle_v3(self, data: bytes, message: List) -> Optional[bytes]:
+        header = message[1][1]
+        msg_id, max_size, flags, model = header[0][1], header[1][1], header[2][1][0], header[3][1]
+        if model != USM:
+            self.counters["bad_version"] += 1
+            return None
+        security, security_offset = message[2][1], message[2][2]
+        fields = parse(security)[1]
+        engine_id, boots, engine_time, name, auth_params, priv_params = (field[1] for field in fields)
+        reportable = bool(flags & FLAG_REPORTABLE)
+        context = (msg_id, max_size, name, reportable)
+
+        def plain_request_id() -> int:
+            try:
+                return message[3][1][2][1][0][1] if message[3][0] == SEQUENCE else 0
+            except (IndexError, TypeError):
+                return 0
+
+        if engine_id != self.engine_id:
+            return self._report(context, 4, plain_request_id())
+        user = self.users.get(name)
+        if user is None:
+            return self._report(context, 3, plain_request_id())
+        level = flags & (FLAG_AUTH | FLAG_PRIV)
+        wanted = (FLAG_AUTH if user["auth"] else 0) | (FLAG_PRIV if user["priv_key"] else 0)
+        if level != wanted:
+            return self._report(context, 1, plain_request_id())
+        if level & FLAG_AUTH:
+            algorithm, length = AUTH_PROTOCOLS[user["auth"]]
+            position = security_offset + fields[4][2]
+            blanked = data[:position] + b"\x00" * len(auth_params) + data[position + len(auth_params):]
+            digest = hmac.new(user["auth_key"], blanked, algorithm).digest()[:length]
+            if len(auth_params) != length or not hmac.compare_digest(digest, auth_params):
+                return self._report(context, 5, 0)
+            if boots != self.boots or abs(engine_time - self.engine_time()) > TIME_WINDOW:
+                return self._report(context, 2, 0, user)
+        scoped = message[3]
+        if level & FLAG_PRIV:
+            try:
+                iv = boots.to_bytes(4, "big") + engine_time.to_bytes(4, "big") + priv_params
+                decrypted = _aes(user["priv_key"], iv, scoped[1], encrypt=False)
+                scoped = parse(decrypted)
+            except (ValueError, IndexError, TypeError, OverflowError):
+                return self._report(context, 6, 0)
+        pdu = self._respond(scoped[1][2], min(max_size, MAX_MESSAGE) - 100)
+        if pdu is None:
+            return None
+        return self._encode_v3(msg_id, max_size, level, name, pdu, user)
+
+    def _report(self, context: Tuple, stat: int, request_id: int, user: Optional[Dict] = None) -> Optional[bytes]:
+        """usmStats report; authenticated only for time window reports, so the sender can resync"""
+        self.usm_stats[stat] += 1
+        msg_id, max_size, name, reportable = context
+        if not reportable:
+            return None
+        pdu = encode_pdu(REPORT, request_id, 0, 0, [(USM_STATS + (stat, 0), (COUNTER32, self.usm_stats[stat]))])
+        return self._encode_v3(msg_id, max_size, FLAG_AUTH if user else 0, name if user else b"", pdu, user)
+
+    def _encode_v3(self, msg_id: int, max_size: int, level: int, name: bytes, pdu: bytes,
+                   user: Optional[Dict]) -> bytes:
+        scoped = tlv(SEQUENCE, encode_octets(self.engine_id) + encode_octets(b"") + pdu)
+        engine_time = self.engine_time()
+        priv_params = b""
+        if level & FLAG_PRIV:
+            self.salt = (self.salt + 1) % 2 ** 64
+            priv_params = self.salt.to_bytes(8, "big")
+            iv = self.boots.to_bytes(4, "big") + engine_time.to_bytes(4, "big") + priv_params
+            scoped = encode_octets(_aes(user["priv_key"], iv, scoped, encrypt=True))
+        auth_length = AUTH_PROTOCOLS[user["auth"]][1] if level & FLAG_AUTH else 0
+        security = tlv(SEQUENCE, encode_octets(self.engine_id) + encode_int(self.boots) + encode_int(engine_time) +
+                       encode_octets(name) + encode_octets(b"\x00" * auth_length) + encode_octets(priv_params))
+        header = tlv(SEQUENCE, encode_int(msg_id) + encode_int(MAX_MESSAGE) + encode_octets(bytes([level])) +
+                     encode_int(USM))
+        wrapped = encode_octets(security)
+        message = tlv(SEQUENCE, encode_int(VERSION_3) + header + wrapped + scoped)
+        if not auth_length:
+            return message
+        # The digest covers the whole message with its own field zeroed
+        end = message.index(wrapped) + len(wrapped) - len(encode_octets(priv_params))
+        digest = hmac.new(user["auth_key"], message, AUTH_PROTOCOLS[user["auth"]][0]).digest()[:auth_length]
+        return message[:end - auth_length] + digest + message[end:]
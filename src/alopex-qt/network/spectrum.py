@@ -0,0 +1,92 @@
+"""
+Spectrum - How crowded each WiFi channel is, from scan results
+Scores every channel by the networks overlapping it, weighted by how loud they
+are, and picks the quietest non-overlapping channel for a hotspot
+"""
+
+from typing import Dict, Iterable, List, Optional
+
+BAND_24 = "2.4 GHz"
+BAND_5 = "5 GHz"
+
+# Channels plotted per band; 14 is Japan-only and left out
+CHANNELS = {
+    BAND_24: list(range(1, 14)),
+    BAND_5: [36, 40, 44, 48, 52, 56, 60, 64, 100, 104, 108, 112, 116, 120, 124, 128,
+             132, 136, 140, 144, 149, 153, 157, 161, 165],
+}
+
+# Where a hotspot can go without overlapping anything else or needing radar detection (DFS)
+HOTSPOT_CHANNELS = {
+    BAND_24: [1, 6, 11],
+    BAND_5: [36, 40, 44, 48, 149, 153, 157, 161, 165],
+}
+
+# A 20 MHz 2.4 GHz signal spills four 5 MHz-spaced channels to each side
+SPREAD_24 = 4
+
+def band_of(channel: Optional[int]) -> Optional[str]:
+    if channel is None:
+        return None
+    return BAND_24 if channel <= 14 else BAND_5
+
+def overlap(channel: int, other: int) -> float:
+    """How much of a network on other lands on channel, 0 to 1"""
+    if band_of(channel) != band_of(other):
+        return 0.0
+    if band_of(channel) == BAND_5:
+        # Scans don't report width, so 5 GHz networks are taken as 20 MHz
+        return 1.0 if channel == other else 0.0
+    distance = abs(channel - other)
+    return max(0.0, 1 - distance / (SPREAD_24 + 1))
+
+def channel_load(networks: Iterable[Dict], band: str) -> Dict[int, float]:
+    """Congestion per channel: the overlap-weighted sum of each network's quality (0-1)"""
+    load = {channel: 0.0 for channel in CHANNELS[band]}
+    for network in networks:
+        channel = network.get("channel")
+        if band_of(channel) != band:
+            continue
+        weight = (network.get("quality_percent") or 0) / 100
+        for candidate in load:
+            load[candidate] += weight * overlap(candidate, channel)
+    return {channel: round(score, 2) for channel, score in load.items()}
+
+def suggest_channel(networks: Iterable[Dict], band: str) -> int:
+    """The least congested hotspot channel in band (lowest number on a tie)"""
+    load = channel_load(networks, band)
+    return min(HOTSPOT_CHANNELS[band], key=lambda channel: (load[channel], channel))
+
+def by_channel(networks: Iterable[Dict], band: str) -> Dict[int, List[Dict]]:
+    """Networks in band grouped by channel, loudest first"""
+    grouped: Dict[int, List[Dict]] = {}
+    for network in sorted(networks, key=lambda n: n.get("signal_strength") or -100, reverse=True):
+        if band_of(network.get("channel")) == band:
+            grouped.setdefault(network["channel"], []).append(network)
+    return grouped
+
+def channel_ranges(selected: Iterable[int], order: List[int]) -> str:
+    """Runs of selected channels along order (one band's channel list), e.g. "36-64, 100-144" """
+    chosen = set(selected)
+    runs: List[List[int]] = [[]]
+    for channel in order:
+        if channel in chosen:
+            runs[-1].append(channel)
+        elif runs[-1]:
+            runs.append([])
+    return ", ".join(f"{run[0]}-{run[-1]}" if len(run) > 1 else str(run[0]) for run in runs if run)
+
+def regulatory_summary(channels: List[Dict]) -> List[Dict]:
+    """Per band, the channels a radio may transmit on, only listen on, and must watch for radar on"""
+    summary = []
+    for band in dict.fromkeys(c["band"] for c in channels):
+        in_band = [c for c in channels if c["band"] == band]
+        order = [c["channel"] for c in in_band]
+        enabled = [c for c in in_band if not c["disabled"]]
+        summary.append({
+            "band": band,
+            "usable": channel_ranges((c["channel"] for c in enabled if not c["no_ir"]), order),
+            "listen_only": channel_ranges((c["channel"] for c in enabled if c["no_ir"]), order),
+            "radar": channel_ranges((c["channel"] for c in enabled if c["radar"]), order)
+        })
+    return summary
@@ -0,0 +1,53 @@
+"""
+Link State Machine - Guarded connection states per interface
+Every change goes through transition() so concurrent requests cannot leave
+an interface half-connected, and every change is reported as an event
+"""
+
+from enum import Enum
+from typing import Dict, FrozenSet
+
+class LinkState(str, Enum):
+    """Connection state of one managed interface"""
+    DISCONNECTED = "disconnected"
+    ACQUIRING = "acquiring"        # Associating, negotiating or waiting for DHCP
+    CONFIGURED = "configured"      # Addressed and usable
+    DEACTIVATING = "deactivating"  # Being taken down
+    FAILED = "failed"              # Last attempt failed or the link was lost
+
+TRANSITIONS: Dict[LinkState, FrozenSet[LinkState]] = {
+    LinkState.DISCONNECTED: frozenset({LinkState.ACQUIRING, LinkState.CONFIGURED}),
+    LinkState.ACQUIRING: frozenset({LinkState.CONFIGURED, LinkState.FAILED, LinkState.DISCONNECTED}),
+    LinkState.CONFIGURED: frozenset({LinkState.ACQUIRING, LinkState.DEACTIVATING,
+                                     LinkState.FAILED, LinkState.DISCONNECTED}),
+    LinkState.DEACTIVATING: frozenset({LinkState.DISCONNECTED, LinkState.FAILED}),
+    LinkState.FAILED: frozenset({LinkState.ACQUIRING, LinkState.DEACTIVATING, LinkState.DISCONNECTED})
+}
+
+# Status strings written by daemons that predate the state machine
+LEGACY_STATES = {
+    "connecting": LinkState.ACQUIRING,
+    "connected": LinkState.CONFIGURED
+}
+
+class StateTransitionError(Exception):
+    """Requested change is not allowed from the interface's current state"""
+
+    def __init__(self, interface: str, current: LinkState, target: LinkState):
+        super().__init__(f"{interface} is {current.value}, cannot become {target.value}")
+        self.interface = interface
+        self.current = current
+        self.target = target
+
+def parse_state(value: str) -> LinkState:
+    """Read a persisted status, upgrading legacy values"""
+    if value in LEGACY_STATES:
+        return LEGACY_STATES[value]
+    try:
+        return LinkState(value)
+    except ValueError:
+        return LinkState.DISCONNECTED
+
+def can_transition(current: LinkState, target: LinkState) -> bool:
+    """Check a transition against the table"""
+    return target in TRANSITIONS[current]
@@ -0,0 +1,77 @@
+"""
+Status Line - One-line connection summary for desktop bars
+Builds waybar custom-module JSON (text, tooltip, class, alt, percentage) from
+discovered interfaces plus what the daemon knows about profiles and metering
+"""
+
+from typing import Dict, List, Optional
+
+from .discovery import InterfaceType, NetworkInterface
+from .units import UnitPreference
+
+# Interfaces a bar should never pick as "the" connection
+IGNORED_TYPES = (InterfaceType.LOOPBACK, InterfaceType.BRIDGE, InterfaceType.OTHER)
+
+def signal_percent(dbm: Optional[int]) -> Optional[int]:
+    """Quality the way WifiNetwork reports it: -100 dBm is 0%, -50 dBm and up 100%"""
+    if dbm is None:
+        return None
+    return max(0, min(100, (dbm + 100) * 2))
+
+def primary_interface(interfaces: List[NetworkInterface]) -> Optional[NetworkInterface]:
+    """The connected interface carrying the default route, or failing that any connected one"""
+    connected = [i for i in interfaces if i.status == "Connected" and i.interface_type not in IGNORED_TYPES]
+    with_gateway = [i for i in connected if i.gateway]
+    candidates = with_gateway or connected
+    # VPNs route everything when up; otherwise prefer wired over wireless
+    order = {InterfaceType.VPN: 0, InterfaceType.TUN: 0, InterfaceType.ETHERNET: 1, InterfaceType.WIFI: 2}
+    return min(candidates, key=lambda i: (order.get(i.interface_type, 3), i.name), default=None)
+
+def build_status(interfaces: List[NetworkInterface], units: UnitPreference, profiles: Dict[str, str],
+                 ssid: Optional[str] = None, signal_dbm: Optional[int] = None, metered: bool = False,
+                 daemon: bool = True, airplane: bool = False) -> Dict:
+    """waybar custom-module object for the current state"""
+    primary = primary_interface(interfaces)
+    if primary is None and airplane:
+        return {"text": "✈ airplane", "tooltip": "Airplane mode: all radios off",
+                "class": ["disconnected", "airplane"], "alt": "airplane"}
+    if primary is None:
+        tooltip = "No connection" + ("" if daemon else "\nalopexd not running")
+        return {"text": "offline", "tooltip": tooltip, "class": "disconnected", "alt": "disconnected"}
+
+    down = units.format_rate(primary.metrics.speed_down * 1024)
+    up = units.format_rate(primary.metrics.speed_up * 1024)
+    percent = signal_percent(signal_dbm) if primary.interface_type == InterfaceType.WIFI else None
+
+    label = ssid if primary.interface_type == InterfaceType.WIFI and ssid else primary.name
+    text = f"{label}" + (f" {percent}%" if percent is not None else "") + f" ↓{down} ↑{up}"
+    if airplane:
+        # Wired (or a radio switched back on by hand) still works in airplane mode
+        text = "✈ " + text
+
+    tooltip = [f"{primary.name} ({primary.interface_type})"]
+    if profiles.get(primary.name):
+        tooltip.append(f"Profile: {profiles[primary.name]}")
+    if ssid and primary.interface_type == InterfaceType.WIFI:
+        tooltip.append(f"SSID: {ssid}" + (f" ({signal_dbm} dBm)" if signal_dbm is not None else ""))
+    tooltip.append(f"Address: {primary.ip or 'none'}")
+    tooltip.append(f"Down {down}, up {up}")
+    if metered:
+        tooltip.append("Metered")
+    if airplane:
+        tooltip.append("Airplane mode: radios off")
+    if not daemon:
+        tooltip.append("alopexd not running")
+
+    classes = ["connected", "wifi" if primary.interface_type == InterfaceType.WIFI else "wired"]
+    if primary.interface_type in (InterfaceType.VPN, InterfaceType.TUN):
+        classes[1] = "vpn"
+    if metered:
+        classes.append("metered")
+    if airplane:
+        classes.append("airplane")
+
+    status = {"text": text, "tooltip": "\n".join(tooltip), "class": classes, "alt": classes[1]}
+    if percent is not None:
+        status["percentage"] = percent
+    return status
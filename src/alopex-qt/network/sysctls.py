@@ -0,0 +1,90 @@
+"""
+Sysctls - Network sysctls a profile sets while it is active
+A profile names its settings by short name (rp_filter, forwarding,
+tcp_congestion_control...); per-interface ones go to the profile's interface.
+Each is written on activation with the value it had before remembered, and put
+back on deactivation unless something else changed it in the meantime
+"""
+
+import re
+import logging
+from pathlib import Path
+from typing import Dict, List, Optional
+
+# Configure logging
+logger = logging.getLogger(__name__)
+
+PROC_SYS = Path("/proc/sys")
+
+# Short name -> (key with {interface} for per-interface settings, values the kernel takes; None checks by pattern)
+SETTINGS = {
+    "rp_filter": ("net.ipv4.conf.{interface}.rp_filter", ("0", "1", "2")),
+    "forwarding": ("net.ipv4.conf.{interface}.forwarding", ("0", "1")),
+    "ipv6_forwarding": ("net.ipv6.conf.{interface}.forwarding", ("0", "1")),
+    "accept_ra": ("net.ipv6.conf.{interface}.accept_ra", ("0", "1", "2")),
+    "disable_ipv6": ("net.ipv6.conf.{interface}.disable_ipv6", ("0", "1")),
+    "arp_ignore": ("net.ipv4.conf.{interface}.arp_ignore", ("0", "1", "2", "3", "4", "5", "8")),
+    "arp_announce": ("net.ipv4.conf.{interface}.arp_announce", ("0", "1", "2")),
+    "tcp_congestion_control": ("net.ipv4.tcp_congestion_control", None),
+    "tcp_mtu_probing": ("net.ipv4.tcp_mtu_probing", ("0", "1", "2")),
+    "tcp_ecn": ("net.ipv4.tcp_ecn", ("0", "1", "2"))
+}
+# TCP_CA_NAME_MAX is 16 with the NUL; the kernel loads tcp_<name> on first use
+CONGESTION_CONTROL = re.compile(r"^[a-z0-9_]{1,15}$")
+
+def check(sysctls: Optional[Dict]) -> Dict[str, str]:
+    """A profile's sysctls with values as the kernel writes them; raises ValueError naming the bad one"""
+    checked = {}
+    for name, value in (sysctls or {}).items():
+        if name not in SETTINGS:
+            raise ValueError(f"Unknown sysctl {name!r}; supported: {', '.join(SETTINGS)}")
+        if isinstance(value, bool):
+            value = int(value)
+        value = str(value).strip()
+        allowed = SETTINGS[name][1]
+        if allowed is None and not CONGESTION_CONTROL.match(value):
+            raise ValueError(f"{name} must be a congestion control name such as cubic or bbr, not {value!r}")
+        if allowed is not None and value not in allowed:
+            raise ValueError(f"{name} must be one of {', '.join(allowed)}, not {value!r}")
+        checked[name] = value
+    return checked
+
+def sysctl_key(name: str, interface: str) -> str:
+    """The dotted key sysctl(8) shows; dots in interface names (VLANs) become slashes as it writes them"""
+    return SETTINGS[name][0].format(interface=interface.replace(".", "/"))
+
+def is_global(name: str) -> bool:
+    """Whether the setting is host-wide rather than the interface's own"""
+    return "{interface}" not in SETTINGS[name][0]
+
+def key_path(key: str, proc_sys: Path = PROC_SYS) -> Path:
+    return proc_sys.joinpath(*[part.replace("/", ".") for part in key.split(".")])
+
+def read(key: str, proc_sys: Path = PROC_SYS) -> Optional[str]:
+    """Current value, None when the key does not exist (no such interface, IPv6 off)"""
+    try:
+        return " ".join(key_path(key, proc_sys).read_text().split())
+    except OSError:
+        return None
+
+def write(key: str, value: str, proc_sys: Path = PROC_SYS) -> Optional[str]:
+    """Set a key (the daemon runs as root); None when it worked, else why not"""
+    try:
+        key_path(key, proc_sys).write_text(value)
+        return None
+    except OSError as e:
+        return e.strerror or str(e)
+
+def status(wanted: Dict[str, str], applied: Dict[str, Dict], interface: str, read_value) -> List[Dict]:
+    """Per setting of the active profile (wanted) and those alopex still holds (applied, by key): the value in
+    force now, what the profile wants and what it was before; read_value(key) gives the current value"""
+    rows = []
+    names = list(wanted) + [entry["name"] for entry in applied.values() if entry["name"] not in wanted]
+    for name in names:
+        key = sysctl_key(name, interface)
+        entry = applied.get(key, {})
+        effective = read_value(key)
+        rows.append({"name": name, "key": key, "wanted": wanted.get(name), "effective": effective,
+                     "previous": entry.get("previous"), "applied": key in applied,
+                     "in_effect": wanted.get(name) is not None and effective == wanted[name]})
+    return rows
@@ -3,10 +3,39 @@ System Integration - Direct network control without NetworkManager
 Extracted from Rust implementation with enhancements
 """
 
+import os
+import re
 import subprocess
 import asyncio
+import ipaddress
+import json
+import time
+import urllib.request
 from pathlib import Path
-from typing import Optional
+from typing import Dict, List, Optional, Tuple
+from . import tunnels
+from .oui import lookup_vendor
+
+# Plain-text "what is my IP" service; only contacted when the user asks for the public IP
+PUBLIC_IP_URL = os.environ.get("ALOPEX_PUBLIC_IP_URL", "https://api.ipify.org")
+
+async def communicate_or_terminate(process: asyncio.subprocess.Process) -> Tuple[bytes, bytes]:
+    """process.communicate(), terminating the child if the caller is cancelled"""
+    try:
+        return await process.communicate()
+    except asyncio.CancelledError:
+        if process.returncode is None:
+            # SIGTERM rather than SIGKILL so sudo forwards it to the real child
+            process.terminate()
+            await process.wait()
+        raise
+
+def dhcp_client_pattern(interface: str) -> str:
+    """pgrep -f pattern for a dhcpcd or dhclient command line naming exactly this interface (not eth0.20 for eth0)"""
+    # Regex characters in a name stand for themselves, the same way in POSIX and Python regexes
+    special = {"^": "\\^", "\\": "[\\\\]"}
+    name = "".join(special.get(char, f"[{char}]" if char in ".[]*+?(){}|$" else char) for char in interface)
+    return f"(^|[ /])(dhcpcd|dhclient)( .*)? {name}( |$)"
 
 class NetworkControl:
     """Direct network interface control"""
@@ -25,7 +54,7 @@ class NetworkControl:
                 stderr=asyncio.subprocess.PIPE
             )
             
-            stdout, stderr = await result.communicate()
+            stdout, stderr = await communicate_or_terminate(result)
             return result.returncode == 0
             
         except Exception as e:
@@ -103,6 +132,152 @@ class NetworkControl:
             print(f"Failed to bring down {interface}: {e}")
             return False
     
+    @staticmethod
+    async def create_netdev(interface: str, netdev: Dict) -> bool:
+        """Create a VLAN, bridge, bond or tunnel (if missing), attach its ports and bring it up"""
+        kind = netdev.get("kind")
+        commands = []
+        
+        if not Path(f"/sys/class/net/{interface}").exists():
+            if kind == "vlan":
+                commands.append(['ip', 'link', 'add', 'link', netdev["parent"], 'name', interface,
+                                 'type', 'vlan', 'id', str(netdev["id"])])
+            elif kind == "bridge":
+                commands.append(['ip', 'link', 'add', interface, 'type', 'bridge'])
+            elif kind == "bond":
+                commands.append(['ip', 'link', 'add', interface, 'type', 'bond',
+                                 'mode', netdev.get("mode", "balance-rr")])
+            elif kind in tunnels.TUNNEL_KINDS:
+                try:
+                    tunnels.create_link(interface, netdev)
+                except OSError as e:
+                    print(f"Failed to create {kind} {interface}: {e}")
+                    return False
+            else:
+                print(f"Unknown virtual device kind for {interface}: {kind}")
+                return False
+        
+        for port in netdev.get("ports", []):
+            # Bond ports must be down while they are enslaved
+            if kind == "bond":
+                commands.append(['ip', 'link', 'set', port, 'down'])
+            commands.append(['ip', 'link', 'set', port, 'master', interface])
+            commands.append(['ip', 'link', 'set', port, 'up'])
+        
+        try:
+            for command in commands:
+                result = await asyncio.create_subprocess_exec(
+                    'sudo', *command,
+                    stdout=asyncio.subprocess.PIPE,
+                    stderr=asyncio.subprocess.PIPE
+                )
+                
+                stdout, stderr = await communicate_or_terminate(result)
+                if result.returncode != 0:
+                    print(f"{' '.join(command)} failed: {stderr.decode().strip()}")
+                    return False
+            
+            return await NetworkControl.bring_interface_up(interface)
+            
+        except Exception as e:
+            print(f"Failed to create {kind} {interface}: {e}")
+            return False
+    
+    @staticmethod
+    def is_dhcp_client_running(interface: str) -> bool:
+        """Check whether dhcpcd or dhclient is already serving an interface"""
+        try:
+            result = subprocess.run(['pgrep', '-f', dhcp_client_pattern(interface)], capture_output=True)
+            return result.returncode == 0
+        except:
+            return False
+    
+    @staticmethod
+    async def ensure_dhcp_client(interface: str) -> bool:
+        """Start a DHCP client only if none is running, keeping the current lease"""
+        if NetworkControl.is_dhcp_client_running(interface):
+            return True
+        
+        try:
+            result = await asyncio.create_subprocess_exec(
+                'sudo', 'dhcpcd', interface,
+                stdout=asyncio.subprocess.PIPE,
+                stderr=asyncio.subprocess.PIPE
+            )
+            await result.communicate()
+            return result.returncode == 0
+        except Exception as e:
+            print(f"Failed to start DHCP client for {interface}: {e}")
+            return False
+    
+    @staticmethod
+    def get_dhcp_lease(interface: str) -> Optional[Dict]:
+        """Read the current dhcpcd lease for an interface"""
+        try:
+            result = subprocess.run(
+                ['dhcpcd', '-U', interface],
+                capture_output=True, text=True, timeout=5
+            )
+            if result.returncode != 0:
+                return None
+            
+            lease = {}
+            for line in result.stdout.split('\n'):
+                if '=' in line:
+                    key, value = line.split('=', 1)
+                    lease[key.strip()] = value.strip().strip("'")
+            
+            if not lease.get('ip_address'):
+                return None
+            
+            def seconds(key):
+                return int(lease[key]) if lease.get(key, '').isdigit() else None
+            
+            return {
+                'ip_address': lease.get('ip_address'),
+                'server': lease.get('dhcp_server_identifier'),
+                'routers': lease.get('routers', '').split(),
+                'dns_servers': lease.get('domain_name_servers', '').split(),
+                'lease_time': seconds('dhcp_lease_time'),
+                'renewal_time': seconds('dhcp_renewal_time'),
+                'rebinding_time': seconds('dhcp_rebinding_time'),
+                'acquired_at': time.time(),
+                # Everything the server sent, under dhcpcd's option names
+                'options': lease
+            }
+        except Exception:
+            return None
+    
+    @staticmethod
+    async def renew_dhcp_lease(interface: str) -> bool:
+        """Ask dhcpcd to renew an interface's lease now"""
+        try:
+            result = await asyncio.create_subprocess_exec(
+                'sudo', 'dhcpcd', '-n', interface,
+                stdout=asyncio.subprocess.PIPE,
+                stderr=asyncio.subprocess.PIPE
+            )
+            await result.communicate()
+            return result.returncode == 0
+        except Exception as e:
+            print(f"Failed to renew DHCP lease for {interface}: {e}")
+            return False
+    
+    @staticmethod
+    async def release_dhcp_lease(interface: str) -> bool:
+        """Release an interface's lease back to the server, dropping its address"""
+        try:
+            result = await asyncio.create_subprocess_exec(
+                'sudo', 'dhcpcd', '-k', interface,
+                stdout=asyncio.subprocess.PIPE,
+                stderr=asyncio.subprocess.PIPE
+            )
+            await result.communicate()
+            return result.returncode == 0
+        except Exception as e:
+            print(f"Failed to release DHCP lease for {interface}: {e}")
+            return False
+    
     @staticmethod
     async def _stop_dhcp_clients(interface: str):
         """Stop running DHCP clients for interface"""
@@ -149,6 +324,143 @@ class NetworkControl:
         except Exception as e:
             print(f"Failed to update DNS: {e}")
 
+    @staticmethod
+    def get_neighbor_mac(ip: str) -> Optional[str]:
+        """Hardware address of a neighbour (usually the gateway) from the ARP table"""
+        try:
+            result = subprocess.run(['ip', 'neigh', 'show', ip], capture_output=True, text=True, timeout=5)
+            words = result.stdout.split()
+            if 'lladdr' in words:
+                return words[words.index('lladdr') + 1].lower()
+        except Exception:
+            pass
+        return None
+
+    @staticmethod
+    def get_interface_address(interface: str) -> Optional[ipaddress.IPv4Interface]:
+        """First global IPv4 address of an interface, with its prefix"""
+        try:
+            result = subprocess.run(['ip', '-j', '-4', 'addr', 'show', 'dev', interface],
+                                    capture_output=True, text=True, timeout=5)
+            for link in json.loads(result.stdout or "[]"):
+                for info in link.get('addr_info', []):
+                    if info.get('scope') == 'global':
+                        return ipaddress.IPv4Interface(f"{info['local']}/{info['prefixlen']}")
+        except Exception:
+            pass
+        return None
+
+    @staticmethod
+    async def set_firewall_zone(interface: str, zone: str) -> bool:
+        """Move an interface into a firewalld zone"""
+        try:
+            result = await asyncio.create_subprocess_exec(
+                'sudo', 'firewall-cmd', f'--zone={zone}', f'--change-interface={interface}',
+                stdout=asyncio.subprocess.PIPE,
+                stderr=asyncio.subprocess.PIPE
+            )
+
+            stdout, stderr = await communicate_or_terminate(result)
+            if result.returncode != 0:
+                print(f"firewall-cmd failed for {interface}: {stderr.decode().strip()}")
+            return result.returncode == 0
+
+        except Exception as e:
+            print(f"Failed to set firewall zone of {interface}: {e}")
+            return False
+
+    @staticmethod
+    async def get_firewall_zone(interface: str) -> Optional[str]:
+        """The firewalld zone an interface is in, None if it has none"""
+        try:
+            result = await asyncio.create_subprocess_exec(
+                'sudo', 'firewall-cmd', f'--get-zone-of-interface={interface}',
+                stdout=asyncio.subprocess.PIPE,
+                stderr=asyncio.subprocess.PIPE
+            )
+
+            stdout, _ = await communicate_or_terminate(result)
+            zone = stdout.decode().strip()
+            return zone if result.returncode == 0 and zone else None
+
+        except Exception as e:
+            print(f"Failed to get firewall zone of {interface}: {e}")
+            return None
+
+    @staticmethod
+    def write_proxy_env(path: Path, proxy: Optional[str]) -> bool:
+        """Write (or with no proxy, clear) an environment file shells and services can source"""
+        try:
+            path.parent.mkdir(parents=True, exist_ok=True)
+            if not proxy:
+                path.unlink(missing_ok=True)
+                return True
+
+            lines = [f"{name}={proxy}\n" for name in ("http_proxy", "https_proxy", "HTTP_PROXY", "HTTPS_PROXY")]
+            lines.append("no_proxy=localhost,127.0.0.1,::1\n")
+            path.write_text("# ALOPEX proxy configuration\n" + "".join(lines))
+            return True
+
+        except Exception as e:
+            print(f"Failed to write proxy settings: {e}")
+            return False
+
+    @staticmethod
+    async def get_public_ip(url: str = PUBLIC_IP_URL) -> Optional[str]:
+        """Address the internet sees us as, from a plain-text echo service"""
+        def fetch() -> str:
+            with urllib.request.urlopen(url, timeout=5) as response:
+                return response.read(64).decode().strip()
+
+        try:
+            text = await asyncio.get_running_loop().run_in_executor(None, fetch)
+            return str(ipaddress.ip_address(text))
+        except Exception as e:
+            print(f"Failed to look up public IP: {e}")
+            return None
+
+    @staticmethod
+    async def ping(host: str, interface: Optional[str] = None, timeout: int = 1) -> Optional[float]:
+        """Round-trip time to host in ms (one echo request), None if no reply"""
+        cmd = ['ping', '-n', '-c', '1', '-W', str(timeout)]
+        if interface:
+            cmd += ['-I', interface]
+        try:
+            process = await asyncio.create_subprocess_exec(
+                *cmd, host,
+                stdout=asyncio.subprocess.PIPE,
+                stderr=asyncio.subprocess.DEVNULL
+            )
+            stdout, _ = await communicate_or_terminate(process)
+            match = re.search(r"time[=<]([0-9.]+) ms", stdout.decode())
+            return float(match.group(1)) if process.returncode == 0 and match else None
+        except Exception as e:
+            print(f"Failed to ping {host}: {e}")
+            return None
+    
+    @staticmethod
+    async def ping_series(host: str, count: int, interval: float, timeout: float,
+                          interface: Optional[str] = None) -> List[Optional[float]]:
+        """Round-trip time in ms of each of count echo requests, None for those lost"""
+        cmd = ['ping', '-n', '-c', str(count), '-i', f"{interval:g}", '-W', f"{timeout:g}"]
+        if interface:
+            cmd += ['-I', interface]
+        rtts: List[Optional[float]] = [None] * count
+        try:
+            process = await asyncio.create_subprocess_exec(
+                *cmd, host,
+                stdout=asyncio.subprocess.PIPE,
+                stderr=asyncio.subprocess.DEVNULL
+            )
+            stdout, _ = await communicate_or_terminate(process)
+        except OSError:
+            return rtts
+        for match in re.finditer(r"icmp_seq=(\d+) .*?time[=<]([0-9.]+) ms", stdout.decode()):
+            sequence = int(match.group(1))
+            if 0 < sequence <= count:
+                rtts[sequence - 1] = float(match.group(2))
+        return rtts
+
 class BluetoothControl:
     """Bluetooth device management without BlueZ complexity"""
     
@@ -172,7 +484,7 @@ class BluetoothControl:
                     if len(parts) >= 3:
                         mac = parts[1]
                         name = ' '.join(parts[2:])
-                        devices.append({'mac': mac, 'name': name})
+                        devices.append({'mac': mac, 'name': name, 'vendor': lookup_vendor(mac)})
             
             return devices
             
@@ -230,7 +542,7 @@ class BluetoothControl:
                     if len(parts) >= 3:
                         mac = parts[1]
                         name = ' '.join(parts[2:])
-                        devices.append({'mac': mac, 'name': name})
+                        devices.append({'mac': mac, 'name': name, 'vendor': lookup_vendor(mac)})
             
             return devices
             
@@ -0,0 +1,91 @@
+"""
+Transceiver - SFP/QSFP module identity and diagnostics from `ethtool -m`
+Reads the pluggable module's EEPROM: vendor, part number, type, wavelength, and
+on modules with digital optical monitoring (DOM) the temperature, supply
+voltage, laser bias and transmit/receive power per channel, plus raised alarms
+"""
+
+import re
+import logging
+import subprocess
+from typing import Dict, List, Optional
+
+# Configure logging
+logger = logging.getLogger(__name__)
+
+CHANNEL = re.compile(r"\s*\(Channel (\d+)\)\s*$")
+NUMBER = re.compile(r"-?(?:\d+(?:\.\d*)?|inf)")
+IDENTITY = {"Identifier": "identifier", "Connector": "connector", "Vendor name": "vendor", "Vendor PN": "part_number",
+            "Vendor rev": "revision", "Vendor SN": "serial", "Date code": "date_code"}
+# SFP keys, then the per-channel QSFP ones
+BIAS_KEYS = ("Laser bias current", "Laser tx bias current")
+TX_POWER_KEYS = ("Laser output power", "Transmit avg optical power")
+RX_POWER_KEYS = ("Receiver signal average optical power", "Rcvr signal avg optical power")
+
+def _decoded(value: str) -> str:
+    """"0x07 (LC)" -> "LC"; text without a code stays as it is"""
+    match = re.match(r"^0x[0-9a-f]+ \((.*)\)$", value, re.IGNORECASE)
+    return match.group(1) if match else value
+
+def _number(value: str, unit: str) -> Optional[float]:
+    """The figure before a unit, e.g. -2.46 from "0.5678 mW / -2.46 dBm" with unit dBm; None for -inf"""
+    match = re.search(rf"({NUMBER.pattern})\s*{unit}\b", value)
+    if not match or "inf" in match.group(1):
+        return None
+    return float(match.group(1))
+
+def parse_module_eeprom(text: str) -> Optional[Dict]:
+    """Identity and DOM readings from `ethtool -m IFACE`, or None if it shows no module"""
+    fields: Dict[str, List[str]] = {}
+    for line in text.splitlines():
+        key, colon, value = line.partition(":")
+        if colon and key.strip():
+            fields.setdefault(key.strip(), []).append(value.strip())
+    if "Identifier" not in fields:
+        return None
+
+    def channels(keys, unit) -> List[Optional[float]]:
+        """A reading per channel, in channel order; SFP modules have the one"""
+        readings = []
+        for key, values in fields.items():
+            name = CHANNEL.sub("", key)
+            if name in keys:
+                channel = CHANNEL.search(key)
+                readings.append((int(channel.group(1)) if channel else 0, _number(values[0], unit)))
+        return [reading for _, reading in sorted(readings)]
+
+    module = {name: _decoded(fields[key][0]) if key in fields else None for key, name in IDENTITY.items()}
+    module["type"] = ", ".join(value for value in fields.get("Transceiver type", []) if value) or None
+    wavelength = fields.get("Laser wavelength")
+    module["wavelength_nm"] = _number(wavelength[0], "nm") if wavelength else None
+    temperature = fields.get("Module temperature")
+    module["temperature_c"] = _number(temperature[0], "degrees C") if temperature else None
+    voltage = fields.get("Module voltage")
+    module["voltage_v"] = _number(voltage[0], "V") if voltage else None
+    module["bias_ma"] = channels(BIAS_KEYS, "mA")
+    module["tx_power_dbm"] = channels(TX_POWER_KEYS, "dBm")
+    module["rx_power_dbm"] = channels(RX_POWER_KEYS, "dBm")
+    module["diagnostics"] = any(value is not None for value in
+                                [module["temperature_c"]] + module["tx_power_dbm"] + module["rx_power_dbm"])
+    # Flags read "On"/"Off"; thresholds share the names but end in "threshold"
+    module["alarms"] = [key for key, values in fields.items()
+                        if re.search(r"\b(alarm|warning)$", key) and values[0] == "On"]
+    return module
+
+def read_module(interface: str) -> Optional[Dict]:
+    """parse_module_eeprom() of an interface, or None without a module, ethtool or driver support"""
+    try:
+        result = subprocess.run(['ethtool', '-m', interface], capture_output=True, text=True, timeout=5)
+    except Exception as e:
+        logger.debug(f"Cannot run ethtool -m on {interface}: {e}")
+        return None
+    if result.returncode != 0:
+        logger.debug(f"No module information for {interface}: {result.stderr.strip()}")
+        return None
+    return parse_module_eeprom(result.stdout)
+
+def format_power(readings: List[Optional[float]]) -> Optional[str]:
+    """"-2.46 dBm", or one figure per channel for QSFP; None without readings"""
+    if not readings:
+        return None
+    return " / ".join("--" if reading is None else f"{reading:.2f}" for reading in readings) + " dBm"
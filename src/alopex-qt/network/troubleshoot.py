@@ -0,0 +1,147 @@
+"""
+Troubleshoot - Why is the internet down?
+Checks one interface layer by layer (link, address, gateway, DNS, internet),
+stops where things break and says what to try; the internet check goes by
+address, so it also tells a dead resolver from a dead uplink
+"""
+
+import logging
+import ipaddress
+from typing import Callable, Dict, List, Optional
+
+from .discovery import InterfaceType, NetworkInterface
+from .dns_health import PROBE_NAME
+from .port_probe import parse_target
+
+# Configure logging
+logger = logging.getLogger(__name__)
+
+# In the order they are checked; each one needs the ones before it
+STEPS = [("link", "Link"), ("address", "IP address"), ("gateway", "Gateway"), ("dns", "DNS"),
+         ("internet", "Internet")]
+# Reached by address so the answer doesn't depend on DNS
+DEFAULT_TARGETS = ["1.1.1.1:443", "8.8.8.8:443", "9.9.9.9:443"]
+DEFAULT_TIMEOUT = 3.0
+LINK_LOCAL = ipaddress.ip_network("169.254.0.0/16")
+
+def _result(status: str, detail: str, fix: Optional[str] = None) -> Dict:
+    return {"status": status, "detail": detail, "fix": fix}
+
+def summary(report: Dict) -> str:
+    """One line: the failing layer and why, or that everything works"""
+    failed = next((step for step in report["steps"] if step["step"] == report["failed"]), None)
+    if not failed:
+        return f"{report['interface']}: everything checks out, from the link to the internet"
+    return f"{report['interface']}: {failed['title']} is the problem: {failed['detail']}"
+
+def format_steps(report: Dict) -> List[str]:
+    """A line per step, marked passed, failed or not reached, with the fix under the failure"""
+    marks = {"ok": "✓", "failed": "✗", "skipped": "·"}
+    lines = []
+    for step in report["steps"]:
+        lines.append(f"{marks[step['status']]} {step['title']:<12}{step['detail'] or 'not checked'}")
+        if step["fix"] and step["step"] == report["failed"]:
+            lines.append(f"  {'':<12}Try: {step['fix']}")
+    return lines
+
+class Troubleshooter:
+    """Runs the checks for one interface against a host backend"""
+
+    def __init__(self, host, name: str = PROBE_NAME, targets: Optional[List[str]] = None,
+                 timeout: float = DEFAULT_TIMEOUT):
+        self.host = host
+        self.name = name
+        self.targets = [parse_target(target) for target in targets or DEFAULT_TARGETS]
+        self.timeout = timeout
+
+    async def check_link(self, interface: NetworkInterface, dns: List[str]) -> Dict:
+        if interface.rfkill:
+            return _result("failed", f"the radio is {interface.rfkill}-blocked",
+                           "Flip the hardware switch" if interface.rfkill == "hard"
+                           else "Turn airplane mode off (alopex-cli airplane off)")
+        if interface.status == "Connected":
+            speed = interface.metrics.link_speed if interface.metrics else None
+            return _result("ok", "carrier up" + (f" at {speed} Mb/s" if speed and speed > 0 else ""))
+        if interface.interface_type == InterfaceType.WIFI:
+            return _result("failed", "not associated with a network",
+                           f"Connect to one: alopex-cli wifi connect SSID -i {interface.name}")
+        return _result("failed", "no carrier", "Check the cable and the port at the other end, or try another cable")
+
+    async def check_address(self, interface: NetworkInterface, dns: List[str]) -> Dict:
+        address = self.host.interface_address(interface.name)
+        if not address:
+            return _result("failed", "no IPv4 address",
+                           f"Ask the DHCP server again (alopex-cli dhcp renew {interface.name}), or give the "
+                           "profile a static address")
+        if address.ip in LINK_LOCAL:
+            return _result("failed", f"only the link-local address {address.ip}: no DHCP server answered",
+                           f"Check the router's DHCP server, then alopex-cli dhcp renew {interface.name}")
+        return _result("ok", str(address))
+
+    async def check_gateway(self, interface: NetworkInterface, dns: List[str]) -> Dict:
+        gateway = interface.gateway
+        if not gateway:
+            return _result("failed", "no default gateway",
+                           "The DHCP lease named no router; set a gateway on the profile")
+        replies = await self.host.ping(gateway, 3, 0.2, self.timeout, interface.name)
+        answered = [rtt for rtt in replies if rtt is not None]
+        # Pinging fills the neighbor table, so the MAC is there now if the gateway exists
+        mac = self.host.gateway_mac(gateway)
+        if not mac and not answered:
+            return _result("failed", f"{gateway} does not answer ARP",
+                           "The router is off or on another subnet or VLAN; check it and the switch between")
+        detail = gateway + (f" at {mac}" if mac else "")
+        if answered:
+            return _result("ok", f"{detail}, {min(answered):.1f} ms")
+        # Plenty of routers drop pings; answering ARP is enough to go on
+        return _result("ok", f"{detail}, not answering ping")
+
+    async def check_dns(self, interface: NetworkInterface, dns: List[str]) -> Dict:
+        if not dns:
+            return _result("failed", "no DNS servers",
+                           f"Set some: alopex-cli dns set {interface.name} 1.1.1.1 9.9.9.9")
+        errors = []
+        for server in dns:
+            try:
+                latency = await self.host.query_dns(server, self.name, self.timeout)
+                return _result("ok", f"{server} answered for {self.name} in {latency:.0f} ms")
+            except OSError as e:
+                errors.append(f"{server} ({e})")
+        return _result("failed", f"no answer for {self.name} from {', '.join(errors)}",
+                       f"Try other resolvers: alopex-cli dns set {interface.name} 1.1.1.1 9.9.9.9")
+
+    async def check_internet(self, interface: NetworkInterface, dns: List[str]) -> Dict:
+        address = self.host.interface_address(interface.name)
+        source = str(address.ip) if address else None
+        failures = []
+        for host, port in self.targets:
+            result = await self.host.probe_port(host, port, source, self.timeout)
+            # Refused still came back from the far side
+            if result["state"] in ("open", "closed"):
+                return _result("ok", f"{host}:{port} answered in {result['latency_ms']:.0f} ms")
+            failures.append(f"{host}:{port} {result['state']}")
+        return _result("failed", f"the gateway answers but nothing beyond it does ({', '.join(failures)})",
+                       "The modem or the provider is down: restart the modem and router, or check for an outage")
+
+    async def run(self, interface: NetworkInterface, dns: List[str],
+                  progress: Optional[Callable[[Optional[int], str], None]] = None) -> Dict:
+        """The steps in order, up to the first that fails, and which layer is to blame"""
+        steps = []
+        for index, (step, title) in enumerate(STEPS):
+            if progress:
+                progress(index * 100 // len(STEPS), f"Checking: {title}")
+            result = await getattr(self, f"check_{step}")(interface, dns)
+            steps.append(dict(result, step=step, title=title))
+            # A resolver can't answer if nothing past the gateway can, so find out before blaming DNS
+            if result["status"] == "failed" and step != "dns":
+                break
+        failed = [step["step"] for step in steps if step["status"] == "failed"]
+        if failed == ["dns", "internet"]:
+            # The resolvers are out there too; fixing the uplink fixes them
+            steps[-2]["fix"] = None
+            failed = ["internet"]
+        steps += [{"step": step, "title": title, "status": "skipped", "detail": None, "fix": None}
+                  for step, title in STEPS[len(steps):]]
+        blamed = failed[0] if failed else None
+        logger.info(f"Troubleshooting {interface.name}: {blamed or 'no problem'} found")
+        return {"interface": interface.name, "steps": steps, "failed": blamed}
@@ -0,0 +1,145 @@
+"""
+Tunnels - GRE, GRETAP and VXLAN overlay devices for profiles
+Checks a profile's tunnel netdev (endpoints, key, VNI) before it is saved and
+creates its link over rtnetlink (RTM_NEWLINK with IFLA_LINKINFO, as ip link add
+does) when the profile connects
+"""
+
+import socket
+import struct
+import ipaddress
+import logging
+from typing import Dict
+
+from .link_names import IFLA_IFNAME, RTM_NEWLINK
+from .netlink import NLA_F_NESTED, NLM_F_ACK, NLM_F_REQUEST, attr, message, rtnetlink_request
+
+# Configure logging
+logger = logging.getLogger(__name__)
+
+TUNNEL_KINDS = ("gre", "gretap", "vxlan")
+# The IANA port; Linux still defaults to the pre-standard 8472 when none is given
+VXLAN_PORT = 4789
+MAX_VNI = (1 << 24) - 1
+MAX_KEY = (1 << 32) - 1
+
+NLM_F_EXCL = 0x200
+NLM_F_CREATE = 0x400
+IFLA_LINKINFO = 18
+IFLA_INFO_KIND = 1
+IFLA_INFO_DATA = 2
+# IFLA_GRE_* and IFLA_VXLAN_* from linux/if_link.h and linux/if_tunnel.h
+IFLA_GRE_IFLAGS = 2
+IFLA_GRE_OFLAGS = 3
+IFLA_GRE_IKEY = 4
+IFLA_GRE_OKEY = 5
+IFLA_GRE_LOCAL = 6
+IFLA_GRE_REMOTE = 7
+IFLA_GRE_TTL = 8
+GRE_KEY = 0x2000
+IFLA_VXLAN_ID = 1
+IFLA_VXLAN_GROUP = 2
+IFLA_VXLAN_LINK = 3
+IFLA_VXLAN_LOCAL = 4
+IFLA_VXLAN_TTL = 5
+IFLA_VXLAN_PORT = 15
+IFLA_VXLAN_GROUP6 = 16
+IFLA_VXLAN_LOCAL6 = 17
+
+class TunnelError(ValueError):
+    """A tunnel netdev the kernel would refuse, or that could never carry traffic"""
+
+def _address(netdev: Dict, field: str) -> ipaddress._BaseAddress:
+    try:
+        return ipaddress.ip_address(str(netdev[field]))
+    except ValueError:
+        raise TunnelError(f"{field} must be an IP address, not {netdev[field]!r}")
+
+def _number(netdev: Dict, field: str, low: int, high: int) -> int:
+    value = netdev[field]
+    if isinstance(value, bool) or not isinstance(value, int) or not low <= value <= high:
+        raise TunnelError(f"{field} must be a whole number from {low} to {high}")
+    return value
+
+def check(netdev: Dict) -> Dict:
+    """The netdev with its fields validated and normalized; raises TunnelError"""
+    kind = netdev.get("kind")
+    if kind not in TUNNEL_KINDS:
+        raise TunnelError(f"Not a tunnel kind: {kind}")
+    unknown = set(netdev) - {"kind", "remote", "local", "key", "ttl", "vni", "port", "parent"}
+    if unknown:
+        raise TunnelError(f"Unknown {kind} settings: {', '.join(sorted(unknown))}")
+    if not netdev.get("remote"):
+        raise TunnelError(f"A {kind} tunnel needs the remote endpoint")
+
+    checked = {"kind": kind, "remote": str(_address(netdev, "remote"))}
+    if netdev.get("local"):
+        checked["local"] = str(_address(netdev, "local"))
+        if ipaddress.ip_address(checked["local"]).version != ipaddress.ip_address(checked["remote"]).version:
+            raise TunnelError("local and remote must both be IPv4 or both IPv6")
+    if netdev.get("ttl") is not None:
+        checked["ttl"] = _number(netdev, "ttl", 1, 255)
+
+    if kind == "vxlan":
+        if netdev.get("vni") is None:
+            raise TunnelError("A vxlan tunnel needs its vni")
+        if netdev.get("key") is not None:
+            raise TunnelError("vxlan tunnels are told apart by vni, not key")
+        checked["vni"] = _number(netdev, "vni", 1, MAX_VNI)
+        checked["port"] = _number(netdev, "port", 1, 65535) if netdev.get("port") is not None else VXLAN_PORT
+        if netdev.get("parent"):
+            checked["parent"] = str(netdev["parent"])
+    else:
+        for field in ("vni", "port", "parent"):
+            if netdev.get(field) is not None:
+                raise TunnelError(f"{field} only applies to vxlan tunnels")
+        if netdev.get("key") is not None:
+            checked["key"] = _number(netdev, "key", 0, MAX_KEY)
+    return checked
+
+def link_type(netdev: Dict) -> str:
+    """GRE over IPv6 endpoints is a different link type to the kernel"""
+    if netdev["kind"] != "vxlan" and ipaddress.ip_address(netdev["remote"]).version == 6:
+        return "ip6" + netdev["kind"]
+    return netdev["kind"]
+
+def _info_data(netdev: Dict, parent_index: int) -> bytes:
+    """IFLA_INFO_DATA for the link kind; keys, flags and the port go in network byte order"""
+    remote = ipaddress.ip_address(netdev["remote"])
+    local = ipaddress.ip_address(netdev["local"]) if netdev.get("local") else None
+    if netdev["kind"] == "vxlan":
+        v6 = remote.version == 6
+        data = (attr(IFLA_VXLAN_ID, struct.pack("=I", netdev["vni"]))
+                + attr(IFLA_VXLAN_PORT, struct.pack(">H", netdev.get("port", VXLAN_PORT)))
+                + attr(IFLA_VXLAN_GROUP6 if v6 else IFLA_VXLAN_GROUP, remote.packed))
+        if local:
+            data += attr(IFLA_VXLAN_LOCAL6 if v6 else IFLA_VXLAN_LOCAL, local.packed)
+        if parent_index:
+            data += attr(IFLA_VXLAN_LINK, struct.pack("=I", parent_index))
+        if netdev.get("ttl") is not None:
+            data += attr(IFLA_VXLAN_TTL, struct.pack("=B", netdev["ttl"]))
+        return data
+    data = attr(IFLA_GRE_REMOTE, remote.packed)
+    if local:
+        data += attr(IFLA_GRE_LOCAL, local.packed)
+    if netdev.get("key") is not None:
+        key = struct.pack(">I", netdev["key"])
+        data += (attr(IFLA_GRE_IFLAGS, struct.pack(">H", GRE_KEY)) + attr(IFLA_GRE_OFLAGS, struct.pack(">H", GRE_KEY))
+                 + attr(IFLA_GRE_IKEY, key) + attr(IFLA_GRE_OKEY, key))
+    if netdev.get("ttl") is not None:
+        data += attr(IFLA_GRE_TTL, struct.pack("=B", netdev["ttl"]))
+    return data
+
+def build_newlink(interface: str, netdev: Dict, parent_index: int = 0, seq: int = 1) -> bytes:
+    """RTM_NEWLINK creating a checked tunnel netdev; fails with EEXIST if the name is taken"""
+    linkinfo = (attr(IFLA_INFO_KIND, link_type(netdev).encode())
+                + attr(IFLA_INFO_DATA | NLA_F_NESTED, _info_data(netdev, parent_index)))
+    body = (struct.pack("=BxHiII", socket.AF_UNSPEC, 0, 0, 0, 0)
+            + attr(IFLA_IFNAME, interface.encode() + b"\0")
+            + attr(IFLA_LINKINFO | NLA_F_NESTED, linkinfo))
+    return message(RTM_NEWLINK, NLM_F_REQUEST | NLM_F_ACK | NLM_F_CREATE | NLM_F_EXCL, body, seq)
+
+def create_link(interface: str, netdev: Dict):
+    """Create the tunnel link (down); raises OSError, e.g. without CAP_NET_ADMIN or the kind's module"""
+    parent_index = socket.if_nametoindex(netdev["parent"]) if netdev.get("parent") else 0
+    rtnetlink_request(build_newlink(interface, netdev, parent_index))
@@ -0,0 +1,164 @@
+"""
+Unit Traffic - Bytes sent and received per systemd unit
+Counts each service's sockets with nftables 'socket cgroupv2' rules in a table
+of our own, so top talkers need neither per-process eBPF nor packet capture
+"""
+
+import re
+import time
+import asyncio
+import logging
+from pathlib import Path
+from typing import Dict, List, Optional
+
+# Configure logging
+logger = logging.getLogger(__name__)
+
+NFT_TABLE = "alopex_acct"
+DEFAULT_INTERVAL = 5.0
+MIN_INTERVAL = 1.0
+DEFAULT_TOP = 10
+# Services, user sessions and containers: the second level of the cgroup tree
+SLICES = ("system.slice", "user.slice", "machine.slice")
+UNIT_SUFFIXES = (".service", ".slice", ".scope")
+# Names nft takes inside a quoted string without escaping
+SAFE_PATH = re.compile(r"^[A-Za-z0-9@._:+-]+/[A-Za-z0-9@._:+-]+$")
+
+def cgroup2_root(mounts: Path = Path("/proc/self/mounts")) -> Optional[Path]:
+    """Where the unified hierarchy is mounted: /sys/fs/cgroup, or .../unified on hybrid systems"""
+    try:
+        for line in mounts.read_text().splitlines():
+            fields = line.split()
+            if len(fields) > 2 and fields[2] == "cgroup2":
+                return Path(fields[1])
+    except OSError:
+        pass
+    return None
+
+def list_cgroups(root: Path) -> Dict[str, int]:
+    """Unit cgroups two levels down (e.g. system.slice/nginx.service) and their IDs (directory inodes)"""
+    cgroups = {}
+    for slice_name in SLICES:
+        try:
+            entries = list((root / slice_name).iterdir())
+        except OSError:
+            continue
+        for entry in entries:
+            path = f"{slice_name}/{entry.name}"
+            if entry.is_dir() and entry.name.endswith(UNIT_SUFFIXES) and SAFE_PATH.match(path):
+                try:
+                    cgroups[path] = entry.stat().st_ino
+                except OSError:
+                    continue
+    return cgroups
+
+def unit_name(cgroup: str) -> str:
+    """The unit a cgroup belongs to: system.slice/nginx.service is nginx.service"""
+    return cgroup.rsplit("/", 1)[-1]
+
+def nft_ruleset(cgroups: List[str]) -> str:
+    """The whole accounting table, replaced in one transaction (which zeroes its counters)"""
+    lines = [f"table inet {NFT_TABLE}", f"delete table inet {NFT_TABLE}", f"table inet {NFT_TABLE} {{"]
+    for chain, hook, direction in (("input", "input", "rx"), ("output", "output", "tx")):
+        lines.append(f"  chain {chain} {{")
+        lines.append(f"    type filter hook {hook} priority -150; policy accept;")
+        for cgroup in sorted(cgroups):
+            lines.append(f'    socket cgroupv2 level {cgroup.count("/") + 1} "{cgroup}" counter '
+                         f'comment "{direction} {cgroup}"')
+        lines.append("  }")
+    lines.append("}")
+    return "\n".join(lines) + "\n"
+
+def parse_counters(listing: Dict) -> Dict[str, Dict[str, int]]:
+    """Bytes per cgroup and direction from 'nft -j list table'"""
+    counters: Dict[str, Dict[str, int]] = {}
+    for item in listing.get("nftables", []):
+        rule = item.get("rule")
+        if not rule or " " not in rule.get("comment", ""):
+            continue
+        direction, cgroup = rule["comment"].split(" ", 1)
+        counter = next((e["counter"] for e in rule.get("expr", []) if "counter" in e), None)
+        if counter is not None and direction in ("rx", "tx"):
+            counters.setdefault(cgroup, {"rx": 0, "tx": 0})[direction] = counter["bytes"]
+    return counters
+
+class UnitTraffic:
+    """Keeps the counting rules in step with running units and turns counters into totals and rates"""
+
+    def __init__(self, backend, interval: float = DEFAULT_INTERVAL):
+        self.backend = backend
+        self.interval = max(MIN_INTERVAL, float(interval))
+        self.cgroups: Dict[str, int] = {}
+        self.raw: Dict[str, Dict[str, int]] = {}
+        self.units: Dict[str, Dict] = {}
+        self.sampled_at: Optional[float] = None
+        self.last_error: Optional[str] = None
+
+    def _failed(self, message: str):
+        if message != self.last_error:
+            logger.warning(f"Unit traffic accounting: {message}")
+        self.last_error = message
+
+    async def poll(self):
+        """Reinstall the rules if units came or went (or restarted), then take a sample"""
+        cgroups = self.backend.cgroups()
+        if cgroups != self.cgroups:
+            ok, message = await self.backend.sync(sorted(cgroups))
+            if not ok:
+                self._failed(message)
+                return
+            self.cgroups = cgroups
+            # The new table starts from zero
+            self.raw = {}
+        try:
+            counters = await self.backend.counters()
+        except OSError as e:
+            self._failed(str(e))
+            return
+
+        now = time.monotonic()
+        elapsed = now - self.sampled_at if self.sampled_at is not None else None
+        for cgroup, values in counters.items():
+            unit = self.units.setdefault(cgroup, {
+                "unit": unit_name(cgroup), "cgroup": cgroup, "rx_bytes": 0, "tx_bytes": 0,
+                "rx_rate": None, "tx_rate": None
+            })
+            previous = self.raw.get(cgroup, {"rx": 0, "tx": 0})
+            for direction in ("rx", "tx"):
+                delta = max(0, values[direction] - previous[direction])
+                unit[f"{direction}_bytes"] += delta
+                unit[f"{direction}_rate"] = round(delta / elapsed, 1) if elapsed and cgroup in self.raw else None
+        self.raw = counters
+        for cgroup in set(self.units) - set(counters):
+            del self.units[cgroup]
+        self.sampled_at = now
+        if self.last_error is not None:
+            logger.info("Unit traffic accounting working again")
+            self.last_error = None
+
+    def top(self, limit: int = DEFAULT_TOP) -> Dict:
+        """Units by current rate, busiest first, with totals since they were first seen"""
+        units = sorted(self.units.values(),
+                       key=lambda u: (-((u["rx_rate"] or 0) + (u["tx_rate"] or 0)), -(u["rx_bytes"] + u["tx_bytes"])))
+        return {
+            "enabled": True,
+            "method": self.backend.backend_name,
+            "interval": self.interval,
+            "units": units[:limit],
+            "tracked": len(self.units),
+            "last_error": self.last_error
+        }
+
+    async def run(self):
+        while True:
+            try:
+                await self.poll()
+            except Exception as e:
+                self._failed(str(e))
+            await asyncio.sleep(self.interval)
+
+    async def stop(self):
+        """Take the counting rules out again"""
+        if self.cgroups:
+            await self.backend.clear()
+            self.cgroups = {}
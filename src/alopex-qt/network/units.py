@@ -0,0 +1,57 @@
+"""
+Display Units - How traffic rates and byte counts are written
+Rates in bytes (KB/s) or bits (Kb/s), SI (1000) or IEC (1024) multiples,
+scaled automatically up to G/T; shared by the GUI and alopex-cli
+"""
+
+from dataclasses import dataclass
+from typing import List
+
+# Names accepted by --units, ALOPEX_UNITS and the GUI setting, in the order the GUI cycles them
+UNIT_STYLES = ["bytes-si", "bytes-iec", "bits-si", "bits-iec"]
+DEFAULT_UNITS = "bytes-iec"
+
+@dataclass(frozen=True)
+class UnitPreference:
+    """Bits or bytes for rates, SI or IEC multiples for everything"""
+    bits: bool = False
+    iec: bool = True
+
+    @classmethod
+    def parse(cls, name: str) -> "UnitPreference":
+        """From "bytes-si", "bits-iec", ...; raises ValueError on anything else"""
+        if name not in UNIT_STYLES:
+            raise ValueError(f"Unknown units {name!r}, expected one of {', '.join(UNIT_STYLES)}")
+        quantity, system = name.split("-")
+        return cls(bits=quantity == "bits", iec=system == "iec")
+
+    @property
+    def name(self) -> str:
+        return f"{'bits' if self.bits else 'bytes'}-{'iec' if self.iec else 'si'}"
+
+    def next(self) -> "UnitPreference":
+        """The following style in UNIT_STYLES, wrapping around"""
+        return UnitPreference.parse(UNIT_STYLES[(UNIT_STYLES.index(self.name) + 1) % len(UNIT_STYLES)])
+
+    def _prefixes(self) -> List[str]:
+        return ["", "Ki", "Mi", "Gi", "Ti"] if self.iec else ["", "k", "M", "G", "T"]
+
+    def _scale(self, value: float, symbol: str) -> str:
+        base = 1024 if self.iec else 1000
+        prefixes = self._prefixes()
+        for index, prefix in enumerate(prefixes):
+            if abs(value) < base or index == len(prefixes) - 1:
+                if index == 0:
+                    return f"{value:.0f} {symbol}"
+                return f"{value:.1f} {prefix}{symbol}"
+            value /= base
+
+    def format_rate(self, bytes_per_second: float) -> str:
+        """A transfer rate, e.g. "1.2 MiB/s" or "9.8 Mb/s\""""
+        if self.bits:
+            return self._scale(bytes_per_second * 8, "b/s")
+        return self._scale(bytes_per_second, "B/s")
+
+    def format_size(self, count: float) -> str:
+        """A byte count, e.g. "3.4 GiB"; always bytes, since nobody totals traffic in bits"""
+        return self._scale(count, "B")
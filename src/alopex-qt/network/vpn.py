@@ -12,6 +12,8 @@ from typing import List, Optional, Dict
 from dataclasses import dataclass, field
 from enum import Enum
 
+from .system_integration import communicate_or_terminate
+
 # Configure logging
 logger = logging.getLogger(__name__)
 
@@ -121,7 +123,7 @@ class VpnManager:
                 stderr=asyncio.subprocess.PIPE
             )
             
-            stdout, stderr = await process.communicate()
+            stdout, stderr = await communicate_or_terminate(process)
             
             if process.returncode == 0:
                 logger.info(f"WireGuard connected: {interface_name}")
@@ -12,9 +12,100 @@ from typing import List, Optional, Dict
 from dataclasses import dataclass
 from enum import Enum
 
+from . import nl80211
+
 # Configure logging
 logger = logging.getLogger(__name__)
 
+# "* 5260.0 MHz [52] (20.0 dBm) (radar detection)" in `iw phy <phy> info`
+PHY_CHANNEL = re.compile(r'^\*\s+(\d+)(?:\.\d+)? MHz \[(\d+)\](.*)$')
+COUNTRY_CODE = re.compile(r'^([A-Z]{2}|00)$')
+# "1697040000.123456: wlan0 (phy #0): deauth: 02:00:00:00:00:01 -> 02:00:00:00:00:02 reason 7: ..." in `iw event -t`
+IW_FRAME_EVENT = re.compile(r'^(?:[\d.]+:\s+)?(\S+) \(phy #\d+\): (deauth|disassoc): ([0-9a-fA-F:]{17})'
+                            r'(?: -> [0-9a-fA-F:]{17})?(?:.*?reason (\d+))?')
+# "wlan0 (phy #0): connected to 02:...", "roamed to 02:...", "disconnected (by AP) reason: 3: ..."
+IW_CONNECTION_EVENT = re.compile(r'^(?:[\d.]+:\s+)?(\S+) \(phy #\d+\): (?:(connected|roamed) to ([0-9a-fA-F:]{17})'
+                                 r'|(disconnected) \((by AP|local request)\)(?: reason: (\d+))?)')
+
+def frequency_band(frequency: int) -> str:
+    if frequency < 3000:
+        return "2.4 GHz"
+    if frequency < 5950:
+        return "5 GHz"
+    return "6 GHz" if frequency < 7200 else "60 GHz"
+
+# CA bundle 802.1X servers are checked against when the profile names no CA
+SYSTEM_CA_BUNDLE = "/etc/ssl/certs/ca-certificates.crt"
+
+def wpa_eap_settings(eap: Dict) -> str:
+    """wpa_supplicant network lines for 802.1X: TLS with a client certificate, else PEAP/TTLS with a password"""
+    method = (eap.get("method") or "peap").upper()
+    lines = [f"eap={method}", f'identity="{eap.get("identity") or ""}"',
+             f'ca_cert="{eap.get("ca_cert") or SYSTEM_CA_BUNDLE}"']
+    if method == "TLS":
+        lines += [f'client_cert="{eap["client_cert"]}"', f'private_key="{eap["private_key"]}"']
+    else:
+        lines += [f'password="{eap.get("password") or ""}"', 'phase2="auth=MSCHAPV2"']
+        if method == "PEAP":
+            lines.append('phase1="peaplabel=0"')
+    return "".join(f"    {line}\n" for line in lines)
+
+def parse_iw_event(line: str) -> Optional[Dict]:
+    """A deauth, disassoc, connected, roamed or disconnected line from `iw event -t`, or None for any other
+    event. Disconnects say whether the access point sent them (by_ap)"""
+    match = IW_FRAME_EVENT.match(line.strip())
+    if match:
+        return {
+            "interface": match.group(1),
+            "kind": match.group(2),
+            "bssid": match.group(3).lower(),
+            "reason": int(match.group(4)) if match.group(4) else None
+        }
+    match = IW_CONNECTION_EVENT.match(line.strip())
+    if not match:
+        return None
+    if match.group(4):
+        return {"interface": match.group(1), "kind": "disconnected", "by_ap": match.group(5) == "by AP",
+                "reason": int(match.group(6)) if match.group(6) else None}
+    return {"interface": match.group(1), "kind": match.group(2), "bssid": match.group(3).lower()}
+
+def parse_iw_link(output: str) -> Optional[Dict]:
+    """{"bssid", "ssid", "frequency", "signal"} from `iw dev X link`; None when not associated"""
+    match = re.search(r'^Connected to ([0-9a-fA-F:]{17})', output, re.MULTILINE)
+    if not match:
+        return None
+    fields = {"bssid": match.group(1).lower(), "ssid": None, "frequency": None, "signal": None}
+    for line in output.splitlines():
+        key, _, value = line.strip().partition(": ")
+        if key == "SSID":
+            fields["ssid"] = value
+        elif key == "freq":
+            fields["frequency"] = int(float(value))
+        elif key == "signal":
+            fields["signal"] = int(float(value.split()[0]))
+    return fields
+
+def parse_phy_channels(output: str) -> List[Dict]:
+    """Channels a phy supports under the current domain, from `iw phy <phy> info`"""
+    channels = []
+    for line in output.splitlines():
+        match = PHY_CHANNEL.match(line.strip())
+        if not match:
+            continue
+        frequency, channel, flags = int(match.group(1)), int(match.group(2)), match.group(3)
+        power = re.search(r'\(([\d.]+) dBm\)', flags)
+        channels.append({
+            "channel": channel,
+            "frequency": frequency,
+            "band": frequency_band(frequency),
+            "max_power_dbm": float(power.group(1)) if power else None,
+            "disabled": "disabled" in flags,
+            # Older iw says "passive scanning" for what is now "no IR": listen only, never transmit first
+            "no_ir": "no IR" in flags or "passive scanning" in flags,
+            "radar": "radar detection" in flags
+        })
+    return channels
+
 class WifiSecurity(Enum):
     """WiFi security types"""
     OPEN = "Open"
@@ -83,6 +174,18 @@ class WiFiManager:
             
         return sorted(networks, key=lambda x: x.signal_strength, reverse=True)
     
+    @staticmethod
+    def cached_networks(interface: str) -> List[WiFiNetwork]:
+        """What the last scan (ours or the supplicant's background scan) found, without scanning again"""
+        try:
+            result = subprocess.run(['sudo', 'iw', 'dev', interface, 'scan', 'dump'],
+                                    capture_output=True, text=True, timeout=10)
+            if result.returncode == 0:
+                return WiFiManager._parse_scan_results(result.stdout)
+        except (OSError, subprocess.TimeoutExpired) as e:
+            logger.debug(f"Scan dump on {interface} failed: {e}")
+        return []
+
     @staticmethod
     def _parse_scan_results(output: str) -> List[WiFiNetwork]:
         """Parse iw scan output with enhanced security detection"""
@@ -163,6 +266,76 @@ class WiFiManager:
         else:
             return 0
     
+    @staticmethod
+    def get_regulatory_domain() -> Optional[Dict]:
+        """The regulatory domain the kernel currently applies (NL80211_CMD_GET_REG)"""
+        try:
+            return nl80211.read_domain()
+        except OSError as e:
+            logger.debug(f"Cannot read the regulatory domain: {e}")
+        return None
+    
+    @staticmethod
+    def set_regulatory_domain(country: str) -> bool:
+        """Ask the kernel (NL80211_CMD_REQ_SET_REG) to apply a country's rules"""
+        try:
+            nl80211.request_domain(country)
+            return True
+        except (OSError, UnicodeEncodeError) as e:
+            logger.error(f"Cannot set regulatory domain {country}: {e}")
+            return False
+    
+    @staticmethod
+    def get_channels(interface: str) -> List[Dict]:
+        """Channels the interface's radio may use under the current domain"""
+        try:
+            result = subprocess.run(['iw', 'dev', interface, 'info'], capture_output=True, text=True, timeout=5)
+            wiphy = re.search(r'wiphy (\d+)', result.stdout)
+            if result.returncode != 0 or not wiphy:
+                return []
+            result = subprocess.run(['iw', 'phy', f'phy{wiphy.group(1)}', 'info'],
+                                    capture_output=True, text=True, timeout=5)
+            return parse_phy_channels(result.stdout) if result.returncode == 0 else []
+        except Exception as e:
+            logger.debug(f"Cannot list channels of {interface}: {e}")
+            return []
+    
+    @staticmethod
+    def get_power_save(interface: str) -> Optional[bool]:
+        """Whether the driver's power management is on (None if iw can't tell)"""
+        try:
+            result = subprocess.run(['iw', 'dev', interface, 'get', 'power_save'],
+                                    capture_output=True, text=True, timeout=5)
+            match = re.search(r'Power save:\s*(on|off)', result.stdout)
+            if result.returncode == 0 and match:
+                return match.group(1) == 'on'
+        except Exception as e:
+            logger.debug(f"Cannot read power save of {interface}: {e}")
+        return None
+    
+    @staticmethod
+    def set_power_save(interface: str, enabled: bool) -> bool:
+        """Turn power management on or off; off trades battery for steady latency"""
+        try:
+            result = subprocess.run(['sudo', 'iw', 'dev', interface, 'set', 'power_save', 'on' if enabled else 'off'],
+                                    capture_output=True, text=True, timeout=5)
+            if result.returncode != 0:
+                logger.error(f"Setting power save on {interface} failed: {result.stderr.strip()}")
+            return result.returncode == 0
+        except Exception as e:
+            logger.error(f"Cannot set power save on {interface}: {e}")
+            return False
+    
+    @staticmethod
+    def get_link(interface: str) -> Optional[Dict]:
+        """The access point the interface is associated with and its signal, None when not associated"""
+        try:
+            result = subprocess.run(['iw', 'dev', interface, 'link'], capture_output=True, text=True, timeout=5)
+            return parse_iw_link(result.stdout) if result.returncode == 0 else None
+        except Exception as e:
+            logger.debug(f"Cannot read the link of {interface}: {e}")
+            return None
+    
     @staticmethod
     def get_current_connection(interface: str) -> Optional[str]:
         """Get currently connected SSID"""
@@ -180,7 +353,8 @@ class WiFiManager:
     
     @staticmethod
     async def connect_to_network(interface: str, ssid: str, password: str = None, 
-                               username: str = None, security_type: WifiSecurity = None) -> bool:
+                               username: str = None, security_type: WifiSecurity = None,
+                               eap: Optional[Dict] = None) -> bool:
         """Connect to WiFi network with enterprise-grade authentication support"""
         import asyncio
         
@@ -196,31 +370,32 @@ class WiFiManager:
             subprocess.run(['sudo', 'ip', 'link', 'set', interface, 'up'], 
                          capture_output=True, check=True)
             
-            if password or username:
+            if eap is None and security_type == WifiSecurity.ENTERPRISE and username:
+                eap = {"method": "peap", "identity": username, "password": password}
+            
+            if password or username or eap:
+                # Keep whatever domain is set; wpa_supplicant applies its country= on start
+                domain = WiFiManager.get_regulatory_domain()
+                country = domain["country"] if domain and domain["country"] != "00" else "US"
+                
                 # Create enterprise-grade wpa_supplicant configuration
                 with tempfile.NamedTemporaryFile(mode='w', suffix='.conf', delete=False) as f:
-                    if security_type == WifiSecurity.ENTERPRISE and username:
+                    if eap:
                         # Enterprise WPA2 (802.1X) configuration
                         config = f'''ctrl_interface=/var/run/wpa_supplicant
 update_config=1
-country=US
+country={country}
 
 network={{
     ssid="{ssid}"
     key_mgmt=WPA-EAP
-    eap=PEAP
-    identity="{username}"
-    password="{password}"
-    phase1="peaplabel=0"
-    phase2="auth=MSCHAPV2"
-    ca_cert="/etc/ssl/certs/ca-certificates.crt"
-}}
+{wpa_eap_settings(eap)}}}
 '''
                     else:
                         # Personal WPA/WPA2/WPA3 configuration
                         config = f'''ctrl_interface=/var/run/wpa_supplicant
 update_config=1
-country=US
+country={country}
 
 network={{
     ssid="{ssid}"
@@ -251,7 +426,7 @@ network={{
                     return False
                 
                 # Wait for connection with enhanced timeout for enterprise networks
-                max_attempts = 15 if security_type == WifiSecurity.ENTERPRISE else 10
+                max_attempts = 15 if eap else 10
                 for attempt in range(max_attempts):
                     await asyncio.sleep(2)
                     current_ssid = WiFiManager.get_current_connection(interface)
@@ -0,0 +1,172 @@
+"""
+WiFi Watch - Passive security observations on WiFi interfaces
+Remembers which access points advertise each SSID and with what security, and
+warns when a known SSID turns up somewhere new with different security or when
+deauthentication frames arrive in bursts
+"""
+
+import os
+import json
+import time
+import asyncio
+import logging
+from collections import deque
+from pathlib import Path
+from typing import Callable, Deque, Dict, List, Optional
+
+# Configure logging
+logger = logging.getLogger(__name__)
+
+DEFAULT_INTERVAL = 60.0
+# Deauths or disassocs from one interface within the window that count as an attack
+DEFAULT_BURST = 5
+DEFAULT_WINDOW = 10.0
+MAX_ALERTS = 100
+# Weakest first; a new access point below the known ones is the evil-twin case
+SECURITY_RANK = {"Open": 0, "WEP": 1, "WPA": 2, "WPA2": 3, "WPA2-Enterprise": 4, "WPA3": 4}
+
+def _security(network) -> str:
+    return network.security.value if hasattr(network.security, "value") else str(network.security)
+
+class WifiWatch:
+    """Rogue access point and deauthentication burst detection from scan results and nl80211 events"""
+
+    def __init__(self, backend, interfaces: Callable[[], List[str]], state_path: Path, events=None,
+                 interval: float = DEFAULT_INTERVAL, burst: int = DEFAULT_BURST, window: float = DEFAULT_WINDOW):
+        self.backend = backend
+        self.interfaces = interfaces
+        self.baseline_file = state_path / "wifi_watch.json"
+        self.events = events
+        self.interval = interval
+        self.burst = burst
+        self.window = window
+        # SSID -> BSSID -> security, as first seen
+        self.baseline: Dict[str, Dict[str, str]] = {}
+        self.alerts: Deque[Dict] = deque(maxlen=MAX_ALERTS)
+        self.frames: Dict[str, Deque[float]] = {}
+        self.last_burst: Dict[str, float] = {}
+        # Called with the connect/roam/disconnect events that share the nl80211 stream
+        self.listeners: List[Callable[[Dict], None]] = []
+        self._load()
+
+    def _load(self):
+        if not self.baseline_file.exists():
+            return
+        try:
+            with open(self.baseline_file) as f:
+                self.baseline = json.load(f).get("ssids", {})
+            logger.info(f"Loaded WiFi baseline for {len(self.baseline)} SSIDs")
+        except Exception as e:
+            logger.error(f"Failed to load WiFi baseline: {e}")
+
+    def _save(self):
+        """Save the baseline via a temp file"""
+        self.baseline_file.parent.mkdir(parents=True, exist_ok=True)
+        temp_file = self.baseline_file.with_suffix(".tmp")
+        with open(temp_file, "w") as f:
+            json.dump({"ssids": self.baseline}, f, indent=2)
+        os.replace(temp_file, self.baseline_file)
+
+    def _alert(self, kind: str, interface: str, message: str, **fields) -> Dict:
+        alert = dict(alert=kind, interface=interface, message=message, timestamp=time.time(), **fields)
+        self.alerts.append(alert)
+        logger.warning(f"{interface}: {message}")
+        if self.events:
+            self.events.publish("WifiSecurityAlert", **{k: v for k, v in alert.items() if k != "timestamp"})
+        return alert
+
+    def observe(self, interface: str, networks) -> List[Dict]:
+        """Compare scan results with the baseline, learning what is new; returns the alerts raised"""
+        alerts = []
+        changed = False
+        for network in networks:
+            if not network.ssid or not network.bssid:
+                continue
+            bssid, security = network.bssid.lower(), _security(network)
+            known = self.baseline.setdefault(network.ssid, {})
+            if known.get(bssid) == security:
+                continue
+            if bssid in known:
+                alerts.append(self._alert(
+                    "security_changed", interface,
+                    f"{network.ssid} on {bssid} changed from {known[bssid]} to {security}",
+                    ssid=network.ssid, bssid=bssid, security=security, expected=[known[bssid]]))
+            elif known and security not in known.values():
+                expected = sorted(set(known.values()))
+                weaker = SECURITY_RANK.get(security, 0) < min(SECURITY_RANK.get(s, 0) for s in expected)
+                alerts.append(self._alert(
+                    "rogue_ap", interface,
+                    f"{network.ssid} advertised by new access point {bssid} with {security}"
+                    f" (known: {', '.join(expected)})",
+                    ssid=network.ssid, bssid=bssid, security=security, expected=expected, weaker=weaker))
+            # Alerted or not, the access point is remembered so it is reported once
+            known[bssid] = security
+            changed = True
+        if changed:
+            self._save()
+        return alerts
+
+    def frame(self, event: Dict) -> Optional[Dict]:
+        """Count a deauth/disassoc event; returns the alert when it completes a burst"""
+        interface = event["interface"]
+        now = event.get("time", time.monotonic())
+        frames = self.frames.setdefault(interface, deque())
+        frames.append(now)
+        while frames and now - frames[0] > self.window:
+            frames.popleft()
+        if len(frames) < self.burst or now - self.last_burst.get(interface, -self.window) < self.window:
+            return None
+        self.last_burst[interface] = now
+        return self._alert(
+            "deauth_burst", interface,
+            f"{len(frames)} {event['kind']} frames in {self.window:g}s from {event.get('bssid') or 'unknown'}",
+            bssid=event.get("bssid"), count=len(frames), reason=event.get("reason"))
+
+    def event(self, event: Dict):
+        """Route one nl80211 event: frames to the burst counter, the rest to listeners"""
+        if event["kind"] in ("deauth", "disassoc"):
+            self.frame(event)
+            return
+        for listener in self.listeners:
+            listener(event)
+
+    def forget(self, ssid: Optional[str] = None) -> int:
+        """Drop the baseline for an SSID (all of them with None) so it is learned afresh; returns how many"""
+        if ssid is None:
+            count = len(self.baseline)
+            self.baseline = {}
+        else:
+            count = 1 if self.baseline.pop(ssid, None) is not None else 0
+        self._save()
+        return count
+
+    def snapshot(self, limit: int = MAX_ALERTS) -> Dict:
+        return {
+            "alerts": list(self.alerts)[-limit:],
+            "baseline": {ssid: dict(bssids) for ssid, bssids in sorted(self.baseline.items())},
+            "burst": self.burst,
+            "window": self.window
+        }
+
+    async def scan_once(self):
+        for interface in self.interfaces():
+            networks = await asyncio.to_thread(self.backend.cached_scan, interface)
+            self.observe(interface, networks)
+
+    async def _scan_loop(self):
+        while True:
+            try:
+                await self.scan_once()
+            except Exception as e:
+                logger.error(f"WiFi watch scan failed: {e}")
+            await asyncio.sleep(self.interval)
+
+    async def run(self):
+        scanning = asyncio.create_task(self._scan_loop())
+        try:
+            await self.backend.watch_frames(self.event)
+        except OSError as e:
+            logger.warning(f"No deauthentication events ({e}); watching scan results only")
+            await scanning
+        finally:
+            scanning.cancel()
@@ -8,10 +8,14 @@ from .interface_panel import InterfacePanel, InterfaceListItem
 from .telemetry_panel import TelemetryPanel, RealTimeGraph
 from .management_panel import ManagementPanel
 from .system_tray import AlopexSystemTray
+from .log_panel import LogPanel
+from .toast import Toast
+from .interface_detail import InterfaceDetailDialog
 
 __all__ = [
     'AlopexMainWindow', 
     'InterfacePanel', 'InterfaceListItem',
     'TelemetryPanel', 'RealTimeGraph',
-    'ManagementPanel', 'AlopexSystemTray'
+    'ManagementPanel', 'AlopexSystemTray', 'LogPanel', 'Toast',
+    'InterfaceDetailDialog'
 ]
\ No newline at end of file
@@ -0,0 +1,195 @@
+"""
+Bluetooth View - Nearby and paired Bluetooth devices, classic and Low Energy
+Lists what BlueZ knows when shown, scans on F5, connects the selected device
+on Enter (LE peripherals need no pairing), pairs it on Shift+P, renames it
+on F2 and sends it a file over OBEX on Shift+F, showing the transfer's progress
+"""
+
+from typing import Dict, List, Optional
+from PyQt6.QtWidgets import (
+    QWidget, QVBoxLayout, QHBoxLayout, QLabel, QListWidget, QListWidgetItem, QInputDialog, QFileDialog
+)
+from PyQt6.QtCore import Qt, QThread, pyqtSignal
+from PyQt6.QtGui import QColor, QKeySequence, QShortcut
+
+from network.ipc import IpcClient, IpcError
+from .arctic_theme import ArcticTheme, FontManager
+
+class BluetoothJobWorker(QThread):
+    """Run one daemon Bluetooth job (scan, pair, connect or send) off the GUI thread, reporting its progress"""
+    done = pyqtSignal(object)
+    failed = pyqtSignal(str)
+    progress = pyqtSignal(str)
+
+    def __init__(self, daemon: IpcClient, request: str, **params):
+        super().__init__()
+        self.daemon = daemon
+        self.request = request
+        self.params = params
+
+    def run(self):
+        try:
+            job = self.daemon.request(self.request, **self.params)
+            while job["state"] == "running":
+                if job["message"]:
+                    self.progress.emit(job["message"])
+                job = self.daemon.request("WaitJob", id=job["id"], timeout=0.5)
+        except IpcError as e:
+            self.failed.emit(str(e))
+            return
+        if job["state"] == "completed":
+            self.done.emit(job["result"])
+        else:
+            self.failed.emit(IpcError.from_reply(job["error"]).message)
+
+class BluetoothView(QWidget):
+    """One row per device: type, signal, battery and whether it is paired or connected"""
+    message = pyqtSignal(str)
+
+    def __init__(self, daemon: Optional[IpcClient] = None):
+        super().__init__()
+        self.daemon = daemon or IpcClient()
+        self.worker: Optional[BluetoothJobWorker] = None
+        self.setup_ui()
+
+    def setup_ui(self):
+        layout = QVBoxLayout(self)
+        layout.setContentsMargins(8, 8, 8, 8)
+
+        header = QHBoxLayout()
+        title = QLabel("Bluetooth")
+        title.setFont(FontManager.get_primary_font(14, 600))
+        title.setStyleSheet(f"color: {ArcticTheme.TEXT_PRIMARY};")
+        header.addWidget(title)
+        header.addStretch()
+        self.status_label = QLabel("")
+        self.status_label.setFont(FontManager.get_primary_font(9))
+        self.status_label.setStyleSheet(f"color: {ArcticTheme.TEXT_SECONDARY};")
+        header.addWidget(self.status_label)
+        layout.addLayout(header)
+
+        self.columns_label = QLabel(self._row("NAME", "MAC", "TYPE", "SIGNAL", "BATTERY", "STATE", "VENDOR"))
+        self.columns_label.setFont(FontManager.get_monospace_font(9))
+        self.columns_label.setStyleSheet(f"color: {ArcticTheme.TEXT_MUTED};")
+        layout.addWidget(self.columns_label)
+
+        self.device_list = QListWidget()
+        self.device_list.setFont(FontManager.get_monospace_font(9))
+        layout.addWidget(self.device_list, 1)
+
+        for key, handler in (("F5", self.scan), ("Return", self.connect_selected), ("Enter", self.connect_selected),
+                             ("Shift+P", self.pair_selected), ("F2", self.rename_selected),
+                             ("Shift+F", self.send_file_to_selected)):
+            shortcut = QShortcut(QKeySequence(key), self)
+            shortcut.setContext(Qt.ShortcutContext.WidgetWithChildrenShortcut)
+            shortcut.activated.connect(handler)
+
+    @staticmethod
+    def _row(*cells) -> str:
+        widths = (24, 19, 9, 10, 9, 11, 0)
+        return "".join(("--" if cell in (None, "") else str(cell)).ljust(width) for cell, width in zip(cells, widths))
+
+    def refresh(self):
+        """List what BlueZ already knows, without scanning"""
+        try:
+            self.show_devices(self.daemon.request("ListBluetoothDevices"))
+        except IpcError as e:
+            self.status_label.setText(str(e))
+
+    def _start(self, request: str, status: str, on_done, **params):
+        """Run a job unless one is already running"""
+        if self.worker and self.worker.isRunning():
+            self.message.emit("Wait for the current Bluetooth job to finish")
+            return
+        self.status_label.setText(status)
+        self.worker = BluetoothJobWorker(self.daemon, request, **params)
+        self.worker.done.connect(on_done)
+        self.worker.failed.connect(self._failed)
+        self.worker.progress.connect(lambda message: self.status_label.setText(f"{status} {message}"))
+        self.worker.start()
+
+    def _failed(self, error: str):
+        self.status_label.setText("")
+        self.message.emit(error)
+
+    def scan(self):
+        self._start("ScanBluetooth", "Scanning...", lambda result: self.show_devices(result["devices"]))
+
+    def _selected(self) -> Optional[Dict]:
+        item = self.device_list.currentItem()
+        return item.data(Qt.ItemDataRole.UserRole) if item else None
+
+    def connect_selected(self):
+        device = self._selected()
+        if not device:
+            self.message.emit("Select a device first")
+            return
+        self._start("ConnectBluetooth", f"Connecting to {device['mac']}...", self._connected, mac=device["mac"])
+
+    def pair_selected(self):
+        device = self._selected()
+        if not device:
+            self.message.emit("Select a device first")
+            return
+        self._start("PairBluetooth", f"Pairing with {device['mac']}...", self._paired, mac=device["mac"])
+
+    def send_file_to_selected(self):
+        device = self._selected()
+        if not device or not device["paired"]:
+            self.message.emit("Select a paired device first")
+            return
+        path, _ = QFileDialog.getOpenFileName(self, f"Send to {device['alias'] or device['name'] or device['mac']}")
+        if path:
+            self._start("SendBluetoothFile", f"Sending to {device['mac']}...", self._sent, mac=device["mac"], path=path)
+
+    def _sent(self, sent: Dict):
+        self.status_label.setText("")
+        self.message.emit(f"Sent {sent['file']} to {sent['device'] or sent['mac']}")
+
+    def rename_selected(self):
+        device = self._selected()
+        if not device:
+            self.message.emit("Select a device first")
+            return
+        text, accepted = QInputDialog.getText(
+            self, "Rename Device", f"Name for {device['mac']} (empty for its own name):",
+            text=device["alias"] or device["name"] or ""
+        )
+        if not accepted:
+            return
+        try:
+            renamed = self.daemon.request("SetBluetoothAlias", mac=device["mac"], alias=text.strip())
+            self.message.emit(f"{renamed['mac']} is now {renamed['alias'] or renamed['name'] or 'unnamed'}")
+        except IpcError as e:
+            self.message.emit(str(e))
+        self.refresh()
+
+    def _connected(self, device: Dict):
+        battery = f", battery {device['battery']}%" if device["battery"] is not None else ""
+        self.message.emit(f"Connected to {device['alias'] or device['name'] or device['mac']}{battery}")
+        self.refresh()
+
+    def _paired(self, device: Dict):
+        self.message.emit(f"Paired with {device['alias'] or device['name'] or device['mac']} (Enter to connect)")
+        self.refresh()
+
+    def show_devices(self, devices: List[Dict]):
+        selected = self._selected()
+        self.device_list.clear()
+        self.status_label.setText(f"{len(devices)} devices")
+        for device in devices:
+            state = "connected" if device["connected"] else "paired" if device["paired"] else None
+            item = QListWidgetItem(self._row(
+                device["alias"] or device["name"], device["mac"],
+                {"le": "LE", "dual": "dual"}.get(device["type"], device["type"]),
+                f"{device['rssi']} dBm" if device["rssi"] is not None else None,
+                f"{device['battery']}%" if device["battery"] is not None else None, state, device["vendor"]))
+            item.setData(Qt.ItemDataRole.UserRole, device)
+            item.setForeground(QColor(ArcticTheme.PRIMARY_ACCENT if device["connected"] else ArcticTheme.TEXT_PRIMARY))
+            self.device_list.addItem(item)
+            if selected and selected["mac"] == device["mac"]:
+                self.device_list.setCurrentItem(item)
+
+    def showEvent(self, event):
+        super().showEvent(event)
+        self.refresh()
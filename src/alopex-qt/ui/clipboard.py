@@ -0,0 +1,39 @@
+"""
+Clipboard - Copy text from the GUI, even when it has no clipboard of its own
+Uses the Qt clipboard normally, and OSC 52 on the controlling terminal when the
+GUI runs headless or ALOPEX_CLIPBOARD=osc52 (the terminal then sets the clipboard)
+"""
+
+import os
+import base64
+from PyQt6.QtGui import QGuiApplication
+
+# Platforms without a real clipboard to hand text to
+HEADLESS_PLATFORMS = {"offscreen", "minimal", "vnc"}
+
+def _use_osc52() -> bool:
+    backend = os.environ.get("ALOPEX_CLIPBOARD", "auto")
+    if backend in ("osc52", "qt"):
+        return backend == "osc52"
+    return QGuiApplication.platformName() in HEADLESS_PLATFORMS
+
+def _copy_osc52(text: str) -> bool:
+    """Write the OSC 52 escape to the terminal we were started from"""
+    payload = base64.b64encode(text.encode()).decode()
+    sequence = f"\033]52;c;{payload}\a"
+    # Inside tmux or screen the sequence has to be passed through to the outer terminal
+    if os.environ.get("TMUX"):
+        sequence = "\033Ptmux;" + sequence.replace("\033", "\033\033") + "\033\\"
+    try:
+        with open("/dev/tty", "w") as tty:
+            tty.write(sequence)
+        return True
+    except OSError:
+        return False
+
+def copy_text(text: str) -> str:
+    """Copy text; returns where it went ("clipboard" or "terminal")"""
+    if _use_osc52() and _copy_osc52(text):
+        return "terminal"
+    QGuiApplication.clipboard().setText(text)
+    return "clipboard"
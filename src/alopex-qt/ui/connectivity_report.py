@@ -0,0 +1,49 @@
+"""
+Connectivity Report - The result of a connectivity diagnosis, for reading or pasting
+Opened by 'c' once the daemon has tried the targets over IPv4 and IPv6
+"""
+
+from PyQt6.QtWidgets import QDialog, QVBoxLayout, QHBoxLayout, QPlainTextEdit, QPushButton
+
+from .clipboard import copy_text
+from .arctic_theme import ArcticTheme, FontManager
+
+class ConnectivityReportDialog(QDialog):
+    """Read-only report with a copy button"""
+
+    def __init__(self, text: str, parent=None):
+        super().__init__(parent)
+        self.setWindowTitle("Connectivity Diagnosis")
+        self.resize(720, 420)
+        self.setup_ui(text)
+
+    def setup_ui(self, text: str):
+        layout = QVBoxLayout(self)
+        layout.setContentsMargins(12, 12, 12, 12)
+        layout.setSpacing(8)
+
+        self.text = QPlainTextEdit(text)
+        self.text.setReadOnly(True)
+        self.text.setFont(FontManager.get_monospace_font(10))
+        self.text.setStyleSheet(f"""
+            QPlainTextEdit {{
+                background: {ArcticTheme.BACKGROUND_PANEL};
+                color: {ArcticTheme.TEXT_PRIMARY};
+                border: 1px solid {ArcticTheme.BORDER_PRIMARY};
+                border-radius: 6px;
+                padding: 8px;
+            }}
+        """)
+        layout.addWidget(self.text)
+
+        buttons = QHBoxLayout()
+        copy_button = QPushButton("Copy")
+        copy_button.setStyleSheet(ArcticTheme.get_button_style("primary"))
+        copy_button.clicked.connect(lambda: copy_text(self.text.toPlainText()))
+        close_button = QPushButton("Close")
+        close_button.setStyleSheet(ArcticTheme.get_button_style("secondary"))
+        close_button.clicked.connect(self.accept)
+        buttons.addStretch()
+        buttons.addWidget(copy_button)
+        buttons.addWidget(close_button)
+        layout.addLayout(buttons)
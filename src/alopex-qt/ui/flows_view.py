@@ -0,0 +1,117 @@
+"""
+Flows View - Active connections from the daemon's conntrack summary
+Busiest destinations and ports, and NAT per hotspot client; refreshed while
+shown, 'f' switches back to the three-panel view
+"""
+
+from typing import Dict, Optional
+from PyQt6.QtWidgets import QWidget, QVBoxLayout, QHBoxLayout, QLabel, QListWidget, QListWidgetItem
+from PyQt6.QtCore import QTimer
+from PyQt6.QtGui import QColor
+
+from network.ipc import IpcClient, IpcError
+from network.units import UnitPreference
+from .arctic_theme import ArcticTheme, FontManager
+
+REFRESH_INTERVAL_MS = 3000
+
+class FlowsView(QWidget):
+    """Top destinations and ports side by side, hotspot NAT below"""
+
+    def __init__(self, daemon: Optional[IpcClient] = None, units: UnitPreference = UnitPreference()):
+        super().__init__()
+        self.daemon = daemon or IpcClient()
+        self.units = units
+        self.flows: Optional[Dict] = None
+        self.refresh_timer = QTimer(self)
+        self.refresh_timer.timeout.connect(self.refresh)
+        self.setup_ui()
+
+    def setup_ui(self):
+        layout = QVBoxLayout(self)
+        layout.setContentsMargins(8, 8, 8, 8)
+
+        header = QHBoxLayout()
+        title = QLabel("Flows")
+        title.setFont(FontManager.get_primary_font(14, 600))
+        title.setStyleSheet(f"color: {ArcticTheme.TEXT_PRIMARY};")
+        header.addWidget(title)
+        header.addStretch()
+        self.status_label = QLabel("Not read yet")
+        self.status_label.setFont(FontManager.get_primary_font(9))
+        self.status_label.setStyleSheet(f"color: {ArcticTheme.TEXT_SECONDARY};")
+        header.addWidget(self.status_label)
+        layout.addLayout(header)
+
+        lists = QHBoxLayout()
+        self.host_list = self._add_list(lists, self._row("DESTINATION", "FLOWS", "BYTES"), 3)
+        self.port_list = self._add_list(lists, self._row("PORT", "FLOWS"), 2)
+        layout.addLayout(lists, 2)
+
+        nat = QVBoxLayout()
+        self.nat_list = self._add_list(nat, self._row("CLIENT", "FLOWS", "BYTES"), 1)
+        layout.addLayout(nat, 1)
+
+    def _add_list(self, layout, columns: str, stretch: int) -> QListWidget:
+        column = QVBoxLayout()
+        label = QLabel(columns)
+        label.setFont(FontManager.get_monospace_font(9))
+        label.setStyleSheet(f"color: {ArcticTheme.TEXT_MUTED};")
+        column.addWidget(label)
+        widget = QListWidget()
+        widget.setFont(FontManager.get_monospace_font(9))
+        column.addWidget(widget, 1)
+        layout.addLayout(column, stretch)
+        return widget
+
+    @staticmethod
+    def _row(*cells) -> str:
+        widths = (40, 8, 0)
+        return "".join(("--" if cell in (None, "") else str(cell)).ljust(width) for cell, width in zip(cells, widths))
+
+    def _size(self, nbytes: Optional[int]) -> Optional[str]:
+        return self.units.format_size(nbytes) if nbytes is not None else None
+
+    def set_units(self, units: UnitPreference):
+        self.units = units
+        if self.flows:
+            self.show_flows(self.flows)
+
+    def refresh(self):
+        """Fetch a fresh summary; the daemon needs root to read conntrack"""
+        try:
+            self.show_flows(self.daemon.request("GetFlows"))
+        except IpcError as e:
+            self.status_label.setText(str(e))
+
+    def show_flows(self, flows: Dict):
+        self.flows = flows
+        usage = f" of {flows['limit']} ({flows['usage']}%)" if flows["limit"] else ""
+        protocols = ", ".join(f"{count} {protocol}" for protocol, count in flows["protocols"].items())
+        self.status_label.setText(f"{flows['total']} flows{usage}" + (f": {protocols}" if protocols else ""))
+
+        self.host_list.clear()
+        for host in flows["top_hosts"]:
+            self.host_list.addItem(self._row(host["host"], host["flows"], self._size(host["bytes"])))
+        self.port_list.clear()
+        for port in flows["top_ports"]:
+            self.port_list.addItem(self._row(f"{port['port']}/{port['protocol']}", port["flows"]))
+
+        self.nat_list.clear()
+        if not flows["nat"]:
+            self.nat_list.addItem("No hotspot running, so nothing is shared")
+        for nat in flows["nat"]:
+            item = QListWidgetItem(f"{nat['interface']} sharing {nat['network']}: {nat['flows']} NAT flows")
+            item.setForeground(QColor(ArcticTheme.PRIMARY_ACCENT))
+            self.nat_list.addItem(item)
+            for client in nat["clients"]:
+                self.nat_list.addItem(self._row(client["address"], client["flows"], self._size(client["bytes"])))
+
+    def showEvent(self, event):
+        super().showEvent(event)
+        self.refresh()
+        self.refresh_timer.start(REFRESH_INTERVAL_MS)
+
+    def hideEvent(self, event):
+        super().hideEvent(event)
+        self.refresh_timer.stop()
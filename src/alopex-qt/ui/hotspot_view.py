@@ -0,0 +1,244 @@
+"""
+Hotspot View - Stations connected to the hotspots alopexd runs
+Refreshed from the daemon while shown; 'k' kicks the selected station, 'b'
+blocks or unblocks it, Shift+L caps its rates, Shift+A puts it on or off the
+allow list, Shift+F switches between allow-list-only and everyone-but-blocked,
+Shift+I turns client isolation on or off, 'h' switches back to the three-panel view
+"""
+
+from typing import Dict, List, Optional
+from PyQt6.QtWidgets import QWidget, QVBoxLayout, QHBoxLayout, QLabel, QListWidget, QListWidgetItem, QInputDialog
+from PyQt6.QtCore import Qt, QTimer, pyqtSignal
+from PyQt6.QtGui import QColor, QKeySequence, QShortcut
+
+from network.ipc import IpcClient, IpcError
+from network.hotspot import format_limit, parse_limit
+from network.units import UnitPreference
+from .arctic_theme import ArcticTheme, FontManager
+
+REFRESH_INTERVAL_MS = 2000
+
+class HotspotView(QWidget):
+    """One row per station (allowed but absent, then blocked ones last), with kick, block, allow and limit on the
+    selection"""
+    message = pyqtSignal(str)
+
+    def __init__(self, daemon: Optional[IpcClient] = None, units: UnitPreference = UnitPreference()):
+        super().__init__()
+        self.daemon = daemon or IpcClient()
+        self.units = units
+        self.hotspots: List[Dict] = []
+        self.refresh_timer = QTimer(self)
+        self.refresh_timer.timeout.connect(self.refresh)
+        self.setup_ui()
+
+    def setup_ui(self):
+        layout = QVBoxLayout(self)
+        layout.setContentsMargins(8, 8, 8, 8)
+
+        header = QHBoxLayout()
+        title = QLabel("Hotspot")
+        title.setFont(FontManager.get_primary_font(14, 600))
+        title.setStyleSheet(f"color: {ArcticTheme.TEXT_PRIMARY};")
+        header.addWidget(title)
+        header.addStretch()
+        self.status_label = QLabel("Not running")
+        self.status_label.setFont(FontManager.get_primary_font(9))
+        self.status_label.setStyleSheet(f"color: {ArcticTheme.TEXT_SECONDARY};")
+        header.addWidget(self.status_label)
+        layout.addLayout(header)
+
+        self.columns_label = QLabel(self._row("MAC", "HOSTNAME", "ADDRESS", "SIGNAL", "RX", "TX", "LIMIT", "STATE"))
+        self.columns_label.setFont(FontManager.get_monospace_font(9))
+        self.columns_label.setStyleSheet(f"color: {ArcticTheme.TEXT_MUTED};")
+        layout.addWidget(self.columns_label)
+
+        self.client_list = QListWidget()
+        self.client_list.setFont(FontManager.get_monospace_font(9))
+        layout.addWidget(self.client_list, 1)
+
+        for key, handler in (("K", self.kick_selected), ("B", self.toggle_block_selected),
+                             ("Shift+L", self.limit_selected), ("Shift+A", self.toggle_allow_selected),
+                             ("Shift+F", self.toggle_filter), ("Shift+I", self.toggle_isolation)):
+            shortcut = QShortcut(QKeySequence(key), self)
+            shortcut.setContext(Qt.ShortcutContext.WidgetWithChildrenShortcut)
+            shortcut.activated.connect(handler)
+
+    @staticmethod
+    def _row(*cells) -> str:
+        widths = (18, 20, 16, 9, 10, 10, 14, 0)
+        return "".join(("--" if cell in (None, "") else str(cell)).ljust(width) for cell, width in zip(cells, widths))
+
+    def set_units(self, units: UnitPreference):
+        self.units = units
+        self.show_hotspots(self.hotspots)
+
+    def refresh(self):
+        """Fetch the stations of every running hotspot"""
+        self.show_hotspots(self.daemon.try_request("ListHotspotClients", default=[]))
+
+    def show_hotspots(self, hotspots: List[Dict]):
+        self.hotspots = hotspots
+        selected = self._selected()
+        self.client_list.clear()
+        if not hotspots:
+            self.status_label.setText("No hotspot running (alopex-cli hotspot start <ssid>)")
+            return
+
+        self.status_label.setText("  ·  ".join(
+            f"{h['interface']}: {h['ssid']}, channel {h['channel'] or 6}, {len(h['clients'])} connected"
+            + (", allow list only" if h.get("filter") == "allow" else "")
+            + (", isolated" if h.get("isolated") else "")
+            for h in hotspots
+        ))
+        for hotspot in hotspots:
+            allowed = hotspot.get("allowed", [])
+            for client in hotspot["clients"]:
+                signal = f"{client['signal']} dBm" if client["signal"] is not None else None
+                self._add(hotspot["interface"], client["mac"], self._row(
+                    client["mac"], client["hostname"] or client.get("vendor"), client["ip"], signal,
+                    self.units.format_size(client["rx_bytes"]), self.units.format_size(client["tx_bytes"]),
+                    format_limit(hotspot.get("limits", {}).get(client["mac"])),
+                    "connected, allowed" if client["mac"] in allowed else "connected"
+                ), ArcticTheme.TEXT_PRIMARY, "connected")
+            connected = {client["mac"] for client in hotspot["clients"]}
+            for mac in allowed:
+                if mac not in connected:
+                    self._add(hotspot["interface"], mac, self._row(mac, None, None, None, None, None, None, "allowed"),
+                              ArcticTheme.TEXT_SECONDARY, "allowed")
+            for mac in hotspot["blocked"]:
+                self._add(hotspot["interface"], mac, self._row(mac, None, None, None, None, None, None, "blocked"),
+                          ArcticTheme.WARNING, "blocked")
+        if selected:
+            for row in range(self.client_list.count()):
+                if self.client_list.item(row).data(Qt.ItemDataRole.UserRole)[:2] == selected[:2]:
+                    self.client_list.setCurrentRow(row)
+
+    def _add(self, interface: str, mac: str, text: str, color: str, state: str):
+        item = QListWidgetItem(text)
+        item.setForeground(QColor(color))
+        item.setData(Qt.ItemDataRole.UserRole, (interface, mac, state))
+        self.client_list.addItem(item)
+
+    def _hotspot(self, interface: str) -> Dict:
+        return next((h for h in self.hotspots if h["interface"] == interface), {})
+
+    def _selected(self) -> Optional[tuple]:
+        """(interface, mac, state) of the selected row; state is connected, allowed or blocked"""
+        item = self.client_list.currentItem()
+        return item.data(Qt.ItemDataRole.UserRole) if item else None
+
+    def kick_selected(self):
+        selected = self._selected()
+        if not selected or selected[2] != "connected":
+            self.message.emit("Select a connected station first")
+            return
+        interface, mac, _ = selected
+        try:
+            self.daemon.request("KickHotspotClient", interface=interface, mac=mac)
+            self.message.emit(f"Disconnected {mac} (b to keep it out)")
+        except IpcError as e:
+            self.message.emit(str(e))
+        self.refresh()
+
+    def toggle_block_selected(self):
+        selected = self._selected()
+        if not selected:
+            self.message.emit("Select a station first")
+            return
+        interface, mac, state = selected
+        blocked = state == "blocked"
+        try:
+            self.daemon.request("BlockHotspotClient", interface=interface, mac=mac, blocked=not blocked)
+            self.message.emit(f"{mac} {'allowed again' if blocked else 'blocked'}")
+        except IpcError as e:
+            self.message.emit(str(e))
+        self.refresh()
+
+    def toggle_allow_selected(self):
+        selected = self._selected()
+        if not selected or selected[2] == "blocked":
+            self.message.emit("Select a connected or allowed station first")
+            return
+        interface, mac, _ = selected
+        hotspot = self._hotspot(interface)
+        allowed = mac not in hotspot.get("allowed", [])
+        try:
+            self.daemon.request("AllowHotspotClient", interface=interface, mac=mac, allowed=allowed)
+            note = "" if hotspot.get("filter") == "allow" else " (Shift+F to admit only the allow list)"
+            self.message.emit(f"{mac} {'on' if allowed else 'off'} the allow list{note}")
+        except IpcError as e:
+            self.message.emit(str(e))
+        self.refresh()
+
+    def _target_interface(self) -> Optional[str]:
+        """The selected station's hotspot, or the only one running"""
+        selected = self._selected()
+        if selected:
+            return selected[0]
+        if len(self.hotspots) == 1:
+            return self.hotspots[0]["interface"]
+        self.message.emit("Select a station on the hotspot to switch")
+        return None
+
+    def toggle_filter(self):
+        """Switch a hotspot between allow list only and deny list"""
+        interface = self._target_interface()
+        if not interface:
+            return
+        mode = "deny" if self._hotspot(interface).get("filter") == "allow" else "allow"
+        try:
+            self.daemon.request("SetHotspotFilter", interface=interface, mode=mode)
+            self.message.emit(f"{interface}: only allow-listed stations may join" if mode == "allow"
+                              else f"{interface}: everyone but blocked stations may join")
+        except IpcError as e:
+            self.message.emit(str(e))
+        self.refresh()
+
+    def toggle_isolation(self):
+        """Keep a hotspot's stations from reaching each other, or let them again"""
+        interface = self._target_interface()
+        if not interface:
+            return
+        isolated = not self._hotspot(interface).get("isolated")
+        try:
+            self.daemon.request("SetHotspotIsolation", interface=interface, isolated=isolated)
+            self.message.emit(f"{interface}: stations {'isolated from' if isolated else 'can reach'} each other")
+        except IpcError as e:
+            self.message.emit(str(e))
+        self.refresh()
+
+    def limit_selected(self):
+        selected = self._selected()
+        if not selected or selected[2] != "connected":
+            self.message.emit("Select a connected station first")
+            return
+        interface, mac, _ = selected
+        hotspot = self._hotspot(interface)
+        text, accepted = QInputDialog.getText(
+            self, "Limit Station", f"Rates for {mac} in kbit/s, down/up (e.g. 2000/500; empty for no limit):",
+            text=format_limit(hotspot.get("limits", {}).get(mac)) or ""
+        )
+        if not accepted:
+            return
+        try:
+            down, up = parse_limit(text)
+        except ValueError as e:
+            self.message.emit(str(e))
+            return
+        try:
+            self.daemon.request("LimitHotspotClient", interface=interface, mac=mac, down=down, up=up)
+            self.message.emit(f"{mac} limited to {format_limit({'down': down, 'up': up})} kbit/s"
+                              if down or up else f"{mac} no longer limited")
+        except IpcError as e:
+            self.message.emit(str(e))
+        self.refresh()
+
+    def showEvent(self, event):
+        super().showEvent(event)
+        self.refresh()
+        self.refresh_timer.start(REFRESH_INTERVAL_MS)
+
+    def hideEvent(self, event):
+        super().hideEvent(event)
+        self.refresh_timer.stop()
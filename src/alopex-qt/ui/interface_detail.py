@@ -0,0 +1,153 @@
+"""
+Interface Detail - Scrollable dump of everything known about one interface
+Opened with Enter or 'i'; Ctrl+S saves the same data as JSON for bug reports
+"""
+
+import json
+from typing import Dict, List, Optional
+from PyQt6.QtWidgets import (
+    QDialog, QVBoxLayout, QHBoxLayout, QPlainTextEdit, QPushButton, QFileDialog, QMessageBox
+)
+from PyQt6.QtGui import QKeySequence, QShortcut
+
+from network.discovery import NetworkDiscovery, NetworkInterface
+from .arctic_theme import ArcticTheme, FontManager
+
+def format_details(details: Dict) -> str:
+    """Human-readable rendering of describe_interface() output"""
+    lines = [f"{details['name']}  ({details['type']}"
+             + (f", {details['type_detail']}" if details["type_detail"] else "") + ")", ""]
+
+    def row(label, value):
+        if value not in (None, "", [], {}):
+            lines.append(f"  {label:<16}{value}")
+
+    lines.append("Link")
+    row("Status", details["status"])
+    row("Operstate", details["operstate"])
+    row("Flags", ",".join(details["flags"]))
+    row("Managed", "yes" if details["managed"] else "no (left to its owner)")
+    row("Container net", details.get("container_network"))
+    row("libvirt net", details.get("virtual_network"))
+    row("MAC", details["mac"])
+    if details["permanent_mac"] != details["mac"]:
+        row("Permanent MAC", details["permanent_mac"])
+    row("Driver", details["driver"])
+    row("Kind", details["kind"])
+    row("Bus", details["bus"])
+    row("MTU", details["mtu"])
+    if details["link_speed"] and details["link_speed"] > 0:
+        row("Speed", f"{details['link_speed']} Mb/s {details['duplex'] or ''}".strip())
+    row("Qdisc", details["qdisc"])
+    queues = details["queues"]
+    if queues["tx"] is not None:
+        row("Queues", f"tx {queues['tx']}, rx {queues['rx']}, txqlen {queues['txqlen']}")
+
+    lines += ["", "Addresses"]
+    for address in details["addresses"]:
+        attributes = [address["scope"]] + (["dynamic"] if address["dynamic"] else [])
+        lines.append(f"  {address['address']:<42}{' '.join(a for a in attributes if a)}")
+    if not details["addresses"]:
+        lines.append("  none")
+
+    lines += ["", "Routes"]
+    for route in details["routes"]:
+        text = route["dst"]
+        if route.get("gateway"):
+            text += f" via {route['gateway']}"
+        for key in ("protocol", "scope", "metric", "prefsrc"):
+            if key in route:
+                text += f" {key} {route[key]}"
+        lines.append(f"  {text}")
+    if not details["routes"]:
+        lines.append("  none")
+
+    lines += ["", "DNS"]
+    lines += [f"  {server}" for server in details["dns"]] or ["  none"]
+
+    guests = details.get("guests")
+    if guests is not None:
+        lines += ["", "Guests"]
+        for guest in guests:
+            lines.append(f"  {guest['domain'] or guest['hostname'] or '(unknown)':<20}{guest['mac']:<20}"
+                         f"{guest['ip'] or 'no lease'}")
+        if not guests:
+            lines.append("  none running")
+
+    sysctls = details.get("sysctls")
+    if sysctls:
+        lines += ["", "Sysctls"]
+        for entry in sysctls:
+            effective = entry["effective"] if entry["effective"] is not None else "missing"
+            note = "" if entry["in_effect"] or entry["wanted"] is None else f"  (profile wants {entry['wanted']})"
+            if entry["applied"] and entry["previous"] != effective:
+                note += f"  (was {entry['previous']})"
+            lines.append(f"  {entry['key']:<42}{effective}{note}")
+
+    lines += ["", "Statistics"]
+    for key, value in details["statistics"].items():
+        lines.append(f"  {key:<24}{value:>16,}")
+
+    return "\n".join(lines)
+
+class InterfaceDetailDialog(QDialog):
+    """Read-only attribute dump with JSON export"""
+
+    def __init__(self, interface: NetworkInterface, parent=None, virtual_network: Optional[Dict] = None,
+                 sysctls: Optional[List[Dict]] = None):
+        super().__init__(parent)
+        self.details = NetworkDiscovery.describe_interface(interface)
+        if virtual_network is not None:
+            self.details["guests"] = virtual_network["guests"]
+        if sysctls is not None:
+            self.details["sysctls"] = sysctls
+        self.setWindowTitle(f"Interface Details - {interface.name}")
+        self.resize(640, 720)
+        self.setup_ui()
+
+    def setup_ui(self):
+        layout = QVBoxLayout(self)
+        layout.setContentsMargins(12, 12, 12, 12)
+        layout.setSpacing(8)
+
+        self.text = QPlainTextEdit(format_details(self.details))
+        self.text.setReadOnly(True)
+        self.text.setFont(FontManager.get_monospace_font(10))
+        self.text.setStyleSheet(f"""
+            QPlainTextEdit {{
+                background: {ArcticTheme.BACKGROUND_PANEL};
+                color: {ArcticTheme.TEXT_PRIMARY};
+                border: 1px solid {ArcticTheme.BORDER_PRIMARY};
+                border-radius: 6px;
+                padding: 8px;
+            }}
+        """)
+        layout.addWidget(self.text)
+
+        buttons = QHBoxLayout()
+        export_button = QPushButton("Export JSON (Ctrl+S)")
+        export_button.setStyleSheet(ArcticTheme.get_button_style("primary"))
+        export_button.clicked.connect(self.export_json)
+        close_button = QPushButton("Close")
+        close_button.setStyleSheet(ArcticTheme.get_button_style("secondary"))
+        close_button.clicked.connect(self.accept)
+        buttons.addStretch()
+        buttons.addWidget(export_button)
+        buttons.addWidget(close_button)
+        layout.addLayout(buttons)
+
+        export_shortcut = QShortcut(QKeySequence.StandardKey.Save, self)
+        export_shortcut.activated.connect(self.export_json)
+
+    def export_json(self):
+        """Save the details to a file chosen by the user"""
+        path, _ = QFileDialog.getSaveFileName(
+            self, "Export Interface Details", f"{self.details['name']}-details.json", "JSON (*.json)"
+        )
+        if not path:
+            return
+        try:
+            with open(path, "w") as f:
+                json.dump(self.details, f, indent=2)
+        except OSError as e:
+            QMessageBox.critical(self, "Export Failed", str(e))
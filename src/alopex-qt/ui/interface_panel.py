@@ -9,9 +9,14 @@ from PyQt6.QtWidgets import (
 from PyQt6.QtCore import Qt, pyqtSignal, QSize
 from PyQt6.QtGui import QFont, QPalette, QBrush, QColor, QPainter, QPen
 
-from network.discovery import NetworkInterface
+from network.discovery import InterfaceType, NetworkInterface
+from network.units import UnitPreference
 from .arctic_theme import ArcticTheme, FontManager
 
+# Orders the 's' key cycles through; "type" groups the list under type headers
+SORT_MODES = ["type", "name", "state", "traffic"]
+STATE_ORDER = {"Connected": 0, "Connecting": 1, "Disconnected": 2}
+
 class InterfaceStatusIndicator(QWidget):
     """Beautiful animated status indicator"""
     
@@ -46,7 +51,7 @@ class InterfaceStatusIndicator(QWidget):
 class InterfaceTypeIcon(QWidget):
     """Interface type icon widget"""
     
-    def __init__(self, interface_type="Unknown"):
+    def __init__(self, interface_type=InterfaceType.OTHER):
         super().__init__()
         self.interface_type = interface_type
         self.setFixedSize(24, 24)
@@ -61,25 +66,40 @@ class InterfaceTypeIcon(QWidget):
         painter.setPen(QPen(color, 2))
         
         # Draw different icons based on type
-        if self.interface_type == "Ethernet":
+        if self.interface_type == InterfaceType.ETHERNET:
             # Ethernet port icon
             painter.drawRect(4, 8, 16, 8)
             painter.drawLine(6, 10, 18, 10)
             painter.drawLine(6, 14, 18, 14)
             
-        elif self.interface_type == "WiFi":
+        elif self.interface_type == InterfaceType.WIFI:
             # WiFi signal icon  
             painter.drawArc(8, 8, 8, 8, 0, 180 * 16)
             painter.drawArc(6, 6, 12, 12, 0, 180 * 16)
             painter.drawArc(4, 4, 16, 16, 0, 180 * 16)
             painter.fillRect(11, 15, 2, 2, color)
             
-        elif self.interface_type == "VPN":
+        elif self.interface_type in (InterfaceType.VPN, InterfaceType.TUN):
             # VPN shield icon
             painter.drawPath(self._get_shield_path())
             
+        elif self.interface_type == InterfaceType.CELLULAR:
+            # Signal bars
+            for i, height in enumerate((4, 8, 12, 16)):
+                painter.fillRect(4 + i * 5, 20 - height, 3, height, color)
+            
+        elif self.interface_type in (InterfaceType.BRIDGE, InterfaceType.BOND, InterfaceType.VLAN):
+            # Two ports joined by a link
+            painter.drawRect(3, 9, 6, 6)
+            painter.drawRect(15, 9, 6, 6)
+            painter.drawLine(9, 12, 15, 12)
+            
+        elif self.interface_type == InterfaceType.LOOPBACK:
+            # Loop
+            painter.drawEllipse(6, 6, 12, 12)
+            
         else:
-            # Unknown - question mark
+            # Other - question mark
             font = painter.font()
             font.setPointSize(12)
             font.setBold(True)
@@ -103,9 +123,10 @@ class InterfaceListItem(QWidget):
     
     clicked = pyqtSignal(NetworkInterface)
     
-    def __init__(self, interface: NetworkInterface):
+    def __init__(self, interface: NetworkInterface, units: UnitPreference = UnitPreference()):
         super().__init__()
         self.interface = interface
+        self.units = units
         self.selected = False
         self.setup_ui()
         
@@ -126,40 +147,69 @@ class InterfaceListItem(QWidget):
         info_layout = QVBoxLayout()
         info_layout.setSpacing(2)
         
+        # Unmanaged interfaces are shown greyed out
+        managed = self.interface.managed
+        name_color = ArcticTheme.TEXT_PRIMARY if managed else ArcticTheme.TEXT_DISABLED
+        detail_color = ArcticTheme.TEXT_SECONDARY if managed else ArcticTheme.TEXT_DISABLED
+        
         # Interface name
-        name_label = QLabel(self.interface.name)
+        name_label = QLabel(self.interface.display_name)
         name_label.setFont(FontManager.get_primary_font(11, 600))
-        name_label.setStyleSheet(f"color: {ArcticTheme.TEXT_PRIMARY};")
+        name_label.setStyleSheet(f"color: {name_color};")
         
         # Interface details
         details = []
+        if not managed:
+            details.append("unmanaged")
+        details.extend(self.interface.groups)
+        if self.interface.container_network:
+            details.append(self.interface.container_network)
+        if self.interface.virtual_network:
+            details.append(f"libvirt/{self.interface.virtual_network}")
+        if self.interface.rfkill:
+            details.append(self.interface.status_text.lower())
         if self.interface.ip:
             details.append(self.interface.ip)
-        if self.interface.metrics.link_speed:
+        if self.interface.metrics.link_speed and not self.interface.metrics.link_warning:
             details.append(f"{self.interface.metrics.link_speed}Mbps")
             
         detail_text = " • ".join(details) if details else self.interface.interface_type
         detail_label = QLabel(detail_text)
         detail_label.setFont(FontManager.get_primary_font(9))
-        detail_label.setStyleSheet(f"color: {ArcticTheme.TEXT_SECONDARY};")
-        
-        info_layout.addWidget(name_label)
+        detail_label.setStyleSheet(f"color: {detail_color};")
+        
+        name_row = QHBoxLayout()
+        name_row.setSpacing(6)
+        name_row.addWidget(name_label)
+        # A link below its NIC's speed gets a badge instead of the plain figure, explained on hover
+        if self.interface.metrics.link_warning:
+            speed, max_speed = self.interface.metrics.link_speed, self.interface.metrics.max_link_speed
+            badge = QLabel(f"⚠ {speed}/{max_speed} Mbps" if max_speed and speed and speed < max_speed
+                           else f"⚠ {speed} Mbps half duplex")
+            badge.setFont(FontManager.get_primary_font(8, 600))
+            badge.setStyleSheet(f"color: {ArcticTheme.WARNING}; border: 1px solid {ArcticTheme.WARNING}; "
+                                f"border-radius: 4px; padding: 0 4px;")
+            badge.setToolTip(self.interface.metrics.link_warning)
+            name_row.addWidget(badge)
+        name_row.addStretch()
+        
+        info_layout.addLayout(name_row)
         info_layout.addWidget(detail_label)
         
         layout.addLayout(info_layout)
         layout.addStretch()
         
         # Speed indicator for active connections
-        if self.interface.status == "Connected":
+        if self.interface.status == "Connected" and managed:
             speed_layout = QVBoxLayout()
             speed_layout.setSpacing(1)
             
-            up_label = QLabel(f"↑ {self.interface.metrics.speed_up:.1f}K")
+            up_label = QLabel(f"↑ {self.units.format_rate(self.interface.metrics.speed_up * 1024)}")
             up_label.setFont(FontManager.get_primary_font(8, 600))
             up_label.setStyleSheet(f"color: {ArcticTheme.SUCCESS};")
             up_label.setAlignment(Qt.AlignmentFlag.AlignRight)
             
-            down_label = QLabel(f"↓ {self.interface.metrics.speed_down:.1f}K")  
+            down_label = QLabel(f"↓ {self.units.format_rate(self.interface.metrics.speed_down * 1024)}")
             down_label.setFont(FontManager.get_primary_font(8, 600))
             down_label.setStyleSheet(f"color: {ArcticTheme.PRIMARY_ACCENT};")
             down_label.setAlignment(Qt.AlignmentFlag.AlignRight)
@@ -244,7 +294,12 @@ class InterfacePanel(QWidget):
         super().__init__()
         self.interfaces = []
         self.selected_item = None
+        self.units = UnitPreference()
+        self.sort_mode = "type"
+        self.connected_first = False
         self.setup_ui()
+        # Takes focus on click so Enter can open the selected interface's details
+        self.setFocusPolicy(Qt.FocusPolicy.ClickFocus)
         
     def setup_ui(self):
         layout = QVBoxLayout(self)
@@ -266,6 +321,19 @@ class InterfacePanel(QWidget):
         """)
         layout.addWidget(header)
         
+        # Current order ('s' cycles, Shift+S toggles connected first)
+        self.sort_label = QLabel()
+        self.sort_label.setStyleSheet("""
+            QLabel {
+                background: #2c3e50;
+                color: #95a5a6;
+                font-size: 9pt;
+                padding: 4px 16px;
+            }
+        """)
+        layout.addWidget(self.sort_label)
+        self._update_sort_label()
+        
         # Scrollable interface list
         from PyQt6.QtWidgets import QScrollArea
         scroll = QScrollArea()
@@ -311,31 +379,77 @@ class InterfacePanel(QWidget):
             self.content_layout.itemAt(i).widget().setParent(None)
             
         self.interfaces = interfaces
-        
-        # Group by interface type
-        grouped = {}
-        for interface in interfaces:
-            interface_type = interface.interface_type
-            if interface_type not in grouped:
-                grouped[interface_type] = []
-            grouped[interface_type].append(interface)
-        
-        # Add interfaces grouped by type
-        for interface_type in ["Ethernet", "WiFi", "VPN", "Unknown"]:
-            if interface_type in grouped:
-                # Add type header
-                header = InterfaceTypeHeader(interface_type)
-                self.content_layout.addWidget(header)
-                
-                # Add interfaces of this type
-                for interface in grouped[interface_type]:
-                    item = InterfaceListItem(interface)
-                    item.clicked.connect(self.on_interface_clicked)
-                    self.content_layout.addWidget(item)
+        selected_name = self.selected_item.interface.name if self.selected_item else None
+        self.selected_item = None
+        # Container veths are listed under their network's bridge in the details, not one by one
+        ordered = self._sorted([i for i in interfaces
+                                if not (i.container_network and i.interface_type != InterfaceType.BRIDGE)])
+        
+        if self.sort_mode == "type":
+            # Group by interface type
+            grouped = {}
+            for interface in ordered:
+                interface_type = interface.interface_type
+                if interface_type not in grouped:
+                    grouped[interface_type] = []
+                grouped[interface_type].append(interface)
+            
+            # Add interfaces grouped by type
+            for interface_type in InterfaceType:
+                if interface_type in grouped:
+                    # Add type header
+                    header = InterfaceTypeHeader(interface_type)
+                    self.content_layout.addWidget(header)
+                    
+                    # Add interfaces of this type
+                    for interface in grouped[interface_type]:
+                        self._add_item(interface, selected_name)
+        else:
+            for interface in ordered:
+                self._add_item(interface, selected_name)
         
         # Add stretch at bottom
         self.content_layout.addStretch()
         
+    def _add_item(self, interface, selected_name):
+        item = InterfaceListItem(interface, self.units)
+        item.clicked.connect(self.on_interface_clicked)
+        if interface.name == selected_name:
+            item.set_selected(True)
+            self.selected_item = item
+        self.content_layout.addWidget(item)
+        
+    def _sorted(self, interfaces):
+        """Interfaces in the current sort order"""
+        def key(interface):
+            if self.sort_mode == "state":
+                primary = (STATE_ORDER.get(interface.status, 3),)
+            elif self.sort_mode == "traffic":
+                primary = (-(interface.metrics.speed_up + interface.metrics.speed_down),)
+            else:
+                primary = ()
+            first = (interface.status != "Connected",) if self.connected_first else ()
+            return first + primary + (interface.name,)
+        return sorted(interfaces, key=key)
+        
+    def set_sort(self, mode: str, connected_first: bool):
+        """Reorder the list; mode is one of SORT_MODES"""
+        self.sort_mode = mode
+        self.connected_first = connected_first
+        self._update_sort_label()
+        self.update_interfaces(self.interfaces)
+        
+    def _update_sort_label(self):
+        text = f"Sorted by {self.sort_mode}"
+        if self.connected_first:
+            text += ", connected first"
+        self.sort_label.setText(text)
+        
+    def set_units(self, units: UnitPreference):
+        """Redraw the list with different rate units"""
+        self.units = units
+        self.update_interfaces(self.interfaces)
+        
     def on_interface_clicked(self, interface):
         """Handle interface selection"""
         # Update visual selection
@@ -347,6 +461,7 @@ class InterfacePanel(QWidget):
                     widget.set_selected(widget.interface.name == interface.name)
                     if widget.interface.name == interface.name:
                         self.selected_item = widget
+        self.setFocus()
         
         # Emit signal
         self.interface_selected.emit(interface)
\ No newline at end of file
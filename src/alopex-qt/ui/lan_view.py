@@ -0,0 +1,95 @@
+"""
+LAN View - Devices found by sweeping the primary interface's subnet
+Scans once each time it is shown and again on F5; 'l' switches back to the
+three-panel view
+"""
+
+from typing import Dict, Optional
+from PyQt6.QtWidgets import QWidget, QVBoxLayout, QHBoxLayout, QLabel, QListWidget, QListWidgetItem
+from PyQt6.QtCore import Qt, QThread, pyqtSignal
+from PyQt6.QtGui import QColor, QKeySequence, QShortcut
+
+from network.ipc import IpcClient, IpcError
+from .arctic_theme import ArcticTheme, FontManager
+
+class LanScanWorker(QThread):
+    """Run one daemon LAN scan off the GUI thread"""
+    scanned = pyqtSignal(dict)
+    failed = pyqtSignal(str)
+
+    def __init__(self, daemon: IpcClient):
+        super().__init__()
+        self.daemon = daemon
+
+    def run(self):
+        try:
+            self.scanned.emit(self.daemon.run_job("ScanLan", timeout=60.0))
+        except IpcError as e:
+            self.failed.emit(str(e))
+
+class LanView(QWidget):
+    """One row per device, the gateway and this machine highlighted"""
+
+    def __init__(self, daemon: Optional[IpcClient] = None):
+        super().__init__()
+        self.daemon = daemon or IpcClient()
+        self.worker: Optional[LanScanWorker] = None
+        self.setup_ui()
+
+    def setup_ui(self):
+        layout = QVBoxLayout(self)
+        layout.setContentsMargins(8, 8, 8, 8)
+
+        header = QHBoxLayout()
+        title = QLabel("Local Network")
+        title.setFont(FontManager.get_primary_font(14, 600))
+        title.setStyleSheet(f"color: {ArcticTheme.TEXT_PRIMARY};")
+        header.addWidget(title)
+        header.addStretch()
+        self.status_label = QLabel("Not scanned yet")
+        self.status_label.setFont(FontManager.get_primary_font(9))
+        self.status_label.setStyleSheet(f"color: {ArcticTheme.TEXT_SECONDARY};")
+        header.addWidget(self.status_label)
+        layout.addLayout(header)
+
+        self.columns_label = QLabel(self._row("ADDRESS", "MAC", "VENDOR", "HOSTNAME", "ROLE"))
+        self.columns_label.setFont(FontManager.get_monospace_font(9))
+        self.columns_label.setStyleSheet(f"color: {ArcticTheme.TEXT_MUTED};")
+        layout.addWidget(self.columns_label)
+
+        self.device_list = QListWidget()
+        self.device_list.setFont(FontManager.get_monospace_font(9))
+        layout.addWidget(self.device_list, 1)
+
+        shortcut = QShortcut(QKeySequence("F5"), self)
+        shortcut.setContext(Qt.ShortcutContext.WidgetWithChildrenShortcut)
+        shortcut.activated.connect(self.scan)
+
+    @staticmethod
+    def _row(*cells) -> str:
+        widths = (17, 19, 26, 24, 0)
+        return "".join(("--" if cell in (None, "") else str(cell)).ljust(width) for cell, width in zip(cells, widths))
+
+    def scan(self):
+        """Ask the daemon for a sweep unless one is already running"""
+        if self.worker and self.worker.isRunning():
+            return
+        self.status_label.setText("Scanning...")
+        self.worker = LanScanWorker(self.daemon)
+        self.worker.scanned.connect(self.show_result)
+        self.worker.failed.connect(lambda error: self.status_label.setText(f"Scan failed: {error}"))
+        self.worker.start()
+
+    def show_result(self, result: Dict):
+        self.device_list.clear()
+        devices = result.get("devices", [])
+        self.status_label.setText(f"{result['interface']}: {len(devices)} devices on {result['network']}")
+        for device in devices:
+            vendor = device["vendor"] or ("(randomized)" if device["randomized_mac"] else None)
+            item = QListWidgetItem(self._row(device["ip"], device["mac"], vendor, device["hostname"], device["role"]))
+            item.setForeground(QColor(ArcticTheme.PRIMARY_ACCENT if device["role"] else ArcticTheme.TEXT_PRIMARY))
+            self.device_list.addItem(item)
+
+    def showEvent(self, event):
+        super().showEvent(event)
+        self.scan()
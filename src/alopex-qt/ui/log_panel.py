@@ -0,0 +1,149 @@
+"""
+Log Panel - Live view of the daemon's in-memory log buffer, or of its events
+Works without journald by polling GetLogs over the control socket; the Events
+source polls GetEvents instead, the persisted notification history
+"""
+
+import time
+from typing import Optional
+from PyQt6.QtWidgets import QWidget, QVBoxLayout, QHBoxLayout, QLabel, QComboBox, QPlainTextEdit
+from PyQt6.QtCore import QTimer
+
+from network.ipc import IpcClient
+from .arctic_theme import ArcticTheme, FontManager
+
+class LogPanel(QWidget):
+    """Daemon log viewer"""
+
+    LEVEL_COLORS = {
+        "DEBUG": ArcticTheme.TEXT_MUTED,
+        "INFO": ArcticTheme.TEXT_SECONDARY,
+        "WARNING": ArcticTheme.WARNING,
+        "ERROR": ArcticTheme.DANGER,
+        "CRITICAL": ArcticTheme.DANGER
+    }
+
+    # Events worth a second look, the rest show in the secondary color
+    EVENT_COLORS = {
+        "Failed": ArcticTheme.DANGER,
+        "GaveUp": ArcticTheme.DANGER,
+        "Degraded": ArcticTheme.WARNING,
+        "Alert": ArcticTheme.WARNING,
+        "Expired": ArcticTheme.WARNING
+    }
+    EVENT_HISTORY = 500
+
+    def __init__(self, daemon: Optional[IpcClient] = None, max_lines=2000):
+        super().__init__()
+        self.daemon = daemon or IpcClient(timeout=1.0)
+        self.last_timestamp = None
+        self.max_lines = max_lines
+        self.setup_ui()
+
+        self.poll_timer = QTimer()
+        self.poll_timer.timeout.connect(self.poll_logs)
+
+    def setup_ui(self):
+        layout = QVBoxLayout(self)
+        layout.setContentsMargins(8, 8, 8, 8)
+        layout.setSpacing(6)
+
+        controls = QHBoxLayout()
+        self.status_label = QLabel("Daemon logs")
+        self.status_label.setStyleSheet(f"color: {ArcticTheme.TEXT_SECONDARY};")
+        controls.addWidget(self.status_label)
+        controls.addStretch()
+
+        controls.addWidget(QLabel("Source:"))
+        self.source_combo = QComboBox()
+        self.source_combo.addItems(["Logs", "Events"])
+        self.source_combo.currentTextChanged.connect(self.reload)
+        controls.addWidget(self.source_combo)
+
+        controls.addWidget(QLabel("Level:"))
+        self.level_combo = QComboBox()
+        self.level_combo.addItems(["DEBUG", "INFO", "WARNING", "ERROR"])
+        self.level_combo.setCurrentText("INFO")
+        self.level_combo.currentTextChanged.connect(self.reload)
+        controls.addWidget(self.level_combo)
+        layout.addLayout(controls)
+
+        self.log_view = QPlainTextEdit()
+        self.log_view.setReadOnly(True)
+        self.log_view.setMaximumBlockCount(self.max_lines)
+        self.log_view.setFont(FontManager.get_monospace_font(9))
+        self.log_view.setStyleSheet(f"""
+            QPlainTextEdit {{
+                background: {ArcticTheme.BACKGROUND_MAIN};
+                color: {ArcticTheme.TEXT_PRIMARY};
+                border: 1px solid {ArcticTheme.BORDER_PRIMARY};
+                border-radius: 6px;
+            }}
+        """)
+        layout.addWidget(self.log_view)
+
+    def showEvent(self, event):
+        self.poll_logs()
+        self.poll_timer.start(2000)
+        super().showEvent(event)
+
+    def hideEvent(self, event):
+        self.poll_timer.stop()
+        super().hideEvent(event)
+
+    def reload(self):
+        """Refetch the whole buffer, e.g. after a level or source change"""
+        self.last_timestamp = None
+        self.log_view.clear()
+        self.level_combo.setEnabled(self.source_combo.currentText() == "Logs")
+        self.poll_logs()
+
+    def poll_logs(self):
+        """Append records logged (or events published) since the last poll"""
+        if self.source_combo.currentText() == "Events":
+            self.poll_events()
+            return
+        records = self.daemon.try_request(
+            "GetLogs", since=self.last_timestamp, level=self.level_combo.currentText()
+        )
+        if records is None:
+            self.status_label.setText("Daemon logs - alopexd not reachable")
+            return
+
+        self.status_label.setText("Daemon logs")
+        for record in records:
+            timestamp = time.strftime("%H:%M:%S", time.localtime(record["timestamp"]))
+            color = self.LEVEL_COLORS.get(record["level"], ArcticTheme.TEXT_PRIMARY)
+            self.log_view.appendHtml(
+                f'<span style="color:{ArcticTheme.TEXT_MUTED}">{timestamp}</span> '
+                f'<span style="color:{color}">{record["level"]:<7} {record["target"]}: '
+                f'{self._escape(record["message"])}</span>'
+            )
+            self.last_timestamp = record["timestamp"]
+
+    def poll_events(self):
+        # The first poll brings the recent history, later ones what arrived since
+        events = self.daemon.try_request(
+            "GetEvents", since=self.last_timestamp, limit=None if self.last_timestamp else self.EVENT_HISTORY
+        )
+        if events is None:
+            self.status_label.setText("Daemon events - alopexd not reachable")
+            return
+
+        self.status_label.setText("Daemon events")
+        for event in events:
+            timestamp = time.strftime("%m-%d %H:%M:%S", time.localtime(event["timestamp"]))
+            color = next((c for suffix, c in self.EVENT_COLORS.items() if event["event"].endswith(suffix)),
+                         ArcticTheme.TEXT_SECONDARY)
+            details = " ".join(f"{key}={value}" for key, value in event.items()
+                               if key not in ("event", "timestamp") and value not in (None, "", [], {}))
+            self.log_view.appendHtml(
+                f'<span style="color:{ArcticTheme.TEXT_MUTED}">{timestamp}</span> '
+                f'<span style="color:{color}">{event["event"]}</span> '
+                f'<span style="color:{ArcticTheme.TEXT_PRIMARY}">{self._escape(details)}</span>'
+            )
+            self.last_timestamp = event["timestamp"]
+
+    @staticmethod
+    def _escape(text: str) -> str:
+        return text.replace("&", "&amp;").replace("<", "&lt;").replace(">", "&gt;")
@@ -4,26 +4,103 @@ Beautiful network management interface with telemetry hub
 """
 
 import sys
+import json
+import logging
 from PyQt6.QtWidgets import (
     QMainWindow, QWidget, QVBoxLayout, QHBoxLayout, 
-    QSplitter, QSystemTrayIcon, QMenu, QApplication
+    QSplitter, QSystemTrayIcon, QMenu, QApplication, QDockWidget, QLabel, QStackedWidget, QTabWidget, QInputDialog,
+    QFileDialog, QComboBox
 )
-from PyQt6.QtCore import Qt, QTimer, pyqtSignal
-from PyQt6.QtGui import QIcon, QAction
+from PyQt6.QtCore import Qt, QTimer, QEvent, QTime, pyqtSignal
+from PyQt6.QtGui import QIcon, QAction, QKeySequence, QShortcut
 
-from .interface_panel import InterfacePanel
-from .management_panel import ManagementPanel  
+from .interface_panel import InterfacePanel, SORT_MODES
+from .management_panel import ManagementPanel, AsyncWorker
 from .telemetry_panel import TelemetryPanel
+from .overview_panel import OverviewPanel
+from .spectrum_view import SpectrumView
+from .hotspot_view import HotspotView
+from .lan_view import LanView
+from .bluetooth_view import BluetoothView
+from .flows_view import FlowsView
+from .roaming_view import RoamingView
 from .system_tray import AlopexSystemTray
-from network.discovery import NetworkDiscovery
+from .log_panel import LogPanel
+from .toast import Toast
+from .interface_detail import InterfaceDetailDialog
+from .connectivity_report import ConnectivityReportDialog
+from .troubleshoot_dialog import TroubleshootDialog
+from .quick_switch import QuickSwitchDialog
+from .clipboard import copy_text
+from .arctic_theme import ArcticTheme
+from network.discovery import NetworkDiscovery, NetworkInterface
+from network.fleet import load_fleet, parse_fleet
+from network.system_integration import NetworkControl
+from network.units import UnitPreference
+from network.ipc import IpcClient, IpcError
+from network.bandwidth import parse_time, to_csv
+from network.port_probe import DEFAULT_TIMEOUT as PROBE_TIMEOUT, parse_target, summary as probe_summary
+from network.connectivity import report as connectivity_report
+
+# Configure logging
+logger = logging.getLogger(__name__)
+
+# Telemetry refresh; --interval or the telemetry/interval_ms setting override it
+DEFAULT_TELEMETRY_INTERVAL_MS = 1000
+MIN_TELEMETRY_INTERVAL_MS = 250  # update_speeds ignores samples closer than 100 ms
+INTERFACE_REFRESH_INTERVAL_MS = 5000
+STATUS_MESSAGE_MS = 5000  # then the status bar goes back to key hints
+# Both timers run this many times slower while the window is not focused
+UNFOCUSED_SLOWDOWN = 5
+# Layout breakpoints, in characters of the window font: below COMPACT_COLUMNS the
+# three panels become tabs, below the minimum only a "too small" notice is shown
+COMPACT_COLUMNS = 100
+MIN_COLUMNS = 40
+MIN_ROWS = 12
+DEFAULT_PANEL_SIZES = [300, 480, 420]  # 25%, 40%, 35%
+SPLIT_STEP = 40  # pixels per Ctrl+arrow
 
 class AlopexMainWindow(QMainWindow):
     """Main application window"""
     
-    def __init__(self):
+    def __init__(self, telemetry_interval_ms: int = DEFAULT_TELEMETRY_INTERVAL_MS,
+                 units: UnitPreference = UnitPreference(), settings=None):
         super().__init__()
+        self.telemetry_interval_ms = max(telemetry_interval_ms, MIN_TELEMETRY_INTERVAL_MS)
+        self.units = units
+        self.settings = settings
+        self.paused = False
+        self.focused = True
         self.network_discovery = NetworkDiscovery()
+        # Rates for the overview grid; kept apart so the two views don't share a sampling clock
+        self.overview_discovery = NetworkDiscovery()
+        # Rates in the interface list (shown, and sorted by), averaged over each refresh
+        self.list_discovery = NetworkDiscovery()
+        self.daemon = IpcClient(timeout=1.0)
+        # Daemons in fleet.json; the window shows one at a time, this machine's to start with
+        self.fleet_error = None
+        try:
+            self.fleet = load_fleet()
+        except ValueError as e:
+            self.fleet, self.fleet_error = parse_fleet({}), str(e)
+        self.host = self.fleet[0]
+        # Our role on the daemon, from its handshake; None when it has no access control
+        self.role = None
         self.selected_interface = None
+        self.public_ip_worker = None
+        self.probe_worker = None
+        self.diagnose_worker = None
+        self.last_probe_target = ""
+        self.pinned_interface = None
+        self.latency_workers = {}
+        self.overview_active = False
+        self.spectrum_active = False
+        self.hotspot_active = False
+        self.lan_active = False
+        self.bluetooth_active = False
+        self.flows_active = False
+        self.roaming_active = False
+        self.layout_mode = "wide"
         
         self.setup_ui()
         self.setup_timers()
@@ -39,16 +116,75 @@ class AlopexMainWindow(QMainWindow):
         central_widget = QWidget()
         self.setCentralWidget(central_widget)
         
-        main_layout = QHBoxLayout(central_widget)
+        main_layout = QVBoxLayout(central_widget)
         main_layout.setContentsMargins(8, 8, 8, 8)
         
+        # Host selector, shown when fleet.json lists more than this machine (Ctrl+H cycles)
+        self.host_bar = QWidget()
+        host_layout = QHBoxLayout(self.host_bar)
+        host_layout.setContentsMargins(0, 0, 0, 4)
+        host_title = QLabel("Host")
+        host_title.setStyleSheet(f"color: {ArcticTheme.TEXT_SECONDARY};")
+        host_layout.addWidget(host_title)
+        self.host_combo = QComboBox()
+        for host in self.fleet:
+            self.host_combo.addItem(f"{host.name} (local)" if host.local else host.name)
+            self.host_combo.setItemData(self.host_combo.count() - 1, host.address, Qt.ItemDataRole.ToolTipRole)
+        self.host_combo.currentIndexChanged.connect(self.switch_host)
+        host_layout.addWidget(self.host_combo)
+        self.host_state_label = QLabel()
+        self.host_state_label.setStyleSheet(f"color: {ArcticTheme.TEXT_SECONDARY};")
+        host_layout.addWidget(self.host_state_label)
+        host_layout.addStretch()
+        main_layout.addWidget(self.host_bar)
+        self.host_bar.setVisible(len(self.fleet) > 1)
+        
+        # The three-panel view (side by side, or tabs when narrow), the overview
+        # of every interface ('d'), WiFi channels ('w'), hotspot stations ('h'), devices
+        # on the LAN ('l'), Bluetooth devices (Shift+B), active flows ('f'), WiFi roams (Shift+W), or a notice
+        # when the window is too small
+        self.view_stack = QStackedWidget()
+        main_layout.addWidget(self.view_stack)
+        
         # Create main splitter
         splitter = QSplitter(Qt.Orientation.Horizontal)
-        main_layout.addWidget(splitter)
+        self.splitter = splitter
+        self.view_stack.addWidget(splitter)
+        
+        self.panel_tabs = QTabWidget()
+        self.view_stack.addWidget(self.panel_tabs)
+        
+        self.overview_panel = OverviewPanel()
+        self.view_stack.addWidget(self.overview_panel)
+        
+        self.spectrum_view = SpectrumView(self.daemon)
+        self.view_stack.addWidget(self.spectrum_view)
+        
+        self.hotspot_view = HotspotView(self.daemon, self.units)
+        self.view_stack.addWidget(self.hotspot_view)
+        
+        self.lan_view = LanView(self.daemon)
+        self.view_stack.addWidget(self.lan_view)
+        
+        self.bluetooth_view = BluetoothView(self.daemon)
+        self.view_stack.addWidget(self.bluetooth_view)
+        
+        self.flows_view = FlowsView(self.daemon, self.units)
+        self.view_stack.addWidget(self.flows_view)
+        
+        self.roaming_view = RoamingView(self.daemon)
+        self.view_stack.addWidget(self.roaming_view)
+        
+        self.too_small_label = QLabel(
+            f"Window too small\n\nEnlarge it to at least {MIN_COLUMNS}×{MIN_ROWS} characters"
+        )
+        self.too_small_label.setAlignment(Qt.AlignmentFlag.AlignCenter)
+        self.too_small_label.setWordWrap(True)
+        self.view_stack.addWidget(self.too_small_label)
         
         # Create panels
         self.interface_panel = InterfacePanel()
-        self.management_panel = ManagementPanel()
+        self.management_panel = ManagementPanel(self.daemon)
         self.telemetry_panel = TelemetryPanel()
         
         # Add panels to splitter
@@ -56,26 +192,221 @@ class AlopexMainWindow(QMainWindow):
         splitter.addWidget(self.management_panel)
         splitter.addWidget(self.telemetry_panel)
         
-        # Set splitter proportions (25%, 40%, 35%)
-        splitter.setSizes([300, 480, 420])
+        # Set splitter proportions, or the ones the user left last time
+        splitter.setSizes(self._saved_sizes())
+        splitter.splitterMoved.connect(self._save_layout)
+        # So the status bar can tell which panel the user is in
+        self.telemetry_panel.setFocusPolicy(Qt.FocusPolicy.ClickFocus)
+        self.panels = [(self.interface_panel, "Interfaces"), (self.management_panel, "Manage"),
+                       (self.telemetry_panel, "Telemetry")]
+        self.collapsed = self._saved_collapsed()
+        self._apply_collapsed()
+        
+        self.interface_panel.set_units(self.units)
+        self.telemetry_panel.set_units(self.units)
+        self.overview_panel.set_units(self.units)
+        self.hotspot_view.set_units(self.units)
+        self.flows_view.set_units(self.units)
+        if self.settings is not None:
+            mode = self.settings.value("display/sort", "type")
+            self.interface_panel.set_sort(mode if mode in SORT_MODES else "type",
+                                          self.settings.value("display/connected_first", False, type=bool))
         
         # Connect signals
         self.interface_panel.interface_selected.connect(self.on_interface_selected)
+        self.overview_panel.interface_selected.connect(self.open_from_overview)
+        
+        # Daemon log panel (Ctrl+L)
+        self.log_dock = QDockWidget("Daemon Logs", self)
+        self.log_dock.setWidget(LogPanel(self.daemon))
+        self.log_dock.setAllowedAreas(Qt.DockWidgetArea.BottomDockWidgetArea)
+        self.addDockWidget(Qt.DockWidgetArea.BottomDockWidgetArea, self.log_dock)
+        self.log_dock.hide()
+        
+        log_shortcut = QShortcut(QKeySequence("Ctrl+L"), self)
+        log_shortcut.activated.connect(lambda: self.log_dock.setVisible(not self.log_dock.isVisible()))
+        
+        # Yank the selected interface's details: y i (IP), y g (gateway), y d (DNS),
+        # y m (MAC), y p (public IP)
+        self.toast = Toast(self)
+        for keys, field in (("Y, I", "ip"), ("Y, G", "gateway"), ("Y, D", "dns"), ("Y, M", "mac")):
+            shortcut = QShortcut(QKeySequence(keys), self)
+            shortcut.activated.connect(lambda field=field: self.copy_interface_field(field))
+        public_ip_shortcut = QShortcut(QKeySequence("Y, P"), self)
+        public_ip_shortcut.activated.connect(self.copy_public_ip)
+        
+        # Cycle rate units: bytes/bits, SI/IEC
+        units_shortcut = QShortcut(QKeySequence("U"), self)
+        units_shortcut.activated.connect(self.cycle_units)
+        
+        # Interface list order: s cycles type/name/state/traffic, Shift+S puts connected links first
+        sort_shortcut = QShortcut(QKeySequence("S"), self)
+        sort_shortcut.activated.connect(self.cycle_sort)
+        connected_first_shortcut = QShortcut(QKeySequence("Shift+S"), self)
+        connected_first_shortcut.activated.connect(self.toggle_connected_first)
         
-        # Setup status bar
-        self.statusBar().showMessage("ALOPEX Network Manager - Ready")
+        # Switch between the three-panel view and the all-interfaces grid
+        overview_shortcut = QShortcut(QKeySequence("D"), self)
+        overview_shortcut.activated.connect(self.toggle_overview)
+        
+        # Scanned networks by channel, to pick a hotspot channel or spot congestion
+        spectrum_shortcut = QShortcut(QKeySequence("W"), self)
+        spectrum_shortcut.activated.connect(self.toggle_spectrum)
+        
+        # Who is on our hotspot, to kick or block them
+        hotspot_shortcut = QShortcut(QKeySequence("H"), self)
+        hotspot_shortcut.activated.connect(self.toggle_hotspot)
+        self.hotspot_view.message.connect(self.toast.show_message)
+        
+        # What else is on the subnet, without reaching for nmap
+        lan_shortcut = QShortcut(QKeySequence("L"), self)
+        lan_shortcut.activated.connect(self.toggle_lan)
+        
+        # Headphones, keyboards and LE sensors: scan, pair, connect, battery levels
+        bluetooth_shortcut = QShortcut(QKeySequence("Shift+B"), self)
+        bluetooth_shortcut.activated.connect(self.toggle_bluetooth)
+        self.bluetooth_view.message.connect(self.toast.show_message)
+        
+        # Where the connections go, and what hotspot clients are NATed
+        flows_shortcut = QShortcut(QKeySequence("F"), self)
+        flows_shortcut.activated.connect(self.toggle_flows)
+        
+        # Which access points WiFi moved between, at what signal, and how long it stayed
+        roaming_shortcut = QShortcut(QKeySequence("Shift+W"), self)
+        roaming_shortcut.activated.connect(self.toggle_roaming)
+        
+        # Next daemon in the fleet
+        host_shortcut = QShortcut(QKeySequence("Ctrl+H"), self)
+        host_shortcut.activated.connect(
+            lambda: self.host_combo.setCurrentIndex((self.host_combo.currentIndex() + 1) % self.host_combo.count())
+        )
+        
+        # Pin the selected interface to compare it with whatever is selected next
+        pin_shortcut = QShortcut(QKeySequence("P"), self)
+        pin_shortcut.activated.connect(self.toggle_pin)
+        
+        # 1/2/3 collapse or restore a panel; Ctrl+Left/Right move the first split,
+        # Ctrl+Shift+Left/Right the second
+        for index in range(3):
+            shortcut = QShortcut(QKeySequence(str(index + 1)), self)
+            shortcut.activated.connect(lambda index=index: self.toggle_panel(index))
+        for keys, handle, step in (("Ctrl+Left", 1, -SPLIT_STEP), ("Ctrl+Right", 1, SPLIT_STEP),
+                                   ("Ctrl+Shift+Left", 2, -SPLIT_STEP), ("Ctrl+Shift+Right", 2, SPLIT_STEP)):
+            shortcut = QShortcut(QKeySequence(keys), self)
+            shortcut.activated.connect(lambda handle=handle, step=step: self.move_split(handle, step))
+        
+        # Every radio off, and back the way each was
+        airplane_shortcut = QShortcut(QKeySequence("A"), self)
+        airplane_shortcut.activated.connect(self.toggle_airplane_mode)
+        
+        # Start the selected interface's session totals over
+        reset_session_shortcut = QShortcut(QKeySequence("R"), self)
+        reset_session_shortcut.activated.connect(self.reset_session_counters)
+        
+        # Is host:port reachable from the selected interface's address
+        probe_shortcut = QShortcut(QKeySequence("T"), self)
+        probe_shortcut.activated.connect(self.probe_port)
+        
+        # Which of IPv4 and IPv6 works, and which one connections actually use
+        diagnose_shortcut = QShortcut(QKeySequence("C"), self)
+        diagnose_shortcut.activated.connect(self.diagnose_connectivity)
+        
+        # Why is the internet down: link, address, gateway, DNS and beyond, for the selected interface
+        troubleshoot_shortcut = QShortcut(QKeySequence("G"), self)
+        troubleshoot_shortcut.activated.connect(self.troubleshoot)
+        
+        # Take back the last profile edit, DNS change or location switch
+        undo_shortcut = QShortcut(QKeySequence("Ctrl+Z"), self)
+        undo_shortcut.activated.connect(self.undo_last_change)
+        
+        # Any profile or location by a few letters of its name
+        quick_switch_shortcut = QShortcut(QKeySequence("Ctrl+P"), self)
+        quick_switch_shortcut.activated.connect(self.quick_switch)
+        
+        # The selected interface's per-minute traffic as CSV or JSON
+        export_shortcut = QShortcut(QKeySequence("E"), self)
+        export_shortcut.activated.connect(self.export_bandwidth)
+        
+        # Everything about the selected interface: 'i' anywhere, Enter in the interface list
+        detail_shortcut = QShortcut(QKeySequence("I"), self)
+        detail_shortcut.activated.connect(self.show_interface_details)
+        for key in ("Return", "Enter"):
+            shortcut = QShortcut(QKeySequence(key), self.interface_panel)
+            shortcut.setContext(Qt.ShortcutContext.WidgetWithChildrenShortcut)
+            shortcut.activated.connect(self.show_interface_details)
+        
+        # Setup status bar: keys for the focused panel on the left (hidden while a
+        # message shows), daemon state, active profile/location and the time on the right
+        self.keys_label = QLabel()
+        self.statusBar().addWidget(self.keys_label, 1)
+        self.airplane_label = QLabel("✈ Airplane mode")
+        self.airplane_label.setStyleSheet(f"color: {ArcticTheme.WARNING}; font-weight: 600;")
+        self.airplane_label.hide()
+        self.daemon_label = QLabel()
+        self.location_label = QLabel()
+        self.clock_label = QLabel()
+        for label in (self.airplane_label, self.daemon_label, self.location_label, self.clock_label):
+            self.statusBar().addPermanentWidget(label)
+        QApplication.instance().focusChanged.connect(self._update_key_hints)
+        self._update_key_hints()
+        self.statusBar().showMessage(self.fleet_error or "ALOPEX Network Manager - Ready", STATUS_MESSAGE_MS)
         
     def setup_timers(self):
         """Setup update timers"""
-        # Interface refresh timer (every 5 seconds)
+        # Interface list refresh
         self.refresh_timer = QTimer()
         self.refresh_timer.timeout.connect(self.refresh_interfaces)
-        self.refresh_timer.start(5000)
         
-        # Telemetry update timer (every 1 second)
+        # Telemetry updates for the selected interface
         self.telemetry_timer = QTimer()
         self.telemetry_timer.timeout.connect(self.update_telemetry)
-        self.telemetry_timer.start(1000)
+        
+        # The clock keeps going while live updates are paused
+        self.clock_timer = QTimer()
+        self.clock_timer.timeout.connect(self._update_clock)
+        self.clock_timer.start(1000)
+        self._update_clock()
+        
+        self._apply_timer_rates()
+        
+    def _apply_timer_rates(self):
+        """(Re)start the timers for the current pause and focus state"""
+        if self.paused:
+            self.refresh_timer.stop()
+            self.telemetry_timer.stop()
+            return
+        
+        factor = 1 if self.focused else UNFOCUSED_SLOWDOWN
+        self.refresh_timer.start(INTERFACE_REFRESH_INTERVAL_MS * factor)
+        self.telemetry_timer.start(self.telemetry_interval_ms * factor)
+        
+    def toggle_pause(self):
+        """Stop or resume live updates"""
+        self.paused = not self.paused
+        self._apply_timer_rates()
+        if self.paused:
+            self.toast.show_message("Live updates paused (space to resume)")
+            self.statusBar().showMessage("Paused")
+        else:
+            self.refresh_interfaces()
+            self.toast.show_message("Live updates resumed")
+            self.statusBar().showMessage("ALOPEX Network Manager - Ready", STATUS_MESSAGE_MS)
+        
+    def keyPressEvent(self, event):
+        """Space pauses; reached only when the focused widget has no use for the key"""
+        if event.key() == Qt.Key.Key_Space and not event.modifiers():
+            self.toggle_pause()
+        else:
+            super().keyPressEvent(event)
+        
+    def changeEvent(self, event):
+        """Slow the timers down while another window has focus"""
+        if event.type() == QEvent.Type.ActivationChange:
+            focused = self.isActiveWindow()
+            if focused != self.focused:
+                self.focused = focused
+                self._apply_timer_rates()
+        super().changeEvent(event)
         
     def setup_system_tray(self):
         """Setup system tray integration"""
@@ -102,11 +433,41 @@ class AlopexMainWindow(QMainWindow):
             self.system_tray.hide()
         QApplication.instance().quit()
     
+    def _discover_interfaces(self):
+        """This machine's interfaces, or those the selected fleet host's daemon reports"""
+        if self.host.local:
+            return NetworkDiscovery.discover_interfaces()
+        return [NetworkInterface.from_dict(entry) for entry in self.daemon.request("GetInterfaces")]
+    
+    def switch_host(self, index: int):
+        """Point every view at another daemon in the fleet"""
+        host = self.fleet[index]
+        if host is self.host:
+            return
+        self.host = host
+        self.daemon.switch(host.client(timeout=self.daemon.timeout))
+        self.role = None
+        # Rates are deltas of one machine's counters, and names mean something else over there
+        self.network_discovery = NetworkDiscovery()
+        self.overview_discovery = NetworkDiscovery()
+        self.list_discovery = NetworkDiscovery()
+        self.selected_interface = None
+        self.pinned_interface = None
+        self.telemetry_panel.set_pinned(None)
+        self.management_panel.set_local(host.local)
+        self.log_dock.widget().reload()
+        self.toast.show_message(f"Showing {host.name}")
+        self.refresh_interfaces()
+    
     def refresh_interfaces(self):
         """Refresh network interface list"""
         try:
-            interfaces = NetworkDiscovery.discover_interfaces()
+            interfaces = self._discover_interfaces()
+            self._mark_unmanaged(interfaces)
+            self.list_discovery.update_speeds(interfaces)
             self.interface_panel.update_interfaces(interfaces)
+            self._update_daemon_state()
+            self.measure_latency()
             
             # Update telemetry if we have a selected interface
             if self.selected_interface:
@@ -120,42 +481,653 @@ class AlopexMainWindow(QMainWindow):
                     self.management_panel.update_interface(updated_interface)
                     
         except Exception as e:
-            self.statusBar().showMessage(f"Error refreshing interfaces: {e}")
+            self.statusBar().showMessage(f"Error refreshing interfaces: {e}", STATUS_MESSAGE_MS)
+    
+    def _mark_unmanaged(self, interfaces):
+        """Flag interfaces the daemon has been told not to touch, and give them their labels, owning networks
+        and link speed warnings"""
+        unmanaged = self.daemon.try_request("GetUnmanaged", default={})
+        names = set(unmanaged.get("interfaces", []))
+        labels = {entry["interface"]: entry for entry in self.daemon.try_request("ListInterfaceLabels", default=[])}
+        networks = self.daemon.try_request("GetContainerNetworks", default={}).get("networks", [])
+        owners = {name: f"{n['engine']}/{n['name']}" for n in networks for name in n["interfaces"]}
+        bridges = {n["bridge"]: n["name"] or "libvirt"
+                   for n in self.daemon.try_request("GetVirtualNetworks", default={}).get("networks", [])}
+        slow = {w["interface"]: w for w in self.daemon.try_request("GetLinkSpeedWarnings", default=[])}
+        for interface in interfaces:
+            interface.managed = interface.name not in names
+            entry = labels.get(interface.name, {})
+            interface.label = entry.get("label")
+            interface.groups = entry.get("groups", [])
+            interface.container_network = owners.get(interface.name)
+            interface.virtual_network = bridges.get(interface.name)
+            interface.metrics.max_link_speed = slow.get(interface.name, {}).get("max_speed")
+            interface.metrics.link_warning = slow.get(interface.name, {}).get("message")
+    
+    def _update_daemon_state(self):
+        """Show daemon reachability, the active profile and location in the title and status bar"""
+        status = self.daemon.try_request("GetStatus")
+        daemon = "alopexd" if self.host.local else f"alopexd on {self.host.name}"
+        if status is None:
+            self.daemon_label.setText(f"{daemon}: {'not running' if self.host.local else 'unreachable'}")
+            self.host_state_label.setText(f"{self.host.address}: unreachable")
+            self.location_label.setText("")
+            self.airplane_label.hide()
+            self.setWindowTitle("ALOPEX Network Manager")
+            return
+        
+        if self.daemon.denied is None:
+            # The handshake, once per daemon: the role it gives us decides which actions the panels offer
+            try:
+                self.role = self.daemon.hello()["role"]
+            except IpcError:
+                # Older daemons and relays don't answer it; the requests themselves report refusals
+                self.daemon.denied, self.role = set(), None
+            if self.daemon.denied and self.selected_interface:
+                self.management_panel.update_interface(self.selected_interface)
+        role = f" as {self.role}" if self.role and self.role != "admin" else ""
+        self.daemon_label.setText(f"{daemon}: {'mock' if status.get('mock') else 'connected'}{role}")
+        self.host_state_label.setText(f"{status.get('hostname') or self.host.address}, up "
+                                      f"{int(status['uptime'] // 3600)}h, {status['connected_interfaces']} connected")
+        self.airplane_label.setVisible(bool(status.get("airplane_mode")))
+        location = status.get("location")
+        title = "ALOPEX Network Manager" + ("" if self.host.local else f" - {self.host.name}")
+        self.setWindowTitle(f"{title} - {location}" if location else title)
+        
+        profiles = status.get("active_profiles", {})
+        selected = self.selected_interface.name if self.selected_interface else None
+        if selected in profiles:
+            profile = f"Profile: {profiles[selected]}"
+        elif profiles:
+            profile = "Profiles: " + ", ".join(sorted(set(profiles.values())))
+        else:
+            profile = "No active profile"
+        self.location_label.setText(profile + (f"  ·  Location: {location}" if location else ""))
+    
+    def _update_clock(self):
+        self.clock_label.setText(QTime.currentTime().toString("HH:mm"))
+    
+    def _update_key_hints(self, *args):
+        """List the keys that do something where focus is now"""
+        focus = QApplication.focusWidget()
+        
+        def within(panel):
+            return focus is not None and (focus is panel or panel.isAncestorOf(focus))
+        
+        if self.overview_active:
+            hints = "click a tile to open it · d panels · u units · space pause"
+        elif self.spectrum_active:
+            hints = "rescans every 15 s · w panels · d overview"
+        elif self.hotspot_active:
+            hints = ("k kick · b block/unblock · Shift+L limit · Shift+A allow · Shift+F allow list on/off · "
+                     "Shift+I isolation · u units · h panels")
+        elif self.lan_active:
+            hints = "F5 rescan · l panels · d overview"
+        elif self.bluetooth_active:
+            hints = ("F5 scan · Enter connect · Shift+P pair · Shift+F send file · F2 rename · Shift+B panels · "
+                     "d overview")
+        elif self.flows_active:
+            hints = "refreshes every 3 s · u units · f panels · d overview"
+        elif self.roaming_active:
+            hints = "newest first, refreshes every 5 s · Shift+W panels · d overview"
+        elif within(self.interface_panel):
+            hints = ("Enter/i details · y i/g/d/m copy · s sort · p pin · t probe port · g troubleshoot · "
+                     "r reset session · 1 collapse")
+        elif within(self.management_panel):
+            hints = ("Tab next field · Ctrl+Shift+M copy MAC · Ctrl+Shift+R/L renew/release lease · "
+                     "Ctrl+Shift+P power save · 2 collapse")
+        elif within(self.telemetry_panel):
+            hints = "p pin · e export history · r reset session · u units · space pause · 3 collapse"
+        else:
+            hints = ("d overview · w channels · h hotspot · l LAN · Shift+B Bluetooth · f flows · c diagnose · "
+                     "g troubleshoot · Ctrl+P switch profile · Ctrl+Z undo · a airplane · 1/2/3 panels · "
+                     "Ctrl+←/→ resize · Ctrl+L logs · space pause")
+            if len(self.fleet) > 1:
+                hints += " · Ctrl+H host"
+        self.keys_label.setText(hints)
     
     def update_telemetry(self):
         """Update telemetry data"""
+        if self.overview_active:
+            self.update_overview()
+            return
+        
         if not self.selected_interface:
             return
             
         try:
             # Get fresh data for selected interface
-            interfaces = NetworkDiscovery.discover_interfaces()
+            interfaces = self._discover_interfaces()
             updated_interface = next(
                 (i for i in interfaces if i.name == self.selected_interface.name),
                 None
             )
             
             if updated_interface:
-                # Update speed calculations
-                self.network_discovery.update_speeds([updated_interface])
+                updated_interface.managed = self.selected_interface.managed
+                
+                pinned = next((i for i in interfaces if i.name == self.pinned_interface), None)
+                
+                # Update speed calculations; both in one call so they share a time base
+                self.network_discovery.update_speeds([i for i in (updated_interface, pinned) if i])
+                
+                # Only the daemon knows when carrier came up
+                uptimes = self.daemon.try_request("GetUptimes", default={})
+                updated_interface.metrics.uptime = uptimes.get(updated_interface.name)
                 
                 # Update telemetry panel
                 self.telemetry_panel.update_metrics(updated_interface.metrics)
                 self.selected_interface = updated_interface
+                if self.pinned_interface:
+                    self.telemetry_panel.update_pinned(pinned.metrics if pinned else None)
+                
+                # Session totals live in the daemon so they survive GUI restarts
+                sessions = self.daemon.try_request("GetSessionCounters", default=[])
+                self.telemetry_panel.update_session(
+                    next((s for s in sessions if s["interface"] == updated_interface.name), None)
+                )
+                self.telemetry_panel.update_sla(self.daemon.try_request("GetSlaProbes", default=[]))
                 
         except Exception as e:
-            print(f"Error updating telemetry: {e}")
+            logger.warning(f"Error updating telemetry: {e}")
+    
+    def toggle_pin(self):
+        """Pin the selected interface for comparison, or unpin"""
+        if self.pinned_interface:
+            self.toast.show_message(f"Unpinned {self.pinned_interface}")
+            self.pinned_interface = None
+        elif self.selected_interface is None:
+            self.toast.show_message("Select an interface first")
+            return
+        else:
+            self.pinned_interface = self.selected_interface.name
+            self.toast.show_message(f"Pinned {self.pinned_interface}; select another interface to compare")
+        self.telemetry_panel.set_pinned(self.pinned_interface)
+        self.measure_latency()
+    
+    def measure_latency(self):
+        """Ping the gateways of the selected and pinned interfaces in the background"""
+        if not self.host.local:
+            # From here the pings would time a different path altogether
+            return
+        interfaces = {i.name: i for i in self.interface_panel.interfaces}
+        for name in {self.pinned_interface, getattr(self.selected_interface, "name", None)}:
+            interface = interfaces.get(name)
+            if interface is None or not interface.gateway or interface.status != "Connected":
+                continue
+            worker = self.latency_workers.get(name)
+            if worker and worker.isRunning():
+                continue
+            worker = AsyncWorker(NetworkControl.ping, interface.gateway, name)
+            worker.finished.connect(
+                lambda success, result, name=name: self.telemetry_panel.set_latency(
+                    name, float(result) if success and result != "None" else None
+                )
+            )
+            self.latency_workers[name] = worker
+            worker.start()
+    
+    def update_overview(self):
+        """Refresh every tile of the overview grid"""
+        try:
+            interfaces = self._discover_interfaces()
+            self._mark_unmanaged(interfaces)
+            self.overview_discovery.update_speeds(interfaces)
+            self.overview_panel.update_interfaces(interfaces)
+        except Exception as e:
+            logger.warning(f"Error updating overview: {e}")
+    
+    def toggle_overview(self):
+        """Show all interfaces at once, or go back to the selected one"""
+        self.overview_active = not self.overview_active
+        self.spectrum_active = False
+        self.hotspot_active = False
+        self.lan_active = False
+        self.bluetooth_active = False
+        self.flows_active = False
+        self.roaming_active = False
+        self._show_current_view()
+        self._update_key_hints()
+        self.update_telemetry()
+    
+    def toggle_spectrum(self):
+        """Show WiFi channel usage, or go back to the panels"""
+        self.spectrum_active = not self.spectrum_active
+        self.overview_active = False
+        self.hotspot_active = False
+        self.lan_active = False
+        self.bluetooth_active = False
+        self.flows_active = False
+        self.roaming_active = False
+        self._show_current_view()
+        self._update_key_hints()
+    
+    def toggle_hotspot(self):
+        """Show the stations on our hotspots, or go back to the panels"""
+        self.hotspot_active = not self.hotspot_active
+        self.overview_active = False
+        self.spectrum_active = False
+        self.lan_active = False
+        self.bluetooth_active = False
+        self.flows_active = False
+        self.roaming_active = False
+        self._show_current_view()
+        self._update_key_hints()
+        if self.hotspot_active:
+            self.hotspot_view.client_list.setFocus()
+    
+    def toggle_lan(self):
+        """Scan the subnet and list its devices, or go back to the panels"""
+        self.lan_active = not self.lan_active
+        self.overview_active = False
+        self.spectrum_active = False
+        self.hotspot_active = False
+        self.bluetooth_active = False
+        self.flows_active = False
+        self.roaming_active = False
+        self._show_current_view()
+        self._update_key_hints()
+        if self.lan_active:
+            self.lan_view.device_list.setFocus()
+    
+    def toggle_bluetooth(self):
+        """List nearby and paired Bluetooth devices, or go back to the panels"""
+        self.bluetooth_active = not self.bluetooth_active
+        self.overview_active = False
+        self.spectrum_active = False
+        self.hotspot_active = False
+        self.lan_active = False
+        self.flows_active = False
+        self.roaming_active = False
+        self._show_current_view()
+        self._update_key_hints()
+        if self.bluetooth_active:
+            self.bluetooth_view.device_list.setFocus()
+    
+    def toggle_flows(self):
+        """Show active connections and hotspot NAT, or go back to the panels"""
+        self.flows_active = not self.flows_active
+        self.overview_active = False
+        self.spectrum_active = False
+        self.hotspot_active = False
+        self.lan_active = False
+        self.bluetooth_active = False
+        self.roaming_active = False
+        self._show_current_view()
+        self._update_key_hints()
+        if self.flows_active:
+            self.flows_view.host_list.setFocus()
+    
+    def toggle_roaming(self):
+        """Show the WiFi association timeline, or go back to the panels"""
+        self.roaming_active = not self.roaming_active
+        self.overview_active = False
+        self.spectrum_active = False
+        self.hotspot_active = False
+        self.lan_active = False
+        self.bluetooth_active = False
+        self.flows_active = False
+        self._show_current_view()
+        self._update_key_hints()
+        if self.roaming_active:
+            self.roaming_view.timeline_list.setFocus()
+    
+    def open_from_overview(self, interface):
+        """Leave the overview with the clicked interface selected"""
+        self.overview_active = False
+        self._show_current_view()
+        self._update_key_hints()
+        self.interface_panel.on_interface_clicked(interface)
+        if self.layout_mode == "compact":
+            self.panel_tabs.setCurrentWidget(self.telemetry_panel)
+    
+    def _show_current_view(self):
+        if self.layout_mode == "tiny":
+            self.view_stack.setCurrentWidget(self.too_small_label)
+        elif self.overview_active:
+            self.view_stack.setCurrentWidget(self.overview_panel)
+        elif self.spectrum_active:
+            self.view_stack.setCurrentWidget(self.spectrum_view)
+        elif self.hotspot_active:
+            self.view_stack.setCurrentWidget(self.hotspot_view)
+        elif self.lan_active:
+            self.view_stack.setCurrentWidget(self.lan_view)
+        elif self.bluetooth_active:
+            self.view_stack.setCurrentWidget(self.bluetooth_view)
+        elif self.flows_active:
+            self.view_stack.setCurrentWidget(self.flows_view)
+        elif self.roaming_active:
+            self.view_stack.setCurrentWidget(self.roaming_view)
+        elif self.layout_mode == "compact":
+            self.view_stack.setCurrentWidget(self.panel_tabs)
+        else:
+            self.view_stack.setCurrentWidget(self.splitter)
+    
+    def _layout_mode_for(self, width: int, height: int) -> str:
+        metrics = self.fontMetrics()
+        columns = width // max(1, metrics.averageCharWidth())
+        rows = height // max(1, metrics.height())
+        if columns < MIN_COLUMNS or rows < MIN_ROWS:
+            return "tiny"
+        return "compact" if columns < COMPACT_COLUMNS else "wide"
+    
+    def resizeEvent(self, event):
+        """Switch between side-by-side panels, tabs and the too-small notice"""
+        super().resizeEvent(event)
+        mode = self._layout_mode_for(event.size().width(), event.size().height())
+        if mode == self.layout_mode:
+            return
+        
+        # Panels only move between splitter and tabs; "tiny" leaves them where they are
+        if mode == "compact" and self.panel_tabs.count() == 0:
+            for panel, title in self.panels:
+                self.panel_tabs.addTab(panel, title)
+        elif mode == "wide" and self.panel_tabs.count() > 0:
+            self.panel_tabs.clear()
+            for panel, _ in self.panels:
+                self.splitter.addWidget(panel)
+            self.splitter.setSizes(self._saved_sizes())
+        
+        self.layout_mode = mode
+        self._apply_collapsed()
+        self._show_current_view()
+    
+    def toggle_panel(self, index: int):
+        """Collapse a panel, or bring it back; one always stays"""
+        title = self.panels[index][1]
+        if index in self.collapsed:
+            self.collapsed.discard(index)
+        elif len(self.collapsed) == len(self.panels) - 1:
+            self.toast.show_message(f"{title} is the last panel showing")
+            return
+        else:
+            self.collapsed.add(index)
+        self._apply_collapsed()
+        self._save_layout()
+        self.toast.show_message(f"{title} {'collapsed' if index in self.collapsed else 'restored'} "
+                                f"({index + 1} to toggle)")
+    
+    def _apply_collapsed(self):
+        for index, (panel, _) in enumerate(self.panels):
+            visible = index not in self.collapsed
+            if self.panel_tabs.count() > 0:
+                self.panel_tabs.setTabVisible(self.panel_tabs.indexOf(panel), visible)
+            else:
+                panel.setVisible(visible)
+    
+    def move_split(self, handle: int, step: int):
+        """Move a splitter handle; positive steps go right"""
+        if self.layout_mode != "wide":
+            return
+        sizes = self.splitter.sizes()
+        left, right = handle - 1, handle
+        # A collapsed panel has size 0; move the handle of the nearest visible neighbours
+        while left > 0 and sizes[left] == 0:
+            left -= 1
+        while right < len(sizes) - 1 and sizes[right] == 0:
+            right += 1
+        if sizes[left] == 0 or sizes[right] == 0:
+            return
+        step = max(-sizes[left] + SPLIT_STEP, min(step, sizes[right] - SPLIT_STEP))
+        sizes[left] += step
+        sizes[right] -= step
+        self.splitter.setSizes(sizes)
+        self._save_layout()
+    
+    def _saved_sizes(self):
+        if self.settings is None:
+            return DEFAULT_PANEL_SIZES
+        sizes = self.settings.value("layout/sizes", DEFAULT_PANEL_SIZES)
+        try:
+            sizes = [int(size) for size in sizes]
+        except (TypeError, ValueError):
+            return DEFAULT_PANEL_SIZES
+        return sizes if len(sizes) == 3 and sum(sizes) > 0 else DEFAULT_PANEL_SIZES
+    
+    def _saved_collapsed(self):
+        if self.settings is None:
+            return set()
+        value = self.settings.value("layout/collapsed", "")
+        names = value if isinstance(value, list) else [n for n in str(value).split(",") if n]
+        titles = [title.lower() for _, title in self.panels]
+        collapsed = {titles.index(name) for name in names if name in titles}
+        # Never start with nothing to show
+        return collapsed if len(collapsed) < len(self.panels) else set()
+    
+    def _save_layout(self, *args):
+        """Remember splitter sizes and collapsed panels in the config file"""
+        if self.settings is None:
+            return
+        if self.layout_mode == "wide":
+            # Collapsed panels report 0; keep their last width so they come back sensibly
+            saved = self._saved_sizes()
+            sizes = [size or saved[index] for index, size in enumerate(self.splitter.sizes())]
+            self.settings.setValue("layout/sizes", sizes)
+        self.settings.setValue("layout/collapsed",
+                               ",".join(self.panels[index][1].lower() for index in sorted(self.collapsed)))
     
     def on_interface_selected(self, interface):
         """Handle interface selection"""
         self.selected_interface = interface
         self.management_panel.update_interface(interface)
         self.telemetry_panel.set_active(interface.status == "Connected")
+        self.telemetry_panel.set_interface(interface.name)
         
         if interface.status == "Connected":
             self.telemetry_panel.update_metrics(interface.metrics)
         
-        self.statusBar().showMessage(f"Selected: {interface.name} ({interface.status})")
+        self.statusBar().showMessage(f"Selected: {interface.name} ({interface.status_text})", STATUS_MESSAGE_MS)
+        self._update_daemon_state()
+    
+    def cycle_units(self):
+        """Switch to the next unit style and remember it"""
+        self.units = self.units.next()
+        self.interface_panel.set_units(self.units)
+        self.telemetry_panel.set_units(self.units)
+        self.overview_panel.set_units(self.units)
+        self.hotspot_view.set_units(self.units)
+        self.flows_view.set_units(self.units)
+        if self.settings is not None:
+            self.settings.setValue("display/units", self.units.name)
+        self.toast.show_message(f"Units: {self.units.name} ({self.units.format_rate(1536000)})")
+    
+    def toggle_airplane_mode(self):
+        """Turn all radios off, or restore them"""
+        status = self.daemon.try_request("GetStatus")
+        if status is None:
+            self.toast.show_message("Airplane mode needs alopexd")
+            return
+        enabled = not status.get("airplane_mode")
+        try:
+            self.daemon.request("SetAirplaneMode", enabled=enabled)
+            self.toast.show_message("Airplane mode on: all radios off" if enabled
+                                    else "Airplane mode off: radios restored")
+        except IpcError as e:
+            self.toast.show_message(str(e))
+        self._update_daemon_state()
+        self.refresh_interfaces()
+    
+    def reset_session_counters(self):
+        """Zero the selected interface's session totals in the daemon"""
+        if self.selected_interface is None:
+            self.toast.show_message("Select an interface first")
+            return
+        name = self.selected_interface.name
+        result = self.daemon.try_request("ResetSessionCounters", interface=name)
+        if result is None:
+            self.toast.show_message(f"No session to reset on {name}")
+            return
+        self.toast.show_message(f"Session counters reset on {name}")
+        self.update_telemetry()
+    
+    def cycle_sort(self):
+        """Order the interface list by the next key in SORT_MODES"""
+        panel = self.interface_panel
+        mode = SORT_MODES[(SORT_MODES.index(panel.sort_mode) + 1) % len(SORT_MODES)]
+        panel.set_sort(mode, panel.connected_first)
+        self._save_sort()
+        self.toast.show_message(f"Interfaces sorted by {mode}")
+    
+    def toggle_connected_first(self):
+        """Keep connected interfaces at the top regardless of sort key"""
+        panel = self.interface_panel
+        panel.set_sort(panel.sort_mode, not panel.connected_first)
+        self._save_sort()
+        self.toast.show_message("Connected interfaces first" if panel.connected_first else "Connected first off")
+    
+    def _save_sort(self):
+        if self.settings is not None:
+            self.settings.setValue("display/sort", self.interface_panel.sort_mode)
+            self.settings.setValue("display/connected_first", self.interface_panel.connected_first)
+    
+    def show_interface_details(self):
+        """Open the attribute dump for the selected interface"""
+        if self.selected_interface is None:
+            self.toast.show_message("Select an interface first")
+            return
+        # A virbr bridge also lists the guests libvirt has on it
+        network = None
+        if self.selected_interface.virtual_network:
+            reply = self.daemon.try_request("GetVirtualNetworks", bridge=self.selected_interface.name, default={})
+            network = next(iter(reply.get("networks", [])), None)
+        # Sysctls its profile sets, as they are now
+        sysctls = self.daemon.try_request("GetSysctls", interface=self.selected_interface.name,
+                                          default={}).get("sysctls")
+        InterfaceDetailDialog(self.selected_interface, self, virtual_network=network, sysctls=sysctls).exec()
+    
+    def copy_interface_field(self, field):
+        """Copy one detail of the selected interface and confirm it in a toast"""
+        labels = {"ip": "IP", "gateway": "gateway", "dns": "DNS servers", "mac": "MAC"}
+        interface = self.selected_interface
+        if interface is None:
+            self.toast.show_message("Select an interface first")
+            return
+        
+        value = getattr(interface, field)
+        if field == "dns":
+            value = ", ".join(value)
+        if not value:
+            self.toast.show_message(f"{interface.name} has no {labels[field]}")
+            return
+        self._copied(f"{interface.name} {labels[field]}", value)
+    
+    def copy_public_ip(self):
+        """Look up the public IP in the background, then copy it"""
+        if self.public_ip_worker and self.public_ip_worker.isRunning():
+            return
+        if not self.host.local:
+            self.toast.show_message(f"The public IP can only be looked up from this machine, not {self.host.name}")
+            return
+        self.toast.show_message("Looking up public IP...")
+        self.public_ip_worker = AsyncWorker(NetworkControl.get_public_ip)
+        self.public_ip_worker.finished.connect(self.on_public_ip)
+        self.public_ip_worker.start()
+    
+    def on_public_ip(self, success, result):
+        if success and result != "None":
+            self._copied("public IP", result)
+        else:
+            self.toast.show_message("Could not determine the public IP")
+    
+    def probe_port(self):
+        """Ask for host:port and have the daemon try it from the selected interface"""
+        if self.probe_worker and self.probe_worker.isRunning():
+            return
+        interface = self.selected_interface.name if self.selected_interface else None
+        text, accepted = QInputDialog.getText(
+            self, "Probe port", f"host:port to reach from {interface or 'any interface'}:", text=self.last_probe_target
+        )
+        if not accepted or not text.strip():
+            return
+        try:
+            host, port = parse_target(text)
+        except ValueError as e:
+            self.toast.show_message(str(e))
+            return
+        self.last_probe_target = text.strip()
+        
+        async def probe():
+            # The reply only comes once the connection is decided
+            client = self.daemon.with_timeout(self.daemon.timeout + PROBE_TIMEOUT)
+            return probe_summary(client.request("ProbePort", host=host, port=port, interface=interface))
+        
+        self.toast.show_message(f"Probing {host}:{port}...")
+        self.probe_worker = AsyncWorker(probe)
+        self.probe_worker.finished.connect(lambda success, result: self.toast.show_message(result))
+        self.probe_worker.start()
+    
+    def diagnose_connectivity(self):
+        """Have the daemon compare IPv4 and IPv6, then show its report"""
+        if self.diagnose_worker and self.diagnose_worker.isRunning():
+            return
+        
+        async def diagnose():
+            return connectivity_report(self.daemon.run_job("DiagnoseConnectivity", timeout=60.0))
+        
+        def finished(success, result):
+            if success:
+                ConnectivityReportDialog(result, self).exec()
+            else:
+                self.toast.show_message(f"Diagnosis failed: {result}")
+        
+        self.toast.show_message("Diagnosing connectivity...")
+        self.diagnose_worker = AsyncWorker(diagnose)
+        self.diagnose_worker.finished.connect(finished)
+        self.diagnose_worker.start()
+    
+    def troubleshoot(self):
+        """Walk the selected (or the primary) interface through the troubleshooter"""
+        interface = self.selected_interface.name if self.selected_interface else None
+        TroubleshootDialog(self.daemon, interface, self).exec()
+    
+    def undo_last_change(self):
+        """Ask the daemon to revert its most recent configuration change"""
+        try:
+            undone = self.daemon.request("Undo")
+            self.toast.show_message(f"Undid: {undone['undone']['summary']}")
+        except IpcError as e:
+            self.toast.show_message(str(e))
+            return
+        self.refresh_interfaces()
+    
+    def quick_switch(self):
+        """Activate a profile or switch location from the keyboard"""
+        QuickSwitchDialog(self.daemon, self).exec()
+        self.refresh_interfaces()
+    
+    def export_bandwidth(self):
+        """Save the selected interface's bandwidth history for a spreadsheet or a report"""
+        if self.selected_interface is None:
+            self.toast.show_message("Select an interface first")
+            return
+        name = self.selected_interface.name
+        spans = {"Last hour": "1h", "Last 24 hours": "24h", "Last 7 days": "7d", "Everything": None}
+        span, accepted = QInputDialog.getItem(self, "Export Bandwidth History", f"History of {name}:",
+                                              list(spans), 1, False)
+        if not accepted:
+            return
+        path, _ = QFileDialog.getSaveFileName(
+            self, "Export Bandwidth History", f"{name}-bandwidth.csv", "CSV (*.csv);;JSON (*.json)"
+        )
+        if not path:
+            return
+        try:
+            history = self.daemon.request("GetBandwidthHistory", interface=name,
+                                          since=parse_time(spans[span]) if spans[span] else None)
+            with open(path, "w") as f:
+                f.write(json.dumps(history, indent=2) if path.endswith(".json") else to_csv(history["samples"]))
+        except IpcError as e:
+            self.toast.show_message(str(e))
+            return
+        except OSError as e:
+            self.toast.show_message(f"Could not write {path}: {e.strerror}")
+            return
+        self.toast.show_message(f"Exported {len(history['samples'])} minutes of {name} to {path}")
+    
+    def _copied(self, what, value):
+        where = copy_text(value)
+        suffix = " (via terminal)" if where == "terminal" else ""
+        self.toast.show_message(f"Copied {what}: {value}{suffix}")
     
     def closeEvent(self, event):
         """Handle window close event"""
@@ -3,7 +3,9 @@ Management Panel - Network interface configuration and control
 Professional interface management that NetworkManager wishes it had
 """
 
+import time
 import asyncio
+from typing import Optional
 from PyQt6.QtWidgets import (
     QWidget, QVBoxLayout, QHBoxLayout, QLabel, QFrame, QPushButton,
     QGroupBox, QFormLayout, QLineEdit, QComboBox, QCheckBox, QSpacerItem,
@@ -11,12 +13,20 @@ from PyQt6.QtWidgets import (
     QProgressBar, QMessageBox
 )
 from PyQt6.QtCore import Qt, pyqtSignal, QThread, QTimer
-from PyQt6.QtGui import QFont, QPalette, QColor
+from PyQt6.QtGui import QFont, QPalette, QColor, QKeySequence, QShortcut
 
-from network.discovery import NetworkInterface
+from network.discovery import InterfaceType, NetworkInterface
+from network.oui import lookup_vendor, is_locally_administered
 from network.system_integration import NetworkControl, BluetoothControl
 from network.wifi import WiFiManager, WiFiNetwork
 from network.vpn import VpnManager, VpnConfig
+from network.ipc import IpcClient
+from network.spectrum import regulatory_summary
+from network.cable_test import PAIRS, describe
+from network.transceiver import format_power
+from network.nic_queues import format_cpu_list
+
+from .clipboard import copy_text
 
 class AsyncWorker(QThread):
     """Background worker for async operations"""
@@ -397,14 +407,751 @@ class WiFiConfigCard(ConfigurationCard):
         else:
             QMessageBox.critical(self, "Connection Failed", message)
 
+class HardwareAddressCard(ConfigurationCard):
+    """Current and permanent MAC address with the vendor behind it"""
+    
+    def __init__(self, interface: NetworkInterface):
+        super().__init__("Hardware Address")
+        self.interface = interface
+        self.setup_address_rows()
+        
+    def setup_address_rows(self):
+        form = QFormLayout()
+        form.setSpacing(8)
+        
+        permanent = self.interface.permanent_mac
+        current = self.interface.mac
+        if permanent and permanent != current:
+            current += "  (changed)"
+        form.addRow("MAC Address:", self._value_label(current))
+        form.addRow("Permanent:", self._value_label(permanent or "unknown"))
+        
+        vendor = lookup_vendor(permanent or self.interface.mac)
+        if vendor is None and is_locally_administered(permanent or self.interface.mac):
+            vendor = "locally administered"
+        form.addRow("Vendor:", self._value_label(vendor or "unknown"))
+        self.content_layout.addLayout(form)
+        
+        self.copy_button = QPushButton("Copy MAC  (Ctrl+Shift+M)")
+        self.copy_button.clicked.connect(self.copy_mac)
+        self.copy_button.setStyleSheet("""
+            QPushButton {
+                background: #2c3e50;
+                color: #ecf0f1;
+                border: 1px solid #4a6473;
+                border-radius: 6px;
+                padding: 6px 14px;
+                font-size: 10pt;
+            }
+            QPushButton:hover {
+                border: 1px solid #3498db;
+            }
+        """)
+        button_layout = QHBoxLayout()
+        button_layout.addWidget(self.copy_button)
+        button_layout.addStretch()
+        self.content_layout.addLayout(button_layout)
+        
+    def _value_label(self, text: str) -> QLabel:
+        label = QLabel(text)
+        label.setTextInteractionFlags(Qt.TextInteractionFlag.TextSelectableByMouse)
+        label.setStyleSheet("color: #ecf0f1; font-family: monospace; font-size: 10pt;")
+        return label
+        
+    def copy_mac(self):
+        """Put the current MAC address on the clipboard"""
+        copy_text(self.interface.mac)
+        self.copy_button.setText("Copied")
+        QTimer.singleShot(1500, lambda: self.copy_button.setText("Copy MAC  (Ctrl+Shift+M)"))
+
+class WiFiRadioCard(ConfigurationCard):
+    """Kill switch, regulatory domain and the channels it lets this radio use, plus power save, from alopexd"""
+    
+    radio_changed = pyqtSignal()
+    
+    # What to do about each kind of block
+    BLOCK_HINTS = {
+        "hard": "Blocked by a hardware switch or key (often Fn plus a function key, or a BIOS setting). "
+                "Only the switch can turn it back on.",
+        "soft": "Switched off in software."
+    }
+    
+    def __init__(self, interface: NetworkInterface, domain: dict, power: dict, radio: Optional[dict],
+                 daemon: IpcClient):
+        super().__init__("Radio")
+        self.interface = interface
+        self.domain = domain
+        self.power = power
+        self.radio = radio
+        self.daemon = daemon
+        self.worker = None
+        self.setup_radio_rows()
+        
+    def setup_radio_rows(self):
+        form = QFormLayout()
+        form.setSpacing(8)
+        
+        block = self.radio.get("block") if self.radio else None
+        if block:
+            hint = self._value_label(self.BLOCK_HINTS[block])
+            hint.setWordWrap(True)
+            hint.setStyleSheet("color: #e74c3c; font-size: 10pt;")
+            form.addRow("Kill Switch:", hint)
+            if block == "soft":
+                self.unblock_button = QPushButton("Turn Radio On")
+                self.unblock_button.setStyleSheet("""
+                    QPushButton {
+                        background: #2c3e50;
+                        color: #ecf0f1;
+                        border: 1px solid #4a6473;
+                        border-radius: 6px;
+                        padding: 6px 14px;
+                        font-size: 10pt;
+                    }
+                    QPushButton:hover {
+                        border: 1px solid #3498db;
+                    }
+                """)
+                self.unblock_button.clicked.connect(self.unblock)
+                self.unblock_button.setVisible(self.daemon.can("SetRfkill"))
+                form.addRow("", self.unblock_button)
+        
+        country = self.domain.get("country") or "unknown"
+        if country == "00":
+            country = "00 (world: 5 GHz listen only; set a country with alopex-cli wifi country)"
+        elif self.domain.get("dfs_region"):
+            country += f" ({self.domain['dfs_region']})"
+        form.addRow("Regulatory Domain:", self._value_label(country))
+        
+        for band in regulatory_summary(self.domain.get("channels", [])):
+            lines = [band["usable"] or "none"]
+            if band["listen_only"]:
+                lines.append(f"listen only: {band['listen_only']}")
+            if band["radar"]:
+                lines.append(f"radar (DFS): {band['radar']}")
+            label = self._value_label("\n".join(lines))
+            label.setWordWrap(True)
+            form.addRow(f"{band['band']} Channels:", label)
+        
+        # Saved on the active profile, so it comes back on the next connect
+        self.power_checkbox = QCheckBox("Power save  (Ctrl+Shift+P)")
+        self.power_checkbox.setChecked(bool(self.power.get("power_save")))
+        self.power_checkbox.setEnabled(bool(self.power.get("profile")) and self.daemon.can("SetPowerSave"))
+        self.power_checkbox.setToolTip("Off avoids the latency spikes of the radio dozing between beacons, "
+                                       "at some battery cost")
+        self.power_checkbox.setStyleSheet("QCheckBox { color: #ecf0f1; font-size: 10pt; }")
+        self.power_checkbox.clicked.connect(self.set_power_save)
+        form.addRow("Power Management:", self.power_checkbox)
+        self.content_layout.addLayout(form)
+        
+    def _value_label(self, text: str) -> QLabel:
+        label = QLabel(text)
+        label.setTextInteractionFlags(Qt.TextInteractionFlag.TextSelectableByMouse)
+        label.setStyleSheet("color: #ecf0f1; font-family: monospace; font-size: 10pt;")
+        return label
+        
+    def unblock(self):
+        """Lift this radio's soft block"""
+        if self.worker and self.worker.isRunning():
+            return
+        index = str(self.radio["index"])
+        
+        async def request():
+            return self.daemon.request("SetRfkill", target=index, blocked=False)
+        
+        self.worker = AsyncWorker(request)
+        self.worker.finished.connect(self.on_unblocked)
+        self.worker.start()
+        self.unblock_button.setEnabled(False)
+        
+    def on_unblocked(self, success, message):
+        if not success:
+            QMessageBox.critical(self, "Radio", message)
+            self.unblock_button.setEnabled(True)
+            return
+        self.radio_changed.emit()
+        
+    def toggle_power_save(self):
+        """Flip power save, as the shortcut does"""
+        if self.power_checkbox.isEnabled():
+            self.power_checkbox.setChecked(not self.power_checkbox.isChecked())
+            self.set_power_save()
+        
+    def set_power_save(self):
+        """Apply the checkbox now and save it on the active profile"""
+        if self.worker and self.worker.isRunning():
+            return
+        enabled = self.power_checkbox.isChecked()
+        
+        async def request():
+            return self.daemon.request("SetPowerSave", interface=self.interface.name, enabled=enabled)
+        
+        self.worker = AsyncWorker(request)
+        self.worker.finished.connect(self.on_power_save_set)
+        self.worker.start()
+        self.power_checkbox.setEnabled(False)
+        
+    def on_power_save_set(self, success, message):
+        if not success:
+            QMessageBox.critical(self, "Power Save", message)
+            self.power_checkbox.setChecked(not self.power_checkbox.isChecked())
+        self.power_checkbox.setEnabled(True)
+
+class DhcpLeaseCard(ConfigurationCard):
+    """The interface's DHCP lease from alopexd, counting down, with renew and release"""
+    
+    lease_changed = pyqtSignal()
+    
+    def __init__(self, interface: NetworkInterface, lease: dict, daemon: IpcClient):
+        super().__init__("DHCP Lease")
+        self.interface = interface
+        self.lease = lease
+        self.daemon = daemon
+        self.fetched_at = time.monotonic()
+        self.worker = None
+        self.setup_lease_rows()
+        
+        self.countdown_timer = QTimer(self)
+        self.countdown_timer.timeout.connect(self.update_countdown)
+        self.countdown_timer.start(1000)
+        
+    def setup_lease_rows(self):
+        form = QFormLayout()
+        form.setSpacing(8)
+        form.addRow("Server:", self._value_label(self.lease.get("server") or "unknown"))
+        form.addRow("Address:", self._value_label(self.lease.get("ip_address") or "none"))
+        form.addRow("Lease Time:", self._value_label(self._duration(self.lease.get("lease_time"))))
+        self.renew_label = self._value_label("")
+        form.addRow("Renews In:", self.renew_label)
+        self.expires_label = self._value_label("")
+        form.addRow("Expires In:", self.expires_label)
+        
+        options = self.lease.get("options") or {}
+        if options:
+            options_label = self._value_label("\n".join(f"{key} = {value}" for key, value in sorted(options.items())))
+            options_label.setWordWrap(True)
+            form.addRow("Options:", options_label)
+        self.content_layout.addLayout(form)
+        self.update_countdown()
+        
+        self.renew_button = QPushButton("Renew  (Ctrl+Shift+R)")
+        self.renew_button.clicked.connect(self.renew)
+        self.release_button = QPushButton("Release  (Ctrl+Shift+L)")
+        self.release_button.clicked.connect(self.release)
+        self.renew_button.setVisible(self.daemon.can("RenewDhcpLease"))
+        self.release_button.setVisible(self.daemon.can("ReleaseDhcpLease"))
+        button_layout = QHBoxLayout()
+        for button in (self.renew_button, self.release_button):
+            button.setStyleSheet("""
+                QPushButton {
+                    background: #2c3e50;
+                    color: #ecf0f1;
+                    border: 1px solid #4a6473;
+                    border-radius: 6px;
+                    padding: 6px 14px;
+                    font-size: 10pt;
+                }
+                QPushButton:hover {
+                    border: 1px solid #3498db;
+                }
+                QPushButton:disabled {
+                    color: #7f8c8d;
+                }
+            """)
+            button_layout.addWidget(button)
+        button_layout.addStretch()
+        self.content_layout.addLayout(button_layout)
+        
+    def _value_label(self, text: str) -> QLabel:
+        label = QLabel(text)
+        label.setTextInteractionFlags(Qt.TextInteractionFlag.TextSelectableByMouse)
+        label.setStyleSheet("color: #ecf0f1; font-family: monospace; font-size: 10pt;")
+        return label
+        
+    @staticmethod
+    def _duration(seconds) -> str:
+        if seconds is None:
+            return "unknown"
+        seconds = int(seconds)
+        return f"{seconds // 3600}h {seconds % 3600 // 60}m {seconds % 60}s"
+        
+    def update_countdown(self):
+        """Tick the renew and expiry timers down from when the lease was fetched"""
+        elapsed = time.monotonic() - self.fetched_at
+        for label, key in ((self.renew_label, "renew_in"), (self.expires_label, "expires_in")):
+            remaining = self.lease.get(key)
+            label.setText(self._duration(max(0, remaining - elapsed)) if remaining is not None else "unknown")
+        
+    def renew(self):
+        """Renew the lease now"""
+        self._run("RenewDhcpLease", self.renew_button, "Renewing...")
+        
+    def release(self):
+        """Release the lease after confirming; the interface loses its address"""
+        if self.release_button.isHidden():
+            return
+        answer = QMessageBox.question(
+            self, "Release DHCP Lease",
+            f"Release the lease on {self.interface.name}? It will have no address until it reconnects."
+        )
+        if answer == QMessageBox.StandardButton.Yes:
+            self._run("ReleaseDhcpLease", self.release_button, "Releasing...")
+        
+    def _run(self, request_type: str, button: QPushButton, busy_text: str):
+        if self.worker and self.worker.isRunning() or button.isHidden():
+            return
+        
+        async def request():
+            return self.daemon.request(request_type, interface=self.interface.name)
+        
+        self.worker = AsyncWorker(request)
+        self.worker.finished.connect(self.on_request_complete)
+        self.worker.start()
+        self.renew_button.setEnabled(False)
+        self.release_button.setEnabled(False)
+        button.setText(busy_text)
+        
+    def on_request_complete(self, success, message):
+        """Show failures; on success the panel reloads the lease"""
+        if not success:
+            QMessageBox.critical(self, "DHCP Lease", message)
+            self.renew_button.setText("Renew  (Ctrl+Shift+R)")
+            self.release_button.setText("Release  (Ctrl+Shift+L)")
+            self.renew_button.setEnabled(True)
+            self.release_button.setEnabled(True)
+        self.lease_changed.emit()
+
+class CableTestCard(ConfigurationCard):
+    """TDR test of the wired link's four pairs through alopexd, with each pair's result and fault distance"""
+    
+    def __init__(self, interface: NetworkInterface, daemon: IpcClient):
+        super().__init__("Cable Test")
+        self.interface = interface
+        self.daemon = daemon
+        self.worker = None
+        self.result = None
+        self.setup_test_rows()
+        
+    def setup_test_rows(self):
+        form = QFormLayout()
+        form.setSpacing(8)
+        self.pair_labels = {}
+        for pair in PAIRS:
+            self.pair_labels[pair] = self._value_label("not tested")
+            form.addRow(f"Pair {pair}:", self.pair_labels[pair])
+        self.content_layout.addLayout(form)
+        self.summary_label = QLabel("The link drops for a few seconds while the test runs")
+        self.summary_label.setWordWrap(True)
+        self.summary_label.setStyleSheet("color: #7f8c8d; font-size: 10pt;")
+        self.content_layout.addWidget(self.summary_label)
+        
+        self.test_button = QPushButton("Test Cable")
+        self.test_button.setStyleSheet("""
+            QPushButton {
+                background: #2c3e50;
+                color: #ecf0f1;
+                border: 1px solid #4a6473;
+                border-radius: 6px;
+                padding: 6px 14px;
+                font-size: 10pt;
+            }
+            QPushButton:hover {
+                border: 1px solid #3498db;
+            }
+            QPushButton:disabled {
+                color: #7f8c8d;
+            }
+        """)
+        self.test_button.clicked.connect(self.run_test)
+        self.test_button.setVisible(self.daemon.can("RunCableTest"))
+        button_layout = QHBoxLayout()
+        button_layout.addWidget(self.test_button)
+        button_layout.addStretch()
+        self.content_layout.addLayout(button_layout)
+        
+    def _value_label(self, text: str) -> QLabel:
+        label = QLabel(text)
+        label.setTextInteractionFlags(Qt.TextInteractionFlag.TextSelectableByMouse)
+        label.setStyleSheet("color: #ecf0f1; font-family: monospace; font-size: 10pt;")
+        return label
+        
+    def run_test(self):
+        """Run the test after confirming, since it takes the link down"""
+        if self.worker and self.worker.isRunning():
+            return
+        if self.interface.status == "Connected":
+            answer = QMessageBox.question(
+                self, "Test Cable",
+                f"Testing the cable drops the link on {self.interface.name} for a few seconds. Continue?"
+            )
+            if answer != QMessageBox.StandardButton.Yes:
+                return
+        
+        async def request():
+            self.result = self.daemon.run_job("RunCableTest", timeout=30.0, interface=self.interface.name)
+            return self.result["summary"]
+        
+        self.worker = AsyncWorker(request)
+        self.worker.finished.connect(self.on_test_complete)
+        self.worker.start()
+        self.test_button.setEnabled(False)
+        self.test_button.setText("Testing...")
+        
+    def on_test_complete(self, success, message):
+        """Fill in each pair, faults in red"""
+        self.test_button.setText("Test Cable")
+        self.test_button.setEnabled(True)
+        if not success:
+            QMessageBox.critical(self, "Cable Test", message)
+            return
+        
+        for pair in self.result["pairs"]:
+            label = self.pair_labels[pair["pair"]]
+            label.setText(describe(pair))
+            color = "#2ecc71" if pair["status"] == "ok" else "#e74c3c"
+            label.setStyleSheet(f"color: {color}; font-family: monospace; font-size: 10pt;")
+        self.summary_label.setText(message)
+
+class ModuleInfoCard(ConfigurationCard):
+    """The plugged SFP/QSFP module: identity from its EEPROM and, with DOM, its optical readings"""
+    
+    def __init__(self, module: dict):
+        super().__init__("Transceiver Module")
+        self.module = module
+        self.setup_module_rows()
+        
+    def setup_module_rows(self):
+        module = self.module
+        form = QFormLayout()
+        form.setSpacing(8)
+        rows = [
+            ("Module:", " ".join(filter(None, [module["identifier"], module["connector"]]))),
+            ("Vendor:", module["vendor"]),
+            ("Part Number:", " rev ".join(filter(None, [module["part_number"], module["revision"]]))),
+            ("Serial:", module["serial"]),
+            ("Type:", module["type"]),
+            ("Wavelength:", f"{module['wavelength_nm']:g} nm" if module["wavelength_nm"] else None),
+            ("Temperature:", f"{module['temperature_c']:g} °C" if module["temperature_c"] is not None else None),
+            ("Voltage:", f"{module['voltage_v']:g} V" if module["voltage_v"] is not None else None),
+            ("TX Power:", format_power(module["tx_power_dbm"])),
+            ("RX Power:", format_power(module["rx_power_dbm"])),
+        ]
+        for label, value in rows:
+            if value:
+                form.addRow(label, self._value_label(value))
+        if module["alarms"]:
+            alarms = self._value_label("\n".join(module["alarms"]))
+            alarms.setStyleSheet("color: #e74c3c; font-family: monospace; font-size: 10pt;")
+            form.addRow("Alarms:", alarms)
+        self.content_layout.addLayout(form)
+        
+    def _value_label(self, text: str) -> QLabel:
+        label = QLabel(text)
+        label.setTextInteractionFlags(Qt.TextInteractionFlag.TextSelectableByMouse)
+        label.setStyleSheet("color: #ecf0f1; font-family: monospace; font-size: 10pt;")
+        return label
+
+class NicQueuesCard(ConfigurationCard):
+    """Each queue's share of the packets and the CPUs the NIC's IRQs go to, with a spread action"""
+    
+    queues_changed = pyqtSignal()
+    
+    def __init__(self, interface: NetworkInterface, queues: dict, daemon: IpcClient):
+        super().__init__("Queues and IRQs")
+        self.interface = interface
+        self.queues = queues
+        self.daemon = daemon
+        self.worker = None
+        self.setup_queue_rows()
+        
+    def setup_queue_rows(self):
+        form = QFormLayout()
+        form.setSpacing(8)
+        for queue in self.queues["queues"]:
+            bar = QProgressBar()
+            bar.setRange(0, 1000)
+            bar.setValue(int((queue["share"] or 0) * 1000))
+            bar.setFormat(f"{queue['share']:.0%}" if queue["share"] is not None else "idle")
+            bar.setMaximumHeight(16)
+            form.addRow(f"Queue {queue['queue']}:", bar)
+        irq_cpus = format_cpu_list(self.queues["irq_cpus"]) or "unknown"
+        form.addRow("IRQ CPUs:", self._value_label(
+            f"{len(self.queues['irqs'])} IRQs on CPUs {irq_cpus} of {format_cpu_list(self.queues['cpus'])}"))
+        self.content_layout.addLayout(form)
+        
+        for warning in self.queues["warnings"]:
+            label = QLabel(f"⚠ {warning}")
+            label.setWordWrap(True)
+            label.setStyleSheet("color: #f39c12; font-size: 10pt;")
+            self.content_layout.addWidget(label)
+        
+        self.spread_button = QPushButton("Spread IRQs")
+        self.spread_button.setToolTip("Pin the NIC's IRQs one per CPU; irqbalance, if running, may move them again")
+        self.spread_button.setEnabled(len(self.queues["irqs"]) > 1)
+        self.spread_button.setVisible(self.daemon.can("SpreadIrqs"))
+        self.spread_button.setStyleSheet("""
+            QPushButton {
+                background: #2c3e50;
+                color: #ecf0f1;
+                border: 1px solid #4a6473;
+                border-radius: 6px;
+                padding: 6px 14px;
+                font-size: 10pt;
+            }
+            QPushButton:hover {
+                border: 1px solid #3498db;
+            }
+            QPushButton:disabled {
+                color: #7f8c8d;
+            }
+        """)
+        self.spread_button.clicked.connect(self.spread)
+        button_layout = QHBoxLayout()
+        button_layout.addWidget(self.spread_button)
+        button_layout.addStretch()
+        self.content_layout.addLayout(button_layout)
+        
+    def _value_label(self, text: str) -> QLabel:
+        label = QLabel(text)
+        label.setTextInteractionFlags(Qt.TextInteractionFlag.TextSelectableByMouse)
+        label.setStyleSheet("color: #ecf0f1; font-family: monospace; font-size: 10pt;")
+        return label
+        
+    def spread(self):
+        """Spread the IRQs over the CPUs through alopexd"""
+        if self.worker and self.worker.isRunning():
+            return
+        
+        async def request():
+            return self.daemon.request("SpreadIrqs", interface=self.interface.name)
+        
+        self.worker = AsyncWorker(request)
+        self.worker.finished.connect(self.on_spread_complete)
+        self.worker.start()
+        self.spread_button.setEnabled(False)
+        self.spread_button.setText("Spreading...")
+        
+    def on_spread_complete(self, success, message):
+        """Show failures; on success the panel reloads the distribution"""
+        if not success:
+            QMessageBox.critical(self, "Spread IRQs", message)
+            self.spread_button.setText("Spread IRQs")
+            self.spread_button.setEnabled(True)
+            return
+        self.queues_changed.emit()
+
+class DnsServersCard(ConfigurationCard):
+    """The active profile's DNS servers, editable in order, with each one's health"""
+    
+    HEALTH_REFRESH_MS = 5000
+    STATUS_COLORS = {"ok": "#2ecc71", "slow": "#f39c12", "failing": "#e74c3c", "unknown": "#7f8c8d"}
+    
+    def __init__(self, interface: NetworkInterface, profile: dict, daemon: IpcClient):
+        super().__init__(f"DNS Servers ({profile['name']})")
+        self.interface = interface
+        self.profile = profile
+        self.daemon = daemon
+        self.saved = list(profile.get("dns_servers") or [])
+        self.worker = None
+        self.setup_dns_controls()
+        
+        self.health_timer = QTimer(self)
+        self.health_timer.timeout.connect(self.refresh_health)
+        self.health_timer.start(self.HEALTH_REFRESH_MS)
+        
+    def setup_dns_controls(self):
+        hint = QLabel("Empty uses the servers from DHCP. Del removes, Alt+Up/Down reorders.")
+        hint.setWordWrap(True)
+        hint.setStyleSheet("color: #7f8c8d; font-size: 9pt;")
+        self.content_layout.addWidget(hint)
+        
+        self.server_list = QListWidget()
+        self.server_list.setMaximumHeight(120)
+        self.server_list.setStyleSheet("""
+            QListWidget {
+                background: #2c3e50;
+                color: #ecf0f1;
+                border: 1px solid #34495e;
+                border-radius: 6px;
+                font-family: monospace;
+                font-size: 10pt;
+            }
+            QListWidget::item:selected {
+                background: #3498db;
+            }
+        """)
+        self.content_layout.addWidget(self.server_list)
+        for keys, action in (("Delete", self.remove_server), ("Alt+Up", lambda: self.move_server(-1)),
+                             ("Alt+Down", lambda: self.move_server(1))):
+            shortcut = QShortcut(QKeySequence(keys), self.server_list)
+            shortcut.setContext(Qt.ShortcutContext.WidgetShortcut)
+            shortcut.activated.connect(action)
+        
+        add_layout = QHBoxLayout()
+        self.server_input = QLineEdit()
+        self.server_input.setPlaceholderText("9.9.9.9")
+        self.server_input.returnPressed.connect(self.add_server)
+        self.server_input.setStyleSheet("""
+            QLineEdit {
+                background: #2c3e50;
+                color: #ecf0f1;
+                border: 1px solid #34495e;
+                border-radius: 6px;
+                padding: 6px;
+                font-size: 10pt;
+            }
+            QLineEdit:focus {
+                border: 1px solid #3498db;
+            }
+        """)
+        add_layout.addWidget(self.server_input)
+        
+        self.add_button = QPushButton("Add")
+        self.add_button.clicked.connect(self.add_server)
+        self.remove_button = QPushButton("Remove")
+        self.remove_button.clicked.connect(self.remove_server)
+        self.apply_button = QPushButton("Apply")
+        self.apply_button.clicked.connect(self.apply_servers)
+        for button in (self.add_button, self.remove_button, self.apply_button):
+            button.setVisible(self.daemon.can("SetDns"))
+        for button in (self.add_button, self.remove_button, self.apply_button):
+            button.setStyleSheet("""
+                QPushButton {
+                    background: #2c3e50;
+                    color: #ecf0f1;
+                    border: 1px solid #4a6473;
+                    border-radius: 6px;
+                    padding: 6px 14px;
+                    font-size: 10pt;
+                }
+                QPushButton:hover {
+                    border: 1px solid #3498db;
+                }
+                QPushButton:disabled {
+                    color: #7f8c8d;
+                }
+            """)
+            add_layout.addWidget(button)
+        self.content_layout.addLayout(add_layout)
+        
+        self.health = {}
+        self.show_servers(self.saved)
+        self.refresh_health()
+        
+    def servers(self) -> list:
+        return [self.server_list.item(row).data(Qt.ItemDataRole.UserRole) for row in range(self.server_list.count())]
+        
+    def show_servers(self, servers: list):
+        """Fill the list, keeping the selection on the same row"""
+        row = self.server_list.currentRow()
+        self.server_list.clear()
+        for server in servers:
+            item = QListWidgetItem()
+            item.setData(Qt.ItemDataRole.UserRole, server)
+            self.server_list.addItem(item)
+        self._label_items()
+        self.server_list.setCurrentRow(min(row, len(servers) - 1))
+        self.apply_button.setEnabled(servers != self.saved)
+        
+    def _label_items(self):
+        for row in range(self.server_list.count()):
+            item = self.server_list.item(row)
+            server = item.data(Qt.ItemDataRole.UserRole)
+            health = self.health.get(server)
+            if health is None:
+                item.setText(f"{server}  (not probed yet)")
+                color = self.STATUS_COLORS["unknown"]
+            else:
+                detail = f"{health['latency_ms']} ms" if health["latency_ms"] is not None else health["last_error"]
+                failed = f"{health['failures']}/{health['checks']} failed"
+                item.setText(f"{server}  {health['status']} · {detail} · {failed}")
+                color = self.STATUS_COLORS.get(health["status"], self.STATUS_COLORS["unknown"])
+            item.setForeground(QColor(color))
+        
+    def refresh_health(self):
+        """Pull the daemon's latest probe results"""
+        results = self.daemon.try_request("GetDnsHealth", default=[])
+        self.health = {h["server"]: h for h in results}
+        self._label_items()
+        
+    def add_server(self):
+        server = self.server_input.text().strip()
+        if not server or server in self.servers():
+            return
+        self.server_input.clear()
+        self.show_servers(self.servers() + [server])
+        
+    def remove_server(self):
+        row = self.server_list.currentRow()
+        if row < 0:
+            return
+        servers = self.servers()
+        del servers[row]
+        self.show_servers(servers)
+        
+    def move_server(self, step: int):
+        row = self.server_list.currentRow()
+        servers = self.servers()
+        if row < 0 or not 0 <= row + step < len(servers):
+            return
+        servers[row], servers[row + step] = servers[row + step], servers[row]
+        self.show_servers(servers)
+        self.server_list.setCurrentRow(row + step)
+        
+    def apply_servers(self):
+        """Save the list to the profile and have the daemon use it now"""
+        if self.worker and self.worker.isRunning():
+            return
+        servers = self.servers()
+        
+        async def request():
+            return self.daemon.request("SetDns", interface=self.interface.name, servers=servers)
+        
+        self.worker = AsyncWorker(request)
+        self.worker.finished.connect(lambda success, message: self.on_apply_complete(success, message, servers))
+        self.worker.start()
+        self.apply_button.setText("Applying...")
+        self.apply_button.setEnabled(False)
+        
+    def on_apply_complete(self, success, message, servers):
+        self.apply_button.setText("Apply")
+        if success:
+            self.saved = servers
+        else:
+            QMessageBox.critical(self, "DNS Servers", message)
+        self.apply_button.setEnabled(self.servers() != self.saved)
+        self.refresh_health()
+
 class ManagementPanel(QWidget):
     """Professional network interface management panel"""
     
-    def __init__(self):
+    def __init__(self, daemon: Optional[IpcClient] = None):
         super().__init__()
         self.current_interface = None
+        self.address_card = None
+        self.lease_card = None
+        self.radio_card = None
+        self.daemon = daemon or IpcClient(timeout=1.0)
+        # The configuration cards apply settings on this machine; a fleet host gets the daemon's cards only
+        self.local = True
         self.setup_ui()
         
+        copy_shortcut = QShortcut(QKeySequence("Ctrl+Shift+M"), self)
+        copy_shortcut.setContext(Qt.ShortcutContext.WindowShortcut)
+        copy_shortcut.activated.connect(self.copy_mac)
+        
+        # Renew or release the selected interface's DHCP lease
+        renew_shortcut = QShortcut(QKeySequence("Ctrl+Shift+R"), self)
+        renew_shortcut.setContext(Qt.ShortcutContext.WindowShortcut)
+        renew_shortcut.activated.connect(lambda: self.lease_card and self.lease_card.renew())
+        release_shortcut = QShortcut(QKeySequence("Ctrl+Shift+L"), self)
+        release_shortcut.setContext(Qt.ShortcutContext.WindowShortcut)
+        release_shortcut.activated.connect(lambda: self.lease_card and self.lease_card.release())
+        
+        power_shortcut = QShortcut(QKeySequence("Ctrl+Shift+P"), self)
+        power_shortcut.setContext(Qt.ShortcutContext.WindowShortcut)
+        power_shortcut.activated.connect(lambda: self.radio_card and self.radio_card.toggle_power_save())
+        
     def setup_ui(self):
         layout = QVBoxLayout(self)
         layout.setContentsMargins(0, 0, 0, 0)
@@ -450,6 +1197,10 @@ class ManagementPanel(QWidget):
         
         layout.addWidget(self.content_widget)
         
+    def set_local(self, local: bool):
+        """Whether the daemon behind the panel runs on this machine"""
+        self.local = local
+        
     def update_interface(self, interface: NetworkInterface):
         """Update panel for selected interface"""
         self.current_interface = interface
@@ -461,18 +1212,54 @@ class ManagementPanel(QWidget):
                 child.widget().setParent(None)
         
         # Add interface-specific management
-        if interface.interface_type == "Ethernet":
-            config_card = EthernetConfigCard(interface)
-            config_card.config_changed.connect(self.on_config_changed)
-            self.content_layout.addWidget(config_card)
+        self.radio_card = None
+        if not interface.managed:
+            info_label = QLabel(f"Interface: {interface.name}\nStatus: {interface.status}\n\n"
+                                "This interface is unmanaged and is left to its owner "
+                                "(docker, libvirt, systemd-networkd, ...)")
+            info_label.setWordWrap(True)
+            info_label.setStyleSheet("""
+                color: #7f8c8d;
+                font-size: 11pt;
+                padding: 20px;
+                background: #34495e;
+                border-radius: 8px;
+            """)
+            self.content_layout.addWidget(info_label)
+            
+        elif interface.interface_type == InterfaceType.ETHERNET:
+            if self.local:
+                config_card = EthernetConfigCard(interface)
+                config_card.config_changed.connect(self.on_config_changed)
+                self.content_layout.addWidget(config_card)
+            module = self.daemon.try_request("GetModuleInfo", interface=interface.name)
+            if module:
+                self.content_layout.addWidget(ModuleInfoCard(module))
+            # Only worth a card on multiqueue NICs
+            queues = self.daemon.try_request("GetNicQueues", interface=interface.name)
+            if queues and (len(queues["queues"]) > 1 or len(queues["irqs"]) > 1):
+                queues_card = NicQueuesCard(interface, queues, self.daemon)
+                queues_card.queues_changed.connect(lambda: self.update_interface(self.current_interface))
+                self.content_layout.addWidget(queues_card)
+            self.content_layout.addWidget(CableTestCard(interface, self.daemon))
             
-        elif interface.interface_type == "WiFi":
-            config_card = WiFiConfigCard(interface)
-            self.content_layout.addWidget(config_card)
+        elif interface.interface_type == InterfaceType.WIFI:
+            if self.local:
+                self.content_layout.addWidget(WiFiConfigCard(interface))
+            
+            domain = self.daemon.try_request("GetRegulatoryDomain", interface=interface.name)
+            if domain:
+                power = self.daemon.try_request("GetPowerSave", default={}, interface=interface.name)
+                radio = next((r for r in self.daemon.try_request("ListRfkill", default=[])
+                              if r["interface"] == interface.name), None)
+                self.radio_card = WiFiRadioCard(interface, domain, power, radio, self.daemon)
+                self.radio_card.radio_changed.connect(lambda: self.update_interface(self.current_interface))
+                self.content_layout.addWidget(self.radio_card)
             
         else:
             # Generic interface info
-            info_label = QLabel(f"Interface: {interface.name}\nType: {interface.interface_type}\nStatus: {interface.status}")
+            kind = f"{interface.interface_type} ({interface.type_detail})" if interface.type_detail else interface.interface_type
+            info_label = QLabel(f"Interface: {interface.name}\nType: {kind}\nStatus: {interface.status}")
             info_label.setStyleSheet("""
                 color: #ecf0f1;
                 font-size: 11pt;
@@ -482,8 +1269,34 @@ class ManagementPanel(QWidget):
             """)
             self.content_layout.addWidget(info_label)
         
+        # DNS servers of the profile alopexd has active here, if any
+        status = self.daemon.try_request("GetStatus", default={}) if interface.managed else {}
+        profile_name = status.get("active_profiles", {}).get(interface.name)
+        profile = next((p for p in self.daemon.try_request("ListProfiles", default=[], interface=interface.name)
+                        if p["name"] == profile_name), None) if profile_name else None
+        if profile:
+            self.content_layout.addWidget(DnsServersCard(interface, profile, self.daemon))
+        
+        # Only alopexd knows the lease; without it (or without a lease) the card is left out
+        self.lease_card = None
+        lease = self.daemon.try_request("GetDhcpLease", interface=interface.name) if interface.managed else None
+        if lease:
+            self.lease_card = DhcpLeaseCard(interface, lease, self.daemon)
+            self.lease_card.lease_changed.connect(lambda: self.update_interface(self.current_interface))
+            self.content_layout.addWidget(self.lease_card)
+        
+        self.address_card = None
+        if interface.mac:
+            self.address_card = HardwareAddressCard(interface)
+            self.content_layout.addWidget(self.address_card)
+        
         self.content_layout.addStretch()
         
+    def copy_mac(self):
+        """Copy the selected interface's MAC address"""
+        if self.address_card:
+            self.address_card.copy_mac()
+        
     def on_config_changed(self):
         """Handle configuration changes"""
         # Emit signal to refresh interface data
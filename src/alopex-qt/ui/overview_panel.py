@@ -0,0 +1,199 @@
+"""
+Overview Panel - Every interface at once, for multi-homed machines
+Compact tiles with status, address and up/down sparklines; 'd' switches
+between this grid and the three-panel view, clicking a tile opens it there
+"""
+
+import math
+from collections import deque
+from typing import Dict, List
+from PyQt6.QtWidgets import QWidget, QVBoxLayout, QHBoxLayout, QGridLayout, QLabel, QScrollArea, QFrame
+from PyQt6.QtCore import Qt, pyqtSignal, QPointF
+from PyQt6.QtGui import QColor, QPainter, QPen, QPolygonF
+
+from network.discovery import NetworkInterface
+from network.units import UnitPreference
+from .arctic_theme import ArcticTheme, FontManager
+from .interface_panel import InterfaceStatusIndicator, InterfaceTypeIcon
+
+SPARKLINE_POINTS = 30
+TILE_WIDTH = 260
+
+class Sparkline(QWidget):
+    """Tiny rate history without axes or labels"""
+
+    def __init__(self, color: QColor, points: int = SPARKLINE_POINTS):
+        super().__init__()
+        self.color = color
+        self.values = deque([0.0] * points, maxlen=points)
+        self.setFixedHeight(22)
+
+    def add(self, value: float):
+        self.values.append(value)
+        self.update()
+
+    def paintEvent(self, event):
+        painter = QPainter(self)
+        painter.setRenderHint(QPainter.RenderHint.Antialiasing)
+        peak = max(self.values)
+        if peak <= 0:
+            painter.setPen(QPen(QColor(ArcticTheme.BORDER_PRIMARY), 1))
+            painter.drawLine(0, self.height() - 1, self.width(), self.height() - 1)
+            return
+
+        # Log scale so a burst doesn't flatten everything else
+        scale = math.log1p(peak)
+        step = self.width() / (len(self.values) - 1)
+        line = QPolygonF([
+            QPointF(i * step, (self.height() - 2) * (1 - math.log1p(v) / scale) + 1)
+            for i, v in enumerate(self.values)
+        ])
+        painter.setPen(QPen(self.color, 1.5))
+        painter.drawPolyline(line)
+
+class InterfaceTile(QFrame):
+    """One interface in the overview grid"""
+
+    clicked = pyqtSignal(NetworkInterface)
+
+    def __init__(self, interface: NetworkInterface):
+        super().__init__()
+        self.interface = interface
+        self.setFixedWidth(TILE_WIDTH)
+        self.setCursor(Qt.CursorShape.PointingHandCursor)
+        self.setStyleSheet(f"""
+            InterfaceTile {{
+                background: {ArcticTheme.BACKGROUND_PANEL};
+                border: 1px solid {ArcticTheme.BORDER_PRIMARY};
+                border-radius: 8px;
+            }}
+            InterfaceTile:hover {{
+                border: 1px solid {ArcticTheme.PRIMARY_ACCENT};
+            }}
+        """)
+        self.setup_ui()
+
+    def setup_ui(self):
+        layout = QVBoxLayout(self)
+        layout.setContentsMargins(10, 8, 10, 8)
+        layout.setSpacing(4)
+
+        header = QHBoxLayout()
+        header.setSpacing(8)
+        self.status_indicator = InterfaceStatusIndicator(self.interface.status)
+        header.addWidget(self.status_indicator)
+        header.addWidget(InterfaceTypeIcon(self.interface.interface_type))
+        self.name_label = QLabel(self.interface.display_name)
+        self.name_label.setFont(FontManager.get_primary_font(11, 600))
+        self.name_label.setStyleSheet(f"color: {ArcticTheme.TEXT_PRIMARY}; border: none;")
+        header.addWidget(self.name_label)
+        header.addStretch()
+        layout.addLayout(header)
+
+        self.address_label = QLabel()
+        self.address_label.setFont(FontManager.get_monospace_font(9))
+        self.address_label.setStyleSheet(f"color: {ArcticTheme.TEXT_SECONDARY}; border: none;")
+        layout.addWidget(self.address_label)
+
+        self.down_label = QLabel()
+        self.down_label.setFont(FontManager.get_primary_font(8, 600))
+        self.down_label.setStyleSheet(f"color: {ArcticTheme.PRIMARY_ACCENT}; border: none;")
+        self.down_line = Sparkline(QColor(ArcticTheme.PRIMARY_ACCENT))
+        self.up_label = QLabel()
+        self.up_label.setFont(FontManager.get_primary_font(8, 600))
+        self.up_label.setStyleSheet(f"color: {ArcticTheme.SUCCESS}; border: none;")
+        self.up_line = Sparkline(QColor(ArcticTheme.SUCCESS))
+        for widget in (self.down_label, self.down_line, self.up_label, self.up_line):
+            layout.addWidget(widget)
+
+    def update_interface(self, interface: NetworkInterface, units: UnitPreference, sample: bool):
+        """Show fresh state; sample adds the current rates to the sparklines"""
+        self.interface = interface
+        self.name_label.setText(interface.display_name)
+        self.status_indicator.status = interface.status
+        self.status_indicator.update()
+
+        address = interface.ip or interface.status_text.lower()
+        if not interface.managed:
+            address += "  (unmanaged)"
+        self.address_label.setText(address)
+
+        down = interface.metrics.speed_down * 1024
+        up = interface.metrics.speed_up * 1024
+        self.down_label.setText(f"↓ {units.format_rate(down)}")
+        self.up_label.setText(f"↑ {units.format_rate(up)}")
+        if sample:
+            self.down_line.add(down)
+            self.up_line.add(up)
+
+    def mousePressEvent(self, event):
+        if event.button() == Qt.MouseButton.LeftButton:
+            self.clicked.emit(self.interface)
+
+class OverviewPanel(QWidget):
+    """Grid of interface tiles that reflows with the window width"""
+
+    interface_selected = pyqtSignal(NetworkInterface)
+
+    def __init__(self):
+        super().__init__()
+        self.units = UnitPreference()
+        self.tiles: Dict[str, InterfaceTile] = {}
+        self.order: List[str] = []
+        self.columns = 0
+        self.setup_ui()
+
+    def setup_ui(self):
+        layout = QVBoxLayout(self)
+        layout.setContentsMargins(8, 8, 8, 8)
+
+        title = QLabel("All Interfaces")
+        title.setFont(FontManager.get_primary_font(14, 600))
+        title.setStyleSheet(f"color: {ArcticTheme.TEXT_PRIMARY};")
+        layout.addWidget(title)
+
+        scroll = QScrollArea()
+        scroll.setWidgetResizable(True)
+        scroll.setFrameShape(QFrame.Shape.NoFrame)
+        container = QWidget()
+        self.grid = QGridLayout(container)
+        self.grid.setSpacing(8)
+        self.grid.setAlignment(Qt.AlignmentFlag.AlignTop | Qt.AlignmentFlag.AlignLeft)
+        scroll.setWidget(container)
+        layout.addWidget(scroll)
+
+    def set_units(self, units: UnitPreference):
+        self.units = units
+
+    def update_interfaces(self, interfaces: List[NetworkInterface], sample: bool = True):
+        """Add, refresh and drop tiles to match interfaces"""
+        names = [i.name for i in interfaces]
+        for name in set(self.tiles) - set(names):
+            self.tiles.pop(name).deleteLater()
+
+        for interface in interfaces:
+            tile = self.tiles.get(interface.name)
+            if tile is None:
+                tile = InterfaceTile(interface)
+                tile.clicked.connect(self.interface_selected)
+                self.tiles[interface.name] = tile
+            tile.update_interface(interface, self.units, sample)
+
+        if names != self.order or self._columns() != self.columns:
+            self.order = names
+            self._layout_tiles()
+
+    def _columns(self) -> int:
+        return max(1, (self.width() - 16) // (TILE_WIDTH + self.grid.spacing()))
+
+    def _layout_tiles(self):
+        self.columns = self._columns()
+        for name in self.order:
+            self.grid.removeWidget(self.tiles[name])
+        for index, name in enumerate(self.order):
+            self.grid.addWidget(self.tiles[name], index // self.columns, index % self.columns)
+
+    def resizeEvent(self, event):
+        super().resizeEvent(event)
+        if self._columns() != self.columns:
+            self._layout_tiles()
@@ -0,0 +1,153 @@
+"""
+Quick Switch - Ctrl+P palette for activating a profile or switching location
+Type a few letters of a name (in order, not necessarily adjacent), Enter
+activates the highlighted entry, and the job's progress and result show below
+the list without leaving the keyboard
+"""
+
+from typing import Dict, List, Optional
+from PyQt6.QtWidgets import QDialog, QVBoxLayout, QLabel, QLineEdit, QListWidget, QListWidgetItem
+from PyQt6.QtCore import Qt, QTimer
+from PyQt6.QtGui import QColor, QKeySequence, QShortcut
+
+from network.ipc import IpcClient, IpcError
+from .arctic_theme import ArcticTheme, FontManager
+
+POLL_INTERVAL_MS = 250
+
+def fuzzy_score(query: str, text: str) -> Optional[int]:
+    """How well query's letters appear in order in text, lower is better; None if they don't.
+    Letters right after the previous match or at the start of a word cost nothing, gaps cost their length"""
+    query, text = query.lower().replace(" ", ""), text.lower()
+    score, position = 0, 0
+    for letter in query:
+        found = text.find(letter, position)
+        if found < 0:
+            return None
+        if found != position and not (found == 0 or not text[found - 1].isalnum()):
+            score += found - position
+        position = found + 1
+    # Among equally good matches, shorter names first
+    return score * 100 + len(text)
+
+class QuickSwitchDialog(QDialog):
+    """Profiles and locations filtered as you type; Enter activates the highlighted one"""
+
+    def __init__(self, daemon: IpcClient, parent=None):
+        super().__init__(parent)
+        self.daemon = daemon
+        self.entries: List[Dict] = []
+        self.job_id: Optional[int] = None
+        self.timer = QTimer(self)
+        self.timer.timeout.connect(self.poll)
+        self.setWindowTitle("Switch Profile or Location")
+        self.resize(560, 380)
+        self.setup_ui()
+        self.load()
+
+    def setup_ui(self):
+        layout = QVBoxLayout(self)
+        layout.setContentsMargins(12, 12, 12, 12)
+        layout.setSpacing(8)
+
+        self.search = QLineEdit()
+        self.search.setPlaceholderText("Profile or location...")
+        self.search.setFont(FontManager.get_primary_font(11))
+        self.search.setStyleSheet(ArcticTheme.get_input_style())
+        self.search.textChanged.connect(self.filter)
+        self.search.returnPressed.connect(self.activate)
+        layout.addWidget(self.search)
+
+        self.results = QListWidget()
+        self.results.setFont(FontManager.get_monospace_font(10))
+        self.results.itemActivated.connect(lambda _: self.activate())
+        layout.addWidget(self.results, 1)
+
+        self.status_label = QLabel("Enter activates · Esc closes")
+        self.status_label.setFont(FontManager.get_primary_font(9))
+        self.status_label.setStyleSheet(f"color: {ArcticTheme.TEXT_SECONDARY};")
+        self.status_label.setWordWrap(True)
+        layout.addWidget(self.status_label)
+
+        # Arrows move through the results while typing in the search field
+        for key, step in (("Down", 1), ("Up", -1), ("PgDown", 5), ("PgUp", -5)):
+            shortcut = QShortcut(QKeySequence(key), self.search)
+            shortcut.setContext(Qt.ShortcutContext.WidgetShortcut)
+            shortcut.activated.connect(lambda step=step: self.move(step))
+
+    def load(self):
+        """Fetch profiles, locations and what is active now"""
+        try:
+            profiles = self.daemon.request("ListProfiles")
+            locations = self.daemon.request("ListLocations")
+            active = set((self.daemon.request("GetStatus").get("active_profiles") or {}).values())
+        except IpcError as e:
+            self.status_label.setText(str(e))
+            return
+        self.entries = [
+            {"kind": "profile", "name": profile["name"], "active": profile["name"] in active,
+             "detail": f"{profile['connection_type']} on {profile['interface']}"}
+            for profile in profiles
+        ] + [
+            {"kind": "location", "name": location["name"], "active": location["name"] == locations["active"],
+             "detail": f"{len(location.get('profiles') or [])} profiles"}
+            for location in locations["locations"]
+        ]
+        self.filter(self.search.text())
+
+    def filter(self, query: str):
+        """Best matches first; an empty query lists everything, profiles before locations"""
+        scored = [(fuzzy_score(query, entry["name"]) if query.strip() else 0, index, entry)
+                  for index, entry in enumerate(self.entries)]
+        self.results.clear()
+        for score, _, entry in sorted(item for item in scored if item[0] is not None):
+            text = f"{entry['kind']:<10}{entry['name']:<28}{entry['detail']:<22}{'active' if entry['active'] else ''}"
+            item = QListWidgetItem(text.rstrip())
+            item.setData(Qt.ItemDataRole.UserRole, entry)
+            item.setForeground(QColor(ArcticTheme.PRIMARY_ACCENT if entry["active"] else ArcticTheme.TEXT_PRIMARY))
+            self.results.addItem(item)
+        self.results.setCurrentRow(0)
+
+    def move(self, step: int):
+        if self.results.count():
+            self.results.setCurrentRow(max(0, min(self.results.count() - 1, self.results.currentRow() + step)))
+
+    def activate(self):
+        """Connect the highlighted profile or switch to the highlighted location"""
+        item = self.results.currentItem()
+        if not item or self.timer.isActive():
+            return
+        entry = item.data(Qt.ItemDataRole.UserRole)
+        try:
+            if entry["kind"] == "profile":
+                job = self.daemon.request("Connect", profile=entry["name"])
+            else:
+                job = self.daemon.request("SwitchLocation", name=entry["name"])
+        except IpcError as e:
+            self.status_label.setText(str(e))
+            return
+        self.job_id = job["id"]
+        self.status_label.setText(f"{job['description']}...")
+        self.timer.start(POLL_INTERVAL_MS)
+
+    def poll(self):
+        try:
+            job = self.daemon.request("GetJob", id=self.job_id)
+        except IpcError as e:
+            self.finish(str(e))
+            return
+        if job["state"] == "running":
+            self.status_label.setText(f"{job['description']}: {job['message'] or 'starting'}...")
+        elif job["state"] == "completed":
+            self.finish(f"{job['description']}: done")
+            self.load()
+        else:
+            self.finish(job["message"])
+
+    def finish(self, text: str):
+        self.timer.stop()
+        self.status_label.setText(text)
+
+    def done(self, result: int):
+        self.timer.stop()
+        super().done(result)
@@ -0,0 +1,125 @@
+"""
+Roaming View - Timeline of WiFi associations, roams and disconnects
+Newest first, with the access point left, its signal at the time and how long
+the interface stayed on it; refreshed while shown, Shift+W switches back to the
+three-panel view
+"""
+
+import time
+from typing import Dict, Optional
+from PyQt6.QtWidgets import QWidget, QVBoxLayout, QHBoxLayout, QLabel, QListWidget, QListWidgetItem
+from PyQt6.QtCore import QTimer
+from PyQt6.QtGui import QColor
+
+from network.ipc import IpcClient, IpcError
+from .arctic_theme import ArcticTheme, FontManager
+
+REFRESH_INTERVAL_MS = 5000
+ENTRIES = 200
+KIND_COLORS = {
+    "associated": ArcticTheme.SUCCESS,
+    "reassociated": ArcticTheme.TEXT_SECONDARY,
+    "roamed": ArcticTheme.PRIMARY_ACCENT,
+    "disconnected": ArcticTheme.WARNING
+}
+
+def _signal(dbm: Optional[int]) -> Optional[str]:
+    return f"{dbm} dBm" if dbm is not None else None
+
+def _stayed(seconds: Optional[float]) -> Optional[str]:
+    if seconds is None:
+        return None
+    seconds = int(seconds)
+    if seconds < 60:
+        return f"{seconds}s"
+    if seconds < 3600:
+        return f"{seconds // 60}m {seconds % 60}s"
+    return f"{seconds // 3600}h {seconds % 3600 // 60}m"
+
+class RoamingView(QWidget):
+    """The access point each WiFi interface is on now, then the timeline"""
+
+    def __init__(self, daemon: Optional[IpcClient] = None):
+        super().__init__()
+        self.daemon = daemon or IpcClient()
+        self.refresh_timer = QTimer(self)
+        self.refresh_timer.timeout.connect(self.refresh)
+        self.setup_ui()
+
+    def setup_ui(self):
+        layout = QVBoxLayout(self)
+        layout.setContentsMargins(8, 8, 8, 8)
+
+        header = QHBoxLayout()
+        title = QLabel("WiFi Roaming")
+        title.setFont(FontManager.get_primary_font(14, 600))
+        title.setStyleSheet(f"color: {ArcticTheme.TEXT_PRIMARY};")
+        header.addWidget(title)
+        header.addStretch()
+        self.status_label = QLabel("Not read yet")
+        self.status_label.setFont(FontManager.get_primary_font(9))
+        self.status_label.setStyleSheet(f"color: {ArcticTheme.TEXT_SECONDARY};")
+        header.addWidget(self.status_label)
+        layout.addLayout(header)
+
+        self.current_label = QLabel()
+        self.current_label.setFont(FontManager.get_monospace_font(9))
+        self.current_label.setStyleSheet(f"color: {ArcticTheme.PRIMARY_ACCENT};")
+        layout.addWidget(self.current_label)
+
+        columns = QLabel(self._row("TIME", "INTERFACE", "EVENT", "SSID", "LEFT", "SIGNAL", "STAYED", "JOINED"))
+        columns.setFont(FontManager.get_monospace_font(9))
+        columns.setStyleSheet(f"color: {ArcticTheme.TEXT_MUTED};")
+        layout.addWidget(columns)
+
+        self.timeline_list = QListWidget()
+        self.timeline_list.setFont(FontManager.get_monospace_font(9))
+        layout.addWidget(self.timeline_list, 1)
+
+    @staticmethod
+    def _row(*cells) -> str:
+        widths = (20, 11, 14, 20, 19, 10, 10, 0)
+        return "".join(("--" if cell in (None, "") else str(cell)).ljust(width) for cell, width in zip(cells, widths))
+
+    def refresh(self):
+        try:
+            self.show_timeline(self.daemon.request("GetRoamingTimeline", limit=ENTRIES))
+        except IpcError as e:
+            self.status_label.setText(str(e))
+
+    def show_timeline(self, timeline: Dict):
+        if not timeline["enabled"]:
+            self.status_label.setText("Roaming timeline is off in enterprise.json")
+            return
+        entries = timeline["entries"]
+        roams = sum(1 for entry in entries if entry["kind"] == "roamed")
+        self.status_label.setText(f"{len(entries)} events, {roams} roams")
+        self.current_label.setText("\n".join(
+            f"{stint['interface']}: {stint['ssid'] or 'unknown SSID'} on {stint['bssid']}, "
+            f"{_signal(stint['signal']) or 'no signal'}, for {_stayed(stint['duration'])}"
+            for stint in timeline["current"]
+        ) or "No interface associated")
+
+        # Keep the reader's place when a refresh brings nothing new
+        position = self.timeline_list.verticalScrollBar().value()
+        self.timeline_list.clear()
+        for entry in reversed(entries):
+            disconnected = entry["kind"] == "disconnected"
+            item = QListWidgetItem(self._row(
+                time.strftime("%m-%d %H:%M:%S", time.localtime(entry["timestamp"])), entry["interface"],
+                entry["kind"], entry["ssid"], entry["bssid"] if disconnected else entry["previous_bssid"],
+                _signal(entry["signal"]), _stayed(entry["duration"]),
+                None if disconnected else f"{entry['bssid']} ({_signal(entry.get('new_signal')) or '?'})"
+            ))
+            item.setForeground(QColor(KIND_COLORS.get(entry["kind"], ArcticTheme.TEXT_PRIMARY)))
+            self.timeline_list.addItem(item)
+        self.timeline_list.verticalScrollBar().setValue(position)
+
+    def showEvent(self, event):
+        super().showEvent(event)
+        self.refresh()
+        self.refresh_timer.start(REFRESH_INTERVAL_MS)
+
+    def hideEvent(self, event):
+        super().hideEvent(event)
+        self.refresh_timer.stop()
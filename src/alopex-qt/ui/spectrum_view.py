@@ -0,0 +1,187 @@
+"""
+Spectrum View - Scanned networks by channel and signal strength
+One plot per band, refreshed from daemon scans while shown; 'w' switches
+between this and the three-panel view
+"""
+
+from typing import Dict, List, Optional
+from PyQt6.QtWidgets import QWidget, QVBoxLayout, QHBoxLayout, QLabel
+from PyQt6.QtCore import Qt, QThread, QTimer, QRectF, QPointF, pyqtSignal
+from PyQt6.QtGui import QColor, QPainter, QPen, QBrush, QPainterPath
+
+from network.ipc import IpcClient, IpcError
+from network.spectrum import BAND_24, BAND_5, CHANNELS, SPREAD_24, channel_load, suggest_channel
+from .arctic_theme import ArcticTheme, FontManager
+
+SCAN_INTERVAL_MS = 15000
+# Signal axis, in dBm
+FLOOR_DBM = -95
+CEILING_DBM = -25
+
+class ScanWorker(QThread):
+    """Run one daemon scan off the GUI thread"""
+    scanned = pyqtSignal(list)
+    failed = pyqtSignal(str)
+
+    def __init__(self, daemon: IpcClient):
+        super().__init__()
+        self.daemon = daemon
+
+    def run(self):
+        try:
+            self.scanned.emit(self.daemon.run_job("ScanWifi", timeout=30.0))
+        except IpcError as e:
+            self.failed.emit(str(e))
+
+class BandPlot(QWidget):
+    """Each network as an arch over the channels it occupies, as tall as it is loud"""
+
+    MARGIN_LEFT = 44
+    MARGIN_BOTTOM = 24
+    MARGIN_TOP = 22
+
+    def __init__(self, band: str):
+        super().__init__()
+        self.band = band
+        self.channels = CHANNELS[band]
+        self.networks: List[Dict] = []
+        self.suggested: Optional[int] = None
+        self.setMinimumHeight(180)
+
+    def set_networks(self, networks: List[Dict], suggested: Optional[int]):
+        self.networks = networks
+        self.suggested = suggested
+        self.update()
+
+    def _x(self, slot: float) -> float:
+        """Pixel position of a (fractional) index into self.channels"""
+        width = self.width() - self.MARGIN_LEFT - 12
+        return self.MARGIN_LEFT + width * (slot + 1) / (len(self.channels) + 1)
+
+    def _y(self, dbm: float) -> float:
+        height = self.height() - self.MARGIN_TOP - self.MARGIN_BOTTOM
+        fraction = (min(max(dbm, FLOOR_DBM), CEILING_DBM) - FLOOR_DBM) / (CEILING_DBM - FLOOR_DBM)
+        return self.MARGIN_TOP + height * (1 - fraction)
+
+    def _slot(self, channel: int) -> Optional[float]:
+        if self.band == BAND_24:
+            # 2.4 GHz channels are evenly spaced, so channel numbers map straight onto slots
+            return channel - self.channels[0]
+        return self.channels.index(channel) if channel in self.channels else None
+
+    def paintEvent(self, event):
+        painter = QPainter(self)
+        painter.setRenderHint(QPainter.RenderHint.Antialiasing)
+        painter.fillRect(self.rect(), QColor(ArcticTheme.BACKGROUND_PANEL))
+        bottom = self.height() - self.MARGIN_BOTTOM
+
+        # Title, dBm grid and channel labels
+        painter.setFont(FontManager.get_primary_font(10, 600))
+        painter.setPen(QColor(ArcticTheme.TEXT_PRIMARY))
+        painter.drawText(8, 15, self.band)
+        painter.setFont(FontManager.get_monospace_font(8))
+        for dbm in range(-90, CEILING_DBM, 20):
+            y = self._y(dbm)
+            painter.setPen(QPen(QColor(ArcticTheme.BORDER_PRIMARY), 1, Qt.PenStyle.DotLine))
+            painter.drawLine(self.MARGIN_LEFT, int(y), self.width() - 12, int(y))
+            painter.setPen(QColor(ArcticTheme.TEXT_MUTED))
+            painter.drawText(4, int(y) + 4, f"{dbm}")
+        for slot, channel in enumerate(self.channels):
+            x = self._x(slot)
+            painter.setPen(QColor(ArcticTheme.SUCCESS if channel == self.suggested else ArcticTheme.TEXT_MUTED))
+            painter.drawText(QRectF(x - 14, bottom + 4, 28, 16), Qt.AlignmentFlag.AlignCenter, str(channel))
+        painter.setPen(QPen(QColor(ArcticTheme.BORDER_PRIMARY), 1))
+        painter.drawLine(self.MARGIN_LEFT, bottom, self.width() - 12, bottom)
+
+        # Suggested channel as a faint column
+        if self.suggested is not None:
+            x = self._x(self._slot(self.suggested))
+            column = QColor(ArcticTheme.SUCCESS)
+            column.setAlpha(40)
+            painter.fillRect(QRectF(x - 6, self.MARGIN_TOP, 12, bottom - self.MARGIN_TOP), column)
+
+        # Networks, quietest first so the loud ones draw on top
+        spread = SPREAD_24 / 2 if self.band == BAND_24 else 0.45
+        for network in sorted(self.networks, key=lambda n: n.get("signal_strength") or FLOOR_DBM):
+            slot = self._slot(network["channel"])
+            if slot is None:
+                continue
+            left, right = self._x(slot - spread), self._x(slot + spread)
+            top = self._y(network.get("signal_strength") or FLOOR_DBM)
+            path = QPainterPath(QPointF(left, bottom))
+            path.cubicTo(QPointF(left, top), QPointF(right, top), QPointF(right, bottom))
+
+            color = QColor(ArcticTheme.PRIMARY_ACCENT if network.get("connected") else ArcticTheme.TEXT_SECONDARY)
+            fill = QColor(color)
+            fill.setAlpha(50)
+            painter.setPen(QPen(color, 2 if network.get("connected") else 1))
+            painter.setBrush(QBrush(fill))
+            painter.drawPath(path)
+
+            # A cubic through those control points peaks at three quarters of the way up
+            peak = bottom - (bottom - top) * 0.75
+            painter.setPen(color)
+            painter.drawText(QRectF(left, peak - 16, right - left, 14), Qt.AlignmentFlag.AlignCenter,
+                             network.get("ssid") or "(hidden)")
+
+class SpectrumView(QWidget):
+    """Both band plots plus the scan status, scanning periodically while visible"""
+
+    def __init__(self, daemon: Optional[IpcClient] = None):
+        super().__init__()
+        self.daemon = daemon or IpcClient()
+        self.worker: Optional[ScanWorker] = None
+        self.scan_timer = QTimer(self)
+        self.scan_timer.timeout.connect(self.scan)
+        self.setup_ui()
+
+    def setup_ui(self):
+        layout = QVBoxLayout(self)
+        layout.setContentsMargins(8, 8, 8, 8)
+
+        header = QHBoxLayout()
+        title = QLabel("WiFi Channels")
+        title.setFont(FontManager.get_primary_font(14, 600))
+        title.setStyleSheet(f"color: {ArcticTheme.TEXT_PRIMARY};")
+        header.addWidget(title)
+        header.addStretch()
+        self.status_label = QLabel("Not scanned yet")
+        self.status_label.setFont(FontManager.get_primary_font(9))
+        self.status_label.setStyleSheet(f"color: {ArcticTheme.TEXT_SECONDARY};")
+        header.addWidget(self.status_label)
+        layout.addLayout(header)
+
+        self.plots = {band: BandPlot(band) for band in (BAND_24, BAND_5)}
+        for plot in self.plots.values():
+            layout.addWidget(plot, 1)
+
+    def scan(self):
+        """Ask the daemon for a fresh scan unless one is already running"""
+        if self.worker and self.worker.isRunning():
+            return
+        self.status_label.setText("Scanning...")
+        self.worker = ScanWorker(self.daemon)
+        self.worker.scanned.connect(self.update_networks)
+        self.worker.failed.connect(lambda error: self.status_label.setText(f"Scan failed: {error}"))
+        self.worker.start()
+
+    def update_networks(self, networks: List[Dict]):
+        networks = [n for n in networks if n.get("channel")]
+        summary = []
+        for band, plot in self.plots.items():
+            in_band = [n for n in networks if n["channel"] in CHANNELS[band]]
+            suggested = suggest_channel(networks, band) if in_band else None
+            plot.set_networks(in_band, suggested)
+            if in_band:
+                busiest = max(channel_load(networks, band).items(), key=lambda item: item[1])[0]
+                summary.append(f"{band}: busiest {busiest}, quietest {suggested}")
+        self.status_label.setText("  ·  ".join(summary) or "No networks found")
+
+    def showEvent(self, event):
+        super().showEvent(event)
+        self.scan()
+        self.scan_timer.start(SCAN_INTERVAL_MS)
+
+    def hideEvent(self, event):
+        super().hideEvent(event)
+        self.scan_timer.stop()
@@ -9,7 +9,7 @@ from PyQt6.QtCore import QTimer, pyqtSignal
 from PyQt6.QtGui import QIcon, QPixmap, QPainter, QBrush, QColor, QAction
 import asyncio
 from typing import Optional
-from network.discovery import NetworkDiscovery
+from network.discovery import InterfaceType, NetworkDiscovery
 
 class AlopexSystemTray(QSystemTrayIcon):
     """Professional system tray integration for ALOPEX"""
@@ -120,7 +120,7 @@ class AlopexSystemTray(QSystemTrayIcon):
             for interface in interfaces:
                 if interface.status == "Connected" and interface.metrics.bytes_rx > 0:
                     active_count += 1
-                    if interface.interface_type in ['ethernet', 'wifi']:
+                    if interface.interface_type in (InterfaceType.ETHERNET, InterfaceType.WIFI, InterfaceType.CELLULAR):
                         has_internet = True
             
             self.active_connections = active_count
@@ -3,8 +3,11 @@ Telemetry Panel - Revolutionary real-time network monitoring
 Making NetworkManager's monitoring look prehistoric
 """
 
+import html
 import math
+import time
 from collections import deque
+from typing import Dict, List, Optional
 from PyQt6.QtWidgets import (
     QWidget, QVBoxLayout, QHBoxLayout, QLabel, QFrame, 
     QGridLayout, QProgressBar, QGroupBox
@@ -16,6 +19,7 @@ from PyQt6.QtGui import (
 )
 
 from network.discovery import NetworkMetrics
+from network.units import UnitPreference
 
 class AnimatedProgressBar(QProgressBar):
     """Beautiful animated progress bar with glow effects"""
@@ -63,13 +67,17 @@ class AnimatedProgressBar(QProgressBar):
 class RealTimeGraph(QWidget):
     """Beautiful real-time network traffic graph"""
     
-    def __init__(self, title="Traffic", max_points=60):
+    # Smallest full-scale value, so an idle link doesn't show noise as peaks
+    MIN_SCALE = 100 * 1024.0  # bytes/s
+    
+    def __init__(self, title="Traffic", max_points=60, units: UnitPreference = UnitPreference()):
         super().__init__()
         self.title = title
         self.max_points = max_points
-        self.upload_data = deque(maxlen=max_points)
+        self.units = units
+        self.upload_data = deque(maxlen=max_points)  # bytes/s
         self.download_data = deque(maxlen=max_points)
-        self.max_value = 100.0
+        self.max_value = self.MIN_SCALE
         
         self.setMinimumHeight(120)
         
@@ -78,8 +86,15 @@ class RealTimeGraph(QWidget):
             self.upload_data.append(0)
             self.download_data.append(0)
             
+    def clear(self):
+        """Drop the history, e.g. when the graph starts showing another interface"""
+        for data in (self.upload_data, self.download_data):
+            data.extend([0] * self.max_points)
+        self.max_value = self.MIN_SCALE
+        self.update()
+        
     def add_data_point(self, upload_speed, download_speed):
-        """Add new data point"""
+        """Add new data point (bytes/s)"""
         self.upload_data.append(upload_speed)
         self.download_data.append(download_speed)
         
@@ -87,8 +102,8 @@ class RealTimeGraph(QWidget):
         current_max = max(max(self.upload_data), max(self.download_data))
         if current_max > self.max_value:
             self.max_value = current_max * 1.2
-        elif current_max < self.max_value * 0.5 and self.max_value > 100:
-            self.max_value = max(current_max * 1.5, 100)
+        elif current_max < self.max_value * 0.5 and self.max_value > self.MIN_SCALE:
+            self.max_value = max(current_max * 1.5, self.MIN_SCALE)
             
         self.update()
         
@@ -138,7 +153,7 @@ class RealTimeGraph(QWidget):
         painter.drawLine(graph_rect.left(), legend_y, graph_rect.left() + 20, legend_y)
         painter.setPen(QPen(QColor(236, 240, 241), 1))
         painter.drawText(graph_rect.left() + 25, legend_y + 4, 
-                        f"↑ Upload: {self.upload_data[-1]:.1f} KB/s")
+                        f"↑ Upload: {self.units.format_rate(self.upload_data[-1])}")
         
         # Download legend  
         painter.setPen(QPen(QColor(52, 152, 219), 2))
@@ -146,7 +161,7 @@ class RealTimeGraph(QWidget):
         painter.drawLine(mid_x, legend_y, mid_x + 20, legend_y)
         painter.setPen(QPen(QColor(236, 240, 241), 1))
         painter.drawText(mid_x + 25, legend_y + 4,
-                        f"↓ Download: {self.download_data[-1]:.1f} KB/s")
+                        f"↓ Download: {self.units.format_rate(self.download_data[-1])}")
         
     def _draw_graph_line(self, painter, rect, data, color):
         """Draw a graph line with gradient fill"""
@@ -328,6 +343,11 @@ class TelemetryPanel(QWidget):
     def __init__(self):
         super().__init__()
         self.active = False
+        self.units = UnitPreference()
+        self.session: Optional[Dict] = None
+        self.interface_name: Optional[str] = None
+        self.pinned: Optional[str] = None
+        self.latency: Dict[str, Optional[float]] = {}
         self.setup_ui()
         
     def setup_ui(self):
@@ -380,6 +400,10 @@ class TelemetryPanel(QWidget):
         self.traffic_graph = RealTimeGraph("Network Traffic")
         content_layout.addWidget(self.traffic_graph)
         
+        # Pinned interface ('p'), drawn on the same scale for comparison
+        self.compare_graph = RealTimeGraph("Pinned")
+        content_layout.addWidget(self.compare_graph)
+        
         # Metrics grid
         metrics_group = QGroupBox("Connection Metrics")
         metrics_group.setStyleSheet("""
@@ -413,6 +437,35 @@ class TelemetryPanel(QWidget):
         metrics_layout.addWidget(self.uptime_card, 1, 1)
         
         content_layout.addWidget(metrics_group)
+        
+        # Totals since the connection came up, kept by the daemon
+        self.session_group = QGroupBox("Session")
+        self.session_group.setStyleSheet(metrics_group.styleSheet())
+        session_layout = QGridLayout(self.session_group)
+        session_layout.setSpacing(8)
+        
+        self.session_rx_card = MetricCard("Downloaded", "--", "", QColor(52, 152, 219))
+        self.session_tx_card = MetricCard("Uploaded", "--", "", QColor(46, 204, 113))
+        self.session_label = QLabel()
+        self.session_label.setStyleSheet("color: #95a5a6; font-size: 9pt; font-weight: normal;")
+        
+        session_layout.addWidget(self.session_rx_card, 0, 0)
+        session_layout.addWidget(self.session_tx_card, 0, 1)
+        session_layout.addWidget(self.session_label, 1, 0, 1, 2)
+        
+        content_layout.addWidget(self.session_group)
+        
+        # Loss, latency and jitter of the daemon's SLA probes; hidden when none are configured
+        self.sla_group = QGroupBox("SLA (last hour)")
+        self.sla_group.setStyleSheet(metrics_group.styleSheet())
+        sla_layout = QVBoxLayout(self.sla_group)
+        self.sla_label = QLabel()
+        self.sla_label.setTextFormat(Qt.TextFormat.RichText)
+        self.sla_label.setStyleSheet("font-family: monospace; font-size: 9pt; font-weight: normal;")
+        sla_layout.addWidget(self.sla_label)
+        self.sla_probes = []
+        
+        content_layout.addWidget(self.sla_group)
         content_layout.addStretch()
         
         layout.addWidget(content_widget, 1)
@@ -429,6 +482,49 @@ class TelemetryPanel(QWidget):
         # Initially show inactive state
         self.set_active(False)
         
+    def set_units(self, units: UnitPreference):
+        """Switch rate and size units; already plotted points are redrawn in them"""
+        self.units = units
+        for graph in (self.traffic_graph, self.compare_graph):
+            graph.units = units
+            graph.update()
+        self.update_session(self.session)
+        
+    def update_session(self, session: Optional[Dict]):
+        """Show the daemon's session totals (GetSessionCounters entry), or none"""
+        self.session = session
+        if not session:
+            self.session_rx_card.update_value("--")
+            self.session_tx_card.update_value("--")
+            self.session_label.setText("No session (alopexd not running or link not configured)")
+            return
+        
+        rx_value, rx_unit = self.units.format_size(session["rx"]).split(" ")
+        tx_value, tx_unit = self.units.format_size(session["tx"]).split(" ")
+        self.session_rx_card.update_value(rx_value, rx_unit)
+        self.session_tx_card.update_value(tx_value, tx_unit)
+        since = time.strftime("%H:%M", time.localtime(session["started_at"]))
+        self.session_label.setText(f"{session['profile'] or session['interface']} since {since}  (R to reset)")
+        
+    def update_sla(self, probes: List[Dict]):
+        """Show the daemon's SLA probes (GetSlaProbes), breaching ones in red"""
+        self.sla_probes = probes
+        self.sla_group.setVisible(self.active and bool(probes))
+        
+        def ms(value):
+            return "--" if value is None else f"{value:.1f} ms"
+        
+        rows = []
+        for probe in probes:
+            hour = probe["hour"]
+            loss = "--" if hour["loss"] is None else f"{hour['loss']:.1f}%"
+            color = {"breach": "#e74c3c", "ok": "#2ecc71"}.get(probe["state"], "#95a5a6")
+            rows.append(
+                f'<span style="color: {color};">{html.escape(probe["name"])}</span> '
+                f'loss {loss}  rtt {ms(hour["rtt"])}  jitter {ms(hour["jitter"])}'
+            )
+        self.sla_label.setText("<br>".join(rows))
+        
     def set_active(self, active: bool):
         """Set telemetry panel active/inactive state"""
         self.active = active
@@ -447,10 +543,16 @@ class TelemetryPanel(QWidget):
         if active:
             self.inactive_label.hide()
             self.traffic_graph.show()
+            self.compare_graph.setVisible(self.pinned is not None)
+            self.session_group.show()
+            self.sla_group.setVisible(bool(self.sla_probes))
             for card in [self.link_speed_card, self.packets_card, self.errors_card, self.uptime_card]:
                 card.show()
         else:
             self.traffic_graph.hide()
+            self.compare_graph.hide()
+            self.session_group.hide()
+            self.sla_group.hide()
             for card in [self.link_speed_card, self.packets_card, self.errors_card, self.uptime_card]:
                 card.hide()
             # Show inactive message
@@ -459,13 +561,58 @@ class TelemetryPanel(QWidget):
                 self.inactive_added = True
             self.inactive_label.show()
             
+    def set_interface(self, name: str):
+        """Name the interface the main graph shows"""
+        if name != self.interface_name:
+            self.interface_name = name
+            self.traffic_graph.clear()
+        self._update_titles()
+        
+    def set_pinned(self, name: Optional[str]):
+        """Compare against name in a second graph, or with None stop comparing"""
+        self.pinned = name
+        self.compare_graph.clear()
+        self.compare_graph.setVisible(name is not None and self.active)
+        self._update_titles()
+        
+    def update_pinned(self, metrics: Optional[NetworkMetrics]):
+        """Add a sample for the pinned interface (None once it has disappeared)"""
+        if metrics is None:
+            self.compare_graph.add_data_point(0, 0)
+        else:
+            self.compare_graph.add_data_point(metrics.speed_up * 1024, metrics.speed_down * 1024)
+        # One scale for both, or the comparison means nothing
+        scale = max(self.traffic_graph.max_value, self.compare_graph.max_value)
+        for graph in (self.traffic_graph, self.compare_graph):
+            graph.max_value = scale
+            graph.update()
+        
+    def set_latency(self, name: str, ms: Optional[float]):
+        """Gateway round-trip time for name, None when it didn't answer"""
+        self.latency[name] = ms
+        self._update_titles()
+        
+    def _update_titles(self):
+        def title(name, fallback):
+            if name is None:
+                return fallback
+            if name not in self.latency:
+                return name
+            ms = self.latency[name]
+            return f"{name}  ·  gateway {f'{ms:.1f} ms' if ms is not None else 'no reply'}"
+        
+        self.traffic_graph.title = title(self.interface_name, "Network Traffic")
+        self.compare_graph.title = title(self.pinned, "Pinned")
+        self.traffic_graph.update()
+        self.compare_graph.update()
+        
     def update_metrics(self, metrics: NetworkMetrics):
         """Update telemetry with fresh metrics"""
         if not self.active:
             return
             
-        # Update traffic graph
-        self.traffic_graph.add_data_point(metrics.speed_up, metrics.speed_down)
+        # Update traffic graph (NetworkMetrics speeds are KiB/s)
+        self.traffic_graph.add_data_point(metrics.speed_up * 1024, metrics.speed_down * 1024)
         
         # Update metric cards
         self.link_speed_card.update_value(
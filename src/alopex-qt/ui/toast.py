@@ -0,0 +1,52 @@
+"""
+Toast - Short-lived message floating over the bottom of a window
+Confirms quick actions (such as copying an address) without a dialog
+"""
+
+from PyQt6.QtWidgets import QLabel, QWidget
+from PyQt6.QtCore import Qt, QTimer, QEvent
+
+from .arctic_theme import ArcticTheme
+
+class Toast(QLabel):
+    """Overlay label that hides itself after a few seconds"""
+
+    def __init__(self, parent: QWidget, duration_ms: int = 2000):
+        super().__init__(parent)
+        self.duration_ms = duration_ms
+        self.setAlignment(Qt.AlignmentFlag.AlignCenter)
+        self.setAttribute(Qt.WidgetAttribute.WA_TransparentForMouseEvents)
+        self.setStyleSheet(f"""
+            QLabel {{
+                background: {ArcticTheme.BACKGROUND_ELEVATED};
+                color: {ArcticTheme.TEXT_PRIMARY};
+                border: 1px solid {ArcticTheme.PRIMARY_ACCENT};
+                border-radius: 8px;
+                padding: 8px 16px;
+                font-size: 10pt;
+            }}
+        """)
+        self.hide()
+
+        self.hide_timer = QTimer(self)
+        self.hide_timer.setSingleShot(True)
+        self.hide_timer.timeout.connect(self.hide)
+        parent.installEventFilter(self)
+
+    def show_message(self, text: str):
+        """Show text, restarting the timer if a toast is already up"""
+        self.setText(text)
+        self.adjustSize()
+        self._place()
+        self.show()
+        self.raise_()
+        self.hide_timer.start(self.duration_ms)
+
+    def _place(self):
+        parent = self.parentWidget()
+        self.move((parent.width() - self.width()) // 2, parent.height() - self.height() - 48)
+
+    def eventFilter(self, watched, event):
+        if event.type() == QEvent.Type.Resize and self.isVisible():
+            self._place()
+        return False
@@ -0,0 +1,118 @@
+"""
+Troubleshoot Dialog - "Why is my internet down?", one layer at a time
+Opened by 'g'; the daemon runs the checks while the list below follows along,
+then shows what failed and what to try. F5 runs them again
+"""
+
+from typing import Dict, Optional
+from PyQt6.QtWidgets import QDialog, QVBoxLayout, QHBoxLayout, QLabel, QPlainTextEdit, QPushButton
+from PyQt6.QtCore import QTimer
+from PyQt6.QtGui import QKeySequence, QShortcut
+
+from network.ipc import IpcClient, IpcError
+from network.troubleshoot import STEPS, format_steps, summary
+from .arctic_theme import ArcticTheme, FontManager
+
+POLL_INTERVAL_MS = 250
+
+def format_progress(job: Dict) -> str:
+    """The checklist while the job runs: steps behind the current one done, the current one being checked"""
+    current = (job["progress"] or 0) * len(STEPS) // 100
+    lines = []
+    for index, (_, title) in enumerate(STEPS):
+        mark, text = ("•", "done") if index < current else ("…", "checking") if index == current else (" ", "")
+        lines.append(f"{mark} {title:<12}{text}")
+    return "\n".join(lines)
+
+class TroubleshootDialog(QDialog):
+    """Start a Troubleshoot job and render it until it finishes"""
+
+    def __init__(self, daemon: IpcClient, interface: Optional[str] = None, parent=None):
+        super().__init__(parent)
+        self.daemon = daemon
+        self.interface = interface
+        self.job_id: Optional[int] = None
+        self.timer = QTimer(self)
+        self.timer.timeout.connect(self.poll)
+        self.setWindowTitle("Why is my internet down?")
+        self.resize(720, 360)
+        self.setup_ui()
+        self.start()
+
+    def setup_ui(self):
+        layout = QVBoxLayout(self)
+        layout.setContentsMargins(12, 12, 12, 12)
+        layout.setSpacing(8)
+
+        self.title = QLabel()
+        self.title.setFont(FontManager.get_primary_font(12, 600))
+        self.title.setStyleSheet(f"color: {ArcticTheme.TEXT_PRIMARY};")
+        self.title.setWordWrap(True)
+        layout.addWidget(self.title)
+
+        self.text = QPlainTextEdit()
+        self.text.setReadOnly(True)
+        self.text.setFont(FontManager.get_monospace_font(10))
+        self.text.setStyleSheet(f"""
+            QPlainTextEdit {{
+                background: {ArcticTheme.BACKGROUND_PANEL};
+                color: {ArcticTheme.TEXT_PRIMARY};
+                border: 1px solid {ArcticTheme.BORDER_PRIMARY};
+                border-radius: 6px;
+                padding: 8px;
+            }}
+        """)
+        layout.addWidget(self.text)
+
+        buttons = QHBoxLayout()
+        self.again_button = QPushButton("Run Again (F5)")
+        self.again_button.setStyleSheet(ArcticTheme.get_button_style("primary"))
+        self.again_button.clicked.connect(self.start)
+        close_button = QPushButton("Close")
+        close_button.setStyleSheet(ArcticTheme.get_button_style("secondary"))
+        close_button.clicked.connect(self.accept)
+        buttons.addStretch()
+        buttons.addWidget(self.again_button)
+        buttons.addWidget(close_button)
+        layout.addLayout(buttons)
+
+        again_shortcut = QShortcut(QKeySequence("F5"), self)
+        again_shortcut.activated.connect(self.start)
+
+    def start(self):
+        """Ask the daemon to run the checks, unless they are running already"""
+        if self.timer.isActive():
+            return
+        try:
+            job = self.daemon.request("Troubleshoot", interface=self.interface)
+        except IpcError as e:
+            self.title.setText(str(e))
+            return
+        self.job_id = job["id"]
+        self.again_button.setEnabled(False)
+        self.title.setText(f"{job['description']}...")
+        self.text.setPlainText(format_progress(job))
+        self.timer.start(POLL_INTERVAL_MS)
+
+    def poll(self):
+        try:
+            job = self.daemon.request("GetJob", id=self.job_id)
+        except IpcError as e:
+            self.finish(str(e))
+            return
+        if job["state"] == "running":
+            self.text.setPlainText(format_progress(job))
+        elif job["state"] == "completed":
+            self.text.setPlainText("\n".join(format_steps(job["result"])))
+            self.finish(summary(job["result"]))
+        else:
+            self.finish(job["message"])
+
+    def finish(self, title: str):
+        self.timer.stop()
+        self.again_button.setEnabled(True)
+        self.title.setText(title)
+
+    def done(self, result: int):
+        self.timer.stop()
+        super().done(result)
@@ -0,0 +1,120 @@
+"""
+Access control - roles for control requests, and the server enforcing them
+Local callers by uid and group, remote ones by certificate; the TLS case needs the openssl CLI
+"""
+
+import grp
+import pwd
+import sys
+import shutil
+import asyncio
+import tempfile
+import unittest
+from pathlib import Path
+
+REPO_ROOT = Path(__file__).resolve().parents[2]
+sys.path.insert(0, str(REPO_ROOT / "src" / "alopex-qt"))
+
+from network.access import AccessPolicy, builtin_role
+from network.ipc import ErrorCode, IpcClient, IpcError, IpcServer, parse_remote, server_tls_context
+from test_fleet import make_certificates
+
+class AccessTest(unittest.TestCase):
+    def test_roles(self):
+        nobody = pwd.getpwnam("nobody")
+        group = grp.getgrgid(nobody.pw_gid).gr_name
+        policy = AccessPolicy({"users": {"nobody": "observer", "54321": "admin"}, "groups": {group: "operator"},
+                               "certificates": {"laptop": "operator"}, "default_role": None,
+                               "permissions": {"SetDns": "operator", "Scan*": "admin"}}, own_uid=4242)
+        # The highest role of every entry that matches
+        self.assertEqual(policy.role(nobody.pw_uid), "operator")
+        self.assertEqual(policy.role(54321), "admin")
+        self.assertEqual((policy.role(0), policy.role(4242)), ("admin", "admin"))
+        self.assertIsNone(policy.role(54322))
+        self.assertEqual((policy.role(None, "laptop"), policy.role(None, "intruder"), policy.role(None)),
+                         ("operator", None, None))
+        self.assertEqual(AccessPolicy({}, own_uid=4242).role(54322), "observer")
+
+        self.assertEqual([builtin_role(request) for request in ("GetStatus", "Subscribe", "Connect", "CreateProfile",
+                                                                "SomethingNew")],
+                         ["observer", "observer", "operator", "admin", "admin"])
+        self.assertEqual((policy.required("SetDns"), policy.required("ScanWifi")), ("operator", "admin"))
+        self.assertTrue(policy.allows("operator", "Disconnect"))
+        self.assertFalse(policy.allows("operator", "ScanWifi"))
+        self.assertFalse(policy.allows(None, "GetStatus"))
+        capabilities = policy.capabilities("operator", ["GetStatus", "Disconnect", "DeleteProfile", "ScanLan"])
+        self.assertEqual(capabilities, {"observe": True, "operate": True, "administer": False,
+                                        "denied": ["DeleteProfile", "ScanLan"]})
+        for config in ({"users": {"alice": "root"}}, {"default_role": "guest"}, {"permissions": {"Connect": None}}):
+            with self.assertRaises(ValueError):
+                AccessPolicy(config)
+
+    @unittest.skipUnless(shutil.which("openssl"), "needs the openssl CLI")
+    def test_enforced(self):
+        with tempfile.TemporaryDirectory() as directory:
+            directory = Path(directory)
+            make_certificates(directory)
+
+            def client(name):
+                return IpcClient(timeout=5.0, remote=parse_remote(
+                    f"tls://localhost:{port}", ca_file=str(directory / "ca.pem"),
+                    cert_file=str(directory / f"{name}.pem"), key_file=str(directory / f"{name}.key")))
+
+            def attempt(ipc_client, request_type, **params):
+                try:
+                    return ipc_client.request(request_type, **params)
+                except IpcError as e:
+                    return e.code, e.details
+
+            async def scenario():
+                records = []
+                access = AccessPolicy({"certificates": {"laptop": "observer"}, "default_role": None,
+                                       "permissions": {"GetLogs": "admin"}})
+                server = IpcServer(directory / "alopex.sock", audit=records.append, access=access)
+                server.register("GetStatus", lambda: {"hostname": "gw"})
+                server.register("GetLogs", lambda: [])
+                server.register("Disconnect", lambda interface: None)
+                server.register("RelayRequest", lambda host, relayed, params=None: {"host": host, "relayed": relayed})
+                context = server_tls_context(str(directory / "ca.pem"), str(directory / "localhost.pem"),
+                                             str(directory / "localhost.key"))
+                await server.start_remote("127.0.0.1", 0, context)
+                nonlocal port
+                port = server.remote_server.sockets[0].getsockname()[1]
+                loop = asyncio.get_running_loop()
+                laptop, intruder = client("laptop"), client("intruder")
+                results = [
+                    await loop.run_in_executor(None, laptop.hello),
+                    await loop.run_in_executor(None, attempt, laptop, "GetStatus"),
+                    await loop.run_in_executor(None, lambda: attempt(laptop, "Disconnect", interface="eth0")),
+                    await loop.run_in_executor(None, lambda: attempt(laptop, "RelayRequest", host="edge",
+                                                                     relayed="GetStatus")),
+                    await loop.run_in_executor(None, lambda: attempt(laptop, "RelayRequest", host="edge",
+                                                                     relayed="GetLogs")),
+                    await loop.run_in_executor(None, intruder.hello),
+                    await loop.run_in_executor(None, attempt, intruder, "GetStatus")
+                ]
+                await server.stop()
+                return results, records, laptop
+
+            port = 0
+            results, records, laptop = asyncio.run(scenario())
+        hello, status, disconnect, relayed, relayed_logs, stranger, refused = results
+        self.assertEqual((hello["certificate"], hello["role"]), ("laptop", "observer"))
+        self.assertEqual(hello["capabilities"]["denied"], ["Disconnect", "GetLogs"])
+        self.assertEqual((laptop.can("GetStatus"), laptop.can("Disconnect")), (True, False))
+        self.assertEqual(status, {"hostname": "gw"})
+        self.assertEqual(disconnect, (ErrorCode.PERMISSION_DENIED,
+                                      {"request": "Disconnect", "role": "observer", "required": "operator"}))
+        # A relayed request needs the role of the request it carries, not just that of RelayRequest
+        self.assertEqual(relayed, {"host": "edge", "relayed": "GetStatus"})
+        self.assertEqual(relayed_logs, (ErrorCode.PERMISSION_DENIED,
+                                        {"request": "GetLogs", "role": "observer", "required": "admin"}))
+        # Callers without a role still get the handshake, and nothing else
+        self.assertEqual((stranger["role"], stranger["capabilities"]["observe"]), (None, False))
+        self.assertEqual(refused[0], ErrorCode.PERMISSION_DENIED)
+        self.assertEqual([(record["request"], record["outcome"]) for record in records],
+                         [("Disconnect", "PermissionDenied"), ("RelayRequest", "ok"),
+                          ("RelayRequest", "PermissionDenied")])
+
+if __name__ == "__main__":
+    unittest.main()
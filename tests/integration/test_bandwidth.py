@@ -0,0 +1,67 @@
+"""
+Bandwidth history - per-minute samples, time ranges and the CSV export
+"""
+
+import sys
+import datetime
+import tempfile
+import unittest
+from pathlib import Path
+
+REPO_ROOT = Path(__file__).resolve().parents[2]
+sys.path.insert(0, str(REPO_ROOT / "src" / "alopex-qt"))
+
+from network.bandwidth import BandwidthHistory, parse_time, to_csv
+from network.discovery import InterfaceType, NetworkInterface, NetworkMetrics
+
+class BandwidthHistoryTest(unittest.TestCase):
+    def setUp(self):
+        self.tmp = tempfile.TemporaryDirectory()
+        self.path = Path(self.tmp.name) / "bandwidth"
+
+    def tearDown(self):
+        self.tmp.cleanup()
+
+    @staticmethod
+    def interface(rx, tx, name="eth0"):
+        return NetworkInterface(name=name, interface_type=InterfaceType.ETHERNET, status="Connected",
+                                metrics=NetworkMetrics(bytes_rx=rx, bytes_tx=tx))
+
+    def test_minutes(self):
+        history = BandwidthHistory(self.path)
+        start = 1_700_000_040.0  # a minute boundary
+        history.record([self.interface(1000, 100)], now=start + 5)
+        history.record([self.interface(7000, 700)], now=start + 30)
+        # Counters reset (device re-created) in the next minute: the new count is all traffic
+        history.record([self.interface(500, 50)], now=start + 65)
+        history.record([self.interface(500, 50)], now=start + 130)
+        history.record([self.interface(500, 50)], now=start + 190)
+
+        samples = history.query("eth0")
+        self.assertEqual([(s["timestamp"], s["rx_bytes"], s["tx_bytes"]) for s in samples],
+                         [(start, 6000, 600), (start + 60, 500, 50)])
+        self.assertEqual(samples[0]["rx_rate"], 100)
+        self.assertEqual(len(history.query("eth0", since=start + 120)), 0)
+        self.assertEqual(len(history.query("eth0", since=start + 30, until=start + 60)), 1)
+        self.assertEqual(history.interfaces(), ["eth0"])
+
+        csv_text = to_csv(samples)
+        self.assertTrue(csv_text.startswith("time,timestamp,interface,rx_bytes,tx_bytes,rx_rate,tx_rate\n"))
+        self.assertEqual(len(csv_text.splitlines()), 3)
+
+        # A fresh instance reads the same file; pruning drops what is past retention
+        history = BandwidthHistory(self.path, retention_days=1)
+        history.prune(now=start + 86400 + 30)
+        self.assertEqual([s["timestamp"] for s in history.query("eth0")], [start + 60])
+
+    def test_parse_time(self):
+        now = 1_700_000_000.0
+        self.assertEqual(parse_time("24h", now), now - 86400)
+        self.assertEqual(parse_time("90m", now), now - 5400)
+        self.assertEqual(parse_time("now", now), now)
+        self.assertEqual(parse_time("2026-10-01T08:00"), datetime.datetime(2026, 10, 1, 8, 0).timestamp())
+        with self.assertRaises(ValueError):
+            parse_time("yesterday")
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,118 @@
+"""
+Bluetooth - bluetoothctl output, device aliases and OBEX transfer replies
+"""
+
+import sys
+import tempfile
+import unittest
+from pathlib import Path
+
+REPO_ROOT = Path(__file__).resolve().parents[2]
+sys.path.insert(0, str(REPO_ROOT / "src" / "alopex-qt"))
+
+from network.bluetooth import BluetoothAliases, normalize_address, parse_devices, parse_info, sort_devices
+from network.obex import gdbus_error, parse_object_path, parse_transfer
+
+class BluetoothTest(unittest.TestCase):
+    """bluetoothctl output to device records"""
+
+    LE_INFO = """Device F1:22:33:44:55:66 (random)
+\tName: Polar H10
+\tAlias: Polar H10
+\tAppearance: 0x0341
+\tPaired: no
+\tTrusted: no
+\tBlocked: no
+\tConnected: yes
+\tLegacyPairing: no
+\tUUID: Generic Access Profile    (00001800-0000-1000-8000-00805f9b34fb)
+\tUUID: Generic Attribute Profile (00001801-0000-1000-8000-00805f9b34fb)
+\tUUID: Heart Rate                (0000180d-0000-1000-8000-00805f9b34fb)
+\tUUID: Battery Service           (0000180f-0000-1000-8000-00805f9b34fb)
+\tRSSI: 0xffffffb9 (-71)
+\tTxPower: 0x0004 (4)
+\tBattery Percentage: 0x40 (64)
+"""
+
+    CLASSIC_INFO = """Device 00:1B:66:DD:EE:01 (public)
+\tName: Headset
+\tAlias: Living room headset
+\tClass: 0x00240404
+\tIcon: audio-headset
+\tPaired: yes
+\tTrusted: yes
+\tConnected: no
+\tUUID: Audio Sink                (0000110b-0000-1000-8000-00805f9b34fb)
+\tRSSI: -58
+"""
+
+    def test_le_device(self):
+        device = parse_info(self.LE_INFO)
+        self.assertEqual(device["type"], "le")
+        self.assertEqual(device["address_type"], "random")
+        self.assertEqual((device["rssi"], device["tx_power"], device["battery"]), (-71, 4, 64))
+        self.assertTrue(device["connected"])
+        self.assertFalse(device["paired"])
+        self.assertTrue(device["battery_service"])
+        self.assertEqual([s["name"] for s in device["services"]][2:], ["Heart Rate", "Battery Service"])
+        self.assertIsNone(device["vendor"])
+
+    def test_classic_device(self):
+        # Older BlueZ prints RSSI as a plain number
+        device = parse_info(self.CLASSIC_INFO)
+        self.assertEqual(device["type"], "classic")
+        self.assertEqual((device["name"], device["alias"], device["icon"]), ("Headset", "Living room headset",
+                                                                            "audio-headset"))
+        self.assertEqual(device["rssi"], -58)
+        self.assertIsNone(device["battery"])
+        self.assertFalse(device["battery_service"])
+        self.assertTrue(device["paired"] and device["trusted"])
+
+    def test_unknown_device(self):
+        self.assertIsNone(parse_info("Device AA:BB:CC:DD:EE:FF not available\n"))
+        self.assertIsNone(parse_info(""))
+
+    def test_devices_and_addresses(self):
+        self.assertEqual(parse_devices("Device F1:22:33:44:55:66 Polar H10\nDevice 00:1B:66:DD:EE:01 (public) Headset\n"
+                                       "[CHG] Controller 00:11:22:33:44:55 Discovering: yes\n"),
+                         [("F1:22:33:44:55:66", "Polar H10"), ("00:1B:66:DD:EE:01", "Headset")])
+        self.assertEqual(normalize_address(" f1-22-33-44-55-66 "), "F1:22:33:44:55:66")
+        with self.assertRaises(ValueError):
+            normalize_address("F1:22:33")
+
+    def test_sorting(self):
+        def device(mac, rssi, connected=False, paired=False):
+            return dict(parse_info(self.CLASSIC_INFO), mac=mac, rssi=rssi, connected=connected, paired=paired)
+        devices = [device("01", -40), device("02", None, paired=True), device("03", -80, connected=True),
+                   device("04", -60, paired=True), device("05", None), device("06", -55)]
+        self.assertEqual([d["mac"] for d in sort_devices(devices)], ["03", "04", "02", "01", "06", "05"])
+
+    def test_aliases(self):
+        with tempfile.TemporaryDirectory() as directory:
+            aliases = BluetoothAliases(Path(directory))
+            aliases.set("00:1B:66:DD:EE:01", " Desk\theadset ")
+            with self.assertRaises(ValueError):
+                aliases.set("00:1B:66:DD:EE:01", "x" * 49)
+            # Kept across restarts, and layered over what BlueZ reports
+            device = BluetoothAliases(Path(directory)).apply(parse_info(self.CLASSIC_INFO))
+            self.assertEqual((device["name"], device["alias"]), ("Headset", "Desk headset"))
+            aliases.set("00:1B:66:DD:EE:01", "")
+            self.assertEqual(BluetoothAliases(Path(directory)).apply(parse_info(self.CLASSIC_INFO))["alias"],
+                             "Living room headset")
+            self.assertIsNone(aliases.apply(None))
+
+    def test_obex_replies(self):
+        reply = ("(objectpath '/org/bluez/obex/client/session0/transfer0', {'Status': <'queued'>, "
+                 "'Session': <objectpath '/org/bluez/obex/client/session0'>, 'Name': <'photo.jpg'>, "
+                 "'Size': <uint64 52340>, 'Filename': <'/home/alice/photo.jpg'>})\n")
+        self.assertEqual(parse_object_path(reply), "/org/bluez/obex/client/session0/transfer0")
+        self.assertEqual(parse_transfer(reply), {"status": "queued", "size": 52340, "transferred": None})
+        signal = ("/org/bluez/obex/client/session0/transfer0: org.freedesktop.DBus.Properties.PropertiesChanged "
+                  "('org.bluez.obex.Transfer1', {'Status': <'active'>, 'Transferred': <uint64 32768>}, @as [])")
+        self.assertEqual(parse_transfer(signal), {"status": "active", "size": None, "transferred": 32768})
+        self.assertEqual(gdbus_error("Error: GDBus.Error:org.bluez.obex.Error.Failed: Unable to find service record\n"
+                                     "(According to introspection data, you need to pass 'sa{sv}')\n"),
+                         "Unable to find service record")
+
+if __name__ == "__main__":
+    unittest.main()
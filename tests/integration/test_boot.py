@@ -0,0 +1,51 @@
+"""
+Boot-critical profiles - retries until they come up, and the wait that times out
+"""
+
+import sys
+import asyncio
+import unittest
+from pathlib import Path
+
+REPO_ROOT = Path(__file__).resolve().parents[2]
+sys.path.insert(0, str(REPO_ROOT / "src" / "alopex-qt"))
+
+from network.boot import BootCritical
+
+class BootCriticalTest(unittest.TestCase):
+
+    def test_retries_until_up(self):
+        up = set()
+        attempts = []
+        online = []
+
+        async def connect(name):
+            attempts.append(name)
+            if name == "Uplink" and attempts.count(name) < 3:
+                return "no carrier"
+            up.add(name)
+            return None
+
+        boot = BootCritical(connect, lambda name: name in up, online.append, initial=0.01, maximum=0.02)
+        self.assertFalse(boot.status()["online"])
+
+        async def scenario():
+            waiter = asyncio.create_task(boot.wait(5))
+            await boot.run([{"name": "Uplink", "interface": "eth0"}, {"name": "Storage", "interface": "eth1"}])
+            return await waiter
+
+        status = asyncio.run(scenario())
+        self.assertTrue(status["online"])
+        self.assertEqual([(entry["profile"], entry["up"], entry["attempts"], entry["last_error"])
+                          for entry in status["profiles"]], [("Uplink", True, 3, None), ("Storage", True, 1, None)])
+        self.assertEqual(online, [["Uplink", "Storage"]])
+
+    def test_wait_times_out(self):
+        async def scenario():
+            boot = BootCritical(lambda name: None, lambda name: False)
+            return await boot.wait(0.01)
+
+        self.assertFalse(asyncio.run(scenario())["online"])
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,44 @@
+"""
+Cable test - ethtool cable test results and the report built from them
+"""
+
+import sys
+import unittest
+from pathlib import Path
+
+REPO_ROOT = Path(__file__).resolve().parents[2]
+sys.path.insert(0, str(REPO_ROOT / "src" / "alopex-qt"))
+
+from network.cable_test import describe, parse_cable_test, report
+
+class CableTestTest(unittest.TestCase):
+    OUTPUT = """Cable test started for device eth0.
+Cable test completed for device eth0.
+Pair A code OK
+Pair B code Short within Pair
+Pair C code Open Circuit
+Pair D code Impedance Mismatch
+Pair B, fault length: 12.80m
+Pair C, fault length: 1.60m
+"""
+
+    def test_parse(self):
+        pairs = parse_cable_test(self.OUTPUT)
+        self.assertEqual([(p["pair"], p["status"], p["fault_length_m"]) for p in pairs],
+                         [("A", "ok", None), ("B", "short", 12.8), ("C", "open", 1.6), ("D", "unknown", None)])
+        self.assertEqual(parse_cable_test("Cable test started for device eth0.\n"), [])
+
+    def test_report(self):
+        pairs = parse_cable_test(self.OUTPUT)
+        self.assertEqual(describe(pairs[2]), "open circuit at 1.6 m")
+        result = report("eth0", pairs)
+        self.assertFalse(result["healthy"])
+        self.assertIn("pair C open circuit at 1.6 m", result["summary"])
+        self.assertNotIn("pair A", result["summary"])
+
+        healthy = report("eth0", [dict(p, status="ok", fault_length_m=None) for p in pairs])
+        self.assertEqual((healthy["healthy"], healthy["summary"]), (True, "eth0: all 4 pairs OK"))
+        self.assertFalse(report("eth0", [])["healthy"])
+
+if __name__ == "__main__":
+    unittest.main()
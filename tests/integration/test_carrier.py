@@ -0,0 +1,34 @@
+"""
+Carrier monitor - ip monitor link lines and per-interface uptime
+"""
+
+import sys
+import unittest
+from pathlib import Path
+
+REPO_ROOT = Path(__file__).resolve().parents[2]
+sys.path.insert(0, str(REPO_ROOT / "src" / "alopex-qt"))
+
+from network.carrier import CarrierTracker, parse_monitor_line
+
+class CarrierMonitorTest(unittest.TestCase):
+    def test_parse_monitor_line(self):
+        self.assertEqual(parse_monitor_line(
+            "3: wlan0: <BROADCAST,MULTICAST,UP,LOWER_UP> mtu 1500 qdisc noqueue state UP mode DORMANT"),
+            ("wlan0", True))
+        self.assertEqual(parse_monitor_line(
+            "2: eth0: <NO-CARRIER,BROADCAST,MULTICAST,UP> mtu 1500 qdisc fq_codel state DOWN"), ("eth0", False))
+        self.assertEqual(parse_monitor_line(
+            "Deleted 7: veth1@if6: <BROADCAST,MULTICAST,UP,LOWER_UP> mtu 1500"), ("veth1", False))
+        self.assertIsNone(parse_monitor_line("    link/ether 52:54:00:12:34:56 brd ff:ff:ff:ff:ff:ff"))
+
+    def test_only_transitions_move_timestamp(self):
+        tracker = CarrierTracker(discovery=None)
+        tracker.observe("eth0", True, at=100.0)
+        tracker.observe("eth0", True, at=200.0)
+        self.assertEqual(tracker.carrier_up["eth0"], 100.0)
+        tracker.observe("eth0", False)
+        self.assertIsNone(tracker.uptime("eth0"))
+
+if __name__ == "__main__":
+    unittest.main()
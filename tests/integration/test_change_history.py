@@ -0,0 +1,40 @@
+"""
+Change history - what Undo takes back, and what it forgets
+"""
+
+import sys
+import unittest
+from pathlib import Path
+
+REPO_ROOT = Path(__file__).resolve().parents[2]
+sys.path.insert(0, str(REPO_ROOT / "src" / "alopex-qt"))
+
+from network.change_history import ChangeHistory
+
+class ChangeHistoryTest(unittest.TestCase):
+    """What Undo takes back, and what it forgets"""
+
+    def test_newest_first_and_bounded(self):
+        history = ChangeHistory(size=2)
+        self.assertIsNone(history.pop())
+        for number in range(3):
+            history.record("profile", f"Edit {number}", {"profile": "Wired", "fields": {"password": "secret"}})
+        self.assertEqual([change["summary"] for change in history.list()], ["Edit 2", "Edit 1"])
+        self.assertNotIn("previous", history.list()[0])
+        self.assertEqual(history.pop().id, 3)
+        self.assertEqual(history.pop().summary, "Edit 1")
+        self.assertIsNone(history.pop())
+
+    def test_latest_stays_until_removed(self):
+        history = ChangeHistory()
+        self.assertIsNone(history.latest())
+        first = history.record("dns", "Set DNS of eth0 to 9.9.9.9", {"interface": "eth0"})
+        self.assertIs(history.latest(), first)
+        self.assertIs(history.latest(), first)
+        # A change recorded while the undo ran is not what gets dropped
+        second = history.record("profile", "Edit Wired", {"profile": "Wired"})
+        history.remove(first)
+        self.assertEqual([change["id"] for change in history.list()], [second.id])
+
+if __name__ == "__main__":
+    unittest.main()
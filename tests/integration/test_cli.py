@@ -0,0 +1,1720 @@
+"""
+Integration tests - run alopex-cli against alopexd --mock
+Checks output and exit codes the way a shell script would see them
+"""
+
+import os
+import sys
+import json
+import socket
+import time
+import datetime
+import tarfile
+import tempfile
+import threading
+import unittest
+import subprocess
+from pathlib import Path
+from typing import Dict
+
+REPO_ROOT = Path(__file__).resolve().parents[2]
+DAEMON = REPO_ROOT / "src" / "alopex-daemon" / "alopexd.py"
+CLI = REPO_ROOT / "src" / "alopex-daemon" / "alopex-cli.py"
+NOTIFY = REPO_ROOT / "src" / "alopex-daemon" / "alopex-notify.py"
+GUI = REPO_ROOT / "src" / "alopex-qt" / "main.py"
+sys.path.insert(0, str(REPO_ROOT / "src" / "alopex-qt"))
+
+from network.ipc import IpcClient, IpcError, ErrorCode
+
+SCENARIO = {
+    "links": [
+        {"name": "eth0", "type": "Ethernet", "up": True, "address": "192.168.1.50",
+         "gateway": "192.168.1.1", "link_speed": 1000, "rx_rate": 250000, "tx_rate": 40000,
+         "queues": [{"queue": 0, "rx_packets": 5200, "tx_packets": 900}, {"queue": 1, "rx_packets": 4800,
+                                                                        "tx_packets": 1100}],
+         "irqs": [{"irq": 129, "name": "eth0-TxRx-0", "interrupts": 5000, "cpus": [0]},
+                  {"irq": 130, "name": "eth0-TxRx-1", "interrupts": 4000, "cpus": [0]}]},
+        {"name": "eth1", "type": "Ethernet", "up": False, "address": "192.168.2.10",
+         "gateway": "192.168.2.1", "gateway_mac": "52:54:00:00:02:01", "mac": "00:1b:21:3a:4c:51",
+         "cable": {"C": {"status": "open", "fault_length_m": 1.6}},
+         "module": {"identifier": "SFP", "connector": "LC", "vendor": "FS", "part_number": "SFP-10GSR-85",
+                    "revision": "A", "serial": "G1234567", "date_code": None, "type": "10G Ethernet: 10G Base-SR",
+                    "wavelength_nm": 850.0, "temperature_c": 34.5, "voltage_v": 3.312, "bias_ma": [6.75],
+                    "tx_power_dbm": [-2.46], "rx_power_dbm": [-3.64], "diagnostics": True, "alarms": []}},
+        {"name": "wlan0", "type": "WiFi", "up": False, "address": "10.20.0.42", "gateway": "10.20.0.1"},
+        {"name": "br-5e7d1f0a9c3b", "type": "Bridge", "up": True, "address": "172.18.0.1"},
+        {"name": "veth41d2c0e", "type": "Other", "detail": "veth", "up": True, "master": "br-5e7d1f0a9c3b"},
+        {"name": "podman0", "type": "Bridge", "up": True},
+        {"name": "virbr0", "type": "Bridge", "up": True, "address": "192.168.122.1"}
+    ],
+    "libvirt_networks": [
+        {"name": "default", "bridge": "virbr0", "subnets": ["192.168.122.0/24"],
+         "guests": [{"domain": "fedora-dev", "mac": "52:54:00:3b:7c:11", "ip": "192.168.122.41",
+                     "hostname": "fedora-dev", "interface": "vnet0"}]},
+        {"name": "isolated", "bridge": "virbr1", "forward": "isolated", "active": False}
+    ],
+    "container_engines": {
+        "docker": [
+            {"Name": "lab", "Id": "5e7d1f0a9c3b8a7d", "Driver": "bridge", "Options": {},
+             "IPAM": {"Config": [{"Subnet": "172.18.0.0/16"}]},
+             "Containers": {"c0ffee": {"Name": "web", "MacAddress": "02:42:ac:12:00:02",
+                                       "IPv4Address": "172.18.0.2/16"}}},
+            {"Name": "host", "Id": "9f8e7d6c5b4a3921", "Driver": "host"}
+        ]
+    },
+    "profiles": [],
+    "wifi_networks": [
+        {"ssid": "AlopexHQ", "signal": -45, "security": "WPA2", "password": "arctic-fox", "channel": 36},
+        {"ssid": "Guest", "signal": -67, "security": "Open", "channel": 1},
+        {"ssid": "Cabin", "signal": -60, "security": "WPA2", "password": "log-fire", "channel": 3},
+        {"ssid": "CorpSecure", "signal": -58, "security": "WPA2-Enterprise", "eap": "tls", "channel": 149}
+    ],
+    "wifi_frames": [
+        {"interface": "wlan0", "kind": "deauth", "bssid": "02:00:00:00:00:01", "reason": 7} for _ in range(5)
+    ],
+    "vpn": [
+        {"name": "office", "address": "10.99.0.7"},
+        {"name": "corp", "address": "10.98.0.2", "certificates": True}
+    ],
+    "units": ["syncthing.service"],
+    "script": [],
+    "delays": {"dhcp": 0.1, "associate": 0.1, "vpn": 0.1, "scan": 0.1},
+    "dns_latency": {"198.51.100.7": None, "203.0.113.5": 450},
+    "ports": {"192.168.1.20:445": 2.5, "203.0.113.9:22": None},
+    "dual_stack": {"www.google.com:443": {"ipv4": 18, "ipv6": None},
+                   "www.cloudflare.com:443": {"ipv4": 12, "ipv6": 15},
+                   "v4only.example:443": {"ipv4": 30}},
+    "flows": [
+        {"src": "192.168.1.50", "dst": "140.82.112.3", "dport": 443, "state": "ESTABLISHED", "bytes": 5000},
+        {"src": "192.168.1.50", "dst": "140.82.112.3", "dport": 22, "state": "ESTABLISHED", "bytes": 1000},
+        {"src": "192.168.1.50", "dst": "192.0.2.53", "protocol": "udp", "dport": 53},
+        {"src": "10.42.0.11", "dst": "140.82.112.3", "dport": 443, "state": "ESTABLISHED", "snat": "192.168.1.50",
+         "bytes": 2048},
+        {"src": "10.42.0.11", "dst": "198.51.100.80", "dport": 443, "state": "SYN_SENT", "snat": "192.168.1.50"}
+    ],
+    "rfkill": [
+        {"type": "wlan", "name": "phy0", "interface": "wlan0"},
+        {"type": "bluetooth", "name": "hci0"},
+        {"type": "wwan", "name": "wwan0", "hard": True}
+    ],
+    "hotspot_clients": [
+        {"mac": "3c:22:fb:10:20:30", "hostname": "pixel", "signal": -48, "rx_rate": 2000, "tx_rate": 30000},
+        {"mac": "9a:8b:7c:00:11:22", "signal": -77}
+    ],
+    "bluetooth": [
+        {"mac": "AA:BB:CC:DD:EE:01", "name": "Headset", "type": "classic", "rssi": -58,
+         "services": ["Audio Sink", "Handsfree"]},
+        {"mac": "F1:22:33:44:55:66", "name": "Heart Rate", "type": "le", "rssi": -71, "battery": 64,
+         "services": ["Heart Rate", "Battery Service"]}
+    ]
+}
+
+def write_certificates(directory: Path) -> Dict[str, Path]:
+    """A CA (ten years) and a client certificate signed by it that runs out in ten days, with its key"""
+    from cryptography import x509
+    from cryptography.hazmat.primitives import hashes, serialization
+    from cryptography.hazmat.primitives.asymmetric import ec
+
+    def issue(subject, issuer, key, signing_key, days, ca):
+        now = datetime.datetime.now(datetime.timezone.utc)
+        name = x509.Name([x509.NameAttribute(x509.NameOID.COMMON_NAME, subject)])
+        issuer_name = x509.Name([x509.NameAttribute(x509.NameOID.COMMON_NAME, issuer)])
+        return (x509.CertificateBuilder().subject_name(name).issuer_name(issuer_name)
+                .public_key(key.public_key()).serial_number(x509.random_serial_number())
+                .not_valid_before(now - datetime.timedelta(days=1)).not_valid_after(now + datetime.timedelta(days=days))
+                .add_extension(x509.BasicConstraints(ca=ca, path_length=None), critical=True)
+                .sign(signing_key, hashes.SHA256()))
+
+    ca_key, client_key = ec.generate_private_key(ec.SECP256R1()), ec.generate_private_key(ec.SECP256R1())
+    ca = issue("Alopex Test CA", "Alopex Test CA", ca_key, ca_key, 3650, True)
+    client = issue("alice@corp", "Alopex Test CA", client_key, ca_key, 10, False)
+    paths = {"ca": directory / "ca.pem", "client": directory / "alice.pem"}
+    paths["ca"].write_bytes(ca.public_bytes(serialization.Encoding.PEM))
+    paths["client"].write_bytes(client.public_bytes(serialization.Encoding.PEM) + client_key.private_bytes(
+        serialization.Encoding.PEM, serialization.PrivateFormat.PKCS8,
+        serialization.BestAvailableEncryption(b"hunter22")))
+    return paths
+
+NM_KEYFILES = {
+    "Home.nmconnection": """
+[connection]
+id=Home
+type=wifi
+autoconnect=false
+autoconnect-priority=5
+
+[wifi]
+mode=infrastructure
+ssid=Cabin
+
+[wifi-security]
+key-mgmt=wpa-psk
+psk=log-fire
+
+[ipv4]
+method=auto
+""",
+    "Wired static.nmconnection": """
+[connection]
+id=Wired static
+type=ethernet
+interface-name=eth1
+autoconnect=false
+
+[ipv4]
+method=manual
+address1=192.168.2.10/24,192.168.2.1
+dns=192.168.2.1;9.9.9.9;
+
+[ipv6]
+method=manual
+address1=fd00::10/64
+""",
+    "Corp.nmconnection": """
+[connection]
+id=Corp
+type=wifi
+interface-name=wlan0
+
+[wifi]
+ssid=CorpNet
+
+[wifi-security]
+key-mgmt=wpa-eap
+""",
+    "br0.nmconnection": """
+[connection]
+id=br0
+type=bridge
+interface-name=br0
+"""
+}
+
+NETWORKD_FILES = {
+    "10-eth1.network": """
+[Match]
+Name=eth1
+
+[Network]
+VLAN=eth1.20
+""",
+    "20-eth1.20.netdev": """
+[NetDev]
+Name=eth1.20
+Kind=vlan
+
+[VLAN]
+Id=20
+""",
+    "20-eth1.20.network": """
+[Match]
+Name=eth1.20
+
+[Network]
+Address=10.20.20.5/24
+Address=fd20::5/64
+Gateway=10.20.20.1
+DNS=10.20.20.1 9.9.9.9
+""",
+    "30-all.network": """
+[Match]
+Name=en*
+
+[Network]
+DHCP=yes
+"""
+}
+
+NETPLAN_FILE = """
+network:
+  version: 2
+  ethernets:
+    eth0:
+      dhcp4: true
+    eth1: {}
+  bonds:
+    bond0:
+      interfaces: [eth1]
+      parameters:
+        mode: active-backup
+      addresses: [192.168.7.2/24]
+      routes:
+        - to: default
+          via: 192.168.7.1
+  wifis:
+    wlan0:
+      dhcp4: true
+      access-points:
+        Cabin:
+          password: log-fire
+  modems:
+    cdc-wdm0:
+      apn: internet
+"""
+
+class CliTest(unittest.TestCase):
+    """One mock daemon for the class, driven through the alopex-cli executable"""
+
+    @classmethod
+    def setUpClass(cls):
+        cls.state_dir = tempfile.TemporaryDirectory(prefix="alopex-cli-test-")
+        state_path = Path(cls.state_dir.name)
+        cls.socket_path = state_path / "alopexd.sock"
+
+        scenario_file = state_path / "scenario.json"
+        scenario_file.write_text(json.dumps(SCENARIO))
+        # wlan0 starts down, so the group fails over to eth0 straight away and stays there
+        (state_path / "enterprise.json").write_text(json.dumps({
+            "failover": {"interval": 0.2, "groups": [
+                {"name": "uplink", "primary": "wlan0", "backup": "eth0", "failback": "never"}
+            ]}
+        }))
+
+        cls.daemon = subprocess.Popen(
+            [sys.executable, str(DAEMON), "--mock", "--scenario", str(scenario_file),
+             "--state-dir", str(state_path), "--socket", str(cls.socket_path)],
+            stdout=subprocess.DEVNULL, stderr=subprocess.DEVNULL
+        )
+        cls.client = IpcClient(cls.socket_path, timeout=5.0)
+
+        deadline = time.monotonic() + 10
+        while cls.client.try_request("GetStatus") is None:
+            if time.monotonic() > deadline or cls.daemon.poll() is not None:
+                cls.tearDownClass()
+                raise RuntimeError("alopexd --mock did not come up")
+            time.sleep(0.1)
+
+    @classmethod
+    def tearDownClass(cls):
+        cls.daemon.terminate()
+        try:
+            cls.daemon.wait(timeout=10)
+        except subprocess.TimeoutExpired:
+            cls.daemon.kill()
+        cls.state_dir.cleanup()
+
+    def setUp(self):
+        self.profiles_before = {p["name"] for p in self.client.request("ListProfiles")}
+        self.active_before = set(self.client.request("GetStatus")["active_profiles"])
+
+    def tearDown(self):
+        # wlan0 is the one radio every test shares: a wifi profile a test made (nm connect makes them with
+        # auto-connect on) would rejoin its network in a later test, so drop those and take wlan0 back down
+        for profile in self.client.request("ListProfiles"):
+            if profile["interface"] == "wlan0" and profile["name"] not in self.profiles_before:
+                self.client.try_request("DeleteProfile", name=profile["name"])
+        if "wlan0" not in self.active_before and "wlan0" in self.client.request("GetStatus")["active_profiles"]:
+            self.client.try_request("Disconnect", interface="wlan0")
+
+    def cli(self, *args, stdin=None, socket_path=None, env=None):
+        return subprocess.run(
+            [sys.executable, str(CLI), "--socket", str(socket_path or self.socket_path), *args],
+            input=stdin, capture_output=True, text=True, timeout=30, env=dict(os.environ, **(env or {}))
+        )
+
+    def test_list(self):
+        result = self.cli("list")
+        self.assertEqual(result.returncode, 0, result.stderr)
+        lines = result.stdout.splitlines()
+        self.assertTrue(lines[0].startswith("DEVICE"))
+        self.assertTrue(any(line.split()[0] == "eth0" for line in lines[1:]))
+
+    def test_interface_labels(self):
+        result = self.cli("label", "set", "eth0", "WAN", "-g", "LAB", "-g", "uplinks")
+        self.assertEqual(result.returncode, 0, result.stderr)
+        self.assertIn("eth0: WAN in LAB, uplinks", result.stdout)
+        try:
+            # Labels stand in for kernel names in requests, and groups select interfaces
+            self.assertEqual(self.client.request("SetUnmanaged", interface="wan", unmanaged=False)["interface"], "eth0")
+            self.assertEqual(self.cli("label", "set", "wlan0", "-g", "lab").returncode, 0)
+            listed = json.loads(self.cli("--json", "list", "--group", "LAB").stdout)
+            self.assertEqual(sorted(i["name"] for i in listed), ["eth0", "wlan0"])
+            self.assertEqual(next(i for i in listed if i["name"] == "wlan0")["groups"], ["LAB"])
+            wan = next(line for line in self.cli("list").stdout.splitlines() if line.startswith("eth0"))
+            self.assertEqual(wan.split()[1], "WAN")
+
+            taken = self.cli("label", "set", "wlan0", "wan")
+            self.assertEqual(taken.returncode, 2, taken.stderr)
+            self.assertIn("eth0 is already labelled WAN", taken.stderr)
+            self.assertEqual(self.cli("label", "set", "wlan0", "eth0").returncode, 2)
+            self.assertEqual(self.cli("list", "-g", "nowhere").returncode, 4)
+        finally:
+            self.assertEqual(self.cli("label", "clear", "WAN").returncode, 0)
+            self.cli("label", "clear", "wlan0")
+        self.assertEqual(json.loads(self.cli("--json", "label", "list").stdout), [])
+
+    def test_rename_interface(self):
+        link_dir = Path(self.state_dir.name) / "systemd-network"
+        self.client.request("CreateProfile", name="Bench", interface="eth1", connection_type="ethernet",
+                            auto_connect=False)
+        self.cli("label", "set", "eth1", "Bench NIC")
+        try:
+            result = self.cli("rename", "bench nic", "lab0")
+            self.assertEqual(result.returncode, 0, result.stderr)
+            self.assertIn("eth1 is now lab0", result.stdout)
+            self.assertRegex(result.stdout, r"Profiles updated: .*Bench")
+            self.assertEqual((link_dir / "10-alopex-lab0.link").read_text().splitlines()[1:],
+                             ["[Match]", "PermanentMACAddress=00:1b:21:3a:4c:51", "", "[Link]", "Name=lab0"])
+            names = {i["name"]: i for i in self.client.request("GetInterfaces")}
+            self.assertNotIn("eth1", names)
+            self.assertEqual(names["lab0"]["label"], "Bench NIC")
+            self.assertIn("Bench", [p["name"] for p in self.client.request("ListProfiles", interface="lab0")])
+
+            self.assertEqual(self.cli("rename", "lab0", "wlan0").returncode, 2)
+            self.assertEqual(self.cli("rename", "lab0", "bad/name").returncode, 2)
+            self.assertEqual(self.cli("rename", "eth9", "lab1").returncode, 4)
+            with self.assertRaises(IpcError) as malformed:
+                self.client.request("RenameInterface", to="lab1")
+            self.assertEqual(malformed.exception.code, ErrorCode.INVALID_REQUEST)
+        finally:
+            # Renaming back replaces our .link file for the same device
+            restored = json.loads(self.cli("--json", "rename", "lab0", "eth1").stdout)
+            self.cli("label", "clear", "eth1")
+            self.client.request("DeleteProfile", name="Bench")
+        self.assertEqual(restored["link_file"], str(link_dir / "10-alopex-eth1.link"))
+        self.assertEqual(sorted(p.name for p in link_dir.iterdir()), ["10-alopex-eth1.link"])
+
+    def test_tunnels(self):
+        result = self.cli("tunnel", "add", "vx-lab", "vxlan", "192.168.1.20", "--vni", "100", "--dev", "eth1",
+                          "--address", "10.99.0.1")
+        self.assertEqual(result.returncode, 0, result.stderr)
+        try:
+            profile = self.client.request("ListProfiles", interface="vx-lab")[0]
+            self.assertEqual(profile["netdev"], {"kind": "vxlan", "remote": "192.168.1.20", "vni": 100,
+                                                 "port": 4789, "parent": "eth1"})
+            self.assertEqual(profile["method"], "static")
+            result = self.cli("connect", "vx-lab")
+            self.assertEqual(result.returncode, 0, result.stderr)
+            self.assertIn("10.99.0.1", result.stdout)
+            self.assertRegex(self.cli("tunnel", "list").stdout, r"vx-lab\s+vxlan\s+192\.168\.1\.20\s+-+\s+100\s")
+
+            self.assertEqual(self.cli("tunnel", "add", "vx-bad", "vxlan", "192.168.1.20", "--vni", "16777216")
+                             .returncode, 2)
+            self.assertEqual(self.cli("tunnel", "add", "gre-bad", "gre", "lab.example").returncode, 2)
+            self.assertEqual(self.cli("tunnel", "add", "gre-bad", "gre", "10.0.0.2", "--vni", "5").returncode, 2)
+            with self.assertRaises(IpcError) as wrong_type:
+                self.client.request("CreateProfile", name="gre-vpn", interface="gre-vpn", connection_type="vpn",
+                                    method="manual", netdev={"kind": "gre", "remote": "10.0.0.2"})
+            self.assertEqual(wrong_type.exception.code, ErrorCode.INVALID_CONFIG)
+            self.assertEqual([p["name"] for p in self.client.request("ListProfiles") if p["name"].endswith("-bad")],
+                             [])
+        finally:
+            self.cli("disconnect", "vx-lab")
+            self.client.request("DeleteProfile", name="vx-lab")
+
+    def test_container_networks(self):
+        result = self.cli("list")
+        self.assertEqual(result.returncode, 0, result.stderr)
+        self.assertRegex(result.stdout, r"br-5e7d1f0a9c3b .*unmanaged")
+        self.assertNotIn("veth41d2c0e", result.stdout)
+        self.assertIn("1 container interfaces not shown", result.stdout)
+        self.assertIn("veth41d2c0e", self.cli("list", "-a").stdout)
+
+        result = self.cli("containers")
+        self.assertEqual(result.returncode, 0, result.stderr)
+        self.assertRegex(result.stdout,
+                         r"docker\s+lab\s+br-5e7d1f0a9c3b\s+172\.18\.0\.0/16\s+web \(172\.18\.0\.2\)\s+1")
+        # Podman isn't running here, so its bridge is known by name alone
+        self.assertRegex(result.stdout, r"podman\s+podman0\s+podman0")
+        networks = json.loads(self.cli("--json", "containers").stdout)
+        self.assertEqual(networks["engines"], {"docker": "ok", "podman": "not running"})
+
+        names = {i["name"]: i for i in self.client.request("GetInterfaces")}
+        self.assertEqual(names["veth41d2c0e"]["container_network"], "docker/lab")
+        self.assertFalse(names["veth41d2c0e"]["managed"])
+        self.assertIsNone(names["eth0"]["container_network"])
+        self.client.request("SetUnmanaged", interface="podman0", unmanaged=False)
+        try:
+            self.assertNotIn("podman0", self.client.request("GetUnmanaged")["interfaces"])
+        finally:
+            self.client.request("SetUnmanaged", interface="podman0", unmanaged=True)
+
+    def test_virtual_networks(self):
+        result = self.cli("virt")
+        self.assertEqual(result.returncode, 0, result.stderr)
+        self.assertRegex(result.stdout,
+                         r"default\s+virbr0\s+nat\s+192\.168\.122\.0/24\s+"
+                         r"fedora-dev\s+52:54:00:3b:7c:11\s+192\.168\.122\.41")
+        self.assertRegex(result.stdout, r"isolated\s+virbr1 \(inactive\)\s+isolated")
+        one = json.loads(self.cli("--json", "virt", "-b", "virbr0").stdout)
+        self.assertEqual([n["name"] for n in one["networks"]], ["default"])
+        self.assertEqual(self.cli("virt", "-b", "eth0").returncode, 4)
+        names = {i["name"]: i for i in self.client.request("GetInterfaces")}
+        self.assertEqual(names["virbr0"]["virtual_network"], "default")
+        self.assertIsNone(names["eth0"]["virtual_network"])
+
+    def test_connect_creates_dhcp_profile(self):
+        result = self.cli("connect", "eth0")
+        self.assertEqual(result.returncode, 0, result.stderr)
+        self.assertIn("192.168.1.50", result.stdout)
+        self.assertTrue(any(p["name"] == "eth0" for p in self.client.request("ListProfiles")))
+
+        result = self.cli("disconnect", "eth0")
+        self.assertEqual(result.returncode, 0, result.stderr)
+
+    def test_wifi_scan_and_connect(self):
+        result = self.cli("wifi", "scan")
+        self.assertEqual(result.returncode, 0, result.stderr)
+        self.assertIn("AlopexHQ", result.stdout)
+
+        result = self.cli("wifi", "connect", "AlopexHQ")
+        self.assertNotEqual(result.returncode, 0)
+
+        result = self.cli("wifi", "connect", "AlopexHQ", "--ask-pass", stdin="arctic-fox\n")
+        self.assertEqual(result.returncode, 0, result.stderr)
+        self.cli("disconnect", "wlan0")
+
+    def test_wifi_alerts(self):
+        result = self.cli("wifi", "alerts")
+        self.assertEqual(result.returncode, 0, result.stderr)
+        self.assertRegex(result.stdout, r"wlan0\s+deauth_burst\s+5 deauth frames in 10s from 02:00:00:00:00:01")
+        alerts = json.loads(self.cli("--json", "wifi", "alerts").stdout)
+        self.assertEqual(set(alerts["baseline"]["AlopexHQ"].values()), {"WPA2"})
+
+        self.assertEqual(self.cli("wifi", "alerts", "--relearn", "Nowhere").returncode, 4)
+        self.assertEqual(self.cli("wifi", "alerts", "--relearn", "Guest").returncode, 0)
+        self.assertNotIn("Guest", self.client.request("GetWifiAlerts")["baseline"])
+
+    def roaming(self):
+        return self.client.request("GetRoamingTimeline", interface="wlan0")
+
+    def test_wifi_roams(self):
+        result = self.cli("wifi", "connect", "AlopexHQ", "--ask-pass", stdin="arctic-fox\n")
+        self.assertEqual(result.returncode, 0, result.stderr)
+        deadline = time.monotonic() + 5
+        while [stint["signal"] for stint in self.roaming()["current"]] != [-45] and time.monotonic() < deadline:
+            time.sleep(0.1)
+        self.client.request("MockRoam", interface="wlan0", bssid="02:00:00:00:00:AA", signal=-55)
+        deadline = time.monotonic() + 5
+        while self.roaming()["current"][0]["bssid"] != "02:00:00:00:00:aa" and time.monotonic() < deadline:
+            time.sleep(0.1)
+        deadline = time.monotonic() + 5
+        while self.roaming()["entries"][-1].get("new_signal") is None and time.monotonic() < deadline:
+            time.sleep(0.1)
+
+        result = self.cli("wifi", "roams", "-i", "wlan0")
+        self.assertEqual(result.returncode, 0, result.stderr)
+        self.assertRegex(result.stdout, r"wlan0\s+roamed\s+AlopexHQ\s+02:00:00:00:00:01\s+-45 dBm\s+\d+s\s+"
+                                        r"02:00:00:00:00:aa\s+-55 dBm")
+        self.assertIn("wlan0: on 02:00:00:00:00:aa (AlopexHQ, -55 dBm)", result.stdout)
+
+        self.cli("disconnect", "wlan0")
+        # test_wifi_scan_and_connect needs AlopexHQ without a saved password
+        self.client.request("DeleteProfile", name="AlopexHQ")
+        deadline = time.monotonic() + 5
+        while self.roaming()["current"] and time.monotonic() < deadline:
+            time.sleep(0.1)
+        timeline = json.loads(self.cli("--json", "wifi", "roams", "-i", "wlan0").stdout)
+        self.assertEqual((timeline["entries"][-1]["kind"], timeline["entries"][-1]["bssid"]),
+                         ("disconnected", "02:00:00:00:00:aa"))
+
+    def test_wifi_channels(self):
+        result = self.cli("--json", "wifi", "channels")
+        self.assertEqual(result.returncode, 0, result.stderr)
+        bands = {entry["band"]: entry for entry in json.loads(result.stdout)}
+        low = {c["channel"]: c for c in bands["2.4 GHz"]["channels"]}
+        self.assertEqual(low[3]["networks"], ["Cabin"])
+        # Cabin on 3 spills onto 6; Guest and Cabin both crowd 1, nothing reaches 11
+        self.assertGreater(low[1]["load"], low[6]["load"])
+        self.assertGreater(low[6]["load"], 0)
+        self.assertEqual(low[11]["load"], 0)
+        self.assertEqual(bands["2.4 GHz"]["suggested"], 11)
+        self.assertEqual(bands["5 GHz"]["suggested"], 40)
+
+        result = self.cli("wifi", "channels", "--band", "5")
+        self.assertEqual(result.returncode, 0, result.stderr)
+        self.assertIn("least congested channel 40", result.stdout)
+        self.assertIn("AlopexHQ", result.stdout)
+        self.assertNotIn("Guest", result.stdout)
+
+    def test_wifi_country(self):
+        result = self.cli("--json", "wifi", "country")
+        self.assertEqual(result.returncode, 0, result.stderr)
+        domain = json.loads(result.stdout)
+        self.assertEqual((domain["country"], domain["interface"]), ("00", "wlan0"))
+        self.assertTrue(all(c["no_ir"] for c in domain["channels"] if c["band"] == "5 GHz"))
+
+        self.assertEqual(self.cli("wifi", "country", "Germany").returncode, 2)
+        try:
+            result = self.cli("wifi", "country", "de")
+            self.assertEqual(result.returncode, 0, result.stderr)
+            self.assertIn("Regulatory domain: DE", result.stdout)
+            band = next(line.split() for line in result.stdout.splitlines() if line.startswith("5 GHz"))
+            # 5 GHz USABLE LISTEN-ONLY RADAR; nothing is listen-only any more
+            self.assertEqual(band[2:], ["36-165", "--", "52-144"])
+        finally:
+            self.cli("wifi", "country", "00")
+
+    def test_wifi_powersave(self):
+        self.client.request("CreateProfile", name="Low latency", interface="wlan0", connection_type="wifi",
+                            ssid="Cabin", password="log-fire", power_save=False, auto_connect=False, replace=True)
+        self.assertEqual(self.cli("wifi", "powersave", "off").returncode, 4)
+        self.assertEqual(self.cli("connect", "Low latency").returncode, 0)
+        try:
+            result = self.cli("--json", "wifi", "powersave")
+            self.assertEqual(result.returncode, 0, result.stderr)
+            state = json.loads(result.stdout)
+            self.assertEqual((state["power_save"], state["profile"]), (False, "Low latency"))
+
+            result = self.cli("wifi", "powersave", "on")
+            self.assertEqual(result.returncode, 0, result.stderr)
+            self.assertIn("wlan0: power save on", result.stdout)
+            self.assertIn("Profile Low latency: on", result.stdout)
+
+            # A one-off switch leaves the profile's choice alone
+            state = json.loads(self.cli("--json", "wifi", "powersave", "off", "--no-save").stdout)
+            self.assertEqual((state["power_save"], state["profile_power_save"]), (False, True))
+            self.assertEqual(self.cli("wifi", "powersave", "default", "--no-save").returncode, 2)
+
+            self.assertIn("driver default", self.cli("wifi", "powersave", "default").stdout)
+        finally:
+            self.cli("disconnect", "wlan0")
+            self.cli("wifi", "powersave", "on", "--no-save")
+
+    def test_rfkill(self):
+        result = self.cli("rfkill", "list")
+        self.assertEqual(result.returncode, 0, result.stderr)
+        self.assertIn("hard blocked (hardware switch)", next(l for l in result.stdout.splitlines() if "wwan0" in l))
+
+        events = []
+
+        def collect():
+            for event in self.client.subscribe(["RfkillChanged"]):
+                events.append(event)
+                if len(events) == 2:
+                    return
+
+        listener = threading.Thread(target=collect, daemon=True)
+        listener.start()
+        time.sleep(0.3)
+        self.client.request("CreateProfile", name="Blocked lab", interface="wlan0", connection_type="wifi",
+                            ssid="Guest", auto_connect=False, replace=True)
+        try:
+            self.assertEqual(self.cli("rfkill", "block", "wifi").returncode, 0)
+            wlan = next(i for i in json.loads(self.cli("--json", "list").stdout) if i["name"] == "wlan0")
+            self.assertEqual(wlan["rfkill"], "soft")
+            self.assertIn("soft blocked", self.cli("list").stdout)
+
+            result = self.cli("connect", "Blocked lab")
+            self.assertNotEqual(result.returncode, 0)
+            self.assertIn("rfkill soft block", result.stderr)
+        finally:
+            self.assertEqual(self.cli("rfkill", "unblock", "wifi").returncode, 0)
+        listener.join(timeout=5)
+        self.assertEqual([(e["name"], e["block"], e["previous"]) for e in events],
+                         [("phy0", "soft", None), ("phy0", None, "soft")])
+
+        # Only the switch lifts a hard block
+        result = self.cli("rfkill", "unblock", "wwan")
+        self.assertEqual(result.returncode, 1)
+        self.assertIn("wwan0 still blocked by a hardware switch", result.stderr)
+        self.assertEqual(self.cli("rfkill", "block", "nfc").returncode, 2)
+
+    def test_airplane_mode(self):
+        # Bluetooth was off before; it must stay off after
+        self.assertEqual(self.cli("rfkill", "block", "bluetooth").returncode, 0)
+        try:
+            result = self.cli("airplane", "on")
+            self.assertEqual(result.returncode, 0, result.stderr)
+            self.assertIn("phy0 (wlan): soft blocked", result.stdout)
+            self.assertIn("wwan0 (wwan): hard blocked", result.stdout)
+            self.assertIn("Airplane mode is on", self.cli("status").stdout)
+            self.assertEqual(self.cli("airplane", "on").returncode, 0)
+        finally:
+            result = self.cli("--json", "airplane", "off")
+            self.cli("rfkill", "unblock", "bluetooth")
+        self.assertEqual(result.returncode, 0, result.stderr)
+        radios = {r["name"]: r["block"] for r in json.loads(result.stdout)["radios"]}
+        self.assertEqual(radios, {"phy0": None, "hci0": "soft", "wwan0": "hard"})
+        self.assertNotIn("Airplane mode", self.cli("status").stdout)
+
+    def test_hotspot(self):
+        self.assertIn("No hotspot running", self.cli("hotspot", "clients").stdout)
+        self.assertEqual(self.cli("hotspot", "kick", "3c:22:fb:10:20:30").returncode, 4)
+
+        result = self.cli("hotspot", "start", "Alopex Share", "-i", "wlan0", "--ask-pass", stdin="short\n")
+        self.assertEqual(result.returncode, 2)
+        result = self.cli("hotspot", "start", "Alopex Share", "-i", "wlan0", "--ask-pass", "--channel", "11",
+                          stdin="northern-lights\n")
+        self.assertEqual(result.returncode, 0, result.stderr)
+        try:
+            self.assertIn("sharing as Alopex Share (10.42.0.1)", result.stdout)
+            result = self.cli("hotspot", "clients")
+            self.assertEqual(result.returncode, 0, result.stderr)
+            self.assertIn("wlan0: Alopex Share, 2 connected", result.stdout)
+            self.assertIn("pixel", next(l for l in result.stdout.splitlines() if "3c:22:fb:10:20:30" in l))
+            result = self.cli("dhcp", "leases", "wlan0")
+            self.assertEqual(result.returncode, 0, result.stderr)
+            self.assertIn("10.42.0.11  3c:22:fb:10:20:30  pixel", result.stdout)
+            self.assertEqual(self.cli("dhcp", "leases", "eth0").returncode, 4)
+
+            result = self.cli("hotspot", "limit", "3c:22:fb:10:20:30", "--down", "2000", "--up", "500")
+            self.assertEqual(result.returncode, 0, result.stderr)
+            self.assertIn("limited to 2000/500 kbit/s", result.stdout)
+            self.assertIn("2000/500", next(l for l in self.cli("hotspot", "clients").stdout.splitlines()
+                                           if "3c:22:fb:10:20:30" in l))
+            self.assertEqual(self.cli("hotspot", "limit", "3c:22:fb:10:20:30", "--down", "20000000").returncode, 2)
+
+            self.assertEqual(self.cli("hotspot", "kick", "3C-22-FB-10-20-30").returncode, 0)
+            self.assertEqual(self.cli("hotspot", "kick", "3c:22:fb:10:20:30").returncode, 4)
+            self.assertEqual(self.cli("hotspot", "block", "not-a-mac").returncode, 2)
+            self.assertEqual(self.cli("hotspot", "block", "9a:8b:7c:00:11:22").returncode, 0)
+            hotspot, = json.loads(self.cli("--json", "hotspot", "clients").stdout)
+            self.assertEqual(hotspot["clients"], [])
+            self.assertEqual(hotspot["blocked"], ["9a:8b:7c:00:11:22"])
+            profile = next(p for p in self.client.request("ListProfiles") if p["name"] == "Alopex Share hotspot")
+            self.assertEqual(profile["blocked_clients"], ["9a:8b:7c:00:11:22"])
+            self.assertEqual(profile["client_limits"], {"3c:22:fb:10:20:30": {"down": 2000, "up": 500}})
+        finally:
+            result = self.cli("hotspot", "stop")
+        self.assertEqual(result.returncode, 0, result.stderr)
+        self.assertIn("No hotspot running", self.cli("hotspot", "clients").stdout)
+
+        # Restarting keeps the block list and reservations, and brings the kicked station back
+        reserve = ("dhcp", "reserve", "Alopex Share hotspot")
+        self.assertEqual(self.cli(*reserve, "3C:22:FB:10:20:30", "10.42.0.5").returncode, 0)
+        self.assertEqual(self.cli(*reserve, "9a:8b:7c:00:11:22", "10.9.0.5").returncode, 2)
+        self.assertEqual(self.cli("hotspot", "start", "Alopex Share", "-i", "wlan0").returncode, 0)
+        try:
+            hotspot, = json.loads(self.cli("--json", "hotspot", "clients").stdout)
+            self.assertEqual([(c["mac"], c["ip"]) for c in hotspot["clients"]], [("3c:22:fb:10:20:30", "10.42.0.5")])
+            self.assertEqual(hotspot["channel"], 11)
+            self.assertEqual(self.cli("hotspot", "unblock", "9a:8b:7c:00:11:22").returncode, 0)
+
+            # Allow list: refused while empty, then only the listed station stays
+            self.assertEqual(self.cli("hotspot", "filter", "allow").returncode, 2)
+            result = self.cli("hotspot", "allow", "3C:22:FB:10:20:30")
+            self.assertEqual(result.returncode, 0, result.stderr)
+            self.assertIn("takes effect with hotspot filter allow", result.stdout)
+            self.assertEqual(len(json.loads(self.cli("--json", "hotspot", "clients").stdout)[0]["clients"]), 2)
+            result = self.cli("hotspot", "filter", "allow")
+            self.assertEqual(result.returncode, 0, result.stderr)
+            self.assertIn("only 3c:22:fb:10:20:30 may join", result.stdout)
+            hotspot, = json.loads(self.cli("--json", "hotspot", "clients").stdout)
+            self.assertEqual([c["mac"] for c in hotspot["clients"]], ["3c:22:fb:10:20:30"])
+            self.assertEqual((hotspot["filter"], hotspot["allowed"]), ("allow", ["3c:22:fb:10:20:30"]))
+            self.assertEqual(self.cli("hotspot", "disallow", "3c:22:fb:10:20:30").returncode, 2)
+            self.assertEqual(self.cli("hotspot", "filter", "deny").returncode, 0)
+            self.assertIn("everyone may join", self.cli("hotspot", "filter").stdout)
+            self.assertEqual(len(json.loads(self.cli("--json", "hotspot", "clients").stdout)[0]["clients"]), 2)
+
+            result = self.cli("hotspot", "isolate", "on")
+            self.assertEqual(result.returncode, 0, result.stderr)
+            self.assertIn("not each other", result.stdout)
+            self.assertIn("clients isolated", self.cli("hotspot", "clients").stdout)
+            profile = next(p for p in self.client.request("ListProfiles") if p["name"] == "Alopex Share hotspot")
+            self.assertTrue(profile["client_isolation"])
+            self.assertEqual(self.cli("hotspot", "isolate", "off", "-i", "eth0").returncode, 4)
+            self.assertEqual(self.cli("hotspot", "isolate", "off").returncode, 0)
+        finally:
+            self.cli("hotspot", "stop")
+
+    def test_lan_scan(self):
+        result = self.cli("lan", "scan")
+        self.assertEqual(result.returncode, 0, result.stderr)
+        self.assertIn("eth0: 4 devices on 192.168.1.0/24", result.stdout)
+        self.assertIn("gateway", next(l for l in result.stdout.splitlines() if l.startswith("192.168.1.1 ")))
+        self.assertIn("NAS", next(l for l in result.stdout.splitlines() if l.startswith("192.168.1.20 ")))
+
+        scan = json.loads(self.cli("--json", "lan", "scan", "-i", "eth0").stdout)
+        self.assertEqual([d["ip"] for d in scan["devices"]],
+                         ["192.168.1.1", "192.168.1.20", "192.168.1.33", "192.168.1.50"])
+        device = {d["ip"]: d for d in scan["devices"]}
+        self.assertEqual(device["192.168.1.1"]["name_source"], "mdns")
+        self.assertEqual(device["192.168.1.50"]["role"], "self")
+        self.assertTrue(device["192.168.1.33"]["randomized_mac"])
+        self.assertIsNone(device["192.168.1.33"]["vendor"])
+
+        # Down, or not there at all
+        self.assertEqual(self.cli("lan", "scan", "-i", "eth1").returncode, 4)
+        self.assertEqual(self.cli("lan", "scan", "-i", "eth9").returncode, 4)
+
+    def test_bluetooth(self):
+        result = self.cli("bluetooth", "list", "--scan")
+        self.assertEqual(result.returncode, 0, result.stderr)
+        row = next(l for l in result.stdout.splitlines() if l.startswith("F1:22:33:44:55:66"))
+        self.assertIn("Heart Rate", row)
+        self.assertIn("-71 dBm", row)
+
+        info = self.cli("bluetooth", "info", "f1-22-33-44-55-66")
+        self.assertEqual(info.returncode, 0, info.stderr)
+        self.assertIn("Low Energy", info.stdout)
+        self.assertIn("Battery Service", info.stdout)
+        self.assertIn("connect to read it", info.stdout)
+
+        # LE peripherals connect without pairing, and the battery shows once connected
+        result = self.cli("bluetooth", "connect", "F1:22:33:44:55:66")
+        self.assertEqual(result.returncode, 0, result.stderr)
+        self.assertIn("Connected to Heart Rate, battery 64%", result.stdout)
+        device = json.loads(self.cli("--json", "bluetooth", "info", "F1:22:33:44:55:66").stdout)
+        self.assertTrue(device["connected"])
+        self.assertFalse(device["paired"])
+        self.assertEqual(device["battery"], 64)
+        self.assertIsNone(device["vendor"])
+        # Connected beats a stronger signal
+        listed = json.loads(self.cli("--json", "bluetooth", "list").stdout)
+        self.assertEqual([d["mac"] for d in listed], ["F1:22:33:44:55:66", "AA:BB:CC:DD:EE:01"])
+
+        # Classic devices need pairing first
+        self.assertEqual(self.cli("bluetooth", "connect", "AA:BB:CC:DD:EE:01").returncode, 2)
+        self.assertEqual(self.cli("bluetooth", "pair", "AA:BB:CC:DD:EE:01").returncode, 0)
+        result = self.cli("bluetooth", "connect", "AA:BB:CC:DD:EE:01")
+        self.assertEqual(result.returncode, 0, result.stderr)
+        listed = {d["mac"]: d for d in json.loads(self.cli("--json", "bluetooth", "list").stdout)}
+        self.assertTrue(listed["AA:BB:CC:DD:EE:01"]["paired"] and listed["AA:BB:CC:DD:EE:01"]["connected"])
+        self.assertEqual(listed["AA:BB:CC:DD:EE:01"]["type"], "classic")
+
+        self.assertEqual(list(listed), ["AA:BB:CC:DD:EE:01", "F1:22:33:44:55:66"])
+
+        # Local aliases, kept by the daemon
+        result = self.cli("bluetooth", "alias", "f1:22:33:44:55:66", "Chest  strap")
+        self.assertEqual(result.returncode, 0, result.stderr)
+        self.assertIn("F1:22:33:44:55:66: Chest strap", result.stdout)
+        self.assertIn("Chest strap", self.cli("bluetooth", "list").stdout)
+        self.assertIn("Connected to Chest strap", self.cli("bluetooth", "connect", "F1:22:33:44:55:66").stdout)
+        self.assertEqual(self.cli("bluetooth", "alias", "F1:22:33:44:55:66", "x" * 49).returncode, 2)
+        self.assertEqual(self.cli("bluetooth", "alias", "AA:BB:CC:DD:EE:99", "Ghost").returncode, 4)
+        result = self.cli("bluetooth", "alias", "F1:22:33:44:55:66")
+        self.assertIn("alias removed (Heart Rate)", result.stdout)
+
+        # OBEX sends go to paired devices only
+        with tempfile.NamedTemporaryFile(suffix=".txt") as f:
+            f.write(b"x" * 3000)
+            f.flush()
+            result = self.cli("bluetooth", "send", "AA:BB:CC:DD:EE:01", f.name)
+            self.assertEqual(result.returncode, 0, result.stderr)
+            self.assertIn(f"Sent {Path(f.name).name} (3000 bytes) to Headset", result.stdout)
+            self.assertEqual(self.cli("bluetooth", "send", "F1:22:33:44:55:66", f.name).returncode, 2)
+        self.assertEqual(self.cli("bluetooth", "send", "AA:BB:CC:DD:EE:01", "/nonexistent/file").returncode, 2)
+
+        self.assertEqual(self.cli("bluetooth", "info", "not-a-mac").returncode, 2)
+        self.assertEqual(self.cli("bluetooth", "info", "AA:BB:CC:DD:EE:99").returncode, 4)
+
+    def test_flows(self):
+        result = self.cli("flows", "--top", "2")
+        self.assertEqual(result.returncode, 0, result.stderr)
+        self.assertIn("5 flows of 65536 (0.0%): 4 tcp, 1 udp", result.stdout)
+        self.assertIn("140.82.112.3", result.stdout.split("\n\n")[1].splitlines()[1])
+        self.assertIn("443/tcp", result.stdout)
+        flows = json.loads(self.cli("--json", "flows").stdout)
+        self.assertEqual(flows["top_hosts"][0], {"host": "140.82.112.3", "flows": 3, "bytes": 8048})
+        self.assertEqual(flows["top_ports"][0], {"protocol": "tcp", "port": 443, "flows": 3})
+        self.assertEqual((flows["snat"], flows["tcp_states"]["ESTABLISHED"], flows["nat"]), (2, 3, []))
+        self.assertEqual(self.cli("flows", "--top", "0").returncode, 2)
+
+        # Translated flows from a hotspot's subnet count against it
+        self.assertEqual(self.cli("hotspot", "start", "Alopex Share", "-i", "wlan0").returncode, 0)
+        try:
+            nat, = json.loads(self.cli("--json", "flows").stdout)["nat"]
+            self.assertEqual((nat["interface"], nat["network"], nat["flows"]), ("wlan0", "10.42.0.0/24", 2))
+            self.assertEqual(nat["clients"], [{"address": "10.42.0.11", "flows": 2, "bytes": 2048}])
+            self.assertIn("wlan0 sharing 10.42.0.0/24: 2 NAT flows", self.cli("flows").stdout)
+        finally:
+            self.cli("hotspot", "stop")
+
+    def test_top_talkers_disabled(self):
+        result = self.cli("top")
+        self.assertEqual(result.returncode, 0, result.stderr)
+        self.assertIn("accounting is off", result.stdout)
+        self.assertEqual(json.loads(self.cli("--json", "top").stdout), {"enabled": False})
+
+    def test_fast_rates_disabled(self):
+        result = self.cli("rates")
+        self.assertEqual(result.returncode, 0, result.stderr)
+        self.assertIn("eBPF counters are off", result.stdout)
+        self.assertEqual(json.loads(self.cli("--json", "rates").stdout), {"enabled": False})
+
+    def test_influx_sink_disabled(self):
+        self.assertEqual(self.client.request("GetInfluxSink"), {"enabled": False})
+
+    def test_notifications_disabled(self):
+        result = self.cli("notify", "list")
+        self.assertEqual(result.returncode, 0, result.stderr)
+        self.assertIn("Notifications are off", result.stdout)
+        self.assertEqual(json.loads(self.cli("--json", "notify", "list").stdout), {"enabled": False})
+        self.assertEqual(self.cli("notify", "test").returncode, 2)
+
+    def test_quota_without_limits(self):
+        result = self.cli("quota")
+        self.assertEqual(result.returncode, 0, result.stderr)
+        self.assertIn("No data quotas", result.stdout)
+        self.assertEqual(json.loads(self.cli("--json", "quota").stdout), [])
+
+    def test_fleet_hosts(self):
+        fleet_file = Path(self.state_dir.name) / "fleet.json"
+        fleet_file.write_text(json.dumps({"cert_file": str(fleet_file.with_name("client.pem")), "hosts": [
+            {"name": "gw", "address": "tls://127.0.0.1:1"}
+        ]}))
+        env = {"ALOPEX_FLEET": str(fleet_file)}
+        result = self.cli("--json", "hosts", env=env)
+        self.assertEqual(result.returncode, 0, result.stderr)
+        local, gw = json.loads(result.stdout)
+        self.assertTrue(local["local"])
+        self.assertEqual(local["status"]["hostname"], socket.gethostname())
+        self.assertEqual((gw["name"], gw["address"], gw["status"]), ("gw", "tls://127.0.0.1:1", None))
+        self.assertIn("tls://127.0.0.1:1", gw["error"])
+        self.assertIn("unreachable", self.cli("hosts", env=env).stdout)
+
+        self.assertEqual(self.cli("--host", "local", "status", env=env).returncode, 0)
+        self.assertEqual(self.cli("--host", "gw", "status", env=env).returncode, 3)
+        result = self.cli("--host", "router", "status", env=env)
+        self.assertEqual(result.returncode, 2)
+        self.assertIn("have: ", result.stderr)
+
+    def test_whoami(self):
+        result = self.cli("whoami")
+        self.assertEqual(result.returncode, 0, result.stderr)
+        self.assertIn("access control is off", result.stdout)
+        hello = json.loads(self.cli("--json", "whoami").stdout)
+        self.assertEqual((hello["uid"], hello["capabilities"]), (os.getuid(), None))
+
+    def test_not_a_relay(self):
+        result = self.cli("relay")
+        self.assertEqual(result.returncode, 2)
+        self.assertIn("not a relay", result.stderr)
+        self.assertEqual(self.cli("list", "--fleet").returncode, 2)
+        self.assertEqual(self.client.try_request("RelayRequest", host="nas", relayed="GetStatus"), None)
+
+    def test_sla_without_probes(self):
+        result = self.cli("sla", "list")
+        self.assertEqual(result.returncode, 0, result.stderr)
+        self.assertIn("No SLA probes", result.stdout)
+        self.assertEqual(json.loads(self.cli("--json", "sla", "list").stdout), [])
+        self.assertEqual(self.cli("sla", "history", "wan").returncode, 4)
+
+    def test_probe(self):
+        result = self.cli("probe", "192.168.1.20:445", "-i", "eth0")
+        self.assertEqual(result.returncode, 0, result.stderr)
+        self.assertEqual(result.stdout.strip(), "192.168.1.20:445 open in 2.5 ms from eth0 (192.168.1.50)")
+
+        result = self.cli("--json", "probe", "192.168.1.20:22")
+        self.assertEqual(result.returncode, 1)
+        probe = json.loads(result.stdout)
+        self.assertEqual((probe["state"], probe["interface"], probe["source"]), ("closed", None, None))
+        self.assertIn("filtered", self.cli("probe", "203.0.113.9 22", "-w", "0.5").stdout)
+
+        self.assertEqual(self.cli("probe", "192.168.1.20").returncode, 2)
+        self.assertEqual(self.cli("probe", "192.168.1.20:445", "-w", "60").returncode, 2)
+        self.assertEqual(self.cli("probe", "192.168.1.20:445", "-i", "eth1").returncode, 4)
+
+    def test_diagnose(self):
+        result = self.cli("diagnose", "www.google.com:443", "www.cloudflare.com:443")
+        self.assertEqual(result.returncode, 1, result.stderr)
+        self.assertIn("IPv6 fails for www.google.com:443", result.stdout)
+        google = next(l for l in result.stdout.splitlines() if l.strip().startswith("www.google.com:443"))
+        self.assertIn("timed out", google)
+        self.assertIn("IPv4 (192.0.2.80)", google)
+
+        result = self.cli("--json", "diagnose", "v4only.example:443", "www.cloudflare.com:443")
+        self.assertEqual(result.returncode, 0, result.stderr)
+        diagnosis = json.loads(result.stdout)
+        self.assertEqual((diagnosis["ipv4"]["status"], diagnosis["ipv6"]["status"]), ("working", "working"))
+        self.assertEqual([t["used"]["family"] for t in diagnosis["targets"]], [4, 6])
+
+        self.assertEqual(self.cli("diagnose", "www.google.com").returncode, 2)
+        self.assertEqual(self.cli("diagnose", "-w", "60").returncode, 2)
+
+    def test_troubleshoot(self):
+        result = self.cli("troubleshoot", "eth0")
+        self.assertEqual(result.returncode, 0, result.stderr)
+        self.assertIn("eth0: everything checks out", result.stdout)
+        self.assertIn("✓ Gateway", result.stdout)
+
+        result = self.cli("troubleshoot", "eth1")
+        self.assertEqual(result.returncode, 1, result.stderr)
+        lines = result.stdout.splitlines()
+        self.assertEqual(lines[0], "✗ Link        no carrier")
+        self.assertTrue(lines[1].strip().startswith("Try: Check the cable"))
+        self.assertEqual(lines[2], "· IP address  not checked")
+
+        result = self.cli("--json", "troubleshoot", "eth0", "--target", "203.0.113.9:22")
+        self.assertEqual(result.returncode, 1, result.stderr)
+        report = json.loads(result.stdout)
+        self.assertEqual(report["failed"], "internet")
+        self.assertEqual([s["status"] for s in report["steps"]], ["ok", "ok", "ok", "ok", "failed"])
+
+        self.assertEqual(self.cli("troubleshoot", "eth9").returncode, 4)
+        self.assertEqual(self.cli("troubleshoot", "eth0", "--target", "1.1.1.1").returncode, 2)
+
+    def test_bandwidth_export(self):
+        # The daemon samples counters every 5 seconds; the second sample starts the history
+        deadline = time.monotonic() + 15
+        while not self.client.try_request("GetBandwidthHistory", interface="eth0"):
+            self.assertLess(time.monotonic(), deadline, "no bandwidth history for eth0")
+            time.sleep(0.5)
+
+        result = self.cli("bandwidth", "export", "eth0", "--since", "1h")
+        self.assertEqual(result.returncode, 0, result.stderr)
+        lines = result.stdout.splitlines()
+        self.assertEqual(lines[0], "time,timestamp,interface,rx_bytes,tx_bytes,rx_rate,tx_rate")
+        self.assertTrue(lines[1].split(",")[2] == "eth0" and int(lines[1].split(",")[3]) > 0)
+
+        output = Path(self.state_dir.name) / "eth0-bandwidth.json"
+        result = self.cli("bandwidth", "export", "eth0", "-o", str(output))
+        self.assertEqual(result.returncode, 0, result.stderr)
+        history = json.loads(output.read_text())
+        self.assertEqual(history["bucket_seconds"], 60)
+        self.assertEqual(history["samples"][0]["interface"], "eth0")
+        self.assertEqual(json.loads(self.cli("--json", "bandwidth", "export", "eth0", "--since", "1h",
+                                              "--until", "2h").stdout)["samples"], [])
+
+        self.assertEqual(self.cli("bandwidth", "export", "eth0", "--since", "yesterday").returncode, 2)
+        self.assertEqual(self.cli("bandwidth", "export", "eth9").returncode, 4)
+
+    def test_vpn_up_and_down(self):
+        result = self.cli("vpn", "list")
+        self.assertEqual(result.returncode, 0, result.stderr)
+        self.assertIn("office", result.stdout)
+
+        self.assertEqual(self.cli("vpn", "up", "office").returncode, 0)
+        self.assertEqual(self.cli("vpn", "down", "office").returncode, 0)
+        self.assertEqual(self.cli("vpn", "up", "nowhere").returncode, 4)
+
+    def test_certificates(self):
+        paths = write_certificates(Path(self.state_dir.name))
+        self.assertEqual(self.cli("cert", "import", str(paths["ca"]), "--name", "Corp CA").returncode, 0)
+        result = self.cli("cert", "import", str(paths["client"]))
+        self.assertEqual(result.returncode, 2)
+        self.assertIn("encrypted", result.stderr)
+
+        events = []
+
+        def collect():
+            for event in self.client.subscribe(["CertificateExpiring"]):
+                events.append(event)
+                return
+
+        listener = threading.Thread(target=collect, daemon=True)
+        listener.start()
+        time.sleep(0.3)
+        result = self.cli("cert", "import", str(paths["client"]), "--ask-pass", stdin="hunter22\n")
+        self.assertEqual(result.returncode, 0, result.stderr)
+        listener.join(timeout=5)
+
+        stored = json.loads(self.cli("--json", "cert", "list").stdout)
+        self.assertEqual([(c["kind"], c["name"]) for c in stored],
+                         [("ca", "Corp CA"), ("client", "alice@corp"), ("key", "alice@corp key")])
+        ca, client, key = stored
+        self.assertEqual(client["key_id"], key["id"])
+        self.assertEqual(client["warned"], "expiring")
+        self.assertIsNone(ca["warned"])
+        self.assertEqual([(e["id"], e["type"]) for e in events], [(client["id"], "client")])
+        self.assertIn("(10 days)", self.cli("cert", "list").stdout)
+
+        # 802.1X with EAP-TLS: the mock network only lets in clients presenting all three files
+        result = self.cli("wifi", "connect", "CorpSecure", "--eap", "tls", "--identity", "alice@corp",
+                          "--ca-cert", ca["id"])
+        self.assertEqual(result.returncode, 2)
+        self.assertIn("needs a client_cert", result.stderr)
+        result = self.cli("wifi", "connect", "CorpSecure", "--eap", "tls", "--ca-cert", client["id"],
+                          "--client-cert", client["id"])
+        self.assertEqual(result.returncode, 4)
+        result = self.cli("wifi", "connect", "CorpSecure", "--eap", "tls", "--identity", "alice@corp",
+                          "--ca-cert", ca["id"], "--client-cert", client["id"])
+        self.assertEqual(result.returncode, 0, result.stderr)
+        self.cli("disconnect", "wlan0")
+
+        # OpenVPN-style tunnel that wants certificates
+        try:
+            self.assertNotEqual(self.cli("vpn", "up", "corp").returncode, 0)
+            result = self.cli("profile", "certs", "corp", "--ca", ca["id"], "--client", client["id"])
+            self.assertEqual(result.returncode, 0, result.stderr)
+            self.assertEqual(self.cli("vpn", "up", "corp").returncode, 0)
+            result = self.cli("cert", "remove", key["id"])
+            self.assertEqual(result.returncode, 6)
+            self.assertIn("in use by CorpSecure, corp", result.stderr)
+        finally:
+            self.cli("vpn", "down", "corp")
+            self.client.request("DeleteProfile", name="corp")
+            self.client.request("DeleteProfile", name="CorpSecure")
+        self.assertEqual(self.cli("cert", "remove", ca["id"]).returncode, 0)
+        self.assertEqual(self.cli("cert", "remove", ca["id"]).returncode, 4)
+
+    def test_link_speed(self):
+        self.assertEqual(self.client.request("GetLinkSpeedWarnings", refresh=True), [])
+        self.client.request("MockSetLinkSpeed", interface="eth0", speed=100, partner_max_speed=1000)
+        try:
+            warning, = self.client.request("GetLinkSpeedWarnings", refresh=True)
+            self.assertEqual((warning["interface"], warning["speed"], warning["max_speed"]), ("eth0", 100, 1000))
+            self.assertIn("cable is the likely culprit", warning["message"])
+            self.assertIn("Warning: eth0 negotiated 100 Mb/s", self.cli("status").stdout)
+            result = self.cli("metrics", "eth0")
+            self.assertIn("100 Mbps (NIC max 1000 Mbps)", result.stdout)
+            self.assertIn("cable", next(line for line in result.stdout.splitlines() if line.startswith("warning")))
+
+            # The switch turns out to be a 100 Mb/s one: nothing to fix
+            self.client.request("MockSetLinkSpeed", interface="eth0", speed=100, partner_max_speed=100)
+            self.assertEqual(self.client.request("GetLinkSpeedWarnings", refresh=True), [])
+            events = [e["event"] for e in self.client.request("GetDiagnostics", events=50)["events"]]
+            self.assertIn("LinkSpeedRestored", events)
+        finally:
+            self.client.request("MockSetLinkSpeed", interface="eth0", speed=1000)
+            self.client.request("GetLinkSpeedWarnings", refresh=True)
+
+    def test_cable_test(self):
+        result = self.cli("cable-test", "eth0")
+        self.assertEqual(result.returncode, 0, result.stderr)
+        self.assertIn("eth0: all 4 pairs OK", result.stdout)
+
+        result = self.cli("cable-test", "eth1")
+        self.assertEqual(result.returncode, 1, result.stderr)
+        row = next(line for line in result.stdout.splitlines() if line.startswith("C "))
+        self.assertEqual(row.split()[-2:], ["1.6", "m"])
+        self.assertIn("pair C open circuit at 1.6 m", result.stdout)
+        result = self.cli("--json", "cable-test", "eth1")
+        self.assertEqual(json.loads(result.stdout)["pairs"][2]["status"], "open")
+
+        self.assertEqual(self.cli("cable-test", "eth9").returncode, 4)
+        self.assertEqual(self.cli("cable-test", "wlan0").returncode, 2)
+
+    def test_module(self):
+        result = self.cli("module", "eth1")
+        self.assertEqual(result.returncode, 0, result.stderr)
+        rows = {line[:12].strip(): line[12:].strip() for line in result.stdout.splitlines()[1:]}
+        self.assertEqual(rows["module"], "SFP LC")
+        self.assertEqual(rows["part number"], "SFP-10GSR-85 rev A")
+        self.assertEqual((rows["wavelength"], rows["rx power"]), ("850 nm", "-3.64 dBm"))
+        self.assertEqual(rows["alarms"], "none")
+        self.assertEqual(json.loads(self.cli("--json", "module", "eth1").stdout)["interface"], "eth1")
+
+        # A copper port has nothing plugged in
+        self.assertEqual(self.cli("module", "eth0").returncode, 4)
+        self.assertEqual(self.cli("module", "wlan0").returncode, 2)
+
+    def test_queues(self):
+        result = self.cli("queues", "eth0")
+        self.assertEqual(result.returncode, 0, result.stderr)
+        self.assertIn("eth0: 2 queues, 2 IRQs on CPUs 0 of 0-3", result.stdout)
+        self.assertIn("Warning: All 2 IRQs of eth0 go to CPU 0", result.stdout)
+
+        result = self.cli("queues", "eth0", "--spread")
+        self.assertEqual(result.returncode, 0, result.stderr)
+        self.assertIn("Moved 2 IRQs", result.stdout)
+        self.assertIn("2 IRQs on CPUs 0-1 of 0-3", result.stdout)
+        self.assertNotIn("Warning", result.stdout)
+        queues = self.client.request("GetNicQueues", interface="eth0")
+        self.assertEqual([(i["irq"], i["cpus"]) for i in queues["irqs"]], [(129, [0]), (130, [1])])
+
+        # Nothing to spread on a single-queue NIC
+        self.assertEqual(self.cli("queues", "wlan0", "--spread").returncode, 4)
+        self.assertEqual(self.cli("queues", "eth9").returncode, 4)
+
+    def test_metrics_json(self):
+        result = self.cli("metrics", "eth0", "--json")
+        self.assertEqual(result.returncode, 0, result.stderr)
+        metrics = json.loads(result.stdout)
+        self.assertEqual(metrics["interface"], "eth0")
+        self.assertGreater(metrics["bytes_rx"], 0)
+
+    def test_metrics_units(self):
+        result = self.cli("--units", "bits-si", "metrics", "eth0")
+        self.assertEqual(result.returncode, 0, result.stderr)
+        rate = next(line for line in result.stdout.splitlines() if line.startswith("rate"))
+        self.assertRegex(rate, r"[0-9.]+ [kMG]?b/s/[0-9.]+ [kMG]?b/s")
+        self.assertNotIn("iB", result.stdout)
+
+        result = self.cli("metrics", "eth0", env={"ALOPEX_UNITS": "bytes-iec"})
+        self.assertRegex(result.stdout, r"rx\s+[0-9.]+ (Ki|Mi|Gi)?B ")
+        self.assertEqual(self.cli("metrics", "eth0", env={"ALOPEX_UNITS": "furlongs"}).returncode, 2)
+
+    def test_json_output(self):
+        result = self.cli("--json", "list")
+        self.assertEqual(result.returncode, 0, result.stderr)
+        interfaces = {i["name"]: i for i in json.loads(result.stdout)}
+        self.assertEqual(interfaces["eth0"]["interface_type"], "Ethernet")
+        self.assertIn("connection", interfaces["eth0"])
+
+        result = self.cli("--json", "wifi", "scan")
+        self.assertEqual(result.returncode, 0, result.stderr)
+        self.assertIn("Guest", [n["ssid"] for n in json.loads(result.stdout)])
+
+        result = self.cli("--json", "metrics", "nope0")
+        self.assertEqual(result.returncode, 4)
+        self.assertEqual(json.loads(result.stdout)["error"]["code"], "NotFound")
+
+        result = subprocess.run(
+            [sys.executable, str(DAEMON), "--socket", str(self.socket_path), "status", "--json", "--logs"],
+            capture_output=True, text=True, timeout=30
+        )
+        self.assertEqual(result.returncode, 0, result.stderr)
+        status = json.loads(result.stdout)
+        self.assertTrue(status["mock"])
+        self.assertIsInstance(status["logs"], list)
+
+    def test_nmcli_shim(self):
+        result = self.cli("nm", "-t", "dev", "status")
+        self.assertEqual(result.returncode, 0, result.stderr)
+        devices = {line.split(":")[0]: line.split(":") for line in result.stdout.splitlines()}
+        self.assertEqual(devices["eth1"][1:3], ["ethernet", "disconnected"])
+
+        result = self.cli("nm", "dev", "wifi", "connect", "Guest", "ifname", "wlan0", "name", "Guest-nm")
+        self.assertEqual(result.returncode, 0, result.stderr)
+        self.assertIn("successfully activated", result.stdout)
+
+        result = self.cli("nm", "-t", "con", "show", "--active")
+        self.assertIn("Guest-nm", result.stdout)
+        self.assertIn(":wifi:wlan0", result.stdout)
+
+        self.assertEqual(self.cli("nm", "con", "down", "id", "Guest-nm").returncode, 0)
+        self.assertEqual(self.cli("nm", "con", "down", "Guest-nm").returncode, 10)
+        self.assertEqual(self.cli("nm", "con", "up", "nowhere").returncode, 10)
+
+    def test_import_networkmanager(self):
+        keyfiles = Path(self.state_dir.name) / "system-connections"
+        keyfiles.mkdir()
+        for name, content in NM_KEYFILES.items():
+            (keyfiles / name).write_text(content)
+
+        result = self.cli("--json", "import", "networkmanager", "--path", str(keyfiles), "--dry-run")
+        self.assertEqual(result.returncode, 0, result.stderr)
+        report = json.loads(result.stdout)
+        self.assertEqual(sorted(report["imported"]), ["Home", "Wired static"])
+        self.assertEqual(sorted(s["name"] for s in report["skipped"]), ["Corp", "br0"])
+        self.assertTrue(any("IPv6" in w["message"] for w in report["warnings"]))
+        self.assertFalse(any(p["name"] == "Home" for p in self.client.request("ListProfiles")))
+
+        result = self.cli("import", "networkmanager", "--path", str(keyfiles))
+        self.assertEqual(result.returncode, 0, result.stderr)
+        profiles = {p["name"]: p for p in self.client.request("ListProfiles")}
+        self.assertEqual(profiles["Home"]["interface"], "wlan0")
+        self.assertEqual(profiles["Home"]["priority"], 5)
+        self.assertEqual(profiles["Wired static"]["method"], "static")
+        self.assertEqual(profiles["Wired static"]["netmask"], "255.255.255.0")
+        self.assertEqual(profiles["Wired static"]["dns_servers"], ["192.168.2.1", "9.9.9.9"])
+
+        self.assertEqual(self.cli("nm", "con", "up", "Home").returncode, 0)
+        self.cli("disconnect", "wlan0")
+
+    def test_import_networkd_and_netplan(self):
+        networkd = Path(self.state_dir.name) / "networkd"
+        networkd.mkdir()
+        for name, content in NETWORKD_FILES.items():
+            (networkd / name).write_text(content)
+
+        result = self.cli("--json", "import", "networkd", "--path", str(networkd))
+        self.assertEqual(result.returncode, 0, result.stderr)
+        report = json.loads(result.stdout)
+        self.assertEqual(report["imported"], ["20-eth1.20"])
+        self.assertEqual([s["name"] for s in report["skipped"]], ["30-all"])
+        vlan = next(p for p in self.client.request("ListProfiles") if p["name"] == "20-eth1.20")
+        self.assertEqual(vlan["netdev"], {"kind": "vlan", "id": 20, "parent": "eth1"})
+        self.assertEqual(vlan["dns_servers"], ["10.20.20.1", "9.9.9.9"])
+
+        self.assertEqual(self.cli("connect", "20-eth1.20").returncode, 0)
+        self.assertIn("eth1.20", [i["name"] for i in self.client.request("GetInterfaces")])
+        self.cli("disconnect", "eth1.20")
+
+        netplan = Path(self.state_dir.name) / "netplan"
+        netplan.mkdir()
+        (netplan / "50-cloud-init.yaml").write_text(NETPLAN_FILE)
+        result = self.cli("--json", "import", "netplan", "--path", str(netplan), "--dry-run")
+        self.assertEqual(result.returncode, 0, result.stderr)
+        report = json.loads(result.stdout)
+        profiles = {p["name"]: p for p in report["profiles"]}
+        self.assertEqual(sorted(profiles), ["Cabin", "bond0", "eth0"])
+        self.assertEqual(profiles["bond0"]["netdev"], {"kind": "bond", "ports": ["eth1"], "mode": "active-backup"})
+        self.assertEqual(profiles["bond0"]["gateway"], "192.168.7.1")
+        self.assertEqual(profiles["Cabin"]["interface"], "wlan0")
+        self.assertIn("modems", [s["name"] for s in report["skipped"]])
+
+    def test_export(self):
+        self.client.request("CreateProfile", name="Exported wifi", interface="wlan0", connection_type="wifi",
+                            ssid="Cabin", password="log-fire", auto_connect=False, replace=True)
+        try:
+            printed = self.cli("export", "netplan", "-p", "Exported wifi")
+            self.assertEqual(printed.returncode, 0, printed.stderr)
+            self.assertIn("Cabin: {}", printed.stdout)
+            self.assertNotIn("log-fire", printed.stdout)
+            self.assertIn("password was left out", printed.stderr)
+
+            # The test runs as the daemon's user, which may have the passwords
+            output = Path(self.state_dir.name) / "exported"
+            result = self.cli("export", "netplan", "-p", "Exported wifi", "--secrets", "-o", str(output))
+            self.assertEqual(result.returncode, 0, result.stderr)
+            written = output / "90-alopex.yaml"
+            self.assertEqual(written.stat().st_mode & 0o777, 0o600)
+            self.assertIn('password: "log-fire"', written.read_text())
+
+            networkd = json.loads(self.cli("--json", "export", "networkd", "-p", "Exported wifi").stdout)
+            self.assertEqual([entry["name"] for entry in networkd["files"]], ["10-alopex-exported-wifi.network"])
+            self.assertIn("SSID=Cabin", networkd["files"][0]["content"])
+            self.assertEqual(self.cli("export", "netplan", "-p", "nowhere").returncode, 4)
+        finally:
+            self.client.request("DeleteProfile", name="Exported wifi")
+
+    def test_backup_and_restore(self):
+        archive = Path(self.state_dir.name) / "profiles.alopex"
+        self.client.request("CreateProfile", name="Backed up", interface="wlan0", connection_type="wifi",
+                            ssid="Cabin", password="log-fire", auto_connect=False)
+        self.client.request("CreateProfile", name="Kept", interface="eth1", connection_type="ethernet",
+                            auto_connect=False, replace=True)
+        env = {"ALOPEX_BACKUP_PASSPHRASE": "correct horse"}
+
+        result = self.cli("backup", str(archive), env=env)
+        self.assertEqual(result.returncode, 0, result.stderr)
+        self.assertEqual(archive.stat().st_mode & 0o777, 0o600)
+        self.assertNotIn("log-fire", archive.read_text())
+
+        self.client.request("DeleteProfile", name="Backed up")
+        result = self.cli("restore", str(archive), env={"ALOPEX_BACKUP_PASSPHRASE": "wrong"})
+        self.assertEqual(result.returncode, 2)
+
+        result = self.cli("--json", "restore", str(archive), env=env)
+        self.assertEqual(result.returncode, 0, result.stderr)
+        report = json.loads(result.stdout)
+        self.assertEqual(report["imported"], ["Backed up"])
+        self.assertEqual([entry["name"] for entry in report["skipped"]], ["Kept"])
+        self.assertEqual(self.cli("nm", "con", "up", "Backed up").returncode, 0)
+        self.cli("disconnect", "wlan0")
+
+    def test_snapshot(self):
+        self.client.request("CreateProfile", name="Snapshot wifi", interface="wlan0", connection_type="wifi",
+                            ssid="Cabin", password="log-fire", auto_connect=False, replace=True)
+        snapshot_file = Path(self.state_dir.name) / "snapshot.json"
+        result = self.cli("snapshot", str(snapshot_file), "--events", "50")
+        self.assertEqual(result.returncode, 0, result.stderr)
+        self.assertEqual(snapshot_file.stat().st_mode & 0o777, 0o600)
+        self.assertNotIn("log-fire", snapshot_file.read_text())
+
+        snapshot = json.loads(snapshot_file.read_text())
+        self.assertTrue(any(iface["name"] == "eth0" for iface in snapshot["interfaces"]))
+        self.assertTrue(any(route["dst"] == "default" for route in snapshot["routes"]))
+        self.assertLessEqual(len(snapshot["events"]), 50)
+        profile = next(p for p in snapshot["config"]["profiles"] if p["name"] == "Snapshot wifi")
+        self.assertEqual(profile["password"], "<redacted>")
+
+        tarball = Path(self.state_dir.name) / "snapshot.tar.gz"
+        result = self.cli("snapshot", str(tarball))
+        self.assertEqual(result.returncode, 0, result.stderr)
+        with tarfile.open(tarball) as tar:
+            names = {Path(name).name for name in tar.getnames()}
+        self.assertTrue({"snapshot.json", "routes.json", "daemon.log"} <= names)
+
+    def wait_for_location(self, name, timeout=5):
+        deadline = time.monotonic() + timeout
+        while self.client.request("GetStatus")["location"] != name and time.monotonic() < deadline:
+            time.sleep(0.1)
+        return self.client.request("GetStatus")["location"]
+
+    def test_locations(self):
+        self.client.request("CreateProfile", name="Lab wired", interface="eth1", connection_type="ethernet",
+                            auto_connect=False, replace=True)
+        result = self.cli("location", "set", "Lab", "--profile", "Lab wired", "--dns", "192.0.2.1",
+                          "--proxy", "http://proxy.lab:3128", "--zone", "work", "--gateway-mac", "52:54:00:00:02:01")
+        self.assertEqual(result.returncode, 0, result.stderr)
+        self.assertEqual(self.cli("location", "set", "Broken", "--profile", "nope").returncode, 2)
+        self.assertEqual(self.cli("location", "set", "Cafe", "--ssid", "Guest").returncode, 0)
+
+        result = self.cli("--json", "location", "switch", "Lab")
+        self.assertEqual(result.returncode, 0, result.stderr)
+        applied = json.loads(result.stdout)
+        self.assertEqual(applied["connected"], ["Lab wired"])
+        self.assertTrue(applied["dns"])
+        self.assertTrue(applied["firewall"]["eth1"])
+        self.assertEqual(self.client.request("GetStatus")["location"], "Lab")
+
+        # Joining the cafe network switches automatically; plugging the lab cable back in switches back
+        self.assertEqual(self.cli("disconnect", "eth1").returncode, 0)
+        self.assertEqual(self.cli("nm", "dev", "wifi", "connect", "Guest").returncode, 0)
+        self.assertEqual(self.wait_for_location("Cafe"), "Cafe")
+        self.cli("disconnect", "wlan0")
+        self.assertEqual(self.cli("connect", "Lab wired").returncode, 0)
+        self.assertEqual(self.wait_for_location("Lab"), "Lab")
+
+        result = self.cli("location", "list")
+        self.assertEqual(result.returncode, 0, result.stderr)
+        self.assertIn("Lab wired", result.stdout)
+
+        self.assertEqual(self.cli("location", "auto", "off").returncode, 0)
+        self.assertEqual(self.cli("location", "remove", "Lab").returncode, 0)
+        self.assertEqual(self.cli("location", "remove", "Lab").returncode, 4)
+        self.cli("location", "remove", "Cafe")
+        self.assertIsNone(self.client.request("GetStatus")["location"])
+        self.cli("disconnect", "eth1")
+
+    def test_profile_units(self):
+        self.client.request("CreateProfile", name="Sync LAN", interface="eth1", connection_type="ethernet",
+                            auto_connect=False, replace=True)
+        result = self.cli("profile", "units", "Sync LAN", "syncthing.service", "missing.service")
+        self.assertEqual(result.returncode, 0, result.stderr)
+        self.assertEqual(self.cli("profile", "units", "Sync LAN", "not a unit").returncode, 2)
+        self.assertEqual(self.cli("profile", "units", "Nobody", "x.service").returncode, 4)
+        self.assertIn("syncthing.service", self.cli("profile", "list").stdout)
+
+        events = []
+
+        def collect():
+            for event in self.client.subscribe(["ProfileServiceChanged"]):
+                events.append(event)
+                if len(events) == 4:
+                    return
+
+        listener = threading.Thread(target=collect, daemon=True)
+        listener.start()
+        time.sleep(0.3)
+
+        self.assertEqual(self.cli("connect", "Sync LAN").returncode, 0)
+        self.assertEqual(self.cli("disconnect", "eth1").returncode, 0)
+        listener.join(timeout=5)
+
+        self.assertEqual([(e["action"], e["unit"], e["ok"]) for e in events], [
+            ("start", "syncthing.service", True), ("start", "missing.service", False),
+            ("stop", "missing.service", False), ("stop", "syncthing.service", True)
+        ])
+        self.assertTrue(all(e["profile"] == "Sync LAN" and e["interface"] == "eth1" for e in events))
+
+    def wait_for_status(self, key, value, timeout=5):
+        deadline = time.monotonic() + timeout
+        while self.client.request("GetStatus")[key] != value and time.monotonic() < deadline:
+            time.sleep(0.1)
+        return self.client.request("GetStatus")[key]
+
+    def test_metered(self):
+        self.client.request("CreateProfile", name="Hotspot", interface="eth1", connection_type="ethernet",
+                            auto_connect=False, priority=100, replace=True)
+        self.assertEqual(self.cli("profile", "metered", "Hotspot", "yes").returncode, 0)
+        self.assertEqual(self.cli("profile", "metered", "Hotspot", "maybe").returncode, 2)
+
+        self.assertEqual(self.cli("connect", "Hotspot").returncode, 0)
+        self.assertIs(self.wait_for_status("metered", True), True)
+        self.assertIn("metered", self.cli("status").stdout)
+
+        self.assertEqual(self.cli("profile", "metered", "Hotspot", "no").returncode, 0)
+        self.assertIs(self.wait_for_status("metered", False), False)
+        self.cli("disconnect", "eth1")
+
+    def test_boot_critical(self):
+        self.client.request("CreateProfile", name="Boot uplink", interface="eth1", connection_type="ethernet",
+                            auto_connect=False, replace=True)
+        try:
+            result = self.cli("profile", "critical", "Boot uplink", "yes")
+            self.assertEqual(result.returncode, 0, result.stderr)
+            self.assertIn("Boot uplink: boot-critical yes", result.stdout)
+            profiles = {p["name"]: p for p in self.client.request("ListProfiles")}
+            self.assertTrue(profiles["Boot uplink"]["boot_critical"])
+            with self.assertRaises(IpcError) as invalid:
+                self.client.request("UpdateProfile", name="Boot uplink", boot_critical="soon")
+            self.assertEqual(invalid.exception.code, ErrorCode.INVALID_CONFIG)
+        finally:
+            self.client.request("DeleteProfile", name="Boot uplink")
+
+        # Flagged after startup, so this daemon had nothing to wait for
+        self.assertEqual(self.client.request("GetBootStatus")["profiles"], [])
+        waited = subprocess.run([sys.executable, str(DAEMON), "--socket", str(self.socket_path), "wait-online",
+                                 "--timeout", "5"], capture_output=True, text=True, timeout=30)
+        self.assertEqual(waited.returncode, 0, waited.stderr)
+
+    def test_auto_connect_retries(self):
+        self.assertEqual(self.cli("profile", "retries").stdout.split()[:3], ["PROFILE", "INTERFACE", "FAILURES"])
+        self.assertEqual(json.loads(self.cli("--json", "profile", "retries").stdout), [])
+        self.assertEqual(self.cli("profile", "retry").stdout.strip(), "Nothing to retry")
+        self.assertEqual(self.cli("profile", "retry", "Nowhere").returncode, 4)
+
+    def test_failover(self):
+        deadline = time.monotonic() + 5
+        while self.client.request("ListFailover")[0]["active"] != "backup" and time.monotonic() < deadline:
+            time.sleep(0.1)
+        result = self.cli("failover", "list")
+        self.assertEqual(result.returncode, 0, result.stderr)
+        self.assertEqual(result.stdout.splitlines()[1].split()[:5], ["uplink", "wlan0", "eth0", "eth0", "never"])
+        # The failover route is one the interfaces config doesn't have
+        plan = self.client.request("ApplyInterfaces", interfaces={"eth0": {}}, dry_run=True)
+        self.assertIn("- eth0 route default via 192.168.1.1 metric 10", [c["text"] for c in plan["changes"]])
+
+        self.assertIn("uplink: on eth0 (backup)", self.cli("failover", "use", "uplink", "backup").stdout)
+        self.assertEqual(self.cli("failover", "use", "nowhere", "primary").returncode, 4)
+        self.assertEqual(self.cli("failover", "use", "uplink", "sideways").returncode, 2)
+
+    def test_profile_metric(self):
+        self.client.request("CreateProfile", name="Metric lab", interface="eth1", connection_type="ethernet",
+                            auto_connect=False, replace=True)
+        try:
+            self.assertIn("Metric lab: route metric 250", self.cli("profile", "metric", "Metric lab", "250").stdout)
+            self.assertEqual(self.cli("profile", "metric", "Metric lab", "fast").returncode, 2)
+            profiles = {p["name"]: p for p in self.client.request("ListProfiles")}
+            self.assertEqual(profiles["Metric lab"]["route_metric"], 250)
+            self.assertEqual(self.cli("profile", "metric", "Metric lab", "auto").returncode, 0)
+            profiles = {p["name"]: p for p in self.client.request("ListProfiles")}
+            self.assertIsNone(profiles["Metric lab"]["route_metric"])
+        finally:
+            self.client.request("DeleteProfile", name="Metric lab")
+
+    def test_profile_sysctl(self):
+        self.client.request("CreateProfile", name="Sysctl lab", interface="eth1", connection_type="ethernet",
+                            auto_connect=False, replace=True)
+        try:
+            result = self.cli("profile", "sysctl", "Sysctl lab", "rp_filter=1", "tcp_congestion_control=bbr")
+            self.assertIn("Sysctl lab: sysctls rp_filter=1, tcp_congestion_control=bbr", result.stdout)
+            self.assertEqual(self.cli("profile", "sysctl", "Sysctl lab", "rp_filter").returncode, 2)
+            self.assertEqual(self.cli("profile", "sysctl", "Sysctl lab", "swappiness=10").returncode, 2)
+            self.assertEqual(self.cli("profile", "sysctl", "Sysctl lab").returncode, 0)
+            profiles = {p["name"]: p for p in self.client.request("ListProfiles")}
+            self.assertEqual(profiles["Sysctl lab"]["sysctls"], {})
+            # None set on eth1, whatever is active there
+            self.assertEqual(self.cli("sysctls", "eth1").returncode, 0)
+            self.assertEqual(self.cli("sysctls", "eth9").returncode, 4)
+        finally:
+            self.client.request("DeleteProfile", name="Sysctl lab")
+
+    def test_profile_mptcp(self):
+        self.client.request("CreateProfile", name="Mptcp lab", interface="eth1", connection_type="ethernet",
+                            auto_connect=False, replace=True)
+        try:
+            result = self.cli("profile", "mptcp", "Mptcp lab", "backup", "subflow")
+            self.assertIn("Mptcp lab: MPTCP endpoints subflow, backup", result.stdout)
+            self.assertEqual(self.cli("profile", "mptcp", "Mptcp lab", "signal", "fullmesh").returncode, 2)
+            self.assertIn("no MPTCP endpoints", self.cli("profile", "mptcp", "Mptcp lab").stdout)
+
+            self.assertIn("up to 3 subflows, 1 accepted addresses", self.cli("mptcp", "limits", "3", "1").stdout)
+            self.assertIn("MPTCP enabled: up to 3 subflows", self.cli("mptcp", "list").stdout)
+            self.assertEqual(self.cli("mptcp", "limits", "9", "1").returncode, 2)
+        finally:
+            self.client.request("SetMptcpLimits", subflows=2, add_addr_accepted=0)
+            self.client.request("DeleteProfile", name="Mptcp lab")
+
+    def test_profile_shaping(self):
+        self.client.request("CreateProfile", name="Shaping lab", interface="eth1", connection_type="ethernet",
+                            auto_connect=False, replace=True)
+        try:
+            result = self.cli("profile", "qdisc", "Shaping lab", "cake", "--bandwidth", "95mbit")
+            self.assertIn("Shaping lab: qdisc cake 95mbit", result.stdout)
+            self.assertEqual(self.cli("profile", "qdisc", "Shaping lab", "fq", "--bandwidth", "95mbit").returncode, 2)
+            self.assertIn("congestion control bbr", self.cli("profile", "congestion", "Shaping lab", "bbr").stdout)
+            self.assertIn("host default", self.cli("profile", "congestion", "Shaping lab", "none").stdout)
+            self.assertIn("Congestion control:", self.cli("shaping", "show", "eth1").stdout)
+            self.assertEqual(self.cli("shaping", "show", "eth9").returncode, 4)
+            # Nothing queues up on eth0 in this scenario, so cake makes no difference
+            result = self.cli("shaping", "test", "eth0", "--qdisc", "cake", "--bandwidth", "500mbit", "--seconds", "2")
+            self.assertEqual(result.returncode, 0)
+            self.assertIn("eth0: +0.0 ms under load with kernel default, +0.0 ms with cake 500mbit; no real difference",
+                          result.stdout)
+            self.assertEqual(self.client.request("GetShaping", interface="eth0")["qdisc"]["kind"], "fq_codel")
+            result = self.cli("bufferbloat", "eth0", "--seconds", "2")
+            self.assertEqual(result.returncode, 0)
+            self.assertIn("eth0: grade A+", result.stdout)
+        finally:
+            self.client.request("DeleteProfile", name="Shaping lab")
+
+    def test_notify(self):
+        self.client.request("CreateProfile", name="Notify lab", interface="eth1", connection_type="ethernet",
+                            auto_connect=False, replace=True)
+        notifier = subprocess.Popen([sys.executable, str(NOTIFY), "--socket", str(self.socket_path), "--print"],
+                                    stdout=subprocess.PIPE, stderr=subprocess.PIPE, text=True)
+        try:
+            time.sleep(1)
+            # Asked for: no notification
+            self.assertEqual(self.cli("connect", "Notify lab").returncode, 0)
+            self.assertEqual(self.cli("disconnect", "eth1").returncode, 0)
+
+            self.client.request("MockSetCarrier", interface="eth1", up=False)
+            self.assertNotEqual(self.cli("connect", "Notify lab").returncode, 0)
+            time.sleep(0.5)
+        finally:
+            self.client.request("MockSetCarrier", interface="eth1", up=True)
+            notifier.terminate()
+            output, _ = notifier.communicate(timeout=10)
+
+        lines = output.splitlines()
+        self.assertEqual(len(lines), 1, output)
+        self.assertTrue(lines[0].startswith("[normal] Connection Notify lab failed to connect"), output)
+
+    def test_gui_once(self):
+        # Needs no display and no PyQt6; the interfaces are this machine's, not the mock's
+        env = dict(os.environ, ALOPEX_SOCKET=str(self.socket_path))
+        result = subprocess.run([sys.executable, str(GUI), "--once", "--json"],
+                                capture_output=True, text=True, timeout=30, env=env)
+        self.assertEqual(result.returncode, 0, result.stderr)
+        interfaces = json.loads(result.stdout)
+        self.assertTrue(all("metrics" in i and "profile" in i for i in interfaces))
+
+        result = subprocess.run([sys.executable, str(GUI), "--once", "--units", "bits-si"],
+                                capture_output=True, text=True, timeout=30, env=env)
+        self.assertEqual(result.returncode, 0, result.stderr)
+        self.assertTrue(result.stdout.startswith("DEVICE"))
+
+    def test_gui_statusbar(self):
+        env = dict(os.environ, ALOPEX_SOCKET=str(self.socket_path))
+        bar = subprocess.Popen([sys.executable, str(GUI), "--statusbar", "--interval", "500"],
+                               stdout=subprocess.PIPE, stderr=subprocess.PIPE, text=True, env=env)
+        try:
+            status = json.loads(bar.stdout.readline())
+        finally:
+            bar.terminate()
+            bar.communicate(timeout=10)
+        self.assertIn("text", status)
+        self.assertIn(status["alt"], ("disconnected", "wired", "wifi", "vpn"))
+        self.assertNotIn("alopexd not running", status["tooltip"])
+
+    def session_interfaces(self):
+        return [s["interface"] for s in json.loads(self.cli("--json", "session", "list").stdout)]
+
+    def test_session_counters(self):
+        self.client.request("CreateProfile", name="Session lab", interface="eth1", connection_type="ethernet",
+                            auto_connect=False, replace=True)
+        self.assertEqual(self.cli("session", "reset", "eth1").returncode, 4)
+
+        self.assertEqual(self.cli("connect", "Session lab").returncode, 0)
+        deadline = time.monotonic() + 5
+        while "eth1" not in self.session_interfaces() and time.monotonic() < deadline:
+            time.sleep(0.1)
+        sessions = json.loads(self.cli("--json", "session", "list").stdout)
+        session = next(s for s in sessions if s["interface"] == "eth1")
+        self.assertEqual(session["profile"], "Session lab")
+        self.assertGreaterEqual(session["rx"], 0)
+
+        result = self.cli("--json", "session", "reset", "eth1")
+        self.assertEqual(result.returncode, 0, result.stderr)
+        self.assertEqual(json.loads(result.stdout), {"reset": ["eth1"]})
+        self.assertIn("session rx/tx", self.cli("metrics", "eth1").stdout)
+
+        self.cli("disconnect", "eth1")
+        deadline = time.monotonic() + 5
+        while "eth1" in self.session_interfaces() and time.monotonic() < deadline:
+            time.sleep(0.1)
+        self.assertNotIn("eth1", self.session_interfaces())
+
+    def dns_health(self):
+        return {h["server"]: h for h in json.loads(self.cli("--json", "dns", "show").stdout)}
+
+    def test_dns_servers(self):
+        self.client.request("CreateProfile", name="DNS lab", interface="eth1", connection_type="ethernet",
+                            auto_connect=False, replace=True)
+        self.assertEqual(self.cli("dns", "set", "eth1", "9.9.9.9").returncode, 4)
+        self.assertEqual(self.cli("connect", "DNS lab").returncode, 0)
+        self.assertEqual(self.cli("dns", "set", "eth1", "9.9.9.9", "resolver.lan").returncode, 2)
+
+        result = self.cli("dns", "set", "eth1", "203.0.113.5", "198.51.100.7", "9.9.9.9")
+        self.assertEqual(result.returncode, 0, result.stderr)
+        self.assertIn("203.0.113.5, 198.51.100.7, 9.9.9.9", result.stdout)
+        profile = next(p for p in self.client.request("ListProfiles") if p["name"] == "DNS lab")
+        self.assertEqual(profile["dns_servers"], ["203.0.113.5", "198.51.100.7", "9.9.9.9"])
+
+        deadline = time.monotonic() + 5
+        while self.dns_health().get("9.9.9.9", {}).get("status") != "ok" and time.monotonic() < deadline:
+            time.sleep(0.1)
+        health = self.dns_health()
+        self.assertEqual(list(health), ["203.0.113.5", "198.51.100.7", "9.9.9.9"])
+        self.assertEqual(health["203.0.113.5"]["status"], "slow")
+        self.assertEqual(health["198.51.100.7"]["status"], "failing")
+        self.assertEqual(health["198.51.100.7"]["failures"], health["198.51.100.7"]["checks"])
+        self.assertEqual(health["9.9.9.9"]["latency_ms"], 20.0)
+
+        # No servers: back to the lease's
+        result = self.cli("--json", "dns", "set", "eth1")
+        self.assertEqual(result.returncode, 0, result.stderr)
+        self.assertEqual(json.loads(result.stdout)["effective"], ["192.0.2.53"])
+        self.cli("disconnect", "eth1")
+
+        # The forwarder is off unless enterprise.json turns it on
+        self.assertIn("forwarder is off", self.cli("dns", "cache").stdout)
+        self.assertEqual(self.cli("dns", "flush").returncode, 4)
+        self.assertIn("filtering is off", self.cli("dns", "filter").stdout)
+        self.assertEqual(self.cli("dns", "filter", "--off", "home").returncode, 4)
+
+    def test_undo(self):
+        self.client.request("CreateProfile", name="Undo lab", interface="eth1", connection_type="ethernet",
+                            auto_connect=False, replace=True)
+        self.cli("location", "auto", "off")
+        try:
+            # A profile edit: static addressing, then metering, undone newest first
+            self.client.request("UpdateProfile", name="Undo lab", method="static", ip_address="192.168.2.77")
+            self.assertEqual(self.cli("profile", "metered", "Undo lab", "yes").returncode, 0)
+            changes = json.loads(self.cli("--json", "undo", "--list").stdout)
+            self.assertEqual([c["summary"] for c in changes[:2]],
+                             ["Edit profile Undo lab (metered)", "Edit profile Undo lab (ip_address, method)"])
+            self.assertIn("Undid: Edit profile Undo lab (metered)", self.cli("undo").stdout)
+            self.assertEqual(self.cli("undo").returncode, 0)
+            profile = next(p for p in self.client.request("ListProfiles") if p["name"] == "Undo lab")
+            self.assertEqual((profile["method"], profile["ip_address"], profile["metered"]), ("dhcp", None, None))
+
+            # DNS servers come back, and are applied while the profile is up
+            self.assertEqual(self.cli("connect", "Undo lab").returncode, 0)
+            self.assertEqual(self.cli("dns", "set", "eth1", "203.0.113.9").returncode, 0)
+            self.assertEqual(self.cli("dns", "set", "eth1", "9.9.9.9").returncode, 0)
+            undone = json.loads(self.cli("--json", "undo").stdout)
+            self.assertEqual(undone["undone"]["summary"], "Set DNS of eth1 to 9.9.9.9")
+            self.assertEqual(undone["result"]["effective"], ["203.0.113.9"])
+            self.assertTrue(undone["result"]["applied"])
+
+            # A location switch: the zone before it, and the location before it
+            for name, zone in (("Undo home", "home"), ("Undo work", "work")):
+                result = self.cli("location", "set", name, "--profile", "Undo lab", "--zone", zone)
+                self.assertEqual(result.returncode, 0, result.stderr)
+            self.assertEqual(self.cli("location", "switch", "Undo home").returncode, 0)
+            switched = json.loads(self.cli("--json", "location", "switch", "Undo work").stdout)
+            self.assertEqual(switched["previous_zones"]["eth1"], "home")
+            undone = json.loads(self.cli("--json", "undo").stdout)
+            self.assertEqual(undone["result"]["location"], "Undo home")
+            self.assertTrue(undone["result"]["firewall"]["eth1"])
+            self.assertEqual(self.client.request("GetStatus")["location"], "Undo home")
+
+            # Undoing an edit of a profile that is gone since fails and keeps the change for another try
+            self.client.request("UpdateProfile", name="Undo lab", metered=True)
+            self.client.request("DeleteProfile", name="Undo lab")
+            self.assertEqual(self.cli("undo").returncode, 4)
+            self.assertIn("Edit profile Undo lab (metered)", self.cli("undo", "--list").stdout)
+            self.client.request("CreateProfile", name="Undo lab", interface="eth1", connection_type="ethernet",
+                                auto_connect=False, metered=True)
+            self.assertIn("Undid: Edit profile Undo lab (metered)", self.cli("undo").stdout)
+            self.assertNotIn("Edit profile Undo lab (metered)", self.cli("undo", "--list").stdout)
+        finally:
+            self.cli("disconnect", "eth1")
+            for name in ("Undo home", "Undo work"):
+                self.cli("location", "remove", name)
+            self.client.try_request("DeleteProfile", name="Undo lab")
+
+    def test_dhcp_lease(self):
+        self.client.request("CreateProfile", name="Lease lab", interface="eth1", connection_type="ethernet",
+                            auto_connect=False, replace=True)
+        self.assertEqual(self.cli("connect", "Lease lab").returncode, 0)
+
+        result = self.cli("--json", "dhcp", "show", "eth1")
+        self.assertEqual(result.returncode, 0, result.stderr)
+        lease = json.loads(result.stdout)
+        self.assertEqual((lease["ip_address"], lease["server"]), ("192.168.2.10", "192.168.2.1"))
+        self.assertEqual(lease["renewal_time"], 1800)
+        self.assertLessEqual(lease["expires_in"], 3600)
+        self.assertIn("subnet_mask", lease["options"])
+        self.assertIn("option subnet_mask", self.cli("dhcp", "show", "eth1").stdout)
+
+        time.sleep(1.1)
+        result = self.cli("--json", "dhcp", "renew", "eth1")
+        self.assertEqual(result.returncode, 0, result.stderr)
+        self.assertGreater(json.loads(result.stdout)["acquired_at"], lease["acquired_at"])
+        result = self.cli("--json", "events", "--filter", "DhcpLease*", "--filter", "interface=eth1", "-n", "1")
+        self.assertEqual(result.returncode, 0, result.stderr)
+        [renewal] = json.loads(result.stdout)
+        self.assertEqual((renewal["address"], renewal["renewed"]), ("192.168.2.10", True))
+
+        result = self.cli("dhcp", "release", "eth1")
+        self.assertEqual(result.returncode, 0, result.stderr)
+        self.assertEqual(self.client.request("GetStatus")["active_profiles"].get("eth1"), None)
+        self.assertEqual(self.cli("dhcp", "show", "eth1").returncode, 4)
+        self.assertEqual(self.cli("dhcp", "renew", "eth1").returncode, 4)
+        self.cli("disconnect", "eth1")
+
+    def test_events(self):
+        started = time.time()
+        self.client.request("SetInterfaceLabel", interface="eth0", label="uplink")
+        self.client.request("SetInterfaceLabel", interface="eth0", label="")
+        result = self.cli("events", "--since", "1m", "--filter", "InterfaceLabelChanged")
+        self.assertEqual(result.returncode, 0, result.stderr)
+        self.assertRegex(result.stdout, r"InterfaceLabelChanged\s+interface=eth0 label=uplink")
+
+        # Kept on disk, without job progress ticks
+        logged = [json.loads(line) for line in (Path(self.state_dir.name) / "events.jsonl").read_text().splitlines()]
+        self.assertTrue(any(e["event"] == "InterfaceLabelChanged" and e["timestamp"] >= started for e in logged))
+        self.assertFalse(any(e["event"] == "JobProgress" for e in logged))
+
+        self.assertEqual(self.client.request("GetEvents", since=time.time()), [])
+        self.assertEqual(self.cli("events", "--filter", "=eth0").returncode, 2)
+        self.assertEqual(self.cli("events", "--since", "yesterday-ish").returncode, 2)
+
+    def test_exit_codes(self):
+        self.assertEqual(self.cli("metrics", "nope0").returncode, 4)
+        self.assertEqual(self.cli("bogus").returncode, 2)
+        missing = Path(self.state_dir.name) / "missing.sock"
+        self.assertEqual(self.cli("status", socket_path=missing).returncode, 3)
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,70 @@
+"""
+Connectivity - IPv4 against IPv6 reachability and the diagnosis built from both
+"""
+
+import sys
+import socket
+import asyncio
+import unittest
+from pathlib import Path
+
+REPO_ROOT = Path(__file__).resolve().parents[2]
+sys.path.insert(0, str(REPO_ROOT / "src" / "alopex-qt"))
+
+from network import connectivity
+
+class ConnectivityTest(unittest.TestCase):
+    @staticmethod
+    def probe(state, latency=None):
+        return {"state": state, "address": "2001:db8::1", "latency_ms": latency, "error": None}
+
+    def result(self, target, ipv4, ipv6):
+        return {"target": target, "ipv4": ipv4, "ipv6": ipv6, "used": connectivity.race_outcome(ipv4, ipv6)}
+
+    def test_check_local(self):
+        with socket.socket() as listener:
+            listener.bind(("127.0.0.1", 0))
+            listener.listen()
+            port = listener.getsockname()[1]
+            result = asyncio.run(connectivity.check("127.0.0.1", port, timeout=2.0))
+        self.assertEqual(result["ipv4"]["state"], "open")
+        # An IPv4 literal has no IPv6 address
+        self.assertEqual(result["ipv6"]["state"], "unresolved")
+        self.assertEqual((result["used"]["family"], result["used"]["address"]), (4, "127.0.0.1"))
+
+    def test_race_outcome(self):
+        # IPv6 wins unless IPv4 beats it by more than the head start
+        self.assertEqual(connectivity.race_outcome(self.probe("open", 20), self.probe("open", 200))["family"], 6)
+        self.assertEqual(connectivity.race_outcome(self.probe("open", 20), self.probe("open", 300))["family"], 4)
+        slow = connectivity.race_outcome(self.probe("open", 20), self.probe("filtered"))
+        self.assertEqual((slow["family"], slow["latency_ms"]), (4, 270))
+        fast = connectivity.race_outcome(self.probe("open", 20), self.probe("unreachable"))
+        self.assertEqual(fast["latency_ms"], 20)
+        self.assertIsNone(connectivity.race_outcome(self.probe("filtered"), self.probe("unresolved"))["family"])
+
+    def test_diagnose(self):
+        dual = connectivity.diagnose([self.result("a:443", self.probe("open", 20), self.probe("open", 25)),
+                                      self.result("b:443", self.probe("open", 30), self.probe("open", 35))])
+        self.assertTrue(dual["healthy"])
+        self.assertEqual((dual["ipv4"]["status"], dual["ipv6"]["status"], dual["preferred"]), ("working", "working", 6))
+
+        broken = connectivity.diagnose([self.result("a:443", self.probe("open", 20), self.probe("filtered")),
+                                        self.result("b:443", self.probe("open", 30), self.probe("filtered"))])
+        self.assertFalse(broken["healthy"])
+        self.assertEqual((broken["ipv6"]["status"], broken["ipv6"]["failing"], broken["preferred"]),
+                         ("broken", ["a:443", "b:443"], 4))
+        self.assertTrue(broken["findings"][0].startswith("IPv6 is broken"))
+        text = connectivity.report(broken)
+        self.assertTrue(text.startswith("Problems found:"))
+        self.assertIn("timed out", next(line for line in text.splitlines() if line.strip().startswith("a:443")))
+
+        v4_only = connectivity.diagnose([self.result("a:443", self.probe("open", 20), self.probe("unreachable"))])
+        self.assertTrue(v4_only["healthy"])
+        self.assertEqual(v4_only["findings"], ["No IPv6 connectivity; everything goes over IPv4"])
+
+        no_dns = connectivity.diagnose([self.result("a:443", self.probe("unresolved"), self.probe("unresolved"))])
+        self.assertFalse(no_dns["healthy"])
+        self.assertIn("check DNS", no_dns["findings"][0])
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,86 @@
+"""
+Conntrack - flows from ctnetlink dumps and /proc lines, and the summary of them
+"""
+
+import sys
+import socket
+import ipaddress
+import struct
+import unittest
+from pathlib import Path
+
+REPO_ROOT = Path(__file__).resolve().parents[2]
+sys.path.insert(0, str(REPO_ROOT / "src" / "alopex-qt"))
+
+from network import conntrack
+
+def nla(kind, payload):
+    """One netlink attribute, padded to 4 bytes"""
+    return (struct.pack("=HH", 4 + len(payload), kind) + payload).ljust((7 + len(payload)) & ~3, b"\0")
+
+def ct_tuple(src, dst, sport, dport, protocol=6):
+    ip = nla(conntrack.CTA_IP_V4_SRC, socket.inet_aton(src)) + nla(conntrack.CTA_IP_V4_DST, socket.inet_aton(dst))
+    proto = (nla(conntrack.CTA_PROTO_NUM, bytes([protocol]))
+             + nla(conntrack.CTA_PROTO_SRC_PORT, struct.pack("!H", sport))
+             + nla(conntrack.CTA_PROTO_DST_PORT, struct.pack("!H", dport)))
+    return nla(conntrack.CTA_TUPLE_IP | 0x8000, ip) + nla(conntrack.CTA_TUPLE_PROTO | 0x8000, proto)
+
+def nlmsg(kind, body):
+    return struct.pack("=IHHII", 16 + len(body), kind, 2, 1, 0) + body
+
+class ConntrackTest(unittest.TestCase):
+    def test_netlink_dump(self):
+        request = conntrack.build_dump_request()
+        self.assertEqual(struct.unpack_from("=IHH", request), (20, 0x0101, 0x301))
+
+        # A hotspot client's HTTPS connection, masqueraded behind 192.168.1.50
+        counters = nla(conntrack.CTA_COUNTERS_PACKETS, struct.pack("!Q", 10)) + nla(
+            conntrack.CTA_COUNTERS_BYTES, struct.pack("!Q", 1500))
+        body = (struct.pack("=BBH", socket.AF_INET, 0, 0)
+                + nla(conntrack.CTA_TUPLE_ORIG | 0x8000, ct_tuple("10.42.0.11", "140.82.112.3", 51000, 443))
+                + nla(conntrack.CTA_TUPLE_REPLY | 0x8000, ct_tuple("140.82.112.3", "192.168.1.50", 443, 51000))
+                + nla(conntrack.CTA_PROTOINFO | 0x8000, nla(conntrack.CTA_PROTOINFO_TCP | 0x8000,
+                                                             nla(conntrack.CTA_PROTOINFO_TCP_STATE, b"\x03")))
+                + nla(conntrack.CTA_COUNTERS_ORIG | 0x8000, counters)
+                + nla(conntrack.CTA_COUNTERS_REPLY | 0x8000, counters))
+        flows, done = conntrack.parse_messages(nlmsg(0x0100, body) + nlmsg(conntrack.NLMSG_DONE, b"\0" * 4))
+        self.assertTrue(done)
+        self.assertEqual(flows, [{
+            "family": 4, "protocol": "tcp", "src": "10.42.0.11", "dst": "140.82.112.3", "sport": 51000, "dport": 443,
+            "state": "ESTABLISHED", "snat": "192.168.1.50", "dnat": None, "packets": 20, "bytes": 3000
+        }])
+
+        with self.assertRaises(OSError):
+            conntrack.parse_messages(nlmsg(conntrack.NLMSG_ERROR, struct.pack("=i", -1) + request))
+
+    def test_proc_lines(self):
+        flow = conntrack.parse_proc_line(
+            "ipv4     2 tcp      6 431999 ESTABLISHED src=192.168.1.50 dst=203.0.113.10 sport=40100 dport=8080 "
+            "packets=4 bytes=240 src=10.0.0.5 dst=192.168.1.50 sport=80 dport=40100 packets=3 bytes=400 "
+            "[ASSURED] mark=0 zone=0 use=2"
+        )
+        self.assertEqual((flow["state"], flow["dnat"], flow["snat"]), ("ESTABLISHED", "10.0.0.5", None))
+        self.assertEqual((flow["packets"], flow["bytes"]), (7, 640))
+        flow = conntrack.parse_proc_line(
+            "ipv6     10 udp      17 25 src=2001:db8::2 dst=2001:db8::53 sport=5353 dport=53 [UNREPLIED] "
+            "src=2001:db8::53 dst=2001:db8::2 sport=53 dport=5353 mark=0 zone=0 use=2"
+        )
+        self.assertEqual((flow["family"], flow["protocol"], flow["state"], flow["bytes"]), (6, "udp", None, None))
+        self.assertIsNone(conntrack.parse_proc_line("garbage"))
+
+    def test_summary(self):
+        flows = [conntrack.make_flow({"src": src, "dst": dst, "protocol": 6, "sport": 1, "dport": port},
+                                     {"src": dst, "dst": nat or src, "protocol": 6, "sport": port, "dport": 1})
+                 for src, dst, port, nat in [("10.42.0.11", "192.0.2.1", 443, "192.168.1.50"),
+                                             ("10.42.0.12", "192.0.2.1", 443, "192.168.1.50"),
+                                             ("192.168.1.50", "192.0.2.9", 22, None),
+                                             ("10.9.0.2", "192.0.2.9", 22, "192.168.1.50")]]
+        summary = conntrack.summarize(flows, {"wlan0": ipaddress.ip_network("10.42.0.0/24")}, limit=400, top=1)
+        self.assertEqual((summary["total"], summary["usage"], summary["snat"]), (4, 1.0, 3))
+        self.assertEqual(summary["top_hosts"], [{"host": "192.0.2.1", "flows": 2, "bytes": None}])
+        self.assertEqual(len(summary["top_ports"]), 1)
+        nat, = summary["nat"]
+        self.assertEqual([c["address"] for c in nat["clients"]], ["10.42.0.11", "10.42.0.12"])
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,130 @@
+"""
+Containers - Docker and Podman bridges from a fake engine API, grouped with their veths
+"""
+
+import os
+import sys
+import json
+import socket
+import asyncio
+import tempfile
+import threading
+import unittest
+from pathlib import Path
+
+REPO_ROOT = Path(__file__).resolve().parents[2]
+sys.path.insert(0, str(REPO_ROOT / "src" / "alopex-qt"))
+
+from network.discovery import InterfaceType, NetworkInterface
+from network import containers
+
+DOCKER_NETWORKS = {
+    "/networks": [{"Name": "bridge", "Id": "aa11", "Driver": "bridge"},
+                  {"Name": "host", "Id": "bb22", "Driver": "host"}],
+    "/networks/aa11": {"Name": "bridge", "Id": "aa11", "Driver": "bridge",
+                       "Options": {"com.docker.network.bridge.name": "docker0"},
+                       "IPAM": {"Config": [{"Subnet": "172.17.0.0/16"}]},
+                       "Containers": {"f00d": {"Name": "db", "MacAddress": "02:42:ac:11:00:03", "IPv4Address": ""},
+                                      "c0ffee": {"Name": "web", "MacAddress": "02:42:ac:11:00:02",
+                                                 "IPv4Address": "172.17.0.2/16"}}}
+}
+
+class FakeEngine:
+    """Docker API socket answering from DOCKER_NETWORKS"""
+
+    def __init__(self, path):
+        self.server = socket.socket(socket.AF_UNIX, socket.SOCK_STREAM)
+        self.server.bind(path)
+        self.server.listen()
+        self.requests = []
+        threading.Thread(target=self.serve, daemon=True).start()
+
+    def serve(self):
+        while True:
+            try:
+                connection, _ = self.server.accept()
+            except OSError:
+                return
+            with connection:
+                request = connection.recv(4096).decode()
+                self.requests.append(request.splitlines()[0])
+                path = request.split()[1]
+                if path in DOCKER_NETWORKS:
+                    body = json.dumps(DOCKER_NETWORKS[path]).encode()
+                    connection.sendall(b"HTTP/1.0 200 OK\r\nContent-Type: application/json\r\n\r\n" + body)
+                else:
+                    connection.sendall(b"HTTP/1.0 404 Not Found\r\n\r\n{}")
+
+    def close(self):
+        self.server.close()
+
+class FakeContainersBackend:
+    def __init__(self, networks, masters):
+        self.reported = networks
+        self.bridges = masters
+
+    async def networks(self, socket_path):
+        if socket_path not in self.reported:
+            raise FileNotFoundError(socket_path)
+        return self.reported[socket_path]
+
+    def masters(self):
+        return self.bridges
+
+class ContainersTest(unittest.TestCase):
+    def test_engine_api(self):
+        with tempfile.TemporaryDirectory() as tmp:
+            path = os.path.join(tmp, "docker.sock")
+            engine = FakeEngine(path)
+            try:
+                networks = containers.engine_networks(path)
+                with self.assertRaises(OSError):
+                    containers.api_get(path, "/networks/missing")
+            finally:
+                engine.close()
+        self.assertEqual(engine.requests[:2], ["GET /networks HTTP/1.0", "GET /networks/aa11 HTTP/1.0"])
+        self.assertEqual(networks, [{
+            "name": "bridge", "engine": "docker", "id": "aa11", "driver": "bridge", "bridge": "docker0",
+            "subnets": ["172.17.0.0/16"],
+            "containers": [{"name": "db", "ip": None, "mac": "02:42:ac:11:00:03"},
+                           {"name": "web", "ip": "172.17.0.2", "mac": "02:42:ac:11:00:02"}]
+        }])
+        # User networks without the option get Docker's br-<id> bridge name
+        self.assertEqual(containers.parse_network("docker", {"Name": "lab", "Id": "5e7d1f0a9c3b8a7d",
+                                                             "Driver": "bridge"})["bridge"], "br-5e7d1f0a9c3b")
+
+    def test_group_interfaces(self):
+        reported = [{"name": "bridge", "engine": "docker", "id": "aa11", "driver": "bridge", "bridge": "docker0",
+                     "subnets": [], "containers": []},
+                    {"name": "gone", "engine": "docker", "id": "cc33", "driver": "bridge", "bridge": "br-cc3300000000",
+                     "subnets": [], "containers": []}]
+        masters = {"veth1": "docker0", "veth2": "docker0", "eth1": "br0", "veth9": "podman1"}
+        present = ["eth0", "eth1", "br0", "docker0", "veth1", "veth2", "podman1", "veth9", "br-lan"]
+        grouped = containers.group_interfaces(reported, masters, present)
+        self.assertEqual([(n["engine"], n["name"], n["interfaces"]) for n in grouped], [
+            ("docker", "bridge", ["docker0", "veth1", "veth2"]),
+            ("podman", "podman1", ["podman1", "veth9"])
+        ])
+
+    def test_refresh(self):
+        changes = []
+        network = {"name": "bridge", "engine": "docker", "id": "aa11", "driver": "bridge", "bridge": "docker0",
+                   "subnets": [], "containers": []}
+        backend = FakeContainersBackend({"/var/run/docker.sock": [network]}, {"veth1": "docker0"})
+        present = ["eth0", "docker0", "veth1"]
+        tracker = containers.ContainerNetworks(backend, lambda: present, on_change=changes.append)
+        self.assertTrue(asyncio.run(tracker.refresh()))
+        self.assertFalse(asyncio.run(tracker.refresh()))
+        self.assertEqual(changes, [{"docker0", "veth1"}])
+        self.assertEqual(tracker.snapshot()["engines"], {"docker": "ok", "podman": "not running"})
+        interfaces = [NetworkInterface(name, InterfaceType.OTHER, "Connected") for name in present]
+        tracker.annotate(interfaces)
+        self.assertEqual([i.container_network for i in interfaces], [None, "docker/bridge", "docker/bridge"])
+
+        # The container went away with its veth
+        present.remove("veth1")
+        self.assertTrue(asyncio.run(tracker.refresh()))
+        self.assertEqual(changes[-1], {"docker0"})
+
+if __name__ == "__main__":
+    unittest.main()
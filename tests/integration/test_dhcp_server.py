@@ -0,0 +1,92 @@
+"""
+DHCP server - leases, reservations and the discover/request exchange through the wire format
+"""
+
+import sys
+import socket
+import tempfile
+import unittest
+from pathlib import Path
+
+REPO_ROOT = Path(__file__).resolve().parents[2]
+sys.path.insert(0, str(REPO_ROOT / "src" / "alopex-qt"))
+
+from network.dhcp_server import (
+    ACK, BOOTREQUEST, DISCOVER, NAK, OFFER, OPT_HOSTNAME, OPT_MESSAGE_TYPE, OPT_REQUESTED_IP, OPT_ROUTER,
+    OPT_SERVER_ID, RELEASE, REQUEST, DhcpPacket, DhcpServer, LeasePool, dhcp_range
+)
+from network.hotspot import hotspot_network
+
+class DhcpServerTest(unittest.TestCase):
+    PHONE = "3c:22:fb:10:20:30"
+    LAPTOP = "9a:8b:7c:00:11:22"
+
+    def setUp(self):
+        self.tmp = tempfile.TemporaryDirectory()
+        self.lease_file = Path(self.tmp.name) / "wlan0.json"
+        self.server = self.make_server()
+
+    def tearDown(self):
+        self.tmp.cleanup()
+
+    def make_server(self, reservations=None):
+        pool = LeasePool(hotspot_network(None, None), reservations=reservations, lease_file=self.lease_file)
+        return DhcpServer("wlan0", pool, dns=["192.0.2.53"])
+
+    def send(self, kind, mac, options=None, server=None, ciaddr="0.0.0.0"):
+        """Round-trip a client message through the wire format"""
+        packet = DhcpPacket(op=BOOTREQUEST, xid=7, mac=mac, ciaddr=ciaddr,
+                            options={OPT_MESSAGE_TYPE: bytes([kind]), **(options or {})})
+        reply = (server or self.server).handle(DhcpPacket.decode(packet.encode()))
+        return DhcpPacket.decode(reply.encode()) if reply else None
+
+    def lease(self, mac, hostname=None, server=None):
+        named = {OPT_HOSTNAME: hostname.encode()} if hostname else {}
+        offer = self.send(DISCOVER, mac, named, server)
+        self.assertEqual(offer.message_type, OFFER)
+        ack = self.send(REQUEST, mac, {**named, OPT_REQUESTED_IP: socket.inet_aton(offer.yiaddr),
+                                       OPT_SERVER_ID: offer.options[OPT_SERVER_ID]}, server)
+        self.assertEqual(ack.message_type, ACK)
+        return ack
+
+    def test_discover_request_ack(self):
+        ack = self.lease(self.PHONE, "pixel")
+        self.assertEqual(ack.yiaddr, "10.42.0.11")
+        self.assertEqual(ack.address_option(OPT_ROUTER), "10.42.0.1")
+        self.assertEqual(self.lease(self.LAPTOP).yiaddr, "10.42.0.12")
+        leases = self.server.pool.active()
+        self.assertEqual([(l.mac, l.ip, l.hostname) for l in leases],
+                         [(self.PHONE, "10.42.0.11", "pixel"), (self.LAPTOP, "10.42.0.12", None)])
+
+    def test_request_for_taken_address_is_refused(self):
+        self.lease(self.PHONE)
+        for taken in ("10.42.0.11", "192.168.1.9"):
+            reply = self.send(REQUEST, self.LAPTOP, {OPT_REQUESTED_IP: socket.inet_aton(taken)})
+            self.assertEqual(reply.message_type, NAK)
+        # It took another server's offer: stay quiet
+        self.assertIsNone(self.send(REQUEST, self.LAPTOP, {OPT_SERVER_ID: socket.inet_aton("10.42.0.2")}))
+
+    def test_reservation_and_persistence(self):
+        server = self.make_server({self.LAPTOP: "10.42.0.5"})
+        self.assertEqual(self.lease(self.LAPTOP, server=server).yiaddr, "10.42.0.5")
+        phone = self.lease(self.PHONE, "pixel", server=server)
+        self.send(RELEASE, self.PHONE, server=server, ciaddr=phone.yiaddr)
+
+        # A restarted server remembers both; the released phone comes back to the same address
+        restarted = self.make_server({self.LAPTOP: "10.42.0.5"})
+        self.assertEqual([l.mac for l in restarted.pool.active()], [self.LAPTOP])
+        self.assertEqual(self.lease(self.PHONE, server=restarted).yiaddr, "10.42.0.11")
+        self.assertEqual(restarted.pool.active()[1].hostname, "pixel")
+
+    def test_dhcp_range_skips_own_address(self):
+        self.assertEqual(dhcp_range(hotspot_network(None, None)), ("10.42.0.11", "10.42.0.254"))
+        self.assertEqual(dhcp_range(hotspot_network("192.168.50.1", "255.255.255.248")),
+                         ("192.168.50.6", "192.168.50.6"))
+        self.assertEqual(dhcp_range(hotspot_network("192.168.50.1", "255.255.255.252")),
+                         ("192.168.50.2", "192.168.50.2"))
+        for netmask in ("255.255.255.254", "255.255.255.255"):
+            with self.assertRaisesRegex(ValueError, "/30 or larger"):
+                dhcp_range(hotspot_network("192.168.50.1", netmask))
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,105 @@
+"""
+Interface detection against a fake /sys/class/net tree
+Names are deliberately misleading: classification must come from sysfs
+"""
+
+import sys
+import json
+import tempfile
+import unittest
+from pathlib import Path
+
+REPO_ROOT = Path(__file__).resolve().parents[2]
+sys.path.insert(0, str(REPO_ROOT / "src" / "alopex-qt"))
+
+from network.discovery import InterfaceType, NetworkDiscovery, NetworkInterface
+
+class InterfaceTypeDetectionTest(unittest.TestCase):
+    def setUp(self):
+        self.tmp = tempfile.TemporaryDirectory()
+        self.root = Path(self.tmp.name)
+        self.net = self.root / "class" / "net"
+        self.net.mkdir(parents=True)
+
+    def tearDown(self):
+        self.tmp.cleanup()
+
+    def link(self, name, arp_type="1", devtype=None, dirs=(), files=(), driver=None):
+        device = self.net / name
+        device.mkdir()
+        (device / "type").write_text(arp_type + "\n")
+        uevent = f"INTERFACE={name}\n" + (f"DEVTYPE={devtype}\n" if devtype else "")
+        (device / "uevent").write_text(uevent)
+        for directory in dirs:
+            (device / directory).mkdir()
+        for file in files:
+            (device / file).write_text("0x1002\n")
+        if driver:
+            driver_dir = self.root / "bus" / "usb" / "drivers" / driver
+            driver_dir.mkdir(parents=True, exist_ok=True)
+            (device / "device").mkdir()
+            (device / "device" / "driver").symlink_to(driver_dir)
+
+    def detect(self, name):
+        return NetworkDiscovery._detect_interface_type(name, self.net)
+
+    def test_renamed_interfaces(self):
+        self.link("uplink", driver="e1000e")
+        self.link("radio", devtype="wlan", dirs=["wireless"], driver="iwlwifi")
+        self.link("eth9", dirs=["phy80211"])
+        self.link("wan", arp_type="519", devtype="wwan", driver="qmi_wwan")
+        self.link("wlan5", devtype="bridge", dirs=["bridge"])
+        self.link("trunk", devtype="bond", dirs=["bonding"])
+        self.link("guests", devtype="vlan")
+        self.link("corp", arp_type="65534", devtype="wireguard")
+        self.link("en0", arp_type="65534", files=["tun_flags"])
+        self.link("phone", driver="ipheth")
+        self.link("loop", arp_type="772")
+
+        self.assertEqual(self.detect("uplink"), (InterfaceType.ETHERNET, None))
+        self.assertEqual(self.detect("radio"), (InterfaceType.WIFI, None))
+        self.assertEqual(self.detect("eth9"), (InterfaceType.WIFI, None))
+        self.assertEqual(self.detect("wan"), (InterfaceType.CELLULAR, None))
+        self.assertEqual(self.detect("wlan5"), (InterfaceType.BRIDGE, None))
+        self.assertEqual(self.detect("trunk"), (InterfaceType.BOND, None))
+        self.assertEqual(self.detect("guests"), (InterfaceType.VLAN, None))
+        self.assertEqual(self.detect("corp"), (InterfaceType.VPN, None))
+        self.assertEqual(self.detect("en0"), (InterfaceType.TUN, "tun"))
+        self.assertEqual(self.detect("phone"), (InterfaceType.ETHERNET, "tethered (ipheth)"))
+        self.assertEqual(self.detect("loop"), (InterfaceType.LOOPBACK, None))
+
+    def test_falls_back_to_name_without_sysfs(self):
+        self.assertEqual(self.detect("wlp2s0"), (InterfaceType.WIFI, None))
+        self.assertEqual(self.detect("enp3s0"), (InterfaceType.ETHERNET, None))
+
+    def test_mac_addresses(self):
+        self.link("uplink", driver="e1000e")
+        (self.net / "uplink" / "address").write_text("00:1B:21:3A:4C:50\n")
+        (self.net / "uplink" / "addr_assign_type").write_text("0\n")
+        self.link("corp", arp_type="65534", devtype="wireguard")
+        (self.net / "corp" / "address").write_text("\n")
+
+        mac = NetworkDiscovery._get_mac("uplink", self.net)
+        self.assertEqual(mac, "00:1b:21:3a:4c:50")
+        self.assertEqual(NetworkDiscovery._get_permanent_mac("uplink", mac, self.net), mac)
+        self.assertIsNone(NetworkDiscovery._get_mac("corp", self.net))
+
+    def test_describe_interface(self):
+        self.link("uplink", driver="e1000e")
+        stats = self.net / "uplink" / "statistics"
+        stats.mkdir()
+        (stats / "rx_bytes").write_text("123456\n")
+        (stats / "tx_errors").write_text("3\n")
+        interface = NetworkInterface(name="uplink", interface_type=InterfaceType.ETHERNET, status="Connected",
+                                     gateway="192.0.2.1", dns=["192.0.2.53"], mac="00:1b:21:3a:4c:50")
+
+        details = NetworkDiscovery.describe_interface(interface, self.net)
+        self.assertEqual(details["type"], "Ethernet")
+        self.assertEqual(details["driver"], "e1000e")
+        self.assertEqual(details["statistics"], {"rx_bytes": 123456, "tx_errors": 3})
+        self.assertEqual(details["dns"], ["192.0.2.53"])
+        # Plain data, ready for the JSON export
+        json.dumps(details)
+
+if __name__ == "__main__":
+    unittest.main()
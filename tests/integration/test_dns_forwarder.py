@@ -0,0 +1,115 @@
+"""
+DNS forwarder - caching, TTL ageing and the blocklist filter against a fake upstream
+"""
+
+import sys
+import socket
+import struct
+import asyncio
+import tempfile
+import unittest
+from pathlib import Path
+
+REPO_ROOT = Path(__file__).resolve().parents[2]
+sys.path.insert(0, str(REPO_ROOT / "src" / "alopex-qt"))
+
+from network.dns_forwarder import DnsForwarder, age_ttls, answer_ttl
+from network.dns_filter import DnsFilter, parse_hosts
+from network.dns_health import build_query
+
+class FakeUpstream(asyncio.DatagramProtocol):
+    """Answers every A query with 192.0.2.10, TTL 300"""
+
+    def __init__(self):
+        self.queries = 0
+
+    def connection_made(self, transport):
+        self.transport = transport
+
+    def datagram_received(self, data, addr):
+        self.queries += 1
+        self.transport.sendto(DnsForwarderTest.answer(data, 300), addr)
+
+class DnsForwarderTest(unittest.TestCase):
+    @staticmethod
+    def answer(query, ttl, address="192.0.2.10"):
+        record = b"\xc0\x0c" + struct.pack("!HHIH", 1, 1, ttl, 4) + socket.inet_aton(address)
+        return query[:2] + struct.pack("!HHHHH", 0x8180, 1, 1, 0, 0) + query[12:] + record
+
+    def test_ttls(self):
+        response = self.answer(build_query("example.com", 1), 300)
+        self.assertEqual(answer_ttl(response), 300)
+        self.assertEqual(answer_ttl(self.answer(build_query("example.com", 1), 86400)), 3600)
+        self.assertEqual(answer_ttl(age_ttls(response, 120)), 180)
+        # NXDOMAIN without records is cached briefly; SERVFAIL not at all
+        nxdomain = bytearray(build_query("nope.example", 2))
+        nxdomain[2:4] = struct.pack("!H", 0x8183)
+        self.assertEqual(answer_ttl(bytes(nxdomain)), 60)
+        nxdomain[3] = 0x82
+        self.assertIsNone(answer_ttl(bytes(nxdomain)))
+
+    def test_forward_and_cache(self):
+        async def scenario():
+            loop = asyncio.get_running_loop()
+            transport, upstream = await loop.create_datagram_endpoint(FakeUpstream, local_addr=("127.0.0.1", 0))
+            port = transport.get_extra_info("sockname")[1]
+            # Our own stub address would loop; the unreachable resolver is skipped over
+            upstreams = ["127.0.0.53", "127.0.0.9", "127.0.0.1"]
+            forwarder = DnsForwarder(lambda: upstreams, upstream_port=port)
+            try:
+                first = await forwarder.resolve(build_query("Example.com", 0x1111))
+                second = await forwarder.resolve(build_query("example.COM", 0x2222))
+                upstreams.clear()
+                failed = await forwarder.resolve(build_query("other.example", 0x3333))
+            finally:
+                transport.close()
+            return forwarder, upstream, first, second, failed
+
+        forwarder, upstream, first, second, failed = asyncio.run(scenario())
+        self.assertEqual(first[:2], b"\x11\x11")
+        self.assertEqual(second[:2], b"\x22\x22")
+        self.assertEqual(first[-4:], socket.inet_aton("192.0.2.10"))
+        self.assertEqual(second[2:], first[2:])
+        self.assertEqual(upstream.queries, 1)
+        self.assertEqual(failed[3] & 0x0F, 2)
+        stats = forwarder.stats()
+        self.assertEqual((stats["queries"], stats["failures"], stats["cache_hits"]), (3, 1, 1))
+        self.assertEqual(stats["last_upstream"], "127.0.0.1")
+
+    def test_blocklists(self):
+        self.assertEqual(parse_hosts("# ads\n127.0.0.1 localhost\n0.0.0.0 Ads.Example.com tracker.example. # x\n"
+                                     "malware.example\n::1 ip6-localhost\n"),
+                         {"ads.example.com", "tracker.example", "malware.example"})
+
+        async def zone_of(client):
+            return {"10.42.0.11": "trusted"}.get(client, "public")
+
+        with tempfile.TemporaryDirectory() as state:
+            blocklist = Path(state) / "ads.hosts"
+            blocklist.write_text("0.0.0.0 ads.example.com\n0.0.0.0 cdn.example.com\n")
+            dns_filter = DnsFilter([str(blocklist), str(Path(state) / "missing.hosts")], Path(state),
+                                   zones={"trusted": False}, allow=["cdn.example.com"], zone_of=zone_of)
+            self.assertEqual(dns_filter.load(), 1)
+            forwarder = DnsForwarder(lambda: [])
+            forwarder.filter = dns_filter
+
+            async def ask(name, client):
+                return await forwarder.resolve(build_query(name, 0x4444), client)
+
+            # Refused without asking upstream (there is none, so anything forwarded gets SERVFAIL)
+            self.assertEqual(asyncio.run(ask("ads.example.com", "127.0.0.1"))[3] & 0x0F, 3)
+            self.assertEqual(asyncio.run(ask("www.ads.example.com", "127.0.0.1"))[3] & 0x0F, 2)
+            self.assertEqual(asyncio.run(ask("cdn.example.com", "127.0.0.1"))[3] & 0x0F, 2)
+            # Filtering is off in the hotspot client's zone
+            self.assertEqual(asyncio.run(ask("ads.example.com", "10.42.0.11"))[3] & 0x0F, 2)
+            stats = dns_filter.stats()
+            self.assertEqual(stats["clients"], [{"client": "127.0.0.1", "blocked": 1, "top": [("ads.example.com", 1)]}])
+            self.assertEqual(stats["lists"][1]["names"], 0)
+            self.assertTrue(stats["lists"][1]["error"])
+
+            dns_filter.set_zone("trusted", True)
+            self.assertEqual(asyncio.run(ask("ads.example.com", "10.42.0.11"))[3] & 0x0F, 3)
+            self.assertEqual(DnsFilter([], Path(state), zones={"trusted": False}).zones, {"trusted": True})
+
+if __name__ == "__main__":
+    unittest.main()
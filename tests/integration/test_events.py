@@ -0,0 +1,54 @@
+"""
+Event log - filter terms, and events persisted to disk and queried back
+"""
+
+import sys
+import tempfile
+import unittest
+from pathlib import Path
+
+REPO_ROOT = Path(__file__).resolve().parents[2]
+sys.path.insert(0, str(REPO_ROOT / "src" / "alopex-qt"))
+
+from network.events import EventBus, EventLog, parse_filter
+
+class EventLogTest(unittest.TestCase):
+    def setUp(self):
+        self.dir = tempfile.TemporaryDirectory()
+        self.bus = EventBus()
+        self.bus.log = EventLog(Path(self.dir.name), retention_days=1)
+
+    def tearDown(self):
+        self.dir.cleanup()
+
+    def test_filter_terms(self):
+        self.assertEqual(parse_filter("Wifi*, interface=wlan0"), (["Wifi*"], {"interface": "wlan0"}))
+        self.assertEqual(parse_filter(None), ([], {}))
+        for bad in ("Wifi*,,", ["=wlan0"]):
+            with self.assertRaises(ValueError):
+                parse_filter(bad)
+
+    def test_persist_and_query(self):
+        self.bus.publish("InterfaceStateChanged", interface="eth0", state="configured")
+        self.bus.publish("JobProgress", job=1, percent=50)
+        self.bus.publish("WifiAssociation", interface="wlan0", change="roamed", signal=-70)
+        started = self.bus.publish("InterfaceStateChanged", interface="wlan0", state="failed")["timestamp"]
+        self.bus.publish("WifiSecurityAlert", interface="wlan0", bssid=object())
+
+        log = EventLog(Path(self.dir.name))
+        self.assertEqual([e["event"] for e in log.query()], ["InterfaceStateChanged", "WifiAssociation",
+                                                              "InterfaceStateChanged", "WifiSecurityAlert"])
+        self.assertEqual([e["event"] for e in log.query(terms="Wifi*,interface=wlan0")],
+                         ["WifiAssociation", "WifiSecurityAlert"])
+        self.assertEqual([e["state"] for e in log.query(terms=["InterfaceState*"], limit=1)], ["failed"])
+        self.assertEqual([e["event"] for e in log.query(since=started)], ["WifiSecurityAlert"])
+        # Values are compared as text
+        self.assertEqual(len(log.query(terms="signal=-70")), 1)
+
+        with open(log.file, "a") as f:
+            f.write('{"event": "Trunc')
+        self.bus.log.prune(started + 86400 + 1)
+        self.assertEqual(log.query(), [])
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,63 @@
+"""
+Exporters - systemd-networkd and netplan files read back by the importers
+"""
+
+import sys
+import tempfile
+import unittest
+from pathlib import Path
+
+REPO_ROOT = Path(__file__).resolve().parents[2]
+sys.path.insert(0, str(REPO_ROOT / "src" / "alopex-qt"))
+
+from network.connection_manager import ConnectionProfile
+from network.exporters import export_netplan, export_networkd
+from network.importers import import_netplan, import_networkd
+from network.cable_test import report
+
+class ExportersTest(unittest.TestCase):
+    """Exported configs read back by the importers give the same profiles"""
+
+    PROFILES = [
+        ConnectionProfile("Office wired", "eth0", "ethernet", "static", ip_address="192.0.2.10",
+                          netmask="255.255.255.0", gateway="192.0.2.1", dns_servers=["9.9.9.9"], priority=5),
+        ConnectionProfile("Home wired", "eth0", "ethernet", "dhcp"),
+        ConnectionProfile("Lab VLAN", "eth0.20", "ethernet", "dhcp",
+                          netdev={"kind": "vlan", "parent": "eth0", "id": 20}),
+        ConnectionProfile("Cabin", "wlan0", "wifi", "dhcp", ssid="Cabin \"2\"", password="log-fire", security="WPA2"),
+        ConnectionProfile("Office VPN", "office", "vpn", "manual")
+    ]
+
+    def setUp(self):
+        self.tmp = tempfile.TemporaryDirectory()
+        self.path = Path(self.tmp.name)
+
+    def tearDown(self):
+        self.tmp.cleanup()
+
+    def test_networkd_round_trip(self):
+        report = export_networkd(self.PROFILES)
+        self.assertEqual(sorted(entry["name"] for entry in report.skipped), ["Home wired", "Office VPN"])
+        self.assertIn("[VLAN]\nId=20", report.files["10-alopex-lab-vlan.netdev"])
+        self.assertIn("VLAN=eth0.20", report.files["10-alopex-office-wired.network"])
+        for name, content in report.files.items():
+            (self.path / name).write_text(content)
+        imported = {profile.interface: profile for profile in import_networkd(self.path, "wlan0").profiles}
+        self.assertEqual(sorted(imported), ["eth0", "eth0.20"])
+        self.assertEqual((imported["eth0"].ip_address, imported["eth0"].netmask, imported["eth0"].gateway),
+                         ("192.0.2.10", "255.255.255.0", "192.0.2.1"))
+        self.assertEqual(imported["eth0.20"].netdev, {"kind": "vlan", "id": 20, "parent": "eth0"})
+
+    def test_netplan_round_trip_and_secrets(self):
+        self.assertNotIn("log-fire", export_netplan(self.PROFILES).files["90-alopex.yaml"])
+        report = export_netplan(self.PROFILES, secrets=True)
+        self.assertEqual(report.secret, ["90-alopex.yaml"])
+        (self.path / "90-alopex.yaml").write_text(report.files["90-alopex.yaml"])
+        imported = {profile.name: profile for profile in import_netplan(self.path).profiles}
+        self.assertEqual(sorted(imported), ["Cabin \"2\"", "eth0", "eth0.20"])
+        self.assertEqual(imported["Cabin \"2\""].password, "log-fire")
+        self.assertEqual((imported["eth0"].method, imported["eth0"].gateway), ("static", "192.0.2.1"))
+        self.assertEqual(imported["eth0.20"].netdev, {"kind": "vlan", "parent": "eth0", "id": 20})
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,95 @@
+"""
+Failover - immediate, stable and never failback, and the controller moving routes
+"""
+
+import sys
+import asyncio
+import unittest
+from pathlib import Path
+
+REPO_ROOT = Path(__file__).resolve().parents[2]
+sys.path.insert(0, str(REPO_ROOT / "src" / "alopex-qt"))
+
+from network.failover import FailoverController, FailoverGroup, GroupState, decide
+from network.events import EventBus
+
+class FailoverTest(unittest.TestCase):
+    def state(self, **config):
+        return GroupState(FailoverGroup.from_config(dict({"name": "uplink", "primary": "eth0", "backup": "wwan0"},
+                                                         **config)))
+
+    def test_stable_failback(self):
+        state = self.state(stable_minutes=5)
+        self.assertIsNone(decide(state, True, True, 0))
+        self.assertEqual(decide(state, False, False, 0)[0], "no_backup")
+        self.assertEqual(decide(state, False, True, 0)[0], "failover")
+        state.active = "backup"
+
+        self.assertEqual(decide(state, True, True, 100)[0], "failback_pending")
+        self.assertIsNone(decide(state, True, True, 200))
+        # A flap starts the clock over
+        self.assertEqual(decide(state, False, True, 250)[0], "failback_cancelled")
+        self.assertIsNone(decide(state, False, True, 260))
+        self.assertEqual(decide(state, True, True, 300)[0], "failback_pending")
+        self.assertIsNone(decide(state, True, True, 599))
+        self.assertEqual(decide(state, True, True, 600), ("failback", "eth0 stable for 5 minutes"))
+
+    def test_immediate_and_never(self):
+        state = self.state(failback="immediate")
+        state.active = "backup"
+        self.assertEqual(decide(state, True, True, 0)[0], "failback")
+
+        state = self.state(failback="never")
+        state.active = "backup"
+        self.assertEqual(decide(state, True, True, 0)[0], "failback_held")
+        self.assertIsNone(decide(state, True, True, 10 ** 6))
+        # Except when the backup is gone
+        self.assertEqual(decide(state, True, False, 10 ** 6), ("failback", "wwan0 went down"))
+
+    def test_config(self):
+        with self.assertRaises(ValueError):
+            FailoverGroup.from_config({"name": "uplink", "primary": "eth0", "backup": "eth0"})
+        with self.assertRaises(ValueError):
+            FailoverGroup.from_config({"name": "uplink", "primary": "eth0", "backup": "wwan0", "failback": "soon"})
+        with self.assertRaises(ValueError):
+            FailoverGroup.from_config({"name": "uplink", "primary": "eth0", "backup": "wwan0", "weight": 2})
+
+    def test_controller_moves_routes(self):
+        links = {"eth0": "192.0.2.1", "wwan0": "100.64.0.1"}
+        probes = {"wan": "ok"}
+        changes = []
+        announced = []
+        events = EventBus()
+
+        async def apply(change):
+            changes.append((change["action"], change["interface"], change["route"]))
+            return None
+
+        async def announce(interface):
+            announced.append(interface)
+
+        controller = FailoverController(
+            [{"name": "uplink", "primary": "eth0", "backup": "wwan0", "probe": "wan", "failback": "immediate"},
+             {"name": "broken", "primary": "eth0", "backup": "eth0"}],
+            lambda: dict(links), probes.get, apply, events, announce=announce)
+        self.assertEqual(list(controller.groups), ["uplink"])
+
+        async def scenario():
+            await controller.check(now=0)
+            probes["wan"] = "breach"
+            await controller.check(now=1)
+            await controller.check(now=2)
+            probes["wan"] = "ok"
+            await controller.check(now=3)
+            await controller.stop()
+
+        asyncio.run(scenario())
+        route = {"to": "default", "via": "100.64.0.1", "metric": 10}
+        self.assertEqual(changes, [("add", "wwan0", route), ("remove", "wwan0", route)])
+        self.assertEqual(announced, ["wwan0", "eth0"])
+        self.assertEqual([(e["decision"], e["interface"]) for e in events.recent()],
+                         [("failover", "wwan0"), ("failback", "eth0")])
+        self.assertEqual(controller.list()[0]["active"], "primary")
+
+if __name__ == "__main__":
+    unittest.main()
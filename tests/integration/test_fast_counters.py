@@ -0,0 +1,121 @@
+"""
+Fast counters - eBPF per-CPU map walks, capability detection and rates from samples
+"""
+
+import sys
+import ctypes
+import asyncio
+import tempfile
+import unittest
+from pathlib import Path
+
+REPO_ROOT = Path(__file__).resolve().parents[2]
+sys.path.insert(0, str(REPO_ROOT / "src" / "alopex-qt"))
+
+from network import fast_counters
+
+class FakeCounters:
+    """Counters backend whose readings the test sets directly"""
+
+    backend_name = "fake"
+
+    def __init__(self, reason=None):
+        self.reason = reason
+        self.attached = []
+        self.values = {}
+
+    def unavailable(self, object_path):
+        return self.reason
+
+    async def start(self, object_path):
+        return True, "Loaded"
+
+    async def attach(self, interface):
+        self.attached.append(interface)
+        return True, "Attached"
+
+    async def detach(self, interface):
+        self.attached.remove(interface)
+        return True
+
+    def read(self):
+        return {name: self.values.get(name, {}) for name in self.attached}
+
+    async def stop(self):
+        self.attached = []
+
+class FastCountersTest(unittest.TestCase):
+    def test_percpu_map_walk(self):
+        attr = fast_counters.MAP_CREATE_ATTR.pack(fast_counters.BPF_MAP_TYPE_PERCPU_HASH, fast_counters.KEY.size,
+                                                  fast_counters.VALUE.size, 16, 0)
+        try:
+            fd = fast_counters.bpf(fast_counters.BPF_MAP_CREATE, attr)
+        except OSError as e:
+            self.skipTest(f"cannot create BPF maps here: {e}")
+        bpf_map = fast_counters.BpfMap(fd, fast_counters.KEY.size, fast_counters.VALUE.size)
+        try:
+            # Per-CPU updates take a value for every possible CPU
+            for key, value in (((3, 0, 0), (2, 3000)), ((3, 1, 1), (1, 80)), ((7, 0, 3), (5, 500))):
+                key_buffer = ctypes.create_string_buffer(fast_counters.KEY.pack(*key, 0))
+                value_buffer = ctypes.create_string_buffer(fast_counters.VALUE.pack(*value) * bpf_map.cpus)
+                fast_counters.bpf(fast_counters.BPF_MAP_UPDATE_ELEM, fast_counters.ELEM_ATTR.pack(
+                    fd, 0, ctypes.addressof(key_buffer), ctypes.addressof(value_buffer), 0))
+            counters = fast_counters.read_counters(bpf_map)
+        finally:
+            bpf_map.close()
+        cpus = bpf_map.cpus
+        self.assertEqual(counters, {
+            3: {"rx": {"tcp": (2 * cpus, 3000 * cpus)}, "tx": {"udp": (cpus, 80 * cpus)}},
+            7: {"rx": {"other": (5 * cpus, 500 * cpus)}}
+        })
+
+    def test_capability_detection(self):
+        with tempfile.TemporaryDirectory() as tmp:
+            root = Path(tmp)
+            obj = root / "traffic_counters.bpf.o"
+            mounts, status = root / "mounts", root / "status"
+            mounts.write_text("sysfs /sys sysfs rw 0 0\n")
+            status.write_text("Name:\talopexd\nCapEff:\t0000000000000000\n")
+            self.assertIn("WITH_EBPF=1", fast_counters.unavailable_reason(obj, mounts, status))
+            obj.write_bytes(b"\x7fELF")
+            self.assertIn("CAP_BPF", fast_counters.unavailable_reason(obj, mounts, status))
+            status.write_text(f"CapEff:\t{1 << fast_counters.CAP_BPF:016x}\n")
+            self.assertIn("CAP_NET_ADMIN", fast_counters.unavailable_reason(obj, mounts, status))
+            admin = (1 << fast_counters.CAP_SYS_ADMIN) | (1 << fast_counters.CAP_NET_ADMIN)
+            status.write_text(f"CapEff:\t{admin:016x}\n")
+            self.assertIn("no bpf filesystem", fast_counters.unavailable_reason(obj, mounts, status))
+            mounts.write_text("bpf /sys/fs/bpf bpf rw,nosuid 0 0\n")
+            self.assertIsNone(fast_counters.unavailable_reason(obj, mounts, status))
+
+    def test_rates_from_samples(self):
+        backend = FakeCounters()
+        interfaces = ["eth0", "wg0"]
+        counters = fast_counters.FastCounters(backend, lambda: interfaces)
+        self.assertTrue(asyncio.run(counters.start()))
+        asyncio.run(counters.sync_interfaces())
+        backend.values["eth0"] = {"rx": {"tcp": (10, 10000)}, "tx": {"udp": (1, 100)}}
+        counters.sample(backend.read(), 10.0)
+        backend.values["eth0"] = {"rx": {"tcp": (30, 30000), "icmp": (1, 84)}, "tx": {"udp": (2, 200)}}
+        counters.sample(backend.read(), 10.1)
+        eth0 = counters.snapshot("eth0")["interfaces"]["eth0"]
+        self.assertAlmostEqual(eth0["rx"]["protocols"]["tcp"]["bytes_per_s"], 200000, delta=1)
+        self.assertAlmostEqual(eth0["rx"]["bytes_per_s"], 200840, delta=1)
+        self.assertAlmostEqual(eth0["tx"]["packets_per_s"], 10, delta=0.1)
+        self.assertEqual(len(eth0["series"]), 1)
+        self.assertEqual(counters.snapshot()["interfaces"]["wg0"]["rx"]["bytes_per_s"], 0)
+        self.assertNotIn("series", counters.snapshot()["interfaces"]["eth0"])
+
+        # wg0 goes away, so it is detached and forgotten
+        interfaces.remove("wg0")
+        asyncio.run(counters.sync_interfaces())
+        self.assertEqual((backend.attached, list(counters.rates)), (["eth0"], ["eth0"]))
+
+    def test_unavailable_stays_off(self):
+        counters = fast_counters.FastCounters(FakeCounters("CAP_BPF (or CAP_SYS_ADMIN) is needed"), list)
+        with self.assertLogs("network.fast_counters", "WARNING"):
+            asyncio.run(counters.run())
+        snapshot = counters.snapshot()
+        self.assertEqual((snapshot["active"], snapshot["reason"]), (False, "CAP_BPF (or CAP_SYS_ADMIN) is needed"))
+
+if __name__ == "__main__":
+    unittest.main()
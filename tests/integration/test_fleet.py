@@ -0,0 +1,133 @@
+"""
+Fleet - fleet files, remote addresses and the IPC server over mutual TLS
+The TLS case needs the openssl CLI
+"""
+
+import sys
+import json
+import dataclasses
+import socket
+import asyncio
+import shutil
+import tempfile
+import subprocess
+import unittest
+from pathlib import Path
+
+REPO_ROOT = Path(__file__).resolve().parents[2]
+sys.path.insert(0, str(REPO_ROOT / "src" / "alopex-qt"))
+
+from network.discovery import InterfaceType, NetworkInterface, NetworkMetrics
+from network.fleet import find_host, load_fleet, parse_fleet
+from network.ipc import ErrorCode, IpcClient, IpcError, IpcServer, parse_remote, server_tls_context
+
+def make_certificates(directory: Path):
+    """A CA and server/client certificates it signed, made with the openssl CLI"""
+    def openssl(*args):
+        subprocess.run(["openssl", *args], cwd=directory, check=True, capture_output=True)
+
+    openssl("req", "-x509", "-newkey", "rsa:2048", "-nodes", "-days", "1", "-subj", "/CN=Fleet CA",
+            "-keyout", "ca.key", "-out", "ca.pem")
+    for name in ("localhost", "laptop", "intruder"):
+        openssl("req", "-newkey", "rsa:2048", "-nodes", "-subj", f"/CN={name}",
+                "-keyout", f"{name}.key", "-out", f"{name}.csr")
+        (directory / f"{name}.ext").write_text(f"subjectAltName=DNS:{name},IP:127.0.0.1\n")
+        openssl("x509", "-req", "-in", f"{name}.csr", "-CA", "ca.pem", "-CAkey", "ca.key", "-CAcreateserial",
+                "-days", "1", "-extfile", f"{name}.ext", "-out", f"{name}.pem")
+
+class FleetTest(unittest.TestCase):
+    def test_parse_remote(self):
+        self.assertEqual(parse_remote("tls://gw.lan").address, "tls://gw.lan:7443")
+        remote = parse_remote("[fd00::1]:9000", cert_file="me.pem")
+        self.assertEqual((remote.host, remote.port, remote.cert_file), ("fd00::1", 9000, "me.pem"))
+        self.assertEqual(remote.address, "tls://[fd00::1]:9000")
+        for bad in ("http://gw.lan", "tls://gw.lan:port", "tls://gw.lan/path", ""):
+            with self.assertRaises(ValueError):
+                parse_remote(bad)
+
+    def test_parse_fleet(self):
+        hosts = parse_fleet({"ca_file": "ca.pem", "cert_file": "me.pem", "hosts": [
+            {"name": "gw", "address": "tls://10.0.0.1"},
+            {"address": "nas.lan:7444", "cert_file": "nas-client.pem"}
+        ]})
+        self.assertEqual([host.name for host in hosts], [socket.gethostname(), "gw", "nas.lan:7444"])
+        self.assertTrue(hosts[0].local)
+        self.assertEqual((hosts[1].remote.ca_file, hosts[1].remote.cert_file), ("ca.pem", "me.pem"))
+        self.assertEqual(hosts[2].remote.cert_file, "nas-client.pem")
+        self.assertIs(find_host(hosts, "local"), hosts[0])
+        self.assertIs(find_host(hosts, "gw"), hosts[1])
+        self.assertIsNone(find_host(hosts, "router"))
+        client = hosts[1].client(timeout=2.0)
+        self.assertEqual((client.address, client.timeout), ("tls://10.0.0.1:7443", 2.0))
+
+        for config in ({"hosts": [{"name": "gw"}]},
+                       {"hosts": [{"name": "gw", "address": "tls://10.0.0.1"}]},
+                       {"cert_file": "me.pem", "hosts": [{"name": "local", "address": "tls://10.0.0.1"}]},
+                       {"cert_file": "me.pem", "hosts": [{"name": "gw", "address": "tls://10.0.0.1"},
+                                                         {"name": "gw", "address": "tls://10.0.0.2"}]}):
+            with self.assertRaises(ValueError):
+                parse_fleet(config)
+
+        with tempfile.TemporaryDirectory() as directory:
+            path = Path(directory) / "fleet.json"
+            self.assertEqual(len(load_fleet(path)), 1)
+            path.write_text("[]")
+            with self.assertRaises(ValueError):
+                load_fleet(path)
+
+    def test_interface_from_dict(self):
+        original = NetworkInterface(name="eth0", interface_type=InterfaceType.ETHERNET, status="Connected",
+                                    ip="10.0.0.2", metrics=NetworkMetrics(bytes_rx=42, link_speed=1000))
+        entry = json.loads(json.dumps(dataclasses.asdict(original)))
+        entry.update(connection=None, future_field=1)
+        entry["metrics"]["future_metric"] = 2
+        self.assertEqual(NetworkInterface.from_dict(entry), original)
+        entry["interface_type"] = "Unknown"
+        self.assertEqual(NetworkInterface.from_dict(entry).interface_type, InterfaceType.OTHER)
+
+    @unittest.skipUnless(shutil.which("openssl"), "needs the openssl CLI")
+    def test_mutual_tls(self):
+        with tempfile.TemporaryDirectory() as directory:
+            directory = Path(directory)
+            make_certificates(directory)
+
+            def client(name):
+                remote = parse_remote(f"tls://localhost:{port}", ca_file=str(directory / "ca.pem"),
+                                      cert_file=str(directory / f"{name}.pem"), key_file=str(directory / f"{name}.key"))
+                return IpcClient(timeout=5.0, remote=remote)
+
+            async def scenario():
+                records = []
+                server = IpcServer(directory / "alopex.sock", audit=records.append)
+                server.register("GetStatus", lambda: {"hostname": "gw"})
+                server.register("Disconnect", lambda interface: None)
+                context = server_tls_context(str(directory / "ca.pem"), str(directory / "localhost.pem"),
+                                             str(directory / "localhost.key"))
+                await server.start_remote("127.0.0.1", 0, context, clients=["laptop"])
+                nonlocal port
+                port = server.remote_server.sockets[0].getsockname()[1]
+                loop = asyncio.get_running_loop()
+                laptop = client("laptop")
+                results = [await loop.run_in_executor(None, laptop.request, "GetStatus")]
+                await loop.run_in_executor(None, lambda: laptop.request("Disconnect", interface="eth0"))
+                for name in ("intruder", None):
+                    try:
+                        other = client(name) if name else IpcClient(timeout=5.0, remote=parse_remote(
+                            f"tls://localhost:{port}", ca_file=str(directory / "ca.pem")))
+                        await loop.run_in_executor(None, other.request, "GetStatus")
+                    except IpcError as e:
+                        results.append(e.code)
+                await server.stop()
+                return results, records
+
+            port = 0
+            results, records = asyncio.run(scenario())
+        self.assertEqual(results[0], {"hostname": "gw"})
+        # Signed by our CA but not on the allow-list; no certificate at all fails the handshake
+        self.assertEqual(results[1:], [ErrorCode.PERMISSION_DENIED, ErrorCode.DAEMON_UNAVAILABLE])
+        self.assertEqual(len(records), 1)
+        self.assertEqual((records[0]["request"], records[0]["client_uid"], records[0]["client_cert"]),
+                         ("Disconnect", None, "laptop"))
+
+if __name__ == "__main__":
+    unittest.main()
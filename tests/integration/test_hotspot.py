@@ -0,0 +1,76 @@
+"""
+Hotspot - hostapd config, station dumps and per-client rate limits
+"""
+
+import sys
+import unittest
+from pathlib import Path
+
+REPO_ROOT = Path(__file__).resolve().parents[2]
+sys.path.insert(0, str(REPO_ROOT / "src" / "alopex-qt"))
+
+from network.hotspot import admitted, format_limit, hostapd_config, limit_commands, parse_limit, parse_station_dump
+
+class HotspotStationTest(unittest.TestCase):
+    STATION_DUMP = """Station 3C:22:FB:10:20:30 (on wlan0)
+	inactive time:	310 ms
+	rx bytes:	184220
+	rx packets:	1023
+	tx bytes:	2241190
+	signal:  	-48 [-50, -51] dBm
+	connected time:	95 seconds
+Station 9a:8b:7c:00:11:22 (on wlan0)
+	rx bytes:	512
+	tx bytes:	128
+	signal:  	-77 dBm
+"""
+
+    def test_parse_station_dump(self):
+        phone, laptop = parse_station_dump(self.STATION_DUMP)
+        self.assertEqual((phone["mac"], phone["rx_bytes"], phone["tx_bytes"], phone["signal"]),
+                         ("3c:22:fb:10:20:30", 184220, 2241190, -48))
+        self.assertEqual((phone["connected_time"], phone["inactive_ms"]), (95, 310))
+        self.assertEqual((laptop["signal"], laptop["connected_time"]), (-77, None))
+
+    def test_client_limits(self):
+        self.assertEqual(parse_limit("2000/500"), (2000, 500))
+        self.assertEqual(parse_limit("-/500"), (None, 500))
+        self.assertEqual(parse_limit(" "), (None, None))
+        for text in ("fast", "0/500", "2000/20000000"):
+            with self.assertRaises(ValueError):
+                parse_limit(text)
+        self.assertEqual(format_limit({"down": 2000, "up": None}), "2000/-")
+        self.assertIsNone(format_limit({"down": None, "up": None}))
+
+        commands = limit_commands("wlan0", {"3c:22:fb:10:20:30": {"down": 2000, "up": 500},
+                                            "9a:8b:7c:00:11:22": {"down": None, "up": 1000}})
+        downloads = [c for c in commands if "dst_mac" in c]
+        self.assertEqual(len(downloads), 1)
+        self.assertEqual(downloads[0][downloads[0].index("dst_mac") + 1:], ["3c:22:fb:10:20:30", "classid", "1:10"])
+        self.assertIn(["tc", "class", "add", "dev", "wlan0", "parent", "1:", "classid", "1:10",
+                       "htb", "rate", "2000kbit", "ceil", "2000kbit"], commands)
+        uploads = [c for c in commands if "src_mac" in c]
+        self.assertEqual([c[c.index("src_mac") + 1] for c in uploads], ["3c:22:fb:10:20:30", "9a:8b:7c:00:11:22"])
+        self.assertEqual(limit_commands("wlan0", {"3c:22:fb:10:20:30": {"down": None, "up": None}}), [])
+
+    def test_mac_filter(self):
+        phone, laptop = "3c:22:fb:10:20:30", "9a:8b:7c:00:11:22"
+        self.assertTrue(admitted(laptop, None, [phone]))
+        self.assertFalse(admitted(phone, None, [phone]))
+        self.assertFalse(admitted(laptop, [phone], []))
+        # A block wins over the allow list
+        self.assertFalse(admitted(phone, [phone], [phone]))
+
+        deny = hostapd_config("wlan0", "Cabin", None, 6, Path("/run/wlan0.deny")).splitlines()
+        self.assertIn("macaddr_acl=0", deny)
+        self.assertIn("ap_isolate=0", deny)
+        self.assertFalse(any(line.startswith("accept_mac_file") for line in deny))
+        allow = hostapd_config("wlan0", "Cabin", None, 6, Path("/run/wlan0.deny"), Path("/run/wlan0.accept"))
+        self.assertIn("macaddr_acl=1", allow.splitlines())
+        self.assertIn("accept_mac_file=/run/wlan0.accept", allow.splitlines())
+        self.assertIn("deny_mac_file=/run/wlan0.deny", allow.splitlines())
+        isolated = hostapd_config("wlan0", "Cabin", None, 6, Path("/run/wlan0.deny"), isolate=True)
+        self.assertIn("ap_isolate=1", isolated.splitlines())
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,97 @@
+"""
+InfluxDB sink - line protocol, rates between samples and pushes over UDP and HTTP
+"""
+
+import sys
+import socket
+import asyncio
+import threading
+import unittest
+from pathlib import Path
+
+REPO_ROOT = Path(__file__).resolve().parents[2]
+sys.path.insert(0, str(REPO_ROOT / "src" / "alopex-qt"))
+
+from network.discovery import InterfaceType, NetworkInterface, NetworkMetrics
+from network.influx import InfluxSink, batch_lines, format_line
+
+class InfluxSinkTest(unittest.TestCase):
+    @staticmethod
+    def interface(name, rx, tx, status="Connected"):
+        return NetworkInterface(name=name, interface_type=InterfaceType.ETHERNET, status=status,
+                                metrics=NetworkMetrics(bytes_rx=rx, bytes_tx=tx, packets_rx=rx // 100))
+
+    def test_line_protocol(self):
+        line = format_line("alopex_interface", {"host": "my laptop", "site": "a,b", "empty": ""},
+                           {"rx_bytes": 5, "rate": 1.5, "up": True, "note": 'say "hi"'}, 1_700_000_000_000_000_000)
+        self.assertEqual(line, 'alopex_interface,host=my\\ laptop,site=a\\,b rx_bytes=5i,rate=1.5,up=true,'
+                               'note="say \\"hi\\"" 1700000000000000000')
+        batches = batch_lines(["x" * 600] * 5, 1400)
+        self.assertEqual([len(b) for b in batches], [1202, 1202, 601])
+
+    def test_rates(self):
+        sink = InfluxSink("udp://127.0.0.1:8094", list, tags={"site": "hq"})
+        sink.host = "test"
+        first = sink.lines([self.interface("eth0", 1000, 500)], now=100.0)
+        self.assertNotIn("rx_rate", first[0])
+        self.assertIn(",interface=eth0,site=hq,type=Ethernet ", first[0])
+        second, = sink.lines([self.interface("eth0", 3000, 1500)], now=110.0)
+        self.assertIn("rx_rate=200.0,tx_rate=100.0,up=true 110000000000", second)
+        with self.assertRaises(ValueError):
+            InfluxSink("udp://127.0.0.1", list)
+        with self.assertRaises(ValueError):
+            InfluxSink("ftp://example.com/write", list)
+
+    def test_push_udp(self):
+        with socket.socket(socket.AF_INET, socket.SOCK_DGRAM) as receiver:
+            receiver.bind(("127.0.0.1", 0))
+            receiver.settimeout(2)
+            port = receiver.getsockname()[1]
+            sink = InfluxSink(f"udp://127.0.0.1:{port}", lambda: [self.interface("eth0", 1, 2)])
+            self.assertTrue(asyncio.run(sink.push()))
+            payload = receiver.recv(2048).decode()
+        self.assertTrue(payload.startswith("alopex_interface,host="))
+        self.assertIn("rx_bytes=1i,tx_bytes=2i", payload)
+        self.assertEqual(sink.stats()["lines_sent"], 1)
+
+    def test_push_http(self):
+        from http.server import BaseHTTPRequestHandler, HTTPServer
+        received = []
+
+        class Handler(BaseHTTPRequestHandler):
+            def do_POST(self):
+                body = self.rfile.read(int(self.headers["Content-Length"])).decode()
+                received.append((self.path, self.headers.get("Authorization"), body))
+                # Like InfluxDB: 204 when written, 401 for a bad token
+                self.send_response(204 if self.headers.get("Authorization") == "Token secret" else 401)
+                self.end_headers()
+
+            def log_message(self, *args):
+                pass
+
+        server = HTTPServer(("127.0.0.1", 0), Handler)
+        thread = threading.Thread(target=server.serve_forever, daemon=True)
+        thread.start()
+        try:
+            url = f"http://user:pw@127.0.0.1:{server.server_port}/api/v2/write?org=home&bucket=alopex"
+            interfaces = [self.interface("eth0", 1, 2), self.interface("wlan0", 0, 0, status="Disconnected")]
+            sink = InfluxSink(url, lambda: interfaces, token="secret")
+            self.assertTrue(asyncio.run(sink.push()))
+            path, auth, body = received[0]
+            self.assertEqual(path, "/api/v2/write?org=home&bucket=alopex")
+            self.assertEqual(len(body.splitlines()), 2)
+            self.assertIn("up=false", body.splitlines()[1])
+
+            sink = InfluxSink(url, lambda: interfaces)
+            self.assertFalse(asyncio.run(sink.push()))
+            self.assertEqual(received[-1][1], "Basic dXNlcjpwdw==")
+            stats = sink.stats()
+            self.assertEqual((stats["failures"], stats["last_error"]), (1, "HTTP 401"))
+            # Credentials in the URL stay out of the stats
+            self.assertNotIn("pw", stats["url"])
+        finally:
+            server.shutdown()
+            server.server_close()
+
+if __name__ == "__main__":
+    unittest.main()
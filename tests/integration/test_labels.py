@@ -0,0 +1,37 @@
+"""
+Interface labels - names and groups stored per interface, resolved case-insensitively
+"""
+
+import sys
+import tempfile
+import unittest
+from pathlib import Path
+
+REPO_ROOT = Path(__file__).resolve().parents[2]
+sys.path.insert(0, str(REPO_ROOT / "src" / "alopex-qt"))
+
+from network.labels import InterfaceLabels, LabelError
+
+class InterfaceLabelsTest(unittest.TestCase):
+    def test_labels_and_groups(self):
+        with tempfile.TemporaryDirectory() as tmp:
+            labels = InterfaceLabels(Path(tmp))
+            labels.set("enp3s0", "WAN", ["Uplinks"], known=["enp3s0", "enp4s0"])
+            labels.set("enp4s0", "Lab 1", ["uplinks", "LAB", "lab"])
+            self.assertEqual(labels.groups("enp4s0"), ["Uplinks", "LAB"])
+            self.assertEqual(labels.members("UPLINKS"), ["enp3s0", "enp4s0"])
+            self.assertEqual((labels.resolve("wan"), labels.resolve("enp4s0"), labels.resolve("eth9")),
+                             ("enp3s0", "enp4s0", "eth9"))
+
+            for label, known in (("wan", []), ("enp4s0", ["enp4s0"]), ("-rf", []), ("x" * 33, [])):
+                with self.assertRaises(LabelError):
+                    labels.set("wlan0", label, known=known)
+
+            # Stored across restarts; no label and no groups forgets the interface
+            reloaded = InterfaceLabels(Path(tmp))
+            self.assertEqual(reloaded.entry("enp3s0"), {"interface": "enp3s0", "label": "WAN", "groups": ["Uplinks"]})
+            reloaded.set("enp3s0", "", [])
+            self.assertEqual([entry["interface"] for entry in InterfaceLabels(Path(tmp)).list()], ["enp4s0"])
+
+if __name__ == "__main__":
+    unittest.main()
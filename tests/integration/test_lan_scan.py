@@ -0,0 +1,57 @@
+"""
+LAN scan - sweep targets and the mDNS and NetBIOS replies that name devices
+"""
+
+import sys
+import ipaddress
+import struct
+import unittest
+from pathlib import Path
+
+REPO_ROOT = Path(__file__).resolve().parents[2]
+sys.path.insert(0, str(REPO_ROOT / "src" / "alopex-qt"))
+
+from network.lan_scan import annotate, mdns_query, netbios_query, parse_mdns_reply, parse_netbios_reply, sweep_targets
+
+class LanScanTest(unittest.TestCase):
+    def test_sweep_targets(self):
+        targets = sweep_targets(ipaddress.IPv4Interface("192.168.1.50/24"))
+        self.assertEqual(len(targets), 253)
+        self.assertNotIn("192.168.1.50", targets)
+        self.assertEqual(targets[0], "192.168.1.1")
+        # A /16 is too much to sweep; only our /24 is
+        targets = sweep_targets(ipaddress.IPv4Interface("10.20.7.9/16"))
+        self.assertEqual((targets[0], targets[-1]), ("10.20.7.1", "10.20.7.254"))
+
+    def test_mdns_reply(self):
+        query = mdns_query("192.168.1.20", 0)
+        self.assertIn(b"\x0220\x011\x03168\x03192\x07in-addr\x04arpa\x00", query)
+        # The answer repeats the question's name through a compression pointer
+        reply = (struct.pack("!HHHHHH", 0, 0x8400, 1, 1, 0, 0) + query[12:]
+                 + b"\xc0\x0c" + struct.pack("!HHIH", 12, 1, 120, 12) + b"\x03nas\x05local\x00")
+        self.assertEqual(parse_mdns_reply(reply), "nas")
+        self.assertIsNone(parse_mdns_reply(reply[:40]))
+
+    def test_netbios_reply(self):
+        query = netbios_query("192.168.1.20", 7)
+        self.assertEqual(query[13:15], b"CK")  # "*" first-level encoded
+
+        def entry(name, suffix, flags):
+            return name.ljust(15).encode() + bytes([suffix]) + struct.pack("!H", flags)
+
+        names = entry("WORKGROUP", 0x00, 0x8400) + entry("FILESERVER", 0x20, 0x0400) + entry("FILESERVER", 0x00, 0x0400)
+        reply = (struct.pack("!HHHHHH", 7, 0x8400, 0, 1, 0, 0) + query[12:-4]
+                 + struct.pack("!HHIH", 0x21, 1, 0, 1 + len(names)) + bytes([3]) + names)
+        self.assertEqual(parse_netbios_reply(reply), "FILESERVER")
+
+    def test_annotate(self):
+        address = ipaddress.IPv4Interface("192.168.1.50/24")
+        devices = annotate([{"ip": "192.168.1.9", "mac": "3e:22:fb:12:34:56", "hostname": None, "name_source": None},
+                            {"ip": "192.168.1.1", "mac": "52:54:00:00:01:01", "hostname": None, "name_source": None}],
+                           address, "00:1b:21:3a:4c:50", "192.168.1.1")
+        self.assertEqual([(d["ip"], d["role"]) for d in devices],
+                         [("192.168.1.1", "gateway"), ("192.168.1.9", None), ("192.168.1.50", "self")])
+        self.assertTrue(devices[1]["randomized_mac"])
+
+if __name__ == "__main__":
+    unittest.main()
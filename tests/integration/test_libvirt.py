@@ -0,0 +1,78 @@
+"""
+Libvirt - virbr bridges matched to networks, leases and guest interfaces
+"""
+
+import sys
+import asyncio
+import unittest
+from pathlib import Path
+
+REPO_ROOT = Path(__file__).resolve().parents[2]
+sys.path.insert(0, str(REPO_ROOT / "src" / "alopex-qt"))
+
+from network.discovery import InterfaceType, NetworkInterface
+from network import libvirt
+
+NET_XML = """<network>
+  <name>default</name>
+  <uuid>3e3fce45-4f53-4fa7-bb32-11f34168b82b</uuid>
+  <forward mode='nat'/>
+  <bridge name='virbr0' stp='on' delay='0'/>
+  <ip address='192.168.122.1' netmask='255.255.255.0'>
+    <dhcp><range start='192.168.122.2' end='192.168.122.254'/></dhcp>
+  </ip>
+  <ip family='ipv6' address='fd00:122::1' prefix='64'/>
+</network>"""
+
+LEASES = """ Expiry Time           MAC address         Protocol   IP address           Hostname     Client ID or DUID
+-------------------------------------------------------------------------------------------------------------
+ 2026-10-17 14:02:11   52:54:00:3b:7c:11   ipv4       192.168.122.41/24    fedora-dev   01:52:54:00:3b:7c:11
+ 2026-10-17 14:05:40   52:54:00:aa:00:02   ipv4       192.168.122.77/24    -            01:52:54:00:aa:00:02
+"""
+
+DOMIFLIST = """ Interface   Type      Source    Model    MAC
+-------------------------------------------------------------
+ vnet0       network   default   virtio   52:54:00:3b:7c:11
+ vnet1       bridge    br0       virtio   52:54:00:3b:7c:12
+"""
+
+class LibvirtTest(unittest.TestCase):
+    def test_parsers(self):
+        self.assertEqual(libvirt.parse_network_xml(NET_XML), {
+            "name": "default", "bridge": "virbr0", "forward": "nat",
+            "subnets": ["192.168.122.0/24", "fd00:122::/64"]
+        })
+        self.assertEqual(libvirt.parse_network_xml("<network><name>lab</name><bridge name='virbr1'/></network>")
+                         ["forward"], "isolated")
+        self.assertEqual(libvirt.parse_leases(LEASES), [
+            {"mac": "52:54:00:3b:7c:11", "ip": "192.168.122.41", "hostname": "fedora-dev"},
+            {"mac": "52:54:00:aa:00:02", "ip": "192.168.122.77", "hostname": None}
+        ])
+        self.assertEqual([nic["interface"] for nic in libvirt.parse_domiflist(DOMIFLIST)], ["vnet0", "vnet1"])
+
+    def test_guests(self):
+        network = dict(libvirt.parse_network_xml(NET_XML), active=True)
+        domains = {"fedora-dev": libvirt.parse_domiflist(DOMIFLIST), "idle": []}
+        guests = libvirt.attach_guests(network, libvirt.parse_leases(LEASES), domains)["guests"]
+        # The bridged NIC belongs to br0, not this network; a lease without a running domain still shows
+        self.assertEqual(guests, [
+            {"domain": "fedora-dev", "mac": "52:54:00:3b:7c:11", "ip": "192.168.122.41", "hostname": "fedora-dev",
+             "interface": "vnet0"},
+            {"domain": None, "mac": "52:54:00:aa:00:02", "ip": "192.168.122.77", "hostname": None, "interface": None}
+        ])
+
+    def test_unreachable(self):
+        class Down:
+            async def networks(self, uri):
+                raise OSError("failed to connect to the hypervisor")
+
+        tracker = libvirt.VirtualNetworks(Down(), lambda: ["eth0", "virbr0", "virbr-lan"])
+        asyncio.run(tracker.refresh())
+        self.assertEqual(tracker.error, "failed to connect to the hypervisor")
+        self.assertEqual([n["bridge"] for n in tracker.networks], ["virbr0"])
+        interfaces = [NetworkInterface(name, InterfaceType.BRIDGE, "Connected") for name in ("virbr0", "virbr-lan")]
+        tracker.annotate(interfaces)
+        self.assertEqual([i.virtual_network for i in interfaces], ["libvirt", None])
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,47 @@
+"""
+Link names - RTM_SETLINK messages and .link files for renamed interfaces
+"""
+
+import sys
+import struct
+import tempfile
+import unittest
+from pathlib import Path
+
+REPO_ROOT = Path(__file__).resolve().parents[2]
+sys.path.insert(0, str(REPO_ROOT / "src" / "alopex-qt"))
+
+from network import link_names
+
+class LinkNamesTest(unittest.TestCase):
+    def test_setlink_message(self):
+        message = link_names.build_setlink(7, name="lab0", seq=3)
+        length, kind, flags, seq, _ = struct.unpack_from("=IHHII", message)
+        self.assertEqual((length, kind, flags, seq), (len(message), 16, 0x5, 3))
+        self.assertEqual(struct.unpack_from("=BxHiII", message, 16), (0, 0, 7, 0, 0))
+        # IFLA_IFNAME, NUL-terminated and padded to 4 bytes
+        self.assertEqual(message[32:], struct.pack("=HH", 9, 3) + b"lab0\0" + b"\0" * 3)
+        down = link_names.build_setlink(7, flags=0, change=link_names.IFF_UP)
+        self.assertEqual((len(down), struct.unpack_from("=II", down, 24)), (32, (0, 1)))
+        with self.assertRaises(OSError) as busy:
+            link_names.parse_ack(struct.pack("=IHHIIi", 36, 2, 0, 1, 0, -16) + bytes(16))
+        self.assertEqual(busy.exception.errno, 16)
+        link_names.parse_ack(struct.pack("=IHHIIi", 36, 2, 0, 1, 0, 0) + bytes(16))
+
+    def test_names_and_link_files(self):
+        self.assertTrue(link_names.valid_name("wan0"))
+        for name in ("", "..", "a/b", "eth 0", "eth0:1", "x" * 16):
+            self.assertFalse(link_names.valid_name(name), name)
+        with tempfile.TemporaryDirectory() as tmp:
+            link_dir = Path(tmp)
+            (link_dir / "99-default.link").write_text("[Match]\nMACAddress=AA:BB:CC:00:00:01\n")
+            first, _ = link_names.write_link_file(link_dir, "lab0", "AA:BB:CC:00:00:01", True)
+            self.assertIn("PermanentMACAddress=aa:bb:cc:00:00:01\n", first.read_text())
+            second, removed = link_names.write_link_file(link_dir, "lab1", "aa:bb:cc:00:00:01", False)
+            self.assertEqual(removed, [first])
+            self.assertIn("MACAddress=aa:bb:cc:00:00:01\n\n[Link]\nName=lab1\n", second.read_text())
+            # Files we didn't write are left alone
+            self.assertEqual(sorted(p.name for p in link_dir.iterdir()), ["10-alopex-lab1.link", "99-default.link"])
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,53 @@
+"""
+Link speed - ethtool output and the warning for links below their best speed
+"""
+
+import sys
+import unittest
+from pathlib import Path
+
+REPO_ROOT = Path(__file__).resolve().parents[2]
+sys.path.insert(0, str(REPO_ROOT / "src" / "alopex-qt"))
+
+from network.link_speed import assess, parse_ethtool
+
+class LinkSpeedTest(unittest.TestCase):
+    ETHTOOL = """Settings for eth0:
+	Supported ports: [ TP ]
+	Supported link modes:   10baseT/Half 10baseT/Full
+	                        100baseT/Half 100baseT/Full
+	                        1000baseT/Full
+	Supported pause frame use: No
+	Advertised link modes:  10baseT/Half 10baseT/Full
+	                        100baseT/Half 100baseT/Full
+	                        1000baseT/Full
+	Link partner advertised link modes:  10baseT/Half 10baseT/Full
+	                                     100baseT/Half 100baseT/Full
+	                                     1000baseT/Full 2500baseT/Full
+	Speed: 100Mb/s
+	Duplex: Full
+	Link detected: yes
+"""
+
+    def test_parse_ethtool(self):
+        self.assertEqual(parse_ethtool(self.ETHTOOL), {"max_speed": 1000, "partner_max_speed": 2500})
+        unreported = self.ETHTOOL.replace("Link partner advertised link modes:  10baseT/Half 10baseT/Full\n"
+                                          "\t                                     100baseT/Half 100baseT/Full\n"
+                                          "\t                                     1000baseT/Full 2500baseT/Full",
+                                          "Link partner advertised link modes:  Not reported")
+        self.assertEqual(parse_ethtool(unreported), {"max_speed": 1000, "partner_max_speed": None})
+        self.assertEqual(parse_ethtool("Settings for lo:\n\tLink detected: yes\n"),
+                         {"max_speed": None, "partner_max_speed": None})
+
+    def test_assess(self):
+        # Both ends do gigabit: the cable
+        self.assertIn("cable is the likely culprit", assess("eth0", 100, "full", 1000, 1000)["message"])
+        self.assertIn("check the cable", assess("eth0", 100, "full", 1000, None)["message"])
+        # The switch only does 100 Mb/s, or everything is as fast as it gets
+        self.assertIsNone(assess("eth0", 100, "full", 1000, 100))
+        self.assertIsNone(assess("eth0", 1000, "full", 1000, 1000))
+        self.assertIsNone(assess("eth0", -1, None, 1000, None))
+        self.assertIn("half duplex", assess("eth0", 1000, "half", 1000, 1000)["message"])
+
+if __name__ == "__main__":
+    unittest.main()
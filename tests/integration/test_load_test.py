@@ -0,0 +1,67 @@
+"""
+Load test - curl transfers that load the link, and the bufferbloat grade from pings under load
+"""
+
+import sys
+import asyncio
+import unittest
+from pathlib import Path
+
+REPO_ROOT = Path(__file__).resolve().parents[2]
+sys.path.insert(0, str(REPO_ROOT / "src" / "alopex-qt"))
+
+from network import load_test
+
+class LoadTestTest(unittest.TestCase):
+    class Host:
+        """Pings 20 ms idle, 20 ms plus bloat while load_link runs"""
+        def __init__(self, bloat):
+            self.bloat = bloat
+            self.loaded = False
+
+        async def ping(self, host, count, interval, timeout, interface=None):
+            return [20.0 + (self.bloat if self.loaded else 0)] * count
+
+        async def load_link(self, interface, direction, seconds, url=None):
+            self.loaded = True
+            await asyncio.sleep(seconds)
+            self.loaded = False
+            return {"bytes": 12_500_000, "seconds": seconds, "mbps": load_test.mbps(12_500_000, seconds)}
+
+    def test_curl(self):
+        command = load_test.curl_command("eth0", "https://example.com/up", 10, "upload")
+        self.assertEqual(command[-5:], ["-T", "/dev/zero", "-w", "%{size_upload}", "https://example.com/up"])
+        self.assertIn("--interface", command)
+        self.assertEqual(load_test.parse_size("123456.000"), 123456)
+        self.assertEqual(load_test.parse_size(""), 0)
+        self.assertEqual(load_test.mbps(12_500_000, 10), 10.0)
+
+    def test_grade_and_assess(self):
+        self.assertEqual([load_test.grade(ms) for ms in (None, 0, 4.9, 5, 59, 150, 399, 400)],
+                         [None, "A+", "A+", "A", "B", "C", "D", "F"])
+        self.assertEqual(load_test.worst([None, "A", "C", "B"]), "C")
+        self.assertEqual(load_test.shaping_rate(18.5), "16650kbit")
+
+        def result(increase, mbps):
+            return {"interface": "eth0", "increase_ms": increase, "throughput": {"mbps": mbps}}
+
+        assessed = load_test.assess(result(12.0, 480.0), result(240.0, 20.0))
+        self.assertEqual((assessed["grade"], assessed["download"]["grade"], assessed["suggestion"]),
+                         ("D", "A", {"qdisc": "cake", "bandwidth": "18mbit"}))
+        self.assertIn("Try cake at 18mbit on eth0", assessed["summary"])
+        # Downstream bloat is the router's to fix: no qdisc suggestion for it
+        assessed = load_test.assess(result(90.0, 480.0), result(3.0, 20.0))
+        self.assertIsNone(assessed["suggestion"])
+        self.assertIn("only shaping on the router fixes", assessed["summary"])
+
+    def test_measure_and_compare(self):
+        bloated = asyncio.run(load_test.measure(self.Host(180), "eth0", seconds=0.5))
+        shaped = asyncio.run(load_test.measure(self.Host(3), "eth0", seconds=0.5))
+        self.assertEqual((bloated["idle"]["rtt"], bloated["loaded"]["rtt"], bloated["increase_ms"]),
+                         (20.0, 200.0, 180.0))
+        self.assertEqual(bloated["throughput"]["mbps"], 200.0)
+        self.assertEqual(load_test.compare(bloated, shaped), {"better": "b", "difference_ms": 177.0})
+        self.assertEqual(load_test.compare(shaped, dict(shaped, increase_ms=4.5))["better"], "same")
+
+if __name__ == "__main__":
+    unittest.main()
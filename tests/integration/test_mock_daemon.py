@@ -0,0 +1,724 @@
+"""
+Integration tests - drive the IPC protocol against alopexd --mock
+Needs neither root nor real hardware: python3 -m unittest discover -s tests/integration
+"""
+
+import sys
+import json
+import socket
+import time
+import tempfile
+import threading
+import unittest
+import subprocess
+from pathlib import Path
+
+REPO_ROOT = Path(__file__).resolve().parents[2]
+DAEMON = REPO_ROOT / "src" / "alopex-daemon" / "alopexd.py"
+sys.path.insert(0, str(REPO_ROOT / "src" / "alopex-qt"))
+
+from network.ipc import IpcClient, IpcError, ErrorCode
+
+SCENARIO = {
+    "links": [
+        {"name": "eth0", "type": "Ethernet", "up": True, "address": "192.168.1.50",
+         "gateway": "192.168.1.1", "link_speed": 1000, "rx_rate": 250000, "tx_rate": 40000,
+         "addresses": ["192.168.1.60/24"], "routes": [{"to": "10.99.0.0/16", "via": "192.168.1.254", "metric": None}]},
+        {"name": "eth1", "type": "Ethernet", "up": False, "address": "192.168.2.10",
+         "gateway": "192.168.2.1", "mac": "52:54:00:12:34:56", "throughput": {"download": 50.0},
+         "bloat": {"fq_codel": 150, "cake": 3}},
+        {"name": "wlan0", "type": "WiFi", "up": False, "address": "10.20.0.42", "gateway": "10.20.0.1"},
+        {"name": "veth7", "type": "Unknown", "up": True, "address": "172.18.0.1"}
+    ],
+    "profiles": [
+        {"name": "Lab", "interface": "eth1", "connection_type": "ethernet", "method": "dhcp",
+         "auto_connect": False},
+        {"name": "HQ", "interface": "wlan0", "connection_type": "wifi", "method": "dhcp",
+         "ssid": "AlopexHQ", "password": "arctic-fox", "auto_connect": False},
+        {"name": "HQ typo", "interface": "wlan0", "connection_type": "wifi", "method": "dhcp",
+         "ssid": "AlopexHQ", "password": "arctic-f0x", "auto_connect": False},
+        {"name": "Stuck", "interface": "wlan0", "connection_type": "wifi", "method": "dhcp",
+         "ssid": "Stuck", "auto_connect": False},
+        {"name": "Office VPN", "interface": "office", "connection_type": "vpn", "method": "manual",
+         "auto_connect": False}
+    ],
+    "wifi_networks": [
+        {"ssid": "AlopexHQ", "signal": -45, "security": "WPA2", "password": "arctic-fox"},
+        {"ssid": "Guest", "signal": -67, "security": "Open"},
+        {"ssid": "Stuck", "signal": -75, "security": "Open", "hang": True}
+    ],
+    "script": [
+        {"at": 1.0, "interface": "eth1", "up": False}
+    ],
+    "unit_traffic": {
+        "system.slice/nginx.service": {"rx": 20000, "tx": 900000},
+        "system.slice/sshd.service": {"rx": 300, "tx": 700}
+    },
+    # Every other echo to the uplink probe target is lost
+    "ping": {"198.51.100.7": [25, None]},
+    "delays": {"dhcp": 0.2, "associate": 0.2, "vpn": 0.2, "scan": 0.2}
+}
+
+class MockDaemonTest(unittest.TestCase):
+    """One mock daemon per test class, talked to over its control socket"""
+
+    @classmethod
+    def setUpClass(cls):
+        cls.state_dir = tempfile.TemporaryDirectory(prefix="alopex-test-")
+        state_path = Path(cls.state_dir.name)
+        socket_path = state_path / "alopexd.sock"
+
+        scenario_file = state_path / "scenario.json"
+        scenario_file.write_text(json.dumps(SCENARIO))
+        # SLA hook that leaves its argument and environment behind
+        hook = state_path / "sla-hook.sh"
+        hook.write_text(f'#!/bin/sh\necho "$1 $ALOPEX_SLA_PROBE $ALOPEX_SLA_EXCEEDED $ALOPEX_SLA_LOSS" '
+                        f'>> {state_path / "sla-hook.log"}\n')
+        hook.chmod(0o755)
+        # Stands in for a syslog collector
+        cls.collector = socket.socket(socket.AF_INET, socket.SOCK_DGRAM)
+        cls.collector.bind(("127.0.0.1", 0))
+        with socket.socket(socket.AF_INET, socket.SOCK_DGRAM) as probe:
+            probe.bind(("127.0.0.1", 0))
+            cls.snmp_port = probe.getsockname()[1]
+        (state_path / "enterprise.json").write_text(json.dumps({
+            "unmanaged_interfaces": ["veth*"],
+            "timeouts": {"connect": 1.5},
+            "auto_connect_retry": {"initial": 0.3, "max_delay": 0.3, "max_attempts": 2},
+            "logging": {"level": "DEBUG"},
+            "syslog": {"enabled": True, "url": f"udp://127.0.0.1:{cls.collector.getsockname()[1]}",
+                       "events": ["LocationChanged", "ProfileUpdated"]},
+            "snmp": {"enabled": True, "port": cls.snmp_port, "communities": ["monitor"], "location": "Lab rack 2"},
+            "accounting": {"enabled": True, "interval": 1},
+            "ebpf_counters": {"enabled": True, "interval_ms": 100, "interfaces": ["eth0", "wlan0"]},
+            "sla": {"probes": [
+                {"name": "gateway", "target": "192.168.1.1", "interval": 0.5, "thresholds": {"loss": 10}},
+                {"name": "uplink", "target": "198.51.100.7", "interval": 0.5, "count": 4, "thresholds": {"loss": 10},
+                 "script": str(hook)}
+            ]}
+        }))
+
+        cls.daemon = subprocess.Popen(
+            [sys.executable, str(DAEMON), "--mock", "--scenario", str(scenario_file),
+             "--state-dir", str(state_path), "--socket", str(socket_path)],
+            stdout=subprocess.DEVNULL, stderr=subprocess.DEVNULL
+        )
+        cls.client = IpcClient(socket_path, timeout=5.0)
+
+        deadline = time.monotonic() + 10
+        while cls.client.try_request("GetStatus") is None:
+            if time.monotonic() > deadline or cls.daemon.poll() is not None:
+                cls.tearDownClass()
+                raise RuntimeError("alopexd --mock did not come up")
+            time.sleep(0.1)
+
+    @classmethod
+    def tearDownClass(cls):
+        cls.daemon.terminate()
+        try:
+            cls.daemon.wait(timeout=10)
+        except subprocess.TimeoutExpired:
+            cls.daemon.kill()
+        cls.collector.close()
+        cls.state_dir.cleanup()
+
+    def interface(self, name):
+        return next(i for i in self.client.request("GetInterfaces") if i["name"] == name)
+
+    def wait_for(self, predicate, timeout=5.0):
+        deadline = time.monotonic() + timeout
+        while not predicate():
+            if time.monotonic() > deadline:
+                self.fail("condition not reached before timeout")
+            time.sleep(0.1)
+
+    def test_status_reports_mock_backends(self):
+        status = self.client.request("GetStatus")
+        self.assertTrue(status["mock"])
+        self.assertEqual(set(status["backends"].values()), {"mock"})
+        self.assertEqual(status["profiles"], len(SCENARIO["profiles"]))
+
+    def test_interfaces_and_metrics(self):
+        eth0 = self.interface("eth0")
+        self.assertEqual(eth0["status"], "Connected")
+        self.assertEqual(eth0["ip"], "192.168.1.50")
+        self.assertEqual(eth0["metrics"]["link_speed"], 1000)
+
+        # Counters advance by a fixed amount per discovery pass
+        before = eth0["metrics"]["bytes_rx"]
+        self.assertEqual(self.interface("eth0")["metrics"]["bytes_rx"] - before, 250000)
+
+    def test_unmanaged_interfaces(self):
+        self.assertFalse(self.interface("veth7")["managed"])
+        unmanaged = self.client.request("GetUnmanaged")
+        self.assertEqual(unmanaged["interfaces"], ["veth7"])
+
+        with self.assertRaises(IpcError) as refused:
+            self.client.request("Disconnect", interface="veth7")
+        self.assertEqual(refused.exception.code, ErrorCode.PERMISSION_DENIED)
+
+        with self.assertRaises(IpcError) as missing:
+            self.client.request("SetUnmanaged", interface="nope0")
+        self.assertEqual(missing.exception.code, ErrorCode.NOT_FOUND)
+        self.assertEqual(missing.exception.details, {"interface": "nope0"})
+
+    def test_wifi_scan_and_connect(self):
+        networks = self.client.run_job("ScanWifi", interface="wlan0")
+        self.assertEqual([n["ssid"] for n in networks], ["AlopexHQ", "Guest", "Stuck"])
+
+        with self.assertRaises(IpcError):
+            self.client.run_job("Connect", profile="HQ typo")
+
+        state = self.client.run_job("Connect", profile="HQ")
+        self.assertEqual(state["status"], "configured")
+        self.assertEqual(state["ip_address"], "10.20.0.42")
+        self.assertEqual(state["dhcp_lease"]["routers"], ["10.20.0.1"])
+
+        connected = [n for n in self.client.run_job("ScanWifi") if n["connected"]]
+        self.assertEqual([n["ssid"] for n in connected], ["AlopexHQ"])
+
+        self.client.request("Disconnect", interface="wlan0")
+        self.assertEqual(self.interface("wlan0")["status"], "Disconnected")
+
+    def test_vpn_appears_as_interface(self):
+        self.client.run_job("Connect", profile="Office VPN")
+        self.assertEqual(self.interface("office")["interface_type"], "VPN")
+
+        self.client.request("Disconnect", interface="office")
+        names = [i["name"] for i in self.client.request("GetInterfaces")]
+        self.assertNotIn("office", names)
+
+    def test_interface_types(self):
+        # "Unknown" in older scenarios reads as Other; types sort in InterfaceType order
+        self.assertEqual(self.interface("veth7")["interface_type"], "Other")
+        types = [i["interface_type"] for i in self.client.request("GetInterfaces")]
+        self.assertEqual(types.index("Ethernet"), 0)
+        self.assertLess(types.index("WiFi"), types.index("Other"))
+
+    def test_job_progress_events(self):
+        events = []
+
+        def collect():
+            for event in self.client.subscribe(["JobProgress", "JobCompleted", "JobFailed"]):
+                events.append(event)
+                if event["event"] != "JobProgress":
+                    return
+
+        listener = threading.Thread(target=collect, daemon=True)
+        listener.start()
+        time.sleep(0.3)
+
+        job = self.client.request("Connect", profile="Office VPN")
+        self.assertEqual(job["state"], "running")
+        listener.join(timeout=5)
+
+        self.assertEqual(events[-1]["event"], "JobCompleted")
+        self.assertEqual(events[-1]["job"]["id"], job["id"])
+        messages = [e["job"]["message"] for e in events if e["event"] == "JobProgress"]
+        self.assertTrue(any("Negotiating" in m for m in messages))
+
+        finished = self.client.request("GetJob", id=job["id"])
+        self.assertEqual((finished["state"], finished["progress"]), ("completed", 100))
+        self.client.request("Disconnect", interface="office")
+
+    def test_cancel_stuck_connect(self):
+        job = self.client.request("Connect", profile="Stuck")
+        time.sleep(0.5)
+
+        with self.assertRaises(IpcError) as busy:
+            self.client.request("Connect", profile="HQ")
+        self.assertEqual(busy.exception.code, ErrorCode.BUSY)
+
+        cancelled = self.client.request("CancelJob", id=job["id"])
+        self.assertEqual(cancelled["state"], "cancelled")
+        self.assertEqual(cancelled["error"]["code"], "Cancelled")
+
+        wlan0 = self.interface("wlan0")
+        self.assertEqual(wlan0["status"], "Disconnected")
+        self.assertEqual(wlan0["connection"]["status"], "disconnected")
+
+        # Cancelling a finished job is a no-op
+        self.assertEqual(self.client.request("CancelJob", id=job["id"])["state"], "cancelled")
+
+    def test_auto_connect_gives_up(self):
+        events = []
+
+        def collect():
+            for event in self.client.subscribe(["AutoConnectRetrying", "AutoConnectGaveUp"]):
+                events.append(event)
+                if event["event"] == "AutoConnectGaveUp":
+                    return
+
+        listener = threading.Thread(target=collect, daemon=True)
+        listener.start()
+        time.sleep(0.3)
+
+        self.client.request("UpdateProfile", name="HQ typo", auto_connect=True)
+        try:
+            self.assertEqual(self.client.request("ResetAutoConnect", profile="HQ typo"), {"reset": []})
+            listener.join(timeout=10)
+            self.assertEqual([(e["event"], e["profile"], e["attempts"]) for e in events],
+                             [("AutoConnectRetrying", "HQ typo", 1), ("AutoConnectGaveUp", "HQ typo", 2)])
+            self.assertEqual(events[0]["retry_in"], 0.3)
+
+            # Given up: no more attempts until someone steps in
+            time.sleep(1)
+            retries = self.client.request("ListAutoConnectRetries")
+            self.assertEqual([(r["profile"], r["interface"], r["failures"], r["gave_up"]) for r in retries],
+                             [("HQ typo", "wlan0", 2, True)])
+            with self.assertRaises(IpcError) as missing:
+                self.client.request("ResetAutoConnect", profile="Nowhere")
+            self.assertEqual(missing.exception.code, ErrorCode.NOT_FOUND)
+        finally:
+            # Editing the profile starts it over
+            self.client.request("UpdateProfile", name="HQ typo", auto_connect=False)
+        self.assertEqual(self.client.request("ListAutoConnectRetries"), [])
+
+    def test_route_metric(self):
+        def wait_for_metric(expected):
+            deadline = time.monotonic() + 5
+            while self.interface("eth1")["connection"]["route_metric"] != expected and time.monotonic() < deadline:
+                time.sleep(0.1)
+            return self.interface("eth1")["connection"]["route_metric"]
+
+        self.client.request("MockSetCarrier", interface="eth1", up=True)
+        job = self.client.request("Connect", profile="Lab")
+        self.client.request("WaitJob", id=job["id"], timeout=5)
+        try:
+            self.assertEqual(wait_for_metric(100), 100)
+            # Still the lease's route, so not something the interfaces config would take out
+            plan = self.client.request("ApplyInterfaces", interfaces={"eth1": {}}, dry_run=True)
+            self.assertEqual(plan["changes"], [])
+            with self.assertRaises(IpcError) as invalid:
+                self.client.request("UpdateProfile", name="Lab", route_metric=-1)
+            self.assertEqual(invalid.exception.code, ErrorCode.INVALID_CONFIG)
+
+            # Applies to the active connection straight away
+            self.client.request("UpdateProfile", name="Lab", route_metric=50)
+            self.assertEqual(wait_for_metric(50), 50)
+            self.client.request("UpdateProfile", name="Lab", route_metric=None, priority=20)
+            self.assertEqual(wait_for_metric(80), 80)
+        finally:
+            self.client.request("UpdateProfile", name="Lab", priority=0)
+            self.client.request("Disconnect", interface="eth1")
+            self.client.request("MockSetCarrier", interface="eth1", up=False)
+
+    def test_announce_and_proxy_arp(self):
+        self.client.request("UpdateProfile", name="Lab", proxy_arp=True)
+        self.client.request("MockSetCarrier", interface="eth1", up=True)
+        job = self.client.request("Connect", profile="Lab")
+        self.client.request("WaitJob", id=job["id"], timeout=5)
+        try:
+            connection = self.interface("eth1")["connection"]
+            self.assertIsNotNone(connection["announced_at"])
+            self.assertTrue(connection["proxy_arp"])
+
+            result = self.client.request("AnnounceAddresses", interface="eth1")
+            self.assertEqual((result["sent"], result["errors"]), (["192.168.2.10"], {}))
+            with self.assertRaises(IpcError) as missing:
+                self.client.request("AnnounceAddresses", interface="eth9")
+            self.assertEqual(missing.exception.code, ErrorCode.NOT_FOUND)
+
+            # Switched off with the profile, and on disconnect
+            self.client.request("UpdateProfile", name="Lab", proxy_arp=False)
+            self.assertFalse(self.interface("eth1")["connection"]["proxy_arp"])
+            self.client.request("UpdateProfile", name="Lab", proxy_arp=True)
+            self.assertTrue(self.interface("eth1")["connection"]["proxy_arp"])
+        finally:
+            self.client.request("Disconnect", interface="eth1")
+            self.client.request("UpdateProfile", name="Lab", proxy_arp=False)
+            self.client.request("MockSetCarrier", interface="eth1", up=False)
+        self.assertFalse(self.interface("eth1")["connection"]["proxy_arp"])
+        with self.assertRaises(IpcError) as down:
+            self.client.request("AnnounceAddresses", interface="eth1")
+        self.assertEqual(down.exception.code, ErrorCode.BACKEND_UNAVAILABLE)
+
+    def test_profile_sysctls(self):
+        def connect():
+            job = self.client.request("Connect", profile="Lab")
+            self.client.request("WaitJob", id=job["id"], timeout=5)
+            return {row["name"]: row for row in self.client.request("GetSysctls", interface="eth1")["sysctls"]}
+
+        for bad in ({"rp_filter": 5}, {"ip_forward": 1}, {"tcp_congestion_control": "bbr; reboot"}):
+            with self.assertRaises(IpcError) as invalid:
+                self.client.request("UpdateProfile", name="Lab", sysctls=bad)
+            self.assertEqual(invalid.exception.code, ErrorCode.INVALID_CONFIG)
+
+        self.client.request("UpdateProfile", name="Lab", sysctls={"rp_filter": 1, "tcp_congestion_control": "bbr"})
+        self.client.request("MockSetCarrier", interface="eth1", up=True)
+        try:
+            rows = connect()
+            self.assertEqual((rows["rp_filter"]["key"], rows["rp_filter"]["effective"], rows["rp_filter"]["previous"]),
+                             ("net.ipv4.conf.eth1.rp_filter", "1", "2"))
+            self.assertEqual((rows["tcp_congestion_control"]["effective"], rows["tcp_congestion_control"]["previous"]),
+                             ("bbr", "cubic"))
+            self.client.request("Disconnect", interface="eth1")
+            self.assertEqual(self.client.request("GetSysctls", interface="eth1")["sysctls"], [])
+
+            # Put back on disconnect: what it was before is cubic again, not bbr
+            self.client.request("UpdateProfile", name="Lab", sysctls={"tcp_congestion_control": "reno"})
+            rows = connect()
+            self.assertEqual((rows["tcp_congestion_control"]["effective"], rows["tcp_congestion_control"]["previous"]),
+                             ("reno", "cubic"))
+            # One the kernel doesn't have stays out of force, and says so
+            self.client.request("UpdateProfile", name="Lab", sysctls={"tcp_congestion_control": "vegas"})
+            row = self.client.request("GetSysctls", interface="eth1")["sysctls"][0]
+            self.assertEqual((row["effective"], row["in_effect"], row["applied"]), ("cubic", False, False))
+        finally:
+            self.client.request("Disconnect", interface="eth1")
+            self.client.request("UpdateProfile", name="Lab", sysctls={})
+            self.client.request("MockSetCarrier", interface="eth1", up=False)
+
+    def test_profile_mptcp_endpoints(self):
+        with self.assertRaises(IpcError) as invalid:
+            self.client.request("UpdateProfile", name="Lab", mptcp_flags=["signal", "fullmesh"])
+        self.assertEqual(invalid.exception.code, ErrorCode.INVALID_CONFIG)
+        with self.assertRaises(IpcError) as invalid:
+            self.client.request("SetMptcpLimits", subflows=9)
+        self.assertEqual(invalid.exception.code, ErrorCode.INVALID_CONFIG)
+
+        profile = self.client.request("UpdateProfile", name="Lab", mptcp_flags=["backup", "subflow"])
+        self.assertEqual(profile["mptcp_flags"], ["subflow", "backup"])
+        self.client.request("MockSetCarrier", interface="eth1", up=True)
+        try:
+            job = self.client.request("Connect", profile="Lab")
+            self.client.request("WaitJob", id=job["id"], timeout=5)
+            endpoints = self.client.request("GetMptcp")["endpoints"]
+            self.assertEqual([(e["address"], e["interface"], e["flags"], e["profile"]) for e in endpoints],
+                             [("192.168.2.10", "eth1", ["subflow", "backup"], "Lab")])
+            self.assertEqual(self.client.request("SetMptcpLimits", subflows=4),
+                             {"subflows": 4, "add_addr_accepted": 0})
+
+            # Dropping the flags while active takes the endpoints out
+            self.client.request("UpdateProfile", name="Lab", mptcp_flags=[])
+            self.wait_for(lambda: not self.client.request("GetMptcp")["endpoints"])
+            self.client.request("UpdateProfile", name="Lab", mptcp_flags=["signal"])
+            self.wait_for(lambda: self.client.request("GetMptcp")["endpoints"])
+            self.client.request("Disconnect", interface="eth1")
+            self.assertEqual(self.client.request("GetMptcp")["endpoints"], [])
+        finally:
+            self.client.request("Disconnect", interface="eth1")
+            self.client.request("UpdateProfile", name="Lab", mptcp_flags=[])
+            self.client.request("SetMptcpLimits", subflows=2)
+            self.client.request("MockSetCarrier", interface="eth1", up=False)
+
+    def test_profile_shaping(self):
+        for bad in ({"qdisc": "pfifo"}, {"qdisc": "fq", "qdisc_bandwidth": "95mbit"},
+                    {"qdisc": "cake", "qdisc_bandwidth": "fast"}, {"congestion_control": "bbr; reboot"}):
+            with self.assertRaises(IpcError) as invalid:
+                self.client.request("UpdateProfile", name="Lab", **bad)
+            self.assertEqual(invalid.exception.code, ErrorCode.INVALID_CONFIG)
+
+        profile = self.client.request("UpdateProfile", name="Lab", qdisc="cake", qdisc_bandwidth="45",
+                                      congestion_control="bbr")
+        self.assertEqual((profile["qdisc"], profile["qdisc_bandwidth"]), ("cake", "45mbit"))
+        self.client.request("MockSetCarrier", interface="eth1", up=True)
+        try:
+            job = self.client.request("Connect", profile="Lab")
+            self.client.request("WaitJob", id=job["id"], timeout=5)
+            status = self.client.request("GetShaping", interface="eth1")
+            self.assertEqual((status["qdisc"], status["congestion_control"], status["in_effect"]),
+                             ({"kind": "cake", "bandwidth": "45mbit"}, "bbr", True))
+
+            # A without shaping, B with the profile's cake; the profile's qdisc is back afterwards
+            result = self.client.run_job("RunShapingTest", interface="eth1", seconds=2)
+            self.assertEqual((result["a"]["qdisc"]["kind"], result["b"]["qdisc"]["kind"], result["better"]),
+                             ("fq_codel", "cake", "b"))
+            self.assertEqual((result["a"]["increase_ms"], result["b"]["increase_ms"]), (150.0, 3.0))
+            self.assertEqual((result["a"]["throughput"]["mbps"], result["b"]["throughput"]["mbps"]), (50.0, 45.0))
+            self.assertEqual(self.client.request("GetShaping", interface="eth1")["qdisc"]["kind"], "cake")
+
+            # Switching the qdisc keeps cake's bandwidth only while it stays cake
+            self.assertIsNone(self.client.request("UpdateProfile", name="Lab", qdisc="fq")["qdisc_bandwidth"])
+            self.wait_for(lambda: self.client.request("GetShaping", interface="eth1")["qdisc"]["kind"] == "fq")
+            self.client.request("Disconnect", interface="eth1")
+            status = self.client.request("GetShaping", interface="eth1")
+            self.assertEqual((status["qdisc"]["kind"], status["set_by_alopex"]["qdisc"]), ("fq_codel", False))
+        finally:
+            self.client.request("Disconnect", interface="eth1")
+            self.client.request("UpdateProfile", name="Lab", qdisc=None, congestion_control=None)
+            self.client.request("MockSetCarrier", interface="eth1", up=False)
+        with self.assertRaises(IpcError) as nothing:
+            self.client.request("RunShapingTest", interface="eth1")
+        self.assertEqual(nothing.exception.code, ErrorCode.INVALID_REQUEST)
+
+    def test_bufferbloat(self):
+        self.client.request("MockSetCarrier", interface="eth1", up=True)
+        try:
+            job = self.client.request("Connect", profile="Lab")
+            self.client.request("WaitJob", id=job["id"], timeout=5)
+            result = self.client.run_job("RunBufferbloatTest", interface="eth1", seconds=2)
+            self.assertEqual((result["grade"], result["download"]["increase_ms"], result["profile"]),
+                             ("C", 150.0, "Lab"))
+            self.assertEqual(result["download"]["throughput"]["mbps"], 50.0)
+            # Nothing set for the upstream: the mock fills it at 100 Mbit/s, so shape to 90
+            self.assertEqual(result["suggestion"], {"qdisc": "cake", "bandwidth": "90mbit"})
+
+            self.client.request("UpdateProfile", name="Lab", qdisc="cake", qdisc_bandwidth="90mbit")
+            self.wait_for(lambda: self.client.request("GetShaping", interface="eth1")["in_effect"])
+            result = self.client.run_job("RunBufferbloatTest", interface="eth1", seconds=2)
+            self.assertEqual((result["grade"], result["suggestion"], result["qdisc"]["kind"]), ("A+", None, "cake"))
+        finally:
+            self.client.request("Disconnect", interface="eth1")
+            self.client.request("UpdateProfile", name="Lab", qdisc=None)
+            self.client.request("MockSetCarrier", interface="eth1", up=False)
+        with self.assertRaises(IpcError) as invalid:
+            self.client.request("RunBufferbloatTest", interface="eth1", seconds=600)
+        self.assertEqual(invalid.exception.code, ErrorCode.INVALID_REQUEST)
+
+    def test_state_transitions_are_guarded(self):
+        transitions = []
+
+        def collect():
+            for event in self.client.subscribe(["InterfaceStateChanged"]):
+                if event["interface"] == "eth1":
+                    transitions.append(event["state"])
+                if event["state"] == "disconnected":
+                    return
+
+        listener = threading.Thread(target=collect, daemon=True)
+        listener.start()
+        time.sleep(0.3)
+
+        self.client.request("MockSetCarrier", interface="eth1", up=True)
+        job = self.client.request("Connect", profile="Lab")
+
+        # The interface is claimed as soon as Connect returns
+        with self.assertRaises(IpcError) as busy:
+            self.client.request("Disconnect", interface="eth1")
+        self.assertEqual(busy.exception.code, ErrorCode.BUSY)
+        self.assertEqual(busy.exception.details["state"], "acquiring")
+        with self.assertRaises(IpcError) as busy:
+            self.client.request("Connect", profile="Lab")
+        self.assertEqual(busy.exception.code, ErrorCode.BUSY)
+
+        self.client.request("WaitJob", id=job["id"], timeout=5)
+        self.assertEqual(self.client.request("Disconnect", interface="eth1")["status"], "disconnected")
+        listener.join(timeout=5)
+
+        self.assertEqual(transitions, ["acquiring", "configured", "deactivating", "disconnected"])
+
+    def test_connect_timeout(self):
+        with self.assertRaises(IpcError) as timed_out:
+            self.client.run_job("Connect", profile="Stuck")
+        self.assertEqual(timed_out.exception.code, ErrorCode.TIMED_OUT)
+        self.assertEqual(self.interface("wlan0")["connection"]["status"], "disconnected")
+
+    def test_link_flap(self):
+        self.client.request("MockSetCarrier", interface="eth0", up=False)
+        self.assertEqual(self.interface("eth0")["status"], "Disconnected")
+
+        self.client.request("MockSetCarrier", interface="eth0", up=True)
+        self.assertEqual(self.interface("eth0")["status"], "Connected")
+
+    def test_carrier_uptime(self):
+        self.wait_for(lambda: "veth7" in self.client.request("GetUptimes"))
+        self.assertGreaterEqual(self.interface("veth7")["metrics"]["uptime"], 0)
+
+        self.client.request("MockSetCarrier", interface="veth7", up=False)
+        self.wait_for(lambda: "veth7" not in self.client.request("GetUptimes"))
+        self.assertIsNone(self.interface("veth7")["metrics"]["uptime"])
+
+        # Uptime starts over when carrier returns, not from when the daemon first saw the link
+        self.client.request("MockSetCarrier", interface="veth7", up=True)
+        self.wait_for(lambda: "veth7" in self.client.request("GetUptimes"))
+        self.assertLess(self.client.request("GetUptimes")["veth7"], 3)
+
+    def test_scripted_flap(self):
+        # eth1 loses carrier one second after start; DHCP then has nothing to talk to
+        started_at = self.client.request("GetStatus")["started_at"]
+        self.wait_for(lambda: time.time() - started_at > 1.5)
+        with self.assertRaises(IpcError):
+            self.client.run_job("Connect", profile="Lab")
+
+        self.client.request("MockSetCarrier", interface="eth1", up=True)
+        self.assertEqual(self.client.run_job("Connect", profile="Lab")["ip_address"], "192.168.2.10")
+        self.assertEqual(self.client.request("GetStatus")["active_profiles"].get("eth1"), "Lab")
+
+    def test_profiles_hide_secrets(self):
+        profiles = {p["name"]: p for p in self.client.request("ListProfiles")}
+        self.assertEqual(profiles["HQ"]["ssid"], "AlopexHQ")
+        self.assertNotIn("password", profiles["HQ"])
+
+        # Connected with the PSK, which stays out of every reply but is kept, root-only, on disk
+        self.client.run_job("Connect", profile="HQ")
+        try:
+            for request in ("GetInterfaces", "ListProfiles", "GetDiagnostics"):
+                self.assertNotIn("arctic-fox", json.dumps(self.client.request(request)), request)
+        finally:
+            self.client.request("Disconnect", interface="wlan0")
+        stored = Path(self.state_dir.name) / "connection-profiles.json"
+        self.assertEqual(json.loads(stored.read_text())["HQ"]["password"], "arctic-fox")
+        self.assertEqual(stored.stat().st_mode & 0o777, 0o600)
+
+    def test_log_levels_and_buffer(self):
+        levels = self.client.request("SetLogLevel", level="warning", module="connection_manager")
+        self.assertEqual(levels["modules"]["connection_manager"], "WARNING")
+        self.client.request("SetLogLevel", level="debug", module="connection_manager")
+
+        records = self.client.request("GetLogs", level="INFO")
+        self.assertTrue(any("Mock mode" in r["message"] for r in records))
+
+        with self.assertRaises(IpcError) as bad_level:
+            self.client.request("GetLogs", level="LOUD")
+        self.assertEqual(bad_level.exception.code, ErrorCode.INVALID_CONFIG)
+
+    def test_syslog_audit(self):
+        with self.assertRaises(IpcError):
+            self.client.request("SetUnmanaged", interface="nope0")
+        self.client.request("GetUnmanaged")
+
+        self.collector.settimeout(5)
+        messages = []
+        while not any("SetUnmanaged" in message for message in messages):
+            messages.append(self.collector.recv(4096).decode())
+        audit = next(message for message in messages if "SetUnmanaged" in message)
+        self.assertRegex(audit, r"^<28>1 \S+ \S+ alopexd \d+ audit \[alopex@32473 request=\"SetUnmanaged\" ")
+        self.assertIn('outcome="NotFound"', audit)
+        self.assertNotIn("GetUnmanaged", "".join(messages))
+
+        status = self.client.request("GetSyslogForwarder")
+        self.assertEqual(status["events"], ["LocationChanged", "ProfileUpdated"])
+        self.assertGreaterEqual(status["sent"], 1)
+
+    def test_snmp_walk(self):
+        from network import snmp
+        names, oid = [], snmp.IF_ENTRY + (2,)
+        with socket.socket(socket.AF_INET, socket.SOCK_DGRAM) as sock:
+            sock.settimeout(5)
+            while True:
+                pdu = snmp.encode_pdu(snmp.GET_NEXT, 1, 0, 0, [(oid, (snmp.NULL, None))])
+                sock.sendto(snmp.tlv(snmp.SEQUENCE, snmp.encode_int(1) + snmp.encode_octets(b"monitor") + pdu),
+                            ("127.0.0.1", self.snmp_port))
+                binding = snmp.parse(sock.recv(65535))[1][2][1][3][1][0][1]
+                oid, value = binding[0][1], binding[1][1]
+                if oid[:len(snmp.IF_ENTRY) + 1] != snmp.IF_ENTRY + (2,):
+                    break
+                names.append(value.decode())
+        # Tunnels other tests bring up may be listed too
+        self.assertTrue({"eth0", "eth1", "wlan0", "veth7"} <= set(names))
+
+        agent = self.client.request("GetSnmpAgent")
+        self.assertEqual(agent["listening"], f"127.0.0.1:{self.snmp_port}")
+        self.assertGreaterEqual(agent["requests"], len(names) + 1)
+
+    def test_sla_probes(self):
+        def probes():
+            return {p["name"]: p for p in self.client.request("GetSlaProbes")}
+        self.wait_for(lambda: probes()["uplink"]["state"] == "breach")
+        self.assertEqual(probes()["gateway"]["state"], "ok")
+        self.assertEqual(probes()["gateway"]["last"]["rtt"], 10.0)
+        self.assertEqual(probes()["uplink"]["hour"]["loss"], 50.0)
+
+        log = Path(self.state_dir.name) / "sla-hook.log"
+        self.wait_for(log.exists)
+        self.assertEqual(log.read_text().splitlines()[0], "breach uplink loss 50.0")
+
+        history = self.client.request("GetSlaHistory", probe="uplink")
+        self.assertGreaterEqual(len(history), 2)
+        self.assertEqual(history[-1]["exceeded"], ["loss"])
+        with self.assertRaises(IpcError) as unknown:
+            self.client.request("GetSlaHistory", probe="moon")
+        self.assertEqual(unknown.exception.code, ErrorCode.NOT_FOUND)
+
+    def test_top_talkers(self):
+        self.wait_for(lambda: self.client.request("GetTopTalkers")["units"][0]["tx_rate"] is not None)
+        talkers = self.client.request("GetTopTalkers", top=1)
+        self.assertEqual((talkers["method"], talkers["tracked"], talkers["last_error"]), ("mock", 2, None))
+        nginx, = talkers["units"]
+        self.assertEqual((nginx["unit"], nginx["cgroup"]), ("nginx.service", "system.slice/nginx.service"))
+        # 900 kB per one-second sample, give or take scheduling
+        self.assertAlmostEqual(nginx["tx_rate"], 900000, delta=300000)
+        self.assertEqual(nginx["tx_bytes"] % 900000, 0)
+        with self.assertRaises(IpcError) as invalid:
+            self.client.request("GetTopTalkers", top=0)
+        self.assertEqual(invalid.exception.code, ErrorCode.INVALID_REQUEST)
+
+    def test_fast_rates(self):
+        self.wait_for(lambda: "eth0" in self.client.request("GetFastRates")["interfaces"])
+        rates = self.client.request("GetFastRates")
+        self.assertEqual((rates["active"], rates["method"], rates["interval_ms"]), (True, "mock", 100))
+        self.assertEqual(sorted(rates["interfaces"]), ["eth0", "wlan0"])
+        eth0 = self.client.request("GetFastRates", interface="eth0")["interfaces"]["eth0"]
+        # The mock sends 80% of eth0's 250 kB/s as TCP; 100ms samples jitter with scheduling
+        self.assertAlmostEqual(eth0["rx"]["protocols"]["tcp"]["bytes_per_s"], 200000, delta=80000)
+        self.assertEqual(set(eth0["tx"]["protocols"]), {"tcp", "udp", "icmp", "other"})
+        self.assertGreaterEqual(len(eth0["series"]), 2)
+        self.assertGreaterEqual(eth0["peak"]["rx"], max(sample[1] for sample in eth0["series"]))
+        # wlan0 is down, so it is counted but idle
+        wlan0 = self.client.request("GetFastRates", interface="wlan0")["interfaces"]["wlan0"]
+        self.assertEqual(wlan0["rx"]["bytes_per_s"], 0)
+        with self.assertRaises(IpcError) as uncounted:
+            self.client.request("GetFastRates", interface="veth7")
+        self.assertEqual(uncounted.exception.code, ErrorCode.NOT_FOUND)
+
+    def test_apply_interfaces(self):
+        state_path = Path(self.state_dir.name)
+        config = state_path / "apply.json"
+        config.write_text(json.dumps({"interfaces": {"eth0": {
+            "addresses": ["192.168.1.60/24", "192.168.1.61/24"],
+            "routes": [{"to": "10.20.0.0/16", "via": "192.168.1.254"}]
+        }}}))
+        command = [sys.executable, str(DAEMON), "--socket", str(state_path / "alopexd.sock"), "apply",
+                   "--config", str(config)]
+        dry = subprocess.run(command + ["--dry-run"], capture_output=True, text=True, timeout=30)
+        # The DHCP address and default route aren't the config's business
+        self.assertEqual(dry.stdout.splitlines(), [
+            "- eth0 route 10.99.0.0/16 via 192.168.1.254",
+            "+ eth0 address 192.168.1.61/24",
+            "+ eth0 route 10.20.0.0/16 via 192.168.1.254"
+        ])
+        self.assertEqual(dry.returncode, 1)
+
+        applied = subprocess.run(command, capture_output=True, text=True, timeout=30)
+        self.assertEqual((applied.returncode, applied.stdout.splitlines()[-1]), (0, "Applied 3 of 3 changes"))
+        again = subprocess.run(command + ["--dry-run"], capture_output=True, text=True, timeout=30)
+        self.assertEqual((again.returncode, again.stdout.strip()), (0, "Interfaces already match the config"))
+
+        # A config that can't be read, and a daemon that can't be reached, are told apart
+        config.write_text("{")
+        unreadable = subprocess.run(command, capture_output=True, text=True, timeout=30)
+        self.assertEqual(unreadable.returncode, 2)
+        self.assertIn(f"Cannot read {config}", unreadable.stderr)
+        unreachable = subprocess.run([sys.executable, str(DAEMON), "--socket", str(state_path / "nobody.sock"),
+                                      "apply"], capture_output=True, text=True, timeout=30)
+        self.assertEqual(unreachable.returncode, 3)
+        self.assertIn("alopexd is not running", unreachable.stderr)
+        self.assertNotIn("Cannot read", unreachable.stderr)
+
+        with self.assertRaises(IpcError) as missing:
+            self.client.request("ApplyInterfaces", interfaces={"eth0": {}, "eth9": {}}, dry_run=True)
+        self.assertEqual(missing.exception.code, ErrorCode.NOT_FOUND)
+        with self.assertRaises(IpcError) as unmanaged:
+            self.client.request("ApplyInterfaces", interfaces={"veth7": {}})
+        self.assertEqual(unmanaged.exception.code, ErrorCode.PERMISSION_DENIED)
+        with self.assertRaises(IpcError) as invalid:
+            self.client.request("ApplyInterfaces", interfaces={"eth0": {"addresses": ["nowhere"]}})
+        self.assertEqual(invalid.exception.code, ErrorCode.INVALID_CONFIG)
+        # enterprise.json here has no interfaces section
+        with self.assertRaises(IpcError) as unconfigured:
+            self.client.request("ApplyInterfaces")
+        self.assertEqual(unconfigured.exception.code, ErrorCode.NOT_FOUND)
+
+    def test_protocol_errors(self):
+        with self.assertRaises(IpcError) as unknown:
+            self.client.request("Reticulate")
+        self.assertIn("Unknown request", str(unknown.exception))
+        self.assertEqual(unknown.exception.code, ErrorCode.INVALID_REQUEST)
+
+        with self.assertRaises(IpcError) as invalid:
+            self.client.run_job("Connect", name="HQ")
+        self.assertIn("Invalid parameters", str(invalid.exception))
+        self.assertEqual(invalid.exception.code, ErrorCode.INVALID_REQUEST)
+
+        with self.assertRaises(IpcError) as missing:
+            self.client.run_job("Connect", profile="Nowhere")
+        self.assertEqual(missing.exception.code, ErrorCode.NOT_FOUND)
+
+        offline = IpcClient(Path(self.state_dir.name) / "missing.sock")
+        with self.assertRaises(IpcError) as unreachable:
+            offline.request("GetStatus")
+        self.assertEqual(unreachable.exception.code, ErrorCode.DAEMON_UNAVAILABLE)
+
+if __name__ == "__main__":
+    unittest.main()
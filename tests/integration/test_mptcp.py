@@ -0,0 +1,60 @@
+"""
+MPTCP - endpoint flags and addresses, and the path manager netlink messages
+"""
+
+import sys
+import struct
+import unittest
+from pathlib import Path
+
+REPO_ROOT = Path(__file__).resolve().parents[2]
+sys.path.insert(0, str(REPO_ROOT / "src" / "alopex-qt"))
+
+from network import mptcp
+from network import netlink
+
+class MptcpTest(unittest.TestCase):
+    def test_check_flags(self):
+        self.assertEqual(mptcp.check_flags(["backup", "subflow"]), ["subflow", "backup"])
+        self.assertEqual(mptcp.check_flags(None), [])
+        for bad in (["signal", "fullmesh"], ["implicit"], ["fast"]):
+            with self.assertRaises(ValueError):
+                mptcp.check_flags(bad)
+        self.assertEqual(mptcp.flag_names(mptcp.flag_bits(["signal", "backup"]) | mptcp.FLAG_IMPLICIT),
+                         ["signal", "backup", "implicit"])
+
+    def test_endpoint_addresses(self):
+        self.assertEqual(mptcp.endpoint_addresses([
+            {"address": "192.168.2.10/24"}, {"address": "fe80::1/64"}, {"address": "127.0.0.1/8"},
+            {"address": "2001:db8::10/64"}, {"address": "192.168.2.10/24"}, {"address": "bogus"}
+        ]), ["192.168.2.10", "2001:db8::10"])
+
+    def test_add_round_trip(self):
+        message = mptcp.build_add(30, "192.168.2.10", 3, ["subflow", "backup"], seq=7)
+        length, family, flags, seq, _ = struct.unpack_from("=IHHII", message)
+        self.assertEqual((length, family, seq), (len(message), 30, 7))
+        self.assertTrue(flags & netlink.NLM_F_REQUEST and flags & mptcp.NLM_F_ACK)
+        self.assertEqual(message[16], mptcp.MPTCP_PM_CMD_ADD_ADDR)
+
+        # A dump reply carries the same nested attribute, plus the ID the kernel chose
+        nested = netlink.attributes(message[20:])[mptcp.MPTCP_PM_ATTR_ADDR] + netlink.attr(mptcp.ADDR_ATTR_ID, b"\x05")
+        reply = mptcp.build_message(30, mptcp.MPTCP_PM_CMD_GET_ADDR,
+                                    netlink.attr(mptcp.MPTCP_PM_ATTR_ADDR | mptcp.NLA_F_NESTED, nested))
+        replies, done = mptcp.parse_replies(reply)
+        self.assertFalse(done)
+        endpoint = mptcp.parse_endpoint(netlink.attributes(replies[0][mptcp.MPTCP_PM_ATTR_ADDR]))
+        self.assertEqual((endpoint["id"], endpoint["address"], endpoint["flags"], endpoint["port"]),
+                         (5, "192.168.2.10", ["subflow", "backup"], None))
+
+    def test_replies_end_on_done_or_error(self):
+        done = struct.pack("=IHHII", 20, netlink.NLMSG_DONE, 0, 1, 0) + b"\0" * 4
+        self.assertEqual(mptcp.parse_replies(done), ([], True))
+        ack = struct.pack("=IHHII", 36, netlink.NLMSG_ERROR, 0, 1, 0) + struct.pack("=i", 0) + b"\0" * 16
+        self.assertEqual(mptcp.parse_replies(ack), ([], True))
+        refused = struct.pack("=IHHII", 36, netlink.NLMSG_ERROR, 0, 1, 0) + struct.pack("=i", -17) + b"\0" * 16
+        with self.assertRaises(OSError) as error:
+            mptcp.parse_replies(refused)
+        self.assertEqual(error.exception.errno, 17)
+
+if __name__ == "__main__":
+    unittest.main()
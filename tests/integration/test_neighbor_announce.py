@@ -0,0 +1,58 @@
+"""
+Neighbor announcements - gratuitous ARP, unsolicited NA and proxy ARP
+"""
+
+import sys
+import socket
+import ipaddress
+import struct
+import tempfile
+import unittest
+from pathlib import Path
+
+REPO_ROOT = Path(__file__).resolve().parents[2]
+sys.path.insert(0, str(REPO_ROOT / "src" / "alopex-qt"))
+
+from network.neighbor_announce import announce_targets, gratuitous_arp, set_proxy_arp, unsolicited_na
+
+class NeighborAnnounceTest(unittest.TestCase):
+    def test_gratuitous_arp(self):
+        frame = gratuitous_arp("52:54:00:12:34:56", "192.0.2.10")
+        self.assertEqual(frame[:14], b"\xff" * 6 + bytes.fromhex("525400123456") + b"\x08\x06")
+        htype, ptype, hlen, plen, op, sha, spa, tha, tpa = struct.unpack("!HHBBH6s4s6s4s", frame[14:])
+        self.assertEqual((htype, ptype, hlen, plen, op), (1, 0x0800, 6, 4, 1))
+        self.assertEqual((sha.hex(), tha), ("525400123456", b"\x00" * 6))
+        # Sender and target both the announced address
+        self.assertEqual(spa, tpa)
+        self.assertEqual(socket.inet_ntoa(spa), "192.0.2.10")
+
+    def test_unsolicited_na(self):
+        message = unsolicited_na("52:54:00:12:34:56", "2001:db8::10")
+        kind, code, checksum, flags, target = struct.unpack("!BBHI16s", message[:24])
+        self.assertEqual((kind, code, checksum), (136, 0, 0))
+        # Override set, solicited not: neighbours replace what they cached
+        self.assertEqual(flags, 0x20000000)
+        self.assertEqual(ipaddress.IPv6Address(target), ipaddress.IPv6Address("2001:db8::10"))
+        self.assertEqual(message[24:], bytes([2, 1]) + bytes.fromhex("525400123456"))
+
+    def test_targets(self):
+        addresses = [{"address": "192.0.2.10/24", "dynamic": True}, {"address": "2001:db8::10/64", "dynamic": False},
+                     {"address": "127.0.0.1/8", "dynamic": False}, {"address": "192.0.2.10/32", "dynamic": False},
+                     {"address": "garbage", "dynamic": False}]
+        self.assertEqual(announce_targets(addresses), ["192.0.2.10", "2001:db8::10"])
+
+    def test_proxy_arp(self):
+        with tempfile.TemporaryDirectory() as tmp:
+            proc = Path(tmp)
+            (proc / "ipv4" / "conf" / "br0").mkdir(parents=True)
+            (proc / "ipv4" / "conf" / "br0" / "proxy_arp").write_text("0")
+            # No IPv6 on the bridge: proxy ARP still goes on
+            self.assertTrue(set_proxy_arp("br0", True, proc))
+            self.assertEqual((proc / "ipv4" / "conf" / "br0" / "proxy_arp").read_text(), "1")
+            (proc / "ipv6" / "conf" / "br0").mkdir(parents=True)
+            self.assertTrue(set_proxy_arp("br0", False, proc))
+            self.assertEqual((proc / "ipv6" / "conf" / "br0" / "proxy_ndp").read_text(), "0")
+            self.assertFalse(set_proxy_arp("eth9", True, proc))
+
+if __name__ == "__main__":
+    unittest.main()
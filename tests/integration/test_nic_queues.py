@@ -0,0 +1,66 @@
+"""
+NIC queues - queue statistics, IRQ spread plans and affinity writes
+"""
+
+import sys
+import tempfile
+import unittest
+from pathlib import Path
+
+REPO_ROOT = Path(__file__).resolve().parents[2]
+sys.path.insert(0, str(REPO_ROOT / "src" / "alopex-qt"))
+
+from network.nic_queues import (
+    format_cpu_list, parse_cpu_list, parse_interrupts, parse_queue_stats, spread_plan, write_affinity,
+    summarize as summarize_queues
+)
+
+class NicQueuesTest(unittest.TestCase):
+    INTERRUPTS = """           CPU0       CPU1       CPU2       CPU3
+ 128:         10          0          0          0  IR-PCI-MSI 524288-edge      eth0
+ 129:       5000          0          0          0  IR-PCI-MSI 524289-edge      eth0-TxRx-0
+ 130:       4000          0          0          0  IR-PCI-MSI 524290-edge      eth0-TxRx-1
+ 131:          3          0          0          0  IR-PCI-MSI 524291-edge      nvme0q0
+ NMI:          0          0          0          0   Non-maskable interrupts
+"""
+
+    def test_parse(self):
+        stats = parse_queue_stats("""NIC statistics:
+     rx_packets: 1010
+     rx_queue_0_packets: 900
+     rx_queue_0_bytes: 1350000
+     tx_queue_0_packets: 50
+     rx-1.packets: 60
+     rx2_packets: 50
+""")
+        self.assertEqual([(q["queue"], q["rx_packets"], q["tx_packets"]) for q in stats],
+                         [(0, 900, 50), (1, 60, 0), (2, 50, 0)])
+        self.assertEqual([i["irq"] for i in parse_interrupts(self.INTERRUPTS, interface="eth0")], [128, 129, 130])
+        self.assertEqual([(i["irq"], i["interrupts"]) for i in parse_interrupts(self.INTERRUPTS, [129, 131])],
+                         [(129, 5000), (131, 3)])
+        self.assertEqual(parse_cpu_list("0-3,8\n"), [0, 1, 2, 3, 8])
+        self.assertEqual(format_cpu_list([8, 0, 1, 2, 3, 10, 11]), "0-3,8,10-11")
+
+    def test_summarize_and_spread(self):
+        irqs = [dict(irq, cpus=[0]) for irq in parse_interrupts(self.INTERRUPTS, interface="eth0")]
+        queues = [{"queue": 0, "rx_packets": 900, "tx_packets": 50}, {"queue": 1, "rx_packets": 50, "tx_packets": 0}]
+        summary = summarize_queues("eth0", {"queues": queues, "irqs": irqs, "cpus": [0, 1, 2, 3]})
+        self.assertEqual([q["share"] for q in summary["queues"]], [0.95, 0.05])
+        self.assertEqual(summary["irq_cpus"], [0])
+        self.assertEqual(len(summary["warnings"]), 2)
+        self.assertIn("go to CPU 0", summary["warnings"][0])
+
+        self.assertEqual(spread_plan(irqs, [0, 2, 4]), {128: [0], 129: [2], 130: [4]})
+        balanced = [dict(irq, cpus=cpus) for irq, cpus in zip(irqs, spread_plan(irqs, [0, 1]).values())]
+        queues[1]["rx_packets"] = 950
+        self.assertEqual(summarize_queues("eth0", {"queues": queues, "irqs": balanced, "cpus": [0, 1]})["warnings"], [])
+
+    def test_write_affinity(self):
+        with tempfile.TemporaryDirectory() as directory:
+            (Path(directory) / "129").mkdir()
+            self.assertTrue(write_affinity(129, [3, 1, 2], Path(directory)))
+            self.assertEqual((Path(directory) / "129" / "smp_affinity_list").read_text(), "1-3")
+            self.assertFalse(write_affinity(130, [0], Path(directory)))
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,35 @@
+"""
+nl80211 - regulatory domain requests and replies, built and parsed without a kernel
+"""
+
+import sys
+import struct
+import unittest
+from pathlib import Path
+
+REPO_ROOT = Path(__file__).resolve().parents[2]
+sys.path.insert(0, str(REPO_ROOT / "src" / "alopex-qt"))
+
+from network import netlink, nl80211
+
+class RegulatoryDomainTest(unittest.TestCase):
+    def test_nl80211_domain(self):
+        request = nl80211.alpha2_attr("DE")
+        self.assertEqual(netlink.attributes(request), {nl80211.NL80211_ATTR_REG_ALPHA2: b"DE\0"})
+        message = netlink.genl_message(28, nl80211.NL80211_CMD_REQ_SET_REG, nl80211.FAMILY_VERSION, request, seq=3)
+        self.assertEqual(struct.unpack_from("=IHHII", message), (len(message), 28, 0x5, 3, 0))
+        self.assertEqual(message[16], nl80211.NL80211_CMD_REQ_SET_REG)
+        # A GET_REG reply also carries the rules, nested under NL80211_ATTR_REG_RULES
+        reply = netlink.genl_message(28, nl80211.NL80211_CMD_GET_REG, 0, request
+                                     + netlink.attr(nl80211.NL80211_ATTR_DFS_REGION, b"\x02")
+                                     + netlink.attr(34 | netlink.NLA_F_NESTED, b""), flags=0)
+        replies, done = netlink.genl_replies(reply, "nl80211")
+        self.assertFalse(done)
+        self.assertEqual(nl80211.parse_regulatory_domain(replies[0]), {"country": "DE", "dfs_region": "DFS-ETSI"})
+        world = netlink.attr(nl80211.NL80211_ATTR_REG_ALPHA2, b"00\0")
+        self.assertEqual(nl80211.parse_regulatory_domain(netlink.attributes(world)),
+                         {"country": "00", "dfs_region": None})
+        self.assertIsNone(nl80211.parse_regulatory_domain({}))
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,273 @@
+"""
+Notifications - alerts from events, templates, sinks, rate limits and quiet hours
+"""
+
+import sys
+import json
+import datetime
+import asyncio
+import threading
+import unittest
+from pathlib import Path
+
+REPO_ROOT = Path(__file__).resolve().parents[2]
+sys.path.insert(0, str(REPO_ROOT / "src" / "alopex-qt"))
+
+from network.events import EventBus
+from network import notifications
+from network.notifications import (
+    NtfySink, Notifier, Sink, SmtpSink, Webhook, derive_alerts, in_quiet_hours, load_sinks, parse_quiet_hours, render
+)
+
+class NotificationsTest(unittest.TestCase):
+    TYPES = {"office": "ethernet", "work-vpn": "vpn", "share": "hotspot"}
+
+    @staticmethod
+    def state(profile, previous="configured", state="failed", reason="carrier lost"):
+        return {"event": "InterfaceStateChanged", "interface": "eth0", "profile": profile, "previous": previous,
+                "state": state, "reason": reason, "timestamp": 1_700_000_000.0}
+
+    def test_derive_alerts(self):
+        types = self.TYPES.get
+        self.assertEqual(derive_alerts(self.state("office"), types), ["wan_down"])
+        self.assertEqual(derive_alerts(self.state("work-vpn", previous="acquiring"), types), ["vpn_down"])
+        self.assertEqual(derive_alerts(self.state("share"), types), [])
+        # Asked for: taking a connection down passes through deactivating
+        self.assertEqual(derive_alerts(self.state("office", previous="deactivating", state="disconnected"),
+                                       types), [])
+        self.assertEqual(derive_alerts(self.state("office", reason="cancelled"), types), [])
+        self.assertEqual(derive_alerts({"event": "QuotaExceeded", "timestamp": 0}, types), ["quota_exceeded"])
+
+    def test_render(self):
+        fields = {"title": "Down", "used": 1100, "fields": {"interface": "eth0"}, "reason": None}
+        body = render({"text": "{title} ({reason})", "used": "{used}", "iface": "{fields[interface]}",
+                       "missing": "{nope}", "tags": ["{title}", 3]}, fields)
+        self.assertEqual(body, {"text": "Down ()", "used": 1100, "iface": "eth0", "missing": "", "tags": ["Down", 3]})
+        self.assertEqual(render("{fields[gone]}", fields), "{fields[gone]}")
+        with self.assertRaises(ValueError):
+            load_sinks({"webhooks": [{"url": "http://localhost/hook", "events": ["wan_down"],
+                                      "body": {"text": "{title"}}]})
+        with self.assertRaises(ValueError):
+            load_sinks({"webhooks": [{"url": "file:///tmp/x", "events": ["wan_down"]}]})
+        with self.assertRaises(ValueError):
+            load_sinks({"webhooks": [{"url": "http://localhost/hook"}]})
+
+    def test_load_sinks(self):
+        sinks = load_sinks({"rate_limit": "5/minute", "quiet_hours": {"start": "22:00", "end": "07:00"},
+                            "ntfy": [{"topic": "alopex-home", "events": ["wan_down"], "token": "tk_secret"}],
+                            "smtp": [{"host": "mail.example.com", "from": "alopex@example.com", "to": "me@example.com",
+                                      "events": ["*"], "port": 465, "password": "hunter2", "rate_limit": None}]})
+        ntfy, smtp = sinks
+        self.assertEqual((ntfy.name, ntfy.rate_limit, ntfy.quiet_hours["allow"]), ("ntfy1", (5, 60.0), ["critical"]))
+        self.assertEqual((smtp.tls, smtp.to, smtp.rate_limit), ("ssl", ["me@example.com"], None))
+        self.assertEqual(ntfy.stats()["url"], "https://ntfy.sh/alopex-home")
+        self.assertNotIn("hunter2", json.dumps(smtp.stats()))
+        self.assertNotIn("tk_secret", json.dumps(ntfy.stats()))
+        for bad in ({"rate_limit": "lots"}, {"quiet_hours": {"start": "25:00", "end": "07:00"}},
+                    {"quiet_hours": {"start": "22:00", "end": "07:00", "allow": ["loud"]}}):
+            with self.assertRaises(ValueError):
+                load_sinks(dict(bad, ntfy=[{"topic": "t", "events": ["wan_down"]}]))
+        with self.assertRaises(ValueError):
+            load_sinks({"ntfy": [{"name": "a", "topic": "t", "events": ["x"]},
+                                 {"name": "a", "topic": "u", "events": ["x"]}]})
+        with self.assertRaises(ValueError):
+            load_sinks({"smtp": [{"host": "mail.example.com", "events": ["x"], "to": ["me@example.com"]}]})
+
+    def test_rate_limit_and_quiet_hours(self):
+        class Recorder(Sink):
+            kind = "recorder"
+
+            def __init__(self, **limits):
+                super().__init__("rec", ["*"], **limits)
+                self.delivered = []
+
+            def deliver(self, fields):
+                self.delivered.append(fields["title"])
+
+        night = datetime.datetime(2026, 10, 15, 23, 30).timestamp()
+        quiet = parse_quiet_hours({"start": "22:00", "end": "07:00"})
+        self.assertTrue(in_quiet_hours(quiet, night))
+        self.assertFalse(in_quiet_hours(quiet, datetime.datetime(2026, 10, 15, 7, 0).timestamp()))
+        self.assertTrue(in_quiet_hours(parse_quiet_hours({"start": "12:00", "end": "13:00"}),
+                                       datetime.datetime(2026, 10, 15, 12, 59).timestamp()))
+
+        limited = Recorder(rate_limit="2/hour")
+
+        async def burst(sink, count, severity="info", force=False):
+            return [await sink.send({"title": f"n{i}", "severity": severity, "alert": "x"}, force=force)
+                    for i in range(count)]
+
+        self.assertEqual(asyncio.run(burst(limited, 3)), [True, True, False])
+        # Tests go out regardless, and don't use up the allowance
+        self.assertEqual(asyncio.run(burst(limited, 1, force=True)), [True])
+        self.assertEqual((limited.delivered, limited.suppressed), (["n0", "n1", "n0"], 1))
+        limited.recent[0] -= 3600
+        self.assertEqual(asyncio.run(burst(limited, 2)), [True, False])
+
+        # Quiet from an hour ago to an hour from now: only critical notifications get through
+        start, end = ((datetime.datetime.now() + datetime.timedelta(hours=hours)).strftime("%H:%M")
+                      for hours in (-1, 1))
+        sleepy = Recorder(rate_limit=None, quiet_hours={"start": start, "end": end})
+        self.assertEqual(asyncio.run(burst(sleepy, 2)), [False, False])
+        self.assertEqual(asyncio.run(burst(sleepy, 1, "critical")), [True])
+        self.assertEqual(sleepy.stats()["quiet_hours"], f"{start}-{end}")
+
+    def test_ntfy_and_smtp_formatting(self):
+        fields = {"title": "Quota used up", "message": "", "severity": "warning", "alert": "quota_exceeded",
+                  "host": "gw", "time": "2026-10-15 23:30", "timestamp": 1_790_000_000.0,
+                  "fields": {"interface": "wwan0", "used": 5_000_000_000, "profile": None, "tags": []}}
+        ntfy = NtfySink("phone", "alopex-home", ["quota_exceeded"], server="https://ntfy.example/", rate_limit="1/hour")
+        self.assertEqual(ntfy.payload(fields), {"topic": "alopex-home", "title": "Quota used up",
+                                                "message": "Quota used up", "priority": 4,
+                                                "tags": ["warning", "quota_exceeded"]})
+        self.assertEqual(ntfy.payload(dict(fields, severity="critical"))["priority"], 5)
+        self.assertEqual(ntfy.stats()["url"], "https://ntfy.example/alopex-home")
+        with self.assertRaises(ValueError):
+            NtfySink("phone", "a/b", ["quota_exceeded"])
+
+        smtp = SmtpSink("mail", "mail.example.com", "alopex@example.com", "noc@example.com", ["*"], port=587)
+        message = smtp.message(fields)
+        self.assertEqual((smtp.tls, message["Subject"], message["To"]),
+                         ("starttls", "[alopex] gw: Quota used up", "noc@example.com"))
+        body = message.get_content()
+        self.assertIn("interface: wwan0\nused: 5000000000\n", body)
+        # Empty fields are left out
+        self.assertNotIn("profile", body)
+        self.assertTrue(body.endswith("warning quota_exceeded on gw at 2026-10-15 23:30\n"))
+        with self.assertRaises(ValueError):
+            SmtpSink("mail", "mail.example.com", "alopex@example.com", [], ["*"])
+        with self.assertRaises(ValueError):
+            SmtpSink("mail", "mail.example.com", "alopex@example.com", "noc@example.com", ["*"], tls="smtps")
+
+        # One an hour: the second is held back until the first ages out
+        ntfy.recent.append(1_790_000_000.0)
+        self.assertEqual(ntfy.held_back(fields, 1_790_000_010.0), "rate limit")
+        self.assertIsNone(ntfy.held_back(fields, 1_790_003_601.0))
+
+    def test_ntfy_and_smtp(self):
+        import socketserver
+        from http.server import BaseHTTPRequestHandler, HTTPServer
+        published, mail = [], []
+
+        class Ntfy(BaseHTTPRequestHandler):
+            def do_POST(self):
+                published.append((self.path, self.headers.get("Authorization"),
+                                  json.loads(self.rfile.read(int(self.headers["Content-Length"])))))
+                self.send_response(200)
+                self.end_headers()
+
+            def log_message(self, *args):
+                pass
+
+        class Smtp(socketserver.StreamRequestHandler):
+            """Just enough SMTP for smtplib to hand over one message"""
+
+            def handle(self):
+                self.wfile.write(b"220 test ESMTP\r\n")
+                while True:
+                    line = self.rfile.readline().decode().strip()
+                    verb = line.split(" ")[0].upper()
+                    if verb in ("EHLO", "HELO"):
+                        self.wfile.write(b"250 test\r\n")
+                    elif verb == "DATA":
+                        self.wfile.write(b"354 go ahead\r\n")
+                        lines = []
+                        while (data := self.rfile.readline()) != b".\r\n":
+                            lines.append(data.decode())
+                        mail.append("".join(lines))
+                        self.wfile.write(b"250 queued\r\n")
+                    elif verb == "QUIT" or not line:
+                        self.wfile.write(b"221 bye\r\n")
+                        return
+                    else:
+                        self.wfile.write(b"250 ok\r\n")
+
+        http = HTTPServer(("127.0.0.1", 0), Ntfy)
+        smtp = socketserver.TCPServer(("127.0.0.1", 0), Smtp)
+        for server in (http, smtp):
+            threading.Thread(target=server.serve_forever, daemon=True).start()
+        try:
+            notifier = Notifier(EventBus(), load_sinks({
+                "ntfy": [{"topic": "alopex-home", "server": f"http://127.0.0.1:{http.server_port}",
+                          "token": "tk_secret", "events": ["wan_down"]}],
+                "smtp": [{"name": "mail", "host": "127.0.0.1", "port": smtp.server_address[1], "tls": "none",
+                          "from": "alopex@example.com", "to": ["noc@example.com"], "events": ["wan_down"]}]
+            }), self.TYPES.get, host="gw")
+
+            async def scenario():
+                sent = await asyncio.gather(*notifier.dispatch(self.state("office")))
+                return sent, await notifier.test("ntfy1")
+
+            sent, tested = asyncio.run(scenario())
+            with self.assertRaises(KeyError):
+                asyncio.run(notifier.test("nope"))
+        finally:
+            for server in (http, smtp):
+                server.shutdown()
+                server.server_close()
+        self.assertEqual(sent, [True, True])
+        self.assertEqual(tested, [{"name": "ntfy1", "kind": "ntfy", "sent": True, "error": None}])
+        path, auth, payload = published[0]
+        self.assertEqual((path, auth), ("/", "Bearer tk_secret"))
+        self.assertEqual(payload, {"topic": "alopex-home", "title": "Connection office went down",
+                                   "message": "eth0: carrier lost", "priority": 5,
+                                   "tags": ["rotating_light", "wan_down"]})
+        self.assertEqual(published[1][2]["title"], "Test notification from alopexd")
+        message, = mail
+        self.assertIn("Subject: [alopex] gw: Connection office went down", message)
+        self.assertIn("To: noc@example.com", message)
+        self.assertIn("profile: office", message)
+
+    def test_delivery(self):
+        from http.server import BaseHTTPRequestHandler, HTTPServer
+        received = []
+
+        class Handler(BaseHTTPRequestHandler):
+            def do_POST(self):
+                body = json.loads(self.rfile.read(int(self.headers["Content-Length"])))
+                received.append((self.path, self.headers.get("X-Token"), body))
+                self.send_response(500 if self.path == "/broken" else 200)
+                self.end_headers()
+
+            def log_message(self, *args):
+                pass
+
+        server = HTTPServer(("127.0.0.1", 0), Handler)
+        thread = threading.Thread(target=server.serve_forever, daemon=True)
+        thread.start()
+        base = f"http://127.0.0.1:{server.server_port}"
+        slack = Webhook("slack", f"{base}/slack?token=abc", ["wan_down", "vpn_down"], {"text": "{title}: {message}"},
+                        headers={"X-Token": "abc"})
+        audit = Webhook("audit", f"{base}/audit", ["Dhcp*"])
+        broken = Webhook("broken", f"{base}/broken", ["wan_down"])
+        bus = EventBus()
+        notifier = Notifier(bus, [slack, audit, broken], self.TYPES.get, host="gw")
+
+        async def scenario():
+            tasks = notifier.dispatch(self.state("office"))
+            tasks += notifier.dispatch({"event": "DhcpLeaseChanged", "interface": "eth0", "address": "10.0.0.5",
+                                        "timestamp": 1_700_000_000.0})
+            tasks += notifier.dispatch({"event": "JobProgress", "timestamp": 1_700_000_000.0})
+            return await asyncio.gather(*tasks)
+
+        delay, notifications.RETRY_DELAY = notifications.RETRY_DELAY, 0
+        try:
+            self.assertEqual(asyncio.run(scenario()), [True, False, True])
+        finally:
+            notifications.RETRY_DELAY = delay
+            server.shutdown()
+            server.server_close()
+        by_path = {path.split("?")[0]: (token, body) for path, token, body in received}
+        self.assertEqual(by_path["/slack"], ("abc", {"text": "Connection office went down: eth0: carrier lost"}))
+        body = by_path["/audit"][1]
+        self.assertEqual((body["title"], body["alert"], body["host"], body["severity"]),
+                         ("DhcpLeaseChanged", "DhcpLeaseChanged", "gw", "info"))
+        # The first try plus every retry
+        self.assertEqual(sum(1 for path, _, _ in received if path == "/broken"), notifications.RETRIES + 1)
+        stats = {sink["name"]: sink for sink in notifier.stats()["sinks"]}
+        self.assertEqual((stats["slack"]["sent"], stats["broken"]["failures"]), (1, 1))
+        self.assertEqual(stats["broken"]["last_error"], "HTTP 500")
+        self.assertNotIn("abc", stats["slack"]["url"])
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,97 @@
+"""
+OUI vendor lookup - system lists, the bundled table and how make builds it
+"""
+
+import os
+import sys
+import tempfile
+import unittest
+import subprocess
+from pathlib import Path
+
+REPO_ROOT = Path(__file__).resolve().parents[2]
+sys.path.insert(0, str(REPO_ROOT / "src" / "alopex-qt"))
+
+from network import oui
+
+class OuiLookupTest(unittest.TestCase):
+    def setUp(self):
+        self.tmp = tempfile.TemporaryDirectory()
+        oui_file = Path(self.tmp.name) / "oui.txt"
+        oui_file.write_text(
+            "OUI/MA-L                                                    Organization\n"
+            "00-1B-21   (hex)\t\tIntel Corporate\n"
+            "001B21     (base 16)\t\tIntel Corporate\n"
+            "A4-34-D9   (hex)\t\tIntel Corporate\n"
+        )
+        os.environ["ALOPEX_OUI_FILE"] = str(oui_file)
+        oui._vendors = None
+
+    def tearDown(self):
+        os.environ.pop("ALOPEX_OUI_FILE", None)
+        oui._vendors = None
+        self.tmp.cleanup()
+
+    def test_vendor_lookup(self):
+        self.assertEqual(oui.lookup_vendor("00:1b:21:3a:4c:50"), "Intel Corporate")
+        self.assertIsNone(oui.lookup_vendor("00:00:5e:00:53:01"))
+        # Randomized addresses have no vendor
+        self.assertTrue(oui.is_locally_administered("da:a1:19:5e:0c:42"))
+        self.assertIsNone(oui.lookup_vendor("da:a1:19:5e:0c:42"))
+
+    def test_bundled_table(self):
+        registry = Path(self.tmp.name) / "oui36.csv"
+        registry.write_text(
+            "Registry,Assignment,Organization Name,Organization Address\n"
+            "MA-S,70B3D5F2C,\"Acme   Sensors, Inc.\",\"1 Main St  Springfield  US 00000 \"\n"
+            "MA-L,70B3D5,IEEE Registration Authority,445 Hoes Lane  Piscataway  NJ  US 08554\n"
+        )
+        table = Path(self.tmp.name) / "data" / "oui.tsv.gz"
+        # The system list comes first and wins for prefixes both have
+        self.assertEqual(oui.build_table([Path(os.environ["ALOPEX_OUI_FILE"]), registry], table), 4)
+        self.assertEqual(oui.read_source(registry)["70B3D5F2C"], "Acme Sensors, Inc.")
+
+        # No system list: the bundled table it is
+        os.environ.pop("ALOPEX_OUI_FILE")
+        files, bundled = oui.OUI_FILES, oui.BUNDLED_TABLE
+        oui.OUI_FILES, oui.BUNDLED_TABLE = [], table
+        try:
+            self.assertEqual(oui.oui_file(), table)
+            self.assertEqual(oui.lookup_vendor("00:1b:21:3a:4c:50"), "Intel Corporate")
+            # An MA-S block inside the IEEE's own MA-L goes to its holder
+            self.assertEqual(oui.lookup_vendor("70:b3:d5:f2:c0:01"), "Acme Sensors, Inc.")
+            self.assertEqual(oui.lookup_vendor("70:b3:d5:11:00:01"), "IEEE Registration Authority")
+        finally:
+            oui.OUI_FILES, oui.BUNDLED_TABLE = files, bundled
+
+class OuiTableBuildTest(unittest.TestCase):
+    def test_make_builds_a_loadable_table(self):
+        # The install targets depend on this rule; registry files already present skip the download
+        with tempfile.TemporaryDirectory() as directory:
+            sources = []
+            for name, rows in (("oui.csv", "MA-L,001B21,Intel Corporate,x\n"),
+                               ("mam.csv", "MA-M,F41E5E1,Northern Radio,x\n"),
+                               ("oui36.csv", "MA-S,70B3D5F2C,\"Acme Sensors, Inc.\",x\n")):
+                source = Path(directory) / name
+                source.write_text("Registry,Assignment,Organization Name,Organization Address\n" + rows)
+                sources.append(str(source))
+            table = Path(directory) / "data" / "oui.tsv.gz"
+            result = subprocess.run(["make", "-C", str(REPO_ROOT / "src"), "-f", str(REPO_ROOT / "Makefile"),
+                                     "oui-table", f"OUI_SOURCES={' '.join(sources)}", f"OUI_TABLE={table}"],
+                                    capture_output=True, text=True, timeout=60)
+            self.assertEqual(result.returncode, 0, result.stderr)
+
+            files, bundled, override = oui.OUI_FILES, oui.BUNDLED_TABLE, os.environ.pop("ALOPEX_OUI_FILE", None)
+            oui.OUI_FILES, oui.BUNDLED_TABLE, oui._vendors = [], table, None
+            try:
+                self.assertEqual(oui.oui_file(), table)
+                self.assertEqual(oui.lookup_vendor("00:1b:21:3a:4c:50"), "Intel Corporate")
+                self.assertEqual(oui.lookup_vendor("f4:1e:5e:10:00:01"), "Northern Radio")
+                self.assertEqual(oui.lookup_vendor("70:b3:d5:f2:c0:01"), "Acme Sensors, Inc.")
+            finally:
+                oui.OUI_FILES, oui.BUNDLED_TABLE, oui._vendors = files, bundled, None
+                if override is not None:
+                    os.environ["ALOPEX_OUI_FILE"] = override
+
+if __name__ == "__main__":
+    unittest.main()
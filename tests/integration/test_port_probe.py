@@ -0,0 +1,37 @@
+"""
+Port probe - target parsing and open/closed probes against a loopback listener
+"""
+
+import sys
+import socket
+import asyncio
+import unittest
+from pathlib import Path
+
+REPO_ROOT = Path(__file__).resolve().parents[2]
+sys.path.insert(0, str(REPO_ROOT / "src" / "alopex-qt"))
+
+from network.port_probe import parse_target, probe as probe_port
+
+class PortProbeTest(unittest.TestCase):
+    def test_parse_target(self):
+        self.assertEqual(parse_target("example.com:443"), ("example.com", 443))
+        self.assertEqual(parse_target("[2001:db8::1]:22"), ("2001:db8::1", 22))
+        self.assertEqual(parse_target("10.0.0.1 8080"), ("10.0.0.1", 8080))
+        for text in ("example.com", "example.com:0", "host:http", ":80"):
+            with self.assertRaises(ValueError):
+                parse_target(text)
+
+    def test_probe_open_and_closed(self):
+        with socket.socket() as listener:
+            listener.bind(("127.0.0.1", 0))
+            listener.listen()
+            port = listener.getsockname()[1]
+            result = asyncio.run(probe_port("127.0.0.1", port, "127.0.0.1", timeout=2.0))
+            self.assertEqual((result["state"], result["address"]), ("open", "127.0.0.1"))
+            self.assertIsNotNone(result["latency_ms"])
+        # Nothing listens there any more
+        self.assertEqual(asyncio.run(probe_port("127.0.0.1", port, timeout=2.0))["state"], "closed")
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,61 @@
+"""
+Quota - size parsing, period starts and one QuotaExceeded event per period
+"""
+
+import sys
+import datetime
+import tempfile
+import unittest
+from pathlib import Path
+
+REPO_ROOT = Path(__file__).resolve().parents[2]
+sys.path.insert(0, str(REPO_ROOT / "src" / "alopex-qt"))
+
+from network.events import EventBus
+from network.quota import QuotaMonitor, parse_size, period_start
+
+class QuotaTest(unittest.TestCase):
+    class History:
+        def __init__(self, samples):
+            self.samples = samples
+
+        def query(self, interface, since=None, until=None):
+            return [s for s in self.samples.get(interface, []) if since is None or s["timestamp"] >= since]
+
+    def test_parse_size(self):
+        self.assertEqual(parse_size("20GB"), 20 * 10 ** 9)
+        self.assertEqual(parse_size("1.5 GiB"), int(1.5 * 2 ** 30))
+        self.assertEqual(parse_size(4096), 4096)
+        for bad in ("lots", "0", "-5GB"):
+            with self.assertRaises(ValueError):
+                parse_size(bad)
+
+    def test_exceeded_once_per_period(self):
+        now = datetime.datetime(2026, 10, 15, 12, 0).timestamp()
+        self.assertEqual(period_start("month", now), datetime.datetime(2026, 10, 1).timestamp())
+        self.assertEqual(period_start("week", now), datetime.datetime(2026, 10, 12).timestamp())
+        samples = {"wwan0": [
+            # Last month's traffic doesn't count against this month
+            {"timestamp": datetime.datetime(2026, 9, 30, 23, 0).timestamp(), "rx_bytes": 10 ** 9, "tx_bytes": 0},
+            {"timestamp": datetime.datetime(2026, 10, 2, 9, 0).timestamp(), "rx_bytes": 400, "tx_bytes": 200}
+        ]}
+        bus = EventBus()
+        queue = bus.subscribe(["QuotaExceeded"])
+        with tempfile.TemporaryDirectory() as tmp:
+            monitor = QuotaMonitor(self.History(samples), [{"interface": "wwan0", "limit": "1kB"}], Path(tmp), bus)
+            usage, = monitor.check(now)
+            self.assertEqual((usage["used"], usage["percent"], usage["exceeded"]), (600, 60.0, False))
+
+            samples["wwan0"].append({"timestamp": now - 60, "rx_bytes": 500, "tx_bytes": 0})
+            self.assertTrue(monitor.check(now)[0]["exceeded"])
+            monitor.check(now + 60)
+            # Reported periods survive a restart
+            QuotaMonitor(self.History(samples), [{"interface": "wwan0", "limit": "1kB"}], Path(tmp), bus).check(now)
+        self.assertEqual(queue.queue.qsize(), 1)
+        event = queue.queue.get_nowait()
+        self.assertEqual((event["interface"], event["used"], event["limit"]), ("wwan0", 1100, 1000))
+        with self.assertRaises(ValueError):
+            QuotaMonitor(self.History({}), [{"interface": "eth0", "limit": "1GB", "period": "year"}], Path("."))
+
+if __name__ == "__main__":
+    unittest.main()
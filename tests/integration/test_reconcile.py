@@ -0,0 +1,65 @@
+"""
+Reconcile - the plan alopexd apply makes from ip -j output and the interfaces section
+"""
+
+import sys
+import json
+import unittest
+from pathlib import Path
+
+REPO_ROOT = Path(__file__).resolve().parents[2]
+sys.path.insert(0, str(REPO_ROOT / "src" / "alopex-qt"))
+
+from network import reconcile
+
+class ReconcileTest(unittest.TestCase):
+    """What `alopexd apply` would change, from ip -j output and the interfaces section"""
+
+    ADDRESSES = json.dumps([{"ifname": "eth0", "addr_info": [
+        {"family": "inet", "local": "192.0.2.10", "prefixlen": 24, "scope": "global"},
+        {"family": "inet", "local": "192.0.2.99", "prefixlen": 24, "scope": "global"},
+        {"family": "inet", "local": "198.51.100.7", "prefixlen": 24, "scope": "global", "dynamic": True},
+        {"family": "inet6", "local": "fe80::1", "prefixlen": 64, "scope": "link"}
+    ]}])
+    ROUTES = json.dumps([
+        {"dst": "default", "gateway": "198.51.100.1", "protocol": "dhcp", "metric": 100, "flags": []},
+        {"dst": "192.0.2.0/24", "protocol": "kernel", "scope": "link", "prefsrc": "192.0.2.10", "flags": []},
+        {"dst": "10.0.0.0/8", "gateway": "192.0.2.254", "flags": []},
+        {"dst": "172.16.0.0/12", "gateway": "192.0.2.254", "protocol": "static", "metric": 50, "flags": []}
+    ])
+
+    def test_plan_leaves_dynamic_and_kernel_state_alone(self):
+        actual = {"addresses": reconcile.parse_addresses(self.ADDRESSES), "routes": reconcile.parse_routes(self.ROUTES)}
+        self.assertEqual([entry["address"] for entry in actual["addresses"]],
+                         ["192.0.2.10/24", "192.0.2.99/24", "198.51.100.7/24"])
+        desired = reconcile.parse_interfaces({"eth0": {
+            "addresses": ["192.0.2.10/24", "2001:db8::10/64"],
+            "routes": [{"to": "10.0.0.0/8", "via": "192.0.2.254"}, {"to": "2001:db8:1::/48", "via": "2001:db8::1"}]
+        }})["eth0"]
+        changes = reconcile.plan("eth0", desired, actual)
+        self.assertEqual([reconcile.format_change(change) for change in changes], [
+            "- eth0 route 172.16.0.0/12 via 192.0.2.254 metric 50",
+            "- eth0 address 192.0.2.99/24",
+            "+ eth0 address 2001:db8::10/64",
+            "+ eth0 route 2001:db8:1::/48 via 2001:db8::1"
+        ])
+        self.assertEqual(reconcile.ip_arguments(changes[0]),
+                         ["route", "del", "172.16.0.0/12", "via", "192.0.2.254", "dev", "eth0", "metric", "50"])
+        self.assertEqual(reconcile.ip_arguments(changes[3])[:3], ["-6", "route", "add"])
+        # Once made, nothing is left to do
+        actual = {"addresses": [{"address": address, "dynamic": False} for address in desired["addresses"]],
+                  "routes": [dict(route, protocol="boot", metric=1024) for route in desired["routes"]]}
+        self.assertEqual(reconcile.plan("eth0", desired, actual), [])
+
+    def test_bad_sections(self):
+        for section, message in (({"eth0": {"addresses": ["192.0.2.10"]}}, "prefix length"),
+                                 ({"eth0": {"routes": [{"via": "192.0.2.1"}]}}, "needs a \"to\""),
+                                 ({"eth0": {"routes": [{"to": "default", "via": "gateway"}]}}, "eth0"),
+                                 ({"eth0": {"routes": [{"to": "default", "metric": -1}]}}, "metric"),
+                                 (["eth0"], "interfaces must map")):
+            with self.assertRaises(ValueError) as invalid:
+                reconcile.parse_interfaces(section)
+            self.assertIn(message, str(invalid.exception))
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,102 @@
+"""
+Relay - fleet hosts reached through another daemon, and the relay serving them
+Downstream daemons run in-process over mutual TLS; the relay case needs the openssl CLI
+"""
+
+import sys
+import shutil
+import asyncio
+import tempfile
+import unittest
+from pathlib import Path
+
+REPO_ROOT = Path(__file__).resolve().parents[2]
+sys.path.insert(0, str(REPO_ROOT / "src" / "alopex-qt"))
+
+from network.events import EventBus
+from network.fleet import parse_fleet
+from network.ipc import ErrorCode, IpcError, IpcServer, server_tls_context
+from network.relay import Relay
+from test_fleet import make_certificates
+
+class RelayTest(unittest.TestCase):
+    def test_fleet_via(self):
+        hosts = parse_fleet({"cert_file": "me.pem", "hosts": [
+            {"name": "gw", "address": "tls://gw.lan"},
+            {"name": "nas", "via": "gw"},
+            {"name": "printer", "via": "local"}
+        ]})
+        nas, printer = hosts[2], hosts[3]
+        self.assertFalse(nas.local or printer.local)
+        self.assertEqual(nas.address, "nas via tls://gw.lan:7443")
+        client = nas.client(timeout=2.0)
+        self.assertEqual((client.remote, client.relayed), (hosts[1].remote, "nas"))
+        self.assertEqual(client.with_timeout(9.0).relayed, "nas")
+        self.assertIsNone(printer.client().remote)
+        for spec in ({"via": "gw"}, {"name": "nas", "via": "router"}, {"name": "nas", "via": "nas"}):
+            with self.assertRaises(ValueError):
+                parse_fleet({"cert_file": "me.pem", "hosts": [{"name": "gw", "address": "tls://gw.lan"}, spec]})
+        with self.assertRaises(ValueError):
+            parse_fleet({"hosts": [{"name": "nas", "via": "local"}, {"name": "tv", "via": "nas"}]})
+        with self.assertRaises(ValueError):
+            Relay({"hosts": [{"name": "nas", "via": "local"}]}, dict, list)
+        with self.assertRaises(ValueError):
+            Relay({}, dict, list)
+
+    @unittest.skipUnless(shutil.which("openssl"), "needs the openssl CLI")
+    def test_relay(self):
+        with tempfile.TemporaryDirectory() as directory:
+            directory = Path(directory)
+            make_certificates(directory)
+
+            async def scenario():
+                downstream = IpcServer(directory / "nas.sock")
+                downstream.register("GetStatus", lambda: {"hostname": "nas", "connected_interfaces": 1})
+                downstream.register("GetInterfaces", lambda: [{"name": "eth0", "ip": "10.0.0.5"}])
+                downstream.register("GetRoutes", lambda interface=None: [{"dst": "default", "dev": interface}])
+                context = server_tls_context(str(directory / "ca.pem"), str(directory / "localhost.pem"),
+                                             str(directory / "localhost.key"))
+                await downstream.start_remote("127.0.0.1", 0, context)
+                port = downstream.remote_server.sockets[0].getsockname()[1]
+
+                bus = EventBus()
+                changes = bus.subscribe(["RelayHostChanged"])
+                relay = Relay({"ca_file": str(directory / "ca.pem"), "cert_file": str(directory / "laptop.pem"),
+                               "key_file": str(directory / "laptop.key"), "timeout": 2.0, "hosts": [
+                                   {"name": "nas", "address": f"tls://localhost:{port}"},
+                                   {"name": "tv", "address": "tls://127.0.0.1:1"}
+                               ]}, lambda: {"hostname": "relay"}, lambda: [{"name": "wan0", "ip": "192.0.2.1"}], bus)
+                await relay.poll()
+
+                # The relay as a client on its control socket sees it, over RelayRequest
+                server = IpcServer(directory / "relay.sock")
+                server.register("RelayRequest", lambda host, relayed, params=None: relay.forward(host, relayed, params))
+                await server.start()
+                loop = asyncio.get_running_loop()
+                client = parse_fleet({"hosts": [{"name": "nas", "via": "local"}]})[1].client(directory / "relay.sock")
+                results = [await loop.run_in_executor(None, lambda: client.request("GetRoutes", interface="eth0"))]
+                try:
+                    await loop.run_in_executor(None, lambda: client.request("Disconnect", interface="eth0"))
+                except IpcError as e:
+                    results.append(e.code)
+                await server.stop()
+                await downstream.stop()
+                return relay, changes.queue.get_nowait(), results
+
+            relay, change, results = asyncio.run(scenario())
+        local, nas, tv = relay.hosts()
+        self.assertEqual((local["name"], local["status"], local["interfaces"]), (relay.name, {"hostname": "relay"}, 1))
+        self.assertEqual((nas["reachable"], nas["status"]["hostname"], nas["interfaces"]), (True, "nas", 1))
+        self.assertEqual((tv["reachable"], tv["address"], tv["updated"]), (False, "tls://127.0.0.1:1", None))
+        self.assertIn("tls://127.0.0.1:1", tv["error"])
+        self.assertEqual((change["host"], change["reachable"]), ("tv", False))
+        self.assertEqual([(iface["host"], iface["name"]) for iface in relay.interfaces()],
+                         [(relay.name, "wan0"), ("nas", "eth0")])
+        self.assertEqual(relay.interfaces("nas"), [{"name": "eth0", "ip": "10.0.0.5", "host": "nas"}])
+        with self.assertRaises(KeyError):
+            relay.interfaces("printer")
+        # Only requests that read state get through
+        self.assertEqual(results, [[{"dst": "default", "dev": "eth0"}], ErrorCode.PERMISSION_DENIED])
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,95 @@
+"""
+Remote syslog - RFC 5424 messages, redacted audit records and TCP framing
+"""
+
+import sys
+import asyncio
+import unittest
+from pathlib import Path
+
+REPO_ROOT = Path(__file__).resolve().parents[2]
+sys.path.insert(0, str(REPO_ROOT / "src" / "alopex-qt"))
+
+from network.events import EventBus
+from network.remote_syslog import SyslogForwarder, format_message, structured_data
+
+class RemoteSyslogTest(unittest.TestCase):
+    def test_format(self):
+        message = format_message(3, 6, 1790000000.25, "CarrierChanged", {"interface": "eth0", "carrier": False},
+                                 "CarrierChanged interface=eth0 carrier=false", hostname="host", procid=42)
+        self.assertEqual(message.decode(), '<30>1 2026-09-21T14:13:20.250Z host alopexd 42 CarrierChanged '
+                                           '[alopex@32473 interface="eth0" carrier="false"] '
+                                           'CarrierChanged interface=eth0 carrier=false')
+        self.assertEqual(structured_data({'bad key="x"': 'a"b]c\\', "none": None}),
+                         '[alopex@32473 badkeyx="a\\"b\\]c\\\\" none="-"]')
+        self.assertEqual(structured_data({}), "-")
+
+    def test_audit_redacts(self):
+        forwarder = SyslogForwarder("udp://127.0.0.1", EventBus(), facility="local4")
+        message = forwarder.format_audit({
+            "timestamp": 1790000000.0, "request": "CreateProfile", "client_uid": 1000, "request_id": 7,
+            "params": {"name": "HQ", "password": "arctic-fox", "dns": ["9.9.9.9"]},
+            "outcome": "PermissionDenied", "message": "Refused by policy"
+        }).decode()
+        self.assertTrue(message.startswith("<164>1 "))
+        self.assertIn(' audit [alopex@32473 request="CreateProfile" uid="1000" request_id="7" '
+                      'outcome="PermissionDenied" name="HQ" password="<redacted>" ', message)
+        self.assertTrue(message.endswith('CreateProfile by uid 1000: PermissionDenied: Refused by policy '
+                                         '(name=HQ password=<redacted> dns=["9.9.9.9"])'))
+        self.assertNotIn("arctic-fox", message)
+        message = forwarder.format_audit({
+            "timestamp": 1790000000.0, "request": "Disconnect", "client_uid": None, "client_cert": "laptop",
+            "request_id": 8, "params": {"interface": "eth0"}, "outcome": "ok"
+        }).decode()
+        self.assertIn('request="Disconnect" cert="laptop" request_id="8"', message)
+        self.assertTrue(message.endswith("Disconnect by certificate laptop: ok (interface=eth0)"))
+        with self.assertRaises(ValueError):
+            SyslogForwarder("http://collector", EventBus())
+        with self.assertRaises(ValueError):
+            SyslogForwarder("udp://collector", EventBus(), facility="mail2")
+
+    def test_tcp_framing(self):
+        async def scenario():
+            received = bytearray()
+
+            async def collect(reader, writer):
+                while data := await reader.read(4096):
+                    received.extend(data)
+
+            server = await asyncio.start_server(collect, "127.0.0.1", 0)
+            port = server.sockets[0].getsockname()[1]
+            bus = EventBus()
+            forwarder = SyslogForwarder(f"tcp://127.0.0.1:{port}", bus)
+            task = asyncio.create_task(forwarder.run())
+            await asyncio.sleep(0.05)
+            bus.publish("JobProgress", id=1, progress=0.5)
+            bus.publish("MeteredChanged", metered=True)
+            forwarder.audit({"timestamp": 1790000000.0, "request": "Disconnect", "client_uid": 0,
+                             "request_id": 3, "params": {"interface": "eth0"}, "outcome": "ok"})
+            for _ in range(50):
+                if forwarder.sent == 2:
+                    break
+                await asyncio.sleep(0.05)
+            await asyncio.sleep(0.05)
+            task.cancel()
+            await asyncio.gather(task, return_exceptions=True)
+            server.close()
+            await server.wait_closed()
+            return bytes(received), forwarder.stats()
+
+        received, stats = asyncio.run(scenario())
+        messages = []
+        while received:
+            length, _, rest = received.partition(b" ")
+            messages.append(rest[:int(length)].decode())
+            received = rest[int(length):]
+        self.assertEqual(len(messages), 2)
+        # Audit records are queued as they happen, events once the bus delivers them
+        self.assertIn(" audit ", messages[0])
+        self.assertTrue(messages[0].endswith("Disconnect by uid 0: ok (interface=eth0)"))
+        self.assertTrue(messages[1].startswith("<30>1 "))
+        self.assertTrue(messages[1].endswith("MeteredChanged metered=true"))
+        self.assertEqual((stats["sent"], stats["queued"], stats["last_error"]), (2, 0, None))
+
+if __name__ == "__main__":
+    unittest.main()
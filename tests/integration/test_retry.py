@@ -0,0 +1,42 @@
+"""
+Retry tracker - exponential backoff for failing auto-connects and when it gives up
+"""
+
+import sys
+import unittest
+from pathlib import Path
+
+REPO_ROOT = Path(__file__).resolve().parents[2]
+sys.path.insert(0, str(REPO_ROOT / "src" / "alopex-qt"))
+
+from network.retry import RetryTracker, backoff
+
+class RetryTrackerTest(unittest.TestCase):
+    def test_backoff(self):
+        self.assertEqual([backoff(attempt, 1, 30) for attempt in range(1, 8)], [1, 2, 4, 8, 16, 30, 30])
+
+    def test_gives_up(self):
+        retry = RetryTracker.from_config({"initial": 10, "max_delay": 15, "max_attempts": 3})
+        self.assertTrue(retry.due("HQ", now=100))
+        self.assertEqual(retry.failed("HQ", "wlan0", "wrong PSK", now=100)["next_attempt"], 110)
+        self.assertFalse(retry.due("HQ", now=105))
+        self.assertTrue(retry.due("HQ", now=110))
+        self.assertEqual(retry.failed("HQ", "wlan0", "wrong PSK", now=110)["next_attempt"], 125)
+        self.assertEqual(retry.next_attempt(["HQ", "Lab"]), 125)
+
+        entry = retry.failed("HQ", "wlan0", "DHCP timed out", now=125)
+        self.assertEqual((entry["gave_up"], entry["failures"], entry["last_error"]), (True, 3, "DHCP timed out"))
+        self.assertFalse(retry.due("HQ", now=10 ** 9))
+        self.assertIsNone(retry.next_attempt(["HQ"]))
+        self.assertTrue(retry.reset("HQ"))
+        self.assertTrue(retry.due("HQ", now=126))
+        self.assertEqual(retry.status(), [])
+
+    def test_never_gives_up(self):
+        retry = RetryTracker(1, 4, max_attempts=0)
+        for attempt in range(20):
+            entry = retry.failed("Lab", "eth1", None, now=0)
+        self.assertEqual((entry["gave_up"], entry["next_attempt"]), (False, 4))
+
+if __name__ == "__main__":
+    unittest.main()
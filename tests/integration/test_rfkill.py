@@ -0,0 +1,56 @@
+"""
+Rfkill - soft blocks read from and written to a fake /sys/class/rfkill tree
+"""
+
+import sys
+import tempfile
+import unittest
+from pathlib import Path
+
+REPO_ROOT = Path(__file__).resolve().parents[2]
+sys.path.insert(0, str(REPO_ROOT / "src" / "alopex-qt"))
+
+from network.rfkill import interface_block, read_devices, write_soft_block
+
+class RfkillTest(unittest.TestCase):
+    def setUp(self):
+        self.tmp = tempfile.TemporaryDirectory()
+        self.root = Path(self.tmp.name)
+        self.rfkill = self.root / "class" / "rfkill"
+        self.net = self.root / "class" / "net"
+        # rfkill0 -> phy0 -> its PCI device, which holds net/wlan0
+        phy = self.root / "devices" / "pci0" / "ieee80211" / "phy0"
+        (phy.parent.parent / "net" / "wlan0").mkdir(parents=True)
+        (phy / "device").parent.mkdir(parents=True)
+        (phy / "device").symlink_to(phy.parent.parent)
+        self.switch(0, "wlan", "phy0", soft=True, parent=phy)
+        self.switch(1, "bluetooth", "hci0", hard=True)
+        (self.net / "wlan0" / "phy80211").mkdir(parents=True)
+        (self.net / "wlan0" / "phy80211" / "rfkill0").symlink_to(self.rfkill / "rfkill0")
+
+    def tearDown(self):
+        self.tmp.cleanup()
+
+    def switch(self, index, kind, name, soft=False, hard=False, parent=None):
+        entry = self.rfkill / f"rfkill{index}"
+        entry.mkdir(parents=True)
+        for file, value in (("type", kind), ("name", name), ("soft", int(soft)), ("hard", int(hard))):
+            (entry / file).write_text(f"{value}\n")
+        if parent:
+            (entry / "device").symlink_to(parent)
+
+    def test_read_devices(self):
+        wlan, bluetooth = read_devices(self.rfkill)
+        self.assertEqual((wlan.type, wlan.name, wlan.interface, wlan.block), ("wlan", "phy0", "wlan0", "soft"))
+        self.assertEqual((bluetooth.interface, bluetooth.block), (None, "hard"))
+        self.assertEqual(interface_block("wlan0", self.net), "soft")
+
+    def test_soft_block_cannot_lift_hard_block(self):
+        self.assertTrue(write_soft_block(0, False, self.rfkill))
+        self.assertIsNone(interface_block("wlan0", self.net))
+        write_soft_block(1, False, self.rfkill)
+        self.assertEqual(read_devices(self.rfkill)[1].block, "hard")
+        self.assertFalse(write_soft_block(7, True, self.rfkill))
+
+if __name__ == "__main__":
+    unittest.main()
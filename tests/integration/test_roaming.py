@@ -0,0 +1,66 @@
+"""
+Roaming timeline - iw link output and the BSSID changes recorded from it
+"""
+
+import sys
+import tempfile
+import unittest
+from pathlib import Path
+
+REPO_ROOT = Path(__file__).resolve().parents[2]
+sys.path.insert(0, str(REPO_ROOT / "src" / "alopex-qt"))
+
+from network.events import EventBus
+from network.roaming import RoamingTimeline
+from network.wifi import parse_iw_link
+
+class RoamingTimelineTest(unittest.TestCase):
+    def setUp(self):
+        self.dir = tempfile.TemporaryDirectory()
+        self.events = EventBus()
+        self.timeline = RoamingTimeline(None, lambda: ["wlan0"], Path(self.dir.name), self.events)
+
+    def tearDown(self):
+        self.dir.cleanup()
+
+    def test_iw_link(self):
+        output = ("Connected to 3C:22:FB:10:20:30 (on wlan0)\n\tSSID: AlopexHQ\n\tfreq: 5180.0\n"
+                  "\tRX: 1234 bytes (10 packets)\n\tsignal: -52 dBm\n\trx bitrate: 866.7 MBit/s\n")
+        self.assertEqual(parse_iw_link(output), {"bssid": "3c:22:fb:10:20:30", "ssid": "AlopexHQ",
+                                                 "frequency": 5180, "signal": -52})
+        self.assertIsNone(parse_iw_link("Not connected.\n"))
+
+    def test_timeline(self):
+        link = {"bssid": "02:00:00:00:00:01", "ssid": "AlopexHQ", "signal": -50}
+        # Associated before the daemon started: picked up quietly
+        self.assertIsNone(self.timeline.sample("wlan0", link))
+        self.assertEqual(self.timeline.snapshot()["current"][0]["bssid"], "02:00:00:00:00:01")
+        self.timeline.sample("wlan0", dict(link, signal=-74))
+
+        self.timeline.stints["wlan0"]["since"] -= 90
+        roam = self.timeline.event({"interface": "wlan0", "kind": "roamed", "bssid": "02:00:00:00:00:02"})
+        self.assertEqual((roam["kind"], roam["previous_bssid"], roam["signal"], roam["ssid"]),
+                         ("roamed", "02:00:00:00:00:01", -74, "AlopexHQ"))
+        self.assertGreaterEqual(roam["duration"], 90)
+        # The next sample fills in the new access point's signal
+        self.timeline.sample("wlan0", dict(link, bssid="02:00:00:00:00:02", signal=-48))
+        self.assertEqual(roam["new_signal"], -48)
+
+        self.assertEqual(self.timeline.event({"interface": "wlan0", "kind": "connected",
+                                              "bssid": "02:00:00:00:00:02"})["kind"], "reassociated")
+        # A roam the events missed shows up in the sample
+        self.assertEqual(self.timeline.sample("wlan0", dict(link, bssid="02:00:00:00:00:03"))["kind"], "roamed")
+        left = self.timeline.event({"interface": "wlan0", "kind": "disconnected", "by_ap": True, "reason": 4})
+        self.assertEqual((left["kind"], left["bssid"], left["by_ap"]), ("disconnected", "02:00:00:00:00:03", True))
+        self.assertIsNone(self.timeline.event({"interface": "wlan0", "kind": "disconnected"}))
+        self.assertEqual(self.timeline.sample("wlan0", link)["kind"], "associated")
+
+        self.assertEqual([e["event"] for e in self.events.recent()], ["WifiAssociation"] * 5)
+        reloaded = RoamingTimeline(None, lambda: [], Path(self.dir.name))
+        self.assertEqual([e["kind"] for e in reloaded.snapshot()["entries"]],
+                         ["roamed", "reassociated", "roamed", "disconnected", "associated"])
+        self.assertEqual(reloaded.snapshot(limit=2)["entries"][0]["kind"], "disconnected")
+        self.assertEqual(reloaded.snapshot(interface="wlan1")["entries"], [])
+
+if __name__ == "__main__":
+    unittest.main()
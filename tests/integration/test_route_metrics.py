@@ -0,0 +1,47 @@
+"""
+Route metrics - default-route metrics from link type and profile priority
+"""
+
+import sys
+import unittest
+from pathlib import Path
+
+REPO_ROOT = Path(__file__).resolve().parents[2]
+sys.path.insert(0, str(REPO_ROOT / "src" / "alopex-qt"))
+
+from network.route_metrics import metric_changes, route_metric
+from network import reconcile
+from network.discovery import InterfaceType
+
+class RouteMetricsTest(unittest.TestCase):
+    def test_route_metric(self):
+        self.assertEqual(route_metric(InterfaceType.ETHERNET), 100)
+        self.assertEqual(route_metric(InterfaceType.WIFI, priority=10), 590)
+        # However much a profile is preferred, WiFi stays behind Ethernet and cellular behind WiFi
+        self.assertEqual(route_metric(InterfaceType.WIFI, priority=1000), 551)
+        self.assertEqual(route_metric(InterfaceType.ETHERNET, priority=-1000), 149)
+        self.assertEqual(route_metric(InterfaceType.CELLULAR, priority=-1000), 749)
+        self.assertIsNone(route_metric(InterfaceType.VPN))
+        self.assertEqual(route_metric(InterfaceType.VPN, override=5), 5)
+
+    def test_metric_changes(self):
+        routes = [
+            {"to": "default", "via": "192.0.2.1", "metric": 1002, "protocol": "dhcp"},
+            {"to": "default", "via": "192.0.2.1", "metric": 10, "protocol": "boot"},
+            {"to": "default", "via": "192.0.2.254", "metric": None, "protocol": "boot"},
+            {"to": "10.0.0.0/8", "via": "192.0.2.254", "metric": None, "protocol": "boot"}
+        ]
+        self.assertEqual([(reconcile.format_change(c), c["route"]["protocol"])
+                          for c in metric_changes("eth0", 100, routes)], [
+            ("+ eth0 route default via 192.0.2.1 metric 100", "dhcp"),
+            ("- eth0 route default via 192.0.2.1 metric 1002", "dhcp"),
+            ("+ eth0 route default via 192.0.2.254 metric 100", "boot"),
+            ("- eth0 route default via 192.0.2.254", "boot")
+        ])
+        self.assertEqual(reconcile.ip_arguments(metric_changes("eth0", 100, routes)[0]),
+                         ["route", "add", "default", "via", "192.0.2.1", "dev", "eth0", "metric", "100",
+                          "proto", "dhcp"])
+        self.assertEqual(metric_changes("eth0", 1002, routes[:2]), [])
+
+if __name__ == "__main__":
+    unittest.main()
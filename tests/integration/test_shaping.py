@@ -0,0 +1,52 @@
+"""
+Shaping - root qdisc and congestion control settings, and the tc and ip route arguments for them
+"""
+
+import sys
+import json
+import unittest
+from pathlib import Path
+
+REPO_ROOT = Path(__file__).resolve().parents[2]
+sys.path.insert(0, str(REPO_ROOT / "src" / "alopex-qt"))
+
+from network import shaping
+
+class ShapingTest(unittest.TestCase):
+    def test_check(self):
+        self.assertEqual(shaping.check_qdisc("cake", "95"), {"qdisc": "cake", "bandwidth": "95mbit"})
+        self.assertEqual(shaping.check_qdisc("cake", "1.5mbit"), {"qdisc": "cake", "bandwidth": "1500kbit"})
+        self.assertEqual(shaping.check_qdisc("none"), {"qdisc": None, "bandwidth": None})
+        for qdisc, bandwidth in (("pfifo", None), ("fq", "100mbit"), ("cake", "0"), ("cake", "fast"), (None, "10")):
+            with self.assertRaises(ValueError):
+                shaping.check_qdisc(qdisc, bandwidth)
+        self.assertEqual(shaping.check_congestion_control(" bbr "), "bbr")
+        self.assertIsNone(shaping.check_congestion_control("none"))
+        with self.assertRaises(ValueError):
+            shaping.check_congestion_control("bbr reno")
+
+    def test_commands(self):
+        self.assertEqual(shaping.qdisc_arguments("eth0", "cake", "95mbit"),
+                         ["qdisc", "replace", "dev", "eth0", "root", "cake", "bandwidth", "95mbit"])
+        self.assertEqual(shaping.qdisc_arguments("eth0", None), ["qdisc", "del", "dev", "eth0", "root"])
+        route = {"via": "192.0.2.1", "metric": 100, "protocol": "dhcp", "congctl": None}
+        self.assertEqual(shaping.route_arguments("eth0", route, "bbr", "-4"),
+                         ["-4", "route", "change", "default", "via", "192.0.2.1", "dev", "eth0", "metric", "100",
+                          "proto", "dhcp", "congctl", "bbr"])
+
+    def test_parse(self):
+        qdiscs = json.dumps([{"kind": "cake", "handle": "8001:", "root": True,
+                              "options": {"bandwidth": 11875000, "diffserv": "diffserv3"}},
+                             {"kind": "fq_codel", "handle": "0:", "parent": "8001:1"}])
+        self.assertEqual(shaping.parse_qdisc(qdiscs), {"kind": "cake", "bandwidth": "95mbit"})
+        self.assertEqual(shaping.parse_qdisc(json.dumps([{"kind": "mq", "root": True}])),
+                         {"kind": "mq", "bandwidth": None})
+        self.assertIsNone(shaping.parse_qdisc("[]"))
+        routes = json.dumps([{"dst": "default", "gateway": "192.0.2.1", "dev": "eth0", "protocol": "dhcp",
+                              "metric": 100, "metrics": [{"congctl": "bbr"}]},
+                             {"dst": "default", "gateway": "192.0.2.254", "dev": "eth0", "metric": 200}])
+        self.assertEqual([(r["via"], r["congctl"]) for r in shaping.parse_default_routes(routes)],
+                         [("192.0.2.1", "bbr"), ("192.0.2.254", None)])
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,71 @@
+"""
+SLA monitor - loss, latency and jitter per probe round and the thresholds they cross
+"""
+
+import sys
+import asyncio
+import unittest
+from pathlib import Path
+
+REPO_ROOT = Path(__file__).resolve().parents[2]
+sys.path.insert(0, str(REPO_ROOT / "src" / "alopex-qt"))
+
+from network.events import EventBus
+from network.sla import SlaMonitor, SlaProbe, round_stats, summarize
+
+class FakePingHost:
+    """Answers every burst with the next queued list of round trips"""
+
+    def __init__(self, bursts):
+        self.bursts = list(bursts)
+
+    async def ping(self, host, count, interval, timeout, interface=None):
+        return self.bursts.pop(0)
+
+class SlaMonitorTest(unittest.TestCase):
+    def test_round_stats(self):
+        stats = round_stats([10.0, None, 14.0, 12.0])
+        self.assertEqual((stats["sent"], stats["received"], stats["loss"]), (4, 3, 25.0))
+        self.assertEqual(stats["rtt"], 12.0)
+        self.assertEqual(stats["jitter"], 3.0)
+        lost = round_stats([None, None])
+        self.assertEqual((lost["loss"], lost["rtt"], lost["jitter"]), (100.0, None, None))
+
+    def test_thresholds(self):
+        probe = SlaProbe.from_config({"name": "wan", "target": "192.0.2.1",
+                                      "thresholds": {"loss": 10, "rtt": 50}})
+        self.assertEqual(probe.exceeded(round_stats([20.0, 30.0])), [])
+        self.assertEqual(probe.exceeded(round_stats([40.0, 90.0, None])), ["loss", "rtt"])
+        # Jitter is not checked, so a dead target only trips what is
+        self.assertEqual(probe.exceeded(round_stats([None])), ["loss", "rtt"])
+        for config in ({"name": "x", "target": "y", "method": "udp"}, {"name": "x", "target": "y", "method": "tcp"},
+                       {"name": "x", "target": "y", "thresholds": {"mos": 4}}, {"name": "x", "target": "y", "to": 1}):
+            with self.assertRaises(ValueError):
+                SlaProbe.from_config(config)
+
+    def test_hysteresis_events_and_history(self):
+        good, bad = [10.0, 11.0], [10.0, None]
+        events = EventBus()
+        subscription = events.subscribe()
+        monitor = SlaMonitor(FakePingHost([good, bad, good, bad, bad, good, good]), [
+            {"name": "wan", "target": "192.0.2.1", "thresholds": {"loss": 10}, "consecutive": 2}
+        ], events)
+
+        states = []
+        for _ in range(7):
+            asyncio.run(monitor.run_round("wan"))
+            states.append(monitor.list()[0]["state"])
+        self.assertEqual(states, ["ok", "ok", "ok", "ok", "breach", "breach", "ok"])
+        published = [subscription.queue.get_nowait() for _ in range(subscription.queue.qsize())]
+        self.assertEqual([e["event"] for e in published], ["SlaBreached", "SlaRecovered"])
+        self.assertEqual((published[0]["probe"], published[0]["exceeded"], published[0]["loss"]),
+                         ("wan", ["loss"], 50.0))
+
+        hour = monitor.list()[0]["hour"]
+        self.assertEqual((hour["rounds"], hour["loss"], hour["breaching_rounds"]), (7, 21.4, 3))
+        self.assertEqual(len(monitor.history("wan")), 7)
+        self.assertIsNone(monitor.history("lan"))
+        self.assertEqual(summarize([])["loss"], None)
+
+if __name__ == "__main__":
+    unittest.main()
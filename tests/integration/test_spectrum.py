@@ -0,0 +1,42 @@
+"""
+Spectrum - the channels iw reports for a phy and the per-band summary built from them
+"""
+
+import sys
+import unittest
+from pathlib import Path
+
+REPO_ROOT = Path(__file__).resolve().parents[2]
+sys.path.insert(0, str(REPO_ROOT / "src" / "alopex-qt"))
+
+from network.spectrum import regulatory_summary
+from network.wifi import parse_phy_channels
+
+IW_PHY_INFO = """Wiphy phy0
+	Band 1:
+		Frequencies:
+			* 2412.0 MHz [1] (20.0 dBm)
+			* 2467.0 MHz [12] (20.0 dBm) (no IR)
+			* 2484.0 MHz [14] (disabled)
+	Band 2:
+		Frequencies:
+			* 5180.0 MHz [36] (23.0 dBm)
+			* 5200.0 MHz [40] (23.0 dBm)
+			* 5260.0 MHz [52] (20.0 dBm) (no IR, radar detection)
+			* 5280.0 MHz [56] (20.0 dBm) (no IR, radar detection)
+"""
+
+class RegulatoryDomainTest(unittest.TestCase):
+    def test_phy_channels(self):
+        channels = {c["channel"]: c for c in parse_phy_channels(IW_PHY_INFO)}
+        self.assertEqual(sorted(channels), [1, 12, 14, 36, 40, 52, 56])
+        self.assertEqual(channels[36]["max_power_dbm"], 23.0)
+        self.assertTrue(channels[14]["disabled"])
+        self.assertTrue(channels[52]["radar"] and channels[52]["no_ir"])
+        self.assertEqual(regulatory_summary(list(channels.values())), [
+            {"band": "2.4 GHz", "usable": "1", "listen_only": "12", "radar": ""},
+            {"band": "5 GHz", "usable": "36-40", "listen_only": "52-56", "radar": "52-56"},
+        ])
+
+if __name__ == "__main__":
+    unittest.main()
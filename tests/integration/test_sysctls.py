@@ -0,0 +1,55 @@
+"""
+Sysctls - per-profile settings checked, written and reported against a fake /proc/sys
+"""
+
+import sys
+import tempfile
+import unittest
+from pathlib import Path
+
+REPO_ROOT = Path(__file__).resolve().parents[2]
+sys.path.insert(0, str(REPO_ROOT / "src" / "alopex-qt"))
+
+from network import sysctls
+
+class SysctlsTest(unittest.TestCase):
+    def test_check(self):
+        self.assertEqual(sysctls.check({"rp_filter": 2, "forwarding": True, "tcp_congestion_control": " bbr "}),
+                         {"rp_filter": "2", "forwarding": "1", "tcp_congestion_control": "bbr"})
+        self.assertEqual(sysctls.check(None), {})
+        for bad in ({"rp_filter": 3}, {"kernel.panic": 1}, {"tcp_congestion_control": "../../etc"},
+                    {"accept_ra": "yes"}):
+            with self.assertRaises(ValueError):
+                sysctls.check(bad)
+
+    def test_keys(self):
+        self.assertEqual(sysctls.sysctl_key("rp_filter", "eth0.100"), "net.ipv4.conf.eth0/100.rp_filter")
+        self.assertEqual(sysctls.key_path("net.ipv4.conf.eth0/100.rp_filter", Path("/proc/sys")),
+                         Path("/proc/sys/net/ipv4/conf/eth0.100/rp_filter"))
+        self.assertTrue(sysctls.is_global("tcp_congestion_control"))
+        self.assertFalse(sysctls.is_global("forwarding"))
+
+    def test_read_write_and_status(self):
+        with tempfile.TemporaryDirectory() as tmp:
+            proc = Path(tmp)
+            (proc / "net" / "ipv4" / "conf" / "eth0").mkdir(parents=True)
+            (proc / "net" / "ipv4" / "conf" / "eth0" / "rp_filter").write_text("2\n")
+            self.assertEqual(sysctls.read("net.ipv4.conf.eth0.rp_filter", proc), "2")
+            self.assertIsNone(sysctls.write("net.ipv4.conf.eth0.rp_filter", "1", proc))
+            self.assertIsNone(sysctls.read("net.ipv4.conf.eth9.rp_filter", proc))
+            self.assertIsNotNone(sysctls.write("net.ipv4.conf.eth9.rp_filter", "1", proc))
+
+            rows = sysctls.status({"rp_filter": "1", "accept_ra": "0"},
+                                  {"net.ipv4.conf.eth0.rp_filter": {"name": "rp_filter", "value": "1", "previous": "2"},
+                                   "net.ipv4.conf.eth0.forwarding": {"name": "forwarding", "value": "1",
+                                                                     "previous": "0"}},
+                                  "eth0", lambda key: sysctls.read(key, proc))
+        self.assertEqual([(r["name"], r["wanted"], r["effective"], r["previous"], r["in_effect"]) for r in rows], [
+            ("rp_filter", "1", "1", "2", True),
+            ("accept_ra", "0", None, None, False),
+            # Still held from before the profile dropped it
+            ("forwarding", None, None, "0", False)
+        ])
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,28 @@
+"""
+System integration - helpers around the system's own network tools
+Patterns are checked with Python's re, which reads them the way pgrep does
+"""
+
+import re
+import sys
+import unittest
+from pathlib import Path
+
+REPO_ROOT = Path(__file__).resolve().parents[2]
+sys.path.insert(0, str(REPO_ROOT / "src" / "alopex-qt"))
+
+from network.system_integration import dhcp_client_pattern
+
+class DhcpClientPatternTest(unittest.TestCase):
+    def test_exact_interface(self):
+        pattern = dhcp_client_pattern("eth0")
+        for command in ("dhcpcd eth0", "/sbin/dhclient -v eth0", "dhcpcd -b eth0 -4"):
+            self.assertTrue(re.search(pattern, command), command)
+        # A VLAN on it, a longer name, or another program mentioning it are someone else
+        for command in ("dhcpcd eth0.20", "dhcpcd -b eth01", "mydhcpcd eth0", "dhcpcd -b wlan0"):
+            self.assertFalse(re.search(pattern, command), command)
+        self.assertTrue(re.search(dhcp_client_pattern("eth0.20"), "dhcpcd eth0.20"))
+        self.assertFalse(re.search(dhcp_client_pattern("eth0.20"), "dhcpcd eth0x20"))
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,54 @@
+"""
+Transceiver - SFP and QSFP identity and DOM readings from ethtool -m output
+"""
+
+import sys
+import unittest
+from pathlib import Path
+
+REPO_ROOT = Path(__file__).resolve().parents[2]
+sys.path.insert(0, str(REPO_ROOT / "src" / "alopex-qt"))
+
+from network.transceiver import format_power, parse_module_eeprom
+
+class TransceiverTest(unittest.TestCase):
+    SFP = """	Identifier                                : 0x03 (SFP)
+	Connector                                 : 0x07 (LC)
+	Transceiver type                          : 10G Ethernet: 10G Base-SR
+	Laser wavelength                          : 850nm
+	Vendor name                               : FS
+	Vendor PN                                 : SFP-10GSR-85
+	Vendor SN                                 : G1234567
+	Laser bias current                        : 6.750 mA
+	Laser output power                        : 0.5678 mW / -2.46 dBm
+	Receiver signal average optical power     : 0.0000 mW / -inf dBm
+	Module temperature                        : 34.50 degrees C / 94.10 degrees F
+	Module voltage                            : 3.3120 V
+	Laser rx power low alarm                  : On
+	Laser rx power high alarm                 : Off
+	Laser rx power low alarm threshold        : 0.0100 mW / -20.00 dBm
+"""
+
+    def test_sfp(self):
+        module = parse_module_eeprom(self.SFP)
+        self.assertEqual((module["identifier"], module["connector"], module["vendor"], module["part_number"]),
+                         ("SFP", "LC", "FS", "SFP-10GSR-85"))
+        self.assertEqual((module["wavelength_nm"], module["temperature_c"], module["voltage_v"]), (850, 34.5, 3.312))
+        self.assertEqual((module["bias_ma"], module["tx_power_dbm"], module["rx_power_dbm"]), ([6.75], [-2.46], [None]))
+        self.assertEqual(module["alarms"], ["Laser rx power low alarm"])
+        self.assertEqual(format_power(module["rx_power_dbm"]), "-- dBm")
+        self.assertIsNone(parse_module_eeprom("Cannot get module EEPROM information: Invalid argument"))
+
+    def test_qsfp_channels(self):
+        module = parse_module_eeprom("""	Identifier                                : 0x11 (QSFP28)
+	Transmit avg optical power (Channel 2)    : 0.5000 mW / -3.01 dBm
+	Transmit avg optical power (Channel 1)    : 0.6000 mW / -2.22 dBm
+	Rcvr signal avg optical power(Channel 1)  : 0.4000 mW / -3.98 dBm
+""")
+        self.assertEqual(module["identifier"], "QSFP28")
+        self.assertEqual(format_power(module["tx_power_dbm"]), "-2.22 / -3.01 dBm")
+        self.assertTrue(module["diagnostics"])
+        self.assertIsNone(module["temperature_c"])
+
+if __name__ == "__main__":
+    unittest.main()
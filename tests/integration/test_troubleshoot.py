@@ -0,0 +1,73 @@
+"""
+Troubleshooter - layer-by-layer checks against a host whose uplink is down
+"""
+
+import sys
+import ipaddress
+import asyncio
+import unittest
+from pathlib import Path
+
+REPO_ROOT = Path(__file__).resolve().parents[2]
+sys.path.insert(0, str(REPO_ROOT / "src" / "alopex-qt"))
+
+from network.discovery import InterfaceType, NetworkInterface
+from network.cable_test import report
+from network import troubleshoot
+
+class TroubleshootTest(unittest.TestCase):
+    class Host:
+        """Answers the way a network with its uplink down would"""
+        address = ipaddress.ip_interface("192.168.1.50/24")
+
+        def interface_address(self, interface):
+            return self.address
+
+        async def ping(self, host, count, interval, timeout, interface=None):
+            return [1.5, None, 1.2]
+
+        def gateway_mac(self, gateway):
+            return "52:54:00:00:01:01"
+
+        async def query_dns(self, server, name, timeout):
+            raise OSError("timed out")
+
+        async def probe_port(self, host, port, source, timeout):
+            return {"state": "filtered", "address": host, "latency_ms": None, "error": "no answer"}
+
+    def run_checks(self, host, **fields):
+        interface = NetworkInterface(name="eth0", interface_type=InterfaceType.ETHERNET, status="Connected",
+                                     gateway="192.168.1.1", **fields)
+        progress = []
+        report = asyncio.run(troubleshoot.Troubleshooter(host, targets=["1.1.1.1:443"]).run(
+            interface, ["192.168.1.1"], lambda percent, message: progress.append(percent)))
+        return report, progress
+
+    def test_uplink_down(self):
+        report, progress = self.run_checks(self.Host())
+        self.assertEqual(progress, [0, 20, 40, 60, 80])
+        # DNS fails too, but only because nothing past the gateway answers
+        self.assertEqual(report["failed"], "internet")
+        steps = {step["step"]: step for step in report["steps"]}
+        self.assertEqual(steps["gateway"]["detail"], "192.168.1.1 at 52:54:00:00:01:01, 1.2 ms")
+        self.assertEqual((steps["dns"]["status"], steps["dns"]["fix"]), ("failed", None))
+        self.assertIn("restart the modem", steps["internet"]["fix"])
+        self.assertTrue(troubleshoot.summary(report).startswith("eth0: Internet is the problem"))
+
+    def test_stops_at_first_failure(self):
+        host = self.Host()
+        host.address = ipaddress.ip_interface("169.254.7.9/16")
+        report, progress = self.run_checks(host)
+        self.assertEqual(progress, [0, 20])
+        self.assertEqual(report["failed"], "address")
+        self.assertEqual([step["status"] for step in report["steps"]],
+                         ["ok", "failed", "skipped", "skipped", "skipped"])
+        lines = troubleshoot.format_steps(report)
+        self.assertTrue(lines[1].startswith("✗ IP address  only the link-local address 169.254.7.9"))
+        self.assertIn("dhcp renew eth0", lines[2])
+
+        report, _ = self.run_checks(self.Host(), rfkill="soft")
+        self.assertEqual(report["steps"][0]["fix"], "Turn airplane mode off (alopex-cli airplane off)")
+
+if __name__ == "__main__":
+    unittest.main()
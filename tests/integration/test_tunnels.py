@@ -0,0 +1,66 @@
+"""
+Tunnels - GRE, GRETAP and VXLAN settings and the RTM_NEWLINK messages for them
+"""
+
+import sys
+import ipaddress
+import struct
+import unittest
+from pathlib import Path
+
+REPO_ROOT = Path(__file__).resolve().parents[2]
+sys.path.insert(0, str(REPO_ROOT / "src" / "alopex-qt"))
+
+from network import netlink
+from network import tunnels
+
+class TunnelsTest(unittest.TestCase):
+    def test_check(self):
+        self.assertEqual(tunnels.check({"kind": "vxlan", "vni": 100, "remote": "192.168.1.20", "parent": "eth0"}),
+                         {"kind": "vxlan", "remote": "192.168.1.20", "vni": 100, "port": 4789, "parent": "eth0"})
+        self.assertEqual(tunnels.check({"kind": "gre", "remote": "2001:db8::7", "key": 0}),
+                         {"kind": "gre", "remote": "2001:db8::7", "key": 0})
+        for netdev in ({"kind": "gre"},
+                       {"kind": "gre", "remote": "lab.example"},
+                       {"kind": "gre", "remote": "10.0.0.2", "local": "2001:db8::1"},
+                       {"kind": "gre", "remote": "10.0.0.2", "key": 1 << 32},
+                       {"kind": "gretap", "remote": "10.0.0.2", "vni": 5},
+                       {"kind": "vxlan", "remote": "10.0.0.2"},
+                       {"kind": "vxlan", "remote": "10.0.0.2", "vni": 1 << 24},
+                       {"kind": "vxlan", "remote": "10.0.0.2", "vni": "100"},
+                       {"kind": "vxlan", "remote": "10.0.0.2", "vni": 1, "key": 1},
+                       {"kind": "vxlan", "remote": "10.0.0.2", "vni": 1, "group": "239.1.1.1"}):
+            with self.assertRaises(tunnels.TunnelError, msg=netdev):
+                tunnels.check(netdev)
+
+    def test_newlink_message(self):
+        def linkinfo(message):
+            return netlink.attributes(netlink.attributes(message[32:])[tunnels.IFLA_LINKINFO])
+
+        vxlan = tunnels.check({"kind": "vxlan", "vni": 100, "remote": "192.168.1.20", "parent": "eth0", "ttl": 8})
+        message = tunnels.build_newlink("vx0", vxlan, parent_index=2, seq=4)
+        length, kind, flags, seq, _ = struct.unpack_from("=IHHII", message)
+        self.assertEqual((length, kind, flags, seq), (len(message), tunnels.RTM_NEWLINK, 0x605, 4))
+        attrs = netlink.attributes(message[32:])
+        self.assertEqual(attrs[tunnels.IFLA_IFNAME], b"vx0\0")
+        info = linkinfo(message)
+        self.assertEqual(info[tunnels.IFLA_INFO_KIND], b"vxlan")
+        self.assertEqual(netlink.attributes(info[tunnels.IFLA_INFO_DATA]), {
+            tunnels.IFLA_VXLAN_ID: struct.pack("=I", 100), tunnels.IFLA_VXLAN_PORT: b"\x12\xb5",
+            tunnels.IFLA_VXLAN_GROUP: bytes([192, 168, 1, 20]), tunnels.IFLA_VXLAN_LINK: struct.pack("=I", 2),
+            tunnels.IFLA_VXLAN_TTL: b"\x08"})
+        gre = tunnels.check({"kind": "gretap", "remote": "2001:db8::7", "local": "2001:db8::1", "key": 42})
+        info = linkinfo(tunnels.build_newlink("gt0", gre))
+        self.assertEqual(info[tunnels.IFLA_INFO_KIND], b"ip6gretap")
+        data = netlink.attributes(info[tunnels.IFLA_INFO_DATA])
+        self.assertEqual(data[tunnels.IFLA_GRE_REMOTE], ipaddress.ip_address("2001:db8::7").packed)
+        self.assertEqual(data[tunnels.IFLA_GRE_LOCAL], ipaddress.ip_address("2001:db8::1").packed)
+        self.assertEqual((data[tunnels.IFLA_GRE_IKEY], data[tunnels.IFLA_GRE_OKEY]), (b"\0\0\0\x2a",) * 2)
+        self.assertEqual(data[tunnels.IFLA_GRE_IFLAGS], b"\x20\x00")
+        # Without a key the flags stay off
+        plain = tunnels.check({"kind": "gre", "remote": "10.0.0.2"})
+        data = netlink.attributes(linkinfo(tunnels.build_newlink("gre1", plain))[tunnels.IFLA_INFO_DATA])
+        self.assertEqual(data, {tunnels.IFLA_GRE_REMOTE: b"\n\0\0\x02"})
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,99 @@
+"""
+Unit traffic - per-unit byte counts from the cgroup tree, the nft ruleset and reloads
+"""
+
+import sys
+import asyncio
+import tempfile
+import unittest
+from pathlib import Path
+
+REPO_ROOT = Path(__file__).resolve().parents[2]
+sys.path.insert(0, str(REPO_ROOT / "src" / "alopex-qt"))
+
+from network import unit_traffic
+
+class FakeAccounting:
+    """Counters the test sets directly; each sync starts them over"""
+    backend_name = "fake"
+
+    def __init__(self):
+        self.groups = {}
+        self.values = {}
+        self.synced = []
+
+    def cgroups(self):
+        return dict(self.groups)
+
+    async def sync(self, cgroups):
+        self.synced.append(cgroups)
+        self.values = {cgroup: {"rx": 0, "tx": 0} for cgroup in cgroups}
+        return True, "ok"
+
+    async def counters(self):
+        return {cgroup: dict(values) for cgroup, values in self.values.items()}
+
+    async def clear(self):
+        return True
+
+class UnitTrafficTest(unittest.TestCase):
+    def test_cgroup_tree(self):
+        with tempfile.TemporaryDirectory() as tmp:
+            root = Path(tmp)
+            mounts = root / "mounts"
+            mounts.write_text("sysfs /sys sysfs rw 0 0\ncgroup2 /sys/fs/cgroup/unified cgroup2 rw 0 0\n")
+            self.assertEqual(unit_traffic.cgroup2_root(mounts), Path("/sys/fs/cgroup/unified"))
+            for path in ("system.slice/nginx.service", "system.slice/var-lib.mount", "user.slice/user-1000.slice",
+                         "system.slice/odd\\x2dname.service", "init.scope"):
+                (root / path).mkdir(parents=True)
+            self.assertEqual(sorted(unit_traffic.list_cgroups(root)),
+                             ["system.slice/nginx.service", "user.slice/user-1000.slice"])
+        self.assertEqual(unit_traffic.unit_name("system.slice/nginx.service"), "nginx.service")
+
+    def test_ruleset_and_counters(self):
+        ruleset = unit_traffic.nft_ruleset(["system.slice/nginx.service"])
+        self.assertTrue(ruleset.startswith("table inet alopex_acct\ndelete table inet alopex_acct\n"))
+        self.assertIn('socket cgroupv2 level 2 "system.slice/nginx.service" counter '
+                      'comment "tx system.slice/nginx.service"', ruleset)
+        listing = {"nftables": [
+            {"metainfo": {"json_schema_version": 1}},
+            {"table": {"family": "inet", "name": "alopex_acct"}},
+            {"rule": {"chain": "input", "comment": "rx system.slice/nginx.service", "expr": [
+                {"match": {"op": "==", "left": {"socket": {"key": "cgroupv2", "level": 2}},
+                           "right": "system.slice/nginx.service"}},
+                {"counter": {"packets": 10, "bytes": 4000}}]}},
+            {"rule": {"chain": "output", "comment": "tx system.slice/nginx.service", "expr": [
+                {"counter": {"packets": 3, "bytes": 900}}]}},
+            {"rule": {"chain": "output", "expr": [{"counter": {"packets": 1, "bytes": 1}}]}}
+        ]}
+        self.assertEqual(unit_traffic.parse_counters(listing), {"system.slice/nginx.service": {"rx": 4000, "tx": 900}})
+
+    def test_totals_survive_reloads(self):
+        backend = FakeAccounting()
+        backend.groups = {"system.slice/a.service": 1}
+        monitor = unit_traffic.UnitTraffic(backend)
+        asyncio.run(monitor.poll())
+        backend.values["system.slice/a.service"] = {"rx": 500, "tx": 100}
+        monitor.sampled_at -= 5
+        asyncio.run(monitor.poll())
+        unit, = monitor.top()["units"]
+        self.assertEqual((unit["rx_bytes"], unit["tx_bytes"]), (500, 100))
+        self.assertAlmostEqual(unit["rx_rate"], 100, delta=1)
+
+        # b starts, so the table is rebuilt from zero; a's total carries on
+        backend.groups["system.slice/b.service"] = 2
+        asyncio.run(monitor.poll())
+        backend.values["system.slice/a.service"] = {"rx": 50, "tx": 0}
+        backend.values["system.slice/b.service"] = {"rx": 9000, "tx": 9000}
+        asyncio.run(monitor.poll())
+        self.assertEqual(len(backend.synced), 2)
+        top = monitor.top(limit=1)
+        self.assertEqual((top["tracked"], top["units"][0]["unit"]), (2, "b.service"))
+        self.assertEqual(monitor.units["system.slice/a.service"]["rx_bytes"], 550)
+
+        del backend.groups["system.slice/a.service"]
+        asyncio.run(monitor.poll())
+        self.assertEqual(list(monitor.units), ["system.slice/b.service"])
+
+if __name__ == "__main__":
+    unittest.main()
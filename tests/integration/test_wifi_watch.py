@@ -0,0 +1,90 @@
+"""
+WiFi watch - rogue access points, deauthentication bursts and iw events
+"""
+
+import sys
+import tempfile
+import unittest
+from pathlib import Path
+
+REPO_ROOT = Path(__file__).resolve().parents[2]
+sys.path.insert(0, str(REPO_ROOT / "src" / "alopex-qt"))
+
+from network.events import EventBus
+from network.wifi_watch import WifiWatch
+from network.wifi import WiFiNetwork, WifiSecurity, parse_iw_event
+
+class WifiWatchTest(unittest.TestCase):
+    def setUp(self):
+        self.dir = tempfile.TemporaryDirectory()
+        self.events = EventBus()
+        self.watch = WifiWatch(None, lambda: ["wlan0"], Path(self.dir.name), self.events, burst=3, window=10)
+
+    def tearDown(self):
+        self.dir.cleanup()
+
+    def ap(self, ssid, bssid, security):
+        return WiFiNetwork(ssid=ssid, signal_strength=-50, security=security, bssid=bssid)
+
+    def test_rogue_access_point(self):
+        self.assertEqual(self.watch.observe("wlan0", [
+            self.ap("eduroam", "02:00:00:00:00:01", WifiSecurity.ENTERPRISE),
+            self.ap("Cafe", "02:00:00:00:00:02", WifiSecurity.OPEN)
+        ]), [])
+        # Another AP with the same security is roaming, not an attack
+        self.assertEqual(self.watch.observe("wlan0", [self.ap("eduroam", "02:00:00:00:00:03",
+                                                              WifiSecurity.ENTERPRISE)]), [])
+        alerts = self.watch.observe("wlan0", [self.ap("eduroam", "0A:00:00:00:00:09", WifiSecurity.OPEN)])
+        self.assertEqual([(a["alert"], a["bssid"], a["weaker"]) for a in alerts],
+                         [("rogue_ap", "0a:00:00:00:00:09", True)])
+        # Reported once; the access point is remembered
+        self.assertEqual(self.watch.observe("wlan0", [self.ap("eduroam", "0a:00:00:00:00:09", WifiSecurity.OPEN)]), [])
+
+        alerts = self.watch.observe("wlan0", [self.ap("Cafe", "02:00:00:00:00:02", WifiSecurity.WPA2)])
+        self.assertEqual([(a["alert"], a["expected"]) for a in alerts], [("security_changed", ["Open"])])
+
+        reloaded = WifiWatch(None, lambda: [], Path(self.dir.name))
+        self.assertEqual(len(reloaded.baseline["eduroam"]), 3)
+        self.assertEqual(reloaded.forget("eduroam"), 1)
+        self.assertEqual(WifiWatch(None, lambda: [], Path(self.dir.name)).baseline,
+                         {"Cafe": {"02:00:00:00:00:02": "WPA2"}})
+
+    def test_deauth_burst(self):
+        frame = {"interface": "wlan0", "kind": "deauth", "bssid": "02:00:00:00:00:01", "reason": 7}
+        self.assertIsNone(self.watch.frame(dict(frame, time=100.0)))
+        self.assertIsNone(self.watch.frame(dict(frame, time=104.0)))
+        # Too spread out to be a burst
+        self.assertIsNone(self.watch.frame(dict(frame, time=115.0)))
+        self.assertIsNone(self.watch.frame(dict(frame, time=116.0)))
+        alert = self.watch.frame(dict(frame, time=117.0))
+        self.assertEqual((alert["alert"], alert["count"]), ("deauth_burst", 3))
+        # One alert per window while the burst goes on
+        self.assertIsNone(self.watch.frame(dict(frame, time=118.0)))
+        self.assertEqual([a["alert"] for a in self.watch.snapshot()["alerts"]], ["deauth_burst"])
+
+    def test_iw_event(self):
+        line = ("1697040000.123456: wlan0 (phy #0): deauth: 02:00:00:00:00:01 -> 3C:22:FB:10:20:30 "
+                "reason 7: Class 3 frame received from nonassociated STA")
+        self.assertEqual(parse_iw_event(line), {"interface": "wlan0", "kind": "deauth",
+                                                "bssid": "02:00:00:00:00:01", "reason": 7})
+        self.assertEqual(parse_iw_event("wlan0 (phy #0): disassoc: 02:00:00:00:00:01 -> 3c:22:fb:10:20:30")["kind"],
+                         "disassoc")
+        self.assertIsNone(parse_iw_event("wlan0 (phy #0): scan started"))
+        self.assertEqual(parse_iw_event("1697040001.5: wlan0 (phy #0): connected to 3C:22:FB:10:20:30"),
+                         {"interface": "wlan0", "kind": "connected", "bssid": "3c:22:fb:10:20:30"})
+        self.assertEqual(parse_iw_event("wlan0 (phy #0): roamed to 3c:22:fb:10:20:31")["kind"], "roamed")
+        self.assertEqual(parse_iw_event("wlan0 (phy #0): disconnected (by AP) reason: 4: Disassociated due to "
+                                        "inactivity"),
+                         {"interface": "wlan0", "kind": "disconnected", "by_ap": True, "reason": 4})
+        self.assertFalse(parse_iw_event("wlan0 (phy #0): disconnected (local request)")["by_ap"])
+
+    def test_events_reach_listeners(self):
+        seen = []
+        self.watch.listeners.append(seen.append)
+        self.watch.event({"interface": "wlan0", "kind": "roamed", "bssid": "02:00:00:00:00:02"})
+        self.watch.event({"interface": "wlan0", "kind": "deauth", "bssid": "02:00:00:00:00:01", "reason": 7})
+        self.assertEqual([e["kind"] for e in seen], ["roamed"])
+        self.assertEqual(len(self.watch.frames["wlan0"]), 1)
+
+if __name__ == "__main__":
+    unittest.main()